@@ -0,0 +1,906 @@
+//! The agenda: scheduled items and deadlines of documents in a date range.
+//!
+//! [`agenda`] collects the headlines that are scheduled or have a deadline in a [`DateRange`].
+//! Timestamps with a repeater (e.g. `<2018-10-08 Mon +1w>`) appear on every date they repeat on.
+//! Diary sexps (in timestamps and in the section of a headline) appear on every date they match
+//! (see [`DiaryExpr`]).
+//! Commented and archived subtrees are skipped unless a [`SubtreeFilter`] includes them.
+//!
+//! [`agenda_log`] collects what was done in a [`DateRange`] instead (like the log mode of the org
+//! mode agenda): closed headlines, clocks and notes about state changes.
+//!
+//! [`due_this_week`], [`scheduled_in`] and [`overdue`] return the headlines of common queries
+//! grouped by day (e.g. for dashboards).
+//!
+//! Org mode sorts the entries of the agenda with `org-agenda-sorting-strategy`: a list of
+//! strategies that are tried in order until one of them distinguishes two entries. [`Sorting`]
+//! implements the same comparator chain.
+//!
+//! [`DiaryExpr`]: `crate::diary::DiaryExpr`
+
+use crate::clocking;
+use crate::diary::DiaryExpr;
+pub use crate::journal::DateRange;
+use crate::logbook;
+use crate::parsing::{ParseConfig, TodoSequence};
+use crate::types::document::Document;
+use crate::types::elements::OrgDuration;
+use crate::types::greater_elements::{Headline, HeadlineContentSet, SubtreeFilter, TodoKeyword};
+use crate::types::objects::Timestamp;
+use crate::types::{ElementSet, Parent};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ptr;
+use std::str::FromStr;
+
+/// The priority of headlines without a priority cookie (`org-default-priority`).
+pub const DEFAULT_PRIORITY: char = 'B';
+
+/// An entry of the agenda.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgendaItem<'a> {
+    pub headline: &'a Headline,
+    /// The category of the entry (usually the `CATEGORY` property or the file name).
+    pub category: String,
+    /// The time of day of the entry (e.g. from a scheduled timestamp).
+    pub time: Option<NaiveTime>,
+}
+
+impl<'a> AgendaItem<'a> {
+    pub fn new(headline: &'a Headline, category: impl Into<String>) -> Self {
+        AgendaItem {
+            headline,
+            category: category.into(),
+            time: None,
+        }
+    }
+
+    pub fn with_time(self, time: NaiveTime) -> Self {
+        AgendaItem {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    /// The priority of the headline. `'A'` is the highest priority.
+    pub fn priority(&self) -> char {
+        self.headline.priority.unwrap_or(DEFAULT_PRIORITY)
+    }
+
+    /// The `EFFORT` property of the headline (e.g. `1:30` or `2h`, see [`OrgDuration`]).
+    pub fn effort(&self) -> Option<OrgDuration> {
+        self.headline.property("EFFORT")?.parse().ok()
+    }
+}
+
+/// Why an entry is in the agenda.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AgendaEntryKind {
+    /// The headline has a deadline on the date.
+    Deadline,
+    /// The headline is scheduled on the date.
+    Scheduled,
+    /// A diary sexp in the section of the headline matches the date.
+    Diary,
+    /// Something was done on the headline on the date (see [`agenda_log`]).
+    Logged(LoggedKind),
+}
+
+/// What was done in an [`AgendaEntryKind::Logged`] entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LoggedKind {
+    /// The headline was closed (its `CLOSED` timestamp).
+    Closed,
+    /// A clock was started. The minutes are the length of the whole clock.
+    Clocked { minutes: i64 },
+    /// The todo state changed (a note in the logbook).
+    StateChange,
+}
+
+/// An entry of the agenda of a [`DateRange`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgendaEntry<'a> {
+    /// The date the entry is shown on. For repeated timestamps this is the date of the
+    /// repetition.
+    pub date: NaiveDate,
+    pub kind: AgendaEntryKind,
+    pub item: AgendaItem<'a>,
+}
+
+/// Returns the scheduled items and deadlines in the range sorted by date and
+/// [`Sorting::default`].
+///
+/// Unlike the org mode agenda, overdue items and upcoming deadlines are only shown on their
+/// date (or the dates they repeat on).
+pub fn agenda(documents: &[Document], range: DateRange) -> Vec<AgendaEntry<'_>> {
+    agenda_with_sorting(documents, range, &Sorting::default())
+}
+
+/// Returns the scheduled items and deadlines in the range sorted by date and the given sorting.
+///
+/// The category of an entry is the `CATEGORY` property of the headline or its nearest ancestor
+/// with one, otherwise the `#+CATEGORY:` keyword of the document.
+pub fn agenda_with_sorting<'a>(
+    documents: &'a [Document],
+    range: DateRange,
+    sorting: &Sorting,
+) -> Vec<AgendaEntry<'a>> {
+    agenda_with_filter(documents, range, sorting, SubtreeFilter::default())
+}
+
+/// Returns the scheduled items and deadlines in the range sorted by the given sorting.
+///
+/// Only the subtrees selected by `filter` are searched.
+pub fn agenda_with_filter<'a>(
+    documents: &'a [Document],
+    range: DateRange,
+    sorting: &Sorting,
+    filter: SubtreeFilter,
+) -> Vec<AgendaEntry<'a>> {
+    let dated = |headline: &Headline| planned(headline, range);
+    sorted_entries(documents, sorting, filter, &dated)
+}
+
+/// Returns the open headlines with a deadline in the week (Monday to Sunday) of `now` grouped
+/// by day.
+pub fn due_this_week(documents: &[Document], now: NaiveDateTime) -> HeadlinesByDay<'_> {
+    let entries = agenda(documents, DateRange::week_of(now.date()));
+    by_day(
+        entries.into_iter().filter(|entry| {
+            entry.kind == AgendaEntryKind::Deadline && !is_done(entry.item.headline)
+        }),
+    )
+}
+
+/// Returns the headlines scheduled in the range (e.g. [`DateRange::month_of`]) grouped by day.
+///
+/// Like in the [`agenda`] a headline with a repeater appears on every date it repeats on.
+pub fn scheduled_in(documents: &[Document], range: DateRange) -> HeadlinesByDay<'_> {
+    let entries = agenda(documents, range);
+    by_day(
+        entries
+            .into_iter()
+            .filter(|entry| entry.kind == AgendaEntryKind::Scheduled),
+    )
+}
+
+/// Returns the open headlines with a deadline or scheduled date before `now` grouped by the day
+/// they were due.
+///
+/// A timestamp with a time is overdue after the time, one without a time from the next day on.
+/// Only the date of a repeated timestamp counts because org mode moves it to the next
+/// repetition when the headline is done.
+pub fn overdue(documents: &[Document], now: NaiveDateTime) -> HeadlinesByDay<'_> {
+    let dated = |headline: &Headline| {
+        let planning = match &headline.planning {
+            Some(planning) if !is_done(headline) => planning,
+            _ => return Vec::new(),
+        };
+        let timestamps = [
+            (AgendaEntryKind::Deadline, &planning.deadline),
+            (AgendaEntryKind::Scheduled, &planning.scheduled),
+        ];
+        timestamps
+            .iter()
+            .filter_map(|(kind, timestamp)| {
+                let timestamp = timestamp.as_ref()?;
+                let date_time = timestamp.start_date_time()?;
+                let has_time = has_time(timestamp);
+                let is_overdue = if has_time {
+                    date_time < now
+                } else {
+                    date_time.date() < now.date()
+                };
+                if is_overdue {
+                    Some((*kind, date_time, has_time))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    };
+    let entries = sorted_entries(
+        documents,
+        &Sorting::default(),
+        SubtreeFilter::default(),
+        &dated,
+    );
+    by_day(entries)
+}
+
+/// Headlines grouped by day. The headlines of a day are sorted like the [`agenda`].
+pub type HeadlinesByDay<'a> = BTreeMap<NaiveDate, Vec<&'a Headline>>;
+
+/// Groups the headlines of the entries by date. A headline is only added once per day.
+fn by_day<'a>(entries: impl IntoIterator<Item = AgendaEntry<'a>>) -> HeadlinesByDay<'a> {
+    let mut days = HeadlinesByDay::new();
+    for entry in entries {
+        let headlines = days.entry(entry.date).or_default();
+        if !headlines
+            .iter()
+            .any(|headline| ptr::eq(*headline, entry.item.headline))
+        {
+            headlines.push(entry.item.headline);
+        }
+    }
+    days
+}
+
+fn is_done(headline: &Headline) -> bool {
+    matches!(headline.todo_keyword, Some(TodoKeyword::Done(_)))
+}
+
+/// Returns the entries returned by `dated` for the headlines selected by `filter` sorted by date
+/// and the given sorting.
+fn sorted_entries<'a>(
+    documents: &'a [Document],
+    sorting: &Sorting,
+    filter: SubtreeFilter,
+    dated: &impl Fn(&Headline) -> DatedEntries,
+) -> Vec<AgendaEntry<'a>> {
+    let mut entries = Vec::new();
+    for document in documents {
+        let category = document_category(document);
+        let entries = &mut entries;
+        collect_entries(&document.headlines, &category, filter, dated, entries);
+    }
+    let sorting = sorting.or_todo_keywords(|| {
+        let mut sequences: Vec<TodoSequence> = Vec::new();
+        for sequence in documents.iter().flat_map(Document::todo_sequences) {
+            if !sequences.contains(&sequence) {
+                sequences.push(sequence);
+            }
+        }
+        sequences
+    });
+    let categories = category_order(entries.iter().map(|entry| &entry.item));
+    entries.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then_with(|| sorting.compare_with(&a.item, &b.item, &categories))
+    });
+    entries
+}
+
+/// Returns what was done in the range sorted by date and time (entries without a time first).
+///
+/// The entries are the `CLOSED` timestamps, the starts of closed clocks and the notes about
+/// state changes (see [`logbook::state_changes`]). Commented and archived subtrees are skipped.
+pub fn agenda_log(documents: &[Document], range: DateRange) -> Vec<AgendaEntry<'_>> {
+    let mut entries = Vec::new();
+    for document in documents {
+        let category = document_category(document);
+        let filter = SubtreeFilter::default();
+        let entries = &mut entries;
+        let dated = |headline: &Headline| logged(headline, range);
+        collect_entries(&document.headlines, &category, filter, &dated, entries);
+    }
+    entries.sort_by_key(|entry| (entry.date, entry.item.time));
+    entries
+}
+
+/// The entries of a headline: the kind, the date and time and whether the time is known.
+type DatedEntries = Vec<(AgendaEntryKind, NaiveDateTime, bool)>;
+
+/// Returns the deadline, scheduled and diary entries of the headline in the range.
+fn planned(headline: &Headline, range: DateRange) -> DatedEntries {
+    let mut entries = Vec::new();
+    if let Some(planning) = &headline.planning {
+        let timestamps = [
+            (AgendaEntryKind::Deadline, &planning.deadline),
+            (AgendaEntryKind::Scheduled, &planning.scheduled),
+        ];
+        for (kind, timestamp) in timestamps.iter() {
+            if let Some(timestamp) = timestamp {
+                let has_time = has_time(timestamp);
+                entries.extend(
+                    timestamp
+                        .occurrences_between(range.start..=range.end)
+                        .into_iter()
+                        .map(|date_time| (*kind, date_time, has_time)),
+                );
+            }
+        }
+    }
+    let section = match headline
+        .content()
+        .and_then(|content| content.value().first())
+    {
+        Some(HeadlineContentSet::Section(section)) => section.content.value().as_slice(),
+        _ => &[],
+    };
+    for element in section {
+        if let ElementSet::DiarySexp(sexp) = element {
+            if let Ok(expr) = sexp.sexp().parse::<DiaryExpr>() {
+                entries.extend(
+                    expr.occurrences_between(range.start..=range.end)
+                        .into_iter()
+                        .map(|date| (AgendaEntryKind::Diary, date.and_hms(0, 0, 0), false)),
+                );
+            }
+        }
+    }
+    entries
+}
+
+/// Returns the closed timestamp, clocks and state changes of the headline in the range.
+fn logged(headline: &Headline, range: DateRange) -> DatedEntries {
+    let mut entries = Vec::new();
+    let closed = headline
+        .planning
+        .as_ref()
+        .and_then(|planning| planning.closed.as_ref());
+    if let Some(closed) = closed {
+        if let Some(date_time) = closed.start_date_time() {
+            entries.push((LoggedKind::Closed, date_time, has_time(closed)));
+        }
+    }
+    for clock in clocking::clocks(headline) {
+        let timestamp = clock.timestamp.as_ref();
+        if let Some(date_time) = timestamp.and_then(Timestamp::start_date_time) {
+            let minutes = clocking::clock_minutes(clock, None);
+            entries.push((LoggedKind::Clocked { minutes }, date_time, true));
+        }
+    }
+    for change in logbook::state_changes(headline) {
+        entries.push((LoggedKind::StateChange, change.time, true));
+    }
+    entries
+        .into_iter()
+        .filter(|(_, date_time, _)| range.contains(date_time.date()))
+        .map(|(kind, date_time, has_time)| (AgendaEntryKind::Logged(kind), date_time, has_time))
+        .collect()
+}
+
+fn has_time(timestamp: &Timestamp) -> bool {
+    matches!(timestamp.timestamp_start(), Some((_, Some(_))))
+}
+
+/// Returns the `#+CATEGORY:` of the document (or an empty string).
+fn document_category(document: &Document) -> String {
+    document.keywords().category().unwrap_or_default()
+}
+
+/// Collects the entries returned by `dated` for the headlines and their children.
+fn collect_entries<'a>(
+    headlines: impl IntoIterator<Item = &'a Headline>,
+    category: &str,
+    filter: SubtreeFilter,
+    dated: &impl Fn(&Headline) -> DatedEntries,
+    entries: &mut Vec<AgendaEntry<'a>>,
+) {
+    for headline in headlines {
+        if filter.skips(headline) {
+            continue;
+        }
+        let category = headline
+            .property("CATEGORY")
+            .map(|category| category.trim().to_string())
+            .unwrap_or_else(|| category.to_string());
+        for (kind, date_time, has_time) in dated(headline) {
+            let item = AgendaItem::new(headline, category.clone());
+            let item = if has_time {
+                item.with_time(date_time.time())
+            } else {
+                item
+            };
+            entries.push(AgendaEntry {
+                date: date_time.date(),
+                kind,
+                item,
+            });
+        }
+        collect_entries(headline.children(), &category, filter, dated, entries);
+    }
+}
+
+/// A single sorting strategy of `org-agenda-sorting-strategy`.
+///
+/// The strategies are parsed from and printed as their org names (e.g. `time-up`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SortingStrategy {
+    /// Entries with a time first, ordered by time.
+    TimeUp,
+    /// Entries with a time first, latest time first.
+    TimeDown,
+    /// Lowest priority first.
+    PriorityUp,
+    /// Highest priority first.
+    PriorityDown,
+    /// Ordered by the position of the todo keyword in [`Sorting::todo_keywords`] (the todo
+    /// keywords of a sequence before its done keywords). Entries without (or with an unknown)
+    /// todo keyword are last.
+    TodoStateUp,
+    /// The reverse of [`SortingStrategy::TodoStateUp`].
+    TodoStateDown,
+    /// Entries of the same category stay together. Categories are kept in the order of their
+    /// first entry.
+    CategoryKeep,
+    /// Alphabetically by category.
+    CategoryUp,
+    /// Reverse alphabetically by category.
+    CategoryDown,
+    /// Smallest effort first. Entries without effort are last.
+    EffortUp,
+    /// Largest effort first. Entries without effort are first.
+    EffortDown,
+}
+
+impl SortingStrategy {
+    const NAMES: [(SortingStrategy, &'static str); 11] = [
+        (SortingStrategy::TimeUp, "time-up"),
+        (SortingStrategy::TimeDown, "time-down"),
+        (SortingStrategy::PriorityUp, "priority-up"),
+        (SortingStrategy::PriorityDown, "priority-down"),
+        (SortingStrategy::TodoStateUp, "todo-state-up"),
+        (SortingStrategy::TodoStateDown, "todo-state-down"),
+        (SortingStrategy::CategoryKeep, "category-keep"),
+        (SortingStrategy::CategoryUp, "category-up"),
+        (SortingStrategy::CategoryDown, "category-down"),
+        (SortingStrategy::EffortUp, "effort-up"),
+        (SortingStrategy::EffortDown, "effort-down"),
+    ];
+
+    fn name(self) -> &'static str {
+        SortingStrategy::NAMES
+            .iter()
+            .find(|(strategy, _)| *strategy == self)
+            .map(|(_, name)| *name)
+            .expect("every strategy has a name")
+    }
+}
+
+impl FromStr for SortingStrategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SortingStrategy::NAMES
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(strategy, _)| *strategy)
+            .ok_or(())
+    }
+}
+
+impl fmt::Display for SortingStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A chain of [`SortingStrategy`]s.
+///
+/// Entries are compared with the first strategy. If they are equal the next strategy is used
+/// and so on. Entries that are equal according to all strategies keep their order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sorting {
+    pub strategies: Vec<SortingStrategy>,
+    /// The todo keyword sequences in the order used by [`SortingStrategy::TodoStateUp`].
+    ///
+    /// If this is empty (the default) the agenda uses the sequences of its documents (see
+    /// [`Document::todo_sequences`]) and [`Sorting::sort`] uses the default todo keywords of the
+    /// parser.
+    pub todo_keywords: Vec<TodoSequence>,
+}
+
+impl Sorting {
+    pub fn new(strategies: Vec<SortingStrategy>) -> Self {
+        Sorting {
+            strategies,
+            ..Sorting::default()
+        }
+    }
+
+    /// Sorts the items in place.
+    pub fn sort(&self, items: &mut [AgendaItem<'_>]) {
+        let sorting = self.or_todo_keywords(|| ParseConfig::default().todo_keywords);
+        let categories = category_order(items.iter());
+        items.sort_by(|a, b| sorting.compare_with(a, b, &categories));
+    }
+
+    /// Compares two items.
+    ///
+    /// [`SortingStrategy::CategoryKeep`] can't be decided for two items alone. Here categories
+    /// are only kept together (ordered alphabetically). Use [`Sorting::sort`] to keep the order
+    /// of the categories.
+    pub fn compare(&self, a: &AgendaItem<'_>, b: &AgendaItem<'_>) -> Ordering {
+        self.or_todo_keywords(|| ParseConfig::default().todo_keywords)
+            .compare_with(a, b, &HashMap::new())
+    }
+
+    /// Returns this sorting with the todo keyword sequences unless it has its own.
+    fn or_todo_keywords(&self, sequences: impl FnOnce() -> Vec<TodoSequence>) -> Cow<'_, Sorting> {
+        if self.todo_keywords.is_empty() {
+            Cow::Owned(Sorting {
+                strategies: self.strategies.clone(),
+                todo_keywords: sequences(),
+            })
+        } else {
+            Cow::Borrowed(self)
+        }
+    }
+
+    fn compare_with(
+        &self,
+        a: &AgendaItem<'_>,
+        b: &AgendaItem<'_>,
+        categories: &HashMap<String, usize>,
+    ) -> Ordering {
+        self.strategies
+            .iter()
+            .map(|strategy| match strategy {
+                SortingStrategy::TimeUp => compare_missing_last(a.time, b.time),
+                SortingStrategy::TimeDown => {
+                    compare_missing_last(a.time.map(Reverse), b.time.map(Reverse))
+                }
+                SortingStrategy::PriorityUp => b.priority().cmp(&a.priority()),
+                SortingStrategy::PriorityDown => a.priority().cmp(&b.priority()),
+                SortingStrategy::TodoStateUp => {
+                    compare_missing_last(self.todo_state(a), self.todo_state(b))
+                }
+                SortingStrategy::TodoStateDown => {
+                    compare_missing_last(self.todo_state(b), self.todo_state(a))
+                }
+                SortingStrategy::CategoryKeep => {
+                    match (categories.get(&a.category), categories.get(&b.category)) {
+                        (Some(a), Some(b)) => a.cmp(b),
+                        _ => a.category.cmp(&b.category),
+                    }
+                }
+                SortingStrategy::CategoryUp => a.category.cmp(&b.category),
+                SortingStrategy::CategoryDown => b.category.cmp(&a.category),
+                SortingStrategy::EffortUp => compare_missing_last(a.effort(), b.effort()),
+                SortingStrategy::EffortDown => compare_missing_last(b.effort(), a.effort()),
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn todo_state(&self, item: &AgendaItem<'_>) -> Option<usize> {
+        let keyword = match item.headline.todo_keyword.as_ref()? {
+            TodoKeyword::Todo(keyword) | TodoKeyword::Done(keyword) => keyword,
+        };
+        self.todo_keywords
+            .iter()
+            .flat_map(|sequence| sequence.todo.iter().chain(&sequence.done))
+            .position(|known| known == keyword)
+    }
+}
+
+impl Default for Sorting {
+    /// Sorts by `time-up priority-down category-keep` with the todo keywords of the documents.
+    fn default() -> Self {
+        Sorting {
+            strategies: vec![
+                SortingStrategy::TimeUp,
+                SortingStrategy::PriorityDown,
+                SortingStrategy::CategoryKeep,
+            ],
+            todo_keywords: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Sorting {
+    type Err = ();
+
+    /// Parses a whitespace separated list of strategies, e.g. `time-up priority-down`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Sorting::new)
+    }
+}
+
+/// Returns the position of every category in the order of their first item.
+fn category_order<'a, 'b: 'a>(
+    items: impl Iterator<Item = &'a AgendaItem<'b>>,
+) -> HashMap<String, usize> {
+    let mut categories: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let next = categories.len();
+        categories.entry(item.category.clone()).or_insert(next);
+    }
+    categories
+}
+
+/// Compares two optional values where missing values are sorted after all others.
+fn compare_missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn titles(items: &[AgendaItem<'_>]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| item.headline.title.as_ref().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_strategies() {
+        let sorting: Sorting = "todo-state-up  effort-down".parse().unwrap();
+        assert_eq!(
+            sorting.strategies,
+            vec![SortingStrategy::TodoStateUp, SortingStrategy::EffortDown]
+        );
+        assert_eq!(SortingStrategy::CategoryKeep.to_string(), "category-keep");
+        assert!("time-sideways".parse::<Sorting>().is_err());
+    }
+
+    #[test]
+    fn default_sorting() {
+        let document = Document::parse("* a\n* [#A] b\n* c\n* [#C] d\n").unwrap();
+        let headlines = &document.headlines;
+        let mut items = vec![
+            AgendaItem::new(&headlines[0], "work"),
+            AgendaItem::new(&headlines[1], "home"),
+            AgendaItem::new(&headlines[2], "home").with_time(NaiveTime::from_hms(9, 0, 0)),
+            AgendaItem::new(&headlines[3], "work").with_time(NaiveTime::from_hms(8, 0, 0)),
+        ];
+        Sorting::default().sort(&mut items);
+        assert_eq!(titles(&items), vec!["d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn todo_state_and_effort() {
+        let text = "* DONE a\n\
+                    :PROPERTIES:\n\
+                    :Effort: 0:30\n\
+                    :END:\n\
+                    * TODO b\n\
+                    :PROPERTIES:\n\
+                    :Effort: 1:00\n\
+                    :END:\n\
+                    * TODO c\n\
+                    :PROPERTIES:\n\
+                    :Effort: 15\n\
+                    :END:\n\
+                    * TODO d\n\
+                    * e\n";
+        let document = Document::parse(text).unwrap();
+        let mut items: Vec<_> = document
+            .headlines
+            .iter()
+            .map(|headline| AgendaItem::new(headline, "work"))
+            .collect();
+        "todo-state-up effort-up"
+            .parse::<Sorting>()
+            .unwrap()
+            .sort(&mut items);
+        assert_eq!(titles(&items), vec!["c", "b", "d", "a", "e"]);
+    }
+
+    #[test]
+    fn todo_state_of_document_keywords() {
+        let documents = [Document::parse(
+            "#+TODO: WAIT TODO | DONE\n\
+             * DONE a\n\
+             SCHEDULED: <2018-10-08 Mon>\n\
+             * TODO b\n\
+             SCHEDULED: <2018-10-08 Mon>\n\
+             * WAIT c\n\
+             SCHEDULED: <2018-10-08 Mon>\n",
+        )
+        .unwrap()];
+        let sorting: Sorting = "todo-state-up".parse().unwrap();
+        let entries = agenda_with_sorting(&documents, DateRange::new(date(8), date(8)), &sorting);
+        let items: Vec<_> = entries.into_iter().map(|entry| entry.item).collect();
+        assert_eq!(titles(&items), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn effort() {
+        let document = Document::parse("* a\n:PROPERTIES:\n:Effort: 1h 30min\n:END:\n").unwrap();
+        let item = AgendaItem::new(&document.headlines[0], "work");
+        assert_eq!(item.effort(), Some("1:30".parse().unwrap()));
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2018, 10, day)
+    }
+
+    /// A document in the category `work` with:
+    ///
+    /// - `a` scheduled weekly from October 8,
+    /// - `b` with a deadline on October 8 (and scheduled in September),
+    /// - `c` in the category `home` scheduled monthly from January 31,
+    /// - `d` in an archived subtree with a deadline on October 9,
+    /// - `e` scheduled with a diary sexp and a birthday in its section.
+    fn agenda_document() -> Document {
+        Document::parse(
+            "#+CATEGORY: work\n\
+             * TODO a\n\
+             SCHEDULED: <2018-10-08 Mon 10:00 +1w>\n\
+             * TODO [#A] b\n\
+             DEADLINE: <2018-10-08 Mon> SCHEDULED: <2018-09-03 Mon>\n\
+             ** c\n\
+             SCHEDULED: <2018-01-31 Wed .+1m>\n\
+             :PROPERTIES:\n\
+             :CATEGORY: home\n\
+             :END:\n\
+             * archived :ARCHIVE:\n\
+             ** TODO d\n\
+             DEADLINE: <2018-10-09 Tue>\n\
+             * e\n\
+             SCHEDULED: <%%(diary-float t 1 -1)>\n\
+             %%(diary-anniversary 10 12 1980) Birthday\n",
+        )
+        .unwrap()
+    }
+
+    /// Returns the day, kind and category of the entries of October with the title.
+    fn october(title: &str) -> Vec<(u32, AgendaEntryKind, String)> {
+        let documents = [agenda_document()];
+        agenda(&documents, DateRange::new(date(1), date(31)))
+            .iter()
+            .filter(|entry| entry.item.headline.title.as_ref().unwrap() == title)
+            .map(|entry| (entry.date.day(), entry.kind, entry.item.category.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn repeated_scheduled_headline() {
+        let work = || "work".to_string();
+        assert_eq!(
+            october("a"),
+            vec![
+                (8, AgendaEntryKind::Scheduled, work()),
+                (15, AgendaEntryKind::Scheduled, work()),
+                (22, AgendaEntryKind::Scheduled, work()),
+                (29, AgendaEntryKind::Scheduled, work()),
+            ]
+        );
+    }
+
+    #[test]
+    fn deadline() {
+        assert_eq!(
+            october("b"),
+            vec![(8, AgendaEntryKind::Deadline, "work".to_string())]
+        );
+    }
+
+    #[test]
+    fn category_of_headline() {
+        assert_eq!(
+            october("c"),
+            vec![(28, AgendaEntryKind::Scheduled, "home".to_string())]
+        );
+    }
+
+    #[test]
+    fn diary_sexps() {
+        assert_eq!(
+            october("e"),
+            vec![
+                (12, AgendaEntryKind::Diary, "work".to_string()),
+                (29, AgendaEntryKind::Scheduled, "work".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn archived_subtree() {
+        assert_eq!(october("d"), vec![]);
+        let documents = [agenda_document()];
+        let entries = agenda_with_filter(
+            &documents,
+            DateRange::new(date(9), date(9)),
+            &Sorting::default(),
+            SubtreeFilter::all(),
+        );
+        assert_eq!(entries.len(), 1, "the archived subtree is included");
+    }
+
+    fn planned_documents() -> [Document; 1] {
+        [Document::parse(
+            "* TODO Report\n\
+             DEADLINE: <2018-10-11 Thu> SCHEDULED: <2018-10-08 Mon>\n\
+             * TODO Call\n\
+             SCHEDULED: <2018-10-12 Fri 09:00 +1w>\n\
+             * DONE Review\n\
+             DEADLINE: <2018-10-09 Tue>\n\
+             * TODO Taxes\n\
+             DEADLINE: <2018-10-12 Fri 08:00>\n",
+        )
+        .unwrap()]
+    }
+
+    fn day_titles(days: HeadlinesByDay<'_>) -> Vec<(u32, Vec<String>)> {
+        days.into_iter()
+            .map(|(date, headlines)| {
+                let titles = headlines
+                    .iter()
+                    .map(|headline| headline.title.as_ref().unwrap().to_string());
+                (date.day(), titles.collect())
+            })
+            .collect()
+    }
+
+    fn now() -> NaiveDateTime {
+        date(12).and_hms(8, 30, 0)
+    }
+
+    #[test]
+    fn headlines_due_this_week() {
+        assert_eq!(
+            day_titles(due_this_week(&planned_documents(), now())),
+            vec![
+                (11, vec!["Report".to_string()]),
+                (12, vec!["Taxes".to_string()])
+            ]
+        );
+    }
+
+    #[test]
+    fn headlines_scheduled_in_month() {
+        let range = DateRange::month_of(now().date());
+        assert_eq!(
+            day_titles(scheduled_in(&planned_documents(), range)),
+            vec![
+                (8, vec!["Report".to_string()]),
+                (12, vec!["Call".to_string()]),
+                (19, vec!["Call".to_string()]),
+                (26, vec!["Call".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn overdue_headlines() {
+        assert_eq!(
+            day_titles(overdue(&planned_documents(), now())),
+            vec![
+                (8, vec!["Report".to_string()]),
+                (11, vec!["Report".to_string()]),
+                (12, vec!["Taxes".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_mode() {
+        let document = Document::parse(
+            "* DONE Write\n\
+             CLOSED: [2018-10-09 Tue 17:00]\n\
+             :LOGBOOK:\n\
+             - State \"DONE\"       from \"TODO\"       [2018-10-09 Tue 17:00]\n\
+             CLOCK: [2018-10-09 Tue 09:00]--[2018-10-09 Tue 10:30] =>  1:30\n\
+             :END:\n\
+             * TODO Later\n\
+             CLOCK: [2018-10-12 Fri 09:00]--[2018-10-12 Fri 09:30] =>  0:30\n",
+        )
+        .unwrap();
+        let documents = [document];
+        let entries = agenda_log(&documents, DateRange::new(date(8), date(10)));
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.date.day(), entry.item.time, entry.kind))
+            .collect();
+        let time = |hour| Some(NaiveTime::from_hms(hour, 0, 0));
+        use self::LoggedKind::*;
+        assert_eq!(
+            entries,
+            vec![
+                (9, time(9), AgendaEntryKind::Logged(Clocked { minutes: 90 })),
+                (9, time(17), AgendaEntryKind::Logged(Closed)),
+                (9, time(17), AgendaEntryKind::Logged(StateChange)),
+            ]
+        );
+    }
+}
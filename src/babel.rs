@@ -0,0 +1,438 @@
+//! Evaluating source blocks and inserting their results (like org babel does).
+//!
+//! The crate doesn't run any code. [`Babel`] finds the source blocks of a document, passes them
+//! to the [`Evaluator`] registered for their language and puts the output into a `#+RESULTS:`
+//! element after the block (replacing the results of a previous evaluation):
+//!
+//! ```text
+//! #+NAME: answer
+//! #+BEGIN_SRC python :var x=6 :results output
+//! print(x * 7)
+//! #+END_SRC
+//!
+//! #+RESULTS: answer
+//! : 42
+//! ```
+//!
+//! The [`HeaderArgs`] of a block are read from its `#+HEADER:` keywords and the arguments
+//! after the language (see [`SrcBlock::header_args`]). Values of `:var` are passed to the evaluator as written (references to
+//! named elements are not resolved). `#+CALL:` lines and inline source blocks are not
+//! evaluated.
+
+use crate::parsing::{parse_document, parse_objects};
+use crate::types::affiliated_keywords::{AffiliatedKeyword, Results};
+use crate::types::document::Document;
+use crate::types::elements::{
+    ExampleBlock, ExportBlock, FixedWidth, Handling, HeaderArgs, ResultFormat, ResultKind,
+    ResultsParams, SrcBlock,
+};
+use crate::types::greater_elements::{FootnoteDefinition, Item, Section};
+use crate::types::visit::{self, VisitorMut};
+use crate::types::{ElementSet, SharedBehavior, Spanned};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Outputs with at least this many lines are put in an example block instead of a fixed width
+/// area (like `org-babel-min-lines-for-block-output`).
+const MIN_LINES_FOR_BLOCK_OUTPUT: usize = 10;
+
+/// Evaluates the source blocks of a language.
+///
+/// Closures (`Fn(&SrcBlock, &HeaderArgs) -> Result<Output, Box<dyn Error>>`) are
+/// evaluators too.
+pub trait Evaluator {
+    fn evaluate(&self, block: &SrcBlock, arguments: &HeaderArgs) -> Result<Output, Box<dyn Error>>;
+}
+
+impl<F> Evaluator for F
+where
+    F: Fn(&SrcBlock, &HeaderArgs) -> Result<Output, Box<dyn Error>>,
+{
+    fn evaluate(&self, block: &SrcBlock, arguments: &HeaderArgs) -> Result<Output, Box<dyn Error>> {
+        self(block, arguments)
+    }
+}
+
+/// The output of an evaluated source block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Output {
+    Text(String),
+    /// The rows of a table. `None` rows are horizontal rules.
+    Table(Vec<Option<Vec<String>>>),
+}
+
+/// The error returned when evaluating source blocks fails.
+#[derive(Debug)]
+pub enum BabelError {
+    /// There is no evaluator for the language of a block.
+    NoEvaluator(String),
+    /// There is no source block with the name.
+    NotFound(String),
+    /// The evaluator of the language failed.
+    Evaluation {
+        language: String,
+        error: Box<dyn Error>,
+    },
+}
+
+impl fmt::Display for BabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BabelError::NoEvaluator(language) => {
+                write!(f, "can't evaluate source blocks in {:?}", language)
+            }
+            BabelError::NotFound(name) => write!(f, "no source block named {:?}", name),
+            BabelError::Evaluation { language, error } => {
+                write!(f, "evaluating {:?} failed: {}", language, error)
+            }
+        }
+    }
+}
+
+impl Error for BabelError {}
+
+/// The evaluators of the languages.
+#[derive(Default)]
+pub struct Babel {
+    evaluators: HashMap<String, Box<dyn Evaluator>>,
+}
+
+impl fmt::Debug for Babel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Babel")
+            .field("languages", &self.evaluators.keys().sorted())
+            .finish()
+    }
+}
+
+impl Babel {
+    pub fn new() -> Self {
+        Babel::default()
+    }
+
+    /// Registers the evaluator of a language (e.g. `python`). Replaces the previous one.
+    pub fn register(
+        &mut self,
+        language: impl Into<String>,
+        evaluator: impl Evaluator + 'static,
+    ) -> &mut Self {
+        self.evaluators.insert(language.into(), Box::new(evaluator));
+        self
+    }
+
+    /// Evaluates all source blocks with a registered language in document order and inserts
+    /// their results.
+    ///
+    /// Blocks of other languages and blocks with `:eval no` are skipped. Returns the number of
+    /// evaluated blocks. The document keeps the results of the blocks evaluated before an error.
+    pub fn execute(&self, document: &mut Document) -> Result<usize, BabelError> {
+        let mut execution = Execution {
+            babel: self,
+            name: None,
+            evaluated: 0,
+            error: None,
+        };
+        visit::walk_mut(document, &mut execution);
+        match execution.error {
+            Some(error) => Err(error),
+            None => Ok(execution.evaluated),
+        }
+    }
+
+    /// Evaluates the source block with the name (`#+NAME:`) and inserts its results.
+    pub fn execute_named(&self, document: &mut Document, name: &str) -> Result<(), BabelError> {
+        let mut execution = Execution {
+            babel: self,
+            name: Some(name),
+            evaluated: 0,
+            error: None,
+        };
+        visit::walk_mut(document, &mut execution);
+        match execution.error {
+            Some(error) => Err(error),
+            None if execution.evaluated == 0 => Err(BabelError::NotFound(name.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Evaluates the block and returns the elements of its results.
+    ///
+    /// Returns `None` if the results are not inserted (`:results silent`).
+    fn evaluate(&self, block: &SrcBlock) -> Result<Option<Vec<ElementSet>>, BabelError> {
+        let evaluator = self
+            .evaluators
+            .get(&block.language)
+            .ok_or_else(|| BabelError::NoEvaluator(block.language.clone()))?;
+        let arguments = block.header_args();
+        let output =
+            evaluator
+                .evaluate(block, &arguments)
+                .map_err(|error| BabelError::Evaluation {
+                    language: block.language.clone(),
+                    error,
+                })?;
+        let params = arguments.results();
+        match params.handling {
+            Handling::Silent | Handling::None => return Ok(None),
+            Handling::Replace | Handling::Append | Handling::Prepend => {}
+        }
+        let mut elements = results_elements(output, &params, &block.language);
+        let name = block
+            .affiliated_keywords
+            .as_ref()
+            .and_then(|keywords| keywords.value().name().cloned())
+            .unwrap_or_default();
+        let keyword = AffiliatedKeyword::Results(Spanned::new(Results::new(
+            parse_objects(&name).into(),
+            None,
+        )));
+        if let Some(keywords) = elements[0].affiliated_keywords_mut() {
+            *keywords = Some(Spanned::new(std::iter::once(keyword).collect()));
+        }
+        Ok(Some(elements))
+    }
+}
+
+/// Returns the elements showing the output. There is at least one element.
+fn results_elements(output: Output, params: &ResultsParams, language: &str) -> Vec<ElementSet> {
+    let text = match output {
+        Output::Table(rows) => {
+            let table = rows
+                .iter()
+                .map(|row| match row {
+                    Some(cells) => format!("| {} |", cells.iter().join(" | ")),
+                    None => "|-".to_string(),
+                })
+                .join("\n");
+            return org_elements(&table);
+        }
+        Output::Text(text) => text.trim_end_matches('\n').to_string(),
+    };
+    match (params.format, params.kind) {
+        (Some(ResultFormat::Raw), _) => org_elements(&text),
+        (Some(ResultFormat::Drawer), _) => org_elements(&format!(":RESULTS:\n{}\n:END:", text)),
+        (Some(ResultFormat::Org), _) => vec![SrcBlock::new("org".to_string(), text + "\n").into()],
+        (Some(ResultFormat::Code), _) => {
+            vec![SrcBlock::new(language.to_string(), text + "\n").into()]
+        }
+        (Some(ResultFormat::Html), _) => vec![ExportBlock::new("html", text + "\n").into()],
+        (Some(ResultFormat::Latex), _) => vec![ExportBlock::new("latex", text + "\n").into()],
+        (_, Some(ResultKind::List)) => {
+            org_elements(&text.lines().map(|line| format!("- {}", line)).join("\n"))
+        }
+        (_, Some(ResultKind::File)) => org_elements(&format!("[[file:{}]]", text.trim())),
+        _ if text.lines().count() >= MIN_LINES_FOR_BLOCK_OUTPUT => {
+            vec![ExampleBlock::new(text + "\n").into()]
+        }
+        _ => vec![FixedWidth::new(text).into()],
+    }
+}
+
+/// Parses org text into elements. Falls back to a fixed width area.
+fn org_elements(text: &str) -> Vec<ElementSet> {
+    let mut document = match parse_document(text) {
+        Ok(document) => document,
+        Err(_) => return vec![FixedWidth::new(text.to_string()).into()],
+    };
+    document.clear_spans();
+    match document.preface {
+        Some(preface) if !preface.content.value().is_empty() => preface.content.to_value(),
+        _ => vec![FixedWidth::new(text.to_string()).into()],
+    }
+}
+
+/// Evaluates the source blocks and inserts their results.
+struct Execution<'a> {
+    babel: &'a Babel,
+    /// Only the block with this name is evaluated.
+    name: Option<&'a str>,
+    evaluated: usize,
+    error: Option<BabelError>,
+}
+
+impl<'a> Execution<'a> {
+    /// Evaluates the source blocks in the elements (and the elements they contain).
+    fn elements(&mut self, elements: &mut Vec<ElementSet>) {
+        let mut index = 0;
+        while index < elements.len() && self.error.is_none() {
+            let block = match &elements[index] {
+                ElementSet::SrcBlock(block) if self.selects(block) => block,
+                _ => {
+                    self.visit_element(&mut elements[index]);
+                    index += 1;
+                    continue;
+                }
+            };
+            let results = match self.babel.evaluate(block) {
+                Ok(results) => results,
+                Err(error) => {
+                    self.error = Some(error);
+                    return;
+                }
+            };
+            self.evaluated += 1;
+            if let Some(mut results) = results {
+                let old = elements
+                    .get(index + 1)
+                    .filter(|element| {
+                        element
+                            .affiliated_keywords()
+                            .is_some_and(|keywords| keywords.value().results().is_some())
+                    })
+                    .is_some();
+                let post_blank = if old {
+                    elements.remove(index + 1).post_blank()
+                } else {
+                    let post_blank = elements[index].post_blank();
+                    elements[index].set_post_blank(Some(1));
+                    post_blank
+                };
+                results.last_mut().unwrap().set_post_blank(post_blank);
+                let count = results.len();
+                elements.splice(index + 1..index + 1, results);
+                index += count;
+            }
+            index += 1;
+        }
+    }
+
+    /// Returns `true` if the block is evaluated.
+    fn selects(&self, block: &SrcBlock) -> bool {
+        match self.name {
+            Some(name) => {
+                block
+                    .affiliated_keywords
+                    .as_ref()
+                    .and_then(|keywords| keywords.value().name())
+                    .map(String::as_str)
+                    == Some(name)
+            }
+            None => {
+                self.babel.evaluators.contains_key(&block.language)
+                    && block.header_args().evaluate()
+            }
+        }
+    }
+}
+
+impl<'a> VisitorMut for Execution<'a> {
+    fn visit_section(&mut self, section: &mut Section) {
+        self.elements(section.content_mut());
+    }
+
+    fn visit_element(&mut self, element: &mut ElementSet) {
+        match element {
+            ElementSet::Drawer(drawer) => self.elements(drawer.content_mut()),
+            ElementSet::DynamicBlock(block) => self.elements(block.content_mut()),
+            ElementSet::QuoteBlock(block) => self.elements(block.content_mut()),
+            _ => visit::walk_element_mut(self, element),
+        }
+    }
+
+    fn visit_item(&mut self, item: &mut Item) {
+        self.elements(item.content_mut());
+    }
+
+    fn visit_footnote_definition(&mut self, definition: &mut FootnoteDefinition) {
+        self.elements(definition.content_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Multiplies the value of the block with the variable `x` (or 1).
+    fn calc() -> Babel {
+        let mut babel = Babel::new();
+        babel.register(
+            "calc",
+            |block: &SrcBlock, arguments: &HeaderArgs| -> Result<Output, Box<dyn Error>> {
+                let x: i64 = match arguments.vars().first() {
+                    Some(var) => var.value.parse()?,
+                    None => 1,
+                };
+                let factor: i64 = block.value.trim().parse()?;
+                Ok(Output::Text((x * factor).to_string()))
+            },
+        );
+        babel
+    }
+
+    const NAMED: &str = "#+HEADER: :var x=6\n\
+                         #+NAME: answer\n\
+                         #+BEGIN_SRC calc :exports both\n\
+                         7\n\
+                         #+END_SRC\n\
+                         \n\
+                         #+RESULTS: answer\n\
+                         : 41\n";
+
+    #[test]
+    fn replace_results() {
+        let mut document = Document::parse(NAMED).unwrap();
+        assert_eq!(calc().execute(&mut document).unwrap(), 1);
+        assert_eq!(document.to_string(), NAMED.replace(": 41\n", ": 42\n"));
+    }
+
+    #[test]
+    fn insert_results() {
+        let text = "* Headline\n#+BEGIN_SRC calc\n4\n#+END_SRC\n";
+        let mut document = Document::parse(text).unwrap();
+        assert_eq!(calc().execute(&mut document).unwrap(), 1);
+        assert_eq!(document.to_string(), format!("{}\n#+RESULTS:\n: 4\n", text));
+    }
+
+    #[test]
+    fn silent_results() {
+        let text = "#+BEGIN_SRC calc :results silent\n2\n#+END_SRC\n";
+        let mut document = Document::parse(text).unwrap();
+        assert_eq!(calc().execute(&mut document).unwrap(), 1);
+        assert_eq!(document.to_string(), text);
+    }
+
+    #[test]
+    fn skip_blocks() {
+        let text = "#+BEGIN_SRC python\n\
+                    print(1)\n\
+                    #+END_SRC\n\
+                    #+BEGIN_SRC calc :eval no\n\
+                    3\n\
+                    #+END_SRC\n";
+        let mut document = Document::parse(text).unwrap();
+        assert_eq!(calc().execute(&mut document).unwrap(), 0);
+        assert_eq!(document.to_string(), text);
+    }
+
+    #[test]
+    fn execute_named_block() {
+        let mut document = Document::parse(NAMED).unwrap();
+        let babel = calc();
+        babel.execute_named(&mut document, "answer").unwrap();
+        let expected = NAMED.replace(": 41\n", ": 42\n");
+        assert_eq!(document.to_string(), expected);
+        // evaluating again replaces the results
+        babel.execute_named(&mut document, "answer").unwrap();
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test]
+    fn named_block_not_found() {
+        let mut document = Document::parse(NAMED).unwrap();
+        match calc().execute_named(&mut document, "missing") {
+            Err(BabelError::NotFound(name)) => assert_eq!(name, "missing"),
+            result => panic!("expected an error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn evaluation_error() {
+        let mut document = Document::parse("#+BEGIN_SRC calc\nx\n#+END_SRC\n").unwrap();
+        match calc().execute(&mut document) {
+            Err(BabelError::Evaluation { language, .. }) => assert_eq!(language, "calc"),
+            result => panic!("expected an error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Checking an org file in one go (e.g. in CI jobs or pre-commit hooks).
+//!
+//! [`check_str`] parses the file with error recovery and collects everything that is wrong with
+//! it in a [`Report`]: parse errors, violated invariants (see [`Document::validate`]) and links
+//! that can't be resolved. The report also contains some statistics of the file. With the
+//! `serde` feature it can be serialized (e.g. to JSON).
+//!
+//! Only links inside the file are checked: links to headlines (`*TITLE`, `#CUSTOM-ID` and
+//! `id:ID`), targets and named elements and footnote references. Links to other files or URLs
+//! are not followed.
+
+use crate::parsing::{line_column, parse_with_diagnostics, ParseConfig};
+use crate::types::document::Document;
+use crate::types::elements::Paragraph;
+use crate::types::greater_elements::{Headline, TodoKeyword};
+use crate::types::index::DocumentIndex;
+use crate::types::node_id::NodeId;
+use crate::types::objects::{FootnoteReference, Link, LinkFormat, LinkPath};
+use crate::types::visit::{self, Visitor};
+use crate::types::Span;
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The result of [`check_str`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// The parts of the file that can't be parsed (see [`parse_with_diagnostics`]).
+    pub parse_errors: Vec<Problem>,
+    /// The violated invariants of the document (see [`Document::validate`]).
+    pub lints: Vec<Problem>,
+    /// The links and footnote references that can't be resolved.
+    pub broken_links: Vec<Problem>,
+    pub statistics: Statistics,
+}
+
+/// A problem found by [`check_str`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Problem {
+    /// The line (starting at 1) if the position is known.
+    pub line: Option<usize>,
+    /// The column in characters (starting at 1) if the position is known.
+    pub column: Option<usize>,
+    /// The titles of the headlines containing the problem. Empty for the preface or if the
+    /// headline is not known.
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+/// The numbers of some things in the checked file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    pub lines: usize,
+    pub headlines: usize,
+    /// The headlines with a todo keyword that is not done.
+    pub todo: usize,
+    /// The headlines with a done keyword.
+    pub done: usize,
+    pub links: usize,
+}
+
+impl Report {
+    /// Returns `true` if no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.parse_errors.is_empty() && self.lints.is_empty() && self.broken_links.is_empty()
+    }
+
+    /// Returns all problems (parse errors first, then lints and broken links).
+    pub fn problems(&self) -> impl Iterator<Item = &Problem> {
+        self.parse_errors
+            .iter()
+            .chain(&self.lints)
+            .chain(&self.broken_links)
+    }
+}
+
+/// Writes the problem like a compiler (`LINE:COLUMN: PATH: MESSAGE`). Unknown parts are left out.
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+            write!(f, " ")?;
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path.iter().format(" / "))?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Checks an org file and returns all problems found in it.
+///
+/// The positions of lints and broken links are only known if the whole file can be parsed
+/// (otherwise the spans are not relative to the start of the file).
+pub fn check_str(input: &str, config: &ParseConfig) -> Report {
+    let (document, diagnostics) = parse_with_diagnostics(input, config);
+    let problem = |path: Vec<String>, span: Option<&Span>, message: String| {
+        let position = span
+            .map(Span::start)
+            .filter(|&start| diagnostics.is_empty() && input.is_char_boundary(start))
+            .map(|start| line_column(input, start));
+        Problem {
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            path,
+            message,
+        }
+    };
+
+    let parse_errors = diagnostics
+        .iter()
+        .map(|diagnostic| Problem {
+            line: Some(diagnostic.line),
+            column: Some(diagnostic.column),
+            path: Vec::new(),
+            message: format!(
+                "expected {}, found {:?}",
+                diagnostic.expected, diagnostic.found
+            ),
+        })
+        .collect();
+    let lints = document
+        .validate()
+        .into_iter()
+        .map(|diagnostic| {
+            problem(
+                diagnostic.path,
+                diagnostic.span.as_ref(),
+                diagnostic.kind.to_string(),
+            )
+        })
+        .collect();
+
+    let mut checker = LinkChecker::new(&document);
+    visit::walk(&document, &mut checker);
+    let broken_links = checker
+        .broken
+        .into_iter()
+        .map(|(path, span, message)| problem(path, span.as_ref(), message))
+        .collect();
+
+    let headlines = document.node_ids();
+    let count = |done| {
+        headlines
+            .iter()
+            .filter(|(_, headline)| match headline.todo_keyword {
+                Some(TodoKeyword::Done(_)) => done,
+                Some(TodoKeyword::Todo(_)) => !done,
+                None => false,
+            })
+            .count()
+    };
+    let statistics = Statistics {
+        lines: input.lines().count(),
+        headlines: headlines.len(),
+        todo: count(false),
+        done: count(true),
+        links: checker.links,
+    };
+
+    Report {
+        parse_errors,
+        lints,
+        broken_links,
+        statistics,
+    }
+}
+
+fn title(headline: &Headline) -> String {
+    headline
+        .title
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// Collects the links and footnote references that can't be resolved.
+struct LinkChecker<'a> {
+    document: &'a Document,
+    index: DocumentIndex<'a>,
+    titles: HashSet<String>,
+    /// The titles of the headlines containing the current object.
+    path: Vec<String>,
+    /// The span of the current object in a paragraph (or of the object containing it).
+    span: Option<Span>,
+    broken: Vec<(Vec<String>, Option<Span>, String)>,
+    links: usize,
+}
+
+impl<'a> LinkChecker<'a> {
+    fn new(document: &'a Document) -> Self {
+        LinkChecker {
+            document,
+            index: document.index(),
+            titles: document
+                .node_ids()
+                .into_iter()
+                .map(|(_, headline)| title(headline))
+                .collect(),
+            path: Vec::new(),
+            span: None,
+            broken: Vec::new(),
+            links: 0,
+        }
+    }
+
+    /// Returns `true` if the link can be followed (or is not a link inside the file).
+    fn resolves(&self, path: &LinkPath) -> bool {
+        match path {
+            LinkPath::Id(id) => self
+                .document
+                .headline_by_id(&NodeId::Id(id.clone()))
+                .is_some(),
+            LinkPath::CustomId(id) => self
+                .document
+                .headline_by_id(&NodeId::CustomId(id.clone()))
+                .is_some(),
+            LinkPath::Fuzzy(fuzzy) if fuzzy.starts_with('*') => self.titles.contains(&fuzzy[1..]),
+            LinkPath::Fuzzy(fuzzy) => {
+                self.index
+                    .targets
+                    .iter()
+                    .any(|target| target.target == *fuzzy)
+                    || self
+                        .index
+                        .named_elements
+                        .iter()
+                        .any(|element| element.name() == Some(fuzzy))
+                    || self.titles.contains(fuzzy)
+            }
+            _ => true,
+        }
+    }
+
+    fn report(&mut self, message: String) {
+        self.broken
+            .push((self.path.clone(), self.span.clone(), message));
+    }
+}
+
+impl<'a> Visitor<'a> for LinkChecker<'a> {
+    fn visit_headline(&mut self, headline: &'a Headline) {
+        self.path.push(title(headline));
+        visit::walk_headline(self, headline);
+        self.path.pop();
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &'a Paragraph) {
+        for (object, span) in paragraph.content.iter_spanned() {
+            self.span = span.cloned();
+            self.visit_object(object.into());
+        }
+        self.span = None;
+    }
+
+    fn visit_link(&mut self, link: &'a Link) {
+        self.links += 1;
+        if let LinkFormat::Bracket(path, _, _) = &link.link {
+            if !self.resolves(path) {
+                self.report(format!("link to {} can't be resolved", path));
+            }
+        }
+        visit::walk_link(self, link);
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &'a FootnoteReference) {
+        if let Some(label) = reference.label() {
+            if self.index.footnote_definition(label).is_none() {
+                self.report(format!("footnote {} is not defined", label));
+            }
+        }
+        visit::walk_footnote_reference(self, reference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "#+TITLE: Notes\n\
+                         * TODO Tasks\n\
+                         See [[*Done]], [[#intro][the intro]] and [[*Missing]].\n\
+                         ** DONE Done\n\
+                         Text with a footnote[fn:1] and a missing one[fn:2].\n\
+                         \n\
+                         [fn:1] The definition.\n";
+
+    #[test]
+    fn broken_links() {
+        let report = check_str(INPUT, &ParseConfig::default());
+        assert!(!report.is_ok());
+        assert!(report.parse_errors.is_empty());
+        assert_eq!(report.lints, vec![]);
+        assert_eq!(
+            report
+                .broken_links
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "3:16: Tasks: link to #intro can't be resolved",
+                "3:42: Tasks: link to *Missing can't be resolved",
+                "5:45: Tasks / Done: footnote 2 is not defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn statistics() {
+        let report = check_str(INPUT, &ParseConfig::default());
+        assert_eq!(
+            report.statistics,
+            Statistics {
+                lines: 7,
+                headlines: 2,
+                todo: 1,
+                done: 1,
+                links: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let report = check_str("Intro\n* a\n*bold*\n", &ParseConfig::default());
+        assert_eq!(report.parse_errors.len(), 1);
+        assert_eq!(report.parse_errors[0].line, Some(3));
+        assert_eq!(report.problems().count(), 1);
+    }
+}
@@ -0,0 +1,322 @@
+//! Clock tables: the time clocked on headlines (like `#+BEGIN: clocktable`).
+//!
+//! The time of a headline is the sum of its closed `CLOCK:` lines (in its section and in drawers
+//! like `LOGBOOK`) and the time of its children. Running clocks are ignored.
+//!
+//! A [`ClockTable`] can be limited to a [`DateRange`]. Only the part of a clock inside the range
+//! counts, e.g. a clock from 23:00 to 01:00 counts one hour for each day. [`ClockTable::steps`]
+//! creates one table per day or week of a range (like the `:step` parameter of clock tables).
+
+pub use crate::journal::DateRange;
+use crate::types::document::Document;
+use crate::types::elements::{Clock, ClockStatus};
+use crate::types::greater_elements::{Headline, HeadlineContentSet};
+use crate::types::{ElementSet, Parent, Spanned};
+use chrono::{Duration, NaiveDateTime};
+use std::fmt;
+
+/// Returns the closed clocks of the headline (not of its children).
+pub fn clocks(headline: &Headline) -> Vec<&Clock> {
+    fn collect<'a>(elements: &'a [ElementSet], clocks: &mut Vec<&'a Clock>) {
+        for element in elements {
+            match element {
+                ElementSet::Clock(clock) if clock.status() == ClockStatus::Closed => {
+                    clocks.push(clock)
+                }
+                ElementSet::Drawer(drawer) => collect(
+                    drawer.content().map_or(&[], |content| content.value()),
+                    clocks,
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut clocks = Vec::new();
+    for content in headline.content().into_iter().flat_map(Spanned::value) {
+        if let HeadlineContentSet::Section(section) = content {
+            collect(
+                section.content().map_or(&[], |content| content.value()),
+                &mut clocks,
+            );
+        }
+    }
+    clocks
+}
+
+/// Returns the minutes of a closed clock inside the range (or all minutes without a range).
+///
+/// The minutes are computed from the timestamps. Returns `0` for clocks without a time range.
+pub fn clock_minutes(clock: &Clock, range: Option<DateRange>) -> i64 {
+    let timestamp = match &clock.timestamp {
+        Some(timestamp) => timestamp,
+        None => return 0,
+    };
+    let (mut start, mut end) = match (timestamp.start_date_time(), timestamp.end_date_time()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return 0,
+    };
+    if let Some(range) = range {
+        let (range_start, range_end) = bounds(range);
+        start = start.max(range_start);
+        end = end.min(range_end);
+    }
+    (end - start).num_minutes().max(0)
+}
+
+/// Returns the minutes clocked on the headline and its children inside the range.
+pub fn clocked_minutes(headline: &Headline, range: Option<DateRange>) -> i64 {
+    let own: i64 = clocks(headline)
+        .into_iter()
+        .map(|clock| clock_minutes(clock, range))
+        .sum();
+    own + headline
+        .children()
+        .map(|child| clocked_minutes(child, range))
+        .sum::<i64>()
+}
+
+/// The start and the (exclusive) end of the range.
+fn bounds(range: DateRange) -> (NaiveDateTime, NaiveDateTime) {
+    (
+        range.start.and_hms(0, 0, 0),
+        (range.end + Duration::days(1)).and_hms(0, 0, 0),
+    )
+}
+
+/// The time clocked on the headlines of a document.
+///
+/// The table is printed like org mode prints clock tables:
+///
+/// ```text
+/// | Headline     | Time   |      |
+/// |--------------+--------+------|
+/// | *Total time* | *4:00* |      |
+/// |--------------+--------+------|
+/// | Project      | 4:00   |      |
+/// | \_  Design   |        | 1:30 |
+/// | \_  Code     |        | 2:30 |
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClockTable {
+    /// The range the clocks were limited to.
+    pub range: Option<DateRange>,
+    /// The headlines with clocked time in document order.
+    pub rows: Vec<ClockTableRow>,
+    /// The total minutes.
+    pub total: i64,
+}
+
+/// A headline in a [`ClockTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClockTableRow {
+    pub level: u32,
+    pub title: String,
+    /// The minutes clocked on the headline and its children.
+    pub minutes: i64,
+}
+
+/// The length of the ranges of [`ClockTable::steps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClockStep {
+    Day,
+    /// Weeks from Monday to Sunday.
+    Week,
+}
+
+impl ClockTable {
+    /// Creates the clock table of the document (limited to the range if given).
+    pub fn new(document: &Document, range: Option<DateRange>) -> Self {
+        fn collect<'a>(
+            headlines: impl IntoIterator<Item = &'a Headline>,
+            range: Option<DateRange>,
+            rows: &mut Vec<ClockTableRow>,
+        ) {
+            for headline in headlines {
+                let minutes = clocked_minutes(headline, range);
+                if minutes == 0 {
+                    continue;
+                }
+                rows.push(ClockTableRow {
+                    level: headline.level,
+                    title: headline
+                        .title
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    minutes,
+                });
+                collect(headline.children().collect::<Vec<_>>(), range, rows);
+            }
+        }
+
+        let mut rows = Vec::new();
+        collect(&document.headlines, range, &mut rows);
+        let total = document
+            .headlines
+            .iter()
+            .map(|headline| clocked_minutes(headline, range))
+            .sum();
+        ClockTable { range, rows, total }
+    }
+
+    /// Creates one clock table for every day or week of the range.
+    ///
+    /// The first and last week are cut off at the range.
+    pub fn steps(document: &Document, range: DateRange, step: ClockStep) -> Vec<Self> {
+        let mut tables = Vec::new();
+        let mut start = range.start;
+        while start <= range.end {
+            let end = match step {
+                ClockStep::Day => start,
+                ClockStep::Week => DateRange::week_of(start).end.min(range.end),
+            };
+            tables.push(ClockTable::new(document, Some(DateRange::new(start, end))));
+            start = end + Duration::days(1);
+        }
+        tables
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// Formats minutes like org mode (`H:MM`).
+pub(crate) fn format_minutes(minutes: i64) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl fmt::Display for ClockTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let top = self.rows.iter().map(|row| row.level).min().unwrap_or(1);
+        let depth = self
+            .rows
+            .iter()
+            .map(|row| row.level - top + 1)
+            .max()
+            .unwrap_or(1) as usize;
+        let row = |cells: Vec<String>| {
+            let mut row = cells;
+            row.resize(depth + 1, String::new());
+            row
+        };
+
+        let header = row(vec!["Headline".to_string(), "Time".to_string()]);
+        let total = row(vec![
+            "*Total time*".to_string(),
+            format!("*{}*", format_minutes(self.total)),
+        ]);
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|clocked| {
+                let level = (clocked.level - top) as usize;
+                let indent = if level == 0 {
+                    String::new()
+                } else {
+                    format!("\\_{}", " ".repeat(2 * level))
+                };
+                let mut cells = vec![format!("{}{}", indent, clocked.title)];
+                cells.resize(level + 1, String::new());
+                cells.push(format_minutes(clocked.minutes));
+                row(cells)
+            })
+            .collect();
+
+        let mut widths = vec![0; depth + 1];
+        for cells in std::iter::once(&header)
+            .chain(std::iter::once(&total))
+            .chain(&rows)
+        {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let write_row = |f: &mut fmt::Formatter<'_>, cells: &[String]| {
+            write!(f, "|")?;
+            for (width, cell) in widths.iter().zip(cells) {
+                write!(f, " {:width$} |", cell, width = width)?;
+            }
+            writeln!(f)
+        };
+        let write_rule = |f: &mut fmt::Formatter<'_>| {
+            let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+            writeln!(f, "|{}|", dashes.join("+"))
+        };
+
+        write_row(f, &header)?;
+        write_rule(f)?;
+        write_row(f, &total)?;
+        write_rule(f)?;
+        for cells in &rows {
+            write_row(f, cells)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn document() -> Document {
+        Document::parse(
+            "* Project\n\
+             ** Design\n\
+             :LOGBOOK:\n\
+             CLOCK: [2018-10-08 Mon 09:00]--[2018-10-08 Mon 10:30] =>  1:30\n\
+             :END:\n\
+             ** Code\n\
+             CLOCK: [2018-10-08 Mon 23:00]--[2018-10-09 Tue 01:30] =>  2:30\n\
+             CLOCK: [2018-10-09 Tue 10:00]\n\
+             * Other\n",
+        )
+        .unwrap()
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2018, 10, day)
+    }
+
+    #[test]
+    fn clock_table() {
+        let table = ClockTable::new(&document(), None);
+        assert_eq!(table.total, 240);
+        assert_eq!(
+            table.to_string(),
+            "| Headline     | Time   |      |\n\
+             |--------------+--------+------|\n\
+             | *Total time* | *4:00* |      |\n\
+             |--------------+--------+------|\n\
+             | Project      | 4:00   |      |\n\
+             | \\_  Design   |        | 1:30 |\n\
+             | \\_  Code     |        | 2:30 |\n"
+        );
+    }
+
+    #[test]
+    fn clock_tables_per_day() {
+        let range = DateRange::new(date(8), date(10));
+        let days = ClockTable::steps(&document(), range, ClockStep::Day);
+        assert_eq!(
+            days.iter().map(|table| table.total).collect::<Vec<_>>(),
+            vec![150, 90, 0]
+        );
+        assert!(days[2].is_empty());
+    }
+
+    #[test]
+    fn clock_tables_per_week() {
+        let range = DateRange::new(date(1), date(9));
+        let weeks = ClockTable::steps(&document(), range, ClockStep::Week);
+        assert_eq!(
+            weeks
+                .iter()
+                .map(|table| (table.range.unwrap().end, table.total))
+                .collect::<Vec<_>>(),
+            vec![(date(7), 0), (date(9), 240)]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Column view: a table of property values per headline (like `org-columns`).
+//!
+//! The columns are defined by a [`ColumnFormat`] (e.g. `%25ITEM %TODO %3PRIORITY %Effort{:}`),
+//! usually the `#+COLUMNS:` keyword of the document. A column shows a property of the headline
+//! (see [`Headline::property`]) or one of these special properties:
+//!
+//! - `ITEM`: the title of the headline
+//! - `TODO`: the todo keyword
+//! - `PRIORITY`: the priority ([`DEFAULT_PRIORITY`] if the headline has none)
+//! - `TAGS`: the tags of the headline (e.g. `:work:urgent:`)
+//! - `CLOCKSUM`: the time clocked on the subtree (see [`clocked_minutes`])
+//! - `DEADLINE`, `SCHEDULED` and `CLOSED`: the timestamps of the planning line
+//!
+//! Summary types (e.g. `{+}`) are parsed but the values of parent headlines are not replaced by
+//! the summaries of their children.
+
+use crate::agenda::DEFAULT_PRIORITY;
+use crate::clocking::{clocked_minutes, format_minutes};
+use crate::types::document::Document;
+use crate::types::greater_elements::Headline;
+use itertools::Itertools;
+use std::fmt;
+use std::str::FromStr;
+
+/// The format of the column view if the document has no `#+COLUMNS:` keyword
+/// (`org-columns-default-format`).
+pub const DEFAULT_COLUMN_FORMAT: &str = "%25ITEM %TODO %3PRIORITY %TAGS";
+
+/// The columns of a column view (e.g. `%25ITEM %TODO %Effort(Estimated){:}`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnFormat {
+    pub columns: Vec<Column>,
+}
+
+/// A column of a [`ColumnFormat`]: `%WIDTH PROPERTY(TITLE){SUMMARY}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Column {
+    /// The name of the property (case is ignored).
+    pub property: String,
+    /// The title of the column. The property name is used if there is none.
+    pub title: Option<String>,
+    /// The width of the column in characters.
+    pub width: Option<usize>,
+    /// The summary type (e.g. `+` or `:`) without the braces.
+    pub summary: Option<String>,
+}
+
+/// The error returned when parsing a [`ColumnFormat`] fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseColumnFormatError {
+    /// The byte offset in the format string.
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseColumnFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid column format at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseColumnFormatError {}
+
+impl ColumnFormat {
+    /// Returns the format of the last `#+COLUMNS:` keyword of the document.
+    ///
+    /// Returns the [default format](`DEFAULT_COLUMN_FORMAT`) if there is no such keyword or its
+    /// value is not a valid format.
+    pub fn of_document(document: &Document) -> Self {
+        document
+            .keywords()
+            .filter(|keyword| keyword.key.eq_ignore_ascii_case("COLUMNS"))
+            .last()
+            .and_then(|keyword| keyword.value.value().iter().join("").parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ColumnFormat {
+    fn default() -> Self {
+        DEFAULT_COLUMN_FORMAT.parse().unwrap()
+    }
+}
+
+impl FromStr for ColumnFormat {
+    type Err = ParseColumnFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |position, message| ParseColumnFormatError { position, message };
+        let mut columns = Vec::new();
+        let mut rest = s.trim_start();
+        while !rest.is_empty() {
+            let position = s.len() - rest.len();
+            if !rest.starts_with('%') {
+                return Err(error(position, "expected `%`"));
+            }
+            rest = &rest[1..];
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let width = rest[..digits].parse().ok();
+            rest = &rest[digits..];
+            let name = rest
+                .find(|c: char| c.is_whitespace() || c == '(' || c == '{')
+                .unwrap_or(rest.len());
+            if name == 0 {
+                return Err(error(s.len() - rest.len(), "missing property name"));
+            }
+            let property = rest[..name].to_string();
+            rest = &rest[name..];
+            let mut title = None;
+            if rest.starts_with('(') {
+                let (value, after) =
+                    enclosed(rest, ')').ok_or_else(|| error(s.len(), "unclosed title"))?;
+                title = Some(value.to_string());
+                rest = after;
+            }
+            let mut summary = None;
+            if rest.starts_with('{') {
+                let (value, after) =
+                    enclosed(rest, '}').ok_or_else(|| error(s.len(), "unclosed summary type"))?;
+                summary = Some(value.to_string());
+                rest = after;
+            }
+            columns.push(Column {
+                property,
+                title,
+                width,
+                summary,
+            });
+            rest = rest.trim_start();
+        }
+        Ok(ColumnFormat { columns })
+    }
+}
+
+/// Returns the part of `rest` after its first character and before `close` and the part after
+/// `close`.
+fn enclosed(rest: &str, close: char) -> Option<(&str, &str)> {
+    let end = rest.find(close)?;
+    Some((&rest[1..end], &rest[end + 1..]))
+}
+
+impl fmt::Display for ColumnFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.columns.iter().format(" "))
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%")?;
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        write!(f, "{}", self.property)?;
+        if let Some(title) = &self.title {
+            write!(f, "({})", title)?;
+        }
+        if let Some(summary) = &self.summary {
+            write!(f, "{{{}}}", summary)?;
+        }
+        Ok(())
+    }
+}
+
+impl Column {
+    /// Returns the title of the column (or the property name if it has no title).
+    pub fn title(&self) -> &str {
+        self.title.as_ref().unwrap_or(&self.property)
+    }
+
+    /// Returns the value of the column for the headline (`None` if it is empty).
+    pub fn value(&self, headline: &Headline) -> Option<String> {
+        let planning = headline.planning.as_ref();
+        match self.property.to_uppercase().as_str() {
+            "ITEM" => headline
+                .title
+                .as_ref()
+                .map(|title| title.to_string().trim().to_string()),
+            "TODO" => headline.todo_keyword.as_ref().map(ToString::to_string),
+            "PRIORITY" => Some(headline.priority.unwrap_or(DEFAULT_PRIORITY).to_string()),
+            "TAGS" if headline.tags.is_empty() => None,
+            "TAGS" => Some(format!(":{}:", headline.tags.join(":"))),
+            "CLOCKSUM" => match clocked_minutes(headline, None) {
+                0 => None,
+                minutes => Some(format_minutes(minutes)),
+            },
+            "DEADLINE" => planning?.deadline.as_ref().map(ToString::to_string),
+            "SCHEDULED" => planning?.scheduled.as_ref().map(ToString::to_string),
+            "CLOSED" => planning?.closed.as_ref().map(ToString::to_string),
+            _ => headline
+                .property(&self.property)
+                .map(|value| value.into_owned()),
+        }
+    }
+}
+
+/// The values of the columns for all headlines of a document.
+///
+/// The view is printed as an org table (like a `columnview` dynamic block):
+///
+/// ```text
+/// | ITEM   | TODO | PRIORITY | Effort |
+/// |--------+------+----------+--------|
+/// | Tasks  |      | B        |        |
+/// | Design | TODO | A        | 1:30   |
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnView {
+    pub columns: Vec<Column>,
+    /// The headlines in document order.
+    pub rows: Vec<ColumnViewRow>,
+}
+
+/// A headline in a [`ColumnView`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnViewRow {
+    pub level: u32,
+    /// The value of each column (`None` if it is empty).
+    pub values: Vec<Option<String>>,
+}
+
+impl ColumnView {
+    /// Creates the column view of all headlines of the document with the format.
+    pub fn new(document: &Document, format: &ColumnFormat) -> Self {
+        fn collect<'a>(
+            headlines: impl IntoIterator<Item = &'a Headline>,
+            columns: &[Column],
+            rows: &mut Vec<ColumnViewRow>,
+        ) {
+            for headline in headlines {
+                rows.push(ColumnViewRow {
+                    level: headline.level,
+                    values: columns
+                        .iter()
+                        .map(|column| column.value(headline))
+                        .collect(),
+                });
+                collect(headline.children(), columns, rows);
+            }
+        }
+
+        let mut rows = Vec::new();
+        collect(&document.headlines, &format.columns, &mut rows);
+        ColumnView {
+            columns: format.columns.clone(),
+            rows,
+        }
+    }
+
+    /// Returns the values of the column with the property (case is ignored) in document order.
+    pub fn column(&self, property: &str) -> Option<Vec<Option<&str>>> {
+        let index = self
+            .columns
+            .iter()
+            .position(|column| column.property.eq_ignore_ascii_case(property))?;
+        Some(
+            self.rows
+                .iter()
+                .map(|row| row.values[index].as_deref())
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for ColumnView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header: Vec<&str> = self.columns.iter().map(Column::title).collect();
+        let rows: Vec<Vec<&str>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.values
+                    .iter()
+                    .map(|value| value.as_ref().map_or("", String::as_str))
+                    .collect()
+            })
+            .collect();
+
+        let mut widths = vec![0; header.len()];
+        for cells in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let write_row = |f: &mut fmt::Formatter<'_>, cells: &[&str]| {
+            write!(f, "|")?;
+            for (width, cell) in widths.iter().zip(cells) {
+                write!(f, " {:width$} |", cell, width = width)?;
+            }
+            writeln!(f)
+        };
+
+        write_row(f, &header)?;
+        let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+        writeln!(f, "|{}|", dashes.join("+"))?;
+        for cells in &rows {
+            write_row(f, cells)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_column_format() {
+        let format: ColumnFormat = "%25ITEM %TODO %Effort(Estimate){:} %CLOCKSUM"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            format.columns[2],
+            Column {
+                property: "Effort".to_string(),
+                title: Some("Estimate".to_string()),
+                width: None,
+                summary: Some(":".to_string()),
+            }
+        );
+        assert_eq!(format.columns[0].width, Some(25));
+        assert_eq!(
+            format.to_string(),
+            "%25ITEM %TODO %Effort(Estimate){:} %CLOCKSUM"
+        );
+    }
+
+    #[test]
+    fn invalid_column_format() {
+        assert_eq!(
+            "%ITEM %(x)".parse::<ColumnFormat>(),
+            Err(ParseColumnFormatError {
+                position: 7,
+                message: "missing property name",
+            })
+        );
+    }
+
+    #[test]
+    fn column_view() {
+        let document = Document::parse(
+            "#+COLUMNS: %ITEM %TODO %PRIORITY %Effort %CLOCKSUM %TAGS\n\
+             * Tasks :work:\n\
+             ** TODO [#A] Design\n\
+             :PROPERTIES:\n\
+             :EFFORT: 1:30\n\
+             :END:\n\
+             CLOCK: [2018-10-08 Mon 09:00]--[2018-10-08 Mon 10:15] =>  1:15\n",
+        )
+        .unwrap();
+        let view = document.column_view();
+        assert_eq!(view.column("todo"), Some(vec![None, Some("TODO")]));
+        assert_eq!(
+            view.to_string(),
+            "| ITEM   | TODO | PRIORITY | Effort | CLOCKSUM | TAGS   |\n\
+             |--------+------+----------+--------+----------+--------|\n\
+             | Tasks  |      | B        |        | 1:15     | :work: |\n\
+             | Design | TODO | A        | 1:30   | 1:15     |        |\n"
+        );
+    }
+
+    #[test]
+    fn default_column_format() {
+        assert_eq!(
+            ColumnView::new(&Document::default(), &ColumnFormat::default()).columns[0].title(),
+            "ITEM"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Encrypted entries (like org-crypt).
+//!
+//! The body of a headline tagged with `:crypt:` can be encrypted. The body (the section after
+//! the planning line and property drawer and all child headlines) is then replaced by an ASCII
+//! armored PGP message, which is parsed into an [`EncryptedBlock`] and written back unchanged.
+//!
+//! This crate doesn't encrypt anything itself. The encryption is done by a [`Cipher`] (e.g. a
+//! wrapper around `gpg`), so tools can decrypt entries, change them and encrypt them again.
+
+use crate::parsing::{parse_document, ParseError};
+use crate::types::document::Document;
+use crate::types::elements::EncryptedBlock;
+use crate::types::greater_elements::{Headline, HeadlineContentSet, Section};
+use crate::types::{ElementSet, Parent, Spanned};
+use itertools::Itertools;
+use std::fmt;
+
+/// The tag of headlines whose body is encrypted by [`encrypt_entries`].
+pub const CRYPT_TAG: &str = "crypt";
+
+/// Encrypts and decrypts the body of entries.
+pub trait Cipher {
+    type Error;
+
+    /// Encrypts the body of an entry. Returns an ASCII armored PGP message.
+    fn encrypt(&mut self, plaintext: &str) -> Result<String, Self::Error>;
+
+    /// Decrypts an ASCII armored PGP message (including the `BEGIN` and `END` lines).
+    fn decrypt(&mut self, message: &str) -> Result<String, Self::Error>;
+}
+
+/// The error returned when decrypting an entry fails.
+#[derive(Debug)]
+pub enum DecryptError<E> {
+    /// The cipher couldn't decrypt the message.
+    Cipher(E),
+    /// The decrypted body is not valid org.
+    Parse(ParseError),
+}
+
+impl<E: fmt::Display> fmt::Display for DecryptError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptError::Cipher(error) => write!(f, "can't decrypt the entry: {}", error),
+            DecryptError::Parse(error) => write!(
+                f,
+                "can't parse the decrypted entry (expected {})",
+                error.expected()
+            ),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for DecryptError<E> {}
+
+/// Returns `true` if the headline (not one of its ancestors) has the [`CRYPT_TAG`].
+pub fn is_crypt_entry(headline: &Headline) -> bool {
+    headline.tags.iter().any(|tag| tag == CRYPT_TAG)
+}
+
+/// Returns the encrypted body of the headline or `None` if the body is not encrypted.
+///
+/// The body is encrypted if it consists of a single [`EncryptedBlock`] (and no child
+/// headlines).
+pub fn encrypted_block(headline: &Headline) -> Option<&EncryptedBlock> {
+    match headline.content().map(Spanned::value).map(Vec::as_slice) {
+        Some([HeadlineContentSet::Section(section)]) => {
+            match section.content().map(Spanned::value).map(Vec::as_slice) {
+                Some([ElementSet::EncryptedBlock(block)]) => Some(block),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns all headlines with an encrypted body in document order.
+///
+/// Headlines in the encrypted body of another headline are only found after decrypting it.
+pub fn encrypted_entries(document: &Document) -> Vec<&Headline> {
+    fn find<'a>(headline: &'a Headline, entries: &mut Vec<&'a Headline>) {
+        if encrypted_block(headline).is_some() {
+            entries.push(headline);
+        }
+        for child in headline.children() {
+            find(child, entries);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for headline in &document.headlines {
+        find(headline, &mut entries);
+    }
+    entries
+}
+
+/// Encrypts the body of the headline (whether it has the [`CRYPT_TAG`] or not).
+///
+/// Returns `false` if the body is empty or already encrypted.
+pub fn encrypt_entry<C: Cipher>(headline: &mut Headline, cipher: &mut C) -> Result<bool, C::Error> {
+    let content = match &headline.content {
+        Some(content) if !content.value().is_empty() && encrypted_block(headline).is_none() => {
+            content.value()
+        }
+        _ => return Ok(false),
+    };
+    let plaintext = format!("{}\n", content.iter().format("\n"));
+    let message = cipher.encrypt(&plaintext)?;
+    let block = EncryptedBlock::new(message.trim_end());
+    headline.content = Some(Spanned::new(vec![HeadlineContentSet::Section(
+        Section::new(Spanned::new(vec![block.into()])),
+    )]));
+    Ok(true)
+}
+
+/// Decrypts the body of the headline and parses it.
+///
+/// Returns `false` if the body is not encrypted.
+pub fn decrypt_entry<C: Cipher>(
+    headline: &mut Headline,
+    cipher: &mut C,
+) -> Result<bool, DecryptError<C::Error>> {
+    let plaintext = match encrypted_block(headline) {
+        Some(block) => cipher
+            .decrypt(&block.message)
+            .map_err(DecryptError::Cipher)?,
+        None => return Ok(false),
+    };
+    let body = parse_document(&plaintext).map_err(DecryptError::Parse)?;
+    let mut content = Vec::new();
+    content.extend(body.preface.map(HeadlineContentSet::Section));
+    content.extend(
+        body.headlines
+            .into_iter()
+            .map(|headline| HeadlineContentSet::Headline(Box::new(headline))),
+    );
+    headline.content = Some(Spanned::new(content));
+    Ok(true)
+}
+
+/// Encrypts the body of all entries with the [`CRYPT_TAG`] (like `org-encrypt-entries`).
+///
+/// Returns the number of encrypted entries. Entries in the body of an encrypted entry are
+/// encrypted as part of it.
+pub fn encrypt_entries<C: Cipher>(
+    document: &mut Document,
+    cipher: &mut C,
+) -> Result<usize, C::Error> {
+    fn encrypt<C: Cipher>(headline: &mut Headline, cipher: &mut C) -> Result<usize, C::Error> {
+        if is_crypt_entry(headline) {
+            return encrypt_entry(headline, cipher).map(|encrypted| encrypted as usize);
+        }
+        let mut encrypted = 0;
+        for child in headline.children_mut() {
+            encrypted += encrypt(child, cipher)?;
+        }
+        Ok(encrypted)
+    }
+
+    let mut encrypted = 0;
+    for headline in &mut document.headlines {
+        encrypted += encrypt(headline, cipher)?;
+    }
+    Ok(encrypted)
+}
+
+/// Decrypts the body of all encrypted entries (like `org-decrypt-entries`).
+///
+/// Returns the number of decrypted entries (including entries that were encrypted inside the
+/// body of another encrypted entry).
+pub fn decrypt_entries<C: Cipher>(
+    document: &mut Document,
+    cipher: &mut C,
+) -> Result<usize, DecryptError<C::Error>> {
+    fn decrypt<C: Cipher>(
+        headline: &mut Headline,
+        cipher: &mut C,
+    ) -> Result<usize, DecryptError<C::Error>> {
+        let mut decrypted = decrypt_entry(headline, cipher)? as usize;
+        for child in headline.children_mut() {
+            decrypted += decrypt(child, cipher)?;
+        }
+        Ok(decrypted)
+    }
+
+    let mut decrypted = 0;
+    for headline in &mut document.headlines {
+        decrypted += decrypt(headline, cipher)?;
+    }
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "Encrypts" by reversing the lines of the plaintext.
+    struct Reverse;
+
+    impl Cipher for Reverse {
+        type Error = ();
+
+        fn encrypt(&mut self, plaintext: &str) -> Result<String, ()> {
+            Ok(format!(
+                "-----BEGIN PGP MESSAGE-----\n\n{}\n-----END PGP MESSAGE-----\n",
+                plaintext.lines().rev().format("\n")
+            ))
+        }
+
+        fn decrypt(&mut self, message: &str) -> Result<String, ()> {
+            let lines: Vec<_> = message.lines().collect();
+            let ciphertext = &lines[2..lines.len() - 1];
+            Ok(format!("{}\n", ciphertext.iter().rev().format("\n")))
+        }
+    }
+
+    #[test]
+    fn crypt_entries() {
+        let plaintext = "* Secrets :crypt:\nThe /password/.\n** Child\nMore.\n* Public\nText.\n";
+        let mut document = parse_document(plaintext).unwrap();
+
+        assert_eq!(encrypt_entries(&mut document, &mut Reverse), Ok(1));
+        let encrypted = document.to_string();
+        assert_eq!(
+            encrypted,
+            "* Secrets :crypt:\n-----BEGIN PGP MESSAGE-----\n\nMore.\n** Child\nThe /password/.\n\
+             -----END PGP MESSAGE-----\n* Public\nText.\n"
+        );
+        assert_eq!(encrypt_entries(&mut document, &mut Reverse), Ok(0));
+
+        // the message is kept verbatim when parsing
+        let mut document = parse_document(&encrypted).unwrap();
+        assert!(encrypted_block(&document.headlines[0]).is_some());
+        assert_eq!(document.to_string(), encrypted);
+
+        assert_eq!(decrypt_entries(&mut document, &mut Reverse).unwrap(), 1);
+        assert_eq!(document.to_string(), plaintext);
+    }
+
+    #[test]
+    fn find_encrypted_entries() {
+        let message = "-----BEGIN PGP MESSAGE-----\n\nabc\n-----END PGP MESSAGE-----\n";
+        let text = format!(
+            "* A :crypt:\n{0}* B :crypt:\nNot encrypted yet.\n* C\n** D\n{0}",
+            message
+        );
+        let document = parse_document(&text).unwrap();
+        let titles: Vec<_> = encrypted_entries(&document)
+            .into_iter()
+            .map(|headline| headline.title.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(titles, ["A", "D"]);
+    }
+
+    #[test]
+    fn display_decrypt_errors() {
+        let error: DecryptError<&str> = DecryptError::Cipher("bad passphrase");
+        assert_eq!(error.to_string(), "can't decrypt the entry: bad passphrase");
+        let error: DecryptError<&str> =
+            DecryptError::Parse(ParseError::StructuralError("a headline"));
+        assert_eq!(
+            error.to_string(),
+            "can't parse the decrypted entry (expected a headline)"
+        );
+    }
+}
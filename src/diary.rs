@@ -0,0 +1,485 @@
+//! Evaluating the common diary sexps of Emacs' calendar.
+//!
+//! Org mode evaluates diary sexps (in [`DiarySexp`] elements like `%%(diary-float t 4 4)` and
+//! timestamps like `<%%(diary-anniversary 10 12 1980)>`) with Emacs lisp to decide on which
+//! dates they appear in the agenda. [`DiaryExpr`] interprets the most common ones:
+//!
+//! - `(diary-anniversary MONTH DAY &optional YEAR)`: every year on the date (after `YEAR`).
+//!   February 29 is on March 1 in other years.
+//! - `(diary-float MONTH DAYNAME N &optional DAY)`: the `N`th `DAYNAME` (0 is Sunday) of the
+//!   months. A negative `N` counts from the end of the month. With `DAY` the days are counted
+//!   from (or back to) that day of the month.
+//! - `(diary-block M1 D1 Y1 M2 D2 Y2)`: all dates from the first to the second date.
+//! - `(diary-date MONTH DAY YEAR)`: the dates matching all fields.
+//!
+//! `MONTH`, `DAY` and `YEAR` of `diary-float` and `diary-date` can be a number, a list of
+//! numbers (`'(1 7)`) or `t` for any value. The order of the date arguments depends on the
+//! [`DateStyle`]. A trailing `MARK` argument is ignored.
+//!
+//! [`DiarySexp`]: `crate::types::elements::DiarySexp`
+
+use chrono::{Datelike, Duration, NaiveDate};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// The order of the date arguments (like `calendar-date-style`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DateStyle {
+    /// `MONTH DAY YEAR` (the default of Emacs).
+    #[default]
+    American,
+    /// `DAY MONTH YEAR`.
+    European,
+    /// `YEAR MONTH DAY`.
+    Iso,
+}
+
+/// A field of a date pattern: `t`, a number or a list of numbers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DateField {
+    Any,
+    Values(Vec<i32>),
+}
+
+impl DateField {
+    pub fn matches(&self, value: i32) -> bool {
+        match self {
+            DateField::Any => true,
+            DateField::Values(values) => values.contains(&value),
+        }
+    }
+}
+
+/// A supported diary sexp.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiaryExpr {
+    Anniversary {
+        month: u32,
+        day: u32,
+        year: Option<i32>,
+    },
+    Float {
+        months: DateField,
+        /// The day of the week (0 is Sunday).
+        weekday: u32,
+        n: i32,
+        day: Option<u32>,
+    },
+    Block {
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+    Date {
+        month: DateField,
+        day: DateField,
+        year: DateField,
+    },
+}
+
+/// The error returned when a diary sexp can't be interpreted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiaryError {
+    /// The sexp is not a list or has invalid arguments.
+    Syntax(String),
+    /// The function is not one of the supported functions.
+    Unsupported(String),
+}
+
+impl fmt::Display for DiaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiaryError::Syntax(message) => write!(f, "invalid diary sexp: {}", message),
+            DiaryError::Unsupported(function) => {
+                write!(f, "unsupported diary sexp function `{}`", function)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiaryError {}
+
+impl DiaryExpr {
+    /// Parses the sexp with the given order of the date arguments.
+    ///
+    /// The outer parentheses can be left out (like in `<%%(diary-float t 4 4)>` timestamps).
+    pub fn parse_with_style(s: &str, style: DateStyle) -> Result<Self, DiaryError> {
+        let s = s.trim();
+        let s = if s.starts_with('(') {
+            s.to_string()
+        } else {
+            format!("({})", s)
+        };
+        let mut tokens = tokenize(&s).into_iter().peekable();
+        let sexp = read(&mut tokens)?;
+        if let Some(token) = tokens.next() {
+            return Err(DiaryError::Syntax(format!("unexpected `{}`", token)));
+        }
+        let items = match sexp {
+            Sexp::List(items) => items,
+            Sexp::Atom(atom) => return Err(DiaryError::Syntax(format!("`{}` is no list", atom))),
+        };
+        let (function, args) = match items.split_first() {
+            Some((Sexp::Atom(function), args)) => (function.as_str(), args),
+            _ => return Err(DiaryError::Syntax("missing function".to_string())),
+        };
+        // the arguments in the order month, day, year
+        let date = |args: &[Sexp]| -> Result<[DateField; 3], DiaryError> {
+            if args.len() < 3 {
+                return Err(DiaryError::Syntax(format!("{} needs a date", function)));
+            }
+            let fields = [field(&args[0])?, field(&args[1])?, field(&args[2])?];
+            let [a, b, c] = fields;
+            Ok(match style {
+                DateStyle::American => [a, b, c],
+                DateStyle::European => [b, a, c],
+                DateStyle::Iso => [b, c, a],
+            })
+        };
+        let number = |field: &DateField| match field {
+            DateField::Values(values) if values.len() == 1 => Ok(values[0]),
+            _ => Err(DiaryError::Syntax(format!("{} needs numbers", function))),
+        };
+        match function {
+            "diary-anniversary" => {
+                let mut args = args.to_vec();
+                // a missing year is `nil`
+                if args.len() == 2 {
+                    let position = if style == DateStyle::Iso { 0 } else { 2 };
+                    args.insert(position, Sexp::Atom("nil".to_string()));
+                }
+                let [month, day, year] = date(&args)?;
+                Ok(DiaryExpr::Anniversary {
+                    month: number(&month)? as u32,
+                    day: number(&day)? as u32,
+                    year: match year {
+                        DateField::Any => None,
+                        year => Some(number(&year)?),
+                    },
+                })
+            }
+            "diary-float" => {
+                if args.len() < 3 {
+                    return Err(DiaryError::Syntax(
+                        "diary-float needs 3 arguments".to_string(),
+                    ));
+                }
+                let n = number(&field(&args[2])?)?;
+                let weekday = number(&field(&args[1])?)?;
+                if n == 0 || !(0..=6).contains(&weekday) {
+                    return Err(DiaryError::Syntax("invalid diary-float".to_string()));
+                }
+                let day = match args.get(3).map(field).transpose()? {
+                    None | Some(DateField::Any) => None,
+                    Some(day) => Some(number(&day)? as u32),
+                };
+                Ok(DiaryExpr::Float {
+                    months: field(&args[0])?,
+                    weekday: weekday as u32,
+                    n,
+                    day,
+                })
+            }
+            "diary-block" => {
+                let to_date = |fields: [DateField; 3]| {
+                    let [month, day, year] = fields;
+                    let (year, month, day) = (number(&year)?, number(&month)?, number(&day)?);
+                    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+                        .ok_or_else(|| DiaryError::Syntax("invalid date".to_string()))
+                };
+                if args.len() < 6 {
+                    return Err(DiaryError::Syntax("diary-block needs 2 dates".to_string()));
+                }
+                Ok(DiaryExpr::Block {
+                    start: to_date(date(&args[..3])?)?,
+                    end: to_date(date(&args[3..])?)?,
+                })
+            }
+            "diary-date" => {
+                let [month, day, year] = date(args)?;
+                Ok(DiaryExpr::Date { month, day, year })
+            }
+            function => Err(DiaryError::Unsupported(function.to_string())),
+        }
+    }
+
+    /// Returns `true` if the sexp matches the date.
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            DiaryExpr::Anniversary { month, day, year } => {
+                if year.is_some_and(|year| date.year() <= year) {
+                    return false;
+                }
+                let is_leap_year = NaiveDate::from_ymd_opt(date.year(), 2, 29).is_some();
+                let (month, day) = if (*month, *day) == (2, 29) && !is_leap_year {
+                    (3, 1)
+                } else {
+                    (*month, *day)
+                };
+                date.month() == month && date.day() == day
+            }
+            DiaryExpr::Float {
+                months,
+                weekday,
+                n,
+                day,
+            } => {
+                // the day can be in the month before or after the counted month
+                let month = date.year() * 12 + date.month0() as i32;
+                (month - 1..=month + 1).any(|month| {
+                    let (year, month) = (month.div_euclid(12), month.rem_euclid(12) as u32 + 1);
+                    months.matches(month as i32)
+                        && nth_weekday(year, month, *weekday, *n, *day) == Some(date)
+                })
+            }
+            DiaryExpr::Block { start, end } => *start <= date && date <= *end,
+            DiaryExpr::Date { month, day, year } => {
+                month.matches(date.month() as i32)
+                    && day.matches(date.day() as i32)
+                    && year.matches(date.year())
+            }
+        }
+    }
+
+    /// Returns the dates in the range the sexp matches.
+    pub fn occurrences_between(&self, range: RangeInclusive<NaiveDate>) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let mut date = *range.start();
+        while date <= *range.end() {
+            if self.matches(date) {
+                dates.push(date);
+            }
+            date = date.succ();
+        }
+        dates
+    }
+}
+
+impl FromStr for DiaryExpr {
+    type Err = DiaryError;
+
+    /// Parses the sexp with [`DateStyle::American`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiaryExpr::parse_with_style(s, DateStyle::default())
+    }
+}
+
+/// Returns the `n`th weekday (0 is Sunday) on or after `day` (default: the first day) of the
+/// month. A negative `n` counts back from `day` (default: the last day).
+fn nth_weekday(year: i32, month: u32, weekday: u32, n: i32, day: Option<u32>) -> Option<NaiveDate> {
+    let weekday = i64::from(weekday);
+    if n > 0 {
+        let start = NaiveDate::from_ymd_opt(year, month, day.unwrap_or(1))?;
+        let offset = (weekday - i64::from(start.weekday().num_days_from_sunday())).rem_euclid(7);
+        Some(start + Duration::days(offset + 7 * i64::from(n - 1)))
+    } else {
+        let start = match day {
+            Some(day) => NaiveDate::from_ymd_opt(year, month, day)?,
+            None => {
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                NaiveDate::from_ymd_opt(year, month, 1)?.pred()
+            }
+        };
+        let offset = (i64::from(start.weekday().num_days_from_sunday()) - weekday).rem_euclid(7);
+        Some(start - Duration::days(offset + 7 * i64::from(-n - 1)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+/// Splits the sexp into parentheses and atoms. Quotes are dropped.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut atom = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | '\'' => {
+                if !atom.is_empty() {
+                    tokens.push(std::mem::take(&mut atom));
+                }
+                if c != '\'' {
+                    tokens.push(c.to_string());
+                }
+            }
+            '"' => {
+                // strings (e.g. the mark) are kept as one atom
+                atom.push(c);
+                for c in chars.by_ref() {
+                    atom.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if !atom.is_empty() {
+                    tokens.push(std::mem::take(&mut atom));
+                }
+            }
+            c => atom.push(c),
+        }
+    }
+    if !atom.is_empty() {
+        tokens.push(atom);
+    }
+    tokens
+}
+
+fn read(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = String>>,
+) -> Result<Sexp, DiaryError> {
+    match tokens.next() {
+        Some(ref token) if token == "(" => {
+            let mut items = Vec::new();
+            loop {
+                match tokens.peek().map(String::as_str) {
+                    Some(")") => {
+                        tokens.next();
+                        return Ok(Sexp::List(items));
+                    }
+                    Some(_) => items.push(read(tokens)?),
+                    None => return Err(DiaryError::Syntax("unclosed list".to_string())),
+                }
+            }
+        }
+        Some(ref token) if token == ")" => Err(DiaryError::Syntax("unexpected `)`".to_string())),
+        Some(token) => Ok(Sexp::Atom(token)),
+        None => Err(DiaryError::Syntax("empty sexp".to_string())),
+    }
+}
+
+/// Reads a date field: `t` or `nil`, a number or a list of numbers.
+fn field(sexp: &Sexp) -> Result<DateField, DiaryError> {
+    let number = |atom: &str| {
+        atom.parse()
+            .map_err(|_| DiaryError::Syntax(format!("`{}` is no number", atom)))
+    };
+    match sexp {
+        Sexp::Atom(atom) if atom == "t" || atom == "nil" => Ok(DateField::Any),
+        Sexp::Atom(atom) => Ok(DateField::Values(vec![number(atom)?])),
+        Sexp::List(items) => items
+            .iter()
+            .map(|item| match item {
+                Sexp::Atom(atom) => number(atom),
+                Sexp::List(_) => Err(DiaryError::Syntax("nested list".to_string())),
+            })
+            .collect::<Result<_, _>>()
+            .map(DateField::Values),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::document::Document;
+    use crate::types::ElementSet;
+
+    fn dates(sexp: &str, start: (i32, u32, u32), end: (i32, u32, u32)) -> Vec<String> {
+        let start = NaiveDate::from_ymd(start.0, start.1, start.2);
+        let end = NaiveDate::from_ymd(end.0, end.1, end.2);
+        let expr: DiaryExpr = sexp.parse().unwrap();
+        expr.occurrences_between(start..=end)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn diary_float() {
+        // Thanksgiving and the last Monday in May
+        assert_eq!(
+            dates("diary-float 11 4 4", (2018, 1, 1), (2019, 12, 31)),
+            ["2018-11-22", "2019-11-28"]
+        );
+        assert_eq!(
+            dates("(diary-float '(5 6) 1 -1)", (2018, 1, 1), (2018, 12, 31)),
+            ["2018-05-28", "2018-06-25"]
+        );
+    }
+
+    #[test]
+    fn diary_float_after_day() {
+        // the first Tuesday after the 29th can be in the next month
+        assert_eq!(
+            dates("diary-float t 2 1 29", (2018, 10, 1), (2018, 11, 30)),
+            ["2018-10-02", "2018-10-30"]
+        );
+    }
+
+    #[test]
+    fn diary_anniversary() {
+        assert_eq!(
+            dates("diary-anniversary 2 29 2016", (2016, 1, 1), (2019, 12, 31)),
+            ["2017-03-01", "2018-03-01", "2019-03-01"]
+        );
+    }
+
+    #[test]
+    fn diary_block() {
+        assert_eq!(
+            dates(
+                "diary-block 12 30 2018 1 2 2019 \"mark\"",
+                (2018, 1, 1),
+                (2019, 12, 31)
+            ),
+            ["2018-12-30", "2018-12-31", "2019-01-01", "2019-01-02"]
+        );
+    }
+
+    #[test]
+    fn diary_date() {
+        assert_eq!(
+            dates("diary-date t '(1 15) 2018", (2018, 1, 1), (2018, 2, 28)),
+            ["2018-01-01", "2018-01-15", "2018-02-01", "2018-02-15"]
+        );
+    }
+
+    #[test]
+    fn iso_date_style() {
+        let iso = DiaryExpr::parse_with_style("diary-anniversary 1980 10 12", DateStyle::Iso);
+        assert_eq!(
+            iso,
+            Ok(DiaryExpr::Anniversary {
+                month: 10,
+                day: 12,
+                year: Some(1980)
+            })
+        );
+    }
+
+    #[test]
+    fn unsupported_sexp() {
+        assert_eq!(
+            "diary-cyclic 7 1 1 2018".parse::<DiaryExpr>(),
+            Err(DiaryError::Unsupported("diary-cyclic".to_string()))
+        );
+    }
+
+    #[test]
+    fn unbalanced_sexp() {
+        assert!("(diary-date 1 2".parse::<DiaryExpr>().is_err());
+    }
+
+    #[test]
+    fn parse_diary_sexp_element() {
+        let text = "%%(diary-anniversary 10 12 (+ 1980 0)) Birthday\n";
+        let document = Document::parse(text).unwrap();
+        assert_eq!(document.to_string(), text);
+        match &document.preface.unwrap().content.value()[0] {
+            ElementSet::DiarySexp(sexp) => {
+                assert_eq!(sexp.sexp(), "diary-anniversary 10 12 (+ 1980 0)");
+                assert_eq!(sexp.text(), "Birthday");
+            }
+            element => panic!("expected a diary sexp, got {:?}", element),
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! Human readable summaries of the changes between two versions of a document.
+//!
+//! [`summarize`] compares two [`Document`]s headline by headline and describes what changed
+//! (e.g. `title changed from "a" to "b"`, `deadline moved from <...> to <...>` or
+//! `2 paragraphs added`). This is intended for sync conflict dialogs and commit messages, not
+//! for patching documents.
+
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, HeadlineContentSet, Section, TodoKeyword};
+use crate::types::objects::Timestamp;
+use crate::types::{Element, ElementKind, ElementSet, Parent};
+
+use itertools::Itertools;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// Summarizes the changes between two versions of a document.
+///
+/// Headlines are matched by their title (on the same level below the same parent). Headlines
+/// that can't be matched by title are matched by their position and reported as renamed. Only
+/// headlines with changes are returned. The changes of the preface are returned with an empty
+/// [`HeadlineSummary::path`].
+pub fn summarize(old: &Document, new: &Document) -> Vec<HeadlineSummary> {
+    let mut summaries = Vec::new();
+
+    let mut preface = Vec::new();
+    diff_sections(old.preface.as_ref(), new.preface.as_ref(), &mut preface);
+    if !preface.is_empty() {
+        summaries.push(HeadlineSummary {
+            path: Vec::new(),
+            changes: preface,
+        });
+    }
+
+    let old: Vec<_> = old.headlines.iter().collect();
+    let new: Vec<_> = new.headlines.iter().collect();
+    diff_headline_lists(&[], &old, &new, &mut summaries);
+
+    summaries
+}
+
+/// The changes of a single headline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeadlineSummary {
+    /// The titles of all ancestors and the (new) title of the headline itself.
+    ///
+    /// This is empty for changes to the preface of the document.
+    pub path: Vec<String>,
+    pub changes: Vec<Change>,
+}
+
+impl fmt::Display for HeadlineSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(preface)")?;
+        } else {
+            write!(f, "{}", self.path.iter().format(" / "))?;
+        }
+        write!(f, ": {}", self.changes.iter().format(", "))
+    }
+}
+
+/// A single change of a headline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Change {
+    /// The headline (including its content) was added.
+    Added,
+    /// The headline (including its content) was removed.
+    Removed,
+    TitleChanged {
+        old: String,
+        new: String,
+    },
+    TodoKeywordChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    PriorityChanged {
+        old: Option<char>,
+        new: Option<char>,
+    },
+    TagsChanged {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    PlanningChanged {
+        keyword: PlanningKeyword,
+        old: Option<Timestamp>,
+        new: Option<Timestamp>,
+    },
+    /// Elements of the given kind were added to the section.
+    ElementsAdded {
+        kind: ElementKind,
+        count: usize,
+    },
+    /// Elements of the given kind were removed from the section.
+    ElementsRemoved {
+        kind: ElementKind,
+        count: usize,
+    },
+    /// The text of the section changed.
+    ///
+    /// The words are compared as multisets so words that only moved are not counted.
+    TextChanged {
+        words_added: usize,
+        words_removed: usize,
+        characters_added: usize,
+        characters_removed: usize,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added => write!(f, "added"),
+            Change::Removed => write!(f, "removed"),
+            Change::TitleChanged { old, new } => {
+                write!(f, "title changed from {:?} to {:?}", old, new)
+            }
+            Change::TodoKeywordChanged { old, new } => match (old, new) {
+                (Some(old), Some(new)) => write!(f, "state changed from {} to {}", old, new),
+                (None, Some(new)) => write!(f, "state set to {}", new),
+                (Some(old), None) => write!(f, "state {} removed", old),
+                (None, None) => write!(f, "state unchanged"),
+            },
+            Change::PriorityChanged { old, new } => match (old, new) {
+                (Some(old), Some(new)) => write!(f, "priority changed from {} to {}", old, new),
+                (None, Some(new)) => write!(f, "priority set to {}", new),
+                (Some(old), None) => write!(f, "priority {} removed", old),
+                (None, None) => write!(f, "priority unchanged"),
+            },
+            Change::TagsChanged { added, removed } => {
+                let tags = added
+                    .iter()
+                    .map(|tag| format!("+{}", tag))
+                    .chain(removed.iter().map(|tag| format!("-{}", tag)));
+                write!(f, "tags changed ({})", tags.format(" "))
+            }
+            Change::PlanningChanged { keyword, old, new } => match (old, new) {
+                (Some(old), Some(new)) => write!(f, "{} moved from {} to {}", keyword, old, new),
+                (None, Some(new)) => write!(f, "{} set to {}", keyword, new),
+                (Some(old), None) => write!(f, "{} {} removed", keyword, old),
+                (None, None) => write!(f, "{} unchanged", keyword),
+            },
+            Change::ElementsAdded { kind, count } => {
+                write!(f, "{} {} added", count, plural(&kind_name(*kind), *count))
+            }
+            Change::ElementsRemoved { kind, count } => {
+                write!(f, "{} {} removed", count, plural(&kind_name(*kind), *count))
+            }
+            Change::TextChanged {
+                words_added,
+                words_removed,
+                characters_added,
+                characters_removed,
+            } => write!(
+                f,
+                "{} {} added ({} characters), {} {} removed ({} characters)",
+                words_added,
+                plural("word", *words_added),
+                characters_added,
+                words_removed,
+                plural("word", *words_removed),
+                characters_removed
+            ),
+        }
+    }
+}
+
+/// The keyword of a timestamp in a [`Planning`](`crate::types::elements::Planning`) line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlanningKeyword {
+    Closed,
+    Deadline,
+    Scheduled,
+}
+
+impl fmt::Display for PlanningKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanningKeyword::Closed => write!(f, "closed"),
+            PlanningKeyword::Deadline => write!(f, "deadline"),
+            PlanningKeyword::Scheduled => write!(f, "scheduled"),
+        }
+    }
+}
+
+/// A human readable name of the kind of an element (e.g. `src block`).
+fn kind_name(kind: ElementKind) -> String {
+    kind.name().replace('-', " ")
+}
+
+fn plural(word: &str, count: usize) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+/// Matches the headlines of two lists and diffs the matching pairs.
+fn diff_headline_lists(
+    path: &[String],
+    old: &[&Headline],
+    new: &[&Headline],
+    summaries: &mut Vec<HeadlineSummary>,
+) {
+    let mut matched_old = vec![false; old.len()];
+    let mut matches: Vec<Option<usize>> = new
+        .iter()
+        .map(|new| {
+            let index = (0..old.len()).find(|&i| !matched_old[i] && title(old[i]) == title(new))?;
+            matched_old[index] = true;
+            Some(index)
+        })
+        .collect();
+
+    // Pair the remaining headlines by position. These are most likely renamed.
+    let mut unmatched_old = (0..old.len())
+        .filter(|&i| !matched_old[i])
+        .collect::<Vec<_>>();
+    unmatched_old.reverse();
+    for m in matches.iter_mut().filter(|m| m.is_none()) {
+        *m = unmatched_old.pop();
+    }
+
+    for (new, m) in new.iter().zip(matches) {
+        let mut path = path.to_vec();
+        path.push(title(new));
+        match m {
+            Some(index) => diff_headlines(path, old[index], new, summaries),
+            None => summaries.push(HeadlineSummary {
+                path,
+                changes: vec![Change::Added],
+            }),
+        }
+    }
+
+    unmatched_old.reverse();
+    for index in unmatched_old {
+        let mut path = path.to_vec();
+        path.push(title(old[index]));
+        summaries.push(HeadlineSummary {
+            path,
+            changes: vec![Change::Removed],
+        });
+    }
+}
+
+fn diff_headlines(
+    path: Vec<String>,
+    old: &Headline,
+    new: &Headline,
+    summaries: &mut Vec<HeadlineSummary>,
+) {
+    let mut changes = Vec::new();
+
+    if title(old) != title(new) {
+        changes.push(Change::TitleChanged {
+            old: title(old),
+            new: title(new),
+        });
+    }
+    if old.todo_keyword != new.todo_keyword {
+        changes.push(Change::TodoKeywordChanged {
+            old: old.todo_keyword.as_ref().map(TodoKeyword::to_string),
+            new: new.todo_keyword.as_ref().map(TodoKeyword::to_string),
+        });
+    }
+    if old.priority != new.priority {
+        changes.push(Change::PriorityChanged {
+            old: old.priority,
+            new: new.priority,
+        });
+    }
+    let added: Vec<_> = new
+        .tags
+        .iter()
+        .filter(|tag| !old.tags.contains(tag))
+        .cloned()
+        .collect();
+    let removed: Vec<_> = old
+        .tags
+        .iter()
+        .filter(|tag| !new.tags.contains(tag))
+        .cloned()
+        .collect();
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(Change::TagsChanged { added, removed });
+    }
+
+    let keywords = [
+        PlanningKeyword::Closed,
+        PlanningKeyword::Deadline,
+        PlanningKeyword::Scheduled,
+    ];
+    for &keyword in &keywords {
+        let old = planning_timestamp(old, keyword);
+        let new = planning_timestamp(new, keyword);
+        if old != new {
+            changes.push(Change::PlanningChanged {
+                keyword,
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+
+    diff_sections(section(old), section(new), &mut changes);
+
+    if !changes.is_empty() {
+        summaries.push(HeadlineSummary {
+            path: path.clone(),
+            changes,
+        });
+    }
+
+    let old_children: Vec<_> = old.children().collect();
+    let new_children: Vec<_> = new.children().collect();
+    diff_headline_lists(&path, &old_children, &new_children, summaries);
+}
+
+/// Compares the number of elements of every kind and the words of two sections.
+fn diff_sections(old: Option<&Section>, new: Option<&Section>, changes: &mut Vec<Change>) {
+    let old = elements(old);
+    let new = elements(new);
+
+    let old_kinds = count(old.iter().map(Element::kind));
+    let new_kinds = count(new.iter().map(Element::kind));
+    for kind in new.iter().chain(old).map(Element::kind).unique() {
+        let old_count = old_kinds.get(&kind).cloned().unwrap_or(0);
+        let new_count = new_kinds.get(&kind).cloned().unwrap_or(0);
+        if new_count > old_count {
+            changes.push(Change::ElementsAdded {
+                kind,
+                count: new_count - old_count,
+            });
+        } else if old_count > new_count {
+            changes.push(Change::ElementsRemoved {
+                kind,
+                count: old_count - new_count,
+            });
+        }
+    }
+
+    let old_text = old.iter().join("\n");
+    let new_text = new.iter().join("\n");
+    let old_words = count(old_text.split_whitespace());
+    let new_words = count(new_text.split_whitespace());
+    let (words_added, characters_added) = difference(&new_words, &old_words);
+    let (words_removed, characters_removed) = difference(&old_words, &new_words);
+    if words_added > 0 || words_removed > 0 {
+        changes.push(Change::TextChanged {
+            words_added,
+            words_removed,
+            characters_added,
+            characters_removed,
+        });
+    }
+}
+
+fn count<T: Eq + Hash>(items: impl Iterator<Item = T>) -> HashMap<T, usize> {
+    let mut counts = HashMap::new();
+    for item in items {
+        *counts.entry(item).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Returns the number of words and characters in `a` that are not in `b`.
+fn difference(a: &HashMap<&str, usize>, b: &HashMap<&str, usize>) -> (usize, usize) {
+    a.iter()
+        .map(|(word, &count)| {
+            let missing = count.saturating_sub(b.get(word).cloned().unwrap_or(0));
+            (missing, missing * word.chars().count())
+        })
+        .fold((0, 0), |(words, chars), (w, c)| (words + w, chars + c))
+}
+
+fn title(headline: &Headline) -> String {
+    headline
+        .title
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+fn planning_timestamp(headline: &Headline, keyword: PlanningKeyword) -> Option<&Timestamp> {
+    let planning = headline.planning.as_ref()?;
+    match keyword {
+        PlanningKeyword::Closed => planning.closed.as_ref(),
+        PlanningKeyword::Deadline => planning.deadline.as_ref(),
+        PlanningKeyword::Scheduled => planning.scheduled.as_ref(),
+    }
+}
+
+fn section(headline: &Headline) -> Option<&Section> {
+    headline
+        .content()
+        .into_iter()
+        .flat_map(|content| content.value())
+        .filter_map(|content| match content {
+            HeadlineContentSet::Section(section) => Some(section),
+            HeadlineContentSet::Headline(_) => None,
+        })
+        .next()
+}
+
+fn elements(section: Option<&Section>) -> &[ElementSet] {
+    section
+        .and_then(|section| section.content())
+        .map(|content| content.value().as_slice())
+        .unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the summaries of the changes between the two org texts.
+    fn summaries(old: &str, new: &str) -> Vec<String> {
+        let old = Document::parse(old).unwrap();
+        let new = Document::parse(new).unwrap();
+        summarize(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn unchanged() {
+        let text = "Intro\n* a\ntext\n** b\n";
+        assert_eq!(summaries(text, text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn added() {
+        assert_eq!(summaries("* a\n", "* a\n* b\n"), ["b: added"]);
+    }
+
+    #[test]
+    fn removed() {
+        assert_eq!(summaries("* a\n* b\n", "* b\n"), ["a: removed"]);
+    }
+
+    #[test]
+    fn title_changed() {
+        assert_eq!(
+            summaries("* a\n* b\n", "* a\n* c\n"),
+            ["c: title changed from \"b\" to \"c\""]
+        );
+    }
+
+    #[test]
+    fn todo_keyword_changed() {
+        assert_eq!(
+            summaries("* TODO a\n", "* DONE a\n"),
+            ["a: state changed from TODO to DONE"]
+        );
+    }
+
+    #[test]
+    fn priority_changed() {
+        assert_eq!(summaries("* [#A] a\n", "* a\n"), ["a: priority A removed"]);
+    }
+
+    #[test]
+    fn tags_changed() {
+        assert_eq!(
+            summaries("* a :work:old:\n", "* a :work:new:\n"),
+            ["a: tags changed (+new -old)"]
+        );
+    }
+
+    #[test]
+    fn planning_changed() {
+        assert_eq!(
+            summaries(
+                "* a\nDEADLINE: <2018-10-12 Fri>\n",
+                "* a\nDEADLINE: <2018-10-15 Mon> SCHEDULED: <2018-10-08 Mon>\n",
+            ),
+            [
+                "a: deadline moved from <2018-10-12 Fri> to <2018-10-15 Mon>, \
+                 scheduled set to <2018-10-08 Mon>"
+            ]
+        );
+    }
+
+    #[test]
+    fn elements_changed() {
+        assert_eq!(
+            summaries("* a\n| x |\n", "* a\n#+BEGIN_SRC sh\nx\n#+END_SRC\n"),
+            ["a: 1 src block added, 1 table removed, \
+              3 words added (22 characters), 2 words removed (2 characters)"]
+        );
+    }
+
+    #[test]
+    fn text_changed() {
+        assert_eq!(
+            summaries("* a\none two\n", "* a\ntwo three\n"),
+            ["a: 1 word added (5 characters), 1 word removed (3 characters)"]
+        );
+    }
+
+    #[test]
+    fn preface_changed() {
+        assert_eq!(
+            summaries("Intro\n* a\n", "* a\n"),
+            [
+                "(preface): 1 paragraph removed, 0 words added (0 characters), \
+                 1 word removed (5 characters)"
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_headline_changed() {
+        assert_eq!(
+            summaries("* a\n** b\n", "* a\n** DONE b\n"),
+            ["a / b: state set to DONE"]
+        );
+    }
+}
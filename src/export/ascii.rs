@@ -0,0 +1,619 @@
+//! Exports documents to readable plain text (like the ASCII backend of org mode), e.g. for
+//! emails.
+//!
+//! Paragraphs are filled to [`AsciiOptions::width`]. Entities are replaced by their ASCII
+//! replacement. Text markup keeps the org markers (e.g. `*bold*`), code and verbatim text is
+//! quoted with backticks. Headlines are underlined (`=` on the first level, `-` on the second
+//! and `~` on the third) and can be numbered. Footnote references become `[1]` and the
+//! footnotes are written at the end of the document or after the section they are referenced
+//! in.
+//!
+//! Drawers, planning lines, clocks, comments and keywords are left out, like commented
+//! subtrees. Raw output for the `ascii` backend ([`text::BACKENDS`]) is included.
+//!
+//! [`text::BACKENDS`]: `super::text::BACKENDS`
+
+use super::ExportContext;
+use crate::entities::ORG_ENTITIES;
+use crate::parsing::{parse_document, parse_objects};
+use crate::types::document::Document;
+use crate::types::greater_elements::{
+    Checkbox, Headline, HeadlineContentSet, Item, ItemKind, PlainList, SubtreeFilter, Table,
+    TableContent, TableRowKind, TodoKeyword,
+};
+use crate::types::index::{DocumentIndex, FootnoteDefinitionRef};
+use crate::types::objects::{FootnoteReference, LinkFormat, LinkPath, TextMarkupKind};
+use crate::types::{ElementSet, Parent, Spanned, StandardSet};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::mem;
+
+/// Marks a line break (`\\`) in the text of a paragraph until it is filled.
+const LINE_BREAK: char = '\u{2028}';
+
+/// The indentation of the content of quote blocks.
+const QUOTE_MARGIN: usize = 4;
+
+/// The options of the ASCII export.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AsciiOptions {
+    /// The maximum length of the lines of paragraphs (`org-ascii-text-width`).
+    pub width: usize,
+    pub headline_style: HeadlineStyle,
+    pub footnotes: FootnotePlacement,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        AsciiOptions {
+            width: 72,
+            headline_style: HeadlineStyle::Underlined,
+            footnotes: FootnotePlacement::EndOfDocument,
+        }
+    }
+}
+
+/// How headlines are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeadlineStyle {
+    /// The title underlined depending on the level. Headlines below the third level are not
+    /// underlined.
+    Underlined,
+    /// Like [`Underlined`][HeadlineStyle::Underlined] with the number of the headline in front
+    /// of the title (e.g. `1.2 Title`).
+    Numbered,
+}
+
+/// Where the footnotes are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FootnotePlacement {
+    /// In a `Footnotes` section at the end of the document (like org mode).
+    EndOfDocument,
+    /// After the section of the headline (or the preface) they are first referenced in.
+    EndOfSection,
+}
+
+/// Exports the document to plain text.
+pub fn export(
+    document: &Document,
+    options: &AsciiOptions,
+    context: &mut ExportContext<'_>,
+) -> String {
+    let mut exporter = Exporter {
+        index: document.index(),
+        options,
+        context,
+        numbers: HashMap::new(),
+        count: 0,
+        notes: Vec::new(),
+    };
+    let mut blocks = Vec::new();
+    if let Some(keyword) = document
+        .keywords()
+        .find(|keyword| keyword.key.eq_ignore_ascii_case("TITLE"))
+    {
+        let objects: Vec<StandardSet> = keyword
+            .value
+            .value()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect();
+        let title = exporter.objects(&objects).trim().to_string();
+        let underline = "=".repeat(title.chars().count());
+        blocks.push(center(&format!("{}\n{}", title, underline), options.width));
+    }
+    if let Some(preface) = &document.preface {
+        blocks.push(exporter.elements(children(preface), options.width));
+        exporter.section_footnotes(&mut blocks);
+    }
+    exporter.headlines(&document.headlines, &[], &mut blocks);
+    if !exporter.notes.is_empty() {
+        blocks.push("Footnotes\n=========".to_string());
+        blocks.extend(exporter.footnotes());
+    }
+    let mut text = blocks.iter().filter(|block| !block.is_empty()).join("\n\n");
+    text.push('\n');
+    text
+}
+
+/// The content of a footnote.
+#[derive(Debug, Clone)]
+enum Note {
+    Elements(Vec<ElementSet>),
+    Objects(Vec<StandardSet>),
+}
+
+fn children<P: Parent<Vec<ElementSet>>>(parent: &P) -> &[ElementSet] {
+    parent.content().map_or(&[], |content| content.value())
+}
+
+/// Fills the text into lines of at most `width` characters (unless a word is longer).
+///
+/// All whitespace is collapsed. Line breaks start a new line.
+fn fill(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for part in text.split(LINE_BREAK) {
+        let mut line = String::new();
+        for word in part.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    while lines.len() > 1 && lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Puts `head` in front of the first line and indents the other lines by its length.
+fn hang(head: &str, body: &str) -> String {
+    let indentation = " ".repeat(head.chars().count());
+    body.lines()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => format!("{}{}", head, line).trim_end().to_string(),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{}{}", indentation, line),
+        })
+        .join("\n")
+}
+
+fn center(text: &str, width: usize) -> String {
+    text.lines()
+        .map(|line| {
+            let padding = width.saturating_sub(line.chars().count()) / 2;
+            format!("{}{}", " ".repeat(padding), line)
+        })
+        .join("\n")
+}
+
+struct Exporter<'a, 'c> {
+    /// Used to find the definitions of footnotes.
+    index: DocumentIndex<'a>,
+    options: &'a AsciiOptions,
+    context: &'a mut ExportContext<'c>,
+    /// The numbers of the labeled footnotes.
+    numbers: HashMap<String, usize>,
+    /// The number of footnotes.
+    count: usize,
+    /// The footnotes which are not written yet.
+    notes: Vec<(usize, Note)>,
+}
+
+impl<'a, 'c> Exporter<'a, 'c> {
+    /// Exports the headlines which have the given number (e.g. `[1, 2]`) as a prefix.
+    fn headlines<'h>(
+        &mut self,
+        headlines: impl IntoIterator<Item = &'h Headline>,
+        number: &[usize],
+        blocks: &mut Vec<String>,
+    ) {
+        let filter = SubtreeFilter {
+            include_commented: false,
+            include_archived: true,
+        };
+        let headlines = headlines
+            .into_iter()
+            .filter(|headline| !filter.skips(headline));
+        for (index, headline) in headlines.enumerate() {
+            let mut number = number.to_vec();
+            number.push(index + 1);
+            self.headline(headline, &number, blocks);
+        }
+    }
+
+    fn headline(&mut self, headline: &Headline, number: &[usize], blocks: &mut Vec<String>) {
+        let mut title = Vec::new();
+        if self.options.headline_style == HeadlineStyle::Numbered {
+            title.push(number.iter().join("."));
+        }
+        if let Some(TodoKeyword::Todo(keyword)) | Some(TodoKeyword::Done(keyword)) =
+            &headline.todo_keyword
+        {
+            title.push(keyword.clone());
+        }
+        if let Some(priority) = headline.priority {
+            title.push(format!("[#{}]", priority));
+        }
+        if let Some(objects) = &headline.title {
+            let objects: Vec<StandardSet> = objects.iter().cloned().map(Into::into).collect();
+            title.push(fill(&self.objects(&objects), usize::MAX));
+        }
+        if !headline.tags.is_empty() {
+            title.push(format!(":{}:", headline.tags.join(":")));
+        }
+        let title = title.join(" ");
+        let underline = match headline.level {
+            1 => Some('='),
+            2 => Some('-'),
+            3 => Some('~'),
+            _ => None,
+        };
+        blocks.push(match underline {
+            Some(c) => format!("{}\n{}", title, c.to_string().repeat(title.chars().count())),
+            None => title,
+        });
+
+        let mut child_headlines = Vec::new();
+        for content in headline.content().into_iter().flat_map(Spanned::value) {
+            match content {
+                HeadlineContentSet::Section(section) => {
+                    blocks.push(self.elements(children(section), self.options.width))
+                }
+                HeadlineContentSet::Headline(child) => child_headlines.push(&**child),
+            }
+        }
+        self.section_footnotes(blocks);
+        self.headlines(child_headlines, number, blocks);
+    }
+
+    /// Writes the footnotes referenced so far if they are placed after the sections.
+    fn section_footnotes(&mut self, blocks: &mut Vec<String>) {
+        if self.options.footnotes == FootnotePlacement::EndOfSection {
+            blocks.extend(self.footnotes());
+        }
+    }
+
+    /// Returns the footnotes which are not written yet (including the ones referenced in
+    /// them).
+    fn footnotes(&mut self) -> Vec<String> {
+        let mut blocks = Vec::new();
+        while !self.notes.is_empty() {
+            for (number, note) in mem::take(&mut self.notes) {
+                let label = format!("[{}] ", number);
+                let width = self.options.width.saturating_sub(label.len());
+                let body = match note {
+                    Note::Elements(elements) => self.elements(&elements, width),
+                    Note::Objects(objects) => fill(&self.objects(&objects), width),
+                };
+                blocks.push(hang(&label, &body));
+            }
+        }
+        blocks
+    }
+
+    /// Exports the elements separated by blank lines.
+    fn elements(&mut self, elements: &[ElementSet], width: usize) -> String {
+        elements
+            .iter()
+            .filter_map(|element| self.element(element, width))
+            .filter(|text| !text.is_empty())
+            .join("\n\n")
+    }
+
+    /// Exports the element to lines of at most `width` characters (except for blocks which are
+    /// written verbatim). Returns `None` for elements which are left out.
+    fn element(&mut self, element: &ElementSet, width: usize) -> Option<String> {
+        let verbatim = |value: &str| value.trim_end_matches('\n').to_string();
+        Some(match element {
+            ElementSet::Paragraph(paragraph) => {
+                fill(&self.objects(paragraph.content.iter()), width)
+            }
+            ElementSet::PlainList(list) => self.list(list, width),
+            ElementSet::Table(table) => self.table(table),
+            ElementSet::SrcBlock(block) => verbatim(&block.value),
+            ElementSet::ExampleBlock(block) => verbatim(&block.value),
+            ElementSet::FixedWidth(fixed_width) => verbatim(&fixed_width.value),
+            ElementSet::LatexEnvironment(latex) => verbatim(&latex.value),
+            ElementSet::ExportBlock(block) => verbatim(self.context.block(block)?),
+            ElementSet::HorizontalRule(_) => "-".repeat(width),
+            ElementSet::QuoteBlock(quote) => {
+                let content = self.elements(children(&**quote), width.saturating_sub(QUOTE_MARGIN));
+                hang(&" ".repeat(QUOTE_MARGIN), &content)
+            }
+            ElementSet::VerseBlock(verse) => {
+                let objects = verse.content().map_or(&[][..], |content| content.value());
+                let text = self.objects(objects).replace(LINE_BREAK, "");
+                verbatim(&text)
+            }
+            ElementSet::CenterBlock(center_block) => {
+                let content = center_block.content().map_or("", |content| content.value());
+                center(&self.div(content, width), width)
+            }
+            ElementSet::SpecialBlock(special) => self.div(
+                special.content().map_or("", |content| content.value()),
+                width,
+            ),
+            ElementSet::DynamicBlock(block) => self.elements(children(&**block), width),
+            _ => return None,
+        })
+    }
+
+    /// Exports the content of a center or special block (which is not parsed).
+    fn div(&mut self, content: &str, width: usize) -> String {
+        match parse_document(content) {
+            Ok(document) => match &document.preface {
+                Some(preface) => self.elements(children(preface), width),
+                None => String::new(),
+            },
+            Err(_) => fill(content, width),
+        }
+    }
+
+    /// Exports the items of the list. Their content is indented by the width of the bullet.
+    fn list(&mut self, list: &PlainList, width: usize) -> String {
+        let items = list.content().map_or(&[][..], |content| content.value());
+        items
+            .iter()
+            .map(|item| {
+                let bullet = match &item.kind {
+                    ItemKind::Ordered { .. } => format!("{} ", item.kind.bullet()),
+                    _ => "- ".to_string(),
+                };
+                let body = self.item(item, width.saturating_sub(bullet.len()));
+                hang(&bullet, &body)
+            })
+            .join("\n")
+    }
+
+    /// Exports the content of the item. The checkbox and the tag are put in front of the first
+    /// paragraph.
+    fn item(&mut self, item: &Item, width: usize) -> String {
+        let mut head = String::new();
+        if let Some(checkbox) = &item.checkbox {
+            head.push_str(match checkbox {
+                Checkbox::Checked => "[X] ",
+                Checkbox::Unchecked => "[ ] ",
+                Checkbox::Partial => "[-] ",
+            });
+        }
+        if let ItemKind::Description { tag, .. } = &item.kind {
+            head.push_str(&format!("{}: ", self.objects(&parse_objects(tag))));
+        }
+        let mut blocks = Vec::new();
+        for element in children(item) {
+            match element {
+                ElementSet::Paragraph(paragraph) if blocks.is_empty() => {
+                    let text = format!("{}{}", head, self.objects(paragraph.content.iter()));
+                    head.clear();
+                    blocks.push(fill(&text, width));
+                }
+                element => blocks.extend(self.element(element, width)),
+            }
+        }
+        if !head.is_empty() {
+            blocks.insert(0, head.trim_end().to_string());
+        }
+        blocks.join("\n")
+    }
+
+    /// Exports the table with aligned columns. table.el tables are written verbatim.
+    fn table(&mut self, table: &Table) -> String {
+        let content = table.content().map_or(&[][..], |content| content.value());
+        let mut rows: Vec<Option<Vec<String>>> = Vec::new();
+        let mut lines = Vec::new();
+        for row in content {
+            match row {
+                TableContent::Org(row) => match &row.kind {
+                    TableRowKind::Normal(cells) => {
+                        let cells = cells.value().iter().map(|cell| {
+                            let objects: Vec<StandardSet> = cell
+                                .content
+                                .value()
+                                .iter()
+                                .cloned()
+                                .map(Into::into)
+                                .collect();
+                            fill(&self.objects(&objects), usize::MAX)
+                        });
+                        rows.push(Some(cells.collect()))
+                    }
+                    TableRowKind::Rule => rows.push(None),
+                },
+                TableContent::TableEl(line) => lines.push(line.as_str()),
+            }
+        }
+        if !lines.is_empty() {
+            return lines.join("\n");
+        }
+        let mut widths: Vec<usize> = Vec::new();
+        for cells in rows.iter().filter_map(Option::as_ref) {
+            for (column, cell) in cells.iter().enumerate() {
+                let width = cell.chars().count();
+                match widths.get_mut(column) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+        rows.iter()
+            .map(|row| match row {
+                Some(cells) => {
+                    let cells = widths.iter().enumerate().map(|(column, width)| {
+                        let cell = cells.get(column).map_or("", String::as_str);
+                        let padding = width - cell.chars().count();
+                        format!("{}{}", cell, " ".repeat(padding))
+                    });
+                    format!("| {} |", cells.format(" | "))
+                }
+                None => {
+                    let dashes = widths.iter().map(|width| "-".repeat(width + 2));
+                    format!("|{}|", dashes.format("+"))
+                }
+            })
+            .join("\n")
+    }
+
+    fn objects<'b>(&mut self, objects: impl IntoIterator<Item = &'b StandardSet>) -> String {
+        let mut text = String::new();
+        for object in objects {
+            self.object(&mut text, object);
+        }
+        text
+    }
+
+    fn object(&mut self, text: &mut String, object: &StandardSet) {
+        match object {
+            StandardSet::RawString(s) => text.push_str(s),
+            StandardSet::LineBreak(_) => text.push(LINE_BREAK),
+            StandardSet::Entity(entity) => match ORG_ENTITIES.get(entity.name.as_str()) {
+                Some(replacement) => text.push_str(replacement.ascii),
+                None => text.push_str(&entity.to_string()),
+            },
+            StandardSet::ExportSnippet(snippet) => {
+                if let Some(value) = self.context.snippet(snippet) {
+                    text.push_str(value);
+                }
+            }
+            StandardSet::FootnoteReference(reference) => match self.footnote(reference) {
+                Some(number) => text.push_str(&format!("[{}]", number)),
+                None => text.push_str(&reference.to_string()),
+            },
+            StandardSet::InlineSrcBlock(src) => text.push_str(&format!("`{}`", src.value)),
+            StandardSet::InlineBabelCall(_) | StandardSet::Target(_) => {}
+            StandardSet::Link(link) => match &link.link {
+                LinkFormat::Radio(s) | LinkFormat::Angle(s) | LinkFormat::Plain(s) => {
+                    text.push_str(s)
+                }
+                LinkFormat::Bracket(path, _, Some(description)) => {
+                    let description: Vec<StandardSet> =
+                        description.iter().cloned().map(Into::into).collect();
+                    let description = self.objects(&description);
+                    match path {
+                        LinkPath::File(_) | LinkPath::Protocol { .. } => {
+                            text.push_str(&format!("{} <{}>", description, path))
+                        }
+                        _ => text.push_str(&description),
+                    }
+                }
+                LinkFormat::Bracket(path, _, None) => text.push_str(&path.to_string()),
+            },
+            StandardSet::RadioTarget(radio) => {
+                let target: Vec<StandardSet> =
+                    radio.target.iter().cloned().map(Into::into).collect();
+                let target = self.objects(&target);
+                text.push_str(&target);
+            }
+            StandardSet::TextMarkup(markup) => {
+                let (marker, content) = match &markup.kind {
+                    TextMarkupKind::Bold(content) => ('*', content),
+                    TextMarkupKind::Italic(content) => ('/', content),
+                    TextMarkupKind::Underline(content) => ('_', content),
+                    TextMarkupKind::StrikeThrough(content) => ('+', content),
+                    TextMarkupKind::Code(content) | TextMarkupKind::Verbatim(content) => {
+                        return text.push_str(&format!("`{}`", content));
+                    }
+                };
+                let content = self.objects(content.iter());
+                text.push_str(&format!("{}{}{}", marker, content, marker));
+            }
+            object => text.push_str(&object.to_string()),
+        }
+    }
+
+    /// Returns the number of the footnote. Labeled footnotes keep the number of their first
+    /// reference. Returns `None` for references to undefined footnotes.
+    fn footnote(&mut self, reference: &FootnoteReference) -> Option<usize> {
+        if let Some(&number) = reference.label().and_then(|label| self.numbers.get(label)) {
+            return Some(number);
+        }
+        let note = match reference.definition() {
+            Some(definition) => Note::Objects(definition.iter().cloned().collect()),
+            None => match self.index.footnote_definition(reference.label()?)? {
+                FootnoteDefinitionRef::Element(definition) => {
+                    Note::Elements(children(definition).to_vec())
+                }
+                FootnoteDefinitionRef::Inline(definition) => {
+                    Note::Objects(definition.iter().cloned().collect())
+                }
+            },
+        };
+        self.count += 1;
+        if let Some(label) = reference.label() {
+            self.numbers.insert(label.to_string(), self.count);
+        }
+        self.notes.push((self.count, note));
+        Some(self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::text::BACKENDS;
+    use crate::export::ExportOptions;
+
+    fn document() -> Document {
+        Document::parse(
+            "#+TITLE: Trip\n\
+             Plans for the trip to the mountains with a long first paragraph[fn:1].\n\
+             * TODO Packing :home:\n\
+             - [X] Boots\n\
+             - [ ] A /warm/ jacket for the evenings in the mountains[fn:1]\n\
+             ** Food\n\
+             | Item | Count |\n\
+             |------+-------|\n\
+             | Bread | 2 |\n\
+             * COMMENT Hidden\n\
+             * Route\n\
+             See [[https://example.org][the map]] and ~map.pdf~.\n\
+             \n\
+             [fn:1] Written in the /evening/.\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn export_to_ascii() {
+        let export_options = ExportOptions::default();
+        let mut context = ExportContext::new(&BACKENDS, &export_options);
+        let options = AsciiOptions {
+            width: 40,
+            headline_style: HeadlineStyle::Numbered,
+            ..AsciiOptions::default()
+        };
+        assert_eq!(
+            export(&document(), &options, &mut context),
+            "                  Trip\n\
+             \x20                 ====\n\
+             \n\
+             Plans for the trip to the mountains with\n\
+             a long first paragraph[1].\n\
+             \n\
+             1 TODO Packing :home:\n\
+             =====================\n\
+             \n\
+             - [X] Boots\n\
+             - [ ] A /warm/ jacket for the evenings\n\
+             \x20 in the mountains[1]\n\
+             \n\
+             1.1 Food\n\
+             --------\n\
+             \n\
+             | Item  | Count |\n\
+             |-------+-------|\n\
+             | Bread | 2     |\n\
+             \n\
+             2 Route\n\
+             =======\n\
+             \n\
+             See the map <https://example.org> and\n\
+             `map.pdf`.\n\
+             \n\
+             Footnotes\n\
+             =========\n\
+             \n\
+             [1] Written in the /evening/.\n"
+        );
+    }
+
+    #[test]
+    fn footnotes_at_end_of_section() {
+        let export_options = ExportOptions::default();
+        let mut context = ExportContext::new(&BACKENDS, &export_options);
+        let options = AsciiOptions {
+            footnotes: FootnotePlacement::EndOfSection,
+            ..AsciiOptions::default()
+        };
+        let text = export(&document(), &options, &mut context);
+        assert!(text.contains("paragraph[1].\n\n[1] Written in the /evening/.\n\nTODO Packing"));
+        assert!(!text.contains("Footnotes"));
+    }
+}
@@ -0,0 +1,213 @@
+//! Caching the exported output of subtrees.
+//!
+//! Static site generators re-export all documents after every change. Most subtrees didn't
+//! change, so [`ExportCache`] keeps their output and only calls the exporter for subtrees with a
+//! new [fingerprint](`Headline::fingerprint`).
+//!
+//! Exporters call [`ExportCache::subtree`] for every headline. The function rendering a
+//! headline gets the cache, so it can use it for the child headlines: after an edit the
+//! subtrees containing the edit are rendered again but their unchanged children are not.
+
+use super::{ExportContext, ExportDiagnostic};
+use crate::types::greater_elements::Headline;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The output of exported subtrees by fingerprint.
+///
+/// The cache can be used for several backends and options. Output is only reused for the same
+/// backends and [`ExportOptions`](`super::ExportOptions`).
+#[derive(Debug, Clone, Default)]
+pub struct ExportCache {
+    entries: HashMap<Key, Entry>,
+    /// The subtrees used while rendering the subtrees currently being rendered (one list for
+    /// every level of nesting).
+    rendering: Vec<Vec<Key>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    fingerprint: u64,
+    /// The fingerprint doesn't contain the level but the output usually depends on it.
+    level: u32,
+    /// The hash of the backends and options of the export.
+    context: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    output: String,
+    /// The diagnostics reported while rendering. They are reported again when the output is
+    /// reused.
+    diagnostics: Vec<ExportDiagnostic>,
+    /// The cached subtrees used by this subtree. They are used again when the output is reused.
+    children: Vec<Key>,
+    /// Whether the entry was used since the last [`ExportCache::sweep`].
+    used: bool,
+}
+
+impl ExportCache {
+    pub fn new() -> Self {
+        ExportCache::default()
+    }
+
+    /// Returns the output of a headline and its children.
+    ///
+    /// `render` is only called if the subtree was not rendered before with the same backends
+    /// and options.
+    pub fn subtree(
+        &mut self,
+        headline: &Headline,
+        context: &mut ExportContext<'_>,
+        render: impl FnOnce(&Headline, &mut ExportCache, &mut ExportContext<'_>) -> String,
+    ) -> String {
+        let key = Key {
+            fingerprint: headline.fingerprint(),
+            level: headline.level,
+            context: context_hash(context),
+        };
+        if let Some(parent) = self.rendering.last_mut() {
+            parent.push(key);
+        }
+        if let Some(entry) = self.entries.get(&key) {
+            context
+                .diagnostics
+                .extend(entry.diagnostics.iter().cloned());
+            let output = entry.output.clone();
+            self.mark_used(key);
+            return output;
+        }
+        let reported = context.diagnostics.len();
+        self.rendering.push(Vec::new());
+        let output = render(headline, self, context);
+        let children = self.rendering.pop().unwrap_or_default();
+        self.entries.insert(
+            key,
+            Entry {
+                output: output.clone(),
+                diagnostics: context.diagnostics[reported..].to_vec(),
+                children,
+                used: true,
+            },
+        );
+        output
+    }
+
+    /// Marks the entry and the entries of its children as used.
+    fn mark_used(&mut self, key: Key) {
+        let mut keys = vec![key];
+        while let Some(key) = keys.pop() {
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.used = true;
+                keys.extend(entry.children.iter().cloned());
+            }
+        }
+    }
+
+    /// Removes the output of the subtrees that were not exported since the last sweep.
+    ///
+    /// Call this after every export of a whole site, so the output of changed and removed
+    /// subtrees doesn't accumulate.
+    pub fn sweep(&mut self) {
+        self.entries.retain(|_, entry| entry.used);
+        for entry in self.entries.values_mut() {
+            entry.used = false;
+        }
+    }
+
+    /// The number of cached subtrees.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
+
+fn context_hash(context: &ExportContext<'_>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    context.backends.hash(&mut hasher);
+    context.options.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{html, ExportOptions};
+    use crate::types::document::Document;
+    use crate::types::StandardSet;
+    use std::cell::Cell;
+
+    fn headline(text: &str) -> Headline {
+        Document::parse(text).unwrap().headlines.remove(0)
+    }
+
+    /// Renders the title and children of a headline and counts the calls.
+    fn render(
+        headline: &Headline,
+        cache: &mut ExportCache,
+        context: &mut ExportContext<'_>,
+        calls: &Cell<usize>,
+    ) -> String {
+        calls.set(calls.get() + 1);
+        let title: Vec<StandardSet> = headline
+            .title
+            .iter()
+            .flat_map(|title| title.iter().cloned().map(Into::into))
+            .collect();
+        let mut output = format!(
+            "<h{0}>{1}</h{0}>",
+            headline.level,
+            html::objects(&title, context)
+        );
+        for child in headline.children() {
+            output += &cache.subtree(child, context, |child, cache, context| {
+                render(child, cache, context, calls)
+            });
+        }
+        output
+    }
+
+    #[test]
+    fn cached_subtrees() {
+        let options = ExportOptions::default();
+        let mut cache = ExportCache::new();
+        let calls = Cell::new(0);
+        let export = |document: &Headline, cache: &mut ExportCache| {
+            let mut context = ExportContext::new(&html::BACKENDS, &options);
+            cache.subtree(document, &mut context, |headline, cache, context| {
+                render(headline, cache, context, &calls)
+            })
+        };
+
+        let document = headline("* a\n** b\n** c\n");
+        assert_eq!(
+            export(&document, &mut cache),
+            "<h1>a</h1><h2>b</h2><h2>c</h2>"
+        );
+        assert_eq!((calls.get(), cache.len()), (3, 3));
+        export(&document, &mut cache);
+        assert_eq!(calls.get(), 3, "everything is cached");
+
+        // only the changed child and its parent are rendered again
+        let document = headline("* a\n** b\n** d\n");
+        assert_eq!(
+            export(&document, &mut cache),
+            "<h1>a</h1><h2>b</h2><h2>d</h2>"
+        );
+        assert_eq!(calls.get(), 5);
+
+        cache.sweep();
+        assert_eq!(cache.len(), 5);
+        export(&document, &mut cache);
+        cache.sweep();
+        assert_eq!(cache.len(), 3, "the old versions were removed");
+    }
+}
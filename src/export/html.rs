@@ -0,0 +1,119 @@
+//! Exports to HTML.
+
+use super::{link_url, ExportContext};
+use crate::entities::ORG_ENTITIES;
+use crate::types::objects::{Link, LinkFormat, TextMarkup, TextMarkupKind};
+use crate::types::StandardSet;
+
+/// The backends of export snippets and blocks included in HTML.
+pub const BACKENDS: [&str; 1] = ["html"];
+
+/// The attributes (e.g. `#+ATTR_HTML: :lang fr`) used by this exporter.
+pub const ATTRIBUTES: &[&str] = &["lang"];
+
+/// Exports objects to HTML.
+///
+/// Line breaks become `<br/>`.
+pub fn objects<'a>(
+    objects: impl IntoIterator<Item = &'a StandardSet>,
+    context: &mut ExportContext<'_>,
+) -> String {
+    let mut html = String::new();
+    for object in objects {
+        write_object(&mut html, object, context);
+    }
+    html
+}
+
+/// Escapes the characters with a special meaning in HTML.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_object(html: &mut String, object: &StandardSet, context: &mut ExportContext<'_>) {
+    match object {
+        StandardSet::RawString(s) => html.push_str(&escape(s)),
+        StandardSet::LineBreak(_) => html.push_str("<br/>\n"),
+        StandardSet::Entity(entity) => match ORG_ENTITIES.get(entity.name.as_str()) {
+            Some(replacement) => html.push_str(replacement.html),
+            None => html.push_str(&escape(&entity.to_string())),
+        },
+        StandardSet::ExportSnippet(snippet) => {
+            if let Some(value) = context.snippet(snippet) {
+                html.push_str(value);
+            }
+        }
+        StandardSet::Link(link) => write_link(html, link, context),
+        StandardSet::TextMarkup(markup) => write_markup(html, markup, context),
+        object => html.push_str(&escape(&object.to_string())),
+    }
+}
+
+fn write_link(html: &mut String, link: &Link, context: &mut ExportContext<'_>) {
+    let (url, description) = match &link.link {
+        LinkFormat::Radio(s) => return html.push_str(&escape(s)),
+        LinkFormat::Angle(s) | LinkFormat::Plain(s) => (s.clone(), escape(s)),
+        LinkFormat::Bracket(path, _, description) => {
+            let description = match description {
+                Some(description) => {
+                    let description: Vec<StandardSet> =
+                        description.iter().cloned().map(Into::into).collect();
+                    objects(&description, context)
+                }
+                None => escape(&path.to_string()),
+            };
+            (link_url(path), description)
+        }
+    };
+    html.push_str(&format!("<a href=\"{}\">{}</a>", escape(&url), description));
+}
+
+fn write_markup(html: &mut String, markup: &TextMarkup, context: &mut ExportContext<'_>) {
+    let (open, close, content) = match &markup.kind {
+        TextMarkupKind::Bold(content) => ("<b>", "</b>", content),
+        TextMarkupKind::Italic(content) => ("<i>", "</i>", content),
+        TextMarkupKind::Underline(content) => ("<span class=\"underline\">", "</span>", content),
+        TextMarkupKind::StrikeThrough(content) => ("<del>", "</del>", content),
+        TextMarkupKind::Code(content) | TextMarkupKind::Verbatim(content) => {
+            html.push_str(&format!("<code>{}</code>", escape(content)));
+            return;
+        }
+    };
+    html.push_str(open);
+    html.push_str(&objects(content.iter(), context));
+    html.push_str(close);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportOptions;
+    use crate::parsing::parse_objects;
+    use crate::types::objects::ExportSnippet;
+
+    #[test]
+    fn objects_to_html() {
+        let options = ExportOptions::default();
+        let mut context = ExportContext::new(&BACKENDS, &options);
+        let mut objects = parse_objects("a & *b* \\\\\n[[https://orgmode.org][=c=]] ");
+        objects.push(StandardSet::ExportSnippet(ExportSnippet {
+            backend: "html".to_string(),
+            value: "<hr>".to_string(),
+        }));
+        assert_eq!(
+            super::objects(&objects, &mut context),
+            "a &amp; <b>b</b> <br/>\n\
+             <a href=\"https://orgmode.org\"><code>c</code></a> <hr>"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! The language of exported documents and blocks.
+//!
+//! The language of a document is set with `#+LANGUAGE: de`. Single elements can use another
+//! language with a `:lang` attribute (e.g. `#+ATTR_HTML: :lang ar`). Exporters use the language
+//! to write `lang` and `dir` attributes and to choose the quotation marks of smart quotes.
+
+use crate::types::affiliated_keywords::AffiliatedKeywords;
+use crate::types::document::Document;
+use crate::types::HasAffiliatedKeywords;
+use std::fmt;
+
+/// Primary language subtags of languages written from right to left.
+const RIGHT_TO_LEFT: [&str; 7] = ["ar", "dv", "fa", "he", "ps", "ur", "yi"];
+
+/// A language tag like `en` or `de-CH`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Language {
+    tag: String,
+}
+
+impl Language {
+    pub fn new(tag: impl Into<String>) -> Self {
+        Language { tag: tag.into() }
+    }
+
+    /// The language of the document (`#+LANGUAGE:` in the preface).
+    pub fn of_document(document: &Document) -> Option<Language> {
+        document.language().map(Language::new)
+    }
+
+    /// The language set with a `:lang` attribute on the element.
+    pub fn of_element(element: &impl HasAffiliatedKeywords) -> Option<Language> {
+        element
+            .affiliated_keywords()
+            .and_then(|keywords| Language::of_affiliated_keywords(keywords.value()))
+    }
+
+    /// The language set with a `:lang` attribute in one of the `#+ATTR_BACKEND:` keywords.
+    pub fn of_affiliated_keywords(keywords: &AffiliatedKeywords) -> Option<Language> {
+        keywords.attribute("lang").map(Language::new)
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// The primary language subtag in lower case (e.g. `de` for `de-CH`).
+    pub fn primary(&self) -> String {
+        self.tag
+            .split(&['-', '_'][..])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+
+    /// The direction the language is written in.
+    pub fn direction(&self) -> Direction {
+        if RIGHT_TO_LEFT.contains(&self.primary().as_str()) {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+
+    /// The quotation marks smart quotes are replaced with (like `org-export-smart-quotes-alist`).
+    ///
+    /// Languages without their own quotation marks use the English ones.
+    pub fn quotes(&self) -> Quotes {
+        let (primary_opening, primary_closing, secondary_opening, secondary_closing) =
+            match self.primary().as_str() {
+                "da" => ("»", "«", "›", "‹"),
+                "de" => ("„", "“", "‚", "‘"),
+                "es" | "it" => ("«", "»", "“", "”"),
+                "fr" => ("«\u{a0}", "\u{a0}»", "‹\u{a0}", "\u{a0}›"),
+                "ja" => ("「", "」", "『", "』"),
+                "ru" => ("«", "»", "„", "“"),
+                "sv" => ("”", "”", "’", "’"),
+                _ => ("“", "”", "‘", "’"),
+            };
+        Quotes {
+            primary_opening,
+            primary_closing,
+            secondary_opening,
+            secondary_closing,
+        }
+    }
+
+    /// Writes the HTML attributes for the language (e.g. ` lang="ar" dir="rtl"`).
+    ///
+    /// `dir` is only written for languages written from right to left.
+    pub fn html_attributes(&self) -> String {
+        let lang = format!(" lang=\"{}\"", super::html::escape(&self.tag));
+        match self.direction() {
+            Direction::LeftToRight => lang,
+            Direction::RightToLeft => format!("{} dir=\"{}\"", lang, Direction::RightToLeft),
+        }
+    }
+}
+
+impl Default for Language {
+    /// English (like `org-export-default-language`).
+    fn default() -> Self {
+        Language::new("en")
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag)
+    }
+}
+
+/// The direction a language is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl fmt::Display for Direction {
+    /// Writes the value of the HTML `dir` attribute.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::LeftToRight => write!(f, "ltr"),
+            Direction::RightToLeft => write!(f, "rtl"),
+        }
+    }
+}
+
+/// The quotation marks of a language.
+///
+/// Primary quotes replace `"`, secondary quotes replace `'` (when used as quotes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quotes {
+    pub primary_opening: &'static str,
+    pub primary_closing: &'static str,
+    pub secondary_opening: &'static str,
+    pub secondary_closing: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{ExportContext, ExportOptions};
+    use crate::types::affiliated_keywords::{AffiliatedKeyword, Attr};
+    use crate::types::elements::{Keyword, KeywordValueSetOfObjects};
+    use crate::types::greater_elements::Section;
+    use crate::types::Spanned;
+
+    #[test]
+    fn languages() {
+        let document = Document {
+            preface: Some(Section::new(Spanned::new(vec![Keyword::new(
+                "language",
+                vec![KeywordValueSetOfObjects::RawString("de-CH".to_string())],
+            )
+            .into()]))),
+            headlines: vec![],
+        };
+        let language = Language::of_document(&document).unwrap();
+        assert_eq!(language.primary(), "de");
+        assert_eq!(language.quotes().primary_opening, "„");
+        assert_eq!(language.html_attributes(), " lang=\"de-CH\"");
+
+        let keywords: AffiliatedKeywords = vec![Spanned::new(Attr::new(
+            "HTML".to_string(),
+            ":class verse :lang ar".to_string(),
+        ))]
+        .into_iter()
+        .map(AffiliatedKeyword::Attr)
+        .collect();
+        let language = Language::of_affiliated_keywords(&keywords).unwrap();
+        assert_eq!(language.direction(), Direction::RightToLeft);
+        assert_eq!(language.html_attributes(), " lang=\"ar\" dir=\"rtl\"");
+        assert_eq!(Language::default().quotes().primary_closing, "”");
+
+        let options = ExportOptions {
+            language: Language::of_document(&document),
+            ..ExportOptions::default()
+        };
+        let context = ExportContext::new(&["html"], &options);
+        assert_eq!(context.language(None).tag(), "de-CH");
+        assert_eq!(context.language(Some(&keywords)).tag(), "ar");
+    }
+}
@@ -0,0 +1,129 @@
+//! Exports to LaTeX.
+
+use super::{link_url, ExportContext};
+use crate::entities::ORG_ENTITIES;
+use crate::types::objects::{Link, LinkFormat, TextMarkup, TextMarkupKind};
+use crate::types::StandardSet;
+
+/// The backends of export snippets and blocks included in LaTeX.
+pub const BACKENDS: [&str; 1] = ["latex"];
+
+/// The attributes (e.g. `#+ATTR_LATEX: :lang fr`) used by this exporter.
+pub const ATTRIBUTES: &[&str] = &["lang"];
+
+/// Exports objects to LaTeX.
+///
+/// Line breaks become `\\`.
+pub fn objects<'a>(
+    objects: impl IntoIterator<Item = &'a StandardSet>,
+    context: &mut ExportContext<'_>,
+) -> String {
+    let mut latex = String::new();
+    for object in objects {
+        write_object(&mut latex, object, context);
+    }
+    latex
+}
+
+/// Escapes the characters with a special meaning in LaTeX.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '{' | '}' | '#' | '$' | '%' | '&' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_object(latex: &mut String, object: &StandardSet, context: &mut ExportContext<'_>) {
+    match object {
+        StandardSet::RawString(s) => latex.push_str(&escape(s)),
+        StandardSet::LineBreak(_) => latex.push_str("\\\\\n"),
+        StandardSet::Entity(entity) => match ORG_ENTITIES.get(entity.name.as_str()) {
+            Some(replacement) if replacement.requires_latex_math => {
+                latex.push_str(&format!("${}$", replacement.latex))
+            }
+            Some(replacement) => latex.push_str(replacement.latex),
+            None => latex.push_str(&escape(&entity.to_string())),
+        },
+        StandardSet::ExportSnippet(snippet) => {
+            if let Some(value) = context.snippet(snippet) {
+                latex.push_str(value);
+            }
+        }
+        StandardSet::Link(link) => write_link(latex, link, context),
+        StandardSet::TextMarkup(markup) => write_markup(latex, markup, context),
+        object => latex.push_str(&escape(&object.to_string())),
+    }
+}
+
+fn write_link(latex: &mut String, link: &Link, context: &mut ExportContext<'_>) {
+    match &link.link {
+        LinkFormat::Radio(s) => latex.push_str(&escape(s)),
+        LinkFormat::Angle(s) | LinkFormat::Plain(s) => {
+            latex.push_str(&format!("\\url{{{}}}", escape_url(s)))
+        }
+        LinkFormat::Bracket(path, _, None) => {
+            latex.push_str(&format!("\\url{{{}}}", escape_url(&link_url(path))))
+        }
+        LinkFormat::Bracket(path, _, Some(description)) => {
+            let description: Vec<StandardSet> =
+                description.iter().cloned().map(Into::into).collect();
+            latex.push_str(&format!(
+                "\\href{{{}}}{{{}}}",
+                escape_url(&link_url(path)),
+                objects(&description, context)
+            ));
+        }
+    }
+}
+
+/// Escapes the characters that can't be used in the URL of `\href` and `\url`.
+fn escape_url(url: &str) -> String {
+    url.replace('%', "\\%").replace('#', "\\#")
+}
+
+fn write_markup(latex: &mut String, markup: &TextMarkup, context: &mut ExportContext<'_>) {
+    let (command, content) = match &markup.kind {
+        TextMarkupKind::Bold(content) => ("textbf", content),
+        TextMarkupKind::Italic(content) => ("emph", content),
+        TextMarkupKind::Underline(content) => ("uline", content),
+        TextMarkupKind::StrikeThrough(content) => ("sout", content),
+        TextMarkupKind::Code(content) | TextMarkupKind::Verbatim(content) => {
+            latex.push_str(&format!("\\texttt{{{}}}", escape(content)));
+            return;
+        }
+    };
+    latex.push_str(&format!(
+        "\\{}{{{}}}",
+        command,
+        objects(content.iter(), context)
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportOptions;
+    use crate::parsing::parse_objects;
+
+    #[test]
+    fn objects_to_latex() {
+        let options = ExportOptions::default();
+        let mut context = ExportContext::new(&BACKENDS, &options);
+        let objects = parse_objects("50% /a_b/ \\\\\n[[#intro][~x~]] [[https://orgmode.org]]");
+        assert_eq!(
+            super::objects(&objects, &mut context),
+            "50\\% \\emph{a\\_b} \\\\\n\
+             \\href{\\#intro}{\\texttt{x}} \\url{https://orgmode.org}"
+        );
+    }
+}
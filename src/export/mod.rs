@@ -0,0 +1,269 @@
+//! Exporting documents to other formats.
+//!
+//! Export snippets (`@@html:<br>@@`) and export blocks (`#+BEGIN_EXPORT html`) contain raw
+//! output for a specific backend. [`ExportContext`] decides what happens with them during an
+//! export: raw output for the current backend is always included, raw output for other
+//! backends is handled according to [`ExportOptions::unknown_backend`].
+//!
+//! The backends are:
+//!
+//! - [`html`] exports to HTML.
+//! - [`latex`] exports to LaTeX.
+//! - [`text`] projects the content to plain text (e.g. for previews and search indexes).
+//! - [`ascii`] exports to readable plain text (e.g. for emails).
+//!
+//! [`outline`] exports only the headline tree (to OPML and JSON). [`pandoc`] exports the whole
+//! document to the JSON AST of Pandoc, which converts it to many more formats.
+//!
+//! The [`language`] of the document and of single elements decides the `lang`/`dir` attributes
+//! and the quotation marks of smart quotes.
+//!
+//! The [settings](`crate::settings`) of the document (e.g. `#+OPTIONS: toc:nil ^:{}`) tell
+//! exporters what to include.
+//!
+//! Exporters can keep the output of unchanged subtrees between exports in an
+//! [`ExportCache`](`cache::ExportCache`).
+//!
+//! The files of a [`Workspace`](`crate::workspace::Workspace`) are exported together as a site
+//! with [`publish`].
+
+use self::language::Language;
+use crate::types::affiliated_keywords::AffiliatedKeywords;
+use crate::types::elements::ExportBlock;
+use crate::types::objects::{ExportSnippet, LinkPath};
+use std::fmt;
+
+pub mod ascii;
+pub mod cache;
+pub mod html;
+pub mod language;
+pub mod latex;
+pub mod outline;
+pub mod pandoc;
+pub mod publish;
+pub mod text;
+
+/// Options shared by all exporters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ExportOptions {
+    /// What to do with export snippets and blocks of other backends.
+    pub unknown_backend: UnknownBackend,
+    /// The language of the document. Usually [`Language::of_document`].
+    ///
+    /// If this is `None` the [default language](`Language::default`) is used.
+    pub language: Option<Language>,
+}
+
+/// What to do with export snippets and blocks of backends other than the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum UnknownBackend {
+    /// Leave them out of the output (like org mode does).
+    #[default]
+    Drop,
+    /// Include the raw value verbatim.
+    PassThrough,
+    /// Leave them out of the output and report an [`ExportDiagnostic`] for each of them.
+    Report,
+}
+
+/// The kind of raw output in an [`ExportDiagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawOutput {
+    Snippet,
+    Block,
+}
+
+/// A problem found during an export.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExportDiagnostic {
+    /// An export snippet or block of another backend was left out.
+    UnknownBackend {
+        kind: RawOutput,
+        backend: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ExportDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportDiagnostic::UnknownBackend {
+                kind,
+                backend,
+                value,
+            } => {
+                let kind = match kind {
+                    RawOutput::Snippet => "snippet",
+                    RawOutput::Block => "block",
+                };
+                write!(
+                    f,
+                    "export {} for backend `{}` was not exported: {:?}",
+                    kind, backend, value
+                )
+            }
+        }
+    }
+}
+
+/// The state of a single export.
+///
+/// Exporters ask the context what to write for export snippets and blocks and collect the
+/// diagnostics here.
+#[derive(Debug, Clone)]
+pub struct ExportContext<'a> {
+    backends: Vec<String>,
+    options: &'a ExportOptions,
+    diagnostics: Vec<ExportDiagnostic>,
+}
+
+impl<'a> ExportContext<'a> {
+    /// Creates a context for an exporter that accepts raw output for the given backends (e.g.
+    /// `["beamer", "latex"]`). Backend names are case-insensitive.
+    pub fn new(backends: &[&str], options: &'a ExportOptions) -> Self {
+        ExportContext {
+            backends: backends
+                .iter()
+                .map(|backend| backend.to_lowercase())
+                .collect(),
+            options,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn options(&self) -> &ExportOptions {
+        self.options
+    }
+
+    /// Returns the language of an element with the given affiliated keywords.
+    ///
+    /// This is the `:lang` attribute of the element or else the language of the document.
+    pub fn language(&self, affiliated_keywords: Option<&AffiliatedKeywords>) -> Language {
+        affiliated_keywords
+            .and_then(Language::of_affiliated_keywords)
+            .or_else(|| self.options.language.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the raw output of the snippet or `None` if nothing should be written.
+    pub fn snippet<'s>(&mut self, snippet: &'s ExportSnippet) -> Option<&'s str> {
+        self.raw(RawOutput::Snippet, &snippet.backend, &snippet.value)
+    }
+
+    /// Returns the raw output of the block or `None` if nothing should be written.
+    pub fn block<'b>(&mut self, block: &'b ExportBlock) -> Option<&'b str> {
+        self.raw(RawOutput::Block, &block.backend, &block.value)
+    }
+
+    fn raw<'v>(&mut self, kind: RawOutput, backend: &str, value: &'v str) -> Option<&'v str> {
+        if self.backends.contains(&backend.to_lowercase()) {
+            return Some(value);
+        }
+        match self.options.unknown_backend {
+            UnknownBackend::Drop => None,
+            UnknownBackend::PassThrough => Some(value),
+            UnknownBackend::Report => {
+                self.diagnostics.push(ExportDiagnostic::UnknownBackend {
+                    kind,
+                    backend: backend.to_string(),
+                    value: value.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[ExportDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Ends the export and returns the diagnostics.
+    pub fn into_diagnostics(self) -> Vec<ExportDiagnostic> {
+        self.diagnostics
+    }
+}
+
+/// Returns the URL a link points to in exported documents.
+///
+/// Links to headlines (by id, custom id or title) point to anchors in the same document.
+pub(crate) fn link_url(path: &LinkPath) -> String {
+    match path {
+        LinkPath::File(file) => file.clone(),
+        LinkPath::Protocol { protocol, path } => format!("{}:{}", protocol, path),
+        LinkPath::Id(id) => format!("#ID-{}", id),
+        LinkPath::CustomId(id) => format!("#{}", id),
+        LinkPath::CodeRef(coderef) => format!("#coderef-{}", coderef),
+        LinkPath::Fuzzy(fuzzy) => format!("#{}", fuzzy),
+    }
+}
+
+/// Returns the string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    use std::fmt::Write;
+
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(backend: &str, value: &str) -> ExportSnippet {
+        ExportSnippet {
+            backend: backend.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn unknown_backends() {
+        let html = snippet("html", "<br>");
+        let latex = snippet("latex", "\\newline");
+        let block = ExportBlock::new("LaTeX", "\\clearpage".to_string());
+
+        let options = ExportOptions::default();
+        let mut context = ExportContext::new(&["html"], &options);
+        assert_eq!(context.snippet(&html), Some("<br>"));
+        assert_eq!(context.snippet(&latex), None);
+        assert_eq!(context.block(&block), None);
+        assert!(context.diagnostics().is_empty());
+
+        let options = ExportOptions {
+            unknown_backend: UnknownBackend::PassThrough,
+            ..ExportOptions::default()
+        };
+        let mut context = ExportContext::new(&["html"], &options);
+        assert_eq!(context.snippet(&latex), Some("\\newline"));
+
+        let options = ExportOptions {
+            unknown_backend: UnknownBackend::Report,
+            ..ExportOptions::default()
+        };
+        let mut context = ExportContext::new(&["beamer", "latex"], &options);
+        assert_eq!(context.block(&block), Some("\\clearpage"));
+        assert_eq!(context.snippet(&html), None);
+        assert_eq!(
+            context.into_diagnostics(),
+            vec![ExportDiagnostic::UnknownBackend {
+                kind: RawOutput::Snippet,
+                backend: "html".to_string(),
+                value: "<br>".to_string(),
+            }]
+        );
+    }
+}
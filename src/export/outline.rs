@@ -0,0 +1,278 @@
+//! Exports the headline tree to OPML and JSON (e.g. for outliners and mind-mapping tools).
+//!
+//! Only the structure is exported: every headline becomes an [`OutlineNode`] with its title,
+//! todo keyword, priority, tags and planning dates. The section of a headline is kept as plain
+//! org text in the note of the node. Commented subtrees are left out like in the other exports.
+
+use super::json_string;
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, HeadlineContentSet, SubtreeFilter};
+use crate::types::objects::Timestamp;
+use crate::types::{Parent, Spanned};
+use itertools::Itertools;
+use std::fmt::Write;
+
+/// A headline in the outline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct OutlineNode {
+    /// The title (including org markup).
+    pub text: String,
+    pub todo_keyword: Option<String>,
+    pub priority: Option<char>,
+    pub tags: Vec<String>,
+    /// The scheduled date (`YYYY-MM-DD` or `YYYY-MM-DDTHH:MM`).
+    pub scheduled: Option<String>,
+    /// The deadline (like [`scheduled`][OutlineNode::scheduled]).
+    pub deadline: Option<String>,
+    /// The date the headline was closed (like [`scheduled`][OutlineNode::scheduled]).
+    pub closed: Option<String>,
+    /// The section of the headline as org text.
+    pub note: Option<String>,
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    /// Creates the node of the headline and its children.
+    pub fn new(headline: &Headline) -> Self {
+        let planning = headline.planning.as_ref();
+        let date = |timestamp: Option<&Timestamp>| timestamp.and_then(iso_date);
+        let sections: Vec<String> = headline
+            .content()
+            .into_iter()
+            .flat_map(Spanned::value)
+            .filter_map(|content| match content {
+                HeadlineContentSet::Section(section) => Some(section.to_string()),
+                HeadlineContentSet::Headline(_) => None,
+            })
+            .collect();
+        let note = sections.join("\n").trim().to_string();
+        OutlineNode {
+            text: headline
+                .title
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            todo_keyword: headline.todo_keyword.as_ref().map(ToString::to_string),
+            priority: headline.priority,
+            tags: headline.tags.clone(),
+            scheduled: date(planning.and_then(|planning| planning.scheduled.as_ref())),
+            deadline: date(planning.and_then(|planning| planning.deadline.as_ref())),
+            closed: date(planning.and_then(|planning| planning.closed.as_ref())),
+            note: if note.is_empty() { None } else { Some(note) },
+            children: nodes(headline.children()),
+        }
+    }
+
+    /// The attributes of the node except the text and children (in the order they are exported).
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attributes = Vec::new();
+        if let Some(keyword) = &self.todo_keyword {
+            attributes.push(("todo", keyword.clone()));
+        }
+        if let Some(priority) = self.priority {
+            attributes.push(("priority", priority.to_string()));
+        }
+        if !self.tags.is_empty() {
+            attributes.push(("tags", self.tags.join(" ")));
+        }
+        let dates = [
+            ("scheduled", &self.scheduled),
+            ("deadline", &self.deadline),
+            ("closed", &self.closed),
+        ];
+        for (name, date) in dates.iter() {
+            if let Some(date) = date {
+                attributes.push((name, date.clone()));
+            }
+        }
+        attributes
+    }
+}
+
+/// Returns the nodes of the top level headlines of the document.
+pub fn outline(document: &Document) -> Vec<OutlineNode> {
+    nodes(&document.headlines)
+}
+
+fn nodes<'a>(headlines: impl IntoIterator<Item = &'a Headline>) -> Vec<OutlineNode> {
+    let filter = SubtreeFilter {
+        include_commented: false,
+        include_archived: true,
+    };
+    headlines
+        .into_iter()
+        .filter(|headline| !filter.skips(headline))
+        .map(OutlineNode::new)
+        .collect()
+}
+
+fn iso_date(timestamp: &Timestamp) -> Option<String> {
+    let date_time = timestamp.start_date_time()?;
+    Some(match timestamp.timestamp_start() {
+        Some((_, Some(_))) => date_time.format("%Y-%m-%dT%H:%M").to_string(),
+        _ => date_time.format("%Y-%m-%d").to_string(),
+    })
+}
+
+/// Returns the `#+TITLE:` of the document.
+fn title(document: &Document) -> Option<String> {
+    document
+        .keywords()
+        .find(|keyword| keyword.key.eq_ignore_ascii_case("TITLE"))
+        .map(|keyword| keyword.value.value().iter().join("").trim().to_string())
+}
+
+/// Exports the document to OPML 2.0.
+///
+/// The attributes of the nodes are added to the `outline` elements (e.g. `todo="TODO"`), the
+/// note as `_note` like most outliners do.
+pub fn opml(document: &Document) -> String {
+    fn write_node(opml: &mut String, node: &OutlineNode, depth: usize) {
+        let indent = "  ".repeat(depth);
+        write!(
+            opml,
+            "{}<outline text=\"{}\"",
+            indent,
+            escape_xml(&node.text)
+        )
+        .unwrap();
+        for (name, value) in node.attributes() {
+            write!(opml, " {}=\"{}\"", name, escape_xml(&value)).unwrap();
+        }
+        if let Some(note) = &node.note {
+            write!(opml, " _note=\"{}\"", escape_xml(note)).unwrap();
+        }
+        if node.children.is_empty() {
+            opml.push_str("/>\n");
+        } else {
+            opml.push_str(">\n");
+            for child in &node.children {
+                write_node(opml, child, depth + 1);
+            }
+            writeln!(opml, "{}</outline>", indent).unwrap();
+        }
+    }
+
+    let mut opml =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    opml.push_str("  <head>\n");
+    if let Some(title) = title(document) {
+        writeln!(opml, "    <title>{}</title>", escape_xml(&title)).unwrap();
+    }
+    opml.push_str("  </head>\n  <body>\n");
+    for node in outline(document) {
+        write_node(&mut opml, &node, 2);
+    }
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Exports the document to a JSON object with the `title` of the document and its `children`.
+///
+/// Every node is an object with `text`, its attributes (only if they are set), `note` and
+/// `children`. Tags are an array of strings.
+pub fn json(document: &Document) -> String {
+    fn write_nodes(json: &mut String, nodes: &[OutlineNode]) {
+        json.push('[');
+        for (index, node) in nodes.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            write!(json, "{{\"text\":{}", json_string(&node.text)).unwrap();
+            for (name, value) in node.attributes() {
+                let value = match name {
+                    "tags" => format!(
+                        "[{}]",
+                        node.tags.iter().map(|tag| json_string(tag)).join(",")
+                    ),
+                    _ => json_string(&value),
+                };
+                write!(json, ",\"{}\":{}", name, value).unwrap();
+            }
+            if let Some(note) = &node.note {
+                write!(json, ",\"note\":{}", json_string(note)).unwrap();
+            }
+            json.push_str(",\"children\":");
+            write_nodes(json, &node.children);
+            json.push('}');
+        }
+        json.push(']');
+    }
+
+    let mut json = String::from("{");
+    if let Some(title) = title(document) {
+        write!(json, "\"title\":{},", json_string(&title)).unwrap();
+    }
+    json.push_str("\"children\":");
+    write_nodes(&mut json, &outline(document));
+    json.push('}');
+    json
+}
+
+/// Escapes the characters with a special meaning in XML attributes and text.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Planning;
+
+    fn document() -> Document {
+        let mut document = Document::parse(
+            "#+TITLE: Plans & ideas\n\
+             * TODO [#A] Write \"docs\" :work:\n\
+             Some notes.\n\
+             ** Intro\n\
+             * COMMENT Hidden\n",
+        )
+        .unwrap();
+        document.headlines[0].planning = Some(Planning {
+            deadline: Some("<2018-10-12 Fri 10:00>".parse().unwrap()),
+            ..Planning::default()
+        });
+        document
+    }
+
+    #[test]
+    fn opml_outline() {
+        assert_eq!(
+            opml(&document()),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <opml version=\"2.0\">\n\
+             \x20 <head>\n\
+             \x20   <title>Plans &amp; ideas</title>\n\
+             \x20 </head>\n\
+             \x20 <body>\n\
+             \x20   <outline text=\"Write &quot;docs&quot;\" todo=\"TODO\" priority=\"A\" \
+             tags=\"work\" deadline=\"2018-10-12T10:00\" _note=\"Some notes.\">\n\
+             \x20     <outline text=\"Intro\"/>\n\
+             \x20   </outline>\n\
+             \x20 </body>\n\
+             </opml>\n"
+        );
+    }
+
+    #[test]
+    fn json_outline() {
+        assert_eq!(
+            json(&document()),
+            "{\"title\":\"Plans & ideas\",\"children\":[{\"text\":\"Write \\\"docs\\\"\",\
+             \"todo\":\"TODO\",\"priority\":\"A\",\"tags\":[\"work\"],\
+             \"deadline\":\"2018-10-12T10:00\",\"note\":\"Some notes.\",\
+             \"children\":[{\"text\":\"Intro\",\"children\":[]}]}]}"
+        );
+    }
+}
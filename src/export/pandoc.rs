@@ -0,0 +1,833 @@
+//! Exports to the JSON AST of [Pandoc](https://pandoc.org/).
+//!
+//! Pandoc reads the output with `pandoc -f json` and writes it to any of its formats (e.g.
+//! `pandoc -f json -t docx`), so formats without a backend in this crate can be used too.
+//!
+//! Elements and objects are mapped to Pandoc's `Block` and `Inline` types like Pandoc's own org
+//! reader does. Some differences:
+//!
+//! - Export snippets and blocks become raw inlines and blocks of their backend. Pandoc only
+//!   writes the ones of the output format.
+//! - Drawers, planning lines, clocks, comments and keywords are left out. The `TITLE`, `AUTHOR`
+//!   and `DATE` keywords and the [language](`Language::of_document`) become the metadata.
+//! - Commented subtrees are left out like in the other exports.
+
+use super::language::Language;
+use super::{json_string, link_url};
+use crate::entities::ORG_ENTITIES;
+use crate::parsing::{parse_document, parse_objects};
+use crate::types::affiliated_keywords::AffiliatedKeywords;
+use crate::types::document::Document;
+use crate::types::greater_elements::{
+    Checkbox, Counter, CounterDelimiter, Headline, HeadlineContentSet, Item, ItemKind, ListKind,
+    PlainList, SubtreeFilter, Table, TableContent, TableRowKind, TodoKeyword,
+};
+use crate::types::index::{DocumentIndex, FootnoteDefinitionRef};
+use crate::types::objects::{
+    FootnoteReference, Link, LinkFormat, LinkPath, TableCell, TextMarkup, TextMarkupKind,
+};
+use crate::types::{ElementSet, Parent, SecondaryString, Spanned, StandardSet};
+use itertools::Itertools;
+use std::fmt;
+use std::mem;
+
+/// The version of the Pandoc API of the output (the one of Pandoc 3).
+pub const API_VERSION: [u32; 3] = [1, 23, 1];
+
+/// Exports the document to the Pandoc JSON AST.
+pub fn json(document: &Document) -> String {
+    let exporter = Exporter {
+        index: document.index(),
+    };
+    let mut blocks = Vec::new();
+    if let Some(preface) = &document.preface {
+        exporter.elements(children(preface), &mut blocks);
+    }
+    for headline in &document.headlines {
+        exporter.headline(headline, &mut blocks);
+    }
+
+    let mut meta = Vec::new();
+    for &(key, name) in [("TITLE", "title"), ("AUTHOR", "author"), ("DATE", "date")].iter() {
+        if let Some(keyword) = document
+            .keywords()
+            .find(|keyword| keyword.key.eq_ignore_ascii_case(key))
+        {
+            let objects: Vec<StandardSet> = keyword
+                .value
+                .value()
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect();
+            let inlines = trim(exporter.inlines(&objects));
+            meta.push((name, node("MetaInlines", Json::Array(inlines))));
+        }
+    }
+    if let Some(language) = Language::of_document(document) {
+        meta.push(("lang", node("MetaString", string(language.tag()))));
+    }
+
+    Json::Object(vec![
+        (
+            "pandoc-api-version",
+            array(API_VERSION.iter().map(|&n| Json::Number(n.into()))),
+        ),
+        ("meta", Json::Object(meta)),
+        ("blocks", Json::Array(blocks)),
+    ])
+    .to_string()
+}
+
+/// A JSON value of the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Json {
+    Null,
+    Number(i64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write!(f, "{}", json_string(s)),
+            Json::Array(values) => write!(f, "[{}]", values.iter().format(",")),
+            Json::Object(fields) => write!(
+                f,
+                "{{{}}}",
+                fields
+                    .iter()
+                    .format_with(",", |(key, value), f| f(&format_args!(
+                        "{}:{}",
+                        json_string(key),
+                        value
+                    )))
+            ),
+        }
+    }
+}
+
+fn string(s: impl Into<String>) -> Json {
+    Json::String(s.into())
+}
+
+fn array(values: impl IntoIterator<Item = Json>) -> Json {
+    Json::Array(values.into_iter().collect())
+}
+
+/// A value of a Pandoc type with content (`{"t":TAG,"c":CONTENT}`).
+fn node(tag: &str, content: Json) -> Json {
+    Json::Object(vec![("t", string(tag)), ("c", content)])
+}
+
+/// A value of a Pandoc type without content (`{"t":TAG}`).
+fn leaf(tag: &str) -> Json {
+    Json::Object(vec![("t", string(tag))])
+}
+
+/// The `Attr` of a block or inline: the identifier, the classes and key value pairs.
+fn attr(id: &str, classes: &[&str], pairs: &[(&str, &str)]) -> Json {
+    array(vec![
+        string(id),
+        array(classes.iter().map(|&class| string(class))),
+        array(
+            pairs
+                .iter()
+                .map(|&(key, value)| array(vec![string(key), string(value)])),
+        ),
+    ])
+}
+
+fn raw(tag: &str, format: &str, value: &str) -> Json {
+    node(
+        tag,
+        array(vec![string(format.to_lowercase()), string(value)]),
+    )
+}
+
+fn code_block(classes: &[&str], value: &str) -> Json {
+    node(
+        "CodeBlock",
+        array(vec![
+            attr("", classes, &[]),
+            string(value.trim_end_matches('\n')),
+        ]),
+    )
+}
+
+/// Splits the text into `Str`, `Space` and `SoftBreak` inlines.
+fn text(s: &str, inlines: &mut Vec<Json>) {
+    let mut word = String::new();
+    // whether the whitespace before the current word contains a newline
+    let mut space = None;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            if !word.is_empty() {
+                inlines.push(node("Str", string(mem::take(&mut word))));
+            }
+            space = Some(space.unwrap_or(false) || c == '\n');
+        } else {
+            if let Some(newline) = space.take() {
+                inlines.push(leaf(if newline { "SoftBreak" } else { "Space" }));
+            }
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        inlines.push(node("Str", string(word)));
+    }
+    if let Some(newline) = space {
+        inlines.push(leaf(if newline { "SoftBreak" } else { "Space" }));
+    }
+}
+
+/// Removes the whitespace at the start and the end.
+fn trim(mut inlines: Vec<Json>) -> Vec<Json> {
+    let is_space = |inline: &Json| *inline == leaf("Space") || *inline == leaf("SoftBreak");
+    while inlines.last().is_some_and(is_space) {
+        inlines.pop();
+    }
+    let start = inlines
+        .iter()
+        .position(|inline| !is_space(inline))
+        .unwrap_or(inlines.len());
+    inlines.split_off(start)
+}
+
+/// Maps LaTeX fragments in math delimiters to `Math` and all others to raw LaTeX.
+fn latex(value: &str) -> Json {
+    let math = |kind, value: &str| node("Math", array(vec![leaf(kind), string(value)]));
+    let delimiters = [
+        ("\\(", "\\)", "InlineMath"),
+        ("\\[", "\\]", "DisplayMath"),
+        ("$$", "$$", "DisplayMath"),
+        ("$", "$", "InlineMath"),
+    ];
+    delimiters
+        .iter()
+        .find(|(open, close, _)| {
+            value.len() >= open.len() + close.len()
+                && value.starts_with(open)
+                && value.ends_with(close)
+        })
+        .map_or_else(
+            || raw("RawInline", "latex", value),
+            |(open, close, kind)| math(kind, &value[open.len()..value.len() - close.len()]),
+        )
+}
+
+fn is_image(path: &LinkPath) -> bool {
+    let extensions = [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"];
+    match path {
+        LinkPath::File(path) | LinkPath::Protocol { path, .. } => {
+            let path = path.to_lowercase();
+            extensions.iter().any(|extension| path.ends_with(extension))
+        }
+        _ => false,
+    }
+}
+
+fn children<P: Parent<Vec<ElementSet>>>(parent: &P) -> &[ElementSet] {
+    parent.content().map_or(&[], |content| content.value())
+}
+
+struct Exporter<'a> {
+    /// Used to find the definitions of footnotes.
+    index: DocumentIndex<'a>,
+}
+
+impl<'a> Exporter<'a> {
+    fn headline(&self, headline: &Headline, blocks: &mut Vec<Json>) {
+        let filter = SubtreeFilter {
+            include_commented: false,
+            include_archived: true,
+        };
+        if filter.skips(headline) {
+            return;
+        }
+        let mut title = Vec::new();
+        if let Some(keyword) = &headline.todo_keyword {
+            let (class, keyword) = match keyword {
+                TodoKeyword::Todo(keyword) => ("todo", keyword),
+                TodoKeyword::Done(keyword) => ("done", keyword),
+            };
+            title.push(node(
+                "Span",
+                array(vec![
+                    attr("", &[class, keyword], &[]),
+                    array(vec![node("Str", string(keyword.as_str()))]),
+                ]),
+            ));
+            title.push(leaf("Space"));
+        }
+        if let Some(objects) = &headline.title {
+            let objects: Vec<StandardSet> = objects.iter().cloned().map(Into::into).collect();
+            title.extend(self.inlines(&objects));
+        }
+        for tag in &headline.tags {
+            title.push(leaf("Space"));
+            let small_caps = node("SmallCaps", array(vec![node("Str", string(tag.as_str()))]));
+            title.push(node(
+                "Span",
+                array(vec![
+                    attr("", &["tag"], &[("tag-name", tag)]),
+                    array(vec![small_caps]),
+                ]),
+            ));
+        }
+        let id = headline
+            .property("CUSTOM_ID")
+            .map(|id| id.into_owned())
+            .or_else(|| headline.property("ID").map(|id| format!("ID-{}", id)))
+            .unwrap_or_default();
+        blocks.push(node(
+            "Header",
+            array(vec![
+                Json::Number(headline.level.into()),
+                attr(&id, &[], &[]),
+                Json::Array(trim(title)),
+            ]),
+        ));
+
+        for content in headline.content().into_iter().flat_map(Spanned::value) {
+            match content {
+                HeadlineContentSet::Section(section) => self.elements(children(section), blocks),
+                HeadlineContentSet::Headline(child) => self.headline(child, blocks),
+            }
+        }
+    }
+
+    fn elements(&self, elements: &[ElementSet], blocks: &mut Vec<Json>) {
+        for element in elements {
+            self.element(element, blocks);
+        }
+    }
+
+    fn element(&self, element: &ElementSet, blocks: &mut Vec<Json>) {
+        let block = match element {
+            ElementSet::Paragraph(paragraph) => {
+                node("Para", Json::Array(self.paragraph(&paragraph.content)))
+            }
+            ElementSet::PlainList(list) => self.list(list),
+            ElementSet::Table(table) => self.table(table),
+            ElementSet::SrcBlock(block) => code_block(&[&block.language], &block.value),
+            ElementSet::ExampleBlock(block) => code_block(&[], &block.value),
+            ElementSet::FixedWidth(fixed_width) => code_block(&[], &fixed_width.value),
+            ElementSet::ExportBlock(block) => raw("RawBlock", &block.backend, &block.value),
+            ElementSet::LatexEnvironment(latex) => raw("RawBlock", "latex", &latex.value),
+            ElementSet::HorizontalRule(_) => leaf("HorizontalRule"),
+            ElementSet::QuoteBlock(quote) => {
+                let mut content = Vec::new();
+                self.elements(children(&**quote), &mut content);
+                node("BlockQuote", Json::Array(content))
+            }
+            ElementSet::CenterBlock(center) => self.div(
+                "center",
+                center.content().map_or("", |content| content.value()),
+            ),
+            ElementSet::SpecialBlock(special) => self.div(
+                &special.name,
+                special.content().map_or("", |content| content.value()),
+            ),
+            ElementSet::VerseBlock(verse) => {
+                let objects = verse.content().map_or(&[][..], |content| content.value());
+                let mut lines = vec![Vec::new()];
+                for inline in self.inlines(objects) {
+                    if inline == leaf("SoftBreak") || inline == leaf("LineBreak") {
+                        lines.push(Vec::new());
+                    } else {
+                        lines.last_mut().unwrap().push(inline);
+                    }
+                }
+                if lines.last().is_some_and(Vec::is_empty) {
+                    lines.pop();
+                }
+                node("LineBlock", array(lines.into_iter().map(Json::Array)))
+            }
+            ElementSet::DynamicBlock(block) => return self.elements(children(&**block), blocks),
+            _ => return,
+        };
+        blocks.push(block);
+    }
+
+    fn paragraph(&self, content: &SecondaryString<StandardSet>) -> Vec<Json> {
+        trim(self.inlines(content.iter()))
+    }
+
+    /// Exports the content of a center or special block (which is not parsed) to a `Div`.
+    fn div(&self, class: &str, content: &str) -> Json {
+        let mut blocks = Vec::new();
+        match parse_document(content) {
+            Ok(document) => {
+                if let Some(preface) = &document.preface {
+                    self.elements(children(preface), &mut blocks);
+                }
+            }
+            Err(_) => {
+                let mut inlines = Vec::new();
+                text(content, &mut inlines);
+                blocks.push(node("Para", Json::Array(trim(inlines))));
+            }
+        }
+        node(
+            "Div",
+            array(vec![attr("", &[class], &[]), Json::Array(blocks)]),
+        )
+    }
+
+    fn list(&self, list: &PlainList) -> Json {
+        let items = list.content().map_or(&[][..], |content| content.value());
+        match list.kind() {
+            ListKind::Unordered => node(
+                "BulletList",
+                array(items.iter().map(|item| Json::Array(self.item(item)))),
+            ),
+            ListKind::Ordered => {
+                let (start, style, delimiter) = match items.first().map(|item| &item.kind) {
+                    Some(ItemKind::Ordered { bullet, counter }) => {
+                        let start = match counter {
+                            Some(Counter::Number(number)) => *number as i64,
+                            Some(Counter::Letter(letter)) => {
+                                i64::from(letter.to_ascii_lowercase() as u8 - b'a' + 1)
+                            }
+                            None => 1,
+                        };
+                        let style = match bullet.counter {
+                            Counter::Number(_) => "Decimal",
+                            Counter::Letter(letter) if letter.is_uppercase() => "UpperAlpha",
+                            Counter::Letter(_) => "LowerAlpha",
+                        };
+                        let delimiter = match bullet.delimiter {
+                            CounterDelimiter::Period => "Period",
+                            CounterDelimiter::Parenthesis => "OneParen",
+                        };
+                        (start, style, delimiter)
+                    }
+                    _ => (1, "Decimal", "Period"),
+                };
+                node(
+                    "OrderedList",
+                    array(vec![
+                        array(vec![Json::Number(start), leaf(style), leaf(delimiter)]),
+                        array(items.iter().map(|item| Json::Array(self.item(item)))),
+                    ]),
+                )
+            }
+            ListKind::Description => node(
+                "DefinitionList",
+                array(items.iter().map(|item| {
+                    let tag = match &item.kind {
+                        ItemKind::Description { tag, .. } => {
+                            trim(self.inlines(&parse_objects(tag)))
+                        }
+                        _ => Vec::new(),
+                    };
+                    array(vec![
+                        Json::Array(tag),
+                        array(vec![Json::Array(self.item(item))]),
+                    ])
+                })),
+            ),
+        }
+    }
+
+    /// Returns the blocks of the item. Paragraphs become `Plain` blocks (like in tight lists).
+    fn item(&self, item: &Item) -> Vec<Json> {
+        let mut checkbox = item.checkbox.as_ref().map(|checkbox| {
+            let checkbox = match checkbox {
+                Checkbox::Checked => "☒",
+                Checkbox::Unchecked | Checkbox::Partial => "☐",
+            };
+            vec![node("Str", string(checkbox)), leaf("Space")]
+        });
+        let mut blocks = Vec::new();
+        for element in children(item) {
+            match element {
+                ElementSet::Paragraph(paragraph) => {
+                    let mut inlines = checkbox.take().unwrap_or_default();
+                    inlines.extend(self.paragraph(&paragraph.content));
+                    blocks.push(node("Plain", Json::Array(inlines)));
+                }
+                element => self.element(element, &mut blocks),
+            }
+        }
+        if let Some(checkbox) = checkbox {
+            blocks.insert(0, node("Plain", Json::Array(trim(checkbox))));
+        }
+        blocks
+    }
+
+    /// Exports the table with the rows before the first rule as the header (if there are rows
+    /// after it).
+    fn table(&self, table: &Table) -> Json {
+        let content = table.content().map_or(&[][..], |content| content.value());
+        let mut groups: Vec<Vec<&[TableCell]>> = vec![Vec::new()];
+        let mut lines = Vec::new();
+        for row in content {
+            match row {
+                TableContent::Org(row) => match &row.kind {
+                    TableRowKind::Normal(cells) => groups.last_mut().unwrap().push(cells.value()),
+                    TableRowKind::Rule if groups.last().is_some_and(|rows| !rows.is_empty()) => {
+                        groups.push(Vec::new())
+                    }
+                    TableRowKind::Rule => {}
+                },
+                TableContent::TableEl(line) => lines.push(line.as_str()),
+            }
+        }
+        if !lines.is_empty() {
+            // Pandoc can't read table.el tables from JSON
+            return code_block(&[], &lines.join("\n"));
+        }
+        groups.retain(|rows| !rows.is_empty());
+        let head = if groups.len() > 1 {
+            groups.remove(0)
+        } else {
+            Vec::new()
+        };
+        let body = groups.concat();
+        let columns = head.iter().chain(&body).map(|row| row.len()).max();
+
+        let rows = |rows: Vec<&[TableCell]>| {
+            array(rows.into_iter().map(|row| {
+                let cells = row.iter().map(|cell| {
+                    let objects: Vec<StandardSet> = cell
+                        .content
+                        .value()
+                        .iter()
+                        .cloned()
+                        .map(Into::into)
+                        .collect();
+                    let inlines = trim(self.inlines(&objects));
+                    let blocks = if inlines.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![node("Plain", Json::Array(inlines))]
+                    };
+                    array(vec![
+                        attr("", &[], &[]),
+                        leaf("AlignDefault"),
+                        Json::Number(1),
+                        Json::Number(1),
+                        Json::Array(blocks),
+                    ])
+                });
+                array(vec![attr("", &[], &[]), array(cells)])
+            }))
+        };
+        let column = || array(vec![leaf("AlignDefault"), leaf("ColWidthDefault")]);
+        node(
+            "Table",
+            array(vec![
+                attr("", &[], &[]),
+                self.caption(table.affiliated_keywords.as_ref()),
+                array((0..columns.unwrap_or(0)).map(|_| column())),
+                array(vec![attr("", &[], &[]), rows(head)]),
+                array(vec![array(vec![
+                    attr("", &[], &[]),
+                    Json::Number(0),
+                    array(vec![]),
+                    rows(body),
+                ])]),
+                array(vec![attr("", &[], &[]), array(vec![])]),
+            ]),
+        )
+    }
+
+    /// Exports the `#+CAPTION:` keywords of an element. The first optional value is the short
+    /// caption.
+    fn caption(&self, keywords: Option<&Spanned<AffiliatedKeywords>>) -> Json {
+        let captions: Vec<_> = keywords
+            .into_iter()
+            .flat_map(|keywords| keywords.value().captions())
+            .collect();
+        let short = captions
+            .iter()
+            .find_map(|caption| caption.optional().as_ref())
+            .map_or(Json::Null, |short| {
+                Json::Array(trim(self.inlines(short.iter())))
+            });
+        let mut long = Vec::new();
+        for caption in &captions {
+            if !long.is_empty() {
+                long.push(leaf("Space"));
+            }
+            long.extend(trim(self.inlines(caption.value().iter())));
+        }
+        let blocks = if long.is_empty() {
+            Vec::new()
+        } else {
+            vec![node("Plain", Json::Array(long))]
+        };
+        array(vec![short, Json::Array(blocks)])
+    }
+
+    fn inlines<'b>(&self, objects: impl IntoIterator<Item = &'b StandardSet>) -> Vec<Json> {
+        let mut inlines = Vec::new();
+        for object in objects {
+            self.object(object, &mut inlines);
+        }
+        inlines
+    }
+
+    fn object(&self, object: &StandardSet, inlines: &mut Vec<Json>) {
+        let inline = match object {
+            StandardSet::RawString(s) => return text(s, inlines),
+            StandardSet::LineBreak(_) => leaf("LineBreak"),
+            StandardSet::Entity(entity) => match ORG_ENTITIES.get(entity.name.as_str()) {
+                Some(replacement) => node("Str", string(replacement.utf8)),
+                None => return text(&entity.to_string(), inlines),
+            },
+            StandardSet::ExportSnippet(snippet) => {
+                raw("RawInline", &snippet.backend, &snippet.value)
+            }
+            StandardSet::FootnoteReference(reference) => self.footnote(reference),
+            StandardSet::InlineSrcBlock(src) => node(
+                "Code",
+                array(vec![
+                    attr("", &[&src.lang], &[]),
+                    string(src.value.as_str()),
+                ]),
+            ),
+            StandardSet::InlineBabelCall(_) => return,
+            StandardSet::LatexFragment(fragment) => latex(&fragment.value),
+            StandardSet::Link(link) => return self.link(link, inlines),
+            StandardSet::RadioTarget(radio) => {
+                let target: Vec<StandardSet> =
+                    radio.target.iter().cloned().map(Into::into).collect();
+                return inlines.extend(self.inlines(&target));
+            }
+            StandardSet::Subscript(subscript) => node(
+                "Subscript",
+                Json::Array(self.inlines(subscript.content.iter())),
+            ),
+            StandardSet::Superscript(superscript) => node(
+                "Superscript",
+                Json::Array(self.inlines(superscript.content.iter())),
+            ),
+            StandardSet::Target(target) => node(
+                "Span",
+                array(vec![attr(&target.target, &[], &[]), array(vec![])]),
+            ),
+            StandardSet::TextMarkup(markup) => self.markup(markup),
+            object => return text(&object.to_string(), inlines),
+        };
+        inlines.push(inline);
+    }
+
+    fn markup(&self, markup: &TextMarkup) -> Json {
+        let (tag, content) = match &markup.kind {
+            TextMarkupKind::Bold(content) => ("Strong", content),
+            TextMarkupKind::Italic(content) => ("Emph", content),
+            TextMarkupKind::Underline(content) => ("Underline", content),
+            TextMarkupKind::StrikeThrough(content) => ("Strikeout", content),
+            TextMarkupKind::Code(content) | TextMarkupKind::Verbatim(content) => {
+                return node(
+                    "Code",
+                    array(vec![attr("", &[], &[]), string(content.as_str())]),
+                )
+            }
+        };
+        node(tag, Json::Array(self.inlines(content.iter())))
+    }
+
+    /// Links to images without a description become images.
+    fn link(&self, link: &Link, inlines: &mut Vec<Json>) {
+        let (url, description) = match &link.link {
+            LinkFormat::Radio(s) => return text(s, inlines),
+            LinkFormat::Angle(s) | LinkFormat::Plain(s) => (s.clone(), None),
+            LinkFormat::Bracket(path, _, None) if is_image(path) => {
+                let target = array(vec![string(link_url(path)), string("")]);
+                inlines.push(node(
+                    "Image",
+                    array(vec![attr("", &[], &[]), array(vec![]), target]),
+                ));
+                return;
+            }
+            LinkFormat::Bracket(path, _, description) => {
+                let description = match description {
+                    Some(description) => {
+                        let description: Vec<StandardSet> =
+                            description.iter().cloned().map(Into::into).collect();
+                        self.inlines(&description)
+                    }
+                    None => {
+                        let mut inlines = Vec::new();
+                        text(&path.to_string(), &mut inlines);
+                        inlines
+                    }
+                };
+                (link_url(path), Some(description))
+            }
+        };
+        let description = description.unwrap_or_else(|| vec![node("Str", string(url.as_str()))]);
+        inlines.push(node(
+            "Link",
+            array(vec![
+                attr("", &[], &[]),
+                Json::Array(description),
+                array(vec![string(url), string("")]),
+            ]),
+        ));
+    }
+
+    /// Footnotes become notes with the content of their definition. References to undefined
+    /// footnotes are kept as text.
+    fn footnote(&self, reference: &FootnoteReference) -> Json {
+        let definition = reference
+            .definition()
+            .map(FootnoteDefinitionRef::Inline)
+            .or_else(|| {
+                reference
+                    .label()
+                    .and_then(|label| self.index.footnote_definition(label))
+            });
+        let blocks = match definition {
+            Some(FootnoteDefinitionRef::Element(definition)) => {
+                let mut blocks = Vec::new();
+                self.elements(children(definition), &mut blocks);
+                blocks
+            }
+            Some(FootnoteDefinitionRef::Inline(definition)) => {
+                vec![node("Para", Json::Array(self.paragraph(definition)))]
+            }
+            None => return node("Str", string(reference.to_string())),
+        };
+        node("Note", Json::Array(blocks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn output() -> Value {
+        let document = Document::parse(
+            "#+TITLE: Notes\n\
+             * TODO Tasks :work:\n\
+             Some *bold* text[fn:1] and [[https://orgmode.org][a link]].\n\
+             - [X] done\n\
+             - item\n\
+             #+BEGIN_SRC rust\n\
+             fn main() {}\n\
+             #+END_SRC\n\
+             #+CAPTION[Short]: The *numbers*\n\
+             | a | b |\n\
+             |---+---|\n\
+             | 1 | 2 |\n\
+             \n\
+             [fn:1] The note.\n\
+             * COMMENT Hidden\n",
+        )
+        .unwrap();
+        serde_json::from_str(&json(&document)).unwrap()
+    }
+
+    fn block(index: usize) -> Value {
+        output()["blocks"][index].clone()
+    }
+
+    fn text(s: &str) -> Value {
+        json!({"t": "Str", "c": s})
+    }
+
+    fn space() -> Value {
+        json!({"t": "Space"})
+    }
+
+    fn plain(inlines: Value) -> Value {
+        json!({"t": "Plain", "c": inlines})
+    }
+
+    fn empty_attr() -> Value {
+        json!(["", [], []])
+    }
+
+    #[test]
+    fn meta() {
+        let output = output();
+        assert_eq!(output["pandoc-api-version"], json!([1, 23, 1]));
+        assert_eq!(output["meta"]["title"]["c"], json!([text("Notes")]));
+        assert_eq!(output["blocks"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn headline() {
+        assert_eq!(
+            block(0),
+            json!({"t": "Header", "c": [1, empty_attr(), [
+                {"t": "Span", "c": [["", ["todo", "TODO"], []], [text("TODO")]]},
+                space(),
+                text("Tasks"),
+                space(),
+                {"t": "Span", "c": [["", ["tag"], [["tag-name", "work"]]],
+                    [{"t": "SmallCaps", "c": [text("work")]}]]},
+            ]]})
+        );
+    }
+
+    #[test]
+    fn paragraph() {
+        assert_eq!(
+            block(1),
+            json!({"t": "Para", "c": [
+                text("Some"),
+                space(),
+                {"t": "Strong", "c": [text("bold")]},
+                space(),
+                text("text"),
+                {"t": "Note", "c": [{"t": "Para", "c": [text("The"), space(), text("note.")]}]},
+                space(),
+                text("and"),
+                space(),
+                {"t": "Link", "c": [empty_attr(), [text("a"), space(), text("link")],
+                    ["https://orgmode.org", ""]]},
+                text("."),
+            ]})
+        );
+    }
+
+    #[test]
+    fn list() {
+        assert_eq!(
+            block(2),
+            json!({"t": "BulletList", "c": [
+                [plain(json!([text("☒"), space(), text("done")]))],
+                [plain(json!([text("item")]))],
+            ]})
+        );
+    }
+
+    #[test]
+    fn src_block() {
+        assert_eq!(
+            block(3),
+            json!({"t": "CodeBlock", "c": [["", ["rust"], []], "fn main() {}"]})
+        );
+    }
+
+    #[test]
+    fn table() {
+        let table = &block(4)["c"];
+        assert_eq!(
+            table[1],
+            json!([
+                [text("Short")],
+                [plain(
+                    json!([text("The"), space(), {"t": "Strong", "c": [text("numbers")]}])
+                )]
+            ])
+        );
+        assert_eq!(table[2].as_array().unwrap().len(), 2);
+        assert_eq!(table[3][1][0][1][0][4], json!([plain(json!([text("a")]))]));
+        assert_eq!(
+            table[4][0][3][0][1][1][4],
+            json!([plain(json!([text("2")]))])
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Publishing the files of a workspace as a site (like `org-publish`).
+//!
+//! A [`Project`] selects the files of a [`Workspace`] in its base directory and exports them
+//! with a [`Backend`] to its publishing directory. The directory structure is kept. Links to
+//! other published files (`[[file:notes/a.org]]`) are changed to link to the exported files
+//! instead, also between projects.
+//!
+//! A project can generate a [`Sitemap`]: an org file with links to all published files of the
+//! project. It is published like the other files.
+//!
+//! [`publish`] only returns the exported files. They are written to disk with [`write`].
+
+use super::{outline, pandoc};
+use crate::parsing::{parse_with_diagnostics, ParseConfig};
+use crate::types::document::Document;
+use crate::types::objects::{LinkFormat, LinkPath};
+use crate::workspace::{for_each_link, normalize, relative_to, Workspace};
+use itertools::Itertools;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The format the files of a [`Project`] are exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The org text of the documents (only the links are changed).
+    Org,
+    /// The JSON AST of Pandoc (see [`pandoc`]).
+    Pandoc,
+    /// The headline tree as OPML (see [`outline`]).
+    Opml,
+}
+
+impl Backend {
+    /// The file extension of the exported files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Backend::Org => "org",
+            Backend::Pandoc => "json",
+            Backend::Opml => "opml",
+        }
+    }
+
+    pub fn export(self, document: &Document) -> String {
+        match self {
+            Backend::Org => document.to_string(),
+            Backend::Pandoc => pandoc::json(document),
+            Backend::Opml => outline::opml(document),
+        }
+    }
+}
+
+/// The settings of a set of files published together.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// The directory containing the files of the project.
+    pub base_directory: PathBuf,
+    /// The directory the exported files are put in (relative to the target directory of
+    /// [`publish`]).
+    pub publishing_directory: PathBuf,
+    pub backend: Backend,
+    /// Publish the files in subdirectories of the base directory too.
+    pub recursive: bool,
+    /// Files with a path (relative to the base directory) matching the regex are not published.
+    pub exclude: Option<Regex>,
+    pub sitemap: Option<Sitemap>,
+}
+
+/// The settings of the sitemap generated for a [`Project`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sitemap {
+    /// The path of the org file (relative to the base directory).
+    pub filename: PathBuf,
+    pub title: String,
+}
+
+/// An exported file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PublishedFile {
+    /// The path of the file in the workspace (or of the sitemap in the base directory).
+    pub source: PathBuf,
+    /// The path of the exported file in the target directory.
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl Project {
+    /// Creates a project publishing the files directly in the base directory to org files in
+    /// the target directory.
+    pub fn new(base_directory: impl Into<PathBuf>) -> Self {
+        Project {
+            base_directory: base_directory.into(),
+            publishing_directory: PathBuf::new(),
+            backend: Backend::Org,
+            recursive: false,
+            exclude: None,
+            sitemap: None,
+        }
+    }
+
+    /// Returns the files of the workspace published by this project and their paths relative
+    /// to the base directory.
+    fn files<'a>(&self, workspace: &'a Workspace) -> Vec<(PathBuf, &'a Document)> {
+        let base = normalize(&self.base_directory);
+        workspace
+            .files()
+            .filter_map(|(path, document)| {
+                let relative = normalize(path).strip_prefix(&base).ok()?.to_path_buf();
+                let excluded = self
+                    .exclude
+                    .as_ref()
+                    .is_some_and(|exclude| exclude.is_match(&relative.to_string_lossy()));
+                if excluded || (!self.recursive && relative.components().count() > 1) {
+                    None
+                } else {
+                    Some((relative, document))
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for Sitemap {
+    fn default() -> Self {
+        Sitemap {
+            filename: PathBuf::from("sitemap.org"),
+            title: "Sitemap".to_string(),
+        }
+    }
+}
+
+impl Sitemap {
+    /// Returns the sitemap of the files (with their paths relative to the base directory).
+    ///
+    /// Every file is linked with its `#+TITLE:` or else its file name as description.
+    fn document(&self, files: &[(PathBuf, &Document)]) -> Document {
+        let mut org = format!("#+TITLE: {}\n\n", self.title);
+        for (path, document) in files.iter().sorted_by_key(|(path, _)| path) {
+            if *path == self.filename {
+                continue;
+            }
+            let title = title(document).unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            org.push_str(&format!("- [[file:{}][{}]]\n", path.display(), title));
+        }
+        parse_with_diagnostics(&org, &ParseConfig::default()).0
+    }
+}
+
+/// Returns the `#+TITLE:` of the document.
+fn title(document: &Document) -> Option<String> {
+    document
+        .keywords()
+        .find(|keyword| keyword.key.eq_ignore_ascii_case("TITLE"))
+        .map(|keyword| keyword.value.value().iter().join("").trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+/// Exports the files of the projects to the target directory.
+///
+/// A file in several projects is published by each of them. Links to it are changed to link
+/// to the file exported by the last of them.
+pub fn publish(
+    workspace: &Workspace,
+    target: impl AsRef<Path>,
+    projects: &[Project],
+) -> Vec<PublishedFile> {
+    let target = normalize(target.as_ref());
+    let mut sources: Vec<(PathBuf, Cow<'_, Document>, PathBuf, Backend)> = Vec::new();
+    for project in projects {
+        let base = normalize(&project.base_directory);
+        let directory = normalize(&target.join(&project.publishing_directory));
+        let mut add = |relative: &Path, document| {
+            let path = directory
+                .join(relative)
+                .with_extension(project.backend.extension());
+            sources.push((base.join(relative), document, path, project.backend));
+        };
+        let files = project.files(workspace);
+        for (relative, document) in &files {
+            add(relative, Cow::Borrowed(*document));
+        }
+        if let Some(sitemap) = &project.sitemap {
+            add(&sitemap.filename, Cow::Owned(sitemap.document(&files)));
+        }
+    }
+
+    let published: HashMap<PathBuf, PathBuf> = sources
+        .iter()
+        .map(|(source, _, path, _)| (source.clone(), path.clone()))
+        .collect();
+    sources
+        .into_iter()
+        .map(|(source, document, path, backend)| {
+            let mut document = document.into_owned();
+            let source_directory = source.parent().unwrap_or_else(|| Path::new(""));
+            let directory = path.parent().unwrap_or_else(|| Path::new(""));
+            for_each_link(&mut document, &mut |link| {
+                let file = match &mut link.link {
+                    LinkFormat::Bracket(LinkPath::File(file), _, _) => file,
+                    _ => return false,
+                };
+                match published.get(&normalize(&source_directory.join(file.as_str()))) {
+                    Some(exported) => {
+                        *file = relative_to(exported, directory)
+                            .unwrap_or_else(|| exported.clone())
+                            .display()
+                            .to_string();
+                        true
+                    }
+                    None => false,
+                }
+            });
+            PublishedFile {
+                content: backend.export(&document),
+                source,
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Writes the published files to disk. Missing directories are created.
+pub fn write(files: &[PublishedFile]) -> io::Result<()> {
+    for file in files {
+        if let Some(directory) = file.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(&file.path, &file.content)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        let files = [
+            ("/site/index.org", "See [[file:notes/a.org::*Task][a]].\n"),
+            (
+                "/site/notes/a.org",
+                "#+TITLE: Notes\n[[file:../index.org]] [[file:/other/b.org]]\n",
+            ),
+            ("/site/drafts/c.org", "Draft\n"),
+            ("/other/b.org", "* Task\n"),
+        ];
+        for (path, org) in files.iter() {
+            workspace.insert(*path, Document::parse(org).unwrap());
+        }
+        workspace
+    }
+
+    fn site() -> Project {
+        Project {
+            recursive: true,
+            exclude: Some(Regex::new("^drafts/").unwrap()),
+            sitemap: Some(Sitemap::default()),
+            ..Project::new("/site")
+        }
+    }
+
+    fn publish_projects() -> Vec<PublishedFile> {
+        let other = Project {
+            publishing_directory: "other".into(),
+            backend: Backend::Opml,
+            ..Project::new("/other/")
+        };
+        publish(&workspace(), "/out", &[site(), other])
+    }
+
+    #[test]
+    fn published_paths() {
+        let paths: Vec<_> = publish_projects()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/out/index.org",
+                "/out/notes/a.org",
+                "/out/sitemap.org",
+                "/out/other/b.opml",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rewrite_links() {
+        let published = publish_projects();
+        assert_eq!(
+            published[0].content,
+            "See [[file:notes/a.org::*Task][a]].\n"
+        );
+        assert_eq!(
+            published[1].content,
+            "#+TITLE: Notes\n[[file:../index.org]] [[file:../other/b.opml]]\n"
+        );
+    }
+
+    #[test]
+    fn sitemap() {
+        assert_eq!(
+            publish_projects()[2].content,
+            "#+TITLE: Sitemap\n\n\
+             - [[file:index.org][index]]\n\
+             - [[file:notes/a.org][Notes]]\n"
+        );
+    }
+
+    #[test]
+    fn export_with_backend() {
+        let site = Project {
+            backend: Backend::Pandoc,
+            sitemap: None,
+            ..site()
+        };
+        let published = publish(&workspace(), "/out", &[site]);
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[1].path, PathBuf::from("/out/notes/a.json"));
+        assert!(published[1].content.contains("\"../index.json\""));
+        // files outside of the published projects are linked as they are
+        assert!(published[1].content.contains("\"/other/b.org\""));
+    }
+}
@@ -0,0 +1,79 @@
+//! Projects objects to plain text.
+//!
+//! The markup is removed: text markup is replaced by its content, entities by their UTF-8
+//! replacement and links by their description (or path). Line breaks become newlines.
+
+use super::ExportContext;
+use crate::entities::ORG_ENTITIES;
+use crate::types::objects::{LinkFormat, TextMarkupKind};
+use crate::types::StandardSet;
+
+/// The backends of export snippets and blocks included in plain text.
+pub const BACKENDS: [&str; 1] = ["ascii"];
+
+/// The attributes (e.g. `#+ATTR_ASCII: :lang fr`) used by this exporter.
+pub const ATTRIBUTES: &[&str] = &["lang"];
+
+/// Projects objects to plain text.
+pub fn objects<'a>(
+    objects: impl IntoIterator<Item = &'a StandardSet>,
+    context: &mut ExportContext<'_>,
+) -> String {
+    let mut text = String::new();
+    for object in objects {
+        write_object(&mut text, object, context);
+    }
+    text
+}
+
+fn write_object(text: &mut String, object: &StandardSet, context: &mut ExportContext<'_>) {
+    match object {
+        StandardSet::RawString(s) => text.push_str(s),
+        StandardSet::LineBreak(_) => text.push('\n'),
+        StandardSet::Entity(entity) => match ORG_ENTITIES.get(entity.name.as_str()) {
+            Some(replacement) => text.push_str(replacement.utf8),
+            None => text.push_str(&entity.to_string()),
+        },
+        StandardSet::ExportSnippet(snippet) => {
+            if let Some(value) = context.snippet(snippet) {
+                text.push_str(value);
+            }
+        }
+        StandardSet::Link(link) => match &link.link {
+            LinkFormat::Radio(s) | LinkFormat::Angle(s) | LinkFormat::Plain(s) => text.push_str(s),
+            LinkFormat::Bracket(_, _, Some(description)) => {
+                let description: Vec<StandardSet> =
+                    description.iter().cloned().map(Into::into).collect();
+                text.push_str(&objects(&description, context));
+            }
+            LinkFormat::Bracket(path, _, None) => text.push_str(&path.to_string()),
+        },
+        StandardSet::TextMarkup(markup) => match &markup.kind {
+            TextMarkupKind::Bold(content)
+            | TextMarkupKind::Italic(content)
+            | TextMarkupKind::Underline(content)
+            | TextMarkupKind::StrikeThrough(content) => {
+                text.push_str(&objects(content.iter(), context))
+            }
+            TextMarkupKind::Code(content) | TextMarkupKind::Verbatim(content) => {
+                text.push_str(content)
+            }
+        },
+        object => text.push_str(&object.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportOptions;
+    use crate::parsing::parse_objects;
+
+    #[test]
+    fn objects_to_text() {
+        let options = ExportOptions::default();
+        let mut context = ExportContext::new(&BACKENDS, &options);
+        let objects = parse_objects("*a* \\\\  \n[[#b][=c=]] d\\\\");
+        assert_eq!(super::objects(&objects, &mut context), "a \nc d\n");
+    }
+}
@@ -0,0 +1,233 @@
+//! Resolving `id:` links across the files of a [`Workspace`] (like org-id does).
+//!
+//! An [`IdRegistry`] indexes the `ID` properties of the headlines of all files. It resolves
+//! `[[id:ID]]` links to the file and headline with the id and finds the links pointing to a
+//! headline (its backlinks, e.g. for org-roam style notes).
+
+use crate::types::elements::Paragraph;
+use crate::types::greater_elements::Headline;
+use crate::types::objects::{Link, LinkFormat, LinkPath};
+use crate::types::visit::{self, Visitor};
+use crate::types::{Parent, Span};
+use crate::workspace::Workspace;
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::path::Path;
+
+/// The headlines with an `ID` property and the `id:` links of a workspace.
+#[derive(Debug, Clone)]
+pub struct IdRegistry<'a> {
+    ids: BTreeMap<String, IdLocation<'a>>,
+    duplicates: Vec<(String, IdLocation<'a>)>,
+    links: Vec<IdLink<'a>>,
+}
+
+/// The headline with an id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdLocation<'a> {
+    pub path: &'a Path,
+    pub headline: &'a Headline,
+    /// The span of the content of the headline (see [`Parent::content`]).
+    pub span: Option<Span>,
+}
+
+/// An `id:` link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdLink<'a> {
+    /// The id the link points to.
+    pub id: &'a str,
+    pub path: &'a Path,
+    pub link: &'a Link,
+    /// The span of the link. Only links in paragraphs have a span.
+    pub span: Option<Span>,
+}
+
+impl<'a> IdRegistry<'a> {
+    /// Indexes the ids and `id:` links of all files of the workspace.
+    ///
+    /// If several headlines have the same id the first one (by path and in document order) is
+    /// used. The others are returned by [`duplicates`](`IdRegistry::duplicates`).
+    pub fn new(workspace: &'a Workspace) -> Self {
+        let mut registry = IdRegistry {
+            ids: BTreeMap::new(),
+            duplicates: Vec::new(),
+            links: Vec::new(),
+        };
+        for (path, document) in workspace.files() {
+            let mut collector = Collector {
+                path,
+                ids: Vec::new(),
+                links: Vec::new(),
+                span: None,
+            };
+            visit::walk(document, &mut collector);
+            for (id, location) in collector.ids {
+                match registry.ids.entry(id) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(location);
+                    }
+                    Entry::Occupied(entry) => {
+                        registry.duplicates.push((entry.key().clone(), location))
+                    }
+                }
+            }
+            registry.links.extend(collector.links);
+        }
+        registry
+    }
+
+    /// Returns the headline with the id.
+    pub fn get(&self, id: &str) -> Option<&IdLocation<'a>> {
+        self.ids.get(id)
+    }
+
+    /// Returns the headline an `id:` link points to.
+    ///
+    /// Returns `None` for other links or if there is no headline with the id.
+    pub fn resolve(&self, link: &Link) -> Option<&IdLocation<'a>> {
+        match &link.link {
+            LinkFormat::Bracket(LinkPath::Id(id), _, _) => self.get(id),
+            _ => None,
+        }
+    }
+
+    /// Returns all ids and their headlines ordered by id.
+    pub fn ids(&self) -> impl Iterator<Item = (&str, &IdLocation<'a>)> {
+        self.ids
+            .iter()
+            .map(|(id, location)| (id.as_str(), location))
+    }
+
+    /// Returns the headlines whose id is already used by another headline.
+    pub fn duplicates(&self) -> &[(String, IdLocation<'a>)] {
+        &self.duplicates
+    }
+
+    /// Returns all `id:` links in document order (of the files ordered by path).
+    pub fn links(&self) -> &[IdLink<'a>] {
+        &self.links
+    }
+
+    /// Returns the links pointing to the id.
+    pub fn backlinks(&self, id: &str) -> Vec<&IdLink<'a>> {
+        self.links.iter().filter(|link| link.id == id).collect()
+    }
+
+    /// Returns the links pointing to ids no headline has.
+    pub fn broken_links(&self) -> Vec<&IdLink<'a>> {
+        self.links
+            .iter()
+            .filter(|link| !self.ids.contains_key(link.id))
+            .collect()
+    }
+}
+
+/// Collects the ids and `id:` links of a file.
+struct Collector<'a> {
+    path: &'a Path,
+    ids: Vec<(String, IdLocation<'a>)>,
+    links: Vec<IdLink<'a>>,
+    /// The span of the current object of a paragraph.
+    span: Option<Span>,
+}
+
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn visit_headline(&mut self, headline: &'a Headline) {
+        if let Some(id) = headline.property("ID") {
+            let location = IdLocation {
+                path: self.path,
+                headline,
+                span: headline
+                    .content()
+                    .and_then(|content| content.span().clone()),
+            };
+            self.ids.push((id.trim().to_string(), location));
+        }
+        visit::walk_headline(self, headline);
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &'a Paragraph) {
+        for (object, span) in paragraph.content.iter_spanned() {
+            self.span = span.cloned();
+            self.visit_object(object.into());
+        }
+        self.span = None;
+    }
+
+    fn visit_link(&mut self, link: &'a Link) {
+        if let LinkFormat::Bracket(LinkPath::Id(id), _, _) = &link.link {
+            self.links.push(IdLink {
+                id,
+                path: self.path,
+                link,
+                span: self.span.clone(),
+            });
+        }
+        visit::walk_link(self, link);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::document::Document;
+
+    const TOOLS: &str = "* Cargo\n:PROPERTIES:\n:ID: cargo\n:END:\n\
+                         Built for [[id:rust]] and [[id:go]].\n\
+                         * Copy\n:PROPERTIES:\n:ID: rust\n:END:\n";
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        let notes = "* Rust\n:PROPERTIES:\n:ID: rust\n:END:\nSee [[id:cargo][Cargo]].\n";
+        workspace.insert("notes.org", Document::parse(notes).unwrap());
+        workspace.insert("tools.org", Document::parse(TOOLS).unwrap());
+        workspace
+    }
+
+    #[test]
+    fn ids() {
+        let workspace = workspace();
+        let registry = IdRegistry::new(&workspace);
+        let ids: Vec<_> = registry
+            .ids()
+            .map(|(id, location)| (id, location.path.to_str().unwrap()))
+            .collect();
+        assert_eq!(ids, [("cargo", "tools.org"), ("rust", "notes.org")]);
+        let location = registry.get("cargo").unwrap();
+        let start = "* Cargo\n".len();
+        assert_eq!(location.span.as_ref().map(Span::start), Some(start));
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let workspace = workspace();
+        let registry = IdRegistry::new(&workspace);
+        assert_eq!(registry.duplicates().len(), 1);
+        assert_eq!(registry.duplicates()[0].1.path, Path::new("tools.org"));
+    }
+
+    #[test]
+    fn backlinks() {
+        let workspace = workspace();
+        let registry = IdRegistry::new(&workspace);
+        let backlinks = registry.backlinks("rust");
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].path, Path::new("tools.org"));
+        let start = TOOLS.find("[[id:rust]]").unwrap();
+        assert_eq!(
+            backlinks[0].span,
+            Some(Span::new(start, start + "[[id:rust]]".len()))
+        );
+        assert_eq!(
+            registry.resolve(backlinks[0].link).unwrap().path,
+            Path::new("notes.org")
+        );
+    }
+
+    #[test]
+    fn broken_links() {
+        let workspace = workspace();
+        let registry = IdRegistry::new(&workspace);
+        let broken: Vec<_> = registry.broken_links().iter().map(|link| link.id).collect();
+        assert_eq!(broken, ["go"]);
+    }
+}
@@ -0,0 +1,582 @@
+//! Converts markdown documents to org.
+//!
+//! The markdown is parsed with [pulldown-cmark](https://docs.rs/pulldown-cmark) (with tables
+//! and footnotes enabled) and then mapped to the corresponding org elements and objects:
+//!
+//! | Markdown                | Org                                          |
+//! |-------------------------|----------------------------------------------|
+//! | ATX and setext headers  | [`Headline`]s (nested by level)              |
+//! | headers in quotes/lists | bold [`Paragraph`]s                          |
+//! | paragraphs              | [`Paragraph`]s                               |
+//! | `*emphasis*`            | `/italic/`                                   |
+//! | `**strong**`            | `*bold*`                                     |
+//! | `` `code` ``            | `~code~`                                     |
+//! | `[text](url)`           | `[[url][text]]`                              |
+//! | `![alt](url)`           | `[[url]]` (displayed inline as an image)     |
+//! | hard line breaks        | [`LineBreak`]s                               |
+//! | block quotes            | [`QuoteBlock`]s                              |
+//! | fenced and indented code| [`SrcBlock`]s or [`ExampleBlock`]s           |
+//! | lists and task lists    | [`PlainList`]s (with [`Checkbox`]es)         |
+//! | tables                  | [`Table`]s                                   |
+//! | footnotes               | [`FootnoteReference`]s and [`FootnoteDefinition`]s |
+//! | thematic breaks         | [`HorizontalRule`]s                          |
+//! | HTML                    | `html` [`ExportBlock`]s and [`ExportSnippet`]s |
+//!
+//! Content before the first header becomes the [`Document::preface`].
+//!
+//! [`Headline`]: `crate::types::greater_elements::Headline`
+//! [`Paragraph`]: `crate::types::elements::Paragraph`
+//! [`LineBreak`]: `crate::types::objects::LineBreak`
+//! [`QuoteBlock`]: `crate::types::greater_elements::QuoteBlock`
+//! [`SrcBlock`]: `crate::types::elements::SrcBlock`
+//! [`ExampleBlock`]: `crate::types::elements::ExampleBlock`
+//! [`PlainList`]: `crate::types::greater_elements::PlainList`
+//! [`Checkbox`]: `crate::types::greater_elements::Checkbox`
+//! [`Table`]: `crate::types::greater_elements::Table`
+//! [`FootnoteReference`]: `crate::types::objects::FootnoteReference`
+//! [`FootnoteDefinition`]: `crate::types::greater_elements::FootnoteDefinition`
+//! [`HorizontalRule`]: `crate::types::elements::HorizontalRule`
+//! [`ExportBlock`]: `crate::types::elements::ExportBlock`
+//! [`ExportSnippet`]: `crate::types::objects::ExportSnippet`
+//! [`Document::preface`]: `crate::types::document::Document::preface`
+
+use crate::parsing::nest_headlines;
+use crate::types::document::Document;
+use crate::types::elements::{ExampleBlock, ExportBlock, HorizontalRule, Paragraph, SrcBlock};
+use crate::types::greater_elements::{
+    Checkbox, Counter, CounterDelimiter, FootnoteDefinition, Headline, HeadlineContentSet, Item,
+    ItemKind, OrderedBullet, PlainList, QuoteBlock, Section, Table, TableRow, TableRowKind,
+    UnorderedBullet,
+};
+use crate::types::objects::{
+    ExportSnippet, FootnoteReference, FootnoteReferenceKind, LineBreak, Link,
+    LinkDescriptionSetOfObjects, LinkFormat, LinkPath, TableCell, TextMarkup, TextMarkupKind,
+};
+use crate::types::{ElementSet, SecondaryString, Spanned, StandardSet, StandardSetNoLineBreak};
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use std::iter::Peekable;
+
+/// Converts a markdown document to an org [`Document`].
+///
+/// Every markdown document is valid so this can't fail. Markdown constructs that have no
+/// representation in org are converted to plain text.
+///
+/// # Example
+///
+/// ```ignore
+/// use rust_orgmode::import::markdown;
+///
+/// let document = markdown::convert("# Title\n\nSome *emphasized* text.\n");
+/// assert_eq!(document.to_string(), "* Title\nSome /emphasized/ text.\n");
+/// ```
+pub fn convert(input: &str) -> Document {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    Converter {
+        events: Parser::new_ext(input, options).peekable(),
+    }
+    .document()
+}
+
+/// Consumes the markdown events and builds the org elements.
+///
+/// Every method is called after the `Start` event of the respective markdown tag has been
+/// consumed and consumes everything up to and including the matching `End` event.
+struct Converter<'a, I: Iterator<Item = Event<'a>>> {
+    events: Peekable<I>,
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>> Converter<'a, I> {
+    fn document(&mut self) -> Document {
+        let mut preface = Vec::new();
+        // Headlines with the elements of their section. The sections are only added once all
+        // elements are known.
+        let mut headlines: Vec<(Headline, Vec<ElementSet>)> = Vec::new();
+
+        while let Some(event) = self.events.next() {
+            if let Event::Start(Tag::Header(level)) = event {
+                let title = self.inlines();
+                let headline = Headline {
+                    level: level as u32,
+                    title: title_from_objects(title),
+                    ..Headline::default()
+                };
+                headlines.push((headline, Vec::new()));
+            } else if let Some(element) = self.element(event) {
+                match headlines.last_mut() {
+                    Some((_, content)) => content.push(element),
+                    None => preface.push(element),
+                }
+            }
+        }
+
+        let headlines = headlines.into_iter().map(|(mut headline, content)| {
+            if !content.is_empty() {
+                headline.push_content(Some(HeadlineContentSet::Section(Section::new(
+                    Spanned::new(content),
+                ))));
+            }
+            headline
+        });
+
+        Document {
+            preface: if preface.is_empty() {
+                None
+            } else {
+                Some(Section::new(Spanned::new(preface)))
+            },
+            headlines: nest_headlines(headlines),
+        }
+    }
+
+    /// Converts a block level event (and all events belonging to it).
+    ///
+    /// Returns `None` for events that can't appear on the block level and are ignored.
+    fn element(&mut self, event: Event<'a>) -> Option<ElementSet> {
+        match event {
+            Event::Start(Tag::Paragraph) => Some(paragraph(self.inlines()).into()),
+            Event::Start(Tag::Rule) => {
+                self.skip_to_end();
+                Some(HorizontalRule::new().into())
+            }
+            Event::Start(Tag::BlockQuote) => {
+                Some(QuoteBlock::new(Spanned::new(self.elements())).into())
+            }
+            Event::Start(Tag::CodeBlock(info)) => {
+                let value = self.text();
+                Some(match info.split_whitespace().next() {
+                    Some(language) => SrcBlock::new(language.to_string(), value).into(),
+                    None => ExampleBlock::new(value).into(),
+                })
+            }
+            Event::Start(Tag::List(start)) => Some(self.list(start).into()),
+            Event::Start(Tag::FootnoteDefinition(label)) => Some(
+                FootnoteDefinition::new(label.into_owned(), Spanned::new(self.elements())).into(),
+            ),
+            Event::Start(Tag::Table(_)) => Some(self.table().into()),
+            Event::Start(Tag::Header(_)) => {
+                // only headers on the top level can be headlines
+                let title = self.inlines();
+                if title.is_empty() {
+                    None
+                } else {
+                    let bold = TextMarkup {
+                        kind: TextMarkupKind::Bold(title.into()),
+                    };
+                    Some(paragraph(vec![StandardSet::TextMarkup(bold)]).into())
+                }
+            }
+            Event::Html(html) => {
+                let mut value = html.into_owned();
+                while let Some(Event::Html(_)) = self.events.peek() {
+                    if let Some(Event::Html(html)) = self.events.next() {
+                        value.push_str(&html);
+                    }
+                }
+                Some(ExportBlock::new("html", value).into())
+            }
+            Event::Start(_) => {
+                self.skip_to_end();
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts all block level elements until the end of the current tag.
+    fn elements(&mut self) -> Vec<ElementSet> {
+        let mut elements = Vec::new();
+        while let Some(event) = self.events.next() {
+            if let Event::End(_) = event {
+                break;
+            }
+            elements.extend(self.element(event));
+        }
+        elements
+    }
+
+    fn list(&mut self, start: Option<usize>) -> PlainList {
+        let mut items = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::Start(Tag::Item) => {
+                    let kind = match start {
+                        Some(start) => ItemKind::Ordered {
+                            bullet: OrderedBullet {
+                                counter: Counter::Number((start + items.len()) as u64),
+                                delimiter: CounterDelimiter::Period,
+                            },
+                            counter: None,
+                        },
+                        None => ItemKind::Unordered {
+                            bullet: UnorderedBullet::Minus,
+                        },
+                    };
+                    items.push(self.item(kind));
+                }
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        PlainList::new(Spanned::new(items))
+    }
+
+    /// Converts a list item.
+    ///
+    /// Items of tight lists contain the inline events directly instead of wrapping them in a
+    /// paragraph. These are collected into paragraphs here.
+    fn item(&mut self, kind: ItemKind) -> Item {
+        let mut content = Vec::new();
+        let mut objects = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::End(_) => break,
+                Event::Start(Tag::Paragraph)
+                | Event::Start(Tag::Rule)
+                | Event::Start(Tag::BlockQuote)
+                | Event::Start(Tag::CodeBlock(_))
+                | Event::Start(Tag::List(_))
+                | Event::Start(Tag::FootnoteDefinition(_))
+                | Event::Start(Tag::Table(_))
+                | Event::Start(Tag::Header(_))
+                | Event::Html(_) => {
+                    if !objects.is_empty() {
+                        content.push(paragraph(std::mem::take(&mut objects)).into());
+                    }
+                    content.extend(self.element(event));
+                }
+                event => self.inline(event, &mut objects),
+            }
+        }
+        if !objects.is_empty() {
+            content.push(paragraph(objects).into());
+        }
+
+        let checkbox = take_checkbox(&mut content);
+        let mut item = Item::new(kind, Spanned::new(content));
+        item.checkbox = checkbox;
+        item
+    }
+
+    fn table(&mut self) -> Table {
+        let mut rows = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::Start(Tag::TableHead) => {
+                    rows.push(self.table_row());
+                    rows.push(TableRow {
+                        kind: TableRowKind::Rule,
+                    });
+                }
+                Event::Start(Tag::TableRow) => rows.push(self.table_row()),
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        Table::new(Spanned::new(rows))
+    }
+
+    fn table_row(&mut self) -> TableRow {
+        let mut cells = Vec::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::Start(Tag::TableCell) => {
+                    let mut objects = self.inlines();
+                    trim_objects(&mut objects);
+                    cells.push(TableCell::new(
+                        objects.into_iter().map(Into::into).collect(),
+                    ));
+                }
+                Event::End(_) => break,
+                _ => {}
+            }
+        }
+        TableRow {
+            kind: TableRowKind::Normal(Spanned::new(cells)),
+        }
+    }
+
+    /// Converts all inline events until the end of the current tag.
+    fn inlines(&mut self) -> Vec<StandardSet> {
+        let mut objects = Vec::new();
+        while let Some(event) = self.events.next() {
+            if let Event::End(_) = event {
+                break;
+            }
+            self.inline(event, &mut objects);
+        }
+        objects
+    }
+
+    fn inline(&mut self, event: Event<'a>, objects: &mut Vec<StandardSet>) {
+        let object = match event {
+            Event::Text(text) => StandardSet::RawString(text.into_owned()),
+            Event::SoftBreak => StandardSet::RawString("\n".to_string()),
+            Event::HardBreak => StandardSet::LineBreak(LineBreak { spaces: 0 }),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                StandardSet::ExportSnippet(ExportSnippet {
+                    backend: "html".to_string(),
+                    value: html.into_owned(),
+                })
+            }
+            Event::FootnoteReference(label) => StandardSet::FootnoteReference(FootnoteReference {
+                kind: FootnoteReferenceKind::Normal {
+                    label: label.into_owned(),
+                },
+            }),
+            Event::Start(Tag::Emphasis) => StandardSet::TextMarkup(TextMarkup {
+                kind: TextMarkupKind::Italic(self.inlines().into()),
+            }),
+            Event::Start(Tag::Strong) => StandardSet::TextMarkup(TextMarkup {
+                kind: TextMarkupKind::Bold(self.inlines().into()),
+            }),
+            Event::Start(Tag::Code) => StandardSet::TextMarkup(TextMarkup {
+                kind: TextMarkupKind::Code(self.text()),
+            }),
+            Event::Start(Tag::Link(url, _title)) => {
+                let description = self.inlines();
+                StandardSet::Link(link(&url, description))
+            }
+            Event::Start(Tag::Image(url, _title)) => {
+                self.skip_to_end();
+                StandardSet::Link(link(&url, Vec::new()))
+            }
+            Event::Start(_) => {
+                objects.extend(self.inlines());
+                return;
+            }
+            Event::End(_) => return,
+        };
+        push_object(objects, object);
+    }
+
+    /// Concatenates the text until the end of the current tag.
+    fn text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(event) = self.events.next() {
+            match event {
+                Event::Text(s) | Event::Html(s) | Event::InlineHtml(s) => text.push_str(&s),
+                Event::SoftBreak | Event::HardBreak => text.push('\n'),
+                Event::Start(_) => text.push_str(&self.text()),
+                Event::End(_) => break,
+                Event::FootnoteReference(_) => {}
+            }
+        }
+        text
+    }
+
+    /// Skips all events until the end of the current tag.
+    fn skip_to_end(&mut self) {
+        let mut depth = 0;
+        for event in self.events.by_ref() {
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) if depth == 0 => break,
+                Event::End(_) => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Appends the object and merges adjacent raw strings.
+fn push_object(objects: &mut Vec<StandardSet>, object: StandardSet) {
+    if let StandardSet::RawString(s) = &object {
+        if let Some(StandardSet::RawString(last)) = objects.last_mut() {
+            last.push_str(s);
+            return;
+        }
+    }
+    objects.push(object);
+}
+
+fn paragraph(objects: Vec<StandardSet>) -> Paragraph {
+    Paragraph::new(objects.into())
+}
+
+/// Removes whitespace at the start and end of a list of objects.
+fn trim_objects(objects: &mut Vec<StandardSet>) {
+    if let Some(StandardSet::RawString(s)) = objects.first_mut() {
+        *s = s.trim_start().to_string();
+    }
+    if let Some(StandardSet::RawString(s)) = objects.last_mut() {
+        *s = s.trim_end().to_string();
+    }
+    objects.retain(|object| match object {
+        StandardSet::RawString(s) => !s.is_empty(),
+        _ => true,
+    });
+}
+
+/// Converts a link target and description to a bracket link.
+///
+/// The description is omitted if it is the same as the link target (e.g. for autolinks).
+fn link(url: &str, description: Vec<StandardSet>) -> Link {
+    let path = if let Some(id) = url.strip_prefix('#') {
+        LinkPath::CustomId(id.to_string())
+    } else if let Some(colon) = url.find(':').filter(|&i| {
+        url[..i]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    }) {
+        LinkPath::Protocol {
+            protocol: url[..colon].to_string(),
+            path: url[colon + 1..].to_string(),
+        }
+    } else {
+        LinkPath::File(url.to_string())
+    };
+
+    let description = SecondaryString::from(description);
+    let description = if description.is_empty() || description.to_string() == url {
+        None
+    } else {
+        Some(
+            description
+                .into_iter()
+                .map(link_description_object)
+                .collect(),
+        )
+    };
+
+    Link {
+        link: LinkFormat::Bracket(path, None, description),
+    }
+}
+
+/// Converts an object to one that is allowed in a link description.
+///
+/// Objects that are not allowed are replaced by their textual representation.
+fn link_description_object(object: StandardSet) -> LinkDescriptionSetOfObjects {
+    match object {
+        StandardSet::RawString(s) => LinkDescriptionSetOfObjects::RawString(s),
+        StandardSet::ExportSnippet(o) => LinkDescriptionSetOfObjects::ExportSnippet(o),
+        StandardSet::TextMarkup(o) => LinkDescriptionSetOfObjects::TextMarkup(o),
+        StandardSet::LineBreak(_) => LinkDescriptionSetOfObjects::RawString(" ".to_string()),
+        object => LinkDescriptionSetOfObjects::RawString(object.to_string()),
+    }
+}
+
+/// Converts the objects of a header to a headline title.
+///
+/// Headline titles can't span multiple lines so line breaks are replaced with spaces.
+fn title_from_objects(
+    objects: Vec<StandardSet>,
+) -> Option<SecondaryString<StandardSetNoLineBreak>> {
+    if objects.is_empty() {
+        return None;
+    }
+    Some(
+        objects
+            .into_iter()
+            .map(|object| match object {
+                StandardSet::RawString(s) => {
+                    StandardSetNoLineBreak::RawString(s.replace('\n', " "))
+                }
+                object => object.into(),
+            })
+            .collect(),
+    )
+}
+
+/// Removes a task list marker (`[ ]` or `[x]`) from the start of an item and returns the
+/// matching [`Checkbox`].
+fn take_checkbox(content: &mut [ElementSet]) -> Option<Checkbox> {
+    let paragraph = match content.first_mut() {
+        Some(ElementSet::Paragraph(paragraph)) => paragraph,
+        _ => return None,
+    };
+    let mut objects: Vec<StandardSet> = paragraph.content.clone().into_iter().collect();
+    let (checkbox, rest) = match objects.first() {
+        Some(StandardSet::RawString(s)) if s.starts_with("[ ] ") => {
+            (Checkbox::Unchecked, s[4..].to_string())
+        }
+        Some(StandardSet::RawString(s)) if s.starts_with("[x] ") || s.starts_with("[X] ") => {
+            (Checkbox::Checked, s[4..].to_string())
+        }
+        _ => return None,
+    };
+    objects[0] = StandardSet::RawString(rest);
+    paragraph.content = objects.into();
+    Some(checkbox)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headlines_and_inline_markup() {
+        let document = convert(
+            "Intro with `code`.\n\n# One\n\nSome *emphasis* and **strong**.\n\n## Two\n\n# Three\n",
+        );
+        assert_eq!(
+            document.to_string(),
+            "Intro with ~code~.\n\
+             * One\n\
+             Some /emphasis/ and *strong*.\n\
+             ** Two\n\
+             * Three\n"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let document = convert("[site](https://example.com) <https://example.com> [x](#id)\n");
+        assert_eq!(
+            document.to_string(),
+            "[[https://example.com][site]] [[https://example.com]] [[#id][x]]\n"
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let document = convert("- [ ] open\n- [x] done\n  - nested\n\n3. three\n4. four\n");
+        assert_eq!(
+            document.to_string(),
+            "- [ ] open\n\
+             - [X] done\n  \
+               - nested\n\
+             \n\
+             3. three\n\
+             4. four\n"
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let document = convert("> quoted\n\n```rust\nfn main() {}\n```\n\n    plain\n\n---\n");
+        assert_eq!(
+            document.to_string(),
+            "#+BEGIN_QUOTE\nquoted\n#+END_QUOTE\n\n\
+             #+BEGIN_SRC rust\nfn main() {}\n#+END_SRC\n\n\
+             #+BEGIN_EXAMPLE\nplain\n#+END_EXAMPLE\n\n\
+             -----\n"
+        );
+    }
+
+    #[test]
+    fn nested_headers() {
+        let document = convert("> # Quoted\n> text\n\n- ## Item\n");
+        assert_eq!(
+            document.to_string(),
+            "#+BEGIN_QUOTE\n*Quoted*\n\ntext\n#+END_QUOTE\n\n- *Item*\n"
+        );
+    }
+
+    #[test]
+    fn table() {
+        let document = convert("| a | b |\n|---|---|\n| 1 | *2* |\n");
+        assert_eq!(document.to_string(), "| a | b |\n|-\n| 1 | /2/ |\n");
+    }
+
+    #[test]
+    fn footnotes() {
+        let document = convert("Text[^1].\n\n[^1]: The note.\n");
+        assert_eq!(document.to_string(), "Text[fn:1].\n\n[fn:1] The note.\n");
+    }
+}
@@ -0,0 +1,17 @@
+//! Converters from other markup languages to org [`Document`]s.
+//!
+//! Importers that need additional dependencies live behind their own cargo feature so the
+//! dependencies are only pulled in when needed:
+//!
+//! - [`plain_text::convert`] converts plain text and emails (quoting, indentation and simple
+//!   lists).
+//! - `markdown`: [`markdown::convert`] converts CommonMark (with tables and footnotes).
+//!
+//! The resulting [`Document`] can be written as an org file with its `Display` implementation.
+//!
+//! [`Document`]: `crate::types::document::Document`
+
+pub mod plain_text;
+
+#[cfg(feature = "markdown")]
+pub mod markdown;
@@ -0,0 +1,342 @@
+//! Converts plain text (e.g. the body of an email) to org.
+//!
+//! The text is split into blocks which are converted as follows:
+//!
+//! - Lines starting with `>` (email quoting) become [`QuoteBlock`]s. The content of the quote is
+//!   converted recursively so nested quotes (`> >`) become nested quote blocks.
+//! - Indented text also becomes a [`QuoteBlock`] with the common indentation removed. Text
+//!   indented further inside it stays in the same quote block (with the remaining indentation).
+//! - Lines starting with a bullet (`-`, `+`, `*`, `•`, `1.` or `1)`) become [`PlainList`]s.
+//!   Lines indented at least as far as the text of an item belong to that item.
+//! - Everything else becomes [`Paragraph`]s which are separated by blank lines.
+//!
+//! [`QuoteBlock`]: `crate::types::greater_elements::QuoteBlock`
+//! [`PlainList`]: `crate::types::greater_elements::PlainList`
+//! [`Paragraph`]: `crate::types::elements::Paragraph`
+
+use crate::types::document::Document;
+use crate::types::elements::Paragraph;
+use crate::types::greater_elements::{
+    Counter, CounterDelimiter, Item, ItemKind, OrderedBullet, PlainList, QuoteBlock, Section,
+    UnorderedBullet,
+};
+use crate::types::{ElementSet, SecondaryString, Spanned, StandardSet};
+
+/// Converts plain text to an org [`Document`].
+///
+/// All elements are put into the [`Document::preface`]. Use [`elements`] to insert the converted
+/// text somewhere else (e.g. into the section of a headline).
+///
+/// [`Document::preface`]: `crate::types::document::Document::preface`
+pub fn convert(input: &str) -> Document {
+    let elements = elements(input);
+    Document {
+        preface: if elements.is_empty() {
+            None
+        } else {
+            Some(Section::new(Spanned::new(elements)))
+        },
+        headlines: Vec::new(),
+    }
+}
+
+/// Converts plain text to a list of org elements.
+pub fn elements(input: &str) -> Vec<ElementSet> {
+    let lines: Vec<&str> = input.lines().map(str::trim_end).collect();
+    convert_lines(&lines, true)
+}
+
+/// Converts the lines to elements. Indented text only becomes a quote block if `quote_indented`
+/// is set (so it isn't quoted again inside a quote block of indented text).
+fn convert_lines(lines: &[&str], quote_indented: bool) -> Vec<ElementSet> {
+    let mut elements = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.is_empty() {
+            i += 1;
+        } else if is_quoted(line) {
+            let end = find_end(lines, i, is_quoted);
+            let content: Vec<&str> = lines[i..end].iter().map(|line| unquote(line)).collect();
+            elements.push(QuoteBlock::new(Spanned::new(convert_lines(&content, true))).into());
+            i = end;
+        } else if let Some(bullet) = Bullet::parse(line) {
+            let (list, end) = convert_list(lines, i, bullet, quote_indented);
+            elements.push(list.into());
+            i = end;
+        } else if quote_indented && indentation(line) > 0 {
+            let end = trim_blank_end(
+                lines,
+                i,
+                find_end(lines, i, |line| line.is_empty() || indentation(line) > 0),
+            );
+            let content = dedent(&lines[i..end], min_indentation(&lines[i..end]));
+            elements.push(QuoteBlock::new(Spanned::new(convert_lines(&content, false))).into());
+            i = end;
+        } else {
+            let end = find_end(lines, i, |line| {
+                !line.is_empty() && !is_quoted(line) && Bullet::parse(line).is_none()
+            });
+            elements.push(paragraph(&lines[i..end]).into());
+            i = end;
+        }
+    }
+    elements
+}
+
+/// Converts a list starting at `start`.
+///
+/// Returns the list and the index of the first line after the list.
+fn convert_list(
+    lines: &[&str],
+    start: usize,
+    first: Bullet,
+    quote_indented: bool,
+) -> (PlainList, usize) {
+    let mut items = Vec::new();
+    let mut i = start;
+    let mut bullet = first.clone();
+    loop {
+        // The item contains all following lines that are indented at least as far as the text
+        // of the item. Blank lines only belong to it if they are followed by such a line.
+        let end = trim_blank_end(
+            lines,
+            i,
+            find_end(lines, i + 1, |line| {
+                line.is_empty() || indentation(line) >= bullet.content_offset
+            }),
+        );
+        let mut content = vec![&lines[i][bullet.content_offset..]];
+        content.extend(dedent(&lines[i + 1..end], bullet.content_offset));
+        items.push(Item::new(
+            bullet.item_kind(&first, items.len()),
+            Spanned::new(convert_lines(&content, quote_indented)),
+        ));
+
+        // The next item can be separated by blank lines.
+        let next = find_end(lines, end, str::is_empty);
+        match lines.get(next).and_then(|line| Bullet::parse(line)) {
+            Some(next_bullet) if next_bullet.indentation == first.indentation => {
+                bullet = next_bullet;
+                i = next;
+            }
+            _ => return (PlainList::new(Spanned::new(items)), end),
+        }
+    }
+}
+
+/// A bullet at the start of a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bullet {
+    indentation: usize,
+    /// The byte offset of the text after the bullet.
+    content_offset: usize,
+    /// Whether the bullet is a number (`1.` or `1)`).
+    ordered: bool,
+    /// The number of an ordered bullet (`None` if it is too large for a counter).
+    number: Option<u64>,
+    delimiter: CounterDelimiter,
+}
+
+impl Bullet {
+    fn parse(line: &str) -> Option<Bullet> {
+        let indentation = indentation(line);
+        let rest = &line[indentation..];
+        let mut ordered = false;
+        let mut number = None;
+        let mut delimiter = CounterDelimiter::Period;
+        let bullet_len = if rest.starts_with('-') || rest.starts_with('+') || rest.starts_with('*')
+        {
+            1
+        } else if rest.starts_with('•') {
+            '•'.len_utf8()
+        } else {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 {
+                return None;
+            }
+            ordered = true;
+            number = rest[..digits].parse().ok();
+            if rest[digits..].starts_with(')') {
+                delimiter = CounterDelimiter::Parenthesis;
+            } else if !rest[digits..].starts_with('.') {
+                return None;
+            }
+            digits + 1
+        };
+        let after = &rest[bullet_len..];
+        if !after.starts_with(' ') && !after.is_empty() {
+            return None;
+        }
+        let spaces = after.chars().take_while(|&c| c == ' ').count();
+
+        Some(Bullet {
+            indentation,
+            content_offset: indentation + bullet_len + spaces.max(1).min(after.len()),
+            ordered,
+            number,
+            delimiter,
+        })
+    }
+
+    /// The kind of the item. All items of a list have the same kind as the first item.
+    ///
+    /// All unordered bullets are converted to `-` because `*` at the start of a line would be
+    /// a headline in org. Items without a number (or with a number too large for a counter) in
+    /// an ordered list are numbered by their position.
+    fn item_kind(&self, first: &Bullet, index: usize) -> ItemKind {
+        if first.ordered {
+            let start = first.number.unwrap_or(1);
+            ItemKind::Ordered {
+                bullet: OrderedBullet {
+                    counter: Counter::Number(
+                        self.number
+                            .unwrap_or_else(|| start.saturating_add(index as u64)),
+                    ),
+                    delimiter: first.delimiter.clone(),
+                },
+                counter: None,
+            }
+        } else {
+            ItemKind::Unordered {
+                bullet: UnorderedBullet::Minus,
+            }
+        }
+    }
+}
+
+fn is_quoted(line: &str) -> bool {
+    line.trim_start().starts_with('>')
+}
+
+/// Removes one level of quoting (`>` and one optional space).
+fn unquote(line: &str) -> &str {
+    let line = &line.trim_start()[1..];
+    line.strip_prefix(' ').unwrap_or(line)
+}
+
+/// Returns the number of spaces and tabs at the start of the line.
+///
+/// Other whitespace (e.g. an ideographic space) is part of the text.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(&[' ', '\t'][..]).len()
+}
+
+fn min_indentation(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or(0)
+}
+
+/// Removes up to `width` spaces and tabs from the start of the lines.
+fn dedent<'a>(lines: &[&'a str], width: usize) -> Vec<&'a str> {
+    lines
+        .iter()
+        .map(|line| &line[indentation(line).min(width)..])
+        .collect()
+}
+
+/// Returns the index of the first line from `start` on that doesn't match the predicate.
+fn find_end(lines: &[&str], start: usize, predicate: impl Fn(&str) -> bool) -> usize {
+    start
+        + lines[start.min(lines.len())..]
+            .iter()
+            .take_while(|line| predicate(line))
+            .count()
+}
+
+/// Moves `end` back so the range `start..end` doesn't end with blank lines.
+fn trim_blank_end(lines: &[&str], start: usize, mut end: usize) -> usize {
+    while end > start + 1 && lines[end - 1].is_empty() {
+        end -= 1;
+    }
+    end
+}
+
+fn paragraph(lines: &[&str]) -> Paragraph {
+    Paragraph::new(SecondaryString::with_one(StandardSet::RawString(
+        lines.join("\n"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn email_reply() {
+        let input = "Sounds good.\n\
+                     \n\
+                     On Monday Bob wrote:\n\
+                     > Can we meet?\n\
+                     >\n\
+                     > > Earlier mail\n";
+        assert_eq!(
+            convert(input).to_string(),
+            "Sounds good.\n\
+             \n\
+             On Monday Bob wrote:\n\
+             \n\
+             #+BEGIN_QUOTE\n\
+             Can we meet?\n\
+             \n\
+             #+BEGIN_QUOTE\n\
+             Earlier mail\n\
+             #+END_QUOTE\n\
+             #+END_QUOTE\n"
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let input = "* one\n  continued\n\n* two\n  1) nested\n  2) list\n";
+        assert_eq!(
+            convert(input).to_string(),
+            "- one\n  continued\n- two\n  1) nested\n  2) list\n"
+        );
+    }
+
+    #[test]
+    fn indented_text() {
+        let input = "Output:\n\n    line 1\n\n      line 2\n\nDone.\n";
+        assert_eq!(
+            convert(input).to_string(),
+            "Output:\n\
+             \n\
+             #+BEGIN_QUOTE\n\
+             line 1\n\
+             \n\
+             \x20 line 2\n\
+             #+END_QUOTE\n\
+             \n\
+             Done.\n"
+        );
+    }
+
+    #[test]
+    fn overflowing_number_is_ordered() {
+        assert_eq!(
+            convert("99999999999999999999. a\n2. b\n").to_string(),
+            "1. a\n2. b\n"
+        );
+    }
+
+    #[test]
+    fn unicode_space_after_list_item() {
+        assert_eq!(
+            convert("- a\n\u{3000}x\n").to_string(),
+            "- a\n\n\u{3000}x\n"
+        );
+    }
+
+    #[test]
+    fn unicode_space_in_indented_text() {
+        assert_eq!(
+            convert(" \u{3000}x\n  y\n").to_string(),
+            "#+BEGIN_QUOTE\n\u{3000}x\n y\n#+END_QUOTE\n"
+        );
+    }
+}
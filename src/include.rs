@@ -0,0 +1,397 @@
+//! Resolving `#+INCLUDE:` keywords (like the export of org mode does).
+//!
+//! An include keyword is replaced by the content of a file:
+//!
+//! ```text
+//! #+INCLUDE: "chapter.org" :minlevel 2
+//! #+INCLUDE: "main.rs" src rust :lines "4-20"
+//! #+INCLUDE: "notes.txt" example
+//! ```
+//!
+//! Without a block type the file is included as org text. Its headlines are moved to `:minlevel`
+//! or else below the headline containing the keyword. Includes in the included files are
+//! resolved too (relative to the included file). With a block type (`src LANGUAGE`, `example`,
+//! `export BACKEND` or the name of a special block) the file becomes the content of a block.
+//!
+//! `:lines "4-20"` includes only the lines 4 to 19 of the file (the end is excluded like in org
+//! mode). `"-20"` starts at the first line and `"4-"` ends at the last line. Search options in
+//! the path (`"file.org::*Headline"`) are not supported.
+//!
+//! Like in org mode the keyword line is replaced by the text of the included file (indented like
+//! the keyword) and the document is parsed again. So the content following the keyword belongs
+//! to the last included headline.
+
+use crate::parsing::ParseError;
+use crate::types::document::Document;
+use crate::types::elements::{
+    ExampleBlock, ExportBlock, Keyword, KeywordValueSetOfObjects, SrcBlock,
+};
+use crate::types::greater_elements::{Headline, SpecialBlock};
+use crate::types::visit::{self, VisitorMut};
+use crate::types::Spanned;
+use itertools::Itertools;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Replaces the value of the resolved keywords until the document is serialized.
+const MARKER: &str = "\u{0}include:";
+
+/// Loads the content of included files.
+///
+/// Relative paths are relative to the directory of the document the includes are resolved for.
+/// Closures (`Fn(&Path) -> io::Result<String>`) are loaders too.
+pub trait FileLoader {
+    fn load(&self, path: &Path) -> io::Result<String>;
+}
+
+impl<F: Fn(&Path) -> io::Result<String>> FileLoader for F {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        self(path)
+    }
+}
+
+/// Loads files from the file system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FileSystemLoader {
+    /// The directory relative paths are resolved against (e.g. the directory of the document).
+    pub directory: PathBuf,
+}
+
+impl FileLoader for FileSystemLoader {
+    fn load(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(self.directory.join(path))
+    }
+}
+
+/// The value of an `#+INCLUDE:` keyword.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Include {
+    pub path: PathBuf,
+    /// The block the content is put in (`None` to include org text).
+    pub block: Option<IncludeBlock>,
+    /// The first included line (1-based).
+    pub first_line: Option<usize>,
+    /// The line after the last included line (1-based).
+    pub end_line: Option<usize>,
+    /// The level of the top level headlines of the included file (`:minlevel`).
+    pub min_level: Option<u32>,
+}
+
+/// The block an included file is put in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IncludeBlock {
+    /// `src LANGUAGE`
+    Src(String),
+    Example,
+    /// `export BACKEND`
+    Export(String),
+    /// Any other block name (e.g. `quote`). The content is org text.
+    Special(String),
+}
+
+/// The error returned when resolving includes fails.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// The value of an `#+INCLUDE:` keyword is not valid.
+    InvalidKeyword(String),
+    /// The file can't be loaded.
+    Load { path: PathBuf, error: io::Error },
+    /// The included org text can't be parsed.
+    ///
+    /// The path is empty if the document with the resolved includes can't be parsed.
+    Parse { path: PathBuf, error: ParseError },
+    /// The file includes itself (directly or through other files).
+    Cycle(PathBuf),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::InvalidKeyword(value) => {
+                write!(f, "invalid #+INCLUDE: keyword {:?}", value)
+            }
+            IncludeError::Load { path, error } => {
+                write!(f, "can't load {}: {}", path.display(), error)
+            }
+            IncludeError::Parse { path, error } => write!(
+                f,
+                "can't parse {} (expected {})",
+                path.display(),
+                error.expected()
+            ),
+            IncludeError::Cycle(path) => write!(f, "{} includes itself", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+impl FromStr for Include {
+    type Err = IncludeError;
+
+    /// Parses the value of an `#+INCLUDE:` keyword.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || IncludeError::InvalidKeyword(s.to_string());
+        let mut words = words(s).into_iter();
+        let path = words.next().ok_or_else(invalid)?;
+        let mut include = Include {
+            path: PathBuf::from(path),
+            block: None,
+            first_line: None,
+            end_line: None,
+            min_level: None,
+        };
+        let mut words = words.peekable();
+        if let Some(name) = words.peek().filter(|word| !word.starts_with(':')).cloned() {
+            words.next();
+            include.block = Some(match name.to_lowercase().as_str() {
+                "src" => IncludeBlock::Src(words.next().ok_or_else(invalid)?),
+                "example" => IncludeBlock::Example,
+                "export" => IncludeBlock::Export(words.next().ok_or_else(invalid)?),
+                _ => IncludeBlock::Special(name),
+            });
+        }
+        while let Some(key) = words.next() {
+            let value = words.next().ok_or_else(invalid)?;
+            match key.to_lowercase().as_str() {
+                ":lines" => {
+                    let dash = value.find('-').ok_or_else(invalid)?;
+                    let line = |s: &str| match s.trim() {
+                        "" => Ok(None),
+                        s => s.parse().map(Some).map_err(|_| invalid()),
+                    };
+                    include.first_line = line(&value[..dash])?;
+                    include.end_line = line(&value[dash + 1..])?;
+                }
+                ":minlevel" => include.min_level = Some(value.parse().map_err(|_| invalid())?),
+                _ => {}
+            }
+        }
+        Ok(include)
+    }
+}
+
+/// Splits the value at whitespace. Double quotes group words (and are removed).
+pub(crate) fn words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut rest = s.trim_start();
+    while !rest.is_empty() {
+        let (word, after) = if let Some(quoted) = rest.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        words.push(word.to_string());
+        rest = after.trim_start();
+    }
+    words
+}
+
+impl Include {
+    /// Returns the included lines of the text.
+    fn lines(&self, text: &str) -> String {
+        let first = self.first_line.unwrap_or(1).max(1) - 1;
+        let mut lines = text.split_terminator('\n').skip(first);
+        match self.end_line {
+            Some(end) => lines.take(end.saturating_sub(first + 1)).join("\n") + "\n",
+            None => lines.join("\n") + "\n",
+        }
+    }
+}
+
+/// Replaces the include keywords of the document with the content of the files.
+///
+/// See the [module documentation](`self`) for the supported keywords.
+pub fn resolve_includes(
+    document: &mut Document,
+    loader: &impl FileLoader,
+) -> Result<(), IncludeError> {
+    let text = expand(document.clone(), Path::new(""), loader, &mut Vec::new())?;
+    *document = Document::parse(&text).map_err(|error| IncludeError::Parse {
+        path: PathBuf::new(),
+        error,
+    })?;
+    Ok(())
+}
+
+/// Returns the text of the document with the includes replaced by the text of the files.
+///
+/// `directory` is the directory of the document and `files` are the files currently being
+/// included (to find cycles).
+fn expand(
+    mut document: Document,
+    directory: &Path,
+    loader: &impl FileLoader,
+    files: &mut Vec<PathBuf>,
+) -> Result<String, IncludeError> {
+    let mut keywords = IncludeKeywords::default();
+    visit::walk_mut(&mut document, &mut keywords);
+    if keywords.includes.is_empty() {
+        return Ok(document.to_string());
+    }
+
+    let mut text = String::new();
+    for line in document.to_string().split_terminator('\n') {
+        let index = line
+            .find(MARKER)
+            .and_then(|start| line[start + MARKER.len()..].parse::<usize>().ok());
+        let (value, level) = match index.and_then(|index| keywords.includes.get(index)) {
+            Some(include) => include,
+            None => {
+                text.push_str(line);
+                text.push('\n');
+                continue;
+            }
+        };
+        let include: Include = value.parse()?;
+        let path = directory.join(&include.path);
+        if files.contains(&path) {
+            return Err(IncludeError::Cycle(path));
+        }
+        let content = loader.load(&path).map_err(|error| IncludeError::Load {
+            path: path.clone(),
+            error,
+        })?;
+        let content = include.lines(&content);
+        let content = match include.block {
+            Some(IncludeBlock::Src(language)) => SrcBlock::new(language, content).to_string(),
+            Some(IncludeBlock::Example) => ExampleBlock::new(content).to_string(),
+            Some(IncludeBlock::Export(backend)) => ExportBlock::new(&backend, content).to_string(),
+            Some(IncludeBlock::Special(name)) => {
+                SpecialBlock::new(name, Spanned::new(content)).to_string()
+            }
+            None => {
+                let mut included =
+                    Document::parse(&content).map_err(|error| IncludeError::Parse {
+                        path: path.clone(),
+                        error,
+                    })?;
+                included.rebase_levels(include.min_level.unwrap_or(level + 1));
+                files.push(path.clone());
+                let directory = path.parent().unwrap_or_else(|| Path::new(""));
+                let content = expand(included, directory, loader, files)?;
+                files.pop();
+                content
+            }
+        };
+        let indentation = &line[..line.len() - line.trim_start().len()];
+        for line in content.split_terminator('\n') {
+            if !line.is_empty() {
+                text.push_str(indentation);
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    Ok(text)
+}
+
+/// Collects the values of the include keywords and the levels of their headlines. The values are
+/// replaced by the [`MARKER`] and the index of the keyword.
+#[derive(Default)]
+struct IncludeKeywords {
+    includes: Vec<(String, u32)>,
+    level: u32,
+}
+
+impl VisitorMut for IncludeKeywords {
+    fn visit_headline(&mut self, headline: &mut Headline) {
+        let parent = self.level;
+        self.level = headline.level;
+        visit::walk_headline_mut(self, headline);
+        self.level = parent;
+    }
+
+    fn visit_keyword(&mut self, keyword: &mut Keyword) {
+        if keyword.key.eq_ignore_ascii_case("INCLUDE") {
+            let value = keyword.value.value().iter().join("");
+            let marker = format!("{}{}", MARKER, self.includes.len());
+            self.includes.push((value, self.level));
+            *keyword.value.get_mut_value() = vec![KeywordValueSetOfObjects::RawString(marker)];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(path: &Path) -> io::Result<String> {
+        let text = match path.to_str().unwrap() {
+            "chapters/one.org" => "* One\n#+INCLUDE: \"two.org\"\n",
+            "chapters/two.org" => "Text of two.\n* Two\n** Deeper\n",
+            "main.rs" => "// header\nfn main() {\n    * ptr;\n}\n// footer\n",
+            "loop.org" => "#+INCLUDE: loop.org\n",
+            _ => return Err(io::Error::from(io::ErrorKind::NotFound)),
+        };
+        Ok(text.to_string())
+    }
+
+    #[test]
+    fn parse_include() {
+        let include: Include = "\"a b.org\" :minlevel 3".parse().unwrap();
+        assert_eq!(include.path, PathBuf::from("a b.org"));
+        assert_eq!(include.min_level, Some(3));
+    }
+
+    #[test]
+    fn include_nested_documents() {
+        let mut document = Document::parse("* Book\n#+INCLUDE: \"chapters/one.org\"\n").unwrap();
+        document.resolve_includes(load).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "* Book\n\
+             ** One\n\
+             Text of two.\n\
+             *** Two\n\
+             **** Deeper\n"
+        );
+    }
+
+    #[test]
+    fn include_lines_as_src_block() {
+        let mut document =
+            Document::parse("* Code\n#+include: main.rs src rust :lines \"2-5\"\n").unwrap();
+        document.resolve_includes(load).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "* Code\n\
+             #+BEGIN_SRC rust\n\
+             fn main() {\n\
+             \x20   ,* ptr;\n\
+             }\n\
+             #+END_SRC\n"
+        );
+    }
+
+    #[test]
+    fn include_cycle() {
+        let error = Document::parse("#+INCLUDE: \"loop.org\"\n")
+            .unwrap()
+            .resolve_includes(load)
+            .unwrap_err();
+        match error {
+            IncludeError::Cycle(path) => assert_eq!(path, PathBuf::from("loop.org")),
+            error => panic!("expected a cycle, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn missing_include() {
+        let error = Document::parse("#+INCLUDE: missing.org\n")
+            .unwrap()
+            .resolve_includes(load)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "can't load missing.org: entity not found"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Helpers for review workflows (journals, weekly reviews).
+//!
+//! Headlines get a creation time in their `CREATED` property (see [`stamp_created`]) and a
+//! closing time in their `CLOSED` planning item (set by org mode when a task is marked as done).
+//! Both can be queried by date ranges, e.g. to produce a "this week I did…" [`Report`].
+
+use crate::types::document::Document;
+use crate::types::greater_elements::Headline;
+use crate::types::objects::{Timestamp, TimestampStatus};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use std::fmt;
+
+/// The property storing the creation time of a headline.
+pub const CREATED_PROPERTY: &str = "CREATED";
+
+/// Sets the `CREATED` property of a headline to an inactive timestamp of the given time (e.g.
+/// `[2018-10-12 Fri 10:30]`).
+///
+/// An existing creation time is not overwritten. Returns `true` if the property was set.
+pub fn stamp_created(headline: &mut Headline, now: NaiveDateTime) -> bool {
+    if headline.property(CREATED_PROPERTY).is_some() {
+        return false;
+    }
+    let timestamp = Timestamp::new(TimestampStatus::Inactive, now.date(), Some(now.time()));
+    headline.set_property(CREATED_PROPERTY, timestamp.to_string());
+    true
+}
+
+/// Returns the creation time of a headline (from its `CREATED` property).
+pub fn created(headline: &Headline) -> Option<NaiveDateTime> {
+    headline
+        .property(CREATED_PROPERTY)?
+        .parse::<Timestamp>()
+        .ok()?
+        .start_date_time()
+}
+
+/// Returns the time a headline was closed (from its `CLOSED` planning item).
+pub fn closed(headline: &Headline) -> Option<NaiveDateTime> {
+    headline
+        .planning
+        .as_ref()?
+        .closed
+        .as_ref()?
+        .start_date_time()
+}
+
+/// An inclusive range of dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        DateRange { start, end }
+    }
+
+    /// The week (Monday to Sunday) containing the given date.
+    pub fn week_of(date: NaiveDate) -> Self {
+        let start = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+        DateRange {
+            start,
+            end: start + Duration::days(6),
+        }
+    }
+
+    /// The month containing the given date.
+    pub fn month_of(date: NaiveDate) -> Self {
+        let next_month = if date.month() == 12 {
+            NaiveDate::from_ymd(date.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+        };
+        DateRange {
+            start: NaiveDate::from_ymd(date.year(), date.month(), 1),
+            end: next_month.pred(),
+        }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+/// Returns all headlines (in document order) created in the given range.
+pub fn created_in(document: &Document, range: DateRange) -> Vec<&Headline> {
+    headlines_in(document, range, created)
+}
+
+/// Returns all headlines (in document order) closed in the given range.
+pub fn closed_in(document: &Document, range: DateRange) -> Vec<&Headline> {
+    headlines_in(document, range, closed)
+}
+
+fn headlines_in(
+    document: &Document,
+    range: DateRange,
+    time: impl Fn(&Headline) -> Option<NaiveDateTime>,
+) -> Vec<&Headline> {
+    let mut headlines = Vec::new();
+    collect(
+        document.headlines.iter(),
+        &mut |headline| match time(headline) {
+            Some(time) => range.contains(time.date()),
+            None => false,
+        },
+        &mut headlines,
+    );
+    headlines
+}
+
+fn collect<'a>(
+    headlines: impl IntoIterator<Item = &'a Headline>,
+    predicate: &mut impl FnMut(&Headline) -> bool,
+    found: &mut Vec<&'a Headline>,
+) {
+    for headline in headlines {
+        if predicate(headline) {
+            found.push(headline);
+        }
+        collect(headline.children(), predicate, found);
+    }
+}
+
+/// A report of the headlines closed in a date range grouped by day.
+///
+/// The report is printed as an org list:
+///
+/// ```text
+/// - 2018-10-08 Mon
+///   - Call Bob
+///   - 10:30 Write report
+/// - 2018-10-10 Wed
+///   - 16:00 Review pull request
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    pub range: DateRange,
+    /// The days (in order) on which at least one headline was closed.
+    pub days: Vec<ReportDay>,
+}
+
+/// The headlines closed on one day of a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReportDay {
+    pub date: NaiveDate,
+    /// The closed headlines ordered by their closing time.
+    pub entries: Vec<ReportEntry>,
+}
+
+/// A closed headline in a [`Report`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReportEntry {
+    pub closed: NaiveDateTime,
+    /// Whether the `CLOSED` timestamp contained a time (and not only a date).
+    pub has_time: bool,
+    pub title: String,
+}
+
+impl Report {
+    /// Creates a report of all headlines closed in the given range.
+    pub fn closed_in(document: &Document, range: DateRange) -> Self {
+        let mut entries: Vec<ReportEntry> = closed_in(document, range)
+            .into_iter()
+            .filter_map(|headline| {
+                let timestamp = headline.planning.as_ref()?.closed.as_ref()?;
+                Some(ReportEntry {
+                    closed: timestamp.start_date_time()?,
+                    has_time: timestamp.timestamp_start()?.1.is_some(),
+                    title: headline
+                        .title
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+        // stable so entries closed at the same time stay in document order
+        entries.sort_by_key(|entry| entry.closed);
+
+        let mut days: Vec<ReportDay> = Vec::new();
+        for entry in entries {
+            let date = entry.closed.date();
+            match days.last_mut() {
+                Some(day) if day.date == date => day.entries.push(entry),
+                _ => days.push(ReportDay {
+                    date,
+                    entries: vec![entry],
+                }),
+            }
+        }
+        Report { range, days }
+    }
+
+    /// Creates a report of the headlines closed in the week (Monday to Sunday) containing the
+    /// given date.
+    pub fn week_of(document: &Document, date: NaiveDate) -> Self {
+        Report::closed_in(document, DateRange::week_of(date))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for day in &self.days {
+            writeln!(f, "- {}", day.date.format("%Y-%m-%d %a"))?;
+            for entry in &day.entries {
+                if entry.has_time {
+                    writeln!(f, "  - {} {}", entry.closed.format("%H:%M"), entry.title)?;
+                } else {
+                    writeln!(f, "  - {}", entry.title)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(year, month, day)
+    }
+
+    #[test]
+    fn stamp_and_query_created() {
+        let mut document = Document::parse("* a\n* b\n").unwrap();
+        let headline = &mut document.headlines[0];
+        let now = date(2018, 10, 12).and_hms(10, 30, 0);
+        assert!(stamp_created(headline, now));
+        assert_eq!(
+            headline.property("created").as_deref(),
+            Some("[2018-10-12 Fri 10:30]")
+        );
+        assert!(!stamp_created(headline, now + Duration::days(1)));
+        assert_eq!(created(headline), Some(now));
+
+        let week = DateRange::week_of(date(2018, 10, 14));
+        assert_eq!(week, DateRange::new(date(2018, 10, 8), date(2018, 10, 14)));
+        assert_eq!(created_in(&document, week).len(), 1);
+        assert!(created_in(&document, DateRange::week_of(date(2018, 10, 15))).is_empty());
+    }
+
+    #[test]
+    fn weekly_report() {
+        let text = "* Review pull request\n\
+                    CLOSED: [2018-10-10 Wed 16:00]\n\
+                    * Call Bob\n\
+                    CLOSED: [2018-10-08 Mon]\n\
+                    * Last week\n\
+                    CLOSED: [2018-10-07 Sun 12:00]\n\
+                    * Write report\n\
+                    CLOSED: [2018-10-08 Mon 10:30]\n\
+                    * Open\n";
+        let document = Document::parse(text).unwrap();
+        assert_eq!(
+            Report::week_of(&document, date(2018, 10, 12)).to_string(),
+            "- 2018-10-08 Mon\n\
+             \x20 - Call Bob\n\
+             \x20 - 10:30 Write report\n\
+             - 2018-10-10 Wed\n\
+             \x20 - 16:00 Review pull request\n"
+        );
+    }
+}
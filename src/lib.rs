@@ -13,11 +13,34 @@
 //!
 //! Currently only parsing of the major outline and timestamp is supported.
 //!
+//! Documents written in other markup languages can be converted to org with the
+//! [`import`] module.
+//!
+//! # Public API
+//!
+//! The supported API consists of the public items of the modules of this crate. The
+//! [`prelude`] re-exports the most commonly used types and traits. The [`parsing::Rule`]s
+//! generated from the grammar are public for testing but not part of the supported API.
+//!
+//! - Fields without invariants are public (e.g. [`Headline::level`]). Content that has a
+//!   [`Span`] or depends on other fields is private and available with getters (mostly
+//!   [`Parent::content`]).
+//! - Enums that will get more variants (e.g. when more of the syntax is supported) are marked
+//!   `#[non_exhaustive]`. Matches on them need a wildcard arm.
+//! - The traits [`Object`], [`Element`] and [`Parent`] are sealed: they describe the org syntax
+//!   and can't be implemented outside of this crate. This allows adding methods to them
+//!   without breaking changes.
+//!
+//! [`Headline::level`]: `types::greater_elements::Headline::level`
+//! [`Span`]: `types::Span`
+//! [`Parent::content`]: `types::Parent::content`
+//! [`Object`]: `types::Object`
+//! [`Element`]: `types::Element`
+//! [`Parent`]: `types::Parent`
+//!
 //! # Todo
 //!
 //! - impl Object for every object
-//! - impl Element for every element and greater element
-//! - impl GreaterElement for every greater element
 #![feature(plugin)]
 #![feature(pattern)]
 #![plugin(phf_macros)]
@@ -40,12 +63,46 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
+// The macros have to be defined before the modules using them.
+#[macro_use]
+pub mod macros;
+
+pub mod agenda;
+pub mod babel;
+pub mod check;
+pub mod clocking;
+pub mod columns;
+pub mod crypt;
+pub mod diary;
+pub mod diff;
 pub mod entities;
+pub mod export;
+pub mod id_registry;
+pub mod import;
+pub mod include;
+pub mod journal;
+pub mod logbook;
 pub mod parsing;
+pub mod prelude;
+pub mod promote;
+pub mod refile;
+pub mod roam;
+pub mod rollup;
+#[cfg(any(test, feature = "roundtrip"))]
+pub mod roundtrip;
+pub mod settings;
+pub mod statistics;
+pub mod table;
+pub mod tag_match;
+pub mod tangle;
+pub mod toggle;
 pub mod types;
-#[macro_use]
-pub mod macros;
+pub mod workspace;
 
+/// Contains the [`Sealed`] trait used to prevent implementations of public traits outside of
+/// this crate.
+///
+/// [`Sealed`]: `private::Sealed`
 mod private {
     pub trait Sealed {}
 
@@ -79,6 +136,7 @@ mod private {
     impl_sealed!(crate::types::elements::Comment);
     impl_sealed!(crate::types::elements::CommentBlock);
     impl_sealed!(crate::types::elements::DiarySexp);
+    impl_sealed!(crate::types::elements::EncryptedBlock);
     impl_sealed!(crate::types::elements::ExampleBlock);
     impl_sealed!(crate::types::elements::ExportBlock);
     impl_sealed!(crate::types::elements::FixedWidth);
@@ -95,6 +153,7 @@ mod private {
     impl_sealed!(crate::types::greater_elements::DynamicBlock);
     impl_sealed!(crate::types::greater_elements::FootnoteDefinition);
     impl_sealed!(crate::types::greater_elements::Headline);
+    impl_sealed!(crate::types::greater_elements::HeadlineContentSet);
     impl_sealed!(crate::types::greater_elements::Inlinetask);
     impl_sealed!(crate::types::greater_elements::Item);
     impl_sealed!(crate::types::greater_elements::PlainList);
@@ -105,6 +164,8 @@ mod private {
     impl_sealed!(crate::types::greater_elements::Table);
     impl_sealed!(crate::types::greater_elements::TableRow);
     impl_sealed!(crate::types::greater_elements::VerseBlock);
+
+    impl_sealed!(crate::types::ElementSet);
 }
 
 #[cfg(test)]
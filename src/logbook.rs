@@ -0,0 +1,604 @@
+//! Logging state changes and clocking work time.
+//!
+//! Org mode records notes about state changes (e.g. `TODO` to `DONE`) and clock lines in the
+//! section of a headline. Depending on the configuration they are written into a drawer
+//! (usually `:LOGBOOK:`) or directly after the planning line. The drawer is configured by:
+//!
+//! 1. The `LOG_INTO_DRAWER` property of the headline or one of its ancestors. `t` means the
+//!    `LOGBOOK` drawer, `nil` means no drawer and every other value is the name of the drawer.
+//! 2. The `#+STARTUP: logdrawer` (or `nologdrawer`) keyword of the document.
+//!
+//! New entries are added at the top (like `org-log-states-order-reversed`).
+//!
+//! [`change_state`] changes the todo keyword of a headline and records the change like org mode
+//! does (see [`LogConfig`]).
+
+use crate::types::document::Document;
+use crate::types::elements::{Clock, ClockStatus, OrgDuration, Paragraph, Planning};
+use crate::types::greater_elements::{
+    Drawer, Headline, HeadlineContentSet, Item, ItemKind, PlainList, Section, TodoKeyword,
+    UnorderedBullet,
+};
+use crate::types::objects::{
+    Timestamp, TimestampData, TimestampKind, TimestampRange, TimestampStatus,
+};
+use crate::types::{ElementSet, Parent, SecondaryString, Spanned, StandardSet};
+use chrono::NaiveDateTime;
+use std::fmt;
+use std::str::FromStr;
+
+/// The name of the default log drawer.
+pub const LOGBOOK: &str = "LOGBOOK";
+
+/// Where notes and clock lines of a headline are written to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LogDestination {
+    /// Into the drawer with the given name (created if necessary).
+    Drawer(String),
+    /// Directly at the start of the section (after the planning line and property drawer).
+    Section,
+}
+
+/// The file wide logging configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LogConfig {
+    /// Log into the `LOGBOOK` drawer when no `LOG_INTO_DRAWER` property is set.
+    pub log_into_drawer: bool,
+    /// Set the `CLOSED:` timestamp when a headline is changed to a done keyword (like
+    /// `org-log-done`).
+    pub log_done: bool,
+    /// The todo keywords whose changes to are logged with a note about the state change.
+    pub log_states: Vec<String>,
+}
+
+impl LogConfig {
+    /// Reads the configuration from the `#+STARTUP` and `#+TODO` keywords in the preface of the
+    /// document.
+    ///
+    /// `logdrawer` (or `logintodrawer`) enables logging into the drawer, `nologdrawer` disables
+    /// it. `logdone` (or `lognotedone`) and `nologdone` switch [`LogConfig::log_done`]. The
+    /// last option wins.
+    ///
+    /// Changes to todo keywords marked with `!` or `@` (e.g. `DONE(d!)` or `WAIT(w@/!)`) are
+    /// logged. Notes when leaving a state (after the `/`) are not supported.
+    pub fn from_document(document: &Document) -> Self {
+        let mut config = LogConfig::default();
+        let elements = document
+            .preface
+            .as_ref()
+            .and_then(Section::content)
+            .map(|content| content.value().as_slice())
+            .unwrap_or(&[]);
+        for element in elements {
+            let keyword = match element {
+                ElementSet::Keyword(keyword) => keyword,
+                _ => continue,
+            };
+            let value: String = keyword
+                .value
+                .value()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            if keyword.key.eq_ignore_ascii_case("STARTUP") {
+                for option in value.split_whitespace() {
+                    match option {
+                        "logdrawer" | "logintodrawer" => config.log_into_drawer = true,
+                        "nologdrawer" => config.log_into_drawer = false,
+                        "logdone" | "lognotedone" => config.log_done = true,
+                        "nologdone" => config.log_done = false,
+                        _ => {}
+                    }
+                }
+            } else if ["TODO", "SEQ_TODO", "TYP_TODO"]
+                .iter()
+                .any(|key| keyword.key.eq_ignore_ascii_case(key))
+            {
+                for word in value.split_whitespace() {
+                    let start = match word.find('(') {
+                        Some(start) => start,
+                        None => continue,
+                    };
+                    let enter = word[start + 1..].split('/').next().unwrap_or_default();
+                    if enter.contains(['!', '@']) {
+                        config.log_states.push(word[..start].to_string());
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    /// Returns where notes for the headline are written to.
+    ///
+    /// `ancestors` are the ancestors of the headline (outermost first). They are used to inherit
+    /// the `LOG_INTO_DRAWER` property.
+    pub fn destination(&self, headline: &Headline, ancestors: &[&Headline]) -> LogDestination {
+        let property = std::iter::once(headline)
+            .chain(ancestors.iter().rev().cloned())
+            .filter_map(|headline| headline.property("LOG_INTO_DRAWER"))
+            .next();
+        match property.as_deref().map(str::trim) {
+            Some("nil") => LogDestination::Section,
+            Some("t") => LogDestination::Drawer(LOGBOOK.to_string()),
+            Some(name) => LogDestination::Drawer(name.to_string()),
+            None if self.log_into_drawer => LogDestination::Drawer(LOGBOOK.to_string()),
+            None => LogDestination::Section,
+        }
+    }
+}
+
+/// A note about a changed todo state.
+///
+/// Printed like org mode does (`- State "DONE"       from "TODO"       [2018-10-12 Fri 10:30]`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StateChange {
+    pub from: Option<String>,
+    pub to: String,
+    pub time: NaiveDateTime,
+    pub note: Option<String>,
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quoted = |state: Option<&String>| match state {
+            Some(state) => format!("\"{}\"", state),
+            None => String::new(),
+        };
+        write!(
+            f,
+            "State {:<12} from {:<12} {}",
+            quoted(Some(&self.to)),
+            quoted(self.from.as_ref()),
+            inactive(self.time)
+        )?;
+        if let Some(note) = &self.note {
+            write!(f, " \\\\\n{}", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for StateChange {
+    type Err = ();
+
+    /// Parses the text of a note (without the bullet of the item).
+    ///
+    /// The note can be separated by a line break (`\\`) and a newline.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, note) = match s.find("\\\\") {
+            Some(index) => (&s[..index], Some(s[index + 2..].trim())),
+            None => (s, None),
+        };
+        let rest = line.trim_start().strip_prefix("State").ok_or(())?;
+        let (to, rest) = quoted(rest).ok_or(())?;
+        let rest = rest.trim_start().strip_prefix("from").ok_or(())?;
+        let (from, rest) = match quoted(rest) {
+            Some((from, rest)) => (Some(from), rest),
+            None => (None, rest),
+        };
+        let time = rest
+            .parse::<Timestamp>()
+            .ok()
+            .and_then(|timestamp| timestamp.start_date_time())
+            .ok_or(())?;
+        Ok(StateChange {
+            from,
+            to,
+            time,
+            note: note.filter(|note| !note.is_empty()).map(String::from),
+        })
+    }
+}
+
+/// Splits off a quoted state (e.g. `"DONE"`) from the start of the trimmed string.
+fn quoted(s: &str) -> Option<(String, &str)> {
+    let s = s.trim_start().strip_prefix('"')?;
+    let end = s.find('"')?;
+    Some((s[..end].to_string(), &s[end + 1..]))
+}
+
+/// Returns the notes about state changes of the headline.
+///
+/// The notes are searched at the start of the section and in all drawers of the section.
+pub fn state_changes(headline: &Headline) -> Vec<StateChange> {
+    fn collect(elements: &[ElementSet], changes: &mut Vec<StateChange>) {
+        for element in elements {
+            match element {
+                ElementSet::PlainList(list) => {
+                    let items = list.content().map_or(&[][..], |content| content.value());
+                    changes.extend(items.iter().filter_map(|item| {
+                        match item.content().map(|content| content.value().as_slice()) {
+                            Some([ElementSet::Paragraph(paragraph), ..]) => {
+                                paragraph.content.to_string().parse().ok()
+                            }
+                            _ => None,
+                        }
+                    }));
+                }
+                ElementSet::Drawer(drawer) => collect(
+                    drawer.content().map_or(&[], |content| content.value()),
+                    changes,
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    for content in headline.content().into_iter().flat_map(Spanned::value) {
+        if let HeadlineContentSet::Section(section) = content {
+            collect(
+                section.content().map_or(&[], |content| content.value()),
+                &mut changes,
+            );
+        }
+    }
+    changes
+}
+
+/// Adds a note about a state change to the headline.
+pub fn log_state_change(
+    headline: &mut Headline,
+    destination: &LogDestination,
+    change: &StateChange,
+) {
+    let item = Item::new(
+        ItemKind::Unordered {
+            bullet: UnorderedBullet::Minus,
+        },
+        Spanned::new(vec![Paragraph::new(SecondaryString::with_one(
+            StandardSet::RawString(change.to_string()),
+        ))
+        .into()]),
+    );
+    let elements = log_elements(headline, destination);
+    // notes directly follow the clock lines
+    let index = elements
+        .iter()
+        .take_while(|element| matches!(element, ElementSet::Clock(_)))
+        .count();
+    match elements.get_mut(index) {
+        Some(ElementSet::PlainList(list)) => list.content_mut().insert(0, item),
+        _ => elements.insert(index, PlainList::new(Spanned::new(vec![item])).into()),
+    }
+}
+
+/// Changes the todo keyword of the headline and logs the change.
+///
+/// When changing to a done keyword the `CLOSED:` timestamp is set if [`LogConfig::log_done`] is
+/// set. It is removed when changing back to a todo keyword (or no keyword). A note is added if
+/// the new keyword is one of [`LogConfig::log_states`]. Nothing is changed if the headline
+/// already has the keyword.
+pub fn change_state(
+    headline: &mut Headline,
+    keyword: Option<TodoKeyword>,
+    config: &LogConfig,
+    destination: &LogDestination,
+    time: NaiveDateTime,
+) {
+    if headline.todo_keyword == keyword {
+        return;
+    }
+    let is_done = |keyword: &Option<TodoKeyword>| matches!(keyword, Some(TodoKeyword::Done(_)));
+    if config.log_done && is_done(&keyword) && !is_done(&headline.todo_keyword) {
+        headline
+            .planning
+            .get_or_insert_with(Planning::default)
+            .closed = Some(inactive(time));
+    } else if !is_done(&keyword) {
+        if let Some(planning) = &mut headline.planning {
+            planning.closed = None;
+            if planning.deadline.is_none() && planning.scheduled.is_none() {
+                headline.planning = None;
+            }
+        }
+    }
+
+    let name = |keyword: &TodoKeyword| match keyword {
+        TodoKeyword::Todo(name) | TodoKeyword::Done(name) => name.clone(),
+    };
+    let from = headline.todo_keyword.as_ref().map(name);
+    let to = keyword.as_ref().map(name);
+    headline.todo_keyword = keyword;
+    if let Some(to) = to.filter(|to| config.log_states.contains(to)) {
+        let change = StateChange {
+            from,
+            to,
+            time,
+            note: None,
+        };
+        log_state_change(headline, destination, &change);
+    }
+}
+
+/// Starts a clock on the headline.
+pub fn clock_in(headline: &mut Headline, destination: &LogDestination, time: NaiveDateTime) {
+    let clock = Clock::new(
+        Some(Timestamp::new(
+            TimestampStatus::Inactive,
+            time.date(),
+            Some(time.time()),
+        )),
+        None,
+    );
+    log_elements(headline, destination).insert(0, clock.into());
+}
+
+/// Stops the running clock of the headline.
+///
+/// The clock is searched at the start of the section and in all drawers of the section. Returns
+/// the duration in minutes or `None` if no clock was running.
+pub fn clock_out(headline: &mut Headline, time: NaiveDateTime) -> Option<i64> {
+    let section = headline.section_mut();
+    let clock = find_running_clock(section.content_mut())?;
+    let start = clock.timestamp.as_ref()?.start_date_time()?;
+    let minutes = (time - start).num_minutes().max(0);
+    clock.timestamp = Some(Timestamp {
+        kind: TimestampKind::Range(
+            TimestampStatus::Inactive,
+            TimestampRange::DateRange(data(start), data(time)),
+        ),
+    });
+    clock.duration = Some(OrgDuration::from_minutes(minutes));
+    Some(minutes)
+}
+
+fn find_running_clock(elements: &mut Vec<ElementSet>) -> Option<&mut Clock> {
+    for element in elements {
+        match element {
+            ElementSet::Clock(clock) if clock.status() == ClockStatus::Running => {
+                return Some(clock)
+            }
+            ElementSet::Drawer(drawer) => {
+                if let Some(clock) = find_running_clock(drawer.content_mut()) {
+                    return Some(clock);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the elements where log entries are inserted (at the front).
+fn log_elements<'a>(
+    headline: &'a mut Headline,
+    destination: &LogDestination,
+) -> &'a mut Vec<ElementSet> {
+    let elements = headline.section_mut().content_mut();
+    let name = match destination {
+        LogDestination::Section => return elements,
+        LogDestination::Drawer(name) => name,
+    };
+    let index = match elements.iter().position(|element| match element {
+        ElementSet::Drawer(drawer) => drawer.name.eq_ignore_ascii_case(name),
+        _ => false,
+    }) {
+        Some(index) => index,
+        None => {
+            elements.insert(
+                0,
+                Drawer::new(name.to_string(), Spanned::new(Vec::new())).into(),
+            );
+            0
+        }
+    };
+    match &mut elements[index] {
+        ElementSet::Drawer(drawer) => drawer.content_mut(),
+        _ => unreachable!("the element at the index is a drawer"),
+    }
+}
+
+fn data(time: NaiveDateTime) -> TimestampData {
+    TimestampData {
+        date: time.date().into(),
+        time: Some(time.time().into()),
+        repeater: None,
+        warning: None,
+    }
+}
+
+fn inactive(time: NaiveDateTime) -> Timestamp {
+    Timestamp::new(TimestampStatus::Inactive, time.date(), Some(time.time()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn time(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2018, 10, 12).and_hms(hour, minute, 0)
+    }
+
+    fn section(headline: &mut Headline) -> String {
+        headline.section_mut().to_string()
+    }
+
+    fn log_config() -> LogConfig {
+        LogConfig {
+            log_into_drawer: true,
+            ..LogConfig::default()
+        }
+    }
+
+    #[test]
+    fn destination_from_config() {
+        let headline = Headline::default();
+        assert_eq!(
+            LogConfig::default().destination(&headline, &[]),
+            LogDestination::Section
+        );
+        assert_eq!(
+            log_config().destination(&headline, &[]),
+            LogDestination::Drawer(LOGBOOK.to_string())
+        );
+    }
+
+    #[test]
+    fn destination_from_properties() {
+        let mut parent = Headline::default();
+        let mut headline = Headline::default();
+        parent.set_property("LOG_INTO_DRAWER", "nil");
+        assert_eq!(
+            log_config().destination(&headline, &[&parent]),
+            LogDestination::Section
+        );
+        headline.set_property("LOG_INTO_DRAWER", "NOTES");
+        assert_eq!(
+            LogConfig::default().destination(&headline, &[&parent]),
+            LogDestination::Drawer("NOTES".to_string())
+        );
+    }
+
+    #[test]
+    fn clock_in_and_out() {
+        let mut headline = Headline::default();
+        let destination = LogDestination::Drawer(LOGBOOK.to_string());
+        clock_in(&mut headline, &destination, time(10, 0));
+        assert_eq!(clock_out(&mut headline, time(11, 30)), Some(90));
+        assert_eq!(clock_out(&mut headline, time(12, 0)), None);
+        assert_eq!(
+            section(&mut headline),
+            ":LOGBOOK:\n\
+             CLOCK: [2018-10-12 Fri 10:00]--[2018-10-12 Fri 11:30] =>  1:30\n\
+             :END:"
+        );
+    }
+
+    #[test]
+    fn log_into_section() {
+        let mut headline = Headline::default();
+        clock_in(&mut headline, &LogDestination::Section, time(9, 5));
+        assert_eq!(section(&mut headline), "CLOCK: [2018-10-12 Fri 09:05]");
+    }
+
+    /// Logs a change to DONE at 11:30 and a change to TODO with a note at 12:00.
+    fn log_two_changes(headline: &mut Headline) {
+        let destination = LogDestination::Drawer(LOGBOOK.to_string());
+        log_state_change(
+            headline,
+            &destination,
+            &StateChange {
+                from: Some("TODO".to_string()),
+                to: "DONE".to_string(),
+                time: time(11, 30),
+                note: None,
+            },
+        );
+        log_state_change(
+            headline,
+            &destination,
+            &StateChange {
+                from: None,
+                to: "TODO".to_string(),
+                time: time(12, 0),
+                note: Some("Reopened".to_string()),
+            },
+        );
+    }
+
+    #[test]
+    fn log_state_changes_into_drawer() {
+        let mut headline = Headline::default();
+        log_two_changes(&mut headline);
+        assert_eq!(
+            section(&mut headline),
+            ":LOGBOOK:\n\
+             - State \"TODO\"       from              [2018-10-12 Fri 12:00] \\\\\n\
+             \x20 Reopened\n\
+             - State \"DONE\"       from \"TODO\"       [2018-10-12 Fri 11:30]\n\
+             :END:"
+        );
+    }
+
+    #[test]
+    fn read_state_changes() {
+        let mut headline = Headline::default();
+        log_two_changes(&mut headline);
+        let changes = state_changes(&headline);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| (change.from.as_deref(), change.to.as_str(), change.time))
+                .collect::<Vec<_>>(),
+            vec![
+                (None, "TODO", time(12, 0)),
+                (Some("TODO"), "DONE", time(11, 30))
+            ]
+        );
+        assert_eq!(changes[0].note.as_deref(), Some("Reopened"));
+        assert_eq!(changes[1].note, None);
+    }
+
+    const TODO_SETTINGS: &str = "#+STARTUP: logdone logdrawer\n\
+                                 #+TODO: TODO WAIT(w@/!) | DONE(d!) CANCELLED\n\
+                                 * TODO Task\n";
+
+    #[test]
+    fn log_config_from_document() {
+        let document = Document::parse(TODO_SETTINGS).unwrap();
+        let config = LogConfig::from_document(&document);
+        assert!(config.log_done);
+        assert!(config.log_into_drawer);
+        assert_eq!(config.log_states, ["WAIT", "DONE"]);
+    }
+
+    #[test]
+    fn change_state_to_done() {
+        let mut document = Document::parse(TODO_SETTINGS).unwrap();
+        let config = LogConfig::from_document(&document);
+        let destination = config.destination(&document.headlines[0], &[]);
+        let headline = &mut document.headlines[0];
+
+        let done = Some(TodoKeyword::Done("DONE".to_string()));
+        change_state(headline, done.clone(), &config, &destination, time(11, 30));
+        // changing to the same state again is not logged
+        change_state(headline, done, &config, &destination, time(12, 0));
+        assert_eq!(
+            headline.to_string(),
+            "* DONE Task\n\
+             CLOSED: [2018-10-12 Fri 11:30]\n\
+             :LOGBOOK:\n\
+             - State \"DONE\"       from \"TODO\"       [2018-10-12 Fri 11:30]\n\
+             :END:"
+        );
+    }
+
+    #[test]
+    fn closed_is_parsed_again() {
+        let mut document = Document::parse(TODO_SETTINGS).unwrap();
+        let config = LogConfig::from_document(&document);
+        let destination = config.destination(&document.headlines[0], &[]);
+        let done = Some(TodoKeyword::Done("DONE".to_string()));
+        change_state(
+            &mut document.headlines[0],
+            done,
+            &config,
+            &destination,
+            time(11, 30),
+        );
+        let reparsed = Document::parse(&document.to_string()).unwrap();
+        assert_eq!(
+            reparsed.headlines[0].planning,
+            document.headlines[0].planning
+        );
+    }
+
+    #[test]
+    fn reopen_removes_closed() {
+        let mut document = Document::parse(TODO_SETTINGS).unwrap();
+        let config = LogConfig::from_document(&document);
+        let destination = config.destination(&document.headlines[0], &[]);
+        let headline = &mut document.headlines[0];
+
+        let done = Some(TodoKeyword::Done("DONE".to_string()));
+        change_state(headline, done, &config, &destination, time(11, 30));
+        let todo = Some(TodoKeyword::Todo("TODO".to_string()));
+        change_state(headline, todo, &config, &destination, time(12, 0));
+        assert_eq!(headline.planning, None);
+        // TODO is not in the logged states
+        assert_eq!(state_changes(headline).len(), 1);
+    }
+}
@@ -1 +1,162 @@
+/// Defines a set of objects: an enum of a `RawString(String)` variant and some of the
+/// [`objects`].
+///
+/// Every variant (except `RawString`) has the same name as the object it contains. The macro
+/// implements [`AsRawString`], `Display` and `From` for each of the contained objects (and
+/// `String`). References to the set can be converted into [`ObjectRef`] and [`ObjectRefMut`].
+/// The enum is `#[non_exhaustive]` because more objects may be allowed in the set in future
+/// versions of org mode.
+///
+/// With `from StandardSet` it also implements `From<StandardSet>`. Objects that are not in the set
+/// are replaced by their textual representation (line breaks by a space). The other direction
+/// (`From<Name> for StandardSet`) is lossless.
+///
+/// ```ignore
+/// define_object_set! {
+///     /// The objects in a radio target.
+///     RadioTargetSetOfObjects from StandardSet {
+///         Entity,
+///         TextMarkup,
+///     }
+/// }
+/// ```
+///
+/// [`objects`]: `crate::types::objects`
+/// [`AsRawString`]: `crate::types::AsRawString`
+/// [`ObjectRef`]: `crate::types::visit::ObjectRef`
+/// [`ObjectRefMut`]: `crate::types::visit::ObjectRefMut`
+macro_rules! define_object_set {
+    (
+        $(#[$meta:meta])*
+        $name:ident from StandardSet {
+            $($(#[$variant_meta:meta])* $variant:ident),+ $(,)*
+        }
+    ) => {
+        define_object_set! {
+            $(#[$meta])*
+            $name {
+                $($(#[$variant_meta])* $variant),+
+            }
+        }
 
+        impl From<$crate::types::StandardSet> for $name {
+            fn from(object: $crate::types::StandardSet) -> Self {
+                #[allow(unreachable_patterns)]
+                match object {
+                    $crate::types::StandardSet::RawString(s) => $name::RawString(s),
+                    $(
+                        $crate::types::StandardSet::$variant(o) => $name::$variant(o),
+                    )+
+                    object => match object {
+                        $crate::types::StandardSet::LineBreak(_) => {
+                            $name::RawString(" ".to_string())
+                        }
+                        object => $name::RawString(object.to_string()),
+                    },
+                }
+            }
+        }
+
+        impl From<$name> for $crate::types::StandardSet {
+            fn from(object: $name) -> Self {
+                match object {
+                    $name::RawString(s) => $crate::types::StandardSet::RawString(s),
+                    $(
+                        $name::$variant(o) => $crate::types::StandardSet::$variant(o),
+                    )+
+                }
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident),+ $(,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[non_exhaustive]
+        pub enum $name {
+            RawString(String),
+            $(
+                $(#[$variant_meta])*
+                $variant($crate::types::objects::$variant),
+            )+
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $name::RawString(s) => write!(f, "{}", s),
+                    $(
+                        $name::$variant(o) => write!(f, "{}", o),
+                    )+
+                }
+            }
+        }
+
+        impl $crate::types::AsRawString for $name {
+            fn as_raw_string(&self) -> Option<&str> {
+                if let $name::RawString(s) = self {
+                    Some(s)
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                $name::RawString(s)
+            }
+        }
+
+        $(
+            impl From<$crate::types::objects::$variant> for $name {
+                fn from(object: $crate::types::objects::$variant) -> Self {
+                    $name::$variant(object)
+                }
+            }
+        )+
+
+        impl<'a> From<&'a $name> for $crate::types::visit::ObjectRef<'a> {
+            fn from(object: &'a $name) -> Self {
+                match object {
+                    $name::RawString(s) => $crate::types::visit::ObjectRef::RawString(s),
+                    $(
+                        $name::$variant(o) => $crate::types::visit::ObjectRef::$variant(o),
+                    )+
+                }
+            }
+        }
+
+        impl<'a> From<&'a mut $name> for $crate::types::visit::ObjectRefMut<'a> {
+            fn from(object: &'a mut $name) -> Self {
+                match object {
+                    $name::RawString(s) => $crate::types::visit::ObjectRefMut::RawString(s),
+                    $(
+                        $name::$variant(o) => $crate::types::visit::ObjectRefMut::$variant(o),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::objects::{LineBreak, RadioTargetSetOfObjects};
+    use crate::types::{AsRawString, StandardSet};
+
+    #[test]
+    fn object_set_from_standard_set() {
+        let raw = RadioTargetSetOfObjects::from(StandardSet::RawString("a".to_string()));
+        assert_eq!(raw.as_raw_string(), Some("a"));
+        assert_eq!(
+            RadioTargetSetOfObjects::from(StandardSet::from(LineBreak { spaces: 0 })),
+            RadioTargetSetOfObjects::RawString(" ".to_string())
+        );
+    }
+}
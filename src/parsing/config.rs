@@ -0,0 +1,204 @@
+//! Configuration of the parser.
+
+use crate::types::greater_elements::TodoKeyword;
+
+/// Configuration for [`parse_document_with_config`].
+///
+/// The default configuration parses the syntax of the latest org version.
+///
+/// [`parse_document_with_config`]: `crate::parsing::parse_document_with_config`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseConfig {
+    /// Switches for syntax that changed between org versions.
+    pub compat: Compat,
+    /// The keywords at the start of a headline title that are parsed as todo keywords (like
+    /// `org-todo-keywords`).
+    ///
+    /// `#+TODO:`, `#+SEQ_TODO:` and `#+TYP_TODO:` keywords in the preface of a document replace
+    /// these sequences for that document.
+    pub todo_keywords: Vec<TodoSequence>,
+    /// The minimum number of stars of an [`Inlinetask`] (like `org-inlinetask-min-level`).
+    ///
+    /// Lines with fewer stars are headlines. `None` parses all of them as headlines (like org
+    /// mode does without `org-inlinetask`).
+    ///
+    /// [`Inlinetask`]: `crate::types::greater_elements::Inlinetask`
+    pub inlinetask_min_level: Option<u32>,
+}
+
+impl ParseConfig {
+    /// Creates a configuration that parses files as the given org version would.
+    pub fn for_org_version(version: OrgVersion) -> Self {
+        ParseConfig {
+            compat: Compat::for_org_version(version),
+            todo_keywords: vec![TodoSequence::new(&["TODO", "NEXT"], &["DONE"])],
+            inlinetask_min_level: Some(15),
+        }
+    }
+
+    /// Returns the todo keyword if `word` is part of one of the sequences.
+    pub fn todo_keyword(&self, word: &str) -> Option<TodoKeyword> {
+        self.todo_keywords.iter().find_map(|sequence| {
+            if sequence.todo.iter().any(|todo| todo == word) {
+                Some(TodoKeyword::Todo(word.to_string()))
+            } else if sequence.done.iter().any(|done| done == word) {
+                Some(TodoKeyword::Done(word.to_string()))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Default for ParseConfig {
+    /// The configuration for the latest org version.
+    fn default() -> Self {
+        ParseConfig::for_org_version(OrgVersion::LATEST)
+    }
+}
+
+/// A sequence of todo keywords (e.g. `TODO NEXT | DONE CANCELLED`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TodoSequence {
+    /// The keywords of unfinished tasks.
+    pub todo: Vec<String>,
+    /// The keywords of finished tasks.
+    pub done: Vec<String>,
+}
+
+impl TodoSequence {
+    pub fn new(todo: &[&str], done: &[&str]) -> Self {
+        TodoSequence {
+            todo: todo.iter().map(|s| s.to_string()).collect(),
+            done: done.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Parses the value of a `#+TODO:` keyword (e.g. `TODO(t) WAIT(w@) | DONE(d!)`).
+    ///
+    /// Fast access keys and logging settings in parentheses are ignored. Without a `|` the last
+    /// keyword is the only done keyword. Returns `None` if there are no keywords.
+    pub fn parse(value: &str) -> Option<TodoSequence> {
+        let keyword = |word: &str| word.split('(').next().unwrap_or_default().to_string();
+        let (todo, done): (Vec<_>, Vec<_>) = match value.find('|') {
+            Some(bar) => (
+                value[..bar].split_whitespace().map(keyword).collect(),
+                value[bar + 1..].split_whitespace().map(keyword).collect(),
+            ),
+            None => {
+                let mut todo: Vec<_> = value.split_whitespace().map(keyword).collect();
+                let done = todo.pop().into_iter().collect();
+                (todo, done)
+            }
+        };
+        if todo.is_empty() && done.is_empty() {
+            None
+        } else {
+            Some(TodoSequence { todo, done })
+        }
+    }
+}
+
+/// Compatibility switches for syntax that changed between org versions.
+///
+/// Use [`Compat::for_org_version`] to get the switches matching the org version a file was
+/// written with. This allows older archives to be parsed as intended.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Compat {
+    /// Headlines whose title starts with `QUOTE` are quoted (their section is quoted text).
+    ///
+    /// Removed in org 8.3 where quote blocks (`#+BEGIN_QUOTE`) have to be used instead. See
+    /// [`Headline::quoted`].
+    ///
+    /// [`Headline::quoted`]: `crate::types::greater_elements::Headline::quoted`
+    pub quote_headlines: bool,
+}
+
+impl Compat {
+    /// Returns the switches matching the syntax of the given org version.
+    pub fn for_org_version(version: OrgVersion) -> Self {
+        Compat {
+            quote_headlines: version < OrgVersion::new(8, 3),
+        }
+    }
+}
+
+impl Default for Compat {
+    /// The switches of the latest org version.
+    fn default() -> Self {
+        Compat::for_org_version(OrgVersion::LATEST)
+    }
+}
+
+/// A version of org mode (only major and minor version are relevant for the syntax).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OrgVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl OrgVersion {
+    /// The latest org version whose syntax is supported.
+    pub const LATEST: OrgVersion = OrgVersion { major: 9, minor: 6 };
+
+    pub fn new(major: u32, minor: u32) -> Self {
+        OrgVersion { major, minor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compat_before_org_8_3() {
+        let compat = Compat::for_org_version(OrgVersion::new(8, 2));
+        assert!(compat.quote_headlines);
+    }
+
+    #[test]
+    fn compat_since_org_8_3() {
+        let compat = Compat::for_org_version(OrgVersion::new(8, 3));
+        assert!(!compat.quote_headlines);
+    }
+
+    #[test]
+    fn default_compat_is_latest_version() {
+        assert_eq!(
+            Compat::default(),
+            Compat::for_org_version(OrgVersion::LATEST)
+        );
+        assert!(!Compat::default().quote_headlines);
+    }
+
+    #[test]
+    fn todo_sequences() {
+        assert_eq!(
+            TodoSequence::parse("TODO(t) WAIT(w@/!) | DONE(d!) CANCELLED(c@)"),
+            Some(TodoSequence::new(&["TODO", "WAIT"], &["DONE", "CANCELLED"]))
+        );
+        assert_eq!(
+            TodoSequence::parse("REPORT BUG KNOWNCAUSE FIXED"),
+            Some(TodoSequence::new(
+                &["REPORT", "BUG", "KNOWNCAUSE"],
+                &["FIXED"]
+            ))
+        );
+        assert_eq!(
+            TodoSequence::parse("| DONE"),
+            Some(TodoSequence::new(&[], &["DONE"]))
+        );
+        assert_eq!(TodoSequence::parse("  "), None);
+
+        let config = ParseConfig::default();
+        assert_eq!(
+            config.todo_keyword("NEXT"),
+            Some(TodoKeyword::Todo("NEXT".to_string()))
+        );
+        assert_eq!(
+            config.todo_keyword("DONE"),
+            Some(TodoKeyword::Done("DONE".to_string()))
+        );
+        assert_eq!(config.todo_keyword("Done"), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Parsing with error recovery.
+//!
+//! [`parse_document_with_config`] fails at the first error. [`parse_with_diagnostics`] instead
+//! parses the preface and every headline on its own (see [`HeadlineIter`]). A part that can't be
+//! parsed is kept as raw text and reported as a [`ParseDiagnostic`], so the rest of the document
+//! is still usable (e.g. in an editor while the user is typing).
+
+use super::stream::first_headline;
+use super::{link_radio_targets, parse_document_with_config, with_in_buffer_settings};
+use super::{HeadlineIter, ParseConfig, ParseError, RawHeadline};
+use crate::types::document::Document;
+use crate::types::elements::Paragraph;
+use crate::types::greater_elements::{Headline, HeadlineContentSet, Section};
+use crate::types::{SecondaryString, Span, Spanned, StandardSet, StandardSetNoLineBreak};
+use std::fmt;
+
+/// A [`ParseError`] with its position in the parsed string.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ParseDiagnostic {
+    pub error: ParseError,
+    /// The position of the error. Errors without a position (see [`ParseError::span`]) span the
+    /// whole part that couldn't be parsed.
+    pub span: Span,
+    /// The line of the start of the span (starting at 1).
+    pub line: usize,
+    /// The column of the start of the span in characters (starting at 1).
+    pub column: usize,
+    /// See [`ParseError::expected`].
+    pub expected: String,
+    /// The text at the start of the span up to the end of the line.
+    pub found: String,
+}
+
+impl ParseDiagnostic {
+    /// Creates the diagnostic for an error in the `part` of `s`.
+    fn new(error: ParseError, s: &str, part: Span) -> Self {
+        let start = part.start();
+        let span = error.span().map_or(part, |span| {
+            Span::new(start + span.start(), start + span.end())
+        });
+        let (line, column) = line_column(s, span.start());
+        let rest = &s[span.start()..];
+        let found = match rest.lines().next() {
+            None => "end of input",
+            Some("") => "end of line",
+            Some(line) => line,
+        };
+        ParseDiagnostic {
+            expected: error.expected(),
+            error,
+            span,
+            line,
+            column,
+            found: found.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: expected {}, found {:?}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+/// Returns the line and the column in characters (both starting at 1) of the byte `offset`.
+pub(crate) fn line_column(s: &str, offset: usize) -> (usize, usize) {
+    let before = &s[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Parses a document and collects the errors instead of failing at the first one.
+///
+/// Returns the same document as [`parse_document_with_config`] if there are no errors.
+/// Otherwise every headline (with its section) that can't be parsed is replaced by a headline
+/// with the raw title and the section as a single paragraph of raw text. Its children are still
+/// parsed. The same is done for the preface. The spans of such a document are relative to the
+/// start of the preface or top level headline they are in.
+pub fn parse_with_diagnostics(s: &str, config: &ParseConfig) -> (Document, Vec<ParseDiagnostic>) {
+    if let Ok(document) = parse_document_with_config(s, config) {
+        return (document, Vec::new());
+    }
+    let mut diagnostics = Vec::new();
+    let headlines = HeadlineIter::new(s);
+    let preface =
+        headlines.preface().and_then(
+            |preface| match parse_document_with_config(preface, config) {
+                Ok(document) => document.preface,
+                Err(error) => {
+                    let span = Span::new(0, preface.len());
+                    diagnostics.push(ParseDiagnostic::new(error, s, span));
+                    Some(raw_section(preface))
+                }
+            },
+        );
+    let mut recovery = Recovery {
+        s,
+        config: with_in_buffer_settings(config, preface.as_ref()),
+        diagnostics,
+    };
+    let headlines = headlines
+        .map(|raw| recovery.headline(raw, raw.offset))
+        .collect();
+    let mut document = Document { preface, headlines };
+    link_radio_targets(&mut document);
+    (document, recovery.diagnostics)
+}
+
+struct Recovery<'a> {
+    s: &'a str,
+    config: ParseConfig,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl<'a> Recovery<'a> {
+    /// Parses the headline at the byte `offset` of the whole string.
+    fn headline(&mut self, raw: RawHeadline<'_>, offset: usize) -> Headline {
+        if let Ok(headline) = raw.parse(&self.config) {
+            return headline;
+        }
+        let line_end = raw
+            .text
+            .find('\n')
+            .map_or(raw.text.len(), |index| index + 1);
+        let own_end = line_end + raw.section().len();
+        let own = parse_document_with_config(&raw.text[..own_end], &self.config);
+        let mut headline = match own.and_then(first_headline) {
+            Ok(headline) => headline,
+            Err(error) => {
+                let span = Span::new(offset, offset + own_end);
+                self.diagnostics
+                    .push(ParseDiagnostic::new(error, self.s, span));
+                let mut headline = self.headline_line(raw);
+                if !raw.section().is_empty() {
+                    let section = raw_section(raw.section());
+                    headline.push_content(Some(HeadlineContentSet::Section(section)));
+                }
+                headline
+            }
+        };
+        let mut children = Vec::new();
+        for child in HeadlineIter::new(&raw.text[own_end..]) {
+            let child = self.headline(child, offset + own_end + child.offset);
+            children.push(HeadlineContentSet::Headline(Box::new(child)));
+        }
+        headline.push_content(children);
+        headline
+    }
+
+    /// Parses only the headline line. Uses the raw title if the line can't be parsed either.
+    fn headline_line(&self, raw: RawHeadline<'_>) -> Headline {
+        let line = raw.headline_line();
+        parse_document_with_config(line, &self.config)
+            .ok()
+            .and_then(|document| document.headlines.into_iter().next())
+            .unwrap_or_else(|| {
+                let title = line.trim_start_matches('*').trim();
+                Headline {
+                    level: raw.level,
+                    title: Some(SecondaryString::with_one(
+                        StandardSetNoLineBreak::RawString(title.to_string()),
+                    )),
+                    ..Headline::default()
+                }
+            })
+    }
+}
+
+/// Keeps the text as a single paragraph.
+fn raw_section(text: &str) -> Section {
+    let text = text.trim_end_matches('\n').to_string();
+    let paragraph = Paragraph::new(SecondaryString::with_one(StandardSet::RawString(text)));
+    Section::new(Spanned::new(vec![paragraph.into()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery() {
+        let s = "Intro\n* a\n*bold*\n** b\ntext\n* c\n";
+        let (document, diagnostics) = parse_with_diagnostics(s, &ParseConfig::default());
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 2));
+        assert_eq!(diagnostic.span.start(), s.find("bold").unwrap());
+        assert_eq!(diagnostic.found, "bold*");
+        assert_eq!(
+            diagnostic.to_string(),
+            format!("3:2: expected {}, found \"bold*\"", diagnostic.expected)
+        );
+        assert_eq!(document.to_string(), s);
+        assert_eq!(document.headlines.len(), 2);
+
+        let (document, diagnostics) = parse_with_diagnostics("* a\n", &ParseConfig::default());
+        assert!(diagnostics.is_empty());
+        assert_eq!(document.headlines.len(), 1);
+    }
+}
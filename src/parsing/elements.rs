@@ -0,0 +1,1414 @@
+//! Parsers for elements and greater elements.
+//!
+//! Currently clocks, planning lines, keywords, blocks, drawers, footnote definitions, plain lists
+//! and tables are recognized. Affiliated keywords are the keywords directly above another
+//! element. Drawers can be indented and are also recognized in the items of plain lists.
+
+use super::incremental::{map_spans, shift_spans};
+use super::objects::{parse_objects, parse_objects_at, parse_table_row};
+use super::{AffiliatedKeywordInfo, OrgModeParser, ParseError, Rule};
+use crate::types::affiliated_keywords::{AffiliatedKeyword, Attr, Caption, Results};
+use crate::types::elements::{
+    BlockFlags, Clock, CommentBlock, ExampleBlock, ExportBlock, HeaderArgs, Keyword,
+    KeywordValueSetOfObjects, NodeProperty, NumberLinesFlag, Paragraph, Planning, SrcBlock,
+};
+use crate::types::greater_elements::{
+    CenterBlock, Checkbox, Counter, CounterDelimiter, Drawer, FootnoteDefinition, Item, ItemKind,
+    OrderedBullet, PlainList, PropertyDrawer, QuoteBlock, SpecialBlock, Table, TableKind,
+    TableRowKind, UnorderedBullet, VerseBlock,
+};
+use crate::types::serialize::KeywordCase;
+use crate::types::{unescape_paragraph, ElementSet, Parent, SharedBehavior, Span, Spanned};
+
+use itertools::Itertools;
+use pest::iterators::Pair;
+use pest::Parser;
+
+use std::str::FromStr;
+
+impl FromStr for SrcBlock {
+    type Err = ParseError;
+
+    /// Parses a source block (see [`SrcBlock`] for the syntax).
+    ///
+    /// Only trailing newlines may follow the `#+END_SRC` line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pair = OrgModeParser::parse(Rule::src_block, s)?.next().unwrap();
+        if !s[pair.as_span().end()..]
+            .trim_start_matches('\n')
+            .is_empty()
+        {
+            return Err(ParseError::StructuralError(
+                "unexpected content after #+END_SRC",
+            ));
+        }
+        parse_src_block(pair)
+    }
+}
+
+impl FromStr for Clock {
+    type Err = ParseError;
+
+    /// Parses a clock line (see [`Clock`] for the syntax).
+    ///
+    /// Only trailing newlines may follow the clock.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pair = OrgModeParser::parse(Rule::clock, s)?.next().unwrap();
+        if !s[pair.as_span().end()..]
+            .trim_start_matches('\n')
+            .is_empty()
+        {
+            return Err(ParseError::StructuralError(
+                "unexpected content after the clock",
+            ));
+        }
+        parse_clock(pair)
+    }
+}
+
+pub(crate) fn parse_clock<'i>(pair: Pair<'i, Rule>) -> Result<Clock, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::clock);
+
+    let mut clock = Clock::new(None, None);
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::clock_timestamp => {
+                let timestamp = pair
+                    .as_str()
+                    .parse()
+                    .map_err(|_| ParseError::StructuralError("invalid timestamp in clock"))?;
+                clock.timestamp = Some(timestamp);
+            }
+            Rule::clock_duration => {
+                let duration = pair
+                    .as_str()
+                    .parse()
+                    .map_err(|_| ParseError::StructuralError("too many hours in clock"))?;
+                clock.duration = Some(duration);
+            }
+            _ => {}
+        }
+    }
+    Ok(clock)
+}
+
+/// Parses the planning line of a headline.
+///
+/// If a keyword is repeated the last timestamp is used.
+pub(crate) fn parse_planning<'i>(pair: Pair<'i, Rule>) -> Result<Planning, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::planning);
+
+    let mut planning = Planning::default();
+    for item in pair
+        .into_inner()
+        .filter(|pair| pair.as_rule() == Rule::planning_item)
+    {
+        let mut keyword = "";
+        let mut timestamp = "";
+        for pair in item.into_inner() {
+            match pair.as_rule() {
+                Rule::planning_keyword => keyword = pair.as_str(),
+                Rule::planning_timestamp => timestamp = pair.as_str(),
+                _ => {}
+            }
+        }
+        let timestamp = timestamp
+            .parse()
+            .map_err(|_| ParseError::StructuralError("invalid timestamp in planning"))?;
+        match keyword {
+            "CLOSED" => planning.closed = Some(timestamp),
+            "DEADLINE" => planning.deadline = Some(timestamp),
+            "SCHEDULED" => planning.scheduled = Some(timestamp),
+            _ => unreachable!("the grammar only allows planning keywords"),
+        }
+    }
+    Ok(planning)
+}
+
+pub(crate) fn parse_keyword<'i>(pair: Pair<'i, Rule>) -> Result<Keyword, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::keyword);
+
+    let mut key = "";
+    let mut value = "";
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::keyword_key => key = pair.as_str(),
+            Rule::keyword_value => value = pair.as_str(),
+            _ => {}
+        }
+    }
+    // TODO parse the objects of keywords that can contain them (e.g. `TITLE`)
+    Ok(Keyword::new(
+        key,
+        vec![KeywordValueSetOfObjects::RawString(
+            value.trim().to_string(),
+        )],
+    ))
+}
+
+/// Converts a keyword directly above another element to an affiliated keyword of that element.
+///
+/// Returns `None` if the key isn't the key of an affiliated keyword.
+pub(crate) fn affiliated_keyword(keyword: &Keyword) -> Option<AffiliatedKeyword> {
+    let value = keyword.value.value().iter().join("");
+    let (key, optional) = match keyword.key.find('[') {
+        Some(start) if keyword.key.ends_with(']') => (
+            &keyword.key[..start],
+            Some(&keyword.key[start + 1..keyword.key.len() - 1]),
+        ),
+        _ => (keyword.key.as_str(), None),
+    };
+    let info = match AffiliatedKeywordInfo::find(key) {
+        Some(info) if optional.is_none() || info.optional_value => info,
+        _ if optional.is_none() && is_attr_key(key) => {
+            return Some(AffiliatedKeyword::Attr(Spanned::new(Attr::new(
+                key[5..].to_string(),
+                value,
+            ))));
+        }
+        _ => return None,
+    };
+    Some(match info.name {
+        "CAPTION" => AffiliatedKeyword::Caption(Spanned::new(Caption::with_option_optional(
+            parse_objects(&value).into(),
+            optional.map(|optional| parse_objects(optional).into()),
+        ))),
+        "RESULTS" => AffiliatedKeyword::Results(Spanned::new(Results::new(
+            parse_objects(&value).into(),
+            optional.map(|optional| parse_objects(optional).into()),
+        ))),
+        "HEADER" => AffiliatedKeyword::Header(Spanned::new(value)),
+        "NAME" => AffiliatedKeyword::Name(Spanned::new(value)),
+        "PLOT" => AffiliatedKeyword::Plot(Spanned::new(value)),
+        _ => return None,
+    })
+}
+
+/// Returns `true` for keys like `ATTR_HTML` (case insensitive).
+fn is_attr_key(key: &str) -> bool {
+    key.len() > 5
+        && key
+            .get(..5)
+            .is_some_and(|start| start.eq_ignore_ascii_case("ATTR_"))
+}
+
+pub(crate) fn parse_src_block<'i>(pair: Pair<'i, Rule>) -> Result<SrcBlock, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::src_block);
+
+    let keyword_case = begin_keyword_case(pair.as_str());
+    let mut parameters = "";
+    let mut contents = "";
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::src_block_parameters => parameters = pair.as_str(),
+            Rule::src_block_contents => contents = pair.as_str(),
+            _ => {}
+        }
+    }
+
+    let parameters = parameters.trim();
+    let (language, rest) = split_word(parameters);
+    let (flags, arguments) = parse_block_flags(rest);
+    let mut block = SrcBlock::new(language.to_string(), unescape_block_content(contents));
+    block.flags = flags;
+    block.arguments = HeaderArgs::parse(arguments);
+    block.keyword_case = keyword_case;
+    Ok(block)
+}
+
+/// Returns the case of the `#+BEGIN_` keyword at the start of a block.
+fn begin_keyword_case(block: &str) -> KeywordCase {
+    KeywordCase::of(block.trim_start().get(2..7).unwrap_or(""))
+}
+
+/// Splits off the first word of `s`. The rest starts at the next non-whitespace character.
+fn split_word(s: &str) -> (&str, &str) {
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    (&s[..end], s[end..].trim_start())
+}
+
+/// Parses the flags at the start of `s` (see [`BlockFlags`]).
+///
+/// Returns the flags and the rest of `s` (the arguments of the block). Parsing stops at the
+/// first word that is not a known flag.
+fn parse_block_flags(mut s: &str) -> (BlockFlags, &str) {
+    let mut flags = BlockFlags::default();
+    loop {
+        let (word, rest) = split_word(s);
+        match word {
+            "-n" | "+n" => {
+                let (number, after_number) = split_word(rest);
+                let (amount, rest) = match number.parse() {
+                    Ok(amount) => (Some(amount), after_number),
+                    Err(_) => (None, rest),
+                };
+                flags.number_lines = Some(if word == "-n" {
+                    NumberLinesFlag::New(amount)
+                } else {
+                    NumberLinesFlag::Continued(amount)
+                });
+                s = rest;
+            }
+            "-i" => {
+                flags.preserve_indent = true;
+                s = rest;
+            }
+            "-r" => {
+                flags.retain_labels = false;
+                s = rest;
+            }
+            "-l" if rest.starts_with('"') => match rest[1..].find('"') {
+                Some(end) => {
+                    flags.label_fmt = Some(rest[1..=end].to_string());
+                    s = rest[end + 2..].trim_start();
+                }
+                None => return (flags, s),
+            },
+            _ => return (flags, s),
+        }
+    }
+}
+
+/// Removes the comma that quotes lines starting with `*` or `#+` in blocks.
+///
+/// This is the reverse of the quoting done when blocks are written.
+fn unescape_block_content(content: &str) -> String {
+    content
+        .split_terminator('\n')
+        .map(|line| {
+            let indentation = line.len() - line.trim_start_matches(&[' ', '\t'][..]).len();
+            let rest = &line[indentation..];
+            let unquoted = rest.trim_start_matches(',');
+            if rest.starts_with(',') && (unquoted.starts_with('*') || unquoted.starts_with("#+")) {
+                format!("{}{}\n", &line[..indentation], &rest[1..])
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect()
+}
+
+/// Parses the blocks whose contents are not elements (except source blocks).
+pub(crate) fn parse_block<'i>(pair: Pair<'i, Rule>) -> Result<ElementSet, ParseError> {
+    let rule = pair.as_rule();
+    let keyword_case = begin_keyword_case(pair.as_str());
+    let mut name = "";
+    let mut parameters = "";
+    let mut contents = "";
+    let mut span = None;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::special_block_name => name = pair.as_str(),
+            Rule::block_parameters => parameters = pair.as_str().trim(),
+            _ => {
+                contents = pair.as_str();
+                span = Some(Span::from(pair.as_span()));
+            }
+        }
+    }
+
+    let value = unescape_block_content(contents);
+    Ok(match rule {
+        Rule::comment_block => {
+            let mut block = CommentBlock::new(value);
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        Rule::example_block => {
+            let mut block = ExampleBlock::new(value);
+            block.flags = parse_block_flags(parameters).0;
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        Rule::export_block => {
+            let mut block = ExportBlock::new(split_word(parameters).0, value);
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        Rule::verse_block => {
+            let mut block =
+                VerseBlock::new(Spanned::with_optional_span(parse_objects(&value), span));
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        Rule::center_block => {
+            let mut block = CenterBlock::new(Spanned::with_optional_span(value, span));
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        Rule::special_block => {
+            let mut block =
+                SpecialBlock::new(name.to_string(), Spanned::with_optional_span(value, span));
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        rule => unreachable!("not a block: {:?}", rule),
+    })
+}
+
+/// Parses drawers and quote blocks (whose contents are elements).
+pub(crate) fn parse_drawer<'i>(pair: Pair<'i, Rule>) -> Result<ElementSet, ParseError> {
+    let rule = pair.as_rule();
+    let keyword_case = begin_keyword_case(pair.as_str());
+    let mut name = "";
+    let mut elements = Vec::new();
+    let mut span = None;
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::drawer_name => name = pair.as_str(),
+            Rule::drawer_contents | Rule::quote_block_contents => {
+                span = Some(Span::from(pair.as_span()));
+                elements = super::parse_elements(pair)?;
+            }
+            _ => {}
+        }
+    }
+
+    let content = Spanned::with_optional_span(elements, span);
+    Ok(match rule {
+        Rule::drawer => Drawer::new(name.to_string(), content).into(),
+        Rule::quote_block => {
+            let mut block = QuoteBlock::new(content);
+            block.keyword_case = keyword_case;
+            block.into()
+        }
+        rule => unreachable!("not a drawer or quote block: {:?}", rule),
+    })
+}
+
+pub(crate) fn parse_footnote_definition<'i>(
+    pair: Pair<'i, Rule>,
+) -> Result<FootnoteDefinition, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::footnote_definition);
+
+    let mut label = "";
+    let mut content = Spanned::new(Vec::new());
+    for pair in pair.into_inner() {
+        match pair.as_rule() {
+            Rule::footnote_label => label = pair.as_str(),
+            _ => {
+                let span = Span::from(pair.as_span());
+                content = Spanned::with_span(super::parse_elements(pair)?, span);
+            }
+        }
+    }
+    Ok(FootnoteDefinition::new(label.to_string(), content))
+}
+
+pub(crate) fn parse_property_drawer<'i>(
+    pair: Pair<'i, Rule>,
+) -> Result<PropertyDrawer, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::property_drawer);
+
+    let span = pair.as_span().into();
+    let properties = pair
+        .into_inner()
+        .map(|pair| {
+            let mut property = NodeProperty {
+                name: String::new(),
+                value: String::new(),
+            };
+            for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::node_property_name => property.name = pair.as_str().to_string(),
+                    Rule::node_property_value => property.value = pair.as_str().trim().to_string(),
+                    _ => {}
+                }
+            }
+            property
+        })
+        .collect();
+    Ok(PropertyDrawer::new(Spanned::with_span(properties, span)))
+}
+
+pub(crate) fn parse_plain_list<'i>(pair: Pair<'i, Rule>) -> Result<PlainList, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::plain_list);
+
+    let start = pair.as_span().start();
+    let mut offset = start;
+    let lines: Vec<_> = pair
+        .as_str()
+        .split('\n')
+        .map(|text| {
+            let line = Line::new(offset, text);
+            offset += text.len() + 1;
+            line
+        })
+        .collect();
+    let mut position = 0;
+    Ok(parse_list(&lines, &mut position, None))
+}
+
+/// A line of a plain list.
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    /// The offset of the line in the parsed string.
+    start: usize,
+    text: &'a str,
+    /// The number of spaces and tabs at the start of the line.
+    indentation: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(start: usize, text: &'a str) -> Self {
+        let indentation = text.len() - text.trim_start_matches(&[' ', '\t'][..]).len();
+        Line {
+            start,
+            text,
+            indentation,
+        }
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+
+    fn is_item(&self) -> bool {
+        parse_bullet(self).is_some()
+    }
+}
+
+/// Parses the items starting at `lines[*position]`.
+///
+/// The list ends at a line that isn't indented more than the item the list is nested in (if
+/// any) or at a blank line that isn't followed by another item. Items with a different
+/// indentation than the first item are siblings as long as they are indented more than the
+/// parent item.
+fn parse_list(lines: &[Line<'_>], position: &mut usize, parent: Option<usize>) -> PlainList {
+    let start = lines[*position].start + lines[*position].indentation;
+    let mut end = start;
+    let mut items = Vec::new();
+    while let Some(line) = lines.get(*position) {
+        if !line.is_item() || parent.is_some_and(|parent| line.indentation <= parent) {
+            break;
+        }
+        let (mut item, item_end) = parse_item(lines, position);
+        end = item_end;
+        // a single blank line between two items doesn't end the list
+        if let (Some(blank), Some(next)) = (lines.get(*position), lines.get(*position + 1)) {
+            if blank.is_blank()
+                && next.is_item()
+                && parent.is_none_or(|parent| next.indentation > parent)
+            {
+                item.set_post_blank(Some(1));
+                *position += 1;
+            }
+        }
+        items.push(item);
+    }
+    PlainList::new(Spanned::with_span(items, Span::new(start, end)))
+}
+
+/// Parses the item starting at `lines[*position]` and returns it with the end of its content.
+fn parse_item(lines: &[Line<'_>], position: &mut usize) -> (Item, usize) {
+    let line = lines[*position];
+    let (kind, checkbox, rest) = parse_bullet(&line).expect("the line is an item");
+    let content_start = line.end() - rest.len();
+    let mut end = line.end();
+    *position += 1;
+
+    let mut elements = Vec::new();
+    let mut paragraph = Vec::new();
+    if !rest.is_empty() {
+        paragraph.push((content_start, rest));
+    }
+    while let Some(next) = lines.get(*position) {
+        if next.is_blank() {
+            // a blank line only belongs to the item if the item continues after it
+            match lines.get(*position + 1) {
+                Some(after) if !after.is_blank() && after.indentation > line.indentation => {
+                    push_paragraph(&mut elements, &mut paragraph);
+                    *position += 1;
+                    continue;
+                }
+                _ => break,
+            }
+        }
+        if next.indentation <= line.indentation {
+            break;
+        }
+        if next.is_item() {
+            push_paragraph(&mut elements, &mut paragraph);
+            let list = parse_list(lines, position, Some(line.indentation));
+            end = list
+                .content()
+                .and_then(|c| c.span().as_ref())
+                .map_or(end, Span::end);
+            elements.push(list.into());
+        } else if let Some((drawer, drawer_end)) = parse_item_drawer(lines, position, &line) {
+            push_paragraph(&mut elements, &mut paragraph);
+            end = drawer_end;
+            elements.push(drawer);
+        } else {
+            let text = next.text.trim_start();
+            paragraph.push((next.end() - text.len(), text.trim_end()));
+            end = next.end();
+            *position += 1;
+        }
+    }
+    push_paragraph(&mut elements, &mut paragraph);
+
+    let mut item = Item::new(
+        kind,
+        Spanned::with_span(elements, Span::new(content_start, end.max(content_start))),
+    );
+    item.checkbox = checkbox;
+    (item, end)
+}
+
+/// Parses the drawer starting at `lines[*position]` in the item starting at `item` and returns
+/// it with its end.
+///
+/// Returns `None` if the line doesn't start a drawer that ends in the item.
+fn parse_item_drawer(
+    lines: &[Line<'_>],
+    position: &mut usize,
+    item: &Line<'_>,
+) -> Option<(ElementSet, usize)> {
+    let first = lines[*position];
+    let name = first.text.trim();
+    if name.len() < 3 || !name.starts_with(':') || !name.ends_with(':') {
+        return None;
+    }
+    let last = *position
+        + lines[*position..]
+            .iter()
+            .take_while(|line| line.is_blank() || line.indentation > item.indentation)
+            .position(|line| line.text.trim().eq_ignore_ascii_case(":END:"))?;
+    let text = lines[*position..=last]
+        .iter()
+        .map(|line| line.text)
+        .join("\n");
+    let pair = OrgModeParser::parse(Rule::drawer, &text).ok()?.next()?;
+    if pair.as_str().len() != text.len() {
+        return None;
+    }
+    let mut drawer = parse_drawer(pair).ok()?;
+    shift_spans(&mut drawer, first.start as isize);
+    *position = last + 1;
+    Some((drawer, lines[last].end()))
+}
+
+/// Adds the collected lines as a paragraph to the elements.
+/// Adds a paragraph of the lines (with their offsets) to the elements.
+///
+/// The lines are joined without their indentation, so the spans of the objects are moved back
+/// to the lines they are on.
+fn push_paragraph(elements: &mut Vec<ElementSet>, lines: &mut Vec<(usize, &str)>) {
+    if lines.is_empty() {
+        return;
+    }
+    let text = lines.iter().map(|(_, line)| line).join("\n");
+    // the start of every line in the joined text and in the parsed string
+    let mut starts = Vec::new();
+    let mut start = 0;
+    for (offset, line) in lines.drain(..) {
+        starts.push((start, offset));
+        start += line.len() + 1;
+    }
+    let mut content = parse_objects_at(&text, 0);
+    unescape_paragraph(&mut content);
+    let mut paragraph = Paragraph::new(content).into();
+    map_spans(&mut paragraph, &|position| {
+        let (start, offset) = starts
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= position)
+            .expect("the first line starts at 0");
+        offset + position - start
+    });
+    elements.push(paragraph);
+}
+
+/// Parses the bullet, counter, checkbox and tag of an item.
+///
+/// Returns `None` if the line is not an item. Otherwise returns the rest of the line (the start
+/// of the content of the item).
+fn parse_bullet<'a>(line: &Line<'a>) -> Option<(ItemKind, Option<Checkbox>, &'a str)> {
+    let text = &line.text[line.indentation..];
+    let bullet_end = text.find(&[' ', '\t'][..]).unwrap_or(text.len());
+    let (bullet, mut rest) = (&text[..bullet_end], text[bullet_end..].trim_start());
+
+    let unordered = match bullet {
+        "-" => Some(UnorderedBullet::Minus),
+        "+" => Some(UnorderedBullet::Plus),
+        // a star at the start of a line is a headline
+        "*" if line.indentation > 0 => Some(UnorderedBullet::Star),
+        _ => None,
+    };
+    let mut kind = match unordered {
+        Some(bullet) => ItemKind::Unordered { bullet },
+        None => {
+            let delimiter = match bullet.chars().last() {
+                Some('.') => CounterDelimiter::Period,
+                Some(')') => CounterDelimiter::Parenthesis,
+                _ => return None,
+            };
+            ItemKind::Ordered {
+                bullet: OrderedBullet {
+                    counter: parse_counter(&bullet[..bullet.len() - 1])?,
+                    delimiter,
+                },
+                counter: None,
+            }
+        }
+    };
+
+    if let ItemKind::Ordered { counter, .. } = &mut kind {
+        if let Some((value, after)) = take_word(rest, "[@", "]") {
+            if let Some(value) = parse_counter(value) {
+                *counter = Some(value);
+                rest = after;
+            }
+        }
+    }
+
+    let checkbox = match take_word(rest, "[", "]") {
+        Some((" ", after)) => Some((Checkbox::Unchecked, after)),
+        Some(("X", after)) | Some(("x", after)) => Some((Checkbox::Checked, after)),
+        Some(("-", after)) => Some((Checkbox::Partial, after)),
+        _ => None,
+    };
+    let checkbox = checkbox.map(|(checkbox, after)| {
+        rest = after;
+        checkbox
+    });
+
+    if let ItemKind::Unordered { bullet } = &kind {
+        let tag = rest.find(" :: ").map(|end| (end, end + 4)).or_else(|| {
+            if rest.ends_with(" ::") {
+                Some((rest.len() - 3, rest.len()))
+            } else {
+                None
+            }
+        });
+        if let Some((tag_end, content_start)) = tag {
+            kind = ItemKind::Description {
+                bullet: bullet.clone(),
+                tag: rest[..tag_end].trim().to_string(),
+            };
+            rest = rest[content_start..].trim_start();
+        }
+    }
+
+    Some((kind, checkbox, rest))
+}
+
+/// Parses a counter (a number or a single letter).
+fn parse_counter(s: &str) -> Option<Counter> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => Some(Counter::Letter(letter)),
+        _ => s.parse().ok().map(Counter::Number),
+    }
+}
+
+/// Takes the value between `open` and `close` from the start of `s` if the closing delimiter is
+/// followed by whitespace or the end of the string.
+///
+/// Returns the value and the rest of `s` (without leading whitespace).
+fn take_word<'a>(s: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    if !s.starts_with(open) {
+        return None;
+    }
+    let end = s[open.len()..].find(close)? + open.len();
+    let rest = &s[end + close.len()..];
+    if rest.is_empty() || rest.starts_with(&[' ', '\t'][..]) {
+        Some((&s[open.len()..end], rest.trim_start()))
+    } else {
+        None
+    }
+}
+
+pub(crate) fn parse_table<'i>(pair: Pair<'i, Rule>) -> Result<Table, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::table);
+    let pair = pair.into_inner().next().unwrap();
+
+    if pair.as_rule() == Rule::table_el_table {
+        let span = pair.as_span().into();
+        let lines = pair
+            .into_inner()
+            .map(|line| line.as_str().trim_start().to_string())
+            .collect();
+        return Ok(Table::table_el(Spanned::with_span(lines, span)));
+    }
+
+    assert_eq!(pair.as_rule(), Rule::org_table);
+    let mut rows = Vec::new();
+    let mut formulas = Vec::new();
+    let (mut start, mut end) = (None, 0);
+    for pair in pair.into_inner() {
+        let line = pair.as_str();
+        match pair.as_rule() {
+            Rule::org_table_row => {
+                let span: Span = pair.as_span().into();
+                start = start.or(Some(span.start()));
+                end = span.end();
+                let mut row = parse_table_row(line).expect("the line is a table row");
+                if let TableRowKind::Normal(cells) = row.kind {
+                    row.kind = TableRowKind::Normal(Spanned::with_span(cells.to_value(), span));
+                }
+                rows.push(row);
+            }
+            Rule::table_formulas => {
+                let formula = line.trim_start()["#+TBLFM:".len()..].trim();
+                formulas.push(formula.to_string());
+            }
+            _ => unreachable!("org tables only contain rows and formulas"),
+        }
+    }
+
+    let span = Span::new(start.unwrap_or(end), end);
+    let mut table = Table::new(Spanned::with_span(rows, span));
+    table.kind = TableKind::Org { formulas };
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_document;
+    use crate::types::document::Document;
+    use crate::types::elements::{ClockStatus, OrgDuration, Tangle};
+    use crate::types::greater_elements::{HeadlineContentSet, TableContent, TodoKeyword};
+    use crate::types::serialize::SerializeConfig;
+    use crate::types::{Element, ElementKind, ElementSet, SecondaryString, StandardSet};
+
+    #[test]
+    fn src_block() {
+        let s = "#+begin_src rust -n 10 -r -l \"(ref:%s)\" :tangle yes\n\
+                 ,* not a headline\n\
+                 ,,#+not a keyword\n\
+                 fn main() {}\n\
+                 #+end_src\n";
+        let block: SrcBlock = s.parse().unwrap();
+        assert_eq!(block.language, "rust");
+        assert_eq!(block.arguments.tangle(), Tangle::Yes);
+        assert_eq!(block.arguments.to_string(), ":tangle yes");
+        assert_eq!(
+            block.value,
+            "* not a headline\n,#+not a keyword\nfn main() {}\n"
+        );
+        assert_eq!(block.keyword_case, KeywordCase::Lower);
+        assert_eq!(block.to_string(), s.trim_end());
+    }
+
+    #[test]
+    fn src_block_flags() {
+        let block: SrcBlock = "#+BEGIN_SRC rust -n 10 -r -l \"(ref:%s)\"\n#+END_SRC"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            block.flags.number_lines,
+            Some(NumberLinesFlag::New(Some(10)))
+        );
+        assert!(!block.flags.retain_labels);
+        assert_eq!(block.flags.label_fmt, Some("(ref:%s)".to_string()));
+    }
+
+    #[test]
+    fn empty_src_block() {
+        let empty: SrcBlock = "#+BEGIN_SRC\n#+END_SRC".parse().unwrap();
+        assert_eq!(empty, SrcBlock::new(String::new(), String::new()));
+    }
+
+    #[test]
+    fn unclosed_src_block() {
+        assert!("#+BEGIN_SRC\n".parse::<SrcBlock>().is_err());
+    }
+
+    #[test]
+    fn indented_src_block() {
+        let document = parse_document("Text\n  #+BEGIN_SRC sh\n  ls\n  #+END_SRC\n").unwrap();
+        let preface = document.preface.unwrap();
+        let elements = preface.content().unwrap().value();
+        match &elements[..] {
+            [ElementSet::Paragraph(_), ElementSet::SrcBlock(block)] => {
+                assert_eq!(block.value, "  ls\n")
+            }
+            elements => panic!("expected a paragraph and a src block: {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn affiliated_keyword_objects() {
+        let keyword = |key: &str, value: &str| {
+            affiliated_keyword(&Keyword::new(
+                key,
+                vec![KeywordValueSetOfObjects::RawString(value.to_string())],
+            ))
+            .unwrap()
+        };
+        let results = match keyword("RESULTS[*hash*]", "[[file:plot.png]]") {
+            AffiliatedKeyword::Results(results) => results.value().clone(),
+            keyword => panic!("expected results: {:?}", keyword),
+        };
+        assert_eq!(
+            *results.value(),
+            SecondaryString::from(parse_objects("[[file:plot.png]]"))
+        );
+        assert!(matches!(
+            results.value().iter().next(),
+            Some(StandardSet::Link(_))
+        ));
+        assert_eq!(
+            *results.optional(),
+            Some(SecondaryString::from(parse_objects("*hash*")))
+        );
+        assert_eq!(results.to_string(), "#+RESULTS[*hash*]: [[file:plot.png]]");
+
+        match keyword("CAPTION", "A [[#intro][link]] and /markup/") {
+            AffiliatedKeyword::Caption(caption) => {
+                assert_eq!(caption.value().value().iter().count(), 4)
+            }
+            keyword => panic!("expected a caption: {:?}", keyword),
+        }
+    }
+
+    const MIXED_CASE: &str = "#+title: Notes\n\
+                              #+todo: NEXT | DONE\n\
+                              * NEXT Task\n\
+                              :properties:\n\
+                              :Effort: 1:00\n\
+                              :end:\n\
+                              #+name: code\n\
+                              #+begin_src rust\n\
+                              fn main() {}\n\
+                              #+end_src\n\
+                              #+BEGIN_QUOTE\n\
+                              quote\n\
+                              #+END_QUOTE\n\
+                              #+Begin_Center\n\
+                              center\n\
+                              #+End_Center\n\
+                              | a |\n\
+                              #+TbLfM: $1=1\n";
+
+    /// [`MIXED_CASE`] as it is written back (block keywords keep lower and upper case).
+    fn normalized_case() -> String {
+        MIXED_CASE
+            .replace(":properties:", ":PROPERTIES:")
+            .replace(":end:", ":END:")
+            .replace("#+name:", "#+NAME:")
+            .replace("Begin_Center", "BEGIN_CENTER")
+            .replace("End_Center", "END_CENTER")
+            .replace("TbLfM", "TBLFM")
+    }
+
+    #[test]
+    fn mixed_case_keywords() {
+        let document = parse_document(MIXED_CASE).unwrap();
+        let headline = &document.headlines[0];
+        assert_eq!(
+            headline.todo_keyword,
+            Some(TodoKeyword::Todo("NEXT".to_string()))
+        );
+        assert_eq!(headline.property("EFFORT").as_deref(), Some("1:00"));
+        let elements = match &headline.content().unwrap().value()[0] {
+            HeadlineContentSet::Section(section) => section.content().unwrap().value(),
+            content => panic!("expected a section: {:?}", content),
+        };
+        match &elements[..] {
+            [src, ElementSet::QuoteBlock(_), ElementSet::CenterBlock(_), ElementSet::Table(table)] =>
+            {
+                assert_eq!(src.name(), Some("code"));
+                assert_eq!(
+                    table.kind,
+                    TableKind::Org {
+                        formulas: vec!["$1=1".to_string()]
+                    }
+                );
+            }
+            elements => panic!("expected blocks and a table: {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn preserve_block_keyword_case() {
+        let document = parse_document(MIXED_CASE).unwrap();
+        assert_eq!(document.to_string(), normalized_case());
+    }
+
+    #[test]
+    fn configured_keyword_case() {
+        let document = parse_document(MIXED_CASE).unwrap();
+        let config = SerializeConfig {
+            keyword_case: Some(KeywordCase::Lower),
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&document),
+            normalized_case()
+                .replace("_QUOTE", "_quote")
+                .replace("_CENTER", "_center")
+                .replace("#+BEGIN", "#+begin")
+                .replace("#+END", "#+end")
+        );
+    }
+
+    /// Returns an org table and a table.el table.
+    fn tables() -> (Table, Table) {
+        let s = "| a | *b* |\n\
+                 |---+-----|\n\
+                 | 1 | 2\n\
+                 #+TBLFM: $2=$1*2\n\
+                 #+tblfm: @1$1=x\n\
+                 \n\
+                 \x20 +---+\n\
+                 \x20 | a |\n\
+                 \x20 +---+\n";
+        let document = parse_document(s).unwrap();
+        let preface = document.preface.unwrap();
+        match &preface.content().unwrap().value()[..] {
+            [ElementSet::Table(org), ElementSet::Table(table_el)] => {
+                ((**org).clone(), (**table_el).clone())
+            }
+            elements => panic!("expected two tables: {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn org_table() {
+        let (org, _) = tables();
+        assert_eq!(
+            org.kind,
+            TableKind::Org {
+                formulas: vec!["$2=$1*2".to_string(), "@1$1=x".to_string()]
+            }
+        );
+        assert_eq!(org.content().unwrap().span(), &Some(Span::new(0, 31)));
+        assert_eq!(
+            org.to_string(),
+            "| a | *b* |\n|-\n| 1 | 2 |\n#+TBLFM: $2=$1*2\n#+TBLFM: @1$1=x"
+        );
+    }
+
+    #[test]
+    fn org_table_cells() {
+        let (org, _) = tables();
+        match &org.content().unwrap().value()[0] {
+            TableContent::Org(row) => match &row.kind {
+                TableRowKind::Normal(cells) => {
+                    assert_eq!(cells.span(), &Some(Span::new(0, 11)));
+                    assert_eq!(cells.value()[1].to_string(), "*b*");
+                }
+                kind => panic!("expected a normal row: {:?}", kind),
+            },
+            row => panic!("expected an org row: {:?}", row),
+        }
+    }
+
+    #[test]
+    fn table_el_table() {
+        let (_, table_el) = tables();
+        assert_eq!(
+            table_el.kind,
+            TableKind::TableEl {
+                value: Some("+---+\n| a |\n+---+".to_string())
+            }
+        );
+        assert_eq!(table_el.to_string(), "+---+\n| a |\n+---+");
+    }
+
+    /// Returns the elements of a paragraph and two lists.
+    fn list_elements() -> Vec<ElementSet> {
+        let s = "Text\n\
+                 - [X] done\n\
+                 - tag :: description\n  continued\n\
+                 \x20 1. [@3] nested\n\
+                 \x20 b) [ ] letter\n\
+                 \n\
+                 \x20    more\n\
+                 \x20 * star\n\
+                 + last\n\
+                 \n\
+                 \n\
+                 - new list\n";
+        let document = parse_document(s).unwrap();
+        document.preface.unwrap().content().unwrap().value().clone()
+    }
+
+    #[test]
+    fn plain_list() {
+        let elements = list_elements();
+        let list = match &elements[1] {
+            ElementSet::PlainList(list) => list,
+            element => panic!("expected a plain list: {:?}", element),
+        };
+        assert_eq!(
+            list.to_string(),
+            "- [X] done\n\
+             - tag :: description\n  continued\n\
+             \x20 1. [@3] nested\n\
+             \x20 b) [ ] letter\n\
+             \n\
+             \x20    more\n\
+             \x20 * star\n\
+             + last"
+        );
+        assert_eq!(list.content().unwrap().span(), &Some(Span::new(5, 108)));
+    }
+
+    #[test]
+    fn item_checkbox() {
+        let elements = list_elements();
+        assert_eq!(
+            list_items(&elements[1])[0].checkbox,
+            Some(Checkbox::Checked)
+        );
+    }
+
+    #[test]
+    fn nested_list() {
+        let elements = list_elements();
+        let nested = list_items(&list_items(&elements[1])[1].content().unwrap().value()[1]);
+        assert_eq!(nested.len(), 3);
+        match &nested[0].kind {
+            ItemKind::Ordered { bullet, counter } => {
+                assert_eq!(bullet.counter, Counter::Number(1));
+                assert_eq!(counter, &Some(Counter::Number(3)));
+            }
+            kind => panic!("expected an ordered item: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn two_blank_lines_end_list() {
+        assert_eq!(
+            kinds(&list_elements()),
+            [
+                ElementKind::Paragraph,
+                ElementKind::PlainList,
+                ElementKind::PlainList
+            ]
+        );
+    }
+
+    const BLOCKS_AND_DRAWERS: &str = "* Headline\n\
+                                      :PROPERTIES:\n\
+                                      :ID: 1\n\
+                                      :TAGS+: a\n\
+                                      :END:\n\
+                                      :LOGBOOK:\n\
+                                      - Note\n\
+                                      :END:\n\
+                                      #+BEGIN_QUOTE\n\
+                                      Quoted *text*\n\
+                                      #+BEGIN_EXAMPLE -n\n\
+                                      ,* example\n\
+                                      #+END_EXAMPLE\n\
+                                      #+END_QUOTE\n\
+                                      #+begin_verse\n\
+                                      \x20 Verse\n\
+                                      #+end_verse\n\
+                                      #+BEGIN_aside\n\
+                                      Aside\n\
+                                      #+END_aside\n\
+                                      #+BEGIN_EXPORT html\n\
+                                      <hr>\n\
+                                      #+END_EXPORT\n\
+                                      :unclosed:\n";
+
+    /// Returns the elements of the section of the first headline.
+    fn section_elements(document: &Document) -> &[ElementSet] {
+        match &document.headlines[0].content().unwrap().value()[0] {
+            HeadlineContentSet::Section(section) => section.content().unwrap().value(),
+            content => panic!("expected a section: {:?}", content),
+        }
+    }
+
+    #[test]
+    fn blocks_and_drawers() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        assert_eq!(
+            kinds(section_elements(&document)),
+            [
+                ElementKind::Drawer,
+                ElementKind::QuoteBlock,
+                ElementKind::VerseBlock,
+                ElementKind::SpecialBlock,
+                ElementKind::ExportBlock,
+                ElementKind::Paragraph,
+            ]
+        );
+    }
+
+    #[test]
+    fn property_drawer() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        let headline = &document.headlines[0];
+        assert_eq!(headline.property("ID").as_deref(), Some("1"));
+        assert_eq!(headline.property("TAGS").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn drawer() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        match &section_elements(&document)[0] {
+            ElementSet::Drawer(drawer) => {
+                assert_eq!(drawer.name, "LOGBOOK");
+                assert_eq!(
+                    kinds(drawer.content().unwrap().value()),
+                    [ElementKind::PlainList]
+                );
+            }
+            element => panic!("expected a drawer: {:?}", element),
+        }
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        let quote = match &section_elements(&document)[1] {
+            ElementSet::QuoteBlock(quote) => quote,
+            element => panic!("expected a quote block: {:?}", element),
+        };
+        match &quote.content().unwrap().value()[..] {
+            [ElementSet::Paragraph(_), ElementSet::ExampleBlock(example)] => {
+                assert_eq!(example.value, "* example\n");
+                assert_eq!(example.flags.number_lines, Some(NumberLinesFlag::New(None)));
+            }
+            elements => panic!("expected a paragraph and an example: {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn verse_block() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        assert_eq!(
+            section_elements(&document)[2].to_string(),
+            "#+begin_verse\n  Verse\n#+end_verse"
+        );
+    }
+
+    #[test]
+    fn special_and_export_blocks() {
+        let document = parse_document(BLOCKS_AND_DRAWERS).unwrap();
+        match &section_elements(&document)[3..5] {
+            [ElementSet::SpecialBlock(special), ElementSet::ExportBlock(export)] => {
+                assert_eq!(special.name, "aside");
+                assert_eq!(export.backend, "html");
+                assert_eq!(export.value, "<hr>\n");
+            }
+            elements => panic!("expected a special and an export block: {:?}", elements),
+        }
+    }
+
+    fn indented_drawers() -> Document {
+        parse_document(
+            "* DONE Task\n\
+             \x20 :LOGBOOK:\n\
+             \x20 - State \"DONE\"       from \"TODO\"       [2018-10-12 Fri 10:00]\n\
+             \x20 :END:\n\
+             - Item\n\
+             \x20 :NOTES:\n\
+             \x20 - *Nested* note\n\
+             \x20 :END:\n\
+             \x20 More text\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn indented_logbook() {
+        let document = indented_drawers();
+        let headline = &document.headlines[0];
+        let logbook = headline.logbook().expect("a logbook");
+        assert_eq!(logbook.content().unwrap().span(), &Some(Span::new(24, 88)));
+        assert_eq!(crate::logbook::state_changes(headline).len(), 1);
+    }
+
+    #[test]
+    fn drawer_in_item() {
+        let document = indented_drawers();
+        let list = match section_elements(&document) {
+            [ElementSet::Drawer(_), ElementSet::PlainList(list)] => list,
+            elements => panic!("unexpected elements: {:?}", elements),
+        };
+        let item = &list.content().unwrap().value()[0];
+        match &item.content().unwrap().value()[..] {
+            [ElementSet::Paragraph(_), ElementSet::Drawer(drawer), ElementSet::Paragraph(text)] => {
+                assert_eq!(drawer.name, "NOTES");
+                assert_eq!(drawer.content().unwrap().span(), &Some(Span::new(113, 131)));
+                assert_eq!(text.content.to_string(), "More text");
+            }
+            elements => panic!("unexpected elements: {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn write_indented_drawers() {
+        let document = indented_drawers();
+        assert_eq!(
+            document.to_string(),
+            "* DONE Task\n\
+             :LOGBOOK:\n\
+             - State \"DONE\"       from \"TODO\"       [2018-10-12 Fri 10:00]\n\
+             :END:\n\
+             - Item\n\
+             \x20 :NOTES:\n\
+             \x20 - *Nested* note\n\
+             \x20 :END:\n\
+             \x20 More text\n"
+        );
+    }
+
+    /// Parses the document, checks that it is written back unchanged and returns the elements
+    /// before the first headline.
+    fn preface(s: &str) -> Vec<ElementSet> {
+        let document = parse_document(s).unwrap();
+        assert_eq!(document.to_string(), s);
+        document.preface.map_or_else(Vec::new, |preface| {
+            preface.content().unwrap().value().clone()
+        })
+    }
+
+    fn kinds(elements: &[ElementSet]) -> Vec<ElementKind> {
+        elements.iter().map(Element::kind).collect()
+    }
+
+    fn footnote_definition(element: &ElementSet) -> &FootnoteDefinition {
+        match element {
+            ElementSet::FootnoteDefinition(definition) => definition,
+            element => panic!("expected a footnote definition, got {:?}", element),
+        }
+    }
+
+    fn list_items(element: &ElementSet) -> &[Item] {
+        match element {
+            ElementSet::PlainList(list) => list.content().unwrap().value(),
+            element => panic!("expected a list, got {:?}", element),
+        }
+    }
+
+    #[test]
+    fn footnote_definition_ends_at_two_blank_lines() {
+        let elements = preface("[fn:1] First\n\nContinued\n\n\nAfter\n");
+        assert_eq!(
+            kinds(&elements),
+            [ElementKind::FootnoteDefinition, ElementKind::Paragraph]
+        );
+        let definition = footnote_definition(&elements[0]);
+        assert_eq!(definition.content().unwrap().value().len(), 2);
+    }
+
+    #[test]
+    fn footnote_definition_ends_at_next_definition() {
+        let elements = preface("[fn:1] First\n- item\n[fn:two] Second\n");
+        assert_eq!(
+            kinds(&elements),
+            [
+                ElementKind::FootnoteDefinition,
+                ElementKind::FootnoteDefinition
+            ]
+        );
+        assert_eq!(footnote_definition(&elements[0]).label, "1");
+        assert_eq!(footnote_definition(&elements[1]).label, "two");
+    }
+
+    #[test]
+    fn footnote_definition_ends_at_headline() {
+        let s = "[fn:1] First\n* Headline\n[fn:2] Second\n* Next\n";
+        assert_eq!(kinds(&preface(s)), [ElementKind::FootnoteDefinition]);
+        let document = parse_document(s).unwrap();
+        assert_eq!(document.headlines.len(), 2);
+        match &document.headlines[0].content().unwrap().value()[..] {
+            [HeadlineContentSet::Section(section)] => assert_eq!(
+                kinds(section.content().unwrap().value()),
+                [ElementKind::FootnoteDefinition]
+            ),
+            content => panic!("expected a section: {:?}", content),
+        }
+    }
+
+    #[test]
+    fn item_ends_at_lesser_indentation() {
+        let elements = preface("- a\n  - nested\n- b\nnot in the list\n");
+        assert_eq!(
+            kinds(&elements),
+            [ElementKind::PlainList, ElementKind::Paragraph]
+        );
+        let items = list_items(&elements[0]);
+        assert_eq!(items.len(), 2);
+        let content = items[0].content().unwrap().value();
+        assert_eq!(
+            kinds(content),
+            [ElementKind::Paragraph, ElementKind::PlainList]
+        );
+        assert_eq!(list_items(&content[1]).len(), 1);
+    }
+
+    #[test]
+    fn item_continues_after_one_blank_line() {
+        let elements = preface("- a\n\n  continued\n\n- b\n");
+        assert_eq!(kinds(&elements), [ElementKind::PlainList]);
+        let items = list_items(&elements[0]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].content().unwrap().value().len(), 2);
+    }
+
+    #[test]
+    fn item_ends_at_two_blank_lines() {
+        let elements = preface("- a\n\n\n  not in the item\n- b\n");
+        assert_eq!(
+            kinds(&elements),
+            [
+                ElementKind::PlainList,
+                ElementKind::Paragraph,
+                ElementKind::PlainList
+            ]
+        );
+        assert_eq!(list_items(&elements[0]).len(), 1);
+    }
+
+    #[test]
+    fn unclosed_drawer_is_paragraph() {
+        let elements = preface(":LOGBOOK:\n- note\n");
+        assert_eq!(
+            kinds(&elements),
+            [ElementKind::Paragraph, ElementKind::PlainList]
+        );
+        assert_eq!(elements[0].to_string(), ":LOGBOOK:");
+    }
+
+    #[test]
+    fn clocks() {
+        let s = "CLOCK: [2018-10-12 Fri 22:00:30]--[2018-10-14 Sun 00:10:30] => 26:10";
+        let clock: Clock = s.parse().unwrap();
+        assert_eq!(
+            clock.duration,
+            Some(OrgDuration::from_hours_minutes(26, 10))
+        );
+        assert_eq!(clock.to_string(), s);
+        let running: Clock = "CLOCK: [2018-10-12 Fri 09:05]\n".parse().unwrap();
+        assert_eq!(running.status(), ClockStatus::Running);
+        assert!("CLOCK: [2018-10-12 Fri 09:05] text"
+            .parse::<Clock>()
+            .is_err());
+
+        let s = ":LOGBOOK:\n\
+                 CLOCK: [2018-10-12 Fri 10:00]--[2018-10-12 Fri 11:30] =>  1:30\n\
+                 CLOCK: [yesterday]\n\
+                 :END:\n";
+        let document = parse_document(s).unwrap();
+        let elements = document
+            .preface
+            .as_ref()
+            .unwrap()
+            .content()
+            .unwrap()
+            .value();
+        match &elements[..] {
+            [ElementSet::Drawer(drawer)] => assert!(matches!(
+                &drawer.content().unwrap().value()[..],
+                [ElementSet::Clock(_), ElementSet::Paragraph(_)]
+            )),
+            elements => panic!("expected a drawer: {:?}", elements),
+        }
+        assert_eq!(document.to_string(), s);
+    }
+}
@@ -0,0 +1,400 @@
+//! Updating a parsed document after its text was edited (e.g. in an editor).
+//!
+//! Parsing a large file again after every keystroke is too slow. [`reparse_range_with_config`]
+//! only parses the top level headlines touched by the edit again (split like [`HeadlineIter`]
+//! does) and moves the spans of the headlines after the edit. Edits of the preface parse the
+//! whole text again because in-buffer settings (e.g. `#+TODO:`) change how the headlines are
+//! parsed. So do edits of documents with radio targets because they link text in all headlines
+//! and of documents with an inline task in the preface.
+
+use super::{parse_document_with_config, with_in_buffer_settings, HeadlineIter, RawHeadline};
+use super::{ParseConfig, ParseError};
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, Item, Section, TableContent, TableRowKind};
+use crate::types::objects::{FootnoteReference, FootnoteReferenceKind, Link, LinkFormat};
+use crate::types::objects::{TextMarkup, TextMarkupKind};
+use crate::types::visit::{self, ObjectRefMut, VisitorMut};
+use crate::types::{AsRawString, ElementSet, SecondaryString, Span, Spanned};
+
+/// An edit of the text a document was parsed from.
+///
+/// The bytes `start..old_end` of the old text were replaced by the bytes `start..new_end` of the
+/// new `text` (like the edits most editors report).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextEdit<'a> {
+    /// The whole text after the edit.
+    pub text: &'a str,
+    pub start: usize,
+    /// The end of the replaced bytes in the old text.
+    pub old_end: usize,
+    /// The end of the inserted bytes in the new text.
+    pub new_end: usize,
+}
+
+/// Updates a document parsed with the configuration after its text was edited.
+///
+/// The result is the same as parsing the new text with [`parse_document_with_config`]. If the
+/// edited text can't be parsed the error is returned and the document is not changed.
+pub fn reparse_range_with_config(
+    document: &mut Document,
+    edit: TextEdit<'_>,
+    config: &ParseConfig,
+) -> Result<(), ParseError> {
+    let TextEdit {
+        text,
+        start,
+        old_end,
+        new_end,
+    } = edit;
+    if start > old_end || text.get(start..new_end).is_none() {
+        return Err(ParseError::StructuralError(
+            "the edit is not inside the text",
+        ));
+    }
+    let headlines = HeadlineIter::new(text);
+    let old_preface_end = match &document.preface {
+        Some(preface) => section_end(preface).unwrap_or(usize::MAX),
+        None => 0,
+    };
+    let new_preface_end = headlines.preface().map_or(0, str::len);
+    let radio_targets = text.contains("<<<") || !document.index().radio_targets.is_empty();
+    // inline tasks are only split off like headlines in the preface
+    let inlinetasks = config
+        .inlinetask_min_level
+        .is_some_and(|min_level| headlines.clone().any(|unit| unit.level >= min_level));
+    if radio_targets || inlinetasks || start < old_preface_end.max(new_preface_end) {
+        *document = parse_document_with_config(text, config)?;
+        return Ok(());
+    }
+
+    let config = with_in_buffer_settings(config, document.preface.as_ref());
+    let delta = new_end as isize - old_end as isize;
+    let units: Vec<_> = headlines.collect();
+    let old_len = document.headlines.len();
+    // the text before the edit is the same, so are the headlines ending before it in the old
+    // and the new text (the old parent of a promoted headline ends after the edit)
+    let before = units
+        .iter()
+        .zip(&document.headlines)
+        .take_while(|(unit, headline)| {
+            unit.offset + unit.text.len() <= start
+                && headline_end(headline).is_none_or(|end| end <= start)
+        })
+        .count();
+    // the text after the edit is the same but a headline can become the child of an edited one
+    let mut after = units[before..]
+        .iter()
+        .rev()
+        .take_while(|unit| unit.offset >= new_end)
+        .count();
+    while after > 0
+        && (old_len < before + after
+            || !is_same_headline(
+                &document.headlines[old_len - after],
+                units[units.len() - after],
+                delta,
+            ))
+    {
+        after -= 1;
+    }
+
+    let edited = &units[before..units.len() - after];
+    let parsed = match (edited.first(), edited.last()) {
+        (Some(first), Some(last)) => {
+            let end = last.offset + last.text.len();
+            let mut headlines =
+                parse_document_with_config(&text[first.offset..end], &config)?.headlines;
+            let mut shift = ChangeSpans::Shift(first.offset as isize);
+            for headline in &mut headlines {
+                shift.visit_headline(headline);
+            }
+            headlines
+        }
+        _ => Vec::new(),
+    };
+    let mut shift = ChangeSpans::Shift(delta);
+    for headline in &mut document.headlines[old_len - after..] {
+        shift.visit_headline(headline);
+    }
+    document.headlines.splice(before..old_len - after, parsed);
+    Ok(())
+}
+
+fn section_end(section: &Section) -> Option<usize> {
+    section.content.span().as_ref().map(Span::end)
+}
+
+/// Returns the end of the old headline including its children (if the span is known).
+fn headline_end(headline: &Headline) -> Option<usize> {
+    let content = headline.content.as_ref()?;
+    content.span().as_ref().map(Span::end)
+}
+
+/// Returns `true` if the old headline (before the edit) is the headline at the start of the
+/// unchanged text of `unit`.
+///
+/// At least one span of the old headline has to be known. All known spans have to be in `unit`
+/// after moving them by `delta`.
+fn is_same_headline(headline: &Headline, unit: RawHeadline<'_>, delta: isize) -> bool {
+    let moved = |offset: usize| offset as isize + delta;
+    let line_end = unit.offset + unit.headline_line().len();
+    let title = headline
+        .title
+        .as_ref()
+        .and_then(|title| title.iter_spanned().next())
+        .and_then(|(_, span)| span)
+        .map(|span| {
+            let start = moved(span.start());
+            unit.offset as isize <= start && start <= line_end as isize
+        });
+    let content = headline
+        .content
+        .as_ref()
+        .and_then(|content| content.span().as_ref())
+        .map(|span| moved(span.end()) == (unit.offset + unit.text.len()) as isize);
+    headline.level == unit.level
+        && (title.is_some() || content.is_some())
+        && title != Some(false)
+        && content != Some(false)
+}
+
+/// Removes all spans of the document (see [`Document::clear_spans`]).
+pub(crate) fn clear_spans(document: &mut Document) {
+    visit::walk_mut(document, &mut ChangeSpans::Clear);
+}
+
+/// Moves all spans of the element by a number of bytes.
+pub(crate) fn shift_spans(element: &mut ElementSet, delta: isize) {
+    ChangeSpans::Shift(delta).visit_element(element);
+}
+
+/// Moves the start and end of all spans of the element to the offsets returned by `f`.
+pub(crate) fn map_spans(element: &mut ElementSet, f: &dyn Fn(usize) -> usize) {
+    ChangeSpans::Map(f).visit_element(element);
+}
+
+/// Changes all spans of the visited nodes.
+#[derive(Clone, Copy)]
+enum ChangeSpans<'a> {
+    /// Moves the spans by a number of bytes.
+    Shift(isize),
+    /// Moves the offsets of the spans with a function.
+    Map(&'a dyn Fn(usize) -> usize),
+    Clear,
+}
+
+impl ChangeSpans<'_> {
+    fn spanned<T>(self, spanned: &mut Spanned<T>) {
+        match self {
+            ChangeSpans::Shift(delta) => spanned.shift_span(delta),
+            ChangeSpans::Map(f) => spanned.map_span(f),
+            ChangeSpans::Clear => spanned.set_span(None),
+        }
+    }
+
+    fn objects<T: AsRawString>(self, objects: &mut SecondaryString<T>) {
+        match self {
+            ChangeSpans::Shift(delta) => objects.shift_spans(delta),
+            ChangeSpans::Map(f) => objects.map_spans(f),
+            ChangeSpans::Clear => objects.clear_spans(),
+        }
+    }
+}
+
+impl VisitorMut for ChangeSpans<'_> {
+    fn visit_headline(&mut self, headline: &mut Headline) {
+        if let Some(title) = &mut headline.title {
+            self.objects(title);
+        }
+        if let Some(drawer) = &mut headline.property_drawer {
+            self.spanned(&mut drawer.content);
+        }
+        if let Some(content) = &mut headline.content {
+            self.spanned(content);
+        }
+        visit::walk_headline_mut(self, headline);
+    }
+
+    fn visit_section(&mut self, section: &mut Section) {
+        self.spanned(&mut section.content);
+        visit::walk_section_mut(self, section);
+    }
+
+    fn visit_element(&mut self, element: &mut ElementSet) {
+        match element {
+            ElementSet::CenterBlock(e) => self.spanned(&mut e.content),
+            ElementSet::Drawer(e) => self.spanned(&mut e.content),
+            ElementSet::DynamicBlock(e) => self.spanned(&mut e.content),
+            ElementSet::FootnoteDefinition(e) => self.spanned(&mut e.content),
+            ElementSet::Inlinetask(e) => {
+                if let Some(title) = &mut e.title {
+                    self.objects(title);
+                }
+                self.spanned(&mut e.content)
+            }
+            ElementSet::Keyword(e) => self.spanned(&mut e.value),
+            ElementSet::Paragraph(e) => self.objects(&mut e.content),
+            ElementSet::PlainList(e) => self.spanned(&mut e.content),
+            ElementSet::PropertyDrawer(e) => self.spanned(&mut e.content),
+            ElementSet::QuoteBlock(e) => self.spanned(&mut e.content),
+            ElementSet::SpecialBlock(e) => self.spanned(&mut e.content),
+            ElementSet::Table(e) => {
+                self.spanned(&mut e.content);
+                for row in e.content_mut() {
+                    if let TableContent::Org(row) = row {
+                        if let TableRowKind::Normal(cells) = &mut row.kind {
+                            self.spanned(cells);
+                        }
+                    }
+                }
+            }
+            ElementSet::VerseBlock(e) => self.spanned(&mut e.content),
+            _ => {}
+        }
+        visit::walk_element_mut(self, element);
+    }
+
+    fn visit_item(&mut self, item: &mut Item) {
+        self.spanned(&mut item.content);
+        visit::walk_item_mut(self, item);
+    }
+
+    fn visit_object(&mut self, mut object: ObjectRefMut<'_>) {
+        match &mut object {
+            ObjectRefMut::RadioTarget(target) => self.objects(&mut target.target),
+            ObjectRefMut::Subscript(script) => self.objects(&mut script.content),
+            ObjectRefMut::Superscript(script) => self.objects(&mut script.content),
+            _ => {}
+        }
+        visit::walk_object_mut(self, object);
+    }
+
+    fn visit_link(&mut self, link: &mut Link) {
+        if let LinkFormat::Bracket(_, _, Some(description)) = &mut link.link {
+            self.objects(description);
+        }
+        visit::walk_link_mut(self, link);
+    }
+
+    fn visit_text_markup(&mut self, markup: &mut TextMarkup) {
+        match &mut markup.kind {
+            TextMarkupKind::Bold(content)
+            | TextMarkupKind::Italic(content)
+            | TextMarkupKind::Underline(content)
+            | TextMarkupKind::StrikeThrough(content) => self.objects(content),
+            TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => {}
+        }
+        visit::walk_text_markup_mut(self, markup);
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &mut FootnoteReference) {
+        match &mut reference.kind {
+            FootnoteReferenceKind::Inline { definition, .. }
+            | FootnoteReferenceKind::Anonymous { definition } => self.objects(definition),
+            FootnoteReferenceKind::Normal { .. } => {}
+        }
+        visit::walk_footnote_reference_mut(self, reference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::position::ElementRef;
+
+    /// Replaces the first `old` after `from` and reparses the document.
+    fn edit(document: &mut Document, text: &mut String, from: &str, old: &str, new: &str) {
+        let from = text.find(from).unwrap();
+        let start = from + text[from..].find(old).unwrap();
+        text.replace_range(start..start + old.len(), new);
+        let edit = TextEdit {
+            text: text.as_str(),
+            start,
+            old_end: start + old.len(),
+            new_end: start + new.len(),
+        };
+        reparse_range_with_config(document, edit, &ParseConfig::default()).unwrap();
+        assert_eq!(*document, Document::parse(text).unwrap(), "{}", text);
+    }
+
+    const TEXT: &str = "#+TODO: TODO WAIT | DONE\n\
+                        * WAIT One\n\
+                        Some *bold* text.\n\
+                        ** Child\n\
+                        * Two\n\
+                        :LOGBOOK:\n\
+                        - a\n\
+                        :END:\n\
+                        See [[#link][with /description/]].\n\
+                        | a | b |\n\
+                        * Three\n";
+
+    fn parse(text: &str) -> (Document, String) {
+        (Document::parse(text).unwrap(), text.to_string())
+    }
+
+    #[test]
+    fn move_spans_after_edit() {
+        let (mut document, mut text) = parse(TEXT);
+        edit(&mut document, &mut text, "Some", "bold", "very bold");
+        let title = document.headlines[2].title.as_ref().unwrap();
+        assert_eq!(
+            title.iter_spanned().next().and_then(|(_, span)| span),
+            Some(&Span::new(text.find("Three").unwrap(), text.len() - 1))
+        );
+        let offset = text.find("[[#link").unwrap();
+        let paragraph = match document.element_at(offset) {
+            Some(ElementRef::Element(ElementSet::Paragraph(paragraph))) => paragraph,
+            node => panic!("expected a paragraph, got {:?}", node),
+        };
+        let end = text.find("]].").unwrap() + 2;
+        assert_eq!(
+            paragraph
+                .content
+                .object_at_offset(offset)
+                .map(|(_, span)| span),
+            Some(&Span::new(offset, end))
+        );
+    }
+
+    #[test]
+    fn edit_in_buffer_settings() {
+        let (mut document, mut text) = parse(TEXT);
+        edit(&mut document, &mut text, "#+TODO", "WAIT |", "|");
+        // the settings apply to all headlines
+        assert_eq!(document.headlines[0].todo_keyword, None);
+    }
+
+    #[test]
+    fn add_headline() {
+        let (mut document, mut text) = parse(TEXT);
+        edit(&mut document, &mut text, "** Child", "\n", "\n* New\n");
+        assert_eq!(document.headlines.len(), 4);
+    }
+
+    #[test]
+    fn remove_headline() {
+        let (mut document, mut text) = parse(TEXT);
+        edit(&mut document, &mut text, "* WAIT", "* WAIT", "WAIT");
+        assert_eq!(document.to_string(), text);
+    }
+
+    #[test]
+    fn demote_and_promote_headline() {
+        let (mut document, mut text) = parse(TEXT);
+        edit(&mut document, &mut text, "* Two", "* Two", "** Two");
+        assert_eq!(document.headlines.len(), 2);
+        edit(&mut document, &mut text, "** Two", "** Two", "* Two");
+        assert_eq!(document.headlines.len(), 3);
+    }
+
+    #[test]
+    fn children_of_demoted_headline() {
+        // the children of a demoted headline become top level headlines
+        let (mut document, mut text) = parse("* a\n** b\n** c\n");
+        edit(&mut document, &mut text, "* a", "*", "**");
+        assert_eq!(document.headlines.len(), 3);
+        edit(&mut document, &mut text, "** a", "**", "*");
+        assert_eq!(document.headlines.len(), 1);
+    }
+}
@@ -0,0 +1,240 @@
+//! Turning headlines with many stars into inline tasks.
+//!
+//! The grammar parses every line starting with stars as a headline. Headlines with at least
+//! [`ParseConfig::inlinetask_min_level`] stars are [`Inlinetask`]s. Before the headlines are
+//! nested they are moved into the section before them (the preface or the section of the
+//! previous headline).
+//!
+//! If the next headline is an `END` line the section of the inline task becomes its content and
+//! the section after the `END` line follows the inline task. Otherwise the inline task has no
+//! content and its section follows it.
+//!
+//! [`ParseConfig::inlinetask_min_level`]: `super::ParseConfig::inlinetask_min_level`
+
+use crate::types::greater_elements::{Headline, HeadlineContentSet, Inlinetask, Section};
+use crate::types::{ElementSet, Span, Spanned};
+
+/// Moves the headlines with at least `min_level` stars (and their `END` lines) into the sections
+/// before them.
+///
+/// `headlines` are the not yet nested headlines with the spans of their text in `s`.
+pub(crate) fn extract_inlinetasks(
+    s: &str,
+    preface: &mut Option<Section>,
+    headlines: Vec<(Headline, Span)>,
+    min_level: u32,
+) -> Vec<Headline> {
+    let mut result: Vec<Headline> = Vec::new();
+    let mut headlines = headlines.into_iter().peekable();
+    while let Some((mut headline, span)) = headlines.next() {
+        if headline.level < min_level {
+            result.push(headline);
+            continue;
+        }
+        let (section, blank_lines) = take_section(&mut headline, &span, s);
+        let end = match headlines.peek() {
+            Some((next, _)) if next.level >= min_level && is_end(next) && !is_end(&headline) => {
+                headlines.next()
+            }
+            _ => None,
+        };
+        let mut task = Inlinetask {
+            content: Spanned::new(Vec::new()),
+            level: headline.level,
+            todo_keyword: headline.todo_keyword,
+            priority: headline.priority,
+            title: headline.title,
+            tags: headline.tags,
+            has_end: end.is_some(),
+            post_blank: Some(blank_lines),
+        };
+        let (following, end) = match end {
+            Some((mut end_line, end_span)) => {
+                let (following, blank_lines) = take_section(&mut end_line, &end_span, s);
+                task.post_blank = Some(blank_lines);
+                if let Some(section) = section {
+                    let span = section.content.span().clone();
+                    task.content = Spanned::with_optional_span(
+                        vec![HeadlineContentSet::Section(section)],
+                        span,
+                    );
+                }
+                (following, end_span.end())
+            }
+            None => (section, span.end()),
+        };
+
+        let mut elements = vec![ElementSet::from(task)];
+        elements.extend(
+            following
+                .into_iter()
+                .flat_map(|section| section.content.to_value()),
+        );
+        let section = match result.last_mut() {
+            Some(previous) => previous.section_mut(),
+            None => preface.get_or_insert_with(|| Section::new(Spanned::new(Vec::new()))),
+        };
+        section.content_mut().extend(elements);
+        let start = section
+            .content
+            .span()
+            .as_ref()
+            .map_or(span.start(), Span::start);
+        section.content.set_span(Some(Span::new(start, end)));
+        // the content of a headline without children has the span of its section
+        if let Some(content) = result
+            .last_mut()
+            .and_then(|previous| previous.content.as_mut())
+        {
+            content.set_span(Some(Span::new(start, end)));
+        }
+    }
+    result
+}
+
+/// Returns `true` if the headline is the `END` line of an inline task.
+fn is_end(headline: &Headline) -> bool {
+    headline.todo_keyword.is_none()
+        && headline.priority.is_none()
+        && headline.tags.is_empty()
+        && headline.title.as_ref().is_some_and(|title| title == "END")
+}
+
+/// Takes the section (including the property drawer) of a not yet nested headline whose text is
+/// the `span` of `s`.
+///
+/// Also returns the number of blank lines after the headline line if there is no section.
+fn take_section(headline: &mut Headline, span: &Span, s: &str) -> (Option<Section>, usize) {
+    let mut section = headline.content.take().and_then(|content| {
+        content
+            .to_value()
+            .into_iter()
+            .find_map(|content| match content {
+                HeadlineContentSet::Section(section) => Some(section),
+                HeadlineContentSet::Headline(_) => None,
+            })
+    });
+    let text = &s[span.start()..span.end()];
+    let line_end = text.find('\n').map_or(text.len(), |index| index + 1);
+    if let Some(drawer) = headline.property_drawer.take() {
+        let section = section.get_or_insert_with(|| {
+            let span = Span::new(span.start() + line_end, span.end());
+            Section::new(Spanned::with_span(Vec::new(), span))
+        });
+        section.content_mut().insert(0, ElementSet::from(drawer));
+    }
+    // the section starts directly after the headline line (blank lines are part of it)
+    let blank_lines = match &section {
+        Some(_) => 0,
+        None => text[line_end..].matches('\n').count(),
+    };
+    (section, blank_lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsing::{parse_document, parse_document_with_config, ParseConfig};
+    use crate::types::document::Document;
+    use crate::types::greater_elements::{HeadlineContentSet, Inlinetask, TodoKeyword};
+    use crate::types::{Element, ElementSet, Parent};
+
+    const TEXT: &str = "* Headline\n\
+                        Intro\n\
+                        *** TODO Task :tag:\n\
+                        :PROPERTIES:\n\
+                        :A: b\n\
+                        :END:\n\
+                        Body\n\
+                        *** END\n\
+                        More\n\
+                        **** No end\n\
+                        Last\n\
+                        ** Child\n";
+
+    fn parse(text: &str) -> Document {
+        let config = ParseConfig {
+            inlinetask_min_level: Some(3),
+            ..ParseConfig::default()
+        };
+        parse_document_with_config(text, &config).unwrap()
+    }
+
+    /// Returns the elements of the section of the first headline.
+    fn section_elements(document: &Document) -> &[ElementSet] {
+        match document.headlines[0].content().unwrap().value().as_slice() {
+            [HeadlineContentSet::Section(section), ..] => section.content().unwrap().value(),
+            content => panic!("expected a section, got {:?}", content),
+        }
+    }
+
+    fn inlinetask(element: &ElementSet) -> &Inlinetask {
+        match element {
+            ElementSet::Inlinetask(task) => task,
+            element => panic!("expected an inline task, got {:?}", element),
+        }
+    }
+
+    #[test]
+    fn parse_inlinetasks() {
+        let document = parse(TEXT);
+        assert_eq!(document.to_string(), TEXT);
+        assert_eq!(document.validate(), []);
+        let kinds: Vec<_> = section_elements(&document)
+            .iter()
+            .map(|element| element.kind().name())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "paragraph",
+                "inlinetask",
+                "paragraph",
+                "inlinetask",
+                "paragraph"
+            ]
+        );
+    }
+
+    #[test]
+    fn headline_after_inlinetask() {
+        let document = parse(TEXT);
+        match document.headlines[0].content().unwrap().value().as_slice() {
+            [HeadlineContentSet::Section(_), HeadlineContentSet::Headline(child)] => {
+                assert_eq!(child.level, 2)
+            }
+            content => panic!("expected a section and a child, got {:?}", content),
+        }
+    }
+
+    #[test]
+    fn inlinetask_with_end() {
+        let document = parse(TEXT);
+        let task = inlinetask(&section_elements(&document)[1]);
+        assert_eq!(task.level, 3);
+        assert_eq!(
+            task.todo_keyword,
+            Some(TodoKeyword::Todo("TODO".to_string()))
+        );
+        assert_eq!(task.tags, ["tag"]);
+        assert!(task.has_end());
+        assert_eq!(
+            task.content().unwrap().value()[0].to_string(),
+            ":PROPERTIES:\n:A: b\n:END:\nBody"
+        );
+    }
+
+    #[test]
+    fn inlinetask_without_end() {
+        let document = parse(TEXT);
+        let task = inlinetask(&section_elements(&document)[3]);
+        assert!(!task.has_end());
+        assert!(task.content().unwrap().value().is_empty());
+    }
+
+    #[test]
+    fn default_min_level() {
+        // the default minimum level is 15
+        let document = parse_document("* Headline\n*** Task\n*** END\n").unwrap();
+        assert_eq!(document.headlines[0].content().unwrap().value().len(), 2);
+    }
+}
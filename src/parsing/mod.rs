@@ -0,0 +1,755 @@
+//! Contains the types and traits needed for parsing.
+
+use crate::types::affiliated_keywords::AffiliatedKeyword;
+use crate::types::document::{Document, Keywords};
+use crate::types::elements::{DiarySexp, EncryptedBlock, Paragraph};
+use crate::types::greater_elements::{Headline, HeadlineContentSet, Section, TodoKeyword};
+use crate::types::{unescape_paragraph, ElementSet, Parent, SharedBehavior, Span, Spanned};
+
+use pest::error::{ErrorVariant, InputLocation};
+use pest::iterators::Pair;
+#[allow(unused_imports)]
+use pest::{self, Parser};
+
+use itertools::Itertools;
+
+use std::iter::Peekable;
+
+mod config;
+mod diagnostics;
+mod elements;
+mod incremental;
+mod inlinetask;
+mod objects;
+mod radio;
+mod stream;
+mod syntax;
+
+pub use self::config::{Compat, OrgVersion, ParseConfig, TodoSequence};
+pub use self::diagnostics::{parse_with_diagnostics, ParseDiagnostic};
+pub use self::incremental::{reparse_range_with_config, TextEdit};
+pub use self::objects::{parse_table_cell, parse_table_row};
+pub use self::radio::link_radio_targets;
+pub use self::stream::{HeadlineIter, RawHeadline};
+pub use self::syntax::{
+    syntax_metadata, AffiliatedKeywordInfo, SyntaxMetadata, AFFILIATED_KEYWORDS, BACKEND_ATTRIBUTES,
+    BLOCK_NAMES,
+};
+pub(crate) use self::diagnostics::line_column;
+pub(crate) use self::incremental::clear_spans;
+use self::inlinetask::extract_inlinetasks;
+pub(crate) use self::objects::{parse_objects, parse_objects_at, parse_title};
+
+#[derive(Parser)]
+#[grammar = "orgmode.pest"]
+pub struct OrgModeParser;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The grammar didn't match (boxed because the error is large).
+    LexError(Box<pest::error::Error<Rule>>),
+    StructuralError(&'static str), // TODO define more specifically and maybe rename
+}
+
+impl From<pest::error::Error<Rule>> for ParseError {
+    fn from(error: pest::error::Error<Rule>) -> Self {
+        ParseError::LexError(Box::new(error))
+    }
+}
+
+impl ParseError {
+    /// Returns the span of the error in the parsed string.
+    ///
+    /// Only errors of the grammar ([`ParseError::LexError`]) know their position.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::LexError(error) => Some(match error.location {
+                InputLocation::Pos(pos) => Span::new(pos, pos),
+                InputLocation::Span((start, end)) => Span::new(start, end),
+            }),
+            ParseError::StructuralError(_) => None,
+        }
+    }
+
+    /// Describes what the parser expected (e.g. `headline or paragraph`) or what is wrong.
+    pub fn expected(&self) -> String {
+        match self {
+            ParseError::LexError(error) => match &error.variant {
+                ErrorVariant::ParsingError { positives, .. } if !positives.is_empty() => positives
+                    .iter()
+                    .map(|rule| format!("{:?}", rule))
+                    .join(" or "),
+                ErrorVariant::ParsingError { .. } => "something else".to_string(),
+                ErrorVariant::CustomError { message } => message.clone(),
+            },
+            ParseError::StructuralError(message) => message.to_string(),
+        }
+    }
+}
+
+/// Helper function to create predicates to filter for or skip the specified rule.
+fn is_rule<'i>(rule: Rule) -> impl Fn(&Pair<'i, Rule>) -> bool {
+    move |pair| pair.as_rule() == rule
+}
+
+/// Parses a document with the default [`ParseConfig`].
+pub fn parse_document(s: &str) -> Result<Document, ParseError> {
+    parse_document_with_config(s, &ParseConfig::default())
+}
+
+/// Parses a document with the given configuration.
+pub fn parse_document_with_config(s: &str, config: &ParseConfig) -> Result<Document, ParseError> {
+    if let Some(pair) = OrgModeParser::parse(Rule::document, s)?.next() {
+        assert_eq!(pair.as_rule(), Rule::document);
+
+        let mut rules = pair.into_inner().peekable();
+
+        let preface = match rules.peek() {
+            Some(pair) if pair.as_rule() == Rule::preface => {
+                Some(parse_preface(rules.next().unwrap())?)
+            }
+            _ => None,
+        };
+        let config = &with_in_buffer_settings(config, preface.as_ref());
+
+        // Try to parse all headlines and fails at the first Err
+        // TODO maybe collect all errors and return them all instead of
+        // just the first (using Itertools::partition_map)
+        let headlines: Vec<_> = rules
+            .by_ref()
+            .skip_while(|pair| pair.as_rule() == Rule::preface)
+            .peekable()
+            .peeking_take_while(is_rule(Rule::headline))
+            .map(|pair| {
+                let span = pair.as_span().into();
+                parse_headline(pair, config).map(|headline| (headline, span))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // TODO The last rule should be EOI, but assert fails
+        // assert_eq!(rules.next().map(|p| p.as_rule()), Some(Rule::EOI));
+
+        let mut preface = preface;
+        let headlines = match config.inlinetask_min_level {
+            Some(min_level) => extract_inlinetasks(s, &mut preface, headlines, min_level),
+            None => headlines.into_iter().map(|(headline, _)| headline).collect(),
+        };
+        let nested_headlines = nest_headlines(headlines);
+
+        let mut document = Document {
+            preface,
+            headlines: nested_headlines,
+        };
+        link_radio_targets(&mut document);
+        return Ok(document);
+    }
+    // The document rule can't fail. Worst case it is just empty ("SOI ~ EOI").
+    unreachable!("document rule can't fail")
+}
+
+/// Applies the settings of keywords in the preface to the configuration.
+///
+/// Only the todo keywords (`#+TODO:`, `#+SEQ_TODO:` and `#+TYP_TODO:`) are supported. All of them
+/// together replace the configured todo keywords.
+fn with_in_buffer_settings(config: &ParseConfig, preface: Option<&Section>) -> ParseConfig {
+    let todo_keywords = Keywords::new(preface).todo_sequences();
+    let mut config = config.clone();
+    if !todo_keywords.is_empty() {
+        config.todo_keywords = todo_keywords;
+    }
+    config
+}
+
+/// Nests a flat list of headlines according to their levels.
+///
+/// Headlines with a lower level than the first headline (e.g. `** a` followed by `* b`) are
+/// also top level headlines.
+pub(crate) fn nest_headlines(headlines: impl IntoIterator<Item = Headline>) -> Vec<Headline> {
+    let mut headlines = headlines.into_iter().peekable();
+    let mut collector = Vec::new();
+    while headlines.peek().is_some() {
+        collector.extend(nest_sibling_headlines(&mut headlines));
+    }
+    collector
+}
+
+/// Nests headlines correctly.
+///
+/// This function calls itself recursively and returns a list of modified
+/// headlines of the lowest level with higher level headlines nested in them
+/// correctly.
+///
+/// The iterator is peekable because the recursive calls skip over all the nested
+/// headlines and return to the lower level headlines. Without peekable we would skip
+/// headlines.
+fn nest_sibling_headlines(
+    headlines: &mut Peekable<impl Iterator<Item = Headline>>,
+) -> Vec<Headline> {
+    let mut collector = Vec::new();
+    collector.push(match headlines.next() {
+        None => return collector,
+        Some(h) => h,
+    });
+
+    while let Some(headline) = headlines.peek() {
+        let level = collector.last().unwrap().level;
+        let current = collector.last_mut().unwrap();
+        if headline.level > level {
+            // nest the headline and extend the span of the content to the nested headlines
+            let children = nest_sibling_headlines(headlines);
+            let span =
+                |child: &Headline| child.content().and_then(|content| content.span().clone());
+            let first = children.iter().find_map(span);
+            let last = children.iter().rev().find_map(span);
+            current.push_content(
+                children
+                    .into_iter()
+                    .map(Box::new)
+                    .map(HeadlineContentSet::Headline),
+            );
+            if let (Some(content), Some(first), Some(last)) = (&mut current.content, first, last) {
+                let start = content.span().as_ref().map_or(first.start(), Span::start);
+                content.set_span(Some(Span::new(start, last.end())));
+            }
+        } else if headline.level < level {
+            // return to higher headline
+            return collector;
+        } else {
+            // insert the headline at the same level
+            collector.push(headlines.next().unwrap());
+        }
+    }
+
+    collector
+}
+
+fn parse_preface<'i>(pair: Pair<'i, Rule>) -> Result<Section, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::preface);
+    parse_section(pair.into_inner().next().unwrap())
+}
+
+fn parse_section<'i>(pair: Pair<'i, Rule>) -> Result<Section, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::section);
+
+    let span = pair.as_span().into();
+    let content = Spanned::with_span(parse_elements(pair)?, span);
+
+    Ok(Section::new(content))
+}
+
+/// Parses the elements of a section (or of the contents of a drawer or greater block).
+///
+/// The blank lines after an element are the newlines up to the next element (or the end of the
+/// contents) except the one ending the element.
+fn parse_elements<'i>(pair: Pair<'i, Rule>) -> Result<Vec<ElementSet>, ParseError> {
+    let contents = pair.as_str();
+    let start = pair.as_span().start();
+    let mut pairs = pair.into_inner().peekable();
+    let mut elements = Vec::new();
+    while let Some(pair) = pairs.next() {
+        let end = pair.as_span().end() - start;
+        let next = pairs
+            .peek()
+            .map_or(contents.len(), |next| next.as_span().start() - start);
+        let newlines = contents[end..next].matches('\n').count();
+        let mut element = parse_element(pair)?;
+        element.set_post_blank(Some(newlines.saturating_sub(1)));
+        elements.push(element);
+    }
+    Ok(attach_affiliated_keywords(elements))
+}
+
+/// Moves the affiliated keywords (e.g. `#+NAME:`) directly above an element into the element.
+///
+/// The keywords stay keywords if there is a blank line after them or the element can't have
+/// affiliated keywords.
+fn attach_affiliated_keywords(elements: Vec<ElementSet>) -> Vec<ElementSet> {
+    let mut attached = Vec::with_capacity(elements.len());
+    let mut keywords: Vec<(ElementSet, AffiliatedKeyword)> = Vec::new();
+    for mut element in elements {
+        if let ElementSet::Keyword(keyword) = &element {
+            if element.post_blank() == Some(0) {
+                if let Some(affiliated) = elements::affiliated_keyword(keyword) {
+                    keywords.push((element, affiliated));
+                    continue;
+                }
+            }
+        }
+        if !keywords.is_empty() {
+            match element.affiliated_keywords_mut() {
+                Some(affiliated_keywords) => {
+                    let affiliated = keywords.drain(..).map(|(_, affiliated)| affiliated);
+                    *affiliated_keywords = Some(Spanned::new(affiliated.collect()));
+                }
+                None => attached.extend(keywords.drain(..).map(|(keyword, _)| keyword)),
+            }
+        }
+        attached.push(element);
+    }
+    attached.extend(keywords.into_iter().map(|(keyword, _)| keyword));
+    attached
+}
+
+fn parse_element<'i>(pair: Pair<'i, Rule>) -> Result<ElementSet, ParseError> {
+    match pair.as_rule() {
+        Rule::keyword => elements::parse_keyword(pair).map(ElementSet::from),
+        Rule::clock => {
+            // clocks with an invalid timestamp are regular text
+            let text = pair.as_str();
+            let start = pair.as_span().start();
+            Ok(elements::parse_clock(pair)
+                .map(ElementSet::from)
+                .unwrap_or_else(|_| Paragraph::new(parse_objects_at(text, start)).into()))
+        }
+        Rule::src_block => elements::parse_src_block(pair).map(ElementSet::from),
+        Rule::comment_block
+        | Rule::example_block
+        | Rule::export_block
+        | Rule::verse_block
+        | Rule::center_block
+        | Rule::special_block => elements::parse_block(pair),
+        Rule::drawer | Rule::quote_block => elements::parse_drawer(pair),
+        Rule::footnote_definition => {
+            elements::parse_footnote_definition(pair).map(ElementSet::from)
+        }
+        Rule::property_drawer => elements::parse_property_drawer(pair).map(ElementSet::from),
+        Rule::plain_list => elements::parse_plain_list(pair).map(ElementSet::from),
+        Rule::table => elements::parse_table(pair).map(ElementSet::from),
+        Rule::encrypted_block => Ok(EncryptedBlock::new(pair.as_str().trim_end()).into()),
+        Rule::diary_sexp => Ok(DiarySexp::new(&pair.as_str()["%%(".len()..]).into()),
+        _ => parse_paragraph(pair).map(ElementSet::from),
+    }
+}
+
+fn parse_paragraph<'i>(pair: Pair<'i, Rule>) -> Result<Paragraph, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::paragraph);
+
+    let span: Span = pair.as_span().into();
+    let mut value = parse_objects_at(pair.as_str(), span.start());
+    unescape_paragraph(&mut value);
+
+    Ok(Paragraph::new(value))
+}
+
+fn parse_headline<'i>(
+    pair: Pair<'i, Rule>,
+    config: &ParseConfig,
+) -> Result<Headline, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::headline);
+
+    let _span: Span = pair.as_span().into();
+
+    let mut inner = pair.into_inner().peekable();
+    let affiliated_keywords = inner
+        .by_ref()
+        .peeking_take_while(is_rule(Rule::affiliated_keywords))
+        .take(1)
+        .map(|_p| unimplemented!()) // TODO parse_affiliated_keywords
+        .next();
+    let stars = inner
+        .by_ref()
+        .take(1)
+        .filter(is_rule(Rule::stars))
+        .map(|p| p.as_str().len())
+        .next()
+        .unwrap(); // grammar guarantees at least one star
+    let stars = if stars <= u32::MAX as usize {
+        stars as u32
+    } else {
+        return Err(ParseError::StructuralError(
+            "to many stars in headline (more than 2^32-1)",
+        ));
+    };
+    let title = inner
+        .by_ref()
+        .skip_while(is_rule(Rule::BLANK))
+        .take(1)
+        .find(is_rule(Rule::title));
+    let title_end = title.as_ref().map_or(0, |p| p.as_span().end());
+    let title = title.map(|p| p.as_str().to_string());
+    // TODO better error handling for title and everything that is derived
+    //      from title
+
+    let (todo_keyword, title) = extract_value(title, |title| extract_todo_keyword(title, config));
+    let (priority, title) = extract_value(title, extract_priority);
+    // only the start of the title was removed until now
+    let title_start = title.as_ref().map_or(0, |title| title_end - title.len());
+    let quoted = config.compat.quote_headlines
+        && title
+            .as_ref()
+            .is_some_and(|title| title.split_whitespace().next() == Some("QUOTE"));
+    let (tags, title) = extract_value(title, extract_tags);
+    let tags = tags.unwrap_or_default();
+    let title = title.and_then(|title| {
+        if title.is_empty() {
+            None
+        } else {
+            Some(parse_title(&title, title_start))
+        }
+    });
+    let planning = inner
+        .by_ref()
+        .peeking_take_while(is_rule(Rule::planning))
+        .take(1)
+        .map(elements::parse_planning)
+        .next()
+        .transpose()?;
+    let mut section = inner
+        .by_ref()
+        .peeking_take_while(is_rule(Rule::section))
+        .take(1)
+        .map(parse_section)
+        .next()
+        .transpose()?;
+
+    // A property drawer directly after the headline belongs to the headline.
+    let property_drawer = match &mut section {
+        Some(section) => match section.content_mut().first() {
+            Some(ElementSet::PropertyDrawer(_)) => match section.content_mut().remove(0) {
+                ElementSet::PropertyDrawer(drawer) => Some(*drawer),
+                _ => unreachable!("the first element is a property drawer"),
+            },
+            _ => None,
+        },
+        None => None,
+    };
+    let section = section.filter(|section| {
+        section
+            .content()
+            .is_some_and(|content| !content.value().is_empty())
+    });
+
+    // Child headlines are added to the content when the headlines are nested.
+    let content = section.map(|section| {
+        let span = section.content().and_then(|content| content.span().clone());
+        Spanned::with_optional_span(vec![HeadlineContentSet::Section(section)], span)
+    });
+
+    Ok(Headline {
+        affiliated_keywords,
+        content,
+        level: stars,
+        todo_keyword,
+        priority,
+        title,
+        tags,
+        planning,
+        property_drawer,
+        quoted,
+    })
+}
+
+/// Extract a value from the start of the title and
+/// remove the string from the title.
+///
+/// Also trims the start of the new title.
+fn extract_value<T, F>(opt_str: Option<String>, f: F) -> (Option<T>, Option<String>)
+where
+    F: FnOnce(&str) -> Option<(T, &str)>,
+{
+    if let Some((res, new_str)) = opt_str.as_ref().and_then(|s| f(s)) {
+        (Some(res), Some(new_str.trim_start().to_string()))
+    } else {
+        (None, opt_str)
+    }
+}
+
+/// Extracts the todo keyword (one of [`ParseConfig::todo_keywords`]) from the start of the
+/// title. The keyword has to be followed by whitespace or the end of the title.
+fn extract_todo_keyword<'a>(
+    title: &'a str,
+    config: &ParseConfig,
+) -> Option<(TodoKeyword, &'a str)> {
+    let end = title.find(char::is_whitespace).unwrap_or(title.len());
+    config
+        .todo_keyword(&title[..end])
+        .map(|keyword| (keyword, &title[end..]))
+}
+fn extract_priority(title: &str) -> Option<(char, &str)> {
+    if let Some(s) = title.trim_start().get(..4) {
+        let mut cs = s.chars();
+        if cs.next() == Some('[') && cs.next() == Some('#') {
+            if let Some(priority) = cs.next() {
+                if cs.next() == Some(']') {
+                    return Some((priority, &title.trim_start()[4..]));
+                }
+            }
+        }
+    }
+    None
+}
+/// Extracts the tags (e.g. `:work:urgent:`) from the end of the title.
+fn extract_tags(title: &str) -> Option<(Vec<String>, &str)> {
+    let title = title.trim_end();
+    let last = title.rsplit(char::is_whitespace).next()?;
+    let tags = last.strip_prefix(':')?.strip_suffix(':')?;
+    let is_tag = |tag: &str| {
+        !tag.is_empty()
+            && tag
+                .chars()
+                .all(|c| c.is_alphanumeric() || "_@#%".contains(c))
+    };
+    if tags.split(':').all(is_tag) {
+        let tags = tags.split(':').map(String::from).collect();
+        Some((tags, title[..title.len() - last.len()].trim_end()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Planning;
+    use crate::types::objects::{Link, LinkFormat};
+    use crate::types::{SecondaryString, StandardSet, StandardSetNoLineBreak};
+
+    mod parse_headline {
+        use super::*;
+
+        #[test]
+        fn empty() {
+            let s = "*";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            let headline = parse_headline(pair, &ParseConfig::default());
+            let expected = Ok(Headline {
+                level: 1,
+                ..Headline::default()
+            });
+            assert_eq!(headline, expected);
+        }
+        #[test]
+        fn priority_no_title() {
+            let s = "* [#A]";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            let headline = parse_headline(pair, &ParseConfig::default());
+            let expected = Ok(Headline {
+                level: 1,
+                priority: Some('A'),
+                title: None,
+                ..Headline::default()
+            });
+            assert_eq!(headline, expected);
+        }
+        #[test]
+        fn todo_no_title() {
+            let s = "* TODO";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            let headline = parse_headline(pair, &ParseConfig::default());
+            let expected = Ok(Headline {
+                level: 1,
+                todo_keyword: Some(TodoKeyword::Todo("TODO".to_string())),
+                title: None,
+                ..Headline::default()
+            });
+            assert_eq!(headline, expected);
+        }
+        #[test]
+        fn todo_with_title() {
+            let s = "* TODO Something todo";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            let headline = parse_headline(pair, &ParseConfig::default());
+            let expected = Ok(Headline {
+                level: 1,
+                todo_keyword: Some(TodoKeyword::Todo("TODO".to_string())),
+                title: Some(SecondaryString::with_one(
+                    StandardSetNoLineBreak::RawString("Something todo".to_string()),
+                )),
+                ..Headline::default()
+            });
+            assert_eq!(headline, expected);
+        }
+        #[test]
+        fn quoted() {
+            let s = "* QUOTE Something quoted";
+            let parse = |config: &ParseConfig| {
+                let pair = OrgModeParser::parse(Rule::headline, s)
+                    .unwrap()
+                    .next()
+                    .unwrap();
+                parse_headline(pair, config).unwrap()
+            };
+            let title = Some(SecondaryString::with_one(StandardSetNoLineBreak::RawString(
+                "QUOTE Something quoted".to_string(),
+            )));
+
+            let old = parse(&ParseConfig::for_org_version(OrgVersion::new(8, 2)));
+            assert_eq!(old.title, title);
+            assert!(old.quoted);
+
+            let new = parse(&ParseConfig::default());
+            assert_eq!(new.title, title);
+            assert!(!new.quoted);
+        }
+        #[test]
+        fn quote_is_a_whole_word() {
+            let config = ParseConfig::for_org_version(OrgVersion::new(8, 2));
+            let quoted = |s| {
+                let pair = OrgModeParser::parse(Rule::headline, s)
+                    .unwrap()
+                    .next()
+                    .unwrap();
+                parse_headline(pair, &config).unwrap().quoted
+            };
+            assert!(quoted("* QUOTE"));
+            assert!(quoted("* TODO QUOTE\tSomething"));
+            assert!(!quoted("* QUOTES of the day"));
+        }
+        #[test]
+        fn planning() {
+            let s = "* TODO Task\n\
+                     SCHEDULED: <2018-10-08 Mon>  CLOSED: [2018-10-09 Tue 17:00]\n\
+                     Body";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            let headline = parse_headline(pair, &ParseConfig::default()).unwrap();
+            let expected = Planning {
+                closed: Some("[2018-10-09 Tue 17:00]".parse().unwrap()),
+                scheduled: Some("<2018-10-08 Mon>".parse().unwrap()),
+                ..Planning::default()
+            };
+            assert_eq!(headline.planning, Some(expected));
+            assert_eq!(headline.content().unwrap().value().len(), 1);
+        }
+        #[test]
+        fn invalid_planning_timestamp() {
+            let s = "* Task\nDEADLINE: <tomorrow>";
+            let pair = OrgModeParser::parse(Rule::headline, s)
+                .unwrap()
+                .next()
+                .unwrap();
+            assert!(parse_headline(pair, &ParseConfig::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn planning_is_written_back() {
+        let s = "* TODO Task\nDEADLINE: <2018-10-10 Wed +1w> SCHEDULED: <2018-10-08 Mon>\nBody\n";
+        assert_eq!(parse_document(s).unwrap().to_string(), s);
+    }
+
+    #[test]
+    fn line_starting_with_todo_comment_is_a_paragraph() {
+        let s = "* H\n// TODO\n";
+        let document = parse_document(s).unwrap();
+        assert!(document.headlines[0].planning.is_none());
+        assert_eq!(document.to_string(), s);
+    }
+
+    #[test]
+    fn todo_keywords() {
+        let todo_keywords = |document: &Document| -> Vec<_> {
+            document
+                .headlines
+                .iter()
+                .map(|headline| headline.todo_keyword.clone())
+                .collect()
+        };
+        let s = "* WAIT a\n* CANCELLED b\n* TODOS c\n";
+        let config = ParseConfig {
+            todo_keywords: vec![TodoSequence::new(&["WAIT"], &["CANCELLED"])],
+            ..ParseConfig::default()
+        };
+        assert_eq!(
+            todo_keywords(&parse_document_with_config(s, &config).unwrap()),
+            vec![
+                Some(TodoKeyword::Todo("WAIT".to_string())),
+                Some(TodoKeyword::Done("CANCELLED".to_string())),
+                None,
+            ]
+        );
+
+        let s = "#+TODO: TODO(t) | DONE\n#+SEQ_TODO: BUG FIXED\n\n* TODO a\n* FIXED b\n* NEXT c\n";
+        assert_eq!(
+            todo_keywords(&parse_document(s).unwrap()),
+            vec![
+                Some(TodoKeyword::Todo("TODO".to_string())),
+                Some(TodoKeyword::Done("FIXED".to_string())),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn tags() {
+        let document = parse_document("* a :work:@home:\n* b:c: d\n* :e:\n").unwrap();
+        let tags: Vec<_> = document
+            .headlines
+            .iter()
+            .map(|headline| headline.tags.join(" "))
+            .collect();
+        assert_eq!(tags, vec!["work @home", "", "e"]);
+        let titles: Vec<_> = document
+            .headlines
+            .iter()
+            .map(|headline| headline.title.as_ref().map(ToString::to_string))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![Some("a".to_string()), Some("b:c: d".to_string()), None]
+        );
+    }
+
+    const SPANS: &str = "* TODO [#A] See <2018-10-12 Fri> :work:\nText [[#a][*the* link]] here.\n";
+
+    fn text(span: &Span) -> &'static str {
+        &SPANS[span.start()..span.end()]
+    }
+
+    #[test]
+    fn title_object_spans() {
+        let document = parse_document(SPANS).unwrap();
+        let title: Vec<_> = document.headlines[0]
+            .title
+            .as_ref()
+            .unwrap()
+            .iter_spanned()
+            .map(|(_, span)| text(span.unwrap()))
+            .collect();
+        assert_eq!(title, vec!["See ", "<2018-10-12 Fri>"]);
+    }
+
+    #[test]
+    fn paragraph_object_spans() {
+        let document = parse_document(SPANS).unwrap();
+        let paragraph = match document.headlines[0].content().unwrap().value().as_slice() {
+            [HeadlineContentSet::Section(section)] => match section.content().unwrap().value()[..] {
+                [ElementSet::Paragraph(ref paragraph)] => paragraph,
+                ref elements => panic!("expected paragraph, got {:?}", elements),
+            },
+            content => panic!("expected section, got {:?}", content),
+        };
+        let (link, span) = paragraph
+            .content
+            .object_at_offset(SPANS.find("link").unwrap())
+            .unwrap();
+        assert_eq!(text(span), "[[#a][*the* link]]");
+        // the spans of nested objects are in the file too
+        match link {
+            StandardSet::Link(Link {
+                link: LinkFormat::Bracket(_, _, Some(description)),
+            }) => {
+                let (_, span) = description
+                    .object_at_offset(SPANS.find("the").unwrap())
+                    .unwrap();
+                assert_eq!(text(span), "*the*");
+            }
+            object => panic!("expected link, got {:?}", object),
+        }
+    }
+}
@@ -0,0 +1,977 @@
+//! A parser for objects (the inline content of paragraphs, table cells, etc.).
+//!
+//! Currently links (except radio links), targets, radio targets, footnote references,
+//! timestamps, statistics cookies, text markup and line breaks are recognized. Everything else
+//! is kept as raw strings. Radio links are found after the whole document is parsed (see
+//! [`link_radio_targets`](`super::link_radio_targets`)). Paragraphs, headline titles, table
+//! cells, keyword values and the values of captions and results are parsed into objects.
+//!
+//! The objects of paragraphs and headline titles store their spans in the file (see
+//! [`SecondaryString::iter_spanned`]).
+//!
+//! Objects are only recognized where they are allowed. E.g. link descriptions can't contain
+//! links. Text markup can't contain markup of the same kind: the first valid closing marker
+//! always ends the markup.
+
+use super::ParseError;
+use crate::types::greater_elements::{TableRow, TableRowKind};
+use crate::types::objects::{
+    CookieKind, FootnoteReference, FootnoteReferenceKind, LineBreak, Link,
+    LinkDescriptionSetOfObjects, LinkFormat, LinkPath, RadioTarget, SearchOption, StatisticsCookie,
+    TableCell, TableCellSetOfObjects, Target, TextMarkup, TextMarkupKind, Timestamp,
+};
+use crate::types::{
+    SecondaryString, Span, Spanned, StandardSet, StandardSetNoLineBreak, ORG_LINK_TYPES,
+};
+use std::str::FromStr;
+
+/// Parses a table row (`| a | b |` or a rule `|---+---|`).
+///
+/// Returns `None` if the line is not a table row.
+pub fn parse_table_row(line: &str) -> Option<TableRow> {
+    let line = line.trim();
+    if !line.starts_with('|') {
+        return None;
+    }
+    if line.starts_with("|-") {
+        return Some(TableRow {
+            kind: TableRowKind::Rule,
+        });
+    }
+    let inner = &line[1..];
+    // the final bar is optional
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    Some(TableRow {
+        kind: TableRowKind::Normal(Spanned::new(
+            inner.split('|').map(parse_table_cell).collect(),
+        )),
+    })
+}
+
+/// Parses the content of a table cell (without the vertical bars).
+///
+/// The whitespace used to align the cell is removed. `\vert{}` in the text is a vertical bar
+/// (see [`TableCell`]).
+pub fn parse_table_cell(s: &str) -> TableCell {
+    TableCell::new(
+        parse_objects_until(s.trim(), false)
+            .into_iter()
+            .map(|object| match object {
+                StandardSet::RawString(s) => {
+                    TableCellSetOfObjects::RawString(s.replace("\\vert{}", "|"))
+                }
+                object => object.into(),
+            })
+            .collect(),
+    )
+}
+
+/// Parses a string into objects. The string has to end at the end of a line.
+pub(crate) fn parse_objects(s: &str) -> Vec<StandardSet> {
+    parse_objects_until(s, true)
+}
+
+/// Parses a string at the byte `offset` of the file into objects with their spans. The string
+/// has to end at the end of a line.
+pub(crate) fn parse_objects_at(s: &str, offset: usize) -> SecondaryString<StandardSet> {
+    parse_spanned_objects(s, true, Some(offset))
+}
+
+/// Parses the title of a headline at the byte `offset` of the file. Titles can't contain line
+/// breaks.
+pub(crate) fn parse_title(s: &str, offset: usize) -> SecondaryString<StandardSetNoLineBreak> {
+    parse_spanned_objects(s, false, Some(offset)).map(Into::into)
+}
+
+/// Parses a string into objects.
+///
+/// `end_of_line` is `true` if the string ends at the end of a line. Otherwise a line break can't
+/// be at the end of the string (e.g. in `*bold\\*`).
+fn parse_objects_until(s: &str, end_of_line: bool) -> Vec<StandardSet> {
+    parse_spanned_objects(s, end_of_line, None)
+        .into_iter()
+        .collect()
+}
+
+/// Parses a string into objects like [`parse_objects_until`].
+///
+/// If the byte `offset` of the string in the file is known the span of every object (also of
+/// objects inside other objects) is stored.
+fn parse_spanned_objects(
+    s: &str,
+    end_of_line: bool,
+    offset: Option<usize>,
+) -> SecondaryString<StandardSet> {
+    let mut objects = Vec::new();
+    let mut spans = Vec::new();
+    let mut push = |object, start, end| {
+        objects.push(object);
+        if let Some(offset) = offset {
+            spans.push(Span::new(offset + start, offset + end));
+        }
+    };
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < s.len() {
+        let at = offset.map(|offset| offset + i);
+        let parsed = match s[i..].chars().next() {
+            Some('[') if s[i..].starts_with("[[") => parse_link(&s[i..], at),
+            Some('\\') if s[i..].starts_with("\\\\") && !s[..i].ends_with('\\') => {
+                parse_line_break(&s[i..], end_of_line)
+            }
+            Some('<') if s[i..].starts_with("<<") => parse_target(&s[i..], at),
+            Some('<') => parse_timestamp(&s[i..]).or_else(|| parse_angle_link(&s[i..])),
+            Some('[') if s[i..].starts_with("[fn:") => parse_footnote_reference(&s[i..], at),
+            Some('[') => parse_timestamp(&s[i..]).or_else(|| parse_statistics_cookie(&s[i..])),
+            Some(c) if MARKUP.contains(c) && is_markup_pre(&s[..i]) => parse_markup(&s[i..], at),
+            Some(c) if c.is_ascii_alphabetic() && is_plain_link_pre(&s[..i]) => {
+                parse_plain_link(&s[i..])
+            }
+            _ => None,
+        };
+        match parsed {
+            Some((object, len)) => {
+                if text_start < i {
+                    let text = StandardSet::RawString(s[text_start..i].to_string());
+                    push(text, text_start, i);
+                }
+                push(object, i, i + len);
+                i += len;
+                text_start = i;
+            }
+            None => i += s[i..].chars().next().map(char::len_utf8).unwrap_or(1),
+        }
+    }
+    if text_start < s.len() {
+        push(
+            StandardSet::RawString(s[text_start..].to_string()),
+            text_start,
+            s.len(),
+        );
+    }
+    if offset.is_some() {
+        SecondaryString::with_spans(objects, spans)
+    } else {
+        SecondaryString::from(objects)
+    }
+}
+
+const MARKUP: &str = "*/_+=~";
+
+impl FromStr for TextMarkup {
+    type Err = ParseError;
+
+    /// Parses text markup (e.g. `*bold*` or `~code~`). The whole string has to be the markup.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with(|c| MARKUP.contains(c)) {
+            return Err(ParseError::StructuralError(
+                "text markup has to start with a marker",
+            ));
+        }
+        match parse_markup(s, None) {
+            Some((StandardSet::TextMarkup(markup), len)) if len == s.len() => Ok(markup),
+            _ => Err(ParseError::StructuralError("invalid text markup")),
+        }
+    }
+}
+
+/// Parses a bracket link (`[[path]]` or `[[path][description]]`).
+///
+/// `offset` is the byte offset of the link in the file if it is known.
+fn parse_link(s: &str, offset: Option<usize>) -> Option<(StandardSet, usize)> {
+    let end = s.find("]]")?;
+    let inner = &s[2..end];
+    if inner.contains('\n') {
+        return None;
+    }
+    let (path, description) = match inner.find("][") {
+        Some(index) => (&inner[..index], Some(&inner[index + 2..])),
+        None => (inner, None),
+    };
+    if path.is_empty() || path.contains('[') || path.contains(']') {
+        return None;
+    }
+    let (path, search_option) = parse_link_path(path);
+    let description = description.map(|description| {
+        // the description is at the end of the link (before the closing brackets)
+        let offset = offset.map(|offset| offset + end - description.len());
+        parse_spanned_objects(description, false, offset).map(|object| match object {
+            StandardSet::TextMarkup(o) => LinkDescriptionSetOfObjects::TextMarkup(o),
+            // only plain and angle links are found in descriptions
+            StandardSet::Link(o) => LinkDescriptionSetOfObjects::Link(o),
+            object => LinkDescriptionSetOfObjects::RawString(object.to_string()),
+        })
+    });
+    let link = Link {
+        link: LinkFormat::Bracket(path, search_option, description),
+    };
+    Some((StandardSet::Link(link), end + 2))
+}
+
+/// Parses a footnote reference (`[fn:LABEL]`, `[fn:LABEL:DEFINITION]` or `[fn::DEFINITION]`).
+///
+/// Square brackets in the definition have to be balanced. `offset` is the byte offset of the
+/// reference in the file if it is known.
+fn parse_footnote_reference(s: &str, offset: Option<usize>) -> Option<(StandardSet, usize)> {
+    let start = "[fn:".len();
+    let mut depth = 0;
+    let end = s
+        .bytes()
+        .enumerate()
+        .skip(start)
+        .find_map(|(index, byte)| match byte {
+            b'[' => {
+                depth += 1;
+                None
+            }
+            b']' if depth == 0 => Some(index),
+            b']' => {
+                depth -= 1;
+                None
+            }
+            _ => None,
+        })?;
+    let inner = &s[start..end];
+    let (label, definition) = match inner.find(':') {
+        Some(index) => (&inner[..index], Some(&inner[index + 1..])),
+        None => (inner, None),
+    };
+    if !label
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    let definition = definition.map(|definition| {
+        let offset = offset.map(|offset| offset + end - definition.len());
+        parse_spanned_objects(definition, false, offset)
+    });
+    let kind = match (label, definition) {
+        ("", None) => return None,
+        ("", Some(definition)) => FootnoteReferenceKind::Anonymous { definition },
+        (label, None) => FootnoteReferenceKind::Normal {
+            label: label.to_string(),
+        },
+        (label, Some(definition)) => FootnoteReferenceKind::Inline {
+            label: label.to_string(),
+            definition,
+        },
+    };
+    Some((
+        StandardSet::FootnoteReference(FootnoteReference { kind }),
+        end + 1,
+    ))
+}
+
+impl FromStr for FootnoteReference {
+    type Err = ParseError;
+
+    /// Parses a footnote reference (e.g. `[fn:1]`). The whole string has to be the reference.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with("[fn:") {
+            return Err(ParseError::StructuralError(
+                "footnote reference has to start with [fn:",
+            ));
+        }
+        match parse_footnote_reference(s, None) {
+            Some((StandardSet::FootnoteReference(reference), len)) if len == s.len() => {
+                Ok(reference)
+            }
+            _ => Err(ParseError::StructuralError("invalid footnote reference")),
+        }
+    }
+}
+
+/// Parses a statistics cookie (`[1/3]` or `[33%]`). The numbers can be empty (`[/]` or `[%]`).
+fn parse_statistics_cookie(s: &str) -> Option<(StandardSet, usize)> {
+    fn number(s: &str) -> Option<Option<u32>> {
+        match s {
+            "" => Some(None),
+            _ if s.bytes().all(|b| b.is_ascii_digit()) => s.parse().ok().map(Some),
+            _ => None,
+        }
+    }
+
+    let end = s.find(']')?;
+    let inner = &s[1..end];
+    let cookie = if let Some(percent) = inner.strip_suffix('%') {
+        CookieKind::Percent(number(percent)?)
+    } else {
+        let slash = inner.find('/')?;
+        CookieKind::Number(number(&inner[..slash])?, number(&inner[slash + 1..])?)
+    };
+    Some((
+        StandardSet::StatisticsCookie(StatisticsCookie { cookie }),
+        end + 1,
+    ))
+}
+
+/// Returns the length of the link type (e.g. `https`) at the start of `s` if it is one of the
+/// [`ORG_LINK_TYPES`] followed by a colon.
+fn link_type_len(s: &str) -> Option<usize> {
+    let len = s.find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))?;
+    if s[len..].starts_with(':') && ORG_LINK_TYPES.contains(&&s[..len]) {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// Parses an angle link (`<PROTOCOL:PATH>`).
+fn parse_angle_link(s: &str) -> Option<(StandardSet, usize)> {
+    let end = 1 + s[1..].find(|c| "<>]\n".contains(c))?;
+    let inner = &s[1..end];
+    if !s[end..].starts_with('>') || inner.len() == link_type_len(inner)? + 1 {
+        return None;
+    }
+    let link = Link {
+        link: LinkFormat::Angle(inner.to_string()),
+    };
+    Some((StandardSet::Link(link), end + 1))
+}
+
+/// Returns `true` if a plain link can start after `before`.
+fn is_plain_link_pre(before: &str) -> bool {
+    match before.chars().last() {
+        None => true,
+        Some(c) => !c.is_alphanumeric() && c != '_',
+    }
+}
+
+/// Parses a plain link (`PROTOCOL:PATH`, e.g. `https://orgmode.org`).
+///
+/// The path ends at whitespace, brackets or angle brackets. Trailing punctuation (except `/`) is
+/// not part of the link.
+fn parse_plain_link(s: &str) -> Option<(StandardSet, usize)> {
+    let path_start = link_type_len(s)? + 1;
+    let path_len = s[path_start..]
+        .find(|c: char| c.is_whitespace() || "()<>[]".contains(c))
+        .unwrap_or(s.len() - path_start);
+    let path = s[path_start..path_start + path_len]
+        .trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '/');
+    if path.is_empty() {
+        return None;
+    }
+    let end = path_start + path.len();
+    let link = Link {
+        link: LinkFormat::Plain(s[..end].to_string()),
+    };
+    Some((StandardSet::Link(link), end))
+}
+
+impl FromStr for Link {
+    type Err = ParseError;
+
+    /// Parses a bracket, angle or plain link. The whole string has to be the link.
+    ///
+    /// Radio links are only found in documents with a matching radio target.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = if s.starts_with("[[") {
+            parse_link(s, None)
+        } else if s.starts_with('<') {
+            parse_angle_link(s)
+        } else {
+            parse_plain_link(s)
+        };
+        match parsed {
+            Some((StandardSet::Link(link), len)) if len == s.len() => Ok(link),
+            _ => Err(ParseError::StructuralError("invalid link")),
+        }
+    }
+}
+
+fn parse_link_path(path: &str) -> (LinkPath, Option<SearchOption>) {
+    if let Some(id) = path.strip_prefix('#') {
+        return (LinkPath::CustomId(id.to_string()), None);
+    }
+    if path.starts_with('(') && path.ends_with(')') {
+        return (LinkPath::CodeRef(path[1..path.len() - 1].to_string()), None);
+    }
+    if let Some(id) = path.strip_prefix("id:") {
+        return (LinkPath::Id(id.to_string()), None);
+    }
+    let file = if let Some(file) = path.strip_prefix("file:") {
+        Some(file)
+    } else if path.starts_with('/') || path.starts_with("./") || path.starts_with("~/") {
+        Some(path)
+    } else {
+        None
+    };
+    if let Some(file) = file {
+        return match file.find("::") {
+            Some(index) => (
+                LinkPath::File(file[..index].to_string()),
+                Some(parse_search_option(&file[index + 2..])),
+            ),
+            None => (LinkPath::File(file.to_string()), None),
+        };
+    }
+    match path.find(':') {
+        Some(colon)
+            if colon > 0
+                && path[..colon]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.') =>
+        {
+            (
+                LinkPath::Protocol {
+                    protocol: path[..colon].to_string(),
+                    path: path[colon + 1..].to_string(),
+                },
+                None,
+            )
+        }
+        _ => (LinkPath::Fuzzy(path.to_string()), None),
+    }
+}
+
+fn parse_search_option(s: &str) -> SearchOption {
+    if let Ok(line) = s.parse() {
+        SearchOption::Line(line)
+    } else if let Some(title) = s.strip_prefix('*') {
+        SearchOption::Headlines(title.to_string())
+    } else if let Some(id) = s.strip_prefix('#') {
+        SearchOption::CustomId(id.to_string())
+    } else if s.len() > 1 && s.starts_with('/') && s.ends_with('/') {
+        SearchOption::Regex(s[1..s.len() - 1].to_string())
+    } else {
+        SearchOption::Target(s.to_string())
+    }
+}
+
+/// Parses a line break (`\\` followed by spaces and the end of the line).
+///
+/// The newline is part of the line break.
+fn parse_line_break(s: &str, end_of_line: bool) -> Option<(StandardSet, usize)> {
+    let rest = &s[2..];
+    let spaces = rest.len() - rest.trim_start_matches(&[' ', '\t'][..]).len();
+    let len = if rest[spaces..].starts_with('\n') {
+        2 + spaces + 1
+    } else if rest[spaces..].is_empty() && end_of_line {
+        2 + spaces
+    } else {
+        return None;
+    };
+    let line_break = LineBreak {
+        spaces: spaces as u64,
+    };
+    Some((StandardSet::LineBreak(line_break), len))
+}
+
+/// Parses a timestamp (including date ranges `<...>--<...>`).
+fn parse_timestamp(s: &str) -> Option<(StandardSet, usize)> {
+    let close = if s.starts_with('<') { '>' } else { ']' };
+    let mut end = s.find(close)? + 1;
+    // date range
+    if s[end..].starts_with("--") {
+        if let Some(second) = s[end + 2..].find(close) {
+            let range_end = end + 2 + second + 1;
+            if s[..range_end].parse::<Timestamp>().is_ok() {
+                end = range_end;
+            }
+        }
+    }
+    let timestamp = s[..end].parse::<Timestamp>().ok()?;
+    Some((StandardSet::Timestamp(timestamp), end))
+}
+
+/// Parses a target (`<<TARGET>>`) or a radio target (`<<<TARGET>>>`).
+///
+/// The target can't contain `<`, `>` or newlines and can't start or end with whitespace. The
+/// content of radio targets is parsed into objects. `offset` is the byte offset of the target in
+/// the file if it is known.
+fn parse_target(s: &str, offset: Option<usize>) -> Option<(StandardSet, usize)> {
+    let (open, close) = if s.starts_with("<<<") {
+        ("<<<", ">>>")
+    } else {
+        ("<<", ">>")
+    };
+    let len = s[open.len()..].find(|c| "<>\n".contains(c))?;
+    let target = &s[open.len()..open.len() + len];
+    if target.is_empty()
+        || target.starts_with(char::is_whitespace)
+        || target.ends_with(char::is_whitespace)
+        || !s[open.len() + len..].starts_with(close)
+    {
+        return None;
+    }
+    let end = open.len() + len + close.len();
+    let object = if open == "<<<" {
+        let offset = offset.map(|offset| offset + open.len());
+        StandardSet::RadioTarget(RadioTarget {
+            target: parse_spanned_objects(target, false, offset).map(Into::into),
+        })
+    } else {
+        StandardSet::Target(Target {
+            target: target.to_string(),
+        })
+    };
+    Some((object, end))
+}
+
+/// Returns `true` if text markup can start after `before`.
+fn is_markup_pre(before: &str) -> bool {
+    match before.chars().last() {
+        None => true,
+        Some(c) => c.is_whitespace() || "-({'\"".contains(c),
+    }
+}
+
+fn is_markup_post(after: &str) -> bool {
+    match after.chars().next() {
+        None => true,
+        Some(c) => c.is_whitespace() || "-.,;:!?')}[\"".contains(c),
+    }
+}
+
+/// Parses text markup (e.g. `*bold*`). The content can't start or end with whitespace.
+///
+/// `offset` is the byte offset of the markup in the file if it is known.
+fn parse_markup(s: &str, offset: Option<usize>) -> Option<(StandardSet, usize)> {
+    let marker = s.chars().next()?;
+    let content_start = marker.len_utf8();
+    if s[content_start..].starts_with(char::is_whitespace) {
+        return None;
+    }
+    let mut search = content_start;
+    loop {
+        let index = search + s[search..].find(marker)?;
+        let content = &s[content_start..index];
+        if !content.is_empty()
+            && !content.ends_with(char::is_whitespace)
+            && is_markup_post(&s[index + marker.len_utf8()..])
+        {
+            let kind = match marker {
+                '=' => TextMarkupKind::Verbatim(content.to_string()),
+                '~' => TextMarkupKind::Code(content.to_string()),
+                _ => {
+                    let offset = offset.map(|offset| offset + content_start);
+                    let content = parse_spanned_objects(content, false, offset);
+                    match marker {
+                        '*' => TextMarkupKind::Bold(content),
+                        '/' => TextMarkupKind::Italic(content),
+                        '_' => TextMarkupKind::Underline(content),
+                        _ => TextMarkupKind::StrikeThrough(content),
+                    }
+                }
+            };
+            let markup = TextMarkup { kind };
+            return Some((StandardSet::TextMarkup(markup), index + marker.len_utf8()));
+        }
+        search = index + marker.len_utf8();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::objects::{RadioTargetSetOfObjects, TableCellSetOfObjects};
+
+    #[test]
+    fn objects() {
+        let objects = parse_objects("see [[https://orgmode.org][*the* site]] on <2018-10-12 Fri>");
+        assert_eq!(objects.len(), 4);
+        assert_eq!(
+            objects[1],
+            StandardSet::Link(Link {
+                link: LinkFormat::Bracket(
+                    LinkPath::Protocol {
+                        protocol: "https".to_string(),
+                        path: "//orgmode.org".to_string(),
+                    },
+                    None,
+                    Some(
+                        vec![
+                            LinkDescriptionSetOfObjects::TextMarkup(TextMarkup {
+                                kind: TextMarkupKind::Bold(SecondaryString::with_one(
+                                    StandardSet::RawString("the".to_string())
+                                )),
+                            }),
+                            LinkDescriptionSetOfObjects::RawString(" site".to_string()),
+                        ]
+                        .into()
+                    ),
+                ),
+            })
+        );
+        match &objects[3] {
+            StandardSet::Timestamp(_) => {}
+            object => panic!("expected timestamp, got {:?}", object),
+        }
+    }
+
+    #[test]
+    fn markup_boundaries() {
+        let to_strings = |s| {
+            parse_objects(s)
+                .iter()
+                .map(|object| match object {
+                    StandardSet::TextMarkup(_) => format!("markup({})", object),
+                    _ => object.to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(to_strings("a*b*c"), vec!["a*b*c"]);
+        assert_eq!(to_strings("2 * 3 * 4"), vec!["2 * 3 * 4"]);
+        assert_eq!(
+            to_strings("(=x=), ~y~"),
+            vec!["(", "markup(=x=)", "), ", "markup(~y~)"]
+        );
+        assert_eq!(to_strings("/a/b/ c/"), vec!["markup(/a/b/)", " c/"]);
+        assert_eq!(to_strings("*a *b* c*"), vec!["markup(*a *b*)", " c*"]);
+        // links in link descriptions are kept as text
+        assert_eq!(to_strings("[[a][see [[b]]]]"), vec!["[[a][see [[b]]", "]]"]);
+    }
+
+    #[test]
+    fn text_markup() {
+        let markup: TextMarkup = "/see [[#a]]/".parse().unwrap();
+        match &markup.kind {
+            TextMarkupKind::Italic(content) => assert_eq!(content.len(), 2),
+            kind => panic!("expected italic text, got {:?}", kind),
+        }
+        assert_eq!(
+            "~a*b~".parse::<TextMarkup>().map(|markup| markup.kind),
+            Ok(TextMarkupKind::Code("a*b".to_string()))
+        );
+        assert!("*a* b*".parse::<TextMarkup>().is_err());
+        assert!("a".parse::<TextMarkup>().is_err());
+
+        let title = parse_title("Project [1/3] [%] [a/b] [50%]", 0);
+        let cookies: Vec<_> = title
+            .iter()
+            .filter_map(|object| match object {
+                StandardSetNoLineBreak::StatisticsCookie(cookie) => Some(&cookie.cookie),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            cookies,
+            vec![
+                &CookieKind::Number(Some(1), Some(3)),
+                &CookieKind::Percent(None),
+                &CookieKind::Percent(Some(50)),
+            ]
+        );
+
+        let title = parse_title("TODO *important* call\\\\", 0);
+        assert_eq!(title.len(), 3);
+        assert_eq!(title.to_string(), "TODO *important* call\\\\");
+    }
+
+    #[test]
+    fn line_breaks() {
+        let objects = parse_objects("a\\\\  \nb\\\\");
+        assert_eq!(
+            objects,
+            vec![
+                StandardSet::RawString("a".to_string()),
+                StandardSet::LineBreak(LineBreak { spaces: 2 }),
+                StandardSet::RawString("b".to_string()),
+                StandardSet::LineBreak(LineBreak { spaces: 0 }),
+            ]
+        );
+        assert_eq!(
+            objects.iter().map(ToString::to_string).collect::<String>(),
+            "a\\\\  \nb\\\\\n"
+        );
+        // not at the end of the line, escaped or in a table cell
+        assert_eq!(parse_objects("a \\\\ b").len(), 1);
+        assert_eq!(parse_objects("a\\\\\\").len(), 1);
+        assert_eq!(parse_objects("*a\\\\*").len(), 1);
+        assert_eq!(parse_table_cell("a\\\\").content.value().len(), 1);
+    }
+
+    /// Parses a link and checks that it is written back unchanged.
+    fn link(s: &str) -> LinkFormat {
+        let link: Link = s.parse().unwrap();
+        assert_eq!(link.to_string(), s);
+        link.link
+    }
+
+    fn bracket_path(s: &str) -> (LinkPath, Option<SearchOption>) {
+        match link(s) {
+            LinkFormat::Bracket(path, search_option, None) => (path, search_option),
+            link => panic!(
+                "expected a bracket link without description, got {:?}",
+                link
+            ),
+        }
+    }
+
+    #[test]
+    fn bracket_links() {
+        match link("[[https://orgmode.org][*the* site]]") {
+            LinkFormat::Bracket(path, None, Some(description)) => {
+                assert_eq!(
+                    path,
+                    LinkPath::Protocol {
+                        protocol: "https".to_string(),
+                        path: "//orgmode.org".to_string(),
+                    }
+                );
+                assert_eq!(description.len(), 2);
+            }
+            link => panic!("expected a description, got {:?}", link),
+        }
+        assert!("[[a]".parse::<Link>().is_err());
+        assert!("[[a\nb]]".parse::<Link>().is_err());
+    }
+
+    #[test]
+    fn angle_links() {
+        assert_eq!(
+            link("<mailto:a@b.c>"),
+            LinkFormat::Angle("mailto:a@b.c".to_string())
+        );
+        assert_eq!(
+            link("<https://orgmode.org/a b>"),
+            LinkFormat::Angle("https://orgmode.org/a b".to_string())
+        );
+        // unknown link types and empty paths
+        assert!("<foo:bar>".parse::<Link>().is_err());
+        assert!("<https:>".parse::<Link>().is_err());
+    }
+
+    #[test]
+    fn plain_links() {
+        assert_eq!(
+            link("https://orgmode.org/manual/"),
+            LinkFormat::Plain("https://orgmode.org/manual/".to_string())
+        );
+        assert!("https://a b".parse::<Link>().is_err());
+        assert!("foo:bar".parse::<Link>().is_err());
+
+        let objects = parse_objects("(see https://orgmode.org/manual/, xhttp://a).");
+        let links: Vec<_> = objects
+            .iter()
+            .filter_map(|object| match object {
+                StandardSet::Link(link) => Some(link.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(links, vec!["https://orgmode.org/manual/"]);
+    }
+
+    /// Radio links are found by [`link_radio_targets`] after the whole document is parsed
+    /// (see the tests of `radio.rs`).
+    ///
+    /// [`link_radio_targets`]: `crate::parsing::link_radio_targets`
+    #[test]
+    fn radio_links_are_not_objects() {
+        let objects = parse_objects("<<<Org>>> Org");
+        assert_eq!(objects[1], StandardSet::RawString(" Org".to_string()));
+        assert!("Org".parse::<Link>().is_err());
+    }
+
+    #[test]
+    fn file_link_paths() {
+        assert_eq!(
+            bracket_path("[[file:notes.org]]"),
+            (LinkPath::File("notes.org".to_string()), None)
+        );
+        for path in &["/notes.org", "./notes.org", "~/notes.org"] {
+            assert_eq!(
+                parse_link_path(path),
+                (LinkPath::File(path.to_string()), None)
+            );
+        }
+    }
+
+    #[test]
+    fn id_link_path() {
+        assert_eq!(
+            bracket_path("[[id:abc-123]]"),
+            (LinkPath::Id("abc-123".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn custom_id_link_path() {
+        assert_eq!(
+            bracket_path("[[#intro]]"),
+            (LinkPath::CustomId("intro".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn coderef_link_path() {
+        assert_eq!(
+            bracket_path("[[(ref)]]"),
+            (LinkPath::CodeRef("ref".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn fuzzy_link_path() {
+        assert_eq!(
+            bracket_path("[[Some heading]]"),
+            (LinkPath::Fuzzy("Some heading".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn headline_search_option() {
+        assert_eq!(
+            bracket_path("[[file:x::*heading]]"),
+            (
+                LinkPath::File("x".to_string()),
+                Some(SearchOption::Headlines("heading".to_string()))
+            )
+        );
+    }
+
+    #[test]
+    fn custom_id_search_option() {
+        assert_eq!(
+            bracket_path("[[file:x::#custom]]").1,
+            Some(SearchOption::CustomId("custom".to_string()))
+        );
+    }
+
+    #[test]
+    fn regex_search_option() {
+        assert_eq!(
+            bracket_path("[[file:x::/regex/]]").1,
+            Some(SearchOption::Regex("regex".to_string()))
+        );
+    }
+
+    #[test]
+    fn line_search_option() {
+        assert_eq!(
+            bracket_path("[[file:x::123]]").1,
+            Some(SearchOption::Line(123))
+        );
+    }
+
+    #[test]
+    fn target_search_option() {
+        assert_eq!(
+            bracket_path("[[file:x::some words]]").1,
+            Some(SearchOption::Target("some words".to_string()))
+        );
+    }
+
+    fn footnote_reference(s: &str) -> FootnoteReferenceKind {
+        s.parse::<FootnoteReference>().unwrap().kind
+    }
+
+    #[test]
+    fn normal_footnote_reference() {
+        assert_eq!(
+            footnote_reference("[fn:1]"),
+            FootnoteReferenceKind::Normal {
+                label: "1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn inline_footnote_reference() {
+        match footnote_reference("[fn:note:see [[#x][x]]]") {
+            FootnoteReferenceKind::Inline { label, definition } => {
+                assert_eq!(label, "note");
+                assert_eq!(definition.len(), 2);
+            }
+            kind => panic!("expected an inline footnote, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn anonymous_footnote_reference() {
+        match footnote_reference("[fn::*anonymous*]") {
+            FootnoteReferenceKind::Anonymous { definition } => assert_eq!(definition.len(), 1),
+            kind => panic!("expected an anonymous footnote, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn footnote_references_in_text() {
+        let s = "a[fn:1] b[fn:note:see [[#x][x]]] c[fn::*anonymous*].";
+        let objects = parse_objects(s);
+        let references = objects
+            .iter()
+            .filter(|object| matches!(object, StandardSet::FootnoteReference(_)));
+        assert_eq!(references.count(), 3);
+        assert_eq!(
+            objects.iter().map(ToString::to_string).collect::<String>(),
+            s
+        );
+    }
+
+    #[test]
+    fn invalid_footnote_references() {
+        // unbalanced brackets and invalid labels
+        assert_eq!(parse_objects("[fn:a:[b] [fn:a b]").len(), 1);
+        assert!("[fn:]".parse::<FootnoteReference>().is_err());
+        assert_eq!(
+            "[fn:x-1]"
+                .parse::<FootnoteReference>()
+                .map(|r| r.to_string()),
+            Ok("[fn:x-1]".to_string())
+        );
+    }
+
+    #[test]
+    fn targets() {
+        let objects = parse_objects_at("a <<here>> and <<<*My* Target>>>", 10);
+        assert_eq!(
+            objects.iter().nth(1),
+            Some(&StandardSet::Target(Target {
+                target: "here".to_string()
+            }))
+        );
+        match objects.iter().nth(3) {
+            Some(StandardSet::RadioTarget(radio)) => {
+                assert_eq!(radio.target.len(), 2);
+                match radio.target.iter().next() {
+                    Some(RadioTargetSetOfObjects::TextMarkup(_)) => {}
+                    object => panic!("expected text markup, got {:?}", object),
+                }
+                assert_eq!(
+                    radio
+                        .target
+                        .iter_spanned()
+                        .nth(1)
+                        .and_then(|(_, span)| span),
+                    Some(&Span::new(32, 39))
+                );
+            }
+            object => panic!("expected a radio target, got {:?}", object),
+        }
+        assert_eq!(objects.to_string(), "a <<here>> and <<<*My* Target>>>");
+        // whitespace at the start or end, forbidden characters and unclosed targets
+        assert_eq!(
+            parse_objects("<< a>> <<a >> <<a<b>> <<a\nb>> <<a>").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn table_row() {
+        let row =
+            parse_table_row("|  [[#a]]  | <2018-10-12 Fri>--<2018-10-13 Sat> | *x* y |").unwrap();
+        assert_eq!(
+            row.to_string(),
+            "| [[#a]] | <2018-10-12 Fri>--<2018-10-13 Sat> | *x* y |"
+        );
+        match row.kind {
+            TableRowKind::Normal(cells) => {
+                let cells = cells.value();
+                assert_eq!(cells.len(), 3);
+                match cells[1].content.value().as_slice() {
+                    [TableCellSetOfObjects::Timestamp(_)] => {}
+                    content => panic!("expected a timestamp, got {:?}", content),
+                }
+                assert_eq!(cells[2].content.value().len(), 2);
+            }
+            TableRowKind::Rule => panic!("expected a normal row"),
+        }
+        assert_eq!(
+            parse_table_row("|---+---|").map(|row| row.kind),
+            Some(TableRowKind::Rule)
+        );
+        assert_eq!(parse_table_row("no table"), None);
+    }
+}
@@ -0,0 +1,280 @@
+//! Linking the text matched by radio targets (like org mode does for the whole buffer).
+//!
+//! A radio target (`<<<My Target>>>`) turns every occurrence of its text in the document into a
+//! [radio link](`LinkFormat::Radio`). The text is matched ignoring case and with any whitespace
+//! between its words. It can't be directly next to a letter or digit. If several targets match at
+//! the same position the longest one is used.
+//!
+//! Only raw strings are searched. So the text is not linked in verbatim text, link descriptions
+//! or inside radio targets, and it can't span several objects (e.g. partly bold text).
+
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, TableContent, TableRowKind};
+use crate::types::objects::{FootnoteReference, FootnoteReferenceKind, Link, LinkFormat};
+use crate::types::objects::{TextMarkup, TextMarkupKind};
+use crate::types::visit::{self, ObjectRefMut, VisitorMut};
+use crate::types::{AsRawString, ElementSet, SecondaryString};
+use itertools::Itertools;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::mem;
+use std::ops::Range;
+
+/// Turns the text matched by the radio targets of the document into radio links.
+///
+/// This is done by [`parse_document_with_config`]. It only has to be called again after the
+/// radio targets of a document are changed.
+///
+/// [`parse_document_with_config`]: `super::parse_document_with_config`
+pub fn link_radio_targets(document: &mut Document) {
+    let index = document.index();
+    let mut targets = index
+        .radio_targets
+        .iter()
+        .map(|target| {
+            target
+                .target
+                .to_string()
+                .split_whitespace()
+                .map(regex::escape)
+                .join(r"\s+")
+        })
+        .filter(|target| !target.is_empty())
+        .unique()
+        .collect::<Vec<_>>();
+    targets.sort_by_key(|target| Reverse(target.len()));
+    if targets.is_empty() {
+        return;
+    }
+    let regex = format!(
+        r"(?i)(?:^|[^\pL\pN])({})(?:[^\pL\pN]|$)",
+        targets.iter().join("|")
+    );
+    let mut links = RadioLinks {
+        regex: Regex::new(&regex).expect("radio target regex is valid"),
+    };
+    visit::walk_mut(document, &mut links);
+}
+
+/// Turns the text matched by the regex (in the first group) into radio links.
+struct RadioLinks {
+    regex: Regex,
+}
+
+impl RadioLinks {
+    /// Splits the raw string into raw strings and radio links with their byte ranges.
+    ///
+    /// Returns nothing if there are no radio links in the string.
+    fn split<T: From<String> + From<Link>>(&self, s: &str) -> Vec<(Range<usize>, T)> {
+        let mut parts = Vec::new();
+        let mut end = 0;
+        while let Some(captures) = self.regex.captures(&s[end..]) {
+            let found = captures.get(1).unwrap();
+            let range = end + found.start()..end + found.end();
+            if range.start > end {
+                parts.push((end..range.start, T::from(s[end..range.start].to_string())));
+            }
+            let link = Link {
+                link: LinkFormat::Radio(s[range.clone()].to_string()),
+            };
+            end = range.end;
+            parts.push((range, T::from(link)));
+        }
+        if !parts.is_empty() && end < s.len() {
+            parts.push((end..s.len(), T::from(s[end..].to_string())));
+        }
+        parts
+    }
+
+    fn objects<T>(&self, objects: &mut SecondaryString<T>)
+    where
+        T: AsRawString + From<String> + From<Link>,
+    {
+        objects.split_raw_strings(|s| self.split(s));
+    }
+
+    /// Like [`objects`](`RadioLinks::objects`) for objects without spans.
+    fn vec<T>(&self, objects: &mut Vec<T>)
+    where
+        T: AsRawString + From<String> + From<Link>,
+    {
+        *objects = mem::take(objects)
+            .into_iter()
+            .flat_map(|object| {
+                let parts = object
+                    .as_raw_string()
+                    .map(|s| self.split(s))
+                    .unwrap_or_default();
+                if parts.is_empty() {
+                    vec![object]
+                } else {
+                    parts.into_iter().map(|(_, part)| part).collect()
+                }
+            })
+            .collect();
+    }
+}
+
+impl VisitorMut for RadioLinks {
+    fn visit_headline(&mut self, headline: &mut Headline) {
+        if let Some(title) = &mut headline.title {
+            self.objects(title);
+        }
+        visit::walk_headline_mut(self, headline);
+    }
+
+    fn visit_element(&mut self, element: &mut ElementSet) {
+        match element {
+            ElementSet::Inlinetask(e) => {
+                if let Some(title) = &mut e.title {
+                    self.objects(title);
+                }
+            }
+            ElementSet::Paragraph(e) => self.objects(&mut e.content),
+            ElementSet::Table(e) => {
+                for row in e.content_mut() {
+                    if let TableContent::Org(row) = row {
+                        if let TableRowKind::Normal(cells) = &mut row.kind {
+                            for cell in cells.get_mut_value() {
+                                self.vec(cell.content.get_mut_value());
+                            }
+                        }
+                    }
+                }
+            }
+            ElementSet::VerseBlock(e) => self.vec(e.content_mut()),
+            _ => {}
+        }
+        visit::walk_element_mut(self, element);
+    }
+
+    fn visit_object(&mut self, mut object: ObjectRefMut<'_>) {
+        match &mut object {
+            // radio targets can't contain links
+            ObjectRefMut::RadioTarget(_) => return,
+            ObjectRefMut::Subscript(script) => self.objects(&mut script.content),
+            ObjectRefMut::Superscript(script) => self.objects(&mut script.content),
+            _ => {}
+        }
+        visit::walk_object_mut(self, object);
+    }
+
+    // link descriptions can't contain radio links
+    fn visit_link(&mut self, _link: &mut Link) {}
+
+    fn visit_text_markup(&mut self, markup: &mut TextMarkup) {
+        match &mut markup.kind {
+            TextMarkupKind::Bold(content)
+            | TextMarkupKind::Italic(content)
+            | TextMarkupKind::Underline(content)
+            | TextMarkupKind::StrikeThrough(content) => self.objects(content),
+            TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => {}
+        }
+        visit::walk_text_markup_mut(self, markup);
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &mut FootnoteReference) {
+        match &mut reference.kind {
+            FootnoteReferenceKind::Inline { definition, .. }
+            | FootnoteReferenceKind::Anonymous { definition } => self.objects(definition),
+            FootnoteReferenceKind::Normal { .. } => {}
+        }
+        visit::walk_footnote_reference_mut(self, reference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Paragraph;
+    use crate::types::greater_elements::Table;
+    use crate::types::{Parent, Span, StandardSet};
+
+    const TEXT: &str = "<<<My Target>>> and <<<target>>>. My  Target, targets, =target= *target*\n\
+         | a target |\n\
+         * About my\ttarget\n";
+
+    fn radio(s: &str) -> Link {
+        Link {
+            link: LinkFormat::Radio(s.to_string()),
+        }
+    }
+
+    /// Returns the paragraph and the table of the preface.
+    fn preface(document: &Document) -> (&Paragraph, &Table) {
+        let preface = document.preface.as_ref().unwrap().content().unwrap();
+        match preface.value().as_slice() {
+            [ElementSet::Paragraph(paragraph), ElementSet::Table(table)] => (paragraph, table),
+            elements => panic!("expected a paragraph and a table, got {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn link_title() {
+        let document = Document::parse(TEXT).unwrap();
+        let title: Vec<_> = document.headlines[0]
+            .title
+            .as_ref()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(
+            title,
+            [&"About ".to_string().into(), &radio("my\ttarget").into()]
+        );
+    }
+
+    #[test]
+    fn link_paragraph() {
+        let document = Document::parse(TEXT).unwrap();
+        let (paragraph, _) = preface(&document);
+        let objects: Vec<_> = paragraph.content.iter().skip(3).take(3).cloned().collect();
+        assert_eq!(
+            objects,
+            [
+                ". ".to_string().into(),
+                radio("My  Target").into(),
+                ", targets, ".to_string().into(),
+            ]
+        );
+        // the spans of the new objects are in the file
+        let (_, span) = paragraph.content.iter_spanned().nth(4).unwrap();
+        let start = "<<<My Target>>> and <<<target>>>. ".len();
+        assert_eq!(span, Some(&Span::new(start, start + "My  Target".len())));
+    }
+
+    #[test]
+    fn link_markup() {
+        let document = Document::parse(TEXT).unwrap();
+        let (paragraph, _) = preface(&document);
+        match paragraph.content.iter().nth(8) {
+            Some(StandardSet::TextMarkup(markup)) => assert_eq!(
+                markup.kind,
+                TextMarkupKind::Bold(SecondaryString::with_one(radio("target").into()))
+            ),
+            object => panic!("expected bold text, got {:?}", object),
+        }
+    }
+
+    #[test]
+    fn link_table_cell() {
+        let document = Document::parse(TEXT).unwrap();
+        let (_, table) = preface(&document);
+        let cell = match &table.content().unwrap().value()[0] {
+            TableContent::Org(row) => match &row.kind {
+                TableRowKind::Normal(cells) => cells.value()[0].clone(),
+                TableRowKind::Rule => panic!("expected a normal row"),
+            },
+            TableContent::TableEl(_) => panic!("expected an org table"),
+        };
+        assert_eq!(
+            cell.content.value().as_slice(),
+            ["a ".to_string().into(), radio("target").into()]
+        );
+    }
+
+    #[test]
+    fn write_radio_links() {
+        assert_eq!(Document::parse(TEXT).unwrap().to_string(), TEXT);
+    }
+}
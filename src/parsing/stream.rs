@@ -0,0 +1,186 @@
+//! Splitting a file into its top level headlines without parsing it.
+//!
+//! [`HeadlineIter`] only looks for headline lines (lines starting with stars followed by
+//! whitespace or the end of the line). Each subtree is parsed on demand with
+//! [`RawHeadline::parse`], so huge files (e.g. journals) can be processed one headline at a time.
+
+use super::{parse_document_with_config, with_in_buffer_settings, ParseConfig, ParseError};
+use crate::types::document::Document;
+use crate::types::greater_elements::Headline;
+
+/// An iterator over the top level headlines of a string.
+///
+/// A top level headline ends at the next headline with the same or a lower level. Like when
+/// parsing a whole document headlines with a lower level than the first headline (e.g. `** a`
+/// followed by `* b`) are also top level headlines.
+#[derive(Debug, Clone)]
+pub struct HeadlineIter<'a> {
+    s: &'a str,
+    /// The byte offset of the first headline.
+    first: usize,
+    /// The byte offset of the next headline.
+    position: usize,
+}
+
+/// A top level headline (and all its children) that is not parsed yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawHeadline<'a> {
+    /// The number of stars.
+    pub level: u32,
+    /// The byte offset of the headline in the string.
+    pub offset: usize,
+    /// The text of the headline and its children (including the final newline).
+    pub text: &'a str,
+}
+
+impl<'a> HeadlineIter<'a> {
+    pub fn new(s: &'a str) -> Self {
+        let first = line_starts(s, 0)
+            .find(|&start| headline_level(&s[start..]).is_some())
+            .unwrap_or(s.len());
+        HeadlineIter {
+            s,
+            first,
+            position: first,
+        }
+    }
+
+    /// Returns the text before the first headline (or `None` if there is none).
+    pub fn preface(&self) -> Option<&'a str> {
+        if self.first == 0 {
+            None
+        } else {
+            Some(&self.s[..self.first])
+        }
+    }
+
+    /// Applies the in-buffer settings of the preface (e.g. `#+TODO:`) to the configuration.
+    ///
+    /// The result should be used to parse the headlines like [`parse_document_with_config`]
+    /// does.
+    pub fn config(&self, config: &ParseConfig) -> Result<ParseConfig, ParseError> {
+        let preface = match self.preface() {
+            Some(preface) => parse_document_with_config(preface, config)?.preface,
+            None => None,
+        };
+        Ok(with_in_buffer_settings(config, preface.as_ref()))
+    }
+}
+
+impl<'a> Iterator for HeadlineIter<'a> {
+    type Item = RawHeadline<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.position;
+        let level = headline_level(self.s.get(start..)?)?;
+        let end = line_starts(self.s, start)
+            .skip(1)
+            .find(|&next| headline_level(&self.s[next..]).is_some_and(|next| next <= level))
+            .unwrap_or(self.s.len());
+        self.position = end;
+        Some(RawHeadline {
+            level,
+            offset: start,
+            text: &self.s[start..end],
+        })
+    }
+}
+
+impl<'a> RawHeadline<'a> {
+    /// Returns the headline line without the newline.
+    pub fn headline_line(&self) -> &'a str {
+        self.text.lines().next().unwrap_or("")
+    }
+
+    /// Returns the text between the headline line and the first child headline.
+    pub fn section(&self) -> &'a str {
+        let start = self
+            .text
+            .find('\n')
+            .map_or(self.text.len(), |index| index + 1);
+        let end = line_starts(self.text, start)
+            .find(|&next| headline_level(&self.text[next..]).is_some())
+            .unwrap_or(self.text.len());
+        &self.text[start..end]
+    }
+
+    /// Parses the headline and its children.
+    ///
+    /// The spans are relative to the start of the headline. Add [`offset`][RawHeadline::offset]
+    /// to get the position in the whole string.
+    ///
+    /// Fails if the headline is an inline task (see [`ParseConfig::inlinetask_min_level`]). These
+    /// are only split off in the preface.
+    pub fn parse(&self, config: &ParseConfig) -> Result<Headline, ParseError> {
+        parse_document_with_config(self.text, config).and_then(first_headline)
+    }
+}
+
+/// Returns the first headline of a document parsed from the text of a [`RawHeadline`].
+pub(crate) fn first_headline(document: Document) -> Result<Headline, ParseError> {
+    document
+        .headlines
+        .into_iter()
+        .next()
+        .ok_or(ParseError::StructuralError(
+            "the headline is an inline task",
+        ))
+}
+
+/// Returns the byte offsets of the starts of the lines from `start` on.
+fn line_starts(s: &str, start: usize) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(start)
+        .chain(
+            s[start..]
+                .match_indices('\n')
+                .map(move |(index, _)| start + index + 1),
+        )
+        .filter(move |&start| start < s.len())
+}
+
+/// Returns the level if the line is a headline.
+fn headline_level(line: &str) -> Option<u32> {
+    let stars = line.len() - line.trim_start_matches('*').len();
+    match line[stars..].chars().next() {
+        _ if stars == 0 => None,
+        None | Some(' ') | Some('\t') | Some('\n') | Some('\r') => Some(stars as u32),
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_document;
+    use crate::types::greater_elements::TodoKeyword;
+
+    #[test]
+    fn headlines() {
+        let s = "#+TODO: WAIT | DONE\n** WAIT early\n* One\ntext\n** Child\n* Two";
+        let mut headlines = HeadlineIter::new(s);
+        assert_eq!(headlines.preface(), Some("#+TODO: WAIT | DONE\n"));
+        let config = headlines.config(&ParseConfig::default()).unwrap();
+
+        let early = headlines.next().unwrap();
+        assert_eq!(early.level, 2);
+        assert_eq!(
+            early.parse(&config).unwrap().todo_keyword,
+            Some(TodoKeyword::Todo("WAIT".to_string()))
+        );
+        let one = headlines.next().unwrap();
+        assert_eq!(one.offset, s.find("* One").unwrap());
+        assert_eq!(one.text, "* One\ntext\n** Child\n");
+        assert_eq!(one.headline_line(), "* One");
+        assert_eq!(one.section(), "text\n");
+        assert_eq!(
+            one.parse(&config).unwrap().to_string(),
+            parse_document(s).unwrap().headlines[1].to_string()
+        );
+        let two = headlines.next().unwrap();
+        assert_eq!((two.text, two.section()), ("* Two", ""));
+        assert_eq!(headlines.next(), None);
+
+        assert_eq!(HeadlineIter::new("* a\n").preface(), None);
+        assert_eq!(HeadlineIter::new("*bold*\n").next(), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! The names the parser knows (block names, affiliated keywords and attributes).
+//!
+//! Editors can use [`syntax_metadata`] to complete `#+BEGIN_` lines and keywords (like the
+//! structure templates of org mode) with exactly the names this crate recognizes.
+
+use crate::export::{html, latex, text};
+
+/// The names of blocks with their own element (e.g. `SRC` for `#+BEGIN_SRC`).
+///
+/// Blocks with any other name are special blocks.
+pub const BLOCK_NAMES: &[&str] = &[
+    "CENTER", "COMMENT", "EXAMPLE", "EXPORT", "QUOTE", "SRC", "VERSE",
+];
+
+/// The affiliated keywords (except `#+ATTR_BACKEND:`, see [`BACKEND_ATTRIBUTES`]).
+pub const AFFILIATED_KEYWORDS: &[AffiliatedKeywordInfo] = &[
+    AffiliatedKeywordInfo {
+        name: "CAPTION",
+        aliases: &[],
+        optional_value: true,
+    },
+    AffiliatedKeywordInfo {
+        name: "HEADER",
+        aliases: &["HEADERS"],
+        optional_value: false,
+    },
+    AffiliatedKeywordInfo {
+        name: "NAME",
+        aliases: &["LABEL", "SRCNAME", "TBLNAME", "DATA", "RESNAME", "SOURCE"],
+        optional_value: false,
+    },
+    AffiliatedKeywordInfo {
+        name: "PLOT",
+        aliases: &[],
+        optional_value: false,
+    },
+    AffiliatedKeywordInfo {
+        name: "RESULTS",
+        aliases: &["RESULT"],
+        optional_value: true,
+    },
+];
+
+/// The attributes used by the exporters of each backend (e.g. `lang` for `#+ATTR_HTML: :lang`).
+pub const BACKEND_ATTRIBUTES: &[(&str, &[&str])] = &[
+    (text::BACKENDS[0], text::ATTRIBUTES),
+    (html::BACKENDS[0], html::ATTRIBUTES),
+    (latex::BACKENDS[0], latex::ATTRIBUTES),
+];
+
+/// An affiliated keyword like `#+CAPTION:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AffiliatedKeywordInfo {
+    /// The name in upper case.
+    pub name: &'static str,
+    /// The (deprecated) names with the same meaning. They are parsed but shouldn't be
+    /// suggested.
+    pub aliases: &'static [&'static str],
+    /// Whether the keyword can have an optional value (e.g. `#+CAPTION[short]: long`).
+    pub optional_value: bool,
+}
+
+impl AffiliatedKeywordInfo {
+    /// Returns the affiliated keyword with the name or alias `key` (case insensitive).
+    pub fn find(key: &str) -> Option<&'static AffiliatedKeywordInfo> {
+        AFFILIATED_KEYWORDS.iter().find(|info| {
+            info.name.eq_ignore_ascii_case(key)
+                || info
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(key))
+        })
+    }
+}
+
+/// The names the parser knows (see [`syntax_metadata`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxMetadata {
+    /// See [`BLOCK_NAMES`].
+    pub block_names: &'static [&'static str],
+    /// See [`AFFILIATED_KEYWORDS`].
+    pub affiliated_keywords: &'static [AffiliatedKeywordInfo],
+    /// See [`BACKEND_ATTRIBUTES`].
+    pub backend_attributes: &'static [(&'static str, &'static [&'static str])],
+}
+
+impl SyntaxMetadata {
+    /// Returns the attributes used by the exporter of the backend (case insensitive).
+    pub fn attributes(&self, backend: &str) -> &'static [&'static str] {
+        self.backend_attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(backend))
+            .map_or(&[], |(_, attributes)| attributes)
+    }
+
+    /// Returns the keys of all affiliated keywords to suggest (e.g. `CAPTION` and `ATTR_HTML`).
+    ///
+    /// Aliases are not included.
+    pub fn affiliated_keyword_keys(&self) -> Vec<String> {
+        self.affiliated_keywords
+            .iter()
+            .map(|info| info.name.to_string())
+            .chain(
+                self.backend_attributes
+                    .iter()
+                    .map(|(backend, _)| format!("ATTR_{}", backend.to_ascii_uppercase())),
+            )
+            .collect()
+    }
+}
+
+/// Returns the block names, affiliated keywords and attributes known to the parser and the
+/// exporters.
+pub fn syntax_metadata() -> SyntaxMetadata {
+    SyntaxMetadata {
+        block_names: BLOCK_NAMES,
+        affiliated_keywords: AFFILIATED_KEYWORDS,
+        backend_attributes: BACKEND_ATTRIBUTES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata() {
+        let metadata = syntax_metadata();
+        assert!(metadata.block_names.contains(&"SRC"));
+        assert_eq!(
+            metadata.affiliated_keyword_keys(),
+            vec![
+                "CAPTION",
+                "HEADER",
+                "NAME",
+                "PLOT",
+                "RESULTS",
+                "ATTR_ASCII",
+                "ATTR_HTML",
+                "ATTR_LATEX"
+            ]
+        );
+        assert_eq!(metadata.attributes("HTML"), &["lang"]);
+        assert!(metadata.attributes("odt").is_empty());
+        assert_eq!(AffiliatedKeywordInfo::find("tblname").unwrap().name, "NAME");
+        assert!(
+            AffiliatedKeywordInfo::find("results")
+                .unwrap()
+                .optional_value
+        );
+        assert_eq!(AffiliatedKeywordInfo::find("AUTHOR"), None);
+    }
+}
@@ -0,0 +1,20 @@
+//! The commonly used types and traits.
+//!
+//! ```ignore
+//! use rust_orgmode::prelude::*;
+//! ```
+//!
+//! The traits are needed to call the shared methods of elements and objects (e.g.
+//! [`Parent::content`]). Everything else can also be imported from its module.
+
+pub use crate::parsing::{parse_document, parse_document_with_config, ParseConfig, ParseError};
+pub use crate::types::document::Document;
+pub use crate::types::greater_elements::{
+    Headline, HeadlineContentSet, Item, PlainList, Section, TodoKeyword,
+};
+pub use crate::types::objects::{Link, Timestamp};
+pub use crate::types::validation::Diagnostic;
+pub use crate::types::{
+    AsRawString, Element, ElementSet, GreaterElement, HasAffiliatedKeywords, Object, Parent,
+    SecondaryString, Span, Spanned, StandardSet, StandardSetNoLineBreak,
+};
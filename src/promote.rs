@@ -0,0 +1,212 @@
+//! Changing the level of headlines (like `org-promote` and `org-demote`).
+//!
+//! Promoting or demoting only the headline changes the level of the headline but not of its
+//! children. Like in the org file the tree then changes: A promoted headline becomes a sibling
+//! of its parent and its following siblings become its children. A demoted headline loses its
+//! children to the previous headline of their level.
+//!
+//! ```text
+//! * A               * A
+//! ** B     --->     * B
+//! *** C             *** C
+//! ** D              ** D
+//! ```
+//!
+//! The `_subtree` variants change the levels of the children too (like
+//! `org-promote-subtree`).
+//!
+//! The content of the sections is not re-indented because it is always written without
+//! indentation (like with `org-adapt-indentation` set to `nil`).
+
+use crate::parsing::{nest_headlines, parse_document, ParseError};
+use crate::refile::has_spans;
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, HeadlineContentSet};
+use crate::types::node_id::NodeId;
+use std::fmt;
+
+/// The error returned when a headline can't be promoted or demoted.
+#[derive(Debug)]
+pub enum PromoteError {
+    /// There is no headline with the id.
+    NotFound(NodeId),
+    /// A top level headline can't be promoted.
+    TopLevel,
+    /// The document can't be parsed again to compute the spans.
+    Parse(ParseError),
+}
+
+impl fmt::Display for PromoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromoteError::NotFound(id) => write!(f, "there is no headline {}", id),
+            PromoteError::TopLevel => write!(f, "can't promote a top level headline"),
+            PromoteError::Parse(error) => write!(
+                f,
+                "can't parse the changed document (expected {})",
+                error.expected()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PromoteError {}
+
+/// Decreases the level of the headline by one.
+///
+/// Only the headline is changed if `subtree` is `false` (see the [module documentation]). The
+/// spans are recomputed by parsing the changed document again with the default configuration.
+/// The document is not changed if an error is returned.
+///
+/// [module documentation]: `self`
+pub fn promote(
+    document: &mut Document,
+    headline: &NodeId,
+    subtree: bool,
+) -> Result<(), PromoteError> {
+    change_level(document, headline, subtree, -1)
+}
+
+/// Increases the level of the headline by one (see [`promote`]).
+pub fn demote(
+    document: &mut Document,
+    headline: &NodeId,
+    subtree: bool,
+) -> Result<(), PromoteError> {
+    change_level(document, headline, subtree, 1)
+}
+
+fn change_level(
+    document: &mut Document,
+    id: &NodeId,
+    subtree: bool,
+    offset: i64,
+) -> Result<(), PromoteError> {
+    let headline = id
+        .resolve(document)
+        .ok_or_else(|| PromoteError::NotFound(id.clone()))?;
+    if offset < 0 && headline.level <= 1 {
+        return Err(PromoteError::TopLevel);
+    }
+    let mut index = 0;
+    let (start, count) = document
+        .headlines
+        .iter()
+        .find_map(|top| find(top, headline, &mut index))
+        .expect("the resolved headline is in the document");
+    let end = if subtree { start + count } else { start + 1 };
+
+    let mut flat = Vec::new();
+    flatten(document.headlines.clone(), &mut flat);
+    for headline in &mut flat[start..end] {
+        headline.level = (i64::from(headline.level) + offset) as u32;
+    }
+    let mut changed = Document {
+        preface: document.preface.clone(),
+        headlines: nest_headlines(flat),
+    };
+    if has_spans(document) {
+        changed = parse_document(&changed.to_string()).map_err(PromoteError::Parse)?;
+    }
+    *document = changed;
+    Ok(())
+}
+
+/// Returns the index of `headline` in document order (counting from `index`) and the number of
+/// headlines in its subtree if it is `current` or one of its descendants.
+fn find(current: &Headline, headline: &Headline, index: &mut usize) -> Option<(usize, usize)> {
+    let start = *index;
+    *index += 1;
+    let mut found = None;
+    for child in current.children() {
+        found = found.or_else(|| find(child, headline, index));
+    }
+    if std::ptr::eq(current, headline) {
+        Some((start, *index - start))
+    } else {
+        found
+    }
+}
+
+/// Adds the headlines and their descendants in document order without their children.
+fn flatten(headlines: Vec<Headline>, flat: &mut Vec<Headline>) {
+    for mut headline in headlines {
+        let mut children = Vec::new();
+        if let Some(content) = &mut headline.content {
+            let (sections, headlines) =
+                content
+                    .get_mut_value()
+                    .drain(..)
+                    .partition(|content| match content {
+                        HeadlineContentSet::Section(_) => true,
+                        HeadlineContentSet::Headline(_) => false,
+                    });
+            *content.get_mut_value() = sections;
+            children.extend(headlines.into_iter().map(|content| match content {
+                HeadlineContentSet::Headline(child) => *child,
+                HeadlineContentSet::Section(_) => unreachable!("the sections were split off"),
+            }));
+            if content.value().is_empty() {
+                headline.content = None;
+            }
+        }
+        flat.push(headline);
+        flatten(children, flat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::node_id::OutlineStep;
+
+    fn outline(titles: &[&str]) -> NodeId {
+        let steps = titles.iter().map(|title| OutlineStep {
+            title: title.to_string(),
+            occurrence: 0,
+        });
+        NodeId::Outline(steps.collect())
+    }
+
+    const TEXT: &str = "* A\n** B\nBody\n*** C\n** D\n";
+
+    #[test]
+    fn promote_headline() {
+        let mut document = Document::parse(TEXT).unwrap();
+        document.promote(&outline(&["A", "B"])).unwrap();
+        let expected = "* A\n* B\nBody\n*** C\n** D\n";
+        assert_eq!(document.to_string(), expected);
+        assert_eq!(document, Document::parse(expected).unwrap());
+        assert_eq!(document.headlines.len(), 2);
+    }
+
+    #[test]
+    fn demote_headline() {
+        let mut document = Document::parse("* A\n* B\nBody\n*** C\n** D\n").unwrap();
+        document.demote(&outline(&["B"])).unwrap();
+        assert_eq!(document.to_string(), TEXT);
+    }
+
+    #[test]
+    fn promote_subtree() {
+        let mut document = Document::parse(TEXT).unwrap();
+        document.promote_subtree(&outline(&["A", "B"])).unwrap();
+        assert_eq!(document.to_string(), "* A\n* B\nBody\n** C\n** D\n");
+    }
+
+    #[test]
+    fn demote_subtree() {
+        let mut document = Document::parse("* A\n* B\nBody\n** C\n** D\n").unwrap();
+        document.demote_subtree(&outline(&["B"])).unwrap();
+        assert_eq!(document.to_string(), "* A\n** B\nBody\n*** C\n*** D\n");
+    }
+
+    #[test]
+    fn promote_top_level() {
+        let mut document = Document::parse(TEXT).unwrap();
+        match document.promote(&outline(&["A"])) {
+            Err(PromoteError::TopLevel) => {}
+            result => panic!("expected an error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Moving subtrees below other headlines (like `org-refile`).
+//!
+//! [`refile`] removes a headline with all its children from its place and inserts it as a
+//! child of the target headline. The levels of the moved headlines are changed so the moved
+//! headline is one level below the target.
+
+use crate::parsing::{parse_document_with_config, ParseConfig, ParseError};
+use crate::types::document::Document;
+use crate::types::greater_elements::{Headline, HeadlineContentSet};
+use crate::types::node_id::NodeId;
+use crate::types::{Parent, Spanned};
+use std::fmt;
+
+/// The options of [`refile`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RefileOptions {
+    pub position: RefilePosition,
+    /// The configuration used to recompute the spans (see [`refile`]).
+    pub config: ParseConfig,
+}
+
+/// Where the moved headline is inserted among the children of the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RefilePosition {
+    /// Before the other children (like with `org-reverse-note-order`).
+    First,
+    /// After the other children.
+    #[default]
+    Last,
+}
+
+/// The error returned when a headline can't be refiled.
+#[derive(Debug)]
+pub enum RefileError {
+    /// There is no headline with the id.
+    NotFound(NodeId),
+    /// The target is the moved headline or one of its descendants.
+    IntoItself,
+    /// The document can't be parsed again to compute the spans.
+    Parse(ParseError),
+}
+
+impl fmt::Display for RefileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefileError::NotFound(id) => write!(f, "there is no headline {}", id),
+            RefileError::IntoItself => write!(f, "can't refile a headline below itself"),
+            RefileError::Parse(error) => write!(
+                f,
+                "can't parse the refiled document (expected {})",
+                error.expected()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RefileError {}
+
+/// Moves the headline with the id (and its children) below the target headline.
+///
+/// If the document has spans they are recomputed by parsing the serialized document again with
+/// [`RefileOptions::config`] (this should be the configuration the document was parsed with).
+/// The document is not changed if an error is returned.
+pub fn refile(
+    document: &mut Document,
+    headline: &NodeId,
+    target: &NodeId,
+    options: &RefileOptions,
+) -> Result<(), RefileError> {
+    let moved_path = path(document, headline)?;
+    let mut target_path = path(document, target)?;
+    if target_path.starts_with(&moved_path) {
+        return Err(RefileError::IntoItself);
+    }
+    // removing the headline moves its later siblings (and their descendants) forward
+    let depth = moved_path.len() - 1;
+    if target_path.len() > depth
+        && target_path[..depth] == moved_path[..depth]
+        && target_path[depth] > moved_path[depth]
+    {
+        target_path[depth] -= 1;
+    }
+
+    let mut refiled = document.clone();
+    let mut moved = remove(&mut refiled, &moved_path);
+    let target = headline_mut(&mut refiled, &target_path);
+    moved.set_level(target.level + 1);
+    let moved = HeadlineContentSet::Headline(Box::new(moved));
+    match options.position {
+        RefilePosition::Last => target.push_content(Some(moved)),
+        RefilePosition::First => {
+            let content = target
+                .content
+                .get_or_insert_with(|| Spanned::new(Vec::new()))
+                .get_mut_value();
+            let index = match content.first() {
+                Some(HeadlineContentSet::Section(_)) => 1,
+                _ => 0,
+            };
+            content.insert(index, moved);
+        }
+    }
+
+    if has_spans(document) {
+        refiled = parse_document_with_config(&refiled.to_string(), &options.config)
+            .map_err(RefileError::Parse)?;
+    }
+    *document = refiled;
+    Ok(())
+}
+
+/// Returns the indices leading to the headline: the index in [`Document::headlines`] followed
+/// by the indices in the content of the ancestors.
+fn path(document: &Document, id: &NodeId) -> Result<Vec<usize>, RefileError> {
+    let headline = id
+        .resolve(document)
+        .ok_or_else(|| RefileError::NotFound(id.clone()))?;
+    let mut path = Vec::new();
+    for (index, top) in document.headlines.iter().enumerate() {
+        path.push(index);
+        if find(top, headline, &mut path) {
+            return Ok(path);
+        }
+        path.pop();
+    }
+    unreachable!("the resolved headline is in the document")
+}
+
+/// Completes the path to `headline` if it is `current` or one of its descendants.
+fn find(current: &Headline, headline: &Headline, path: &mut Vec<usize>) -> bool {
+    if std::ptr::eq(current, headline) {
+        return true;
+    }
+    let content = current.content().map_or(&[][..], |content| content.value());
+    for (index, content) in content.iter().enumerate() {
+        if let HeadlineContentSet::Headline(child) = content {
+            path.push(index);
+            if find(child, headline, path) {
+                return true;
+            }
+            path.pop();
+        }
+    }
+    false
+}
+
+fn headline_mut<'a>(document: &'a mut Document, path: &[usize]) -> &'a mut Headline {
+    let mut headline = &mut document.headlines[path[0]];
+    for &index in &path[1..] {
+        headline = match &mut headline.content.as_mut().unwrap().get_mut_value()[index] {
+            HeadlineContentSet::Headline(child) => child,
+            HeadlineContentSet::Section(_) => unreachable!("the path only contains headlines"),
+        };
+    }
+    headline
+}
+
+fn remove(document: &mut Document, path: &[usize]) -> Headline {
+    let (&index, parent) = path.split_last().expect("the path is not empty");
+    if parent.is_empty() {
+        return document.headlines.remove(index);
+    }
+    let parent = headline_mut(document, parent);
+    let content = parent.content.as_mut().unwrap().get_mut_value();
+    match content.remove(index) {
+        HeadlineContentSet::Headline(headline) => *headline,
+        HeadlineContentSet::Section(_) => unreachable!("the path only contains headlines"),
+    }
+}
+
+/// Returns `true` if the document was parsed (and not built without spans).
+pub(crate) fn has_spans(document: &Document) -> bool {
+    let preface = document
+        .preface
+        .as_ref()
+        .and_then(|preface| preface.content())
+        .is_some_and(|content| content.span().is_some());
+    let headlines = document
+        .headlines
+        .iter()
+        .filter_map(|headline| headline.content())
+        .any(|content| content.span().is_some());
+    preface || headlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::node_id::OutlineStep;
+
+    const TEXT: &str = "* Inbox\n\
+                        ** Call Bob\n\
+                        Notes\n\
+                        *** Number\n\
+                        ** Other\n\
+                        * Projects\n\
+                        ** Website\n";
+
+    fn outline(titles: &[&str]) -> NodeId {
+        let steps = titles.iter().map(|title| OutlineStep {
+            title: title.to_string(),
+            occurrence: 0,
+        });
+        NodeId::Outline(steps.collect())
+    }
+
+    #[test]
+    fn refile_as_last_child() {
+        let mut document = Document::parse(TEXT).unwrap();
+        let call = outline(&["Inbox", "Call Bob"]);
+        document
+            .refile(&call, &outline(&["Projects"]), &RefileOptions::default())
+            .unwrap();
+        let expected = "* Inbox\n\
+                        ** Other\n\
+                        * Projects\n\
+                        ** Website\n\
+                        ** Call Bob\n\
+                        Notes\n\
+                        *** Number\n";
+        assert_eq!(document.to_string(), expected);
+        assert_eq!(document, Document::parse(expected).unwrap());
+    }
+
+    #[test]
+    fn refile_as_first_child() {
+        let mut document = Document::parse(TEXT).unwrap();
+        let call = outline(&["Inbox", "Call Bob"]);
+        let options = RefileOptions {
+            position: RefilePosition::First,
+            ..RefileOptions::default()
+        };
+        refile(&mut document, &call, &outline(&["Projects"]), &options).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "* Inbox\n\
+             ** Other\n\
+             * Projects\n\
+             ** Call Bob\n\
+             Notes\n\
+             *** Number\n\
+             ** Website\n"
+        );
+    }
+
+    #[test]
+    fn change_levels_of_subtree() {
+        let mut document = Document::parse(TEXT).unwrap();
+        let call = outline(&["Inbox", "Call Bob"]);
+        let website = outline(&["Projects", "Website"]);
+        refile(&mut document, &call, &website, &RefileOptions::default()).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "* Inbox\n\
+             ** Other\n\
+             * Projects\n\
+             ** Website\n\
+             *** Call Bob\n\
+             Notes\n\
+             **** Number\n"
+        );
+    }
+
+    #[test]
+    fn refile_into_itself() {
+        let mut document = Document::parse(TEXT).unwrap();
+        let inbox = outline(&["Inbox"]);
+        let number = outline(&["Inbox", "Call Bob", "Number"]);
+        match refile(&mut document, &inbox, &number, &RefileOptions::default()) {
+            Err(RefileError::IntoItself) => {}
+            result => panic!("expected an error, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Metadata of notes like [org-roam](https://www.orgroam.com/) uses it.
+//!
+//! A note is a file with a property drawer at its top:
+//!
+//! ```text
+//! :PROPERTIES:
+//! :ID: 2d5b1c3e
+//! :ROAM_ALIASES: "Org mode" org
+//! :ROAM_REFS: https://orgmode.org @dominik2010
+//! :END:
+//! #+TITLE: Org
+//! #+FILETAGS: :tool:
+//! ```
+//!
+//! [`NoteMeta`] collects this metadata of a file and [`workspace_json`] exports it for all files
+//! of a [`Workspace`] (e.g. for knowledge-graph tools).
+
+use crate::export::json_string;
+use crate::include::words;
+use crate::types::document::Document;
+use crate::workspace::Workspace;
+use itertools::Itertools;
+use std::fmt::Write;
+use std::path::Path;
+
+/// The metadata of a note (a file).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteMeta {
+    /// The value of the `#+TITLE:` keywords (see [`Keywords::title`]).
+    ///
+    /// [`Keywords::title`]: `crate::types::document::Keywords::title`
+    pub title: Option<String>,
+    /// The `ID` property of the file.
+    pub id: Option<String>,
+    /// See [`Document::file_tags`].
+    pub file_tags: Vec<String>,
+    /// The other titles of the note from the `ROAM_ALIASES` property.
+    pub aliases: Vec<String>,
+    /// The references (e.g. URLs or citation keys) from the `ROAM_REFS` property.
+    pub refs: Vec<String>,
+}
+
+impl NoteMeta {
+    /// Collects the metadata from the keywords and properties of the document.
+    ///
+    /// The values of `ROAM_ALIASES` and `ROAM_REFS` are split at whitespace. Double quotes
+    /// group words (e.g. `"Org mode"`).
+    pub fn new(document: &Document) -> Self {
+        let title = document
+            .keywords()
+            .title()
+            .filter(|title| !title.is_empty());
+        let list = |name| {
+            document
+                .property(name)
+                .map_or_else(Vec::new, |value| words(&value))
+        };
+        NoteMeta {
+            title,
+            id: document.property("ID"),
+            file_tags: document.file_tags(),
+            aliases: list("ROAM_ALIASES"),
+            refs: list("ROAM_REFS"),
+        }
+    }
+
+    /// Returns the metadata as a JSON object.
+    ///
+    /// Missing titles and ids are `null`, the lists are arrays of strings.
+    pub fn to_json(&self) -> String {
+        let string = |value: &Option<String>| match value {
+            Some(value) => json_string(value),
+            None => "null".to_string(),
+        };
+        let array = |values: &[String]| {
+            format!(
+                "[{}]",
+                values.iter().map(|value| json_string(value)).join(",")
+            )
+        };
+        format!(
+            "{{\"title\":{},\"id\":{},\"file_tags\":{},\"aliases\":{},\"refs\":{}}}",
+            string(&self.title),
+            string(&self.id),
+            array(&self.file_tags),
+            array(&self.aliases),
+            array(&self.refs)
+        )
+    }
+}
+
+/// Returns the metadata of all files of the workspace ordered by their path.
+pub fn notes(workspace: &Workspace) -> Vec<(&Path, NoteMeta)> {
+    workspace
+        .files()
+        .map(|(path, document)| (path, NoteMeta::new(document)))
+        .collect()
+}
+
+/// Exports the metadata of all files of the workspace to a JSON array.
+///
+/// Every file is an object with its `path` and the fields of [`NoteMeta::to_json`].
+pub fn workspace_json(workspace: &Workspace) -> String {
+    let mut json = String::from("[");
+    for (index, (path, meta)) in notes(workspace).into_iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        let meta = meta.to_json();
+        write!(
+            json,
+            "{{\"path\":{},{}",
+            json_string(&path.to_string_lossy()),
+            &meta[1..]
+        )
+        .unwrap();
+    }
+    json.push(']');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = ":PROPERTIES:\n\
+                        :ID: 2d5b1c3e\n\
+                        :ROAM_ALIASES: \"Org mode\" org\n\
+                        :ROAM_REFS: https://orgmode.org @dominik2010\n\
+                        :END:\n\
+                        #+TITLE: Org \"the\" tool\n\
+                        #+FILETAGS: :tool:\n\
+                        * Notes\n";
+
+    #[test]
+    fn note_metadata() {
+        let meta = NoteMeta::new(&Document::parse(NOTE).unwrap());
+        assert_eq!(
+            meta,
+            NoteMeta {
+                title: Some("Org \"the\" tool".to_string()),
+                id: Some("2d5b1c3e".to_string()),
+                file_tags: vec!["tool".to_string()],
+                aliases: vec!["Org mode".to_string(), "org".to_string()],
+                refs: vec![
+                    "https://orgmode.org".to_string(),
+                    "@dominik2010".to_string()
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn empty_note_metadata() {
+        let meta = NoteMeta::new(&Document::parse("* Empty\n").unwrap());
+        assert_eq!(meta, NoteMeta::default());
+        assert_eq!(
+            meta.to_json(),
+            "{\"title\":null,\"id\":null,\"file_tags\":[],\"aliases\":[],\"refs\":[]}"
+        );
+    }
+
+    #[test]
+    fn workspace_json_is_ordered_by_path() {
+        let mut workspace = Workspace::new();
+        workspace.insert("org.org", Document::parse(NOTE).unwrap());
+        workspace.insert("empty.org", Document::parse("* Empty\n").unwrap());
+        assert_eq!(
+            workspace_json(&workspace),
+            "[{\"path\":\"empty.org\",\"title\":null,\"id\":null,\"file_tags\":[],\
+             \"aliases\":[],\"refs\":[]},\
+             {\"path\":\"org.org\",\"title\":\"Org \\\"the\\\" tool\",\"id\":\"2d5b1c3e\",\
+             \"file_tags\":[\"tool\"],\"aliases\":[\"Org mode\",\"org\"],\
+             \"refs\":[\"https://orgmode.org\",\"@dominik2010\"]}]"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Clock and effort sums of subtrees (like the `CLOCKSUM` and `EFFORT` columns of column view).
+//!
+//! Summing the clocks of every subtree walks the whole document. [`RollupCache`] keeps the sums
+//! of all headlines and only sums a headline again after it was
+//! [invalidated](`RollupCache::invalidate`). Its ancestors are invalidated with it but only add
+//! up the cached sums of their children again, so an edit in a file with thousands of headlines
+//! only recomputes one path of the tree.
+//!
+//! Headlines are addressed by their path: the indices of the headline and its ancestors among
+//! their sibling headlines (e.g. `[1, 0]` is the first child of the second top level headline).
+
+use crate::clocking::{clock_minutes, clocks};
+use crate::types::document::Document;
+use crate::types::elements::OrgDuration;
+use crate::types::greater_elements::Headline;
+use std::convert::TryFrom;
+use std::ops::Add;
+
+/// The sums of a headline (or of a subtree).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Sums {
+    /// The minutes of the closed clocks (see [`clocks`]).
+    pub clocked_minutes: i64,
+    /// The minutes of the `EFFORT` properties (see [`effort_minutes`]).
+    pub effort_minutes: u32,
+}
+
+impl Sums {
+    /// Returns the sums of the headline without its children.
+    pub fn of(headline: &Headline) -> Self {
+        Sums {
+            clocked_minutes: clocks(headline)
+                .into_iter()
+                .map(|clock| clock_minutes(clock, None))
+                .sum(),
+            effort_minutes: effort_minutes(headline).unwrap_or(0),
+        }
+    }
+}
+
+impl Add for Sums {
+    type Output = Sums;
+
+    fn add(self, other: Sums) -> Sums {
+        Sums {
+            clocked_minutes: self.clocked_minutes + other.clocked_minutes,
+            effort_minutes: self.effort_minutes + other.effort_minutes,
+        }
+    }
+}
+
+/// The `EFFORT` property of the headline in minutes.
+///
+/// Efforts are written as `H:MM`, as plain minutes or with units (see [`OrgDuration`]).
+pub fn effort_minutes(headline: &Headline) -> Option<u32> {
+    let effort: OrgDuration = headline.property("EFFORT")?.parse().ok()?;
+    u32::try_from(effort.minutes()).ok()
+}
+
+/// The sums of the subtrees of a document.
+///
+/// Call [`update`](`RollupCache::update`) after the document changed and
+/// [`invalidate`](`RollupCache::invalidate`) the edited headlines before:
+///
+/// - after editing a headline (its properties or section) invalidate the headline
+/// - after adding, removing or moving headlines invalidate their parents (the empty path for top
+///   level headlines)
+#[derive(Debug, Clone, Default)]
+pub struct RollupCache {
+    headlines: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// The sums of the headline without its children (`None` if invalidated).
+    own: Option<Sums>,
+    /// The sums of the subtree (`None` if the headline or one of its descendants was
+    /// invalidated).
+    total: Option<Sums>,
+    children: Vec<Node>,
+}
+
+impl RollupCache {
+    pub fn new() -> Self {
+        RollupCache::default()
+    }
+
+    /// Sums the invalidated headlines (or all headlines on the first update) of the document.
+    pub fn update(&mut self, document: &Document) {
+        update_nodes(&mut self.headlines, &document.headlines);
+    }
+
+    /// Returns the sums of the subtree at the path.
+    ///
+    /// Returns `None` if there is no headline at the path or it was invalidated since the last
+    /// [`update`](`RollupCache::update`).
+    pub fn get(&self, path: &[usize]) -> Option<Sums> {
+        let (&last, ancestors) = path.split_last()?;
+        let mut nodes = &self.headlines;
+        for &index in ancestors {
+            nodes = &nodes.get(index)?.children;
+        }
+        nodes.get(last)?.total
+    }
+
+    /// Returns the sums of all headlines (or `None` if some were invalidated).
+    pub fn total(&self) -> Option<Sums> {
+        self.headlines
+            .iter()
+            .map(|node| node.total)
+            .try_fold(Sums::default(), |sum, total| Some(sum + total?))
+    }
+
+    /// Marks the headline at the path and its ancestors to be summed again.
+    ///
+    /// The children of the headline are summed again too. The empty path invalidates the whole
+    /// document.
+    pub fn invalidate(&mut self, path: &[usize]) {
+        let (&last, ancestors) = match path.split_last() {
+            Some(split) => split,
+            None => return self.clear(),
+        };
+        let mut nodes = &mut self.headlines;
+        for &index in ancestors {
+            match nodes.get_mut(index) {
+                Some(node) => {
+                    node.total = None;
+                    nodes = &mut node.children;
+                }
+                None => return,
+            }
+        }
+        if let Some(node) = nodes.get_mut(last) {
+            *node = Node::default();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.headlines.clear()
+    }
+}
+
+impl Node {
+    fn update(&mut self, headline: &Headline) {
+        if self.total.is_some() {
+            return;
+        }
+        let own = *self.own.get_or_insert_with(|| Sums::of(headline));
+        update_nodes(&mut self.children, headline.children());
+        self.total = Some(
+            self.children
+                .iter()
+                .filter_map(|child| child.total)
+                .fold(own, Add::add),
+        );
+    }
+}
+
+/// Updates the nodes of the headlines. They are created again if the number of headlines
+/// changed.
+fn update_nodes<'a>(nodes: &mut Vec<Node>, headlines: impl IntoIterator<Item = &'a Headline>) {
+    let headlines: Vec<_> = headlines.into_iter().collect();
+    if nodes.len() != headlines.len() {
+        *nodes = vec![Node::default(); headlines.len()];
+    }
+    for (node, headline) in nodes.iter_mut().zip(headlines) {
+        node.update(headline);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(design: &str, code: &str) -> Document {
+        Document::parse(&format!(
+            "* Project\n\
+             :PROPERTIES:\n:EFFORT: 1:00\n:END:\n\
+             ** Design\n\
+             :PROPERTIES:\n:EFFORT: 2:00\n:END:\n\
+             CLOCK: [2018-10-08 Mon {}]--[2018-10-08 Mon 10:30] =>  1:30\n\
+             ** Code\n\
+             CLOCK: [2018-10-08 Mon {}]--[2018-10-08 Mon 12:00] =>  1:00\n\
+             * Other\n",
+            design, code
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn invalidate_edited_headlines() {
+        let mut cache = RollupCache::new();
+        cache.update(&document("09:00", "11:00"));
+        let sums = |clocked_minutes, effort_minutes| {
+            Some(Sums {
+                clocked_minutes,
+                effort_minutes,
+            })
+        };
+        assert_eq!(cache.get(&[0]), sums(150, 180));
+        assert_eq!(cache.get(&[0, 1]), sums(60, 0));
+        assert_eq!(cache.get(&[1]), sums(0, 0));
+        assert_eq!(cache.get(&[2]), None);
+        assert_eq!(cache.total(), sums(150, 180));
+
+        // only the invalidated headline is summed again, not its sibling
+        let edited = document("10:00", "10:00");
+        cache.invalidate(&[0, 1]);
+        assert_eq!(cache.get(&[0]), None);
+        assert_eq!(cache.get(&[0, 0]), sums(90, 120));
+        cache.update(&edited);
+        assert_eq!(cache.get(&[0, 1]), sums(120, 0));
+        assert_eq!(cache.get(&[0]), sums(210, 180));
+
+        cache.invalidate(&[]);
+        cache.update(&edited);
+        assert_eq!(cache.get(&[0]), sums(150, 180));
+    }
+}
@@ -0,0 +1,178 @@
+//! A round-trip check of the parser and the serialization (for tests and fuzzers).
+//!
+//! Serializing a parsed document and parsing the text again has to result in the same document
+//! (ignoring the spans, which change when the serialization normalizes the text). [`check`]
+//! tests this for any input. If it fails, the input is shrunk to the smallest range of lines that
+//! still fails on its own, which is usually the part the parser or serialization gets wrong.
+//!
+//! This module is only available with the `roundtrip` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! rust-orgmode = { version = "0.1", features = ["roundtrip"] }
+//! ```
+//!
+//! ```
+//! use rust_orgmode::roundtrip::assert_roundtrip;
+//!
+//! assert_roundtrip("* TODO Write *tests* :work:\nSome text.\n");
+//! ```
+
+use crate::parsing::{line_column, parse_document_with_config, ParseConfig, ParseError};
+use crate::types::document::Document;
+use crate::types::Span;
+use std::fmt;
+
+/// The result of a failed [`check`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The smallest range of lines of the input that fails on its own.
+    pub span: Span,
+    /// The text of the span.
+    pub input: String,
+    /// The serialization of the document parsed from `input`.
+    pub serialized: String,
+    pub kind: FailureKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The serialized text can't be parsed.
+    Reparse(ParseError),
+    /// The serialized text is parsed to a different document.
+    Mismatch {
+        /// The document parsed from the input (without spans).
+        expected: Box<Document>,
+        /// The document parsed from the serialized text (without spans).
+        actual: Box<Document>,
+    },
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "round trip failed for bytes {}..{} of the input {:?} (serialized as {:?}): ",
+            self.span.start(),
+            self.span.end(),
+            self.input,
+            self.serialized
+        )?;
+        match &self.kind {
+            FailureKind::Reparse(error) => write!(
+                f,
+                "the serialized text can't be parsed (expected {})",
+                error.expected()
+            ),
+            FailureKind::Mismatch { expected, actual } => write!(
+                f,
+                "the serialized text is parsed to a different document\n\
+                 expected: {:#?}\nactual: {:#?}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// Checks that the document parsed from the input is parsed again from its serialization.
+///
+/// Inputs that can't be parsed pass because there is nothing to serialize.
+pub fn check(input: &str, config: &ParseConfig) -> Result<(), Failure> {
+    if check_span(input, config).is_none() {
+        return Ok(());
+    }
+    let span = shrink(input, |part| check_span(part, config).is_some());
+    let part = &input[span.start()..span.end()];
+    let (serialized, kind) = check_span(part, config).unwrap();
+    Err(Failure {
+        input: part.to_string(),
+        span,
+        serialized,
+        kind,
+    })
+}
+
+/// Returns the smallest range of lines of the failing input which still fails.
+///
+/// Lines are dropped from the start, then from the end, as long as the rest still fails.
+fn shrink(input: &str, fails: impl Fn(&str) -> bool) -> Span {
+    let line_starts: Vec<usize> = input
+        .match_indices('\n')
+        .map(|(index, _)| index + 1)
+        .filter(|&start| start < input.len())
+        .collect();
+    let start = line_starts
+        .iter()
+        .take_while(|&&start| fails(&input[start..]))
+        .last()
+        .map_or(0, |&start| start);
+    let end = line_starts
+        .iter()
+        .rev()
+        .take_while(|&&end| end > start && fails(&input[start..end]))
+        .last()
+        .map_or(input.len(), |&end| end);
+    Span::new(start, end)
+}
+
+/// Checks the input like [`check`] with the default configuration and panics if it fails.
+///
+/// The panic message contains the line of the failing span and the differing documents.
+pub fn assert_roundtrip(input: &str) {
+    if let Err(failure) = check(input, &ParseConfig::default()) {
+        let (line, _) = line_column(input, failure.span.start());
+        panic!("line {}: {}", line, failure);
+    }
+}
+
+/// Returns the serialized text and the failure or `None` if the round trip succeeds.
+fn check_span(input: &str, config: &ParseConfig) -> Option<(String, FailureKind)> {
+    let mut expected = parse_document_with_config(input, config).ok()?;
+    let serialized = expected.to_string();
+    let mut actual = match parse_document_with_config(&serialized, config) {
+        Ok(document) => document,
+        Err(error) => return Some((serialized, FailureKind::Reparse(error))),
+    };
+    expected.clear_spans();
+    actual.clear_spans();
+    if expected == actual {
+        None
+    } else {
+        let kind = FailureKind::Mismatch {
+            expected: Box::new(expected),
+            actual: Box::new(actual),
+        };
+        Some((serialized, kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn shrink_to_failing_lines() {
+        assert_roundtrip(include_str!("../tests/correct/simple.org"));
+        assert_roundtrip("* \n** \na\n");
+
+        let input = "a\nb\nc\nd\ne\n";
+        let fails = |part: &str| part.contains("b\nc");
+        assert_eq!(shrink(input, fails), Span::new(2, 6));
+        assert_eq!(shrink("b\nc", fails), Span::new(0, 3));
+    }
+
+    proptest! {
+        #[test]
+        // TODO also generate blank lines (`"\n* "` is parsed as a paragraph)
+        fn roundtrip_lines(
+            lines in prop::collection::vec(
+                "(\\*{1,3} )?(TODO )?[a-z][a-z ]{0,8}(\\*[a-z]{1,4}\\*)?[a-z ]{0,4}",
+                0..8,
+            )
+        ) {
+            let input: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+            prop_assert_eq!(check(&input, &ParseConfig::default()), Ok(()));
+        }
+    }
+}
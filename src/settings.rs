@@ -0,0 +1,461 @@
+//! The in-buffer settings of a document (`#+TITLE:`, `#+OPTIONS:`, `#+SETUPFILE:`, ...).
+//!
+//! ```text
+//! #+TITLE: My Notes
+//! #+AUTHOR: Jane Doe
+//! #+OPTIONS: toc:nil num:2 ^:{}
+//! #+SETUPFILE: "theme.setup"
+//! ```
+//!
+//! The keywords are read in document order, so later keywords override earlier ones (several
+//! `#+TITLE:` keywords are joined with a space like in org mode). A `#+SETUPFILE:` keyword reads
+//! the keywords of the file at its position. [`Document::settings`] only records the setup files.
+//! [`resolve_settings`] loads them with a [`FileLoader`] too.
+//!
+//! Exporters use [`ExportSettings`] to decide e.g. whether to add a table of contents or how
+//! `a_b` is exported.
+
+use crate::include::FileLoader;
+use crate::parsing::ParseError;
+use crate::types::document::Document;
+use crate::types::elements::Keyword;
+use itertools::Itertools;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The settings of a document read from its keywords.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DocumentSettings {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub author: Option<String>,
+    pub email: Option<String>,
+    pub date: Option<String>,
+    pub language: Option<String>,
+    pub description: Option<String>,
+    /// The value of `#+KEYWORDS:` (e.g. for the HTML meta tag).
+    pub keywords: Option<String>,
+    /// The tags of the subtrees to export (`#+SELECT_TAGS:`).
+    pub select_tags: Vec<String>,
+    /// The tags of the subtrees to leave out of the export (`#+EXCLUDE_TAGS:`).
+    pub exclude_tags: Vec<String>,
+    /// The paths of the `#+SETUPFILE:` keywords (including the ones of setup files).
+    pub setup_files: Vec<PathBuf>,
+    /// The settings of `#+OPTIONS:`.
+    pub options: ExportSettings,
+}
+
+/// The settings of the `#+OPTIONS:` keyword.
+///
+/// The defaults are the defaults of org mode. Unknown options are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExportSettings {
+    /// `toc:` - which headlines are in the table of contents.
+    pub table_of_contents: Levels,
+    /// `num:` - which headlines are numbered.
+    pub section_numbers: Levels,
+    /// `H:` - the deepest level exported as a headline. Deeper headlines become list items.
+    pub headline_levels: u32,
+    /// `^:` - how `a_b` and `a^b` are exported.
+    pub sub_superscripts: SubSuperscripts,
+    /// `*:` - whether text markup is exported (or the markers are kept).
+    pub emphasis: bool,
+    /// `f:` - whether footnotes are exported.
+    pub footnotes: bool,
+    /// `|:` - whether tables are exported.
+    pub tables: bool,
+    /// `todo:` - whether the todo keywords of headlines are exported.
+    pub todo_keywords: bool,
+    /// `tags:` - whether the tags of headlines are exported (`not-in-toc` counts as `t`).
+    pub tags: bool,
+    /// `pri:` - whether the priority cookies of headlines are exported.
+    pub priority: bool,
+    /// `p:` - whether planning lines are exported.
+    pub planning: bool,
+    /// `<:` - whether timestamps are exported.
+    pub timestamps: bool,
+    /// `d:` - whether drawers are exported.
+    pub drawers: bool,
+    /// `prop:` - whether property drawers are exported.
+    pub properties: bool,
+    /// `stat:` - whether statistics cookies are exported.
+    pub statistics_cookies: bool,
+    /// `':` - whether quotes are exported as smart quotes.
+    pub smart_quotes: bool,
+    /// `-:` - whether special strings (`--`, `...`) are exported as typographic characters.
+    pub special_strings: bool,
+    /// `::` - whether fixed width areas are exported.
+    pub fixed_width: bool,
+    /// `\n:` - whether every newline is exported as a line break.
+    pub preserve_line_breaks: bool,
+    /// `e:` - whether entities are exported.
+    pub entities: bool,
+    /// `title:` - whether the title is exported.
+    pub title: bool,
+    /// `author:` - whether the author is exported.
+    pub author: bool,
+    /// `email:` - whether the email address is exported.
+    pub email: bool,
+    /// `date:` - whether the date is exported.
+    pub date: bool,
+    /// `creator:` - whether the creator is exported.
+    pub creator: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            table_of_contents: Levels::All,
+            section_numbers: Levels::All,
+            headline_levels: 3,
+            sub_superscripts: SubSuperscripts::On,
+            emphasis: true,
+            footnotes: true,
+            tables: true,
+            todo_keywords: true,
+            tags: true,
+            priority: false,
+            planning: false,
+            timestamps: true,
+            drawers: true,
+            properties: false,
+            statistics_cookies: true,
+            smart_quotes: false,
+            special_strings: true,
+            fixed_width: true,
+            preserve_line_breaks: false,
+            entities: true,
+            title: true,
+            author: true,
+            email: false,
+            date: true,
+            creator: false,
+        }
+    }
+}
+
+/// The headline levels an option (`toc:`, `num:`) applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Levels {
+    /// `nil`
+    None,
+    /// `t` (up to [`ExportSettings::headline_levels`])
+    All,
+    /// A number: up to this level.
+    UpTo(u32),
+}
+
+impl Levels {
+    /// Returns `true` if the option applies to a headline of the level.
+    pub fn includes(self, level: u32, headline_levels: u32) -> bool {
+        match self {
+            Levels::None => false,
+            Levels::All => level <= headline_levels,
+            Levels::UpTo(max) => level <= max.min(headline_levels),
+        }
+    }
+}
+
+/// How subscripts (`a_b`) and superscripts (`a^b`) are exported (`^:`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubSuperscripts {
+    /// `nil` - they are exported as the plain text.
+    Off,
+    /// `{}` - only the ones with braces (`a_{b}`) are exported as sub-/superscripts.
+    Braces,
+    /// `t`
+    On,
+}
+
+/// The error returned when loading the setup files fails.
+#[derive(Debug)]
+pub enum SetupFileError {
+    /// The file can't be loaded.
+    Load { path: PathBuf, error: io::Error },
+    /// The file can't be parsed.
+    Parse { path: PathBuf, error: ParseError },
+    /// The file sets itself up (directly or through other files).
+    Cycle(PathBuf),
+}
+
+impl fmt::Display for SetupFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupFileError::Load { path, error } => {
+                write!(f, "can't load {}: {}", path.display(), error)
+            }
+            SetupFileError::Parse { path, error } => write!(
+                f,
+                "can't parse {} (expected {})",
+                path.display(),
+                error.expected()
+            ),
+            SetupFileError::Cycle(path) => write!(f, "{} sets itself up", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SetupFileError {}
+
+impl DocumentSettings {
+    /// Reads the settings from the keywords without loading setup files.
+    pub fn from_keywords<'a>(keywords: impl IntoIterator<Item = &'a Keyword>) -> Self {
+        let mut settings = DocumentSettings::default();
+        for keyword in keywords {
+            let value = keyword.value.value().iter().join("");
+            settings.apply(&keyword.key, value.trim());
+        }
+        settings
+    }
+
+    /// Applies a single keyword. Unknown keywords are ignored.
+    ///
+    /// `#+SETUPFILE:` only adds the path to [`setup_files`](`DocumentSettings::setup_files`).
+    pub fn apply(&mut self, key: &str, value: &str) {
+        let value = value.to_string();
+        match key.to_uppercase().as_str() {
+            "TITLE" => {
+                self.title = match self.title.take() {
+                    Some(title) if !value.is_empty() => Some(title + " " + &value),
+                    Some(title) => Some(title),
+                    None => Some(value),
+                }
+            }
+            "SUBTITLE" => self.subtitle = Some(value),
+            "AUTHOR" => self.author = Some(value),
+            "EMAIL" => self.email = Some(value),
+            "DATE" => self.date = Some(value),
+            "LANGUAGE" => self.language = Some(value),
+            "DESCRIPTION" => self.description = Some(value),
+            "KEYWORDS" => self.keywords = Some(value),
+            "SELECT_TAGS" => self.select_tags = tags(&value),
+            "EXCLUDE_TAGS" => self.exclude_tags = tags(&value),
+            "SETUPFILE" => self
+                .setup_files
+                .push(PathBuf::from(value.trim_matches('"'))),
+            "OPTIONS" => self.options.apply(&value),
+            _ => {}
+        }
+    }
+}
+
+/// Splits the value of `#+SELECT_TAGS:` or `#+EXCLUDE_TAGS:` (`tag1 tag2` or `:tag1:tag2:`).
+fn tags(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl ExportSettings {
+    /// Applies the value of an `#+OPTIONS:` keyword (e.g. `toc:nil num:2 ^:{}`).
+    ///
+    /// Options with invalid values are ignored.
+    pub fn apply(&mut self, options: &str) {
+        for option in options.split_whitespace() {
+            // the key can be a colon (`::`) so the separator is searched after the first char
+            let colon = match option.char_indices().skip(1).find(|&(_, c)| c == ':') {
+                Some((colon, _)) => colon,
+                None => continue,
+            };
+            let (key, value) = (&option[..colon], &option[colon + 1..]);
+            let flag = match value {
+                "nil" => Some(false),
+                "" => None,
+                _ => Some(true),
+            };
+            let levels = match value {
+                "nil" => Some(Levels::None),
+                "t" => Some(Levels::All),
+                _ => value.parse().ok().map(Levels::UpTo),
+            };
+            match key {
+                "toc" => self.table_of_contents = levels.unwrap_or(self.table_of_contents),
+                "num" => self.section_numbers = levels.unwrap_or(self.section_numbers),
+                "H" => self.headline_levels = value.parse().unwrap_or(self.headline_levels),
+                "^" => {
+                    self.sub_superscripts = match value {
+                        "nil" => SubSuperscripts::Off,
+                        "{}" => SubSuperscripts::Braces,
+                        _ => SubSuperscripts::On,
+                    }
+                }
+                _ => {
+                    let setting = match key {
+                        "*" => &mut self.emphasis,
+                        "f" => &mut self.footnotes,
+                        "|" => &mut self.tables,
+                        "todo" => &mut self.todo_keywords,
+                        "tags" => &mut self.tags,
+                        "pri" => &mut self.priority,
+                        "p" => &mut self.planning,
+                        "<" => &mut self.timestamps,
+                        "d" => &mut self.drawers,
+                        "prop" => &mut self.properties,
+                        "stat" => &mut self.statistics_cookies,
+                        "'" => &mut self.smart_quotes,
+                        "-" => &mut self.special_strings,
+                        ":" => &mut self.fixed_width,
+                        "\\n" => &mut self.preserve_line_breaks,
+                        "e" => &mut self.entities,
+                        "title" => &mut self.title,
+                        "author" => &mut self.author,
+                        "email" => &mut self.email,
+                        "date" => &mut self.date,
+                        "creator" => &mut self.creator,
+                        _ => continue,
+                    };
+                    *setting = flag.unwrap_or(*setting);
+                }
+            }
+        }
+    }
+}
+
+/// Reads the settings of the document and loads its setup files.
+///
+/// The keywords of a setup file are applied at the position of the `#+SETUPFILE:` keyword. So
+/// the document can override them with keywords after it. Setup files can contain setup files
+/// too (relative to the setup file).
+pub fn resolve_settings(
+    document: &Document,
+    loader: &impl FileLoader,
+) -> Result<DocumentSettings, SetupFileError> {
+    let mut settings = DocumentSettings::default();
+    apply_document(
+        &mut settings,
+        document,
+        Path::new(""),
+        loader,
+        &mut Vec::new(),
+    )?;
+    Ok(settings)
+}
+
+/// Applies the keywords of the document and loads the setup files.
+///
+/// `directory` is the directory of the document and `files` are the setup files currently being
+/// read (to find cycles).
+fn apply_document(
+    settings: &mut DocumentSettings,
+    document: &Document,
+    directory: &Path,
+    loader: &impl FileLoader,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), SetupFileError> {
+    for keyword in document.keywords() {
+        let value = keyword.value.value().iter().join("");
+        settings.apply(&keyword.key, value.trim());
+        if !keyword.key.eq_ignore_ascii_case("SETUPFILE") {
+            continue;
+        }
+        let path = directory.join(settings.setup_files.last().unwrap());
+        if files.contains(&path) {
+            return Err(SetupFileError::Cycle(path));
+        }
+        let content = loader.load(&path).map_err(|error| SetupFileError::Load {
+            path: path.clone(),
+            error,
+        })?;
+        let setup = Document::parse(&content).map_err(|error| SetupFileError::Parse {
+            path: path.clone(),
+            error,
+        })?;
+        files.push(path.clone());
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        apply_document(settings, &setup, directory, loader, files)?;
+        files.pop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(path: &Path) -> io::Result<String> {
+        let text = match path.to_str().unwrap() {
+            "setup/theme.setup" => {
+                "#+AUTHOR: Someone\n#+OPTIONS: toc:2 ^:{} H:4\n#+SETUPFILE: \"more.setup\"\n"
+            }
+            "setup/more.setup" => "#+EMAIL: jane@example.com\n",
+            "loop.setup" => "#+SETUPFILE: loop.setup\n",
+            _ => return Err(io::Error::from(io::ErrorKind::NotFound)),
+        };
+        Ok(text.to_string())
+    }
+
+    fn document() -> Document {
+        Document::parse(
+            "#+TITLE: My\n\
+             #+TITLE: Notes\n\
+             #+SETUPFILE: \"setup/theme.setup\"\n\
+             #+AUTHOR: Jane Doe\n\
+             #+OPTIONS: num:nil \\n:t ::nil tags:not-in-toc unknown:t\n\
+             #+EXCLUDE_TAGS: :noexport:draft:\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve_keywords() {
+        let settings = resolve_settings(&document(), &load).unwrap();
+        assert_eq!(settings.title.as_deref(), Some("My Notes"));
+        assert_eq!(settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(settings.email.as_deref(), Some("jane@example.com"));
+        assert_eq!(settings.exclude_tags, ["noexport", "draft"]);
+        assert_eq!(
+            settings.setup_files,
+            [
+                PathBuf::from("setup/theme.setup"),
+                PathBuf::from("more.setup")
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_options() {
+        let settings = resolve_settings(&document(), &load).unwrap();
+        let options = &settings.options;
+        assert_eq!(options.table_of_contents, Levels::UpTo(2));
+        assert_eq!(options.section_numbers, Levels::None);
+        assert_eq!(options.headline_levels, 4);
+        assert_eq!(options.sub_superscripts, SubSuperscripts::Braces);
+        assert!(options.preserve_line_breaks);
+        assert!(!options.fixed_width);
+        assert!(options.tags);
+        assert!(options.emphasis);
+    }
+
+    #[test]
+    fn table_of_contents_levels() {
+        let settings = resolve_settings(&document(), &load).unwrap();
+        let options = &settings.options;
+        assert!(options
+            .table_of_contents
+            .includes(2, options.headline_levels));
+        assert!(!options
+            .table_of_contents
+            .includes(3, options.headline_levels));
+    }
+
+    #[test]
+    fn settings_without_setup_files() {
+        // without a loader the setup files are not read
+        let settings = document().settings();
+        assert_eq!(settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(settings.email, None);
+        assert_eq!(settings.options.table_of_contents, Levels::All);
+    }
+
+    #[test]
+    fn setup_file_cycle() {
+        let document = Document::parse("#+SETUPFILE: loop.setup\n").unwrap();
+        match resolve_settings(&document, &load) {
+            Err(SetupFileError::Cycle(path)) => assert_eq!(path, PathBuf::from("loop.setup")),
+            result => panic!("expected a cycle, got {:?}", result),
+        }
+    }
+}
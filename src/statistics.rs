@@ -0,0 +1,268 @@
+//! Updating statistics cookies (`[1/3]` or `[33%]`) like org mode does.
+//!
+//! A cookie in the title of a headline counts the child headlines with a todo keyword (the done
+//! ones and all of them) or the checkboxes in the section of the headline. A cookie in the first
+//! paragraph of an item counts the checkboxes of its child items.
+//!
+//! What is counted is configured by:
+//!
+//! 1. The `COOKIE_DATA` property of the headline. `todo` or `checkbox` choose what the cookies of
+//!    the headline count (without them todo keywords are counted if there are any, otherwise
+//!    checkboxes). `recursive` counts all descendants instead of the direct children (for the
+//!    cookies of the headline and of its items).
+//! 2. The `#+STARTUP` keywords of the document (see [`StatisticsConfig`]).
+
+use crate::types::document::Document;
+use crate::types::greater_elements::{
+    Checkbox, Headline, HeadlineContentSet, Item, Section, TodoKeyword,
+};
+use crate::types::objects::{CookieKind, StatisticsCookie};
+use crate::types::{ElementSet, Parent, Spanned, StandardSet, StandardSetNoLineBreak};
+
+/// The file wide configuration of statistics cookies.
+///
+/// In org mode these are the variables `org-hierarchical-todo-statistics` and
+/// `org-checkbox-hierarchical-statistics`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatisticsConfig {
+    /// Only count the direct children with a todo keyword (unless `COOKIE_DATA` is
+    /// `recursive`).
+    pub hierarchical_todo: bool,
+    /// Only count the checkboxes of the direct children (unless `COOKIE_DATA` is `recursive`).
+    pub hierarchical_checkbox: bool,
+}
+
+impl Default for StatisticsConfig {
+    fn default() -> Self {
+        StatisticsConfig {
+            hierarchical_todo: true,
+            hierarchical_checkbox: true,
+        }
+    }
+}
+
+impl StatisticsConfig {
+    /// Reads the configuration from the `#+STARTUP` keywords in the preface of the document.
+    ///
+    /// `nohierarchicaltodos` and `nohierarchicalcheckboxes` count all descendants,
+    /// `hierarchicaltodos` and `hierarchicalcheckboxes` only the direct children. The last option
+    /// wins.
+    pub fn from_document(document: &Document) -> Self {
+        let mut config = StatisticsConfig::default();
+        for keyword in document
+            .keywords()
+            .filter(|keyword| keyword.key.eq_ignore_ascii_case("STARTUP"))
+        {
+            let value: String = keyword
+                .value
+                .value()
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            for option in value.split_whitespace() {
+                match option {
+                    "hierarchicaltodos" => config.hierarchical_todo = true,
+                    "nohierarchicaltodos" => config.hierarchical_todo = false,
+                    "hierarchicalcheckboxes" => config.hierarchical_checkbox = true,
+                    "nohierarchicalcheckboxes" => config.hierarchical_checkbox = false,
+                    _ => {}
+                }
+            }
+        }
+        config
+    }
+}
+
+/// The number of done tasks (or checked boxes) and the number of all tasks (or boxes).
+type Count = (u32, u32);
+
+/// Updates all statistics cookies of the document.
+pub fn update_statistics(document: &mut Document, config: &StatisticsConfig) {
+    if let Some(preface) = &mut document.preface {
+        update_elements(preface.content_mut(), !config.hierarchical_checkbox);
+    }
+    for headline in &mut document.headlines {
+        update_headline(headline, config);
+    }
+}
+
+fn update_headline(headline: &mut Headline, config: &StatisticsConfig) {
+    let cookie_data = headline
+        .property("COOKIE_DATA")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let has = |word| cookie_data.split_whitespace().any(|data| data == word);
+    let recursive_todo = has("recursive") || !config.hierarchical_todo;
+    let recursive_checkbox = has("recursive") || !config.hierarchical_checkbox;
+
+    let todos = count_todos(headline, recursive_todo);
+    let count = if has("todo") || (!has("checkbox") && todos.1 > 0) {
+        todos
+    } else {
+        sections(headline)
+            .map(|section| count_lists(section_elements(section), recursive_checkbox))
+            .fold((0, 0), add)
+    };
+    if let Some(title) = &mut headline.title {
+        for object in title.iter_mut() {
+            if let StandardSetNoLineBreak::StatisticsCookie(cookie) = object {
+                set_cookie(cookie, count);
+            }
+        }
+    }
+
+    for content in headline.content.iter_mut().flat_map(Spanned::get_mut_value) {
+        match content {
+            HeadlineContentSet::Section(section) => {
+                update_elements(section.content_mut(), recursive_checkbox)
+            }
+            HeadlineContentSet::Headline(child) => update_headline(child, config),
+        }
+    }
+}
+
+/// Updates the cookies of the items in the elements.
+fn update_elements(elements: &mut [ElementSet], recursive: bool) {
+    for element in elements {
+        match element {
+            ElementSet::PlainList(list) => {
+                for item in list.content_mut() {
+                    update_item(item, recursive);
+                }
+            }
+            ElementSet::Drawer(drawer) => update_elements(drawer.content_mut(), recursive),
+            _ => {}
+        }
+    }
+}
+
+fn update_item(item: &mut Item, recursive: bool) {
+    let count = count_lists(item_elements(item), recursive);
+    if let Some(ElementSet::Paragraph(paragraph)) = item.content_mut().first_mut() {
+        for object in paragraph.content.iter_mut() {
+            if let StandardSet::StatisticsCookie(cookie) = object {
+                set_cookie(cookie, count);
+            }
+        }
+    }
+    update_elements(item.content_mut(), recursive);
+}
+
+fn set_cookie(cookie: &mut StatisticsCookie, (done, total): Count) {
+    cookie.cookie = match cookie.cookie {
+        CookieKind::Percent(_) => {
+            CookieKind::Percent(Some((done * 100).checked_div(total).unwrap_or(0)))
+        }
+        CookieKind::Number(..) => CookieKind::Number(Some(done), Some(total)),
+    };
+}
+
+fn add(a: Count, b: Count) -> Count {
+    (a.0 + b.0, a.1 + b.1)
+}
+
+/// Counts the child headlines (or all descendants) with a todo keyword.
+fn count_todos(headline: &Headline, recursive: bool) -> Count {
+    headline
+        .children()
+        .map(|child| {
+            let own = match child.todo_keyword {
+                Some(TodoKeyword::Done(_)) => (1, 1),
+                Some(TodoKeyword::Todo(_)) => (0, 1),
+                None => (0, 0),
+            };
+            if recursive {
+                add(own, count_todos(child, true))
+            } else {
+                own
+            }
+        })
+        .fold((0, 0), add)
+}
+
+/// Counts the checkboxes of the items of the lists in the elements (and of their nested items
+/// if `recursive`).
+fn count_lists(elements: &[ElementSet], recursive: bool) -> Count {
+    elements
+        .iter()
+        .filter_map(|element| match element {
+            ElementSet::PlainList(list) => Some(list),
+            _ => None,
+        })
+        .flat_map(|list| list.content().map_or(&[][..], |content| content.value()))
+        .map(|item| {
+            let own = match item.checkbox {
+                Some(Checkbox::Checked) => (1, 1),
+                Some(_) => (0, 1),
+                None => (0, 0),
+            };
+            if recursive {
+                add(own, count_lists(item_elements(item), true))
+            } else {
+                own
+            }
+        })
+        .fold((0, 0), add)
+}
+
+fn sections(headline: &Headline) -> impl Iterator<Item = &Section> {
+    headline
+        .content()
+        .into_iter()
+        .flat_map(Spanned::value)
+        .filter_map(|content| match content {
+            HeadlineContentSet::Section(section) => Some(section),
+            HeadlineContentSet::Headline(_) => None,
+        })
+}
+
+fn section_elements(section: &Section) -> &[ElementSet] {
+    section.content().map_or(&[], |content| content.value())
+}
+
+fn item_elements(item: &Item) -> &[ElementSet] {
+    item.content().map_or(&[], |content| content.value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(s: &str) -> String {
+        let mut document = Document::parse(s).unwrap();
+        let config = StatisticsConfig::from_document(&document);
+        update_statistics(&mut document, &config);
+        document.to_string()
+    }
+
+    #[test]
+    fn cookies() {
+        assert_eq!(
+            update(
+                "* Project [/] [%]\n\
+                 ** DONE a\n\
+                 ** TODO b [/]\n\
+                 - [X] one [/]\n  - [X] x\n  - [ ] y\n\
+                 - [ ] two\n"
+            ),
+            "* Project [1/2] [50%]\n\
+             ** DONE a\n\
+             ** TODO b [1/2]\n\
+             - [X] one [1/2]\n  - [X] x\n  - [ ] y\n\
+             - [ ] two\n"
+        );
+        // recursive counting with the property and the startup option
+        assert_eq!(
+            update(
+                "* Project [/]\n:PROPERTIES:\n:COOKIE_DATA: todo recursive\n:END:\n\
+                 ** TODO a\n*** DONE b\n"
+            ),
+            "* Project [1/2]\n:PROPERTIES:\n:COOKIE_DATA: todo recursive\n:END:\n\
+             ** TODO a\n*** DONE b\n"
+        );
+        assert_eq!(
+            update("#+STARTUP: nohierarchicalcheckboxes\n- [-] all [/]\n  - [-] a\n    - [X] x\n  - [ ] b\n"),
+            "#+STARTUP: nohierarchicalcheckboxes\n- [-] all [1/3]\n  - [-] a\n    - [X] x\n  - [ ] b\n"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Aligning the columns of org tables (like `org-table-align`).
+//!
+//! All fields of a column are padded to the width of the widest field. Columns which mostly
+//! contain numbers are aligned to the right, other columns to the left. A field containing
+//! only an alignment cookie (`<l>`, `<c>` or `<r>`, optionally with a width like `<r10>`)
+//! sets the alignment of its column (the width is ignored).
+//!
+//! ```text
+//! | Name | Qty |
+//! |-
+//! | apples | 12 |
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! | Name   | Qty |
+//! |--------+-----|
+//! | apples |  12 |
+//! ```
+
+use crate::types::greater_elements::{Table, TableContent, TableKind, TableRowKind};
+use std::fmt::Write;
+
+/// The alignment of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Returns the text of the table with aligned columns.
+///
+/// Rows with fewer fields than others are filled with empty fields and horizontal rules span
+/// all columns. The `#+TBLFM:` lines and affiliated keywords are kept. Table.el tables are
+/// returned unchanged.
+pub fn align(table: &Table) -> String {
+    let formulas = match &table.kind {
+        TableKind::Org { formulas } => formulas,
+        TableKind::TableEl { .. } => return table.to_string(),
+    };
+    let rows: Vec<Option<Vec<String>>> = table
+        .content
+        .value()
+        .iter()
+        .filter_map(|content| match content {
+            TableContent::Org(row) => Some(match &row.kind {
+                TableRowKind::Normal(cells) => Some(
+                    cells
+                        .value()
+                        .iter()
+                        .map(|cell| cell.to_string().trim().to_string())
+                        .collect(),
+                ),
+                TableRowKind::Rule => None,
+            }),
+            TableContent::TableEl(_) => None,
+        })
+        .collect();
+    let columns = rows.iter().flatten().map(Vec::len).max().unwrap_or(0);
+    let fields = |column: usize| {
+        rows.iter()
+            .flatten()
+            .map(move |cells| cells.get(column).map_or("", String::as_str))
+    };
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| fields(column).map(width).max().unwrap_or(0).max(1))
+        .collect();
+    let alignments: Vec<Alignment> = (0..columns)
+        .map(|column| column_alignment(fields(column)))
+        .collect();
+
+    let mut text = String::new();
+    if let Some(keywords) = &table.affiliated_keywords {
+        if !keywords.value().is_empty() {
+            writeln!(text, "{}", keywords).unwrap();
+        }
+    }
+    let lines = rows.iter().map(|row| match row {
+        Some(cells) => {
+            let mut line = "|".to_string();
+            for (column, (&width, &alignment)) in widths.iter().zip(&alignments).enumerate() {
+                let field = cells.get(column).map_or("", String::as_str);
+                write!(line, " {} |", pad(field, width, alignment)).unwrap();
+            }
+            line
+        }
+        None => {
+            let dashes: Vec<_> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+            format!("|{}|", dashes.join("+"))
+        }
+    });
+    let lines: Vec<_> = lines
+        .chain(
+            formulas
+                .iter()
+                .map(|formula| format!("#+TBLFM: {}", formula)),
+        )
+        .collect();
+    text.push_str(&lines.join("\n"));
+    text
+}
+
+/// Returns the alignment of the column with the fields.
+///
+/// Columns where at least half of the non-empty fields are numbers are aligned to the right
+/// (like with the default `org-table-number-fraction`).
+fn column_alignment<'a>(fields: impl Iterator<Item = &'a str>) -> Alignment {
+    let mut numbers = 0;
+    let mut others = 0;
+    for field in fields.filter(|field| !field.is_empty()) {
+        if let Some(alignment) = cookie(field) {
+            return alignment;
+        }
+        if is_number(field) {
+            numbers += 1;
+        } else {
+            others += 1;
+        }
+    }
+    if numbers > 0 && numbers >= others {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    }
+}
+
+/// Returns the alignment of an alignment cookie (e.g. `<r>` or `<l10>`).
+fn cookie(field: &str) -> Option<Alignment> {
+    if !field.starts_with('<') || !field.ends_with('>') || field.len() < 3 {
+        return None;
+    }
+    let inner = &field[1..field.len() - 1];
+    let (alignment, width) = match inner.chars().next()? {
+        'l' => (Alignment::Left, &inner[1..]),
+        'c' => (Alignment::Center, &inner[1..]),
+        'r' => (Alignment::Right, &inner[1..]),
+        _ => return None,
+    };
+    if width.chars().all(|c| c.is_ascii_digit()) {
+        Some(alignment)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the field contains a number (e.g. `12`, `-1.5`, `3e5` or `50%`).
+fn is_number(field: &str) -> bool {
+    field.trim_end_matches('%').parse::<f64>().is_ok()
+}
+
+/// Returns the width of the field in characters.
+fn width(field: &str) -> usize {
+    field.chars().count()
+}
+
+/// Pads the field to the width.
+fn pad(field: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(self::width(field));
+    match alignment {
+        Alignment::Left => format!("{}{}", field, " ".repeat(padding)),
+        Alignment::Right => format!("{}{}", " ".repeat(padding), field),
+        Alignment::Center => format!(
+            "{}{}{}",
+            " ".repeat(padding / 2),
+            field,
+            " ".repeat(padding - padding / 2)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::document::Document;
+    use crate::types::ElementSet;
+
+    #[test]
+    fn align_table() {
+        let text = "#+NAME: fruit\n\
+                    | Name | Qty | Note |\n\
+                    |-\n\
+                    | apples | 12 | <c> |\n\
+                    | pears | 7.5\n\
+                    | äpfel | x | ok |\n\
+                    #+TBLFM: $2=$2";
+        let mut document = Document::parse(text).unwrap();
+        let table = match &document.preface.as_mut().unwrap().content_mut()[0] {
+            ElementSet::Table(table) => table.format(),
+            element => panic!("expected a table, got {:?}", element),
+        };
+        assert_eq!(
+            table,
+            "#+NAME: fruit\n\
+             | Name   | Qty | Note |\n\
+             |--------+-----+------|\n\
+             | apples |  12 | <c>  |\n\
+             | pears  | 7.5 |      |\n\
+             | äpfel  |   x |  ok  |\n\
+             #+TBLFM: $2=$2"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Converting org tables from and to CSV (or TSV) data.
+//!
+//! Fields containing the separator, double quotes or line breaks are quoted with double
+//! quotes (`"a, ""b"""`). Line breaks in fields are replaced by spaces when importing because
+//! org table fields can't contain them.
+//!
+//! Horizontal rules have no equivalent in CSV. They are left out when exporting. When importing
+//! a rule can be inserted after the first row (see [`CsvOptions::header`]).
+
+use crate::parsing::parse_table_cell;
+use crate::types::greater_elements::{Table, TableContent, TableKind, TableRow, TableRowKind};
+use crate::types::Spanned;
+use std::io::{self, Read, Write};
+
+/// The options for reading and writing CSV data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CsvOptions {
+    /// The character separating the fields (default: `,`).
+    pub separator: char,
+    /// `true` if the first row is a header.
+    ///
+    /// A horizontal rule is inserted after the header when importing.
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            separator: ',',
+            header: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Returns the options for tab separated values.
+    pub fn tsv() -> Self {
+        CsvOptions {
+            separator: '\t',
+            ..CsvOptions::default()
+        }
+    }
+}
+
+/// Reads an org table from CSV data.
+///
+/// Returns an error with the kind [`InvalidData`](`io::ErrorKind::InvalidData`) if the data is
+/// not valid UTF-8 or a quoted field is not closed. Empty lines are skipped.
+pub fn from_csv(mut reader: impl Read, options: &CsvOptions) -> io::Result<Table> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    let records = parse_records(&data, options.separator)?;
+    let mut rows = Vec::new();
+    for (index, record) in records.into_iter().enumerate() {
+        let cells = record
+            .iter()
+            .map(|field| parse_table_cell(&field.replace("\r\n", " ").replace('\n', " ")))
+            .collect();
+        rows.push(TableRow {
+            kind: TableRowKind::Normal(Spanned::new(cells)),
+        });
+        if index == 0 && options.header {
+            rows.push(TableRow {
+                kind: TableRowKind::Rule,
+            });
+        }
+    }
+    Ok(Table::new(Spanned::new(rows)))
+}
+
+/// Writes the rows of an org table as CSV data (one line per row).
+///
+/// Horizontal rules are left out and short rows are filled with empty fields. Returns an
+/// error with the kind [`InvalidInput`](`io::ErrorKind::InvalidInput`) for table.el tables.
+pub fn to_csv(table: &Table, mut writer: impl Write, options: &CsvOptions) -> io::Result<()> {
+    if let TableKind::TableEl { .. } = table.kind {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "table.el tables can't be converted to CSV",
+        ));
+    }
+    let rows: Vec<Vec<String>> = table
+        .content
+        .value()
+        .iter()
+        .filter_map(|content| match content {
+            TableContent::Org(TableRow {
+                kind: TableRowKind::Normal(cells),
+            }) => Some(
+                cells
+                    .value()
+                    .iter()
+                    .map(|cell| cell.to_string().trim().replace("\\vert{}", "|"))
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in rows {
+        let fields: Vec<_> = (0..columns)
+            .map(|column| {
+                quote(
+                    row.get(column).map_or("", String::as_str),
+                    options.separator,
+                )
+            })
+            .collect();
+        writeln!(writer, "{}", fields.join(&options.separator.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Quotes the field if it contains the separator, double quotes or line breaks.
+fn quote(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits the data into records and fields.
+fn parse_records(data: &str, separator: char) -> io::Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    // `true` if the current record contains anything (even an empty quoted field)
+    let mut started = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                started = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "unclosed quoted field",
+                            ))
+                        }
+                    }
+                }
+            }
+            c if c == separator => {
+                started = true;
+                record.push(std::mem::take(&mut field));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                if started || !field.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                started = false;
+            }
+            c => field.push(c),
+        }
+    }
+    if started || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_csv() {
+        let csv = "Name,Note\r\n\"Smith, J.\",\"says \"\"hi\"\"\"\n\nx|y,\"two\nlines\"\n,\n";
+        let options = CsvOptions {
+            header: true,
+            ..CsvOptions::default()
+        };
+        let table = from_csv(csv.as_bytes(), &options).unwrap();
+        assert_eq!(
+            table.format(),
+            "| Name      | Note      |\n\
+             |-----------+-----------|\n\
+             | Smith, J. | says \"hi\" |\n\
+             | x\\vert{}y | two lines |\n\
+             |           |           |"
+        );
+
+        let mut output = Vec::new();
+        table.to_csv(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Name,Note\n\"Smith, J.\",\"says \"\"hi\"\"\"\nx|y,two lines\n,\n"
+        );
+
+        let mut output = Vec::new();
+        let table = from_csv("a\tb\n1\n".as_bytes(), &CsvOptions::tsv()).unwrap();
+        table
+            .to_csv_with_options(&mut output, &CsvOptions::tsv())
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a\tb\n1\t\n");
+
+        let error = from_csv("\"open".as_bytes(), &CsvOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
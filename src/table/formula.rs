@@ -0,0 +1,881 @@
+//! Evaluating the `#+TBLFM:` formulas of org tables.
+//!
+//! Only a subset of the formulas org mode supports (with Emacs Calc) is implemented:
+//!
+//! - column formulas (`$4=$2*$3`) and field formulas (`@>$4=vsum(@I..@II)`)
+//! - the arithmetic operators `+`, `-`, `*`, `/` and `^` and parentheses
+//! - references to fields (`$2`, `@2$3`, `@-1`, `$>`, `@<$1`) and ranges (`@2$1..@4$1`,
+//!   `@I..@II`)
+//! - the functions `vsum`, `vmean`, `vmin`, `vmax` and `vcount`
+//! - the formats `%.Nf` and `%d` and the flag `N` after a semicolon (`$3=$1/$2;%.2f`)
+//!
+//! Like in org mode rows are counted without the horizontal rules (`@1` is the first row)
+//! and column formulas don't change the header rows (the rows before the first horizontal
+//! rule). Column formulas are applied before field formulas. Empty fields are zero and are
+//! left out of ranges.
+//!
+//! ```
+//! use rust_orgmode::table::formula::recalculate;
+//! use rust_orgmode::types::document::Document;
+//! use rust_orgmode::types::ElementSet;
+//!
+//! let mut document = Document::parse("| 2 | 3 | |\n#+TBLFM: $3=$1*$2\n").unwrap();
+//! let table = match &document.preface.as_mut().unwrap().content_mut()[0] {
+//!     ElementSet::Table(table) => recalculate(table).unwrap(),
+//!     _ => unreachable!(),
+//! };
+//! assert_eq!(table.to_string(), "| 2 | 3 | 6 |\n#+TBLFM: $3=$1*$2");
+//! ```
+
+use crate::parsing::parse_table_cell;
+use crate::types::greater_elements::{Table, TableContent, TableKind, TableRowKind};
+use crate::types::objects::TableCell;
+use std::fmt;
+use std::str::FromStr;
+
+/// A formula of a `#+TBLFM:` line (e.g. `$3=$1+$2;%.2f`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub target: Target,
+    pub expression: Expression,
+    /// The format after the semicolon (e.g. `%.2f`).
+    pub format: Option<String>,
+}
+
+/// The fields a [`Formula`] changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// `$N`: all fields of the column (except the header rows).
+    Column(ColumnRef),
+    /// `@R$C`: a single field.
+    Field(RowRef, ColumnRef),
+}
+
+/// The expression of a [`Formula`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    /// A single field.
+    Reference(Reference),
+    /// All fields in the rectangle between two fields (`@2$1..@4$3`).
+    Range(Reference, Reference),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+/// A reference to a field. Missing parts refer to the row or column of the computed field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Reference {
+    pub row: Option<RowRef>,
+    pub column: Option<ColumnRef>,
+}
+
+/// A row of a [`Reference`] (after `@`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowRef {
+    /// `@N`: the Nth row (starting at 1, horizontal rules are not counted).
+    Absolute(usize),
+    /// `@-N` or `@+N`: relative to the computed field.
+    Relative(isize),
+    /// `@<`
+    First,
+    /// `@>`
+    Last,
+    /// `@I`, `@II`, ...: the Nth horizontal rule. Only allowed in ranges.
+    Hline(usize),
+}
+
+/// A column of a [`Reference`] (after `$`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnRef {
+    /// `$N`: the Nth column (starting at 1).
+    Absolute(usize),
+    /// `$-N` or `$+N`: relative to the computed field.
+    Relative(isize),
+    /// `$<`
+    First,
+    /// `$>`
+    Last,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+/// The functions of a [`Expression::Call`]. All functions take ranges (and numbers).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Function {
+    /// `vsum`
+    Sum,
+    /// `vmean`
+    Mean,
+    /// `vmin`
+    Min,
+    /// `vmax`
+    Max,
+    /// `vcount`
+    Count,
+}
+
+/// The error returned when parsing or evaluating formulas fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormulaError {
+    /// The formula is not valid.
+    Syntax(String),
+    /// The formula uses a feature that is not implemented (e.g. Emacs Lisp formulas, named
+    /// fields or remote references).
+    Unsupported(String),
+    /// A reference points outside of the table.
+    InvalidReference(String),
+    /// A referenced field doesn't contain a number.
+    NotANumber(String),
+    /// The expression can't be computed (e.g. a division by zero).
+    Evaluation(String),
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormulaError::Syntax(formula) => write!(f, "invalid formula {:?}", formula),
+            FormulaError::Unsupported(formula) => write!(f, "unsupported formula {:?}", formula),
+            FormulaError::InvalidReference(reference) => {
+                write!(f, "{} is outside of the table", reference)
+            }
+            FormulaError::NotANumber(text) => write!(f, "{:?} is not a number", text),
+            FormulaError::Evaluation(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+/// Parses the formulas of a `#+TBLFM:` line (separated by `::`).
+pub fn parse_formulas(line: &str) -> Result<Vec<Formula>, FormulaError> {
+    line.split("::")
+        .map(str::trim)
+        .filter(|formula| !formula.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+impl FromStr for Formula {
+    type Err = FormulaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax_error = || FormulaError::Syntax(s.to_string());
+        let equals = s.find('=').ok_or_else(syntax_error)?;
+        let (target, rest) = (s[..equals].trim(), &s[equals + 1..]);
+        let (expression, format) = match rest.rfind(';') {
+            Some(index) => (&rest[..index], Some(rest[index + 1..].trim().to_string())),
+            None => (rest, None),
+        };
+        if expression.trim_start().starts_with('\'') {
+            return Err(FormulaError::Unsupported(s.to_string()));
+        }
+
+        let mut parser = Parser::new(target, s);
+        let reference = parser.reference()?;
+        if !parser.at_end() {
+            return Err(FormulaError::Unsupported(s.to_string()));
+        }
+        let target = match (reference.row, reference.column) {
+            (None, Some(column)) => Target::Column(column),
+            (Some(row), Some(column)) => Target::Field(row, column),
+            _ => return Err(FormulaError::Unsupported(s.to_string())),
+        };
+
+        let mut parser = Parser::new(expression, s);
+        let expression = parser.expression()?;
+        if !parser.at_end() {
+            return Err(syntax_error());
+        }
+        Ok(Formula {
+            target,
+            expression,
+            format,
+        })
+    }
+}
+
+/// A recursive descent parser for the expressions of formulas.
+struct Parser<'a> {
+    s: &'a str,
+    position: usize,
+    /// The whole formula (for errors).
+    formula: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str, formula: &'a str) -> Self {
+        Parser {
+            s,
+            position: 0,
+            formula,
+        }
+    }
+
+    fn error(&self) -> FormulaError {
+        FormulaError::Syntax(self.formula.to_string())
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    /// Consumes the string if the rest starts with it (directly, whitespace is not skipped).
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.position += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the characters while the predicate is true.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+
+    /// `term (('+' | '-') term)*`
+    fn expression(&mut self) -> Result<Expression, FormulaError> {
+        let mut left = self.term()?;
+        loop {
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.term()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    /// `factor (('*' | '/') factor)*`
+    fn term(&mut self) -> Result<Expression, FormulaError> {
+        let mut left = self.factor()?;
+        loop {
+            let operator = match self.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.factor()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    /// `'-' factor | primary ('^' factor)?`
+    fn factor(&mut self) -> Result<Expression, FormulaError> {
+        if self.peek() == Some('-') {
+            self.position += 1;
+            return Ok(Expression::Negate(Box::new(self.factor()?)));
+        }
+        let base = self.primary()?;
+        if self.peek() == Some('^') {
+            self.position += 1;
+            let exponent = self.factor()?;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    /// A number, a parenthesized expression, a function call, a reference or a range.
+    fn primary(&mut self) -> Result<Expression, FormulaError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let expression = self.expression()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error());
+                }
+                self.position += 1;
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| self.error())
+            }
+            Some('@') | Some('$') => {
+                let start = self.reference()?;
+                if self.eat("..") {
+                    Ok(Expression::Range(start, self.reference()?))
+                } else {
+                    Ok(Expression::Reference(start))
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric());
+                let function = match name {
+                    "vsum" => Function::Sum,
+                    "vmean" => Function::Mean,
+                    "vmin" => Function::Min,
+                    "vmax" => Function::Max,
+                    "vcount" => Function::Count,
+                    _ => return Err(FormulaError::Unsupported(self.formula.to_string())),
+                };
+                if self.peek() != Some('(') {
+                    return Err(self.error());
+                }
+                self.position += 1;
+                let mut arguments = vec![self.expression()?];
+                while self.peek() == Some(',') {
+                    self.position += 1;
+                    arguments.push(self.expression()?);
+                }
+                if self.peek() != Some(')') {
+                    return Err(self.error());
+                }
+                self.position += 1;
+                Ok(Expression::Call(function, arguments))
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    /// `('@' ROW)? ('$' COLUMN)?` (at least one of them)
+    fn reference(&mut self) -> Result<Reference, FormulaError> {
+        self.skip_whitespace();
+        let row = if self.eat("@") {
+            Some(self.row()?)
+        } else {
+            None
+        };
+        let column = if self.eat("$") {
+            Some(self.column()?)
+        } else {
+            None
+        };
+        if row.is_none() && column.is_none() {
+            return Err(self.error());
+        }
+        Ok(Reference { row, column })
+    }
+
+    fn row(&mut self) -> Result<RowRef, FormulaError> {
+        if self.eat("<") {
+            return Ok(RowRef::First);
+        }
+        if self.eat(">") {
+            return Ok(RowRef::Last);
+        }
+        let hlines = self.take_while(|c| c == 'I');
+        if !hlines.is_empty() {
+            return Ok(RowRef::Hline(hlines.len()));
+        }
+        match self.offset()? {
+            Offset::Absolute(number) => Ok(RowRef::Absolute(number)),
+            Offset::Relative(delta) => Ok(RowRef::Relative(delta)),
+        }
+    }
+
+    fn column(&mut self) -> Result<ColumnRef, FormulaError> {
+        if self.eat("<") {
+            return Ok(ColumnRef::First);
+        }
+        if self.eat(">") {
+            return Ok(ColumnRef::Last);
+        }
+        if self.rest().starts_with(|c: char| c.is_ascii_alphabetic()) {
+            // named fields and columns
+            return Err(FormulaError::Unsupported(self.formula.to_string()));
+        }
+        match self.offset()? {
+            Offset::Absolute(number) => Ok(ColumnRef::Absolute(number)),
+            Offset::Relative(delta) => Ok(ColumnRef::Relative(delta)),
+        }
+    }
+
+    /// `N`, `+N` or `-N`
+    fn offset(&mut self) -> Result<Offset, FormulaError> {
+        let sign = if self.eat("-") {
+            Some(-1)
+        } else if self.eat("+") {
+            Some(1)
+        } else {
+            None
+        };
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        let number: usize = digits.parse().map_err(|_| self.error())?;
+        Ok(match sign {
+            Some(sign) => Offset::Relative(sign * number as isize),
+            None => Offset::Absolute(number),
+        })
+    }
+}
+
+enum Offset {
+    Absolute(usize),
+    Relative(isize),
+}
+
+/// The value of an expression.
+enum Value {
+    Number(f64),
+    Vector(Vec<f64>),
+}
+
+/// The fields of an org table.
+struct Grid {
+    /// The rows (without horizontal rules).
+    rows: Vec<Vec<String>>,
+    /// The number of rows before each horizontal rule.
+    hlines: Vec<usize>,
+    columns: usize,
+}
+
+impl Grid {
+    fn new(table: &Table) -> Self {
+        let mut rows = Vec::new();
+        let mut hlines = Vec::new();
+        for content in table.content.value() {
+            match content {
+                TableContent::Org(row) => match &row.kind {
+                    TableRowKind::Normal(cells) => rows.push(
+                        cells
+                            .value()
+                            .iter()
+                            .map(|cell| cell.to_string().trim().to_string())
+                            .collect(),
+                    ),
+                    TableRowKind::Rule => hlines.push(rows.len()),
+                },
+                TableContent::TableEl(_) => {}
+            }
+        }
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        Grid {
+            rows,
+            hlines,
+            columns,
+        }
+    }
+
+    /// Returns the row index (starting at 0) or `None` if it is outside of the table.
+    fn row(&self, row: RowRef, current: usize) -> Option<usize> {
+        let index = match row {
+            RowRef::Absolute(number) => number.checked_sub(1)?,
+            RowRef::Relative(delta) => (current as isize + delta) as usize,
+            RowRef::First => 0,
+            RowRef::Last => self.rows.len().checked_sub(1)?,
+            RowRef::Hline(_) => return None,
+        };
+        if index < self.rows.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the column index (starting at 0) or `None` if it is outside of the table.
+    fn column(&self, column: ColumnRef, current: usize) -> Option<usize> {
+        let index = match column {
+            ColumnRef::Absolute(number) => number.checked_sub(1)?,
+            ColumnRef::Relative(delta) => (current as isize + delta) as usize,
+            ColumnRef::First => 0,
+            ColumnRef::Last => self.columns.checked_sub(1)?,
+        };
+        if index < self.columns {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the row and column index of a reference.
+    fn field(
+        &self,
+        reference: &Reference,
+        (row, column): (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let row = match reference.row {
+            Some(reference) => self.row(reference, row)?,
+            None => row,
+        };
+        let column = match reference.column {
+            Some(reference) => self.column(reference, column)?,
+            None => column,
+        };
+        Some((row, column))
+    }
+
+    /// Returns the row index of the start (`start == true`) or end of a range.
+    ///
+    /// Horizontal rules are the first row after or the last row before the rule.
+    fn range_row(&self, row: Option<RowRef>, current: usize, start: bool) -> Option<usize> {
+        match row {
+            Some(RowRef::Hline(number)) => {
+                let before = *self.hlines.get(number - 1)?;
+                if start {
+                    Some(before)
+                } else {
+                    before.checked_sub(1)
+                }
+            }
+            Some(row) => self.row(row, current),
+            None => Some(current),
+        }
+    }
+
+    fn get(&self, row: usize, column: usize) -> &str {
+        self.rows[row].get(column).map_or("", String::as_str)
+    }
+
+    fn set(&mut self, row: usize, column: usize, value: String) {
+        let cells = &mut self.rows[row];
+        if cells.len() <= column {
+            cells.resize(column + 1, String::new());
+        }
+        cells[column] = value;
+    }
+}
+
+/// Returns the number in the field. Empty fields are zero.
+fn number(text: &str, flags: &Flags) -> Result<Option<f64>, FormulaError> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse() {
+        Ok(number) => Ok(Some(number)),
+        Err(_) if flags.non_numbers_are_zero => Ok(Some(0.0)),
+        Err(_) => Err(FormulaError::NotANumber(text.to_string())),
+    }
+}
+
+/// The settings of the format of a [`Formula`].
+#[derive(Default)]
+struct Flags {
+    /// `N`: fields which don't contain numbers are zero.
+    non_numbers_are_zero: bool,
+    /// The number of digits after the decimal point (`%.Nf` or `%d`).
+    precision: Option<usize>,
+}
+
+impl Flags {
+    fn new(format: Option<&str>) -> Self {
+        let mut flags = Flags::default();
+        let format = match format {
+            Some(format) => format,
+            None => return flags,
+        };
+        if let Some(index) = format.find('%') {
+            let spec = &format[index + 1..];
+            if spec.starts_with('d') {
+                flags.precision = Some(0);
+            } else if let Some(precision) = spec.strip_prefix('.') {
+                let digits: String = precision.chars().take_while(char::is_ascii_digit).collect();
+                flags.precision = digits.parse().ok();
+            }
+        }
+        flags.non_numbers_are_zero =
+            format[..format.find('%').unwrap_or(format.len())].contains('N');
+        flags
+    }
+
+    fn format(&self, value: f64) -> String {
+        match self.precision {
+            Some(precision) => format!("{:.*}", precision, value),
+            None if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", value as i64),
+            None => {
+                let text = format!("{:.10}", value);
+                text.trim_end_matches('0').trim_end_matches('.').to_string()
+            }
+        }
+    }
+}
+
+fn evaluate(
+    expression: &Expression,
+    grid: &Grid,
+    field: (usize, usize),
+    flags: &Flags,
+) -> Result<Value, FormulaError> {
+    let invalid =
+        |reference: &dyn fmt::Debug| FormulaError::InvalidReference(format!("{:?}", reference));
+    match expression {
+        Expression::Number(number) => Ok(Value::Number(*number)),
+        Expression::Reference(reference) => {
+            let (row, column) = grid
+                .field(reference, field)
+                .ok_or_else(|| invalid(reference))?;
+            let number = number(grid.get(row, column), flags)?;
+            Ok(Value::Number(number.unwrap_or(0.0)))
+        }
+        Expression::Range(start, end) => {
+            let rows = (
+                grid.range_row(start.row, field.0, true)
+                    .ok_or_else(|| invalid(start))?,
+                grid.range_row(end.row, field.0, false)
+                    .ok_or_else(|| invalid(end))?,
+            );
+            let column = |reference: &Reference| match reference.column {
+                Some(column) => grid
+                    .column(column, field.1)
+                    .ok_or_else(|| invalid(reference)),
+                None => Ok(field.1),
+            };
+            let columns = (column(start)?, column(end)?);
+            let mut numbers = Vec::new();
+            for row in rows.0.min(rows.1)..=rows.0.max(rows.1) {
+                for column in columns.0.min(columns.1)..=columns.0.max(columns.1) {
+                    numbers.extend(number(grid.get(row, column), flags)?);
+                }
+            }
+            Ok(Value::Vector(numbers))
+        }
+        Expression::Negate(expression) => {
+            let value = scalar(evaluate(expression, grid, field, flags)?)?;
+            Ok(Value::Number(-value))
+        }
+        Expression::Binary(operator, left, right) => {
+            let left = scalar(evaluate(left, grid, field, flags)?)?;
+            let right = scalar(evaluate(right, grid, field, flags)?)?;
+            let value = match operator {
+                Operator::Add => left + right,
+                Operator::Subtract => left - right,
+                Operator::Multiply => left * right,
+                Operator::Divide if right == 0.0 => {
+                    return Err(FormulaError::Evaluation("division by zero".to_string()))
+                }
+                Operator::Divide => left / right,
+                Operator::Power => left.powf(right),
+            };
+            Ok(Value::Number(value))
+        }
+        Expression::Call(function, arguments) => {
+            let mut numbers = Vec::new();
+            for argument in arguments {
+                match evaluate(argument, grid, field, flags)? {
+                    Value::Number(number) => numbers.push(number),
+                    Value::Vector(vector) => numbers.extend(vector),
+                }
+            }
+            let empty = || FormulaError::Evaluation(format!("{:?} of nothing", function));
+            let value = match function {
+                Function::Sum => numbers.iter().sum(),
+                Function::Mean if numbers.is_empty() => return Err(empty()),
+                Function::Mean => numbers.iter().sum::<f64>() / numbers.len() as f64,
+                Function::Min => numbers
+                    .iter()
+                    .cloned()
+                    .fold(None, |min: Option<f64>, x| {
+                        Some(min.map_or(x, |min| min.min(x)))
+                    })
+                    .ok_or_else(empty)?,
+                Function::Max => numbers
+                    .iter()
+                    .cloned()
+                    .fold(None, |max: Option<f64>, x| {
+                        Some(max.map_or(x, |max| max.max(x)))
+                    })
+                    .ok_or_else(empty)?,
+                Function::Count => numbers.len() as f64,
+            };
+            Ok(Value::Number(value))
+        }
+    }
+}
+
+fn scalar(value: Value) -> Result<f64, FormulaError> {
+    match value {
+        Value::Number(number) => Ok(number),
+        Value::Vector(_) => Err(FormulaError::Evaluation(
+            "ranges can only be used in functions".to_string(),
+        )),
+    }
+}
+
+/// Applies the formulas of the table and returns the table with the computed fields.
+///
+/// Fields are computed in the order of the formulas (column formulas first) and later
+/// formulas see the values computed before. Tables without formulas are returned unchanged.
+pub fn recalculate(table: &Table) -> Result<Table, FormulaError> {
+    let formulas = match &table.kind {
+        TableKind::Org { formulas } => formulas,
+        TableKind::TableEl { .. } => return Ok(table.clone()),
+    };
+    let mut parsed = Vec::new();
+    for line in formulas {
+        parsed.extend(parse_formulas(line)?);
+    }
+    let (columns, fields): (Vec<_>, Vec<_>) = parsed.into_iter().partition(|formula| match formula
+        .target
+    {
+        Target::Column(_) => true,
+        Target::Field(..) => false,
+    });
+
+    let mut grid = Grid::new(table);
+    let mut changed = Vec::new();
+    // the rows before the first horizontal rule are the header
+    let first_row = grid
+        .hlines
+        .first()
+        .cloned()
+        .filter(|&header| header < grid.rows.len())
+        .unwrap_or(0);
+    for formula in columns.iter().chain(&fields) {
+        let flags = Flags::new(formula.format.as_deref());
+        let targets = match formula.target {
+            Target::Column(column) => {
+                let column = grid
+                    .column(column, 0)
+                    .ok_or_else(|| FormulaError::InvalidReference(format!("{:?}", column)))?;
+                (first_row..grid.rows.len())
+                    .map(|row| (row, column))
+                    .collect()
+            }
+            Target::Field(row, column) => {
+                let reference = Reference {
+                    row: Some(row),
+                    column: Some(column),
+                };
+                let field = grid
+                    .field(&reference, (0, 0))
+                    .ok_or_else(|| FormulaError::InvalidReference(format!("{:?}", reference)))?;
+                vec![field]
+            }
+        };
+        for field in targets {
+            let value = scalar(evaluate(&formula.expression, &grid, field, &flags)?)?;
+            grid.set(field.0, field.1, flags.format(value));
+            changed.push(field);
+        }
+    }
+
+    let mut table = table.clone();
+    let rows = table
+        .content_mut()
+        .iter_mut()
+        .filter_map(|content| match content {
+            TableContent::Org(row) => match &mut row.kind {
+                TableRowKind::Normal(cells) => Some(cells),
+                TableRowKind::Rule => None,
+            },
+            TableContent::TableEl(_) => None,
+        });
+    for (index, cells) in rows.enumerate() {
+        for &(_, column) in changed.iter().filter(|(row, _)| *row == index) {
+            let cells = cells.get_mut_value();
+            if cells.len() <= column {
+                cells.resize(column + 1, TableCell::new(Vec::new()));
+            }
+            cells[column] = parse_table_cell(grid.get(index, column));
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::document::Document;
+    use crate::types::ElementSet;
+
+    fn parse_table(text: &str) -> Table {
+        let document = Document::parse(text).unwrap();
+        match &document.preface.unwrap().content.value()[0] {
+            ElementSet::Table(table) => (**table).clone(),
+            element => panic!("expected a table, got {:?}", element),
+        }
+    }
+
+    #[test]
+    fn recalculate_table() {
+        let text = "| Item | Qty | Price | Total |\n\
+                    |-\n\
+                    | a | 2 | 1.5 | |\n\
+                    | b | 3 | 2 | |\n\
+                    |-\n\
+                    | Sum | | | |\n\
+                    #+TBLFM: $4=$2*$3 :: @>$4=vsum(@I..@II)\n\
+                    #+TBLFM: @>$2=vmean(@2$2..@-1$2);%.2f :: @>$3=(@-1 + 1) ^ 2 / 2";
+        let table = recalculate(&parse_table(text)).unwrap();
+        assert_eq!(
+            table.to_string(),
+            text.replace("| a | 2 | 1.5 | |", "| a | 2 | 1.5 | 3 |")
+                .replace("| b | 3 | 2 | |", "| b | 3 | 2 | 6 |")
+                .replace("| Sum | | | |", "| Sum | 2.50 | 4.5 | 9 |")
+        );
+    }
+
+    #[test]
+    fn parse_formula() {
+        assert_eq!(
+            "$3=vsum($1..$2)".parse::<Formula>().unwrap(),
+            Formula {
+                target: Target::Column(ColumnRef::Absolute(3)),
+                expression: Expression::Call(
+                    Function::Sum,
+                    vec![Expression::Range(
+                        Reference {
+                            row: None,
+                            column: Some(ColumnRef::Absolute(1)),
+                        },
+                        Reference {
+                            row: None,
+                            column: Some(ColumnRef::Absolute(2)),
+                        },
+                    )],
+                ),
+                format: None,
+            }
+        );
+    }
+
+    #[test]
+    fn lisp_formula() {
+        assert_eq!(
+            "$2='(+ $1 1)".parse::<Formula>(),
+            Err(FormulaError::Unsupported("$2='(+ $1 1)".to_string()))
+        );
+    }
+
+    #[test]
+    fn not_a_number() {
+        let error = recalculate(&parse_table("| x | |\n#+TBLFM: $2=$1+1\n"));
+        assert_eq!(
+            error.unwrap_err(),
+            FormulaError::NotANumber("x".to_string())
+        );
+    }
+
+    #[test]
+    fn non_numbers_as_zero() {
+        let table = recalculate(&parse_table("| x | |\n#+TBLFM: $2=$1+1;N\n")).unwrap();
+        assert_eq!(table.to_string(), "| x | 1 |\n#+TBLFM: $2=$1+1;N");
+    }
+}
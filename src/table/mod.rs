@@ -0,0 +1,7 @@
+//! Aligning org [`Table`]s, computing their formulas and converting them from and to CSV.
+//!
+//! [`Table`]: `crate::types::greater_elements::Table`
+
+pub mod align;
+pub mod csv;
+pub mod formula;
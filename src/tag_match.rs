@@ -0,0 +1,633 @@
+//! Tag and property match expressions.
+//!
+//! These are the match strings used by org mode for tag searches, sparse trees and agenda
+//! filtering (see [Matching tags and properties] in the org manual), e.g.
+//!
+//! ```text
+//! +work-boss|+project&TODO="NEXT"+Effort<"0:30"
+//! ```
+//!
+//! The syntax is:
+//!
+//! - `|` separates alternatives. A headline matches if any alternative matches.
+//! - An alternative is a list of terms, optionally separated by `&`. All terms have to match.
+//!   A term prefixed with `-` (or `!`) has to not match. A term prefixed with `+` (or without
+//!   prefix) has to match.
+//! - A term is one of:
+//!   - a tag name (`work`),
+//!   - a regex in braces matching any tag (`{^proj}`),
+//!   - a property comparison `NAME OP VALUE` where `OP` is one of `=`, `<>`, `!=`, `<`, `<=`,
+//!     `>`, `>=`. `VALUE` is a number, a string in double quotes, a timestamp in double quotes
+//!     (`"<2018-10-12>"`) or a regex in braces (only with `=` and `<>`).
+//! - A match can end with `/` followed by a match on the todo keyword only (`/TODO|NEXT`,
+//!   `/-DONE`). If it starts with `!` (`/!TODO`) only headlines with a todo keyword match.
+//!
+//! The special properties `TODO`, `LEVEL`, `PRIORITY`, `TAGS`, `SCHEDULED`, `DEADLINE` and
+//! `CLOSED` are taken from the headline itself. All other properties are looked up in the
+//! property drawer. Missing properties are compared as an empty string (or as `0` when compared
+//! with a number). Comparisons of the `EFFORT` property with a duration (`"0:30"`) compare
+//! minutes.
+//!
+//! [Matching tags and properties]: https://orgmode.org/manual/Matching-tags-and-properties.html
+
+use crate::types::document::Document;
+use crate::types::elements::OrgDuration;
+use crate::types::greater_elements::{Headline, SubtreeFilter, TodoKeyword};
+use crate::types::objects::Timestamp;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed tag/property match expression.
+///
+/// Use [`TagMatch::matches`] as predicate for headlines.
+#[derive(Debug, Clone)]
+pub struct TagMatch {
+    /// The alternatives separated by `|`. An empty list matches every headline.
+    pub alternatives: Vec<Vec<Term>>,
+    pub todo: Option<TodoMatch>,
+}
+
+/// The part of a [`TagMatch`] after `/`.
+#[derive(Debug, Clone)]
+pub struct TodoMatch {
+    /// Only headlines with a todo keyword (that isn't done) match (`/!`).
+    pub only_todo: bool,
+    /// The alternatives separated by `|`. Only [`TermKind::Tag`] and [`TermKind::TagRegex`]
+    /// terms which match the todo keyword.
+    pub alternatives: Vec<Vec<Term>>,
+}
+
+/// A single (possibly negated) condition of a [`TagMatch`].
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub negated: bool,
+    pub kind: TermKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum TermKind {
+    /// The headline has the tag.
+    Tag(String),
+    /// The headline has a tag matching the regex.
+    TagRegex(Regex),
+    /// A property of the headline compares to the value.
+    Property {
+        name: String,
+        op: CompareOp,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// The value on the right hand side of a property comparison.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Time(NaiveDateTime),
+    Regex(Regex),
+}
+
+/// The error returned when parsing a [`TagMatch`] fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseTagMatchError {
+    /// The byte offset in the match string.
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseTagMatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid match at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseTagMatchError {}
+
+impl TagMatch {
+    /// Returns `true` if the headline matches (only using the tags of the headline itself).
+    pub fn matches(&self, headline: &Headline) -> bool {
+        self.matches_with_tags(headline, &headline.tags)
+    }
+
+    /// Returns `true` if the headline matches using the given tags (e.g. including tags
+    /// inherited from parent headlines).
+    pub fn matches_with_tags(&self, headline: &Headline, tags: &[String]) -> bool {
+        let tags_match = self.alternatives.is_empty()
+            || self
+                .alternatives
+                .iter()
+                .any(|terms| terms.iter().all(|term| term.matches(headline, tags)));
+        tags_match
+            && self
+                .todo
+                .as_ref()
+                .map(|todo| todo.matches(headline))
+                .unwrap_or(true)
+    }
+
+    /// Returns the matching headlines of the document in document order (like a tags search).
+    ///
+    /// Headlines inherit the tags of their ancestors and the `#+FILETAGS:` of the document (see
+    /// [`Headline::tags_with_inheritance`]). Only the subtrees selected by `filter` are searched.
+    pub fn search<'a>(&self, document: &'a Document, filter: SubtreeFilter) -> Vec<&'a Headline> {
+        fn search<'a>(
+            tag_match: &TagMatch,
+            headlines: impl IntoIterator<Item = &'a Headline>,
+            inherited: &[String],
+            filter: SubtreeFilter,
+            result: &mut Vec<&'a Headline>,
+        ) {
+            for headline in headlines {
+                if filter.skips(headline) {
+                    continue;
+                }
+                let mut tags = inherited.to_vec();
+                tags.extend(headline.tags.iter().cloned());
+                if tag_match.matches_with_tags(headline, &tags) {
+                    result.push(headline);
+                }
+                search(tag_match, headline.children(), &tags, filter, result);
+            }
+        }
+
+        let mut result = Vec::new();
+        let file_tags = document.file_tags();
+        search(self, &document.headlines, &file_tags, filter, &mut result);
+        result
+    }
+}
+
+impl TodoMatch {
+    fn matches(&self, headline: &Headline) -> bool {
+        let keyword = match &headline.todo_keyword {
+            Some(TodoKeyword::Todo(keyword)) => keyword,
+            Some(TodoKeyword::Done(_)) if self.only_todo => return false,
+            Some(TodoKeyword::Done(keyword)) => keyword,
+            None if self.only_todo => return false,
+            None => "",
+        };
+        let keywords = [keyword.to_string()];
+        self.alternatives.is_empty()
+            || self
+                .alternatives
+                .iter()
+                .any(|terms| terms.iter().all(|term| term.matches(headline, &keywords)))
+    }
+}
+
+impl Term {
+    fn matches(&self, headline: &Headline, tags: &[String]) -> bool {
+        let matches = match &self.kind {
+            TermKind::Tag(tag) => tags.contains(tag),
+            TermKind::TagRegex(regex) => tags.iter().any(|tag| regex.is_match(tag)),
+            TermKind::Property { name, op, value } => {
+                compare(&property(headline, tags, name), name, *op, value)
+            }
+        };
+        matches != self.negated
+    }
+}
+
+fn property(headline: &Headline, tags: &[String], name: &str) -> String {
+    let name = name.to_uppercase();
+    match name.as_str() {
+        "TODO" => match &headline.todo_keyword {
+            Some(TodoKeyword::Todo(keyword)) | Some(TodoKeyword::Done(keyword)) => keyword.clone(),
+            None => String::new(),
+        },
+        "LEVEL" => headline.level.to_string(),
+        "PRIORITY" => headline
+            .priority
+            .map(|priority| priority.to_string())
+            .unwrap_or_default(),
+        "TAGS" => {
+            if tags.is_empty() {
+                String::new()
+            } else {
+                format!(":{}:", tags.join(":"))
+            }
+        }
+        "SCHEDULED" | "DEADLINE" | "CLOSED" => {
+            let planning = headline.planning.as_ref();
+            let timestamp = match name.as_str() {
+                "SCHEDULED" => planning.and_then(|planning| planning.scheduled.as_ref()),
+                "DEADLINE" => planning.and_then(|planning| planning.deadline.as_ref()),
+                _ => planning.and_then(|planning| planning.closed.as_ref()),
+            };
+            timestamp.map(ToString::to_string).unwrap_or_default()
+        }
+        _ => headline.property(&name).unwrap_or_default().to_string(),
+    }
+}
+
+fn compare(actual: &str, name: &str, op: CompareOp, value: &Value) -> bool {
+    let ordering = match value {
+        Value::Regex(regex) => {
+            let matches = regex.is_match(actual);
+            return match op {
+                CompareOp::Equal => matches,
+                _ => !matches,
+            };
+        }
+        Value::Number(number) => {
+            let actual: f64 = actual.trim().parse().unwrap_or(0.0);
+            match actual.partial_cmp(number) {
+                Some(ordering) => ordering,
+                None => return false,
+            }
+        }
+        Value::Time(time) => match actual
+            .parse::<Timestamp>()
+            .ok()
+            .and_then(|timestamp| timestamp.start_date_time())
+        {
+            Some(actual) => actual.cmp(time),
+            None => return false,
+        },
+        Value::String(string) => match (duration(actual), duration(string)) {
+            (Some(actual), Some(value)) if name.eq_ignore_ascii_case("EFFORT") => {
+                actual.cmp(&value)
+            }
+            _ => actual.cmp(string.as_str()),
+        },
+    };
+    match op {
+        CompareOp::Equal => ordering == Ordering::Equal,
+        CompareOp::NotEqual => ordering != Ordering::Equal,
+        CompareOp::Less => ordering == Ordering::Less,
+        CompareOp::LessOrEqual => ordering != Ordering::Greater,
+        CompareOp::Greater => ordering == Ordering::Greater,
+        CompareOp::GreaterOrEqual => ordering != Ordering::Less,
+    }
+}
+
+/// Parses a duration like `1:30` (or plain minutes or units like `1h 30min`) to minutes.
+fn duration(s: &str) -> Option<i64> {
+    s.parse::<OrgDuration>()
+        .ok()
+        .map(|duration| duration.minutes())
+}
+
+impl FromStr for TagMatch {
+    type Err = ParseTagMatchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let alternatives = parser.alternatives(false)?;
+        let todo = if parser.eat('/') {
+            let only_todo = parser.eat('!');
+            Some(TodoMatch {
+                only_todo,
+                alternatives: parser.alternatives(true)?,
+            })
+        } else {
+            None
+        };
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(TagMatch { alternatives, todo })
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.input.len() - self.rest().trim_start().len();
+    }
+
+    fn error(&self, message: &'static str) -> ParseTagMatchError {
+        ParseTagMatchError {
+            position: self.pos,
+            message,
+        }
+    }
+
+    /// Parses alternatives separated by `|` up to `/` or the end of the input.
+    fn alternatives(&mut self, todo: bool) -> Result<Vec<Vec<Term>>, ParseTagMatchError> {
+        let mut alternatives = Vec::new();
+        let mut terms = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('/') => break,
+                Some('|') => {
+                    self.pos += 1;
+                    alternatives.push(std::mem::take(&mut terms));
+                }
+                Some('&') => self.pos += 1,
+                Some(_) => terms.push(self.term(todo)?),
+            }
+        }
+        if !terms.is_empty() || !alternatives.is_empty() {
+            alternatives.push(terms);
+        }
+        Ok(alternatives)
+    }
+
+    fn term(&mut self, todo: bool) -> Result<Term, ParseTagMatchError> {
+        let negated = match self.peek() {
+            Some('-') | Some('!') => true,
+            Some('+') => false,
+            _ => {
+                return Ok(Term {
+                    negated: false,
+                    kind: self.term_kind(todo)?,
+                })
+            }
+        };
+        self.pos += 1;
+        Ok(Term {
+            negated,
+            kind: self.term_kind(todo)?,
+        })
+    }
+
+    fn term_kind(&mut self, todo: bool) -> Result<TermKind, ParseTagMatchError> {
+        if self.peek() == Some('{') {
+            return Ok(TermKind::TagRegex(self.regex()?));
+        }
+        let name = self.name();
+        if name.is_empty() {
+            return Err(self.error("expected a tag, regex or property"));
+        }
+        match self.op() {
+            Some(_) if todo => Err(self.error("property comparisons are not allowed here")),
+            Some(op) => {
+                let value = self.value()?;
+                if let Value::Regex(_) = value {
+                    if op != CompareOp::Equal && op != CompareOp::NotEqual {
+                        return Err(self.error("regexes can only be compared with = and <>"));
+                    }
+                }
+                Ok(TermKind::Property {
+                    name: name.to_string(),
+                    op,
+                    value,
+                })
+            }
+            None => Ok(TermKind::Tag(name.to_string())),
+        }
+    }
+
+    fn name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || "_@#%-".contains(c)))
+            .unwrap_or(rest.len());
+        // a `-` in a tag is the start of the next (negated) term
+        let len = match rest[..len].find('-') {
+            Some(index) if !is_op_start(rest[len..].chars().next()) => index,
+            _ => len,
+        };
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn op(&mut self) -> Option<CompareOp> {
+        let ops = [
+            ("<=", CompareOp::LessOrEqual),
+            (">=", CompareOp::GreaterOrEqual),
+            ("<>", CompareOp::NotEqual),
+            ("!=", CompareOp::NotEqual),
+            ("==", CompareOp::Equal),
+            ("=", CompareOp::Equal),
+            ("<", CompareOp::Less),
+            (">", CompareOp::Greater),
+        ];
+        let (text, op) = ops.iter().find(|(text, _)| self.rest().starts_with(text))?;
+        self.pos += text.len();
+        Some(*op)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseTagMatchError> {
+        match self.peek() {
+            Some('{') => Ok(Value::Regex(self.regex()?)),
+            Some('"') => {
+                let end = self.rest()[1..]
+                    .find('"')
+                    .ok_or_else(|| self.error("unterminated string"))?;
+                let string = &self.rest()[1..=end];
+                let value = if string.starts_with('<') || string.starts_with('[') {
+                    let time = string
+                        .parse::<Timestamp>()
+                        .ok()
+                        .and_then(|timestamp| timestamp.start_date_time())
+                        .ok_or_else(|| self.error("invalid timestamp"))?;
+                    Value::Time(time)
+                } else {
+                    Value::String(string.to_string())
+                };
+                self.pos += end + 2;
+                Ok(value)
+            }
+            _ => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+                    .unwrap_or(rest.len());
+                let number = rest[..len]
+                    .parse()
+                    .map_err(|_| self.error("expected a number, string or regex"))?;
+                self.pos += len;
+                Ok(Value::Number(number))
+            }
+        }
+    }
+
+    fn regex(&mut self) -> Result<Regex, ParseTagMatchError> {
+        let end = self
+            .rest()
+            .find('}')
+            .ok_or_else(|| self.error("unterminated regex"))?;
+        let regex = Regex::new(&self.rest()[1..end]).map_err(|_| self.error("invalid regex"))?;
+        self.pos += end + 1;
+        Ok(regex)
+    }
+}
+
+fn is_op_start(c: Option<char>) -> bool {
+    match c {
+        Some(c) => "<>=!".contains(c),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(s: &str, headline: &Headline) -> bool {
+        s.parse::<TagMatch>().unwrap().matches(headline)
+    }
+
+    #[test]
+    fn tags() {
+        let work = Headline::builder().tag("work").tag("urgent").build();
+        let boss = Headline::builder().tag("work").tag("boss").build();
+        assert!(matches("work", &work));
+        assert!(matches("+work-boss", &work));
+        assert!(!matches("+work-boss", &boss));
+        assert!(matches("work&urgent", &work));
+        assert!(matches("home|boss", &boss));
+        assert!(matches("{^wo}-{^b}", &work));
+        assert!(!matches("{^wo}-{^b}", &boss));
+        assert!(matches("", &boss));
+    }
+
+    #[test]
+    fn search() {
+        let document = Document::parse(
+            "* Work :work:\n** TODO Report\n** COMMENT Draft\n*** TODO Notes\n\
+             * Old :ARCHIVE:work:\n** TODO Taxes\n",
+        )
+        .unwrap();
+        let titles = |headlines: Vec<&Headline>| {
+            headlines
+                .iter()
+                .map(|headline| headline.title.as_ref().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let tag_match: TagMatch = "work/TODO".parse().unwrap();
+        assert_eq!(
+            titles(tag_match.search(&document, SubtreeFilter::default())),
+            vec!["Report"]
+        );
+        assert_eq!(
+            titles(tag_match.search(&document, SubtreeFilter::all())),
+            vec!["Report", "Notes", "Taxes"]
+        );
+
+        let notes = tag_match.search(&document, SubtreeFilter::all())[1];
+        assert!(notes.is_effectively_commented(&document));
+        assert!(!notes.is_effectively_archived(&document));
+        assert!(!notes.is_effectively_commented(&Document::default()));
+    }
+
+    #[test]
+    fn inherited_tags() {
+        let document = Document::parse(
+            "#+FILETAGS: :notes:\n\
+             * Work :work:\n** Report :urgent:\n** Old :archive:\n* Home\n",
+        )
+        .unwrap();
+        let titles = |headlines: Vec<&Headline>| {
+            headlines
+                .iter()
+                .map(|headline| headline.title.as_ref().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(document.file_tags(), vec!["notes"]);
+        assert_eq!(
+            titles(document.headlines_with_tag("work")),
+            vec!["Work", "Report", "Old"]
+        );
+        assert_eq!(titles(document.headlines_with_tag("notes")).len(), 4);
+        let tag_match: TagMatch = "work&!archive".parse().unwrap();
+        let report = tag_match.search(&document, SubtreeFilter::all())[1];
+        assert_eq!(titles(vec![report]), vec!["Report"]);
+        assert_eq!(
+            report.tags_with_inheritance(&document),
+            vec!["notes", "work", "urgent"]
+        );
+        assert_eq!(tag_match.search(&document, SubtreeFilter::all()).len(), 2);
+    }
+
+    #[test]
+    fn properties() {
+        let next = Headline::builder()
+            .level(2)
+            .todo("NEXT")
+            .tag("project")
+            .property("Effort", "0:20")
+            .property("count", "3")
+            .build();
+        let long = Headline::builder()
+            .todo("NEXT")
+            .tag("project")
+            .property("Effort", "1:00")
+            .build();
+        let query = r#"+work-boss|+project&TODO="NEXT"+Effort<"0:30""#;
+        assert!(matches(query, &next));
+        assert!(!matches(query, &long));
+        assert!(matches("count>2&LEVEL=2", &next));
+        assert!(!matches("count>2", &long));
+        assert!(matches("TODO={N.XT}", &next));
+        assert!(matches("TODO<>{DONE}", &next));
+
+        let document = Document::parse("* a\nSCHEDULED: <2018-10-12 Fri>\n").unwrap();
+        let scheduled = &document.headlines[0];
+        assert!(matches(r#"SCHEDULED<"<2018-10-13>""#, scheduled));
+        assert!(!matches(r#"SCHEDULED>="<2018-10-13>""#, scheduled));
+        assert!(!matches(r#"DEADLINE<"<2018-10-13>""#, scheduled));
+    }
+
+    #[test]
+    fn todo_part() {
+        let todo = Headline::builder().todo("TODO").tag("work").build();
+        let done = Headline::builder().done("DONE").tag("work").build();
+        let plain = Headline::builder().tag("work").build();
+        assert!(matches("work/TODO|NEXT", &todo));
+        assert!(!matches("work/TODO|NEXT", &done));
+        assert!(matches("work/-DONE", &plain));
+        assert!(!matches("work/!-NEXT", &plain));
+        assert!(matches("work/!", &todo));
+        assert!(!matches("work/!", &done));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("work&".parse::<TagMatch>().is_ok());
+        assert_eq!(
+            "a=\"b".parse::<TagMatch>().unwrap_err(),
+            ParseTagMatchError {
+                position: 2,
+                message: "unterminated string",
+            }
+        );
+        assert!("a<{b}".parse::<TagMatch>().is_err());
+        assert!("{[}".parse::<TagMatch>().is_err());
+        assert!("/TODO=\"a\"".parse::<TagMatch>().is_err());
+        assert!("a)".parse::<TagMatch>().is_err());
+    }
+}
@@ -0,0 +1,377 @@
+//! Extracting the source blocks of a document into files (tangling, like org babel does).
+//!
+//! Every source block with a `:tangle` header argument other than `no` is written to the file
+//! named by the argument (`yes` uses the name of the org file with the extension of the
+//! language). Blocks tangled to the same file are joined in document order:
+//!
+//! ```text
+//! #+PROPERTY: header-args:rust :tangle src/main.rs :mkdirp yes
+//!
+//! #+BEGIN_SRC rust :noweb yes
+//! fn main() {
+//!     <<body>>
+//! }
+//! #+END_SRC
+//!
+//! #+NAME: body
+//! #+BEGIN_SRC rust :tangle no
+//! println!("Hello");
+//! #+END_SRC
+//! ```
+//!
+//! The header arguments of a block are inherited from the `header-args` and
+//! `header-args:LANGUAGE` properties (of the document and the headlines the block is in).
+//!
+//! Supported header arguments are `:tangle`, `:mkdirp`, `:noweb`, `:noweb-ref`, `:noweb-sep`,
+//! `:padline`, `:shebang` and `:tangle-mode`. `<<name>>` references are replaced by the blocks
+//! named `name` (with `#+NAME:` or `:noweb-ref`). References calling blocks (`<<name()>>`) are
+//! not evaluated and comments linking back to the org file (`:comments`) are not written.
+
+use crate::types::document::Document;
+use crate::types::elements::{HeaderArgs, SrcBlock, Tangle};
+use crate::types::greater_elements::Headline;
+use crate::types::visit::{self, Visitor};
+use std::path::{Path, PathBuf};
+
+/// The name used for the org file by [`tangle`] (like org mode uses the buffer name for buffers
+/// without a file).
+const DEFAULT_FILE_NAME: &str = "tangled.org";
+
+/// The maximum depth of nested noweb references. Deeper (e.g. recursive) references are not
+/// expanded.
+const MAX_NOWEB_DEPTH: usize = 32;
+
+/// A file produced by tangling.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TangledFile {
+    /// The path of the file (as written in the `:tangle` argument, relative to the org file).
+    pub path: PathBuf,
+    /// The content of the tangled blocks (with the shebang line).
+    pub content: String,
+    /// `true` if the directories of the path should be created (`:mkdirp yes`).
+    pub mkdirp: bool,
+    /// The permissions of the file (`:tangle-mode`, e.g. `(identity #o755)` or `o+x`).
+    pub mode: Option<String>,
+}
+
+/// Tangles the source blocks of the document.
+///
+/// Blocks with `:tangle yes` are written to files named `tangled.EXTENSION`. Use
+/// [`tangle_file`] to name them after the org file.
+pub fn tangle(document: &Document) -> Vec<TangledFile> {
+    tangle_file(document, Path::new(DEFAULT_FILE_NAME))
+}
+
+/// Tangles the source blocks of the document read from `path`.
+///
+/// The files are ordered by their first block. Relative paths are not resolved.
+pub fn tangle_file(document: &Document, path: &Path) -> Vec<TangledFile> {
+    let mut collector = Collector {
+        document,
+        headlines: Vec::new(),
+        blocks: Vec::new(),
+    };
+    visit::walk(document, &mut collector);
+    let blocks = collector.blocks;
+
+    let mut files: Vec<TangledFile> = Vec::new();
+    for block in &blocks {
+        let target = match block.args.tangle() {
+            Tangle::No => continue,
+            Tangle::Yes => path.with_extension(extension(&block.block.language)),
+            Tangle::File(file) => PathBuf::from(file),
+        };
+        let index = match files.iter().position(|file| file.path == target) {
+            Some(index) => index,
+            None => {
+                files.push(TangledFile {
+                    path: target,
+                    content: String::new(),
+                    mkdirp: false,
+                    mode: None,
+                });
+                files.len() - 1
+            }
+        };
+        let file = &mut files[index];
+        let first = file.content.is_empty();
+        if first {
+            if let Some(shebang) = block.args.get("shebang").filter(|value| !value.is_empty()) {
+                file.content.push_str(shebang.trim_matches('"'));
+                file.content.push('\n');
+            }
+        }
+        file.mkdirp |= is_yes(block.args.get("mkdirp"));
+        if let Some(mode) = block
+            .args
+            .get("tangle-mode")
+            .filter(|mode| !mode.is_empty())
+        {
+            file.mode = Some(mode.to_string());
+        }
+        let padline = block.args.get("padline") != Some("no");
+        if padline && !first {
+            file.content.push('\n');
+        }
+        let body = if expands_noweb(&block.args) {
+            expand_noweb(&blocks, &block.block.value, 0)
+        } else if block.args.get("noweb") == Some("strip-tangle") {
+            strip_noweb(&block.block.value)
+        } else {
+            block.block.value.clone()
+        };
+        file.content.push_str(&body);
+        if !file.content.ends_with('\n') {
+            file.content.push('\n');
+        }
+    }
+    files
+}
+
+/// A source block with its header arguments (including inherited ones).
+struct Block<'a> {
+    block: &'a SrcBlock,
+    args: HeaderArgs,
+}
+
+/// Collects the source blocks in document order.
+struct Collector<'a> {
+    document: &'a Document,
+    /// The headlines the current element is in.
+    headlines: Vec<&'a Headline>,
+    blocks: Vec<Block<'a>>,
+}
+
+impl<'a> Visitor<'a> for Collector<'a> {
+    fn visit_headline(&mut self, headline: &'a Headline) {
+        self.headlines.push(headline);
+        visit::walk_headline(self, headline);
+        self.headlines.pop();
+    }
+
+    fn visit_src_block(&mut self, block: &'a SrcBlock) {
+        let property = |name: &str| match self.headlines.last() {
+            Some(headline) => headline.property_with_inheritance(self.document, name),
+            None => self.document.property(name),
+        };
+        let mut args = HeaderArgs::default();
+        let names = [
+            "header-args".to_string(),
+            format!("header-args:{}", block.language),
+        ];
+        for value in names.iter().filter_map(|name| property(name)) {
+            args.extend(&value);
+        }
+        args.args.extend(block.header_args().args);
+        self.blocks.push(Block { block, args });
+    }
+}
+
+/// Returns `true` if noweb references are expanded when tangling the block.
+fn expands_noweb(args: &HeaderArgs) -> bool {
+    matches!(
+        args.get("noweb"),
+        Some("yes") | Some("tangle") | Some("no-export") | Some("strip-export")
+    )
+}
+
+/// Returns `true` if the value of a header argument is `yes`.
+fn is_yes(value: Option<&str>) -> bool {
+    value.is_some_and(|value| value.eq_ignore_ascii_case("yes"))
+}
+
+/// Replaces the noweb references (`<<name>>`) in the body with the blocks of the name.
+///
+/// Text before a reference in the same line is repeated before every line of the inserted
+/// blocks. References to unknown names are kept.
+fn expand_noweb(blocks: &[Block<'_>], body: &str, depth: usize) -> String {
+    let mut expanded = Vec::new();
+    for line in body.lines() {
+        let reference = noweb_reference(line);
+        let (prefix, name, suffix) = match reference {
+            Some(reference) if depth < MAX_NOWEB_DEPTH => reference,
+            _ => {
+                expanded.push(line.to_string());
+                continue;
+            }
+        };
+        let referenced: Vec<_> = blocks
+            .iter()
+            .filter(|block| {
+                block.args.get("noweb-ref") == Some(name)
+                    || block
+                        .block
+                        .affiliated_keywords
+                        .as_ref()
+                        .and_then(|keywords| keywords.value().name())
+                        .is_some_and(|block_name| block_name == name)
+            })
+            .collect();
+        if referenced.is_empty() {
+            expanded.push(line.to_string());
+            continue;
+        }
+        let separator = referenced[0]
+            .args
+            .get("noweb-sep")
+            .map_or("\n", |separator| separator.trim_matches('"'));
+        let text = referenced
+            .iter()
+            .map(|block| {
+                let body = block.block.value.trim_end_matches('\n');
+                if expands_noweb(&block.args) {
+                    expand_noweb(blocks, body, depth + 1)
+                } else {
+                    body.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(separator);
+        let mut lines: Vec<String> = text
+            .lines()
+            .map(|inserted| format!("{}{}", prefix, inserted))
+            .collect();
+        match lines.last_mut() {
+            Some(last) => last.push_str(suffix),
+            None => lines.push(format!("{}{}", prefix, suffix)),
+        }
+        expanded.extend(lines);
+    }
+    let mut expanded = expanded.join("\n");
+    if body.ends_with('\n') {
+        expanded.push('\n');
+    }
+    expanded
+}
+
+/// Removes the noweb references from the body (`:noweb strip-tangle`).
+fn strip_noweb(body: &str) -> String {
+    let mut stripped: Vec<String> = Vec::new();
+    for line in body.lines() {
+        match noweb_reference(line) {
+            Some((prefix, _, suffix)) => stripped.push(format!("{}{}", prefix, suffix)),
+            None => stripped.push(line.to_string()),
+        }
+    }
+    let mut stripped = stripped.join("\n");
+    if body.ends_with('\n') {
+        stripped.push('\n');
+    }
+    stripped
+}
+
+/// Returns the text before, the name in and the text after the first noweb reference in the
+/// line.
+///
+/// References calling a block (`<<name(x=1)>>`) are ignored.
+fn noweb_reference(line: &str) -> Option<(&str, &str, &str)> {
+    let start = line.find("<<")?;
+    let end = start + 2 + line[start + 2..].find(">>")?;
+    let name = &line[start + 2..end];
+    if name.is_empty() || name.contains(char::is_whitespace) || name.contains('(') {
+        return None;
+    }
+    Some((&line[..start], name, &line[end + 2..]))
+}
+
+/// Returns the file extension used for the language (like `org-babel-tangle-lang-exts`).
+fn extension(language: &str) -> &str {
+    match language {
+        "emacs-lisp" | "elisp" => "el",
+        "python" => "py",
+        "rust" => "rs",
+        "shell" | "bash" | "zsh" => "sh",
+        "javascript" => "js",
+        "typescript" => "ts",
+        "ruby" => "rb",
+        "haskell" => "hs",
+        "C" => "c",
+        "C++" | "cpp" => "cpp",
+        "latex" => "tex",
+        "perl" => "pl",
+        "ocaml" => "ml",
+        "clojure" => "clj",
+        "scheme" => "scm",
+        "lisp" => "lisp",
+        "makefile" => "mk",
+        language => language,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tangled_files() -> Vec<TangledFile> {
+        let document = Document::parse(
+            "#+PROPERTY: header-args:rust :tangle src/main.rs :mkdirp yes\n\
+             #+BEGIN_SRC rust :noweb yes\n\
+             fn main() {\n    <<body>>\n}\n\
+             #+END_SRC\n\
+             #+BEGIN_SRC sh :tangle yes :shebang \"#!/bin/sh\" :tangle-mode o+x\n\
+             echo <<missing>>\n\
+             #+END_SRC\n\
+             #+BEGIN_SRC rust\n\
+             fn helper() {}\n\
+             #+END_SRC\n\
+             #+BEGIN_SRC python :tangle yes :noweb strip-tangle\n\
+             x = 1 <<body>>\n\
+             #+END_SRC\n\
+             * Body\n\
+             :PROPERTIES:\n\
+             :header-args: :noweb-ref body\n\
+             :END:\n\
+             #+BEGIN_SRC rust :tangle no\n\
+             println!(\"Hello\");\n\
+             #+END_SRC\n\
+             #+BEGIN_SRC rust :tangle no\n\
+             println!(\"World\");\n\
+             #+END_SRC\n",
+        )
+        .unwrap();
+        tangle_file(&document, Path::new("notes/hello.org"))
+    }
+
+    #[test]
+    fn tangled_paths() {
+        let files = tangled_files();
+        let paths: Vec<_> = files
+            .iter()
+            .map(|file| file.path.to_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["src/main.rs", "notes/hello.sh", "notes/hello.py"]);
+    }
+
+    #[test]
+    fn expand_noweb_references() {
+        let files = tangled_files();
+        assert_eq!(
+            files[0].content,
+            "fn main() {\n    println!(\"Hello\");\n    println!(\"World\");\n}\n\
+             \n\
+             fn helper() {}\n"
+        );
+        assert!(files[0].mkdirp);
+        assert_eq!(files[0].mode, None);
+    }
+
+    #[test]
+    fn shebang_and_mode() {
+        let files = tangled_files();
+        // references are only expanded with `:noweb yes`
+        assert_eq!(files[1].content, "#!/bin/sh\necho <<missing>>\n");
+        assert!(!files[1].mkdirp);
+        assert_eq!(files[1].mode.as_deref(), Some("o+x"));
+    }
+
+    #[test]
+    fn strip_noweb_references() {
+        assert_eq!(tangled_files()[2].content, "x = 1 \n");
+    }
+
+    #[test]
+    fn tangle_without_file_name() {
+        let document = Document::parse("#+BEGIN_SRC sh :tangle yes\nls\n#+END_SRC\n").unwrap();
+        assert_eq!(tangle(&document)[0].path, Path::new("tangled.sh"));
+    }
+}
@@ -0,0 +1,179 @@
+//! Converting plain lists to headlines and back.
+//!
+//! This is what `org-toggle-heading` and `org-toggle-item` do in org mode:
+//!
+//! - [`list_to_headlines`] turns every item into a headline. The first paragraph of the item
+//!   becomes the title, nested lists become child headlines and everything else becomes the
+//!   section. Checkboxes become todo keywords (`[X]` becomes `DONE`, the others `TODO`).
+//! - [`headlines_to_list`] does the opposite. `DONE` keywords become checked checkboxes and the
+//!   other keywords unchecked ones.
+//!
+//! The conversion is lossy where the other side has no equivalent: the name of the todo
+//! keywords, the priority, tags, planning and properties of headlines are not kept in items.
+
+use crate::types::elements::Paragraph;
+use crate::types::greater_elements::{
+    Checkbox, Headline, HeadlineContentSet, Item, ItemKind, PlainList, Section, TodoKeyword,
+    UnorderedBullet,
+};
+use crate::types::{ElementSet, SecondaryString, Spanned, StandardSetNoLineBreak};
+
+/// Converts the items of a list to headlines with the given level.
+///
+/// Elements following a nested list are moved into the section before the child headlines.
+pub fn list_to_headlines(mut list: PlainList, level: u32) -> Vec<Headline> {
+    list.content_mut()
+        .drain(..)
+        .map(|item| item_to_headline(item, level))
+        .collect()
+}
+
+fn item_to_headline(mut item: Item, level: u32) -> Headline {
+    let mut elements: Vec<_> = item.content_mut().drain(..).collect();
+    let first_paragraph = match elements.first() {
+        Some(ElementSet::Paragraph(_)) => match elements.remove(0) {
+            ElementSet::Paragraph(paragraph) => Some(paragraph.content),
+            _ => unreachable!("the first element is a paragraph"),
+        },
+        _ => None,
+    };
+    let mut title: Vec<StandardSetNoLineBreak> = first_paragraph
+        .into_iter()
+        .flat_map(|content| content.into_iter().map(Into::into))
+        .collect();
+    if let ItemKind::Description { tag, .. } = &item.kind {
+        title.insert(0, StandardSetNoLineBreak::RawString(format!("{} :: ", tag)));
+    }
+
+    let mut section = Vec::new();
+    let mut children = Vec::new();
+    for element in elements {
+        match element {
+            ElementSet::PlainList(list) => children.extend(
+                list_to_headlines(*list, level + 1)
+                    .into_iter()
+                    .map(|child| HeadlineContentSet::Headline(Box::new(child))),
+            ),
+            element => section.push(element),
+        }
+    }
+
+    let mut headline = Headline {
+        level,
+        todo_keyword: item.checkbox.as_ref().map(|checkbox| match checkbox {
+            Checkbox::Checked => TodoKeyword::Done("DONE".to_string()),
+            Checkbox::Unchecked | Checkbox::Partial => TodoKeyword::Todo("TODO".to_string()),
+        }),
+        title: if title.is_empty() {
+            None
+        } else {
+            Some(title.into())
+        },
+        ..Headline::default()
+    };
+    if !section.is_empty() {
+        headline.push_content(Some(HeadlineContentSet::Section(Section::new(
+            Spanned::new(section),
+        ))));
+    }
+    headline.push_content(children);
+    headline
+}
+
+/// Converts headlines (and their children) to an unordered list.
+pub fn headlines_to_list(headlines: Vec<Headline>) -> PlainList {
+    PlainList::new(Spanned::new(
+        headlines.into_iter().map(headline_to_item).collect(),
+    ))
+}
+
+fn headline_to_item(headline: Headline) -> Item {
+    let mut elements = Vec::new();
+    if let Some(title) = headline.title {
+        let title = title
+            .into_iter()
+            .map(Into::into)
+            .collect::<SecondaryString<_>>();
+        elements.push(Paragraph::new(title).into());
+    }
+    let mut children = Vec::new();
+    for content in headline.content.map(Spanned::to_value).unwrap_or_default() {
+        match content {
+            HeadlineContentSet::Section(mut section) => elements.append(section.content_mut()),
+            HeadlineContentSet::Headline(child) => children.push(*child),
+        }
+    }
+    if !children.is_empty() {
+        elements.push(headlines_to_list(children).into());
+    }
+
+    let mut item = Item::new(
+        ItemKind::Unordered {
+            bullet: UnorderedBullet::Minus,
+        },
+        Spanned::new(elements),
+    );
+    item.checkbox = headline.todo_keyword.map(|keyword| match keyword {
+        TodoKeyword::Todo(_) => Checkbox::Unchecked,
+        TodoKeyword::Done(_) => Checkbox::Checked,
+    });
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::StandardSet;
+
+    fn item(text: &str, checkbox: Option<Checkbox>, children: Vec<Item>) -> Item {
+        let mut elements = vec![
+            Paragraph::new(SecondaryString::with_one(StandardSet::RawString(
+                text.to_string(),
+            )))
+            .into(),
+        ];
+        if !children.is_empty() {
+            elements.push(PlainList::new(Spanned::new(children)).into());
+        }
+        let mut item = Item::new(
+            ItemKind::Unordered {
+                bullet: UnorderedBullet::Minus,
+            },
+            Spanned::new(elements),
+        );
+        item.checkbox = checkbox;
+        item
+    }
+
+    #[test]
+    fn toggle() {
+        let list = PlainList::new(Spanned::new(vec![
+            item(
+                "Project",
+                None,
+                vec![
+                    item("Done", Some(Checkbox::Checked), vec![]),
+                    item("Open", Some(Checkbox::Partial), vec![]),
+                ],
+            ),
+            item("Other", Some(Checkbox::Unchecked), vec![]),
+        ]));
+        let org = list.to_string();
+
+        let headlines = list_to_headlines(list, 2);
+        assert_eq!(
+            headlines
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["** Project\n*** DONE Done\n*** TODO Open", "** TODO Other"]
+        );
+
+        let list = headlines_to_list(headlines);
+        assert_eq!(
+            list.to_string(),
+            org.replace("[-]", "[ ]"),
+            "partial checkboxes become unchecked"
+        );
+    }
+}
@@ -59,6 +59,7 @@ use std::slice;
 ///
 /// The attr keywords for one backend can occur more than once.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AffiliatedKeywords {
     captions: Vec<Spanned<Caption>>,
     headers: Vec<Spanned<String>>,
@@ -179,15 +180,15 @@ impl AffiliatedKeywords {
                 None
             }
             AffiliatedKeyword::Name(name) => {
-                self.name.replace(name).map(|x| AffiliatedKeyword::Name(x))
+                self.name.replace(name).map(AffiliatedKeyword::Name)
             }
             AffiliatedKeyword::Plot(plot) => {
-                self.plot.replace(plot).map(|x| AffiliatedKeyword::Plot(x))
+                self.plot.replace(plot).map(AffiliatedKeyword::Plot)
             }
             AffiliatedKeyword::Results(results) => self
                 .results
                 .replace(results)
-                .map(|x| AffiliatedKeyword::Results(x)),
+                .map(AffiliatedKeyword::Results),
             AffiliatedKeyword::Attr(attr) => {
                 self.attrs.push(attr);
                 None
@@ -244,6 +245,16 @@ impl AffiliatedKeywords {
         }
     }
 
+    /// Returns the value of an attribute (e.g. `:lang`) in the `#+ATTR_BACKEND:` keywords.
+    ///
+    /// The `name` is given without the colon. The attributes of all backends are searched. If
+    /// the attribute is set more than once the last value is returned.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attrs()
+            .filter_map(|attr| attr.attribute(name))
+            .last()
+    }
+
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = AffiliatedKeyword> + 'a {
         self.captions
             .iter()
@@ -266,6 +277,7 @@ impl AffiliatedKeywords {
 
 /// Represents a single affiliated keyword.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AffiliatedKeyword {
     Caption(Spanned<Caption>),
     Header(Spanned<String>),
@@ -313,6 +325,7 @@ impl AffiliatedKeyword {
 ///
 /// See [`AffiliatedKeywords`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Caption {
     optional: Option<SecondaryString<StandardSet>>,
     value: SecondaryString<StandardSet>,
@@ -362,23 +375,38 @@ impl Caption {
 ///
 /// See [`AffiliatedKeywords`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Results {
-    value: String,
-    optional: Option<String>,
+    value: SecondaryString<StandardSet>,
+    optional: Option<SecondaryString<StandardSet>>,
 }
 
 impl Results {
-    pub fn new(value: String, optional: Option<String>) -> Self {
+    pub fn new(
+        value: SecondaryString<StandardSet>,
+        optional: Option<SecondaryString<StandardSet>>,
+    ) -> Self {
         Results { value, optional }
     }
+
+    pub fn optional(&self) -> &Option<SecondaryString<StandardSet>> {
+        &self.optional
+    }
+    pub fn value(&self) -> &SecondaryString<StandardSet> {
+        &self.value
+    }
 }
 
 impl fmt::Display for Results {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.optional {
-            Some(optional) => write!(f, "#+RESULTS[{}]: {}", optional, self.value),
-            None => write!(f, "#+RESULTS: {}", self.value),
+            Some(optional) => write!(f, "#+RESULTS[{}]:", optional)?,
+            None => write!(f, "#+RESULTS:")?,
+        }
+        if !self.value.is_empty() {
+            write!(f, " {}", self.value)?;
         }
+        Ok(())
     }
 }
 
@@ -386,6 +414,7 @@ impl fmt::Display for Results {
 ///
 /// See [`AffiliatedKeywords`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attr {
     backend: String,
     value: String,
@@ -395,6 +424,22 @@ impl Attr {
     pub fn new(backend: String, value: String) -> Self {
         Attr { backend, value }
     }
+
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the value of the attribute `:name` (e.g. `fr` for `:lang fr`).
+    ///
+    /// The value ends at the next attribute (a word starting with a colon).
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        let mut words = self.value.split_whitespace();
+        words.find(|word| word.len() > 1 && word.starts_with(':') && &word[1..] == name)?;
+        words.next().filter(|word| !word.starts_with(':'))
+    }
 }
 
 impl fmt::Display for Attr {
@@ -413,6 +458,7 @@ mod iter {
     // XXX: This type is humongous. But this is easier than implementing some sort of state to know
     // what field we are currently in and where in that field if it is a vector and where to go to next
     // and what happens if one of the fields is empty.
+    #[allow(clippy::type_complexity)]
     pub struct IntoIter {
         pub(super) inner: Chain<
             Chain<
@@ -606,6 +652,8 @@ mod tests {
             optional in "[a-zA-Z_]{1,}".prop_perturb(|o, mut rng| if rng.gen() { Some(o) } else { None }),
             value in "[a-zA-Z_]{1,}"
         ) -> Spanned<Results> {
+            let value = SecondaryString::with_one(StandardSet::RawString(value));
+            let optional = optional.map(|value| SecondaryString::with_one(StandardSet::RawString(value)));
             let caption = Results { value, optional, };
             Spanned::with_span(caption, span)
         }
@@ -693,7 +741,7 @@ mod tests {
     #[ignore]
     fn test_parse_affiliated_keywords_attr() {
         let _text = "#+ATTR_something: value";
-        let _result = unimplemented!();
+        unimplemented!();
         // let mut expected = AffiliatedKeywords::new();
         // expected.push(AffiliatedKeyword::Attr(Spanned::with_span(
         //     Attr {
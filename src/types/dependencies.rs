@@ -0,0 +1,291 @@
+//! Dependencies between tasks.
+//!
+//! A task (a headline with a todo keyword) can't be marked as done while it is blocked. This
+//! implements the rules of `org-enforce-todo-dependencies` and of the `org-depend` module:
+//!
+//! - A headline is blocked by each child that is not done yet.
+//! - If the parent of a headline has the `ORDERED` property, the headline is blocked by each
+//!   previous sibling that is not done yet. This also applies to all descendants of the headline.
+//! - The `BLOCKER` property contains a whitespace separated list of ids (of the `ID` property)
+//!   of headlines blocking the headline. The special value `previous-sibling` refers to the
+//!   previous sibling.
+//!
+//! The `TRIGGER` property lists state changes that should happen once the headline is done
+//! (see [`Trigger`]).
+
+use super::document::Document;
+use super::greater_elements::{Headline, TodoKeyword};
+use std::fmt;
+
+/// A state change triggered when a headline is marked as done (from the `TRIGGER` property).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Trigger {
+    /// `chain-siblings(KEYWORD)`: the next sibling gets the todo keyword.
+    ChainSiblings(String),
+    /// `ID(KEYWORD)`: the headline with the id gets the todo keyword.
+    Id { id: String, keyword: String },
+}
+
+impl Trigger {
+    /// Parses the value of a `TRIGGER` property. Unsupported triggers are skipped.
+    pub fn parse_all(s: &str) -> Vec<Trigger> {
+        s.split_whitespace()
+            .filter_map(|trigger| {
+                if !trigger.ends_with(')') {
+                    return None;
+                }
+                let open = trigger.find('(')?;
+                let name = &trigger[..open];
+                let keyword = trigger[open + 1..trigger.len() - 1].to_string();
+                match name {
+                    "chain-siblings" => Some(Trigger::ChainSiblings(keyword)),
+                    "" => None,
+                    _ if name.starts_with("chain-") => None,
+                    _ => Some(Trigger::Id {
+                        id: name.to_string(),
+                        keyword,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::ChainSiblings(keyword) => write!(f, "chain-siblings({})", keyword),
+            Trigger::Id { id, keyword } => write!(f, "{}({})", id, keyword),
+        }
+    }
+}
+
+/// One level of the path to a headline: the siblings and the index of the headline in them.
+type Level<'a> = (Vec<&'a Headline>, usize);
+
+pub(crate) fn is_blocked(document: &Document, headline: &Headline) -> bool {
+    if headline.children().any(is_open) {
+        return true;
+    }
+    let path = match find_path(document, headline) {
+        Some(path) => path,
+        None => return false,
+    };
+
+    // ORDERED on the parent of the headline or of one of its ancestors
+    for depth in 1..path.len() {
+        let (parents, parent_index) = &path[depth - 1];
+        let (siblings, index) = &path[depth];
+        if is_ordered(parents[*parent_index]) && siblings[..*index].iter().any(|s| is_open(s)) {
+            return true;
+        }
+    }
+
+    let (siblings, index) = path
+        .last()
+        .expect("the path contains at least the headline");
+    headline
+        .property("BLOCKER")
+        .unwrap_or_default()
+        .split_whitespace()
+        .any(|blocker| {
+            if blocker == "previous-sibling" {
+                *index > 0 && is_open(siblings[index - 1])
+            } else {
+                find(document, &|headline| {
+                    headline.property("ID").as_deref() == Some(blocker)
+                })
+                .map(is_open)
+                .unwrap_or(false)
+            }
+        })
+}
+
+/// Returns the headlines which get a new todo keyword when the headline is marked as done.
+pub(crate) fn triggered<'a>(
+    document: &'a Document,
+    headline: &Headline,
+) -> Vec<(&'a Headline, String)> {
+    let triggers = Trigger::parse_all(&headline.property("TRIGGER").unwrap_or_default());
+    let path = find_path(document, headline);
+    triggers
+        .into_iter()
+        .filter_map(|trigger| match trigger {
+            Trigger::ChainSiblings(keyword) => {
+                let (siblings, index) = path.as_ref()?.last()?;
+                Some((*siblings.get(index + 1)?, keyword))
+            }
+            Trigger::Id { id, keyword } => Some((
+                find(document, &|headline| {
+                    headline.property("ID").as_deref() == Some(id.as_str())
+                })?,
+                keyword,
+            )),
+        })
+        .collect()
+}
+
+fn is_open(headline: &Headline) -> bool {
+    matches!(headline.todo_keyword, Some(TodoKeyword::Todo(_)))
+}
+
+fn is_ordered(headline: &Headline) -> bool {
+    match headline.property("ORDERED").as_deref().map(str::trim) {
+        None | Some("") | Some("nil") => false,
+        Some(_) => true,
+    }
+}
+
+/// Returns the ancestors of the headline (outermost first). Empty if the document doesn't contain
+/// the headline.
+pub(crate) fn ancestors<'a>(
+    document: &'a Document,
+    headline: &Headline,
+) -> impl Iterator<Item = &'a Headline> {
+    let mut path = find_path(document, headline).unwrap_or_default();
+    path.pop();
+    path.into_iter().map(|(siblings, index)| siblings[index])
+}
+
+/// Finds the path from the top level of the document to the headline (compared by identity).
+fn find_path<'a>(document: &'a Document, headline: &Headline) -> Option<Vec<Level<'a>>> {
+    fn search<'a>(
+        siblings: Vec<&'a Headline>,
+        target: &Headline,
+        path: &mut Vec<Level<'a>>,
+    ) -> bool {
+        for index in 0..siblings.len() {
+            let current = siblings[index];
+            path.push((siblings.clone(), index));
+            if std::ptr::eq(current, target) || search(current.children().collect(), target, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = Vec::new();
+    if search(document.headlines.iter().collect(), headline, &mut path) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn find<'a>(
+    document: &'a Document,
+    predicate: &impl Fn(&Headline) -> bool,
+) -> Option<&'a Headline> {
+    fn search<'a>(
+        headlines: Vec<&'a Headline>,
+        predicate: &impl Fn(&Headline) -> bool,
+    ) -> Option<&'a Headline> {
+        headlines.into_iter().find_map(|headline| {
+            if predicate(headline) {
+                Some(headline)
+            } else {
+                search(headline.children().collect(), predicate)
+            }
+        })
+    }
+    search(document.headlines.iter().collect(), predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::greater_elements::HeadlineContentSet;
+
+    fn task(keyword: Option<&str>, children: Vec<Headline>) -> Headline {
+        let mut headline = Headline {
+            todo_keyword: keyword.map(|keyword| match keyword {
+                "DONE" => TodoKeyword::Done(keyword.to_string()),
+                _ => TodoKeyword::Todo(keyword.to_string()),
+            }),
+            ..Headline::default()
+        };
+        headline.push_content(
+            children
+                .into_iter()
+                .map(|child| HeadlineContentSet::Headline(Box::new(child))),
+        );
+        headline
+    }
+
+    fn child(headline: &Headline, index: usize) -> &Headline {
+        headline.children().nth(index).unwrap()
+    }
+
+    #[test]
+    fn blocked_by_children() {
+        let document = Document {
+            preface: None,
+            headlines: vec![
+                task(Some("TODO"), vec![task(Some("TODO"), vec![])]),
+                task(Some("TODO"), vec![task(Some("DONE"), vec![])]),
+            ],
+        };
+        assert!(document.headlines[0].is_blocked(&document));
+        assert!(!document.headlines[1].is_blocked(&document));
+    }
+
+    #[test]
+    fn ordered_siblings() {
+        let mut project = task(
+            None,
+            vec![
+                task(Some("DONE"), vec![]),
+                task(Some("TODO"), vec![]),
+                task(Some("TODO"), vec![task(Some("TODO"), vec![])]),
+            ],
+        );
+        project.set_property("ORDERED", "t");
+        let document = Document {
+            preface: None,
+            headlines: vec![project],
+        };
+        let project = &document.headlines[0];
+        assert!(!child(project, 0).is_blocked(&document));
+        assert!(!child(project, 1).is_blocked(&document));
+        assert!(child(project, 2).is_blocked(&document));
+        // descendants of blocked siblings are blocked too
+        assert!(child(child(project, 2), 0).is_blocked(&document));
+    }
+
+    #[test]
+    fn blockers_and_triggers() {
+        let mut first = task(Some("TODO"), vec![]);
+        first.set_property("ID", "first");
+        first.set_property(
+            "TRIGGER",
+            "chain-siblings(NEXT) third(TODO) chain-find-next(X)",
+        );
+        let mut second = task(Some("TODO"), vec![]);
+        second.set_property("BLOCKER", "first");
+        let mut third = task(None, vec![]);
+        third.set_property("ID", "third");
+        third.set_property("BLOCKER", "previous-sibling");
+        let document = Document {
+            preface: None,
+            headlines: vec![first, second, third],
+        };
+        assert!(!document.headlines[0].is_blocked(&document));
+        assert!(document.headlines[1].is_blocked(&document));
+        assert!(document.headlines[2].is_blocked(&document));
+
+        let triggered: Vec<_> = document.headlines[0]
+            .triggered(&document)
+            .into_iter()
+            .map(|(headline, keyword)| (headline.property("ID"), keyword))
+            .collect();
+        assert_eq!(
+            triggered,
+            vec![
+                (None, "NEXT".to_string()),
+                (Some("third".into()), "TODO".to_string())
+            ]
+        );
+    }
+}
@@ -1,38 +1,468 @@
 //! The `Document` struct represents an entire org file.
 
 use super::*;
+use crate::columns::{ColumnFormat, ColumnView};
+use crate::include::{FileLoader, IncludeError};
+use crate::parsing::{ParseConfig, ParseError, TextEdit, TodoSequence};
+use crate::promote::PromoteError;
+use crate::refile::{RefileError, RefileOptions};
+use crate::settings::DocumentSettings;
+use crate::tag_match::{TagMatch, Term, TermKind};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
 /// A complete org document/file.
 ///
 /// Contains the global document properties and section before the first headline as well as the
 /// list of all top level headlines.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document {
     pub preface: Option<greater_elements::Section>,
     pub headlines: Vec<greater_elements::Headline>,
 }
 
 impl Document {
+    /// Parses a document with the default [`ParseConfig`].
+    ///
+    /// This is the same as [`parse_document`]. Use [`parse_document_with_config`] for other
+    /// configurations.
+    ///
+    /// [`ParseConfig`]: `crate::parsing::ParseConfig`
+    /// [`parse_document`]: `crate::parsing::parse_document`
+    /// [`parse_document_with_config`]: `crate::parsing::parse_document_with_config`
+    pub fn parse(s: &str) -> Result<Document, ParseError> {
+        crate::parsing::parse_document(s)
+    }
+
+    /// Parses a document and builds the [`LineIndex`] of the text.
+    ///
+    /// The index converts the spans of the document to lines and columns (e.g. for an editor).
+    /// Build a new one with [`LineIndex::new`] after the text was edited.
+    ///
+    /// [`LineIndex`]: `line_index::LineIndex`
+    /// [`LineIndex::new`]: `line_index::LineIndex::new`
+    pub fn parse_with_line_index(s: &str) -> Result<(Document, line_index::LineIndex), ParseError> {
+        Ok((Document::parse(s)?, line_index::LineIndex::new(s)))
+    }
+
+    /// Updates the document after the text it was parsed from was edited.
+    ///
+    /// Only the edited top level headlines are parsed again with the default [`ParseConfig`]
+    /// (see [`reparse_range_with_config`]). The document is not changed if the edited text can't
+    /// be parsed.
+    ///
+    /// [`ParseConfig`]: `crate::parsing::ParseConfig`
+    /// [`reparse_range_with_config`]: `crate::parsing::reparse_range_with_config`
+    pub fn reparse_range(&mut self, edit: TextEdit<'_>) -> Result<(), ParseError> {
+        crate::parsing::reparse_range_with_config(self, edit, &Default::default())
+    }
+
+    /// Replaces the `#+INCLUDE:` keywords with the content of the files (e.g. before an export).
+    ///
+    /// See [`include`](`crate::include`) for the supported keywords. The document is not
+    /// changed if a file can't be loaded or parsed.
+    pub fn resolve_includes(&mut self, loader: impl FileLoader) -> Result<(), IncludeError> {
+        crate::include::resolve_includes(self, &loader)
+    }
+
+    /// Removes the spans of all elements and objects.
+    ///
+    /// Documents parsed from different text (e.g. after normalizing it) only compare equal
+    /// without their spans.
+    pub fn clear_spans(&mut self) {
+        crate::parsing::clear_spans(self)
+    }
+
     /// Gets an iterator over all keywords in the documents preface.
     ///
     /// TODO maybe make this iterate over all keywords (not affiliated keywords) in the whole
     /// document not just in the preface.
     pub fn keywords(&self) -> Keywords<'_> {
-        unimplemented!()
+        Keywords::new(self.preface.as_ref())
+    }
+
+    /// Returns the todo keyword sequences of the document.
+    ///
+    /// These are the sequences of the `#+TODO:` keywords in the preface (see
+    /// [`Keywords::todo_sequences`]) or the default todo keywords of the parser if there are
+    /// none.
+    pub fn todo_sequences(&self) -> Vec<TodoSequence> {
+        let sequences = self.keywords().todo_sequences();
+        if sequences.is_empty() {
+            ParseConfig::default().todo_keywords
+        } else {
+            sequences
+        }
+    }
+
+    /// Returns the value of the `#+LANGUAGE:` keyword in the preface (e.g. `de`).
+    ///
+    /// See [`Language`] for using it during an export.
+    ///
+    /// [`Language`]: `crate::export::language::Language`
+    pub fn language(&self) -> Option<String> {
+        self.keywords().language()
+    }
+
+    /// Returns the tags of the `#+FILETAGS:` keywords in the preface (e.g. `:work:project:`).
+    ///
+    /// All headlines of the document inherit these tags.
+    pub fn file_tags(&self) -> Vec<String> {
+        self.keywords().file_tags()
+    }
+
+    /// Returns the value of a document property.
+    ///
+    /// The value comes from the `#+PROPERTY: NAME VALUE` keywords and the property drawer in
+    /// the preface. `NAME+` properties append to the value. All headlines inherit these
+    /// properties (see [`Headline::property_with_inheritance`]).
+    ///
+    /// [`Headline::property_with_inheritance`]: `greater_elements::Headline::property_with_inheritance`
+    pub fn property(&self, name: &str) -> Option<String> {
+        let mut value: Option<String> = None;
+        for keyword in self
+            .keywords()
+            .filter(|keyword| keyword.key.eq_ignore_ascii_case("PROPERTY"))
+        {
+            let line = keyword.value.value().iter().join("");
+            let line = line.trim();
+            let end = line.find(char::is_whitespace).unwrap_or(line.len());
+            let (key, rest) = (&line[..end], line[end..].trim());
+            value = greater_elements::accumulate_property(value.map(Cow::Owned), name, key, rest)
+                .map(Cow::into_owned);
+        }
+        let preface_drawer = self
+            .preface
+            .iter()
+            .flat_map(|preface| preface.content())
+            .flat_map(Spanned::value)
+            .find_map(|element| match element {
+                ElementSet::PropertyDrawer(drawer) => Some(&**drawer),
+                _ => None,
+            });
+        if let Some(drawer) = preface_drawer {
+            value = drawer
+                .get_with_inherited(name, value.map(Cow::Owned))
+                .map(Cow::into_owned);
+        }
+        value
+    }
+
+    /// Returns all headlines which have the tag (including inherited tags) in document order.
+    ///
+    /// Commented and archived subtrees are searched too. Use a [`TagMatch`] for more complex
+    /// queries (e.g. `work&!archive`) or to leave out these subtrees.
+    ///
+    /// [`TagMatch`]: `crate::tag_match::TagMatch`
+    pub fn headlines_with_tag(&self, tag: &str) -> Vec<&greater_elements::Headline> {
+        let tag_match = TagMatch {
+            alternatives: vec![vec![Term {
+                negated: false,
+                kind: TermKind::Tag(tag.to_string()),
+            }]],
+            todo: None,
+        };
+        tag_match.search(self, greater_elements::SubtreeFilter::all())
+    }
+
+    /// Returns an immutable snapshot of the document that can be shared between threads.
+    ///
+    /// This copies the document. To take many snapshots of a document that is edited use a
+    /// [`SharedDocument`] instead, whose snapshots don't copy the document.
+    ///
+    /// [`SharedDocument`]: `snapshot::SharedDocument`
+    pub fn snapshot(&self) -> snapshot::Snapshot {
+        self.clone().into()
+    }
+
+    /// Returns the stable ids of all headlines in document order.
+    ///
+    /// See [`NodeId`] for how the ids are computed.
+    ///
+    /// [`NodeId`]: `node_id::NodeId`
+    pub fn node_ids(&self) -> Vec<(node_id::NodeId, &greater_elements::Headline)> {
+        node_id::node_ids(self)
+    }
+
+    /// Finds the headline with the given id. See [`NodeId::resolve`].
+    ///
+    /// [`NodeId::resolve`]: `node_id::NodeId::resolve`
+    pub fn headline_by_id(&self, id: &node_id::NodeId) -> Option<&greater_elements::Headline> {
+        id.resolve(self)
+    }
+
+    /// Returns the value of a property of the headline with the id including inherited values.
+    ///
+    /// Returns `None` if the headline doesn't exist. See
+    /// [`Headline::property_with_inheritance`].
+    ///
+    /// [`Headline::property_with_inheritance`]: `greater_elements::Headline::property_with_inheritance`
+    pub fn property_with_inheritance(&self, id: &node_id::NodeId, name: &str) -> Option<String> {
+        id.resolve(self)?.property_with_inheritance(self, name)
+    }
+
+    /// Moves the headline with the id (and its children) below the target headline. See
+    /// [`refile`].
+    ///
+    /// [`refile`]: `crate::refile::refile`
+    pub fn refile(
+        &mut self,
+        headline: &node_id::NodeId,
+        target: &node_id::NodeId,
+        options: &RefileOptions,
+    ) -> Result<(), RefileError> {
+        crate::refile::refile(self, headline, target, options)
+    }
+
+    /// Decreases the level of the headline with the id but not of its children (like
+    /// `org-promote`). See [`promote`].
+    ///
+    /// [`promote`]: `crate::promote`
+    pub fn promote(&mut self, headline: &node_id::NodeId) -> Result<(), PromoteError> {
+        crate::promote::promote(self, headline, false)
+    }
+
+    /// Increases the level of the headline with the id but not of its children (like
+    /// `org-demote`). See [`promote`].
+    ///
+    /// [`promote`]: `crate::promote`
+    pub fn demote(&mut self, headline: &node_id::NodeId) -> Result<(), PromoteError> {
+        crate::promote::demote(self, headline, false)
+    }
+
+    /// Decreases the level of the headline with the id and its children (like
+    /// `org-promote-subtree`).
+    pub fn promote_subtree(&mut self, headline: &node_id::NodeId) -> Result<(), PromoteError> {
+        crate::promote::promote(self, headline, true)
+    }
+
+    /// Increases the level of the headline with the id and its children (like
+    /// `org-demote-subtree`).
+    pub fn demote_subtree(&mut self, headline: &node_id::NodeId) -> Result<(), PromoteError> {
+        crate::promote::demote(self, headline, true)
+    }
+
+    /// Returns the column view of all headlines with the format of the `#+COLUMNS:` keyword.
+    ///
+    /// See [`ColumnFormat::of_document`]. Use [`ColumnView::new`] for other formats.
+    ///
+    /// [`ColumnFormat::of_document`]: `crate::columns::ColumnFormat::of_document`
+    /// [`ColumnView::new`]: `crate::columns::ColumnView::new`
+    pub fn column_view(&self) -> ColumnView {
+        ColumnView::new(self, &ColumnFormat::of_document(self))
+    }
+
+    /// Returns the settings of the keywords in the preface (`#+TITLE:`, `#+OPTIONS:`, ...).
+    ///
+    /// Setup files are not loaded. Use [`resolve_settings`] for this.
+    ///
+    /// [`resolve_settings`]: `crate::settings::resolve_settings`
+    pub fn settings(&self) -> DocumentSettings {
+        DocumentSettings::from_keywords(self.keywords())
+    }
+
+    /// Collects all links, targets, footnotes, macros, timestamps and named elements of the
+    /// document.
+    ///
+    /// See [`DocumentIndex`].
+    ///
+    /// [`DocumentIndex`]: `index::DocumentIndex`
+    pub fn index(&self) -> index::DocumentIndex<'_> {
+        index::index(self)
+    }
+
+    /// Finds the definition of the footnote with the label (without `fn:`).
+    ///
+    /// This is either a footnote definition element or an inline footnote with the label. Use
+    /// [`Document::index`] to resolve several footnotes.
+    pub fn resolve_footnote(&self, label: &str) -> Option<index::FootnoteDefinitionRef<'_>> {
+        self.index().footnote_definition(label)
+    }
+
+    /// Finds the first element with the given name (set with `#+NAME:`).
+    ///
+    /// Named elements are the targets of `#+CALL:` lines, noweb references, `remote()`
+    /// references in table formulas and internal links like `[[tbl-results]]`. Use
+    /// [`Document::index`] to look up several names.
+    pub fn element_by_name(&self, name: &str) -> Option<&ElementSet> {
+        self.index()
+            .named_elements
+            .into_iter()
+            .find(|element| element.name() == Some(name))
+    }
+
+    /// Returns the innermost element (or headline, section or item) containing the byte offset.
+    ///
+    /// Only headlines, sections, paragraphs and greater elements are found because the other
+    /// elements don't store their position (see [`position`]).
+    pub fn element_at(&self, offset: usize) -> Option<position::ElementRef<'_>> {
+        position::element_at(self, offset)
+    }
+
+    /// Returns the innermost object containing the byte offset (e.g. the link under the cursor).
+    ///
+    /// Objects are found in paragraphs and headline titles. Text between other objects is
+    /// returned as [`ObjectRef::RawString`].
+    ///
+    /// [`ObjectRef::RawString`]: `visit::ObjectRef::RawString`
+    pub fn object_at(&self, offset: usize) -> Option<visit::ObjectRef<'_>> {
+        position::object_at(self, offset)
+    }
+
+    /// Checks the structural invariants of the document.
+    ///
+    /// This is useful after modifying the document programmatically. Returns an empty list if
+    /// the document is valid. See [`DiagnosticKind`] for the checked invariants.
+    ///
+    /// [`DiagnosticKind`]: `validation::DiagnosticKind`
+    pub fn validate(&self) -> Vec<validation::Diagnostic> {
+        validation::validate(self)
+    }
+
+    /// Appends another document (e.g. a generated fragment) to this document.
+    ///
+    /// The result is the same as concatenating both files except for the keywords and the
+    /// property drawer in the preface of `other`. They are moved to the preface of this
+    /// document:
+    ///
+    /// - Keywords that have a single value per document (e.g. `TITLE`, see
+    ///   [`SINGLE_VALUE_KEYWORDS`]) are dropped if this document already has them.
+    /// - Keywords that are already in this document with the same value are dropped.
+    /// - Properties are merged with [`Section::merge`].
+    ///
+    /// The remaining preface of `other` belongs to the last headline of this document (like in
+    /// the concatenated file). Use [`Document::rebase_levels`] before appending to nest the
+    /// headlines of `other`.
+    ///
+    /// [`Section::merge`]: `greater_elements::Section::merge`
+    pub fn append(&mut self, other: Document) {
+        if let Some(mut preface) = other.preface {
+            let (settings, rest): (Vec<_>, Vec<_>) =
+                preface.content_mut().drain(..).partition(|element| {
+                    matches!(
+                        element,
+                        ElementSet::Keyword(_) | ElementSet::PropertyDrawer(_)
+                    )
+                });
+            for element in settings {
+                match element {
+                    ElementSet::PropertyDrawer(_) => self
+                        .preface_mut()
+                        .merge(greater_elements::Section::new(Spanned::new(vec![element]))),
+                    keyword => self.push_keyword(keyword),
+                }
+            }
+            if !rest.is_empty() {
+                let rest = greater_elements::Section::new(Spanned::new(rest));
+                match self.headlines.last_mut() {
+                    Some(headline) => headline.last_descendant_mut().section_mut().merge(rest),
+                    None => self.preface_mut().merge(rest),
+                }
+            }
+        }
+        self.headlines.extend(other.headlines);
+    }
+
+    /// Changes the levels of all headlines so the top level headlines have the given level.
+    ///
+    /// The levels relative to the top level headlines stay the same. E.g. with level `2` the
+    /// headlines of the document can be appended below a top level headline.
+    pub fn rebase_levels(&mut self, level: u32) {
+        let top = match self.headlines.iter().map(|headline| headline.level).min() {
+            Some(top) => top,
+            None => return,
+        };
+        for headline in &mut self.headlines {
+            let new_level = headline.level - top + level;
+            headline.set_level(new_level);
+        }
+    }
+
+    /// Adds a keyword after the other keywords of the preface unless it is already there.
+    fn push_keyword(&mut self, keyword: ElementSet) {
+        let preface = self.preface_mut();
+        if has_keyword(preface, &keyword) {
+            return;
+        }
+        let elements = preface.content_mut();
+        let index = elements
+            .iter()
+            .rposition(|element| {
+                matches!(
+                    element,
+                    ElementSet::Keyword(_) | ElementSet::PropertyDrawer(_)
+                )
+            })
+            .map_or(0, |index| index + 1);
+        elements.insert(index, keyword);
+    }
+
+    fn preface_mut(&mut self) -> &mut greater_elements::Section {
+        self.preface
+            .get_or_insert_with(|| greater_elements::Section::new(Spanned::new(Vec::new())))
+    }
+}
+
+/// Keywords that have only one value per document.
+///
+/// [`Document::append`] keeps only the first of these keywords.
+pub const SINGLE_VALUE_KEYWORDS: [&str; 6] =
+    ["TITLE", "SUBTITLE", "AUTHOR", "DATE", "EMAIL", "LANGUAGE"];
+
+/// Returns `true` if `section` already contains the keyword (or a keyword with the same key if
+/// the keyword has a single value).
+fn has_keyword(section: &greater_elements::Section, keyword: &ElementSet) -> bool {
+    let keyword = match keyword {
+        ElementSet::Keyword(keyword) => keyword,
+        _ => return false,
+    };
+    let single_value = SINGLE_VALUE_KEYWORDS
+        .iter()
+        .any(|key| key.eq_ignore_ascii_case(&keyword.key));
+    section
+        .content()
+        .map(|content| content.value().as_slice())
+        .unwrap_or(&[])
+        .iter()
+        .any(|element| match element {
+            ElementSet::Keyword(existing) => {
+                existing.key.eq_ignore_ascii_case(&keyword.key)
+                    && (single_value || existing.value.value() == keyword.value.value())
+            }
+            _ => false,
+        })
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(preface) = &self.preface {
+            writeln!(f, "{}", preface)?;
+        }
+        for headline in &self.headlines {
+            writeln!(f, "{}", headline)?;
+        }
+        Ok(())
     }
 }
 
-use crate::parsing::Rule;
-use pest::iterators::Pair;
-use std::iter::FromIterator;
-impl<'a> FromIterator<Pair<'a, Rule>> for Document {
-    fn from_iter<I: IntoIterator<Item = Pair<'a, Rule>>>(_iter: I) -> Self {
-        unimplemented!();
+impl FromStr for Document {
+    type Err = ParseError;
+
+    /// Parses a document (see [`Document::parse`]).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse(s)
     }
 }
 
 /// An iterator over all [`Keyword`]s in the [`Document::preface`].
 ///
+/// The keywords are in document order and a key can have several values, so this is also an
+/// ordered multimap from the keys to the values (see [`Keywords::values`]). The getters for the
+/// common keywords use the last value like org mode does (except for [`Keywords::title`] and
+/// [`Keywords::file_tags`]).
+///
 /// This `struct` is currently only created by the [`keywords`] method on [`Document`]. In the
 /// future it may be renamed and generated by methods on other elements.
 ///
@@ -40,14 +470,318 @@ impl<'a> FromIterator<Pair<'a, Rule>> for Document {
 /// [`keywords`]: `Document::keywords`
 #[derive(Debug, Clone)]
 pub struct Keywords<'a> {
-    // TODO actually implement this iterator maybe as abstraction over other iterators
-    document: &'a Document,
+    elements: std::slice::Iter<'a, ElementSet>,
+}
+
+impl<'a> Keywords<'a> {
+    /// Returns the keywords of the section (e.g. the preface of a document).
+    pub(crate) fn new(section: Option<&'a greater_elements::Section>) -> Self {
+        Keywords {
+            elements: section
+                .and_then(|section| section.content())
+                .map(|content| content.value().as_slice())
+                .unwrap_or(&[])
+                .iter(),
+        }
+    }
+
+    /// Returns the values of all keywords with the key (ignoring case) in document order.
+    pub fn values(&self, key: &str) -> Vec<String> {
+        self.clone()
+            .filter(|keyword| keyword.key.eq_ignore_ascii_case(key))
+            .map(|keyword| keyword.value.value().iter().join("").trim().to_string())
+            .collect()
+    }
+
+    /// Returns the value of the last keyword with the key (ignoring case).
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.values(key).pop()
+    }
+
+    /// Returns the values of all `#+TITLE:` keywords joined with a space.
+    pub fn title(&self) -> Option<String> {
+        let titles = self.values("TITLE");
+        if titles.is_empty() {
+            return None;
+        }
+        Some(titles.iter().filter(|title| !title.is_empty()).join(" "))
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.get("AUTHOR")
+    }
+
+    pub fn date(&self) -> Option<String> {
+        self.get("DATE")
+    }
+
+    /// Returns the value of `#+LANGUAGE:` (e.g. `de`) unless it is empty.
+    pub fn language(&self) -> Option<String> {
+        self.get("LANGUAGE").filter(|language| !language.is_empty())
+    }
+
+    /// Returns the tags of all `#+FILETAGS:` keywords (e.g. `:work:project:`) without
+    /// duplicates.
+    pub fn file_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = Vec::new();
+        for value in self.values("FILETAGS") {
+            for tag in value
+                .split(':')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+            {
+                if !tags.iter().any(|other| other == tag) {
+                    tags.push(tag.to_string());
+                }
+            }
+        }
+        tags
+    }
+
+    /// Returns the value of `#+CATEGORY:` unless it is empty.
+    pub fn category(&self) -> Option<String> {
+        self.get("CATEGORY").filter(|category| !category.is_empty())
+    }
+
+    /// Returns the sequences of all `#+TODO:`, `#+SEQ_TODO:` and `#+TYP_TODO:` keywords (see
+    /// [`TodoSequence::parse`]).
+    pub fn todo_sequences(&self) -> Vec<TodoSequence> {
+        self.clone()
+            .filter(|keyword| {
+                ["TODO", "SEQ_TODO", "TYP_TODO"]
+                    .iter()
+                    .any(|key| keyword.key.eq_ignore_ascii_case(key))
+            })
+            .filter_map(|keyword| TodoSequence::parse(&keyword.value.value().iter().join("")))
+            .collect()
+    }
 }
 
 impl<'a> Iterator for Keywords<'a> {
     type Item = &'a elements::Keyword;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unimplemented!()
+        self.elements.by_ref().find_map(|element| match element {
+            ElementSet::Keyword(keyword) => Some(&**keyword),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::greater_elements::{HeadlineContentSet, TodoKeyword};
+
+    #[test]
+    fn append() {
+        let mut document = Document::parse("#+TITLE: Report\nSummary\n* a\n** b\n").unwrap();
+        let mut fragment = Document::parse(
+            "#+TITLE: Fragment\n\
+             #+TODO: TODO DONE\n\
+             Text\n\
+             * c\n\
+             *** d\n",
+        )
+        .unwrap();
+        fragment.rebase_levels(2);
+        document.append(fragment);
+        assert_eq!(
+            document.to_string(),
+            "#+TITLE: Report\n\
+             #+TODO: TODO DONE\n\
+             Summary\n\
+             * a\n\
+             ** b\n\
+             Text\n\
+             ** c\n\
+             **** d\n"
+        );
+        assert_eq!(document.validate(), vec![]);
+    }
+
+    fn notes() -> Document {
+        "#+TITLE: Notes\n\
+         Intro\n\
+         * TODO One\n\
+         Text\n\
+         #+FILETAGS: :a:\n\
+         ** Two\n\
+         * Three"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let document = notes();
+        assert_eq!(
+            document
+                .keywords()
+                .map(|k| k.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["TITLE"]
+        );
+        assert_eq!(document.headlines.len(), 2);
+    }
+
+    #[test]
+    fn parse_headline() {
+        let document = notes();
+        let one = &document.headlines[0];
+        assert_eq!(
+            one.todo_keyword,
+            Some(TodoKeyword::Todo("TODO".to_string()))
+        );
+        let content = one.content().unwrap();
+        assert_eq!(content.span(), &Some(Span::new(32, 53)));
+        match &content.value()[..] {
+            [HeadlineContentSet::Section(section), HeadlineContentSet::Headline(two)] => {
+                match &section.content().unwrap().value()[..] {
+                    [ElementSet::Paragraph(_), ElementSet::Keyword(keyword)] => {
+                        assert_eq!(keyword.to_string(), "#+FILETAGS: :a:")
+                    }
+                    elements => panic!("expected a paragraph and a keyword: {:?}", elements),
+                }
+                assert_eq!(two.level, 2);
+            }
+            content => panic!("expected a section and a headline: {:?}", content),
+        }
+    }
+
+    #[test]
+    fn parse_without_final_newline() {
+        assert_eq!(Document::parse("* a\n** b\n"), "* a\n** b".parse());
+    }
+
+    #[test]
+    fn blank_lines_round_trip() {
+        let s = "#+TITLE: Blank lines\n\
+                 \n\
+                 Intro\n\
+                 continued\n\
+                 \n\
+                 \n\
+                 * Headline\n\
+                 :LOGBOOK:\n\
+                 - Note\n\
+                 \n\
+                 :END:\n\
+                 #+BEGIN_SRC sh\n\
+                 ls\n\
+                 #+END_SRC\n\
+                 \n\
+                 ** Child\n";
+        let document = Document::parse(s).unwrap();
+        let preface = document.preface.as_ref().unwrap().content().unwrap();
+        assert_eq!(
+            preface
+                .value()
+                .iter()
+                .map(SharedBehavior::post_blank)
+                .collect::<Vec<_>>(),
+            vec![Some(1), Some(0), Some(2)]
+        );
+        assert_eq!(document.to_string(), s);
+    }
+
+    #[test]
+    fn element_by_name() {
+        let s = "#+NAME: not-affiliated\n\
+                 \n\
+                 * Results\n\
+                 #+NAME: tbl-results\n\
+                 #+ATTR_HTML: :class results\n\
+                 | a | 1 |\n\
+                 :NOTES:\n\
+                 #+TBLNAME: source\n\
+                 #+BEGIN_SRC sh\n\
+                 ls\n\
+                 #+END_SRC\n\
+                 :END:\n";
+        let document = Document::parse(s).unwrap();
+        let table = document.element_by_name("tbl-results").unwrap();
+        assert!(matches!(table, ElementSet::Table(_)));
+        assert_eq!(
+            table
+                .affiliated_keywords()
+                .and_then(|keywords| keywords.value().attribute("class")),
+            Some("results")
+        );
+        assert!(matches!(
+            document.element_by_name("source"),
+            Some(ElementSet::SrcBlock(_))
+        ));
+        assert_eq!(document.element_by_name("not-affiliated"), None);
+        assert_eq!(document.keywords().count(), 1);
+        assert_eq!(
+            document.to_string(),
+            s.replace("#+TBLNAME: source", "#+NAME: source")
+        );
+    }
+
+    fn keywords_document() -> Document {
+        Document::parse(
+            "#+TITLE: My\n\
+             #+title: Notes\n\
+             #+AUTHOR: Jane Doe\n\
+             #+DATE: 2018-10-12\n\
+             #+LANGUAGE: en\n\
+             #+LANGUAGE: de\n\
+             #+FILETAGS: :work:notes:\n\
+             #+FILETAGS: :work:\n\
+             #+CATEGORY:\n\
+             * Headline\n\
+             #+AUTHOR: Not in the preface\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn keyword_values() {
+        let document = keywords_document();
+        let keywords = document.keywords();
+        assert_eq!(keywords.values("Language"), vec!["en", "de"]);
+        assert_eq!(keywords.get("FILETAGS").as_deref(), Some(":work:"));
+        assert_eq!(keywords.get("OPTIONS"), None);
+    }
+
+    #[test]
+    fn keywords_of_preface() {
+        assert_eq!(keywords_document().keywords().count(), 9);
+    }
+
+    #[test]
+    fn typed_keyword_getters() {
+        let document = keywords_document();
+        let keywords = document.keywords();
+        assert_eq!(keywords.title().as_deref(), Some("My Notes"));
+        assert_eq!(keywords.author().as_deref(), Some("Jane Doe"));
+        assert_eq!(keywords.date().as_deref(), Some("2018-10-12"));
+        assert_eq!(keywords.category(), None);
+    }
+
+    #[test]
+    fn language_and_file_tags() {
+        let document = keywords_document();
+        assert_eq!(document.language().as_deref(), Some("de"));
+        assert_eq!(document.file_tags(), vec!["work", "notes"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let document = Document::parse(
+            "#+TITLE: Notes\n\
+             * TODO [#A] Task <2018-10-12 Fri 10:00 +1w> :work:\n\
+             - [X] a *bold* [[https://example.com][link]]\n\
+             | x | y |\n",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(json.contains("\"2018-10-12\"") && json.contains("\"10:00\""));
+        let parsed: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.to_string(), document.to_string());
     }
 }
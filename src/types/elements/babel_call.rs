@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A babel call element.
 ///
@@ -15,11 +16,46 @@ use super::*;
 /// `FUNCTION` is the name of a [`SrcBlock`] to execute. `INSIDE-HEADER`, `ARGUEMENTS` and
 /// `END-HEADER` can contain everything except a newline (and their respective closing char).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BabelCall {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     /// The code block to call
     pub call: String,
-    pub inside_header: String,
+    /// The header arguments used when evaluating the called block.
+    pub inside_header: HeaderArgs,
+    /// The arguments of the call (e.g. `x=1, y=2`).
     pub arguments: String,
-    pub end_header: String,
+    /// The header arguments applied to the results of the call.
+    pub end_header: HeaderArgs,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl BabelCall {
+    /// Returns the header arguments of the `#+HEADER:` keywords, the inside header, the
+    /// arguments of the call (as `:var`) and the end header (in this order).
+    pub fn header_args(&self) -> HeaderArgs {
+        let mut args = header_keyword_args(self.affiliated_keywords.as_ref());
+        args.args.extend(self.inside_header.args.iter().cloned());
+        if !self.arguments.trim().is_empty() {
+            args.args
+                .push(("var".to_string(), self.arguments.trim().to_string()));
+        }
+        args.args.extend(self.end_header.args.iter().cloned());
+        args
+    }
+}
+
+impl fmt::Display for BabelCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "#+CALL: {}", self.call)?;
+        if !self.inside_header.is_empty() {
+            write!(f, "[{}]", self.inside_header)?;
+        }
+        write!(f, "({})", self.arguments)?;
+        if !self.end_header.is_empty() {
+            write!(f, " {}", self.end_header)?;
+        }
+        Ok(())
+    }
 }
@@ -1,4 +1,8 @@
 use super::*;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
 
 /// A clock element.
 ///
@@ -21,14 +25,24 @@ use super::*;
 /// `TIMESTAMP` and `DURATION` are optional. `TIMESTAMP` is a [`objects::Timestamp`].
 ///
 /// `DURATION` follows the pattern `=> HH:MM` where `HH` is a number containing any number of
-/// digits and `MM` is a two digit number.
+/// digits and `MM` is a two digit number. See [`OrgDuration`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Clock {
     pub timestamp: Option<objects::Timestamp>,
-    pub duration: Option<(u64, u8)>,
+    pub duration: Option<OrgDuration>,
+    pub(crate) post_blank: Option<usize>,
 }
 
 impl Clock {
+    pub fn new(timestamp: Option<objects::Timestamp>, duration: Option<OrgDuration>) -> Self {
+        Clock {
+            timestamp,
+            duration,
+            post_blank: None,
+        }
+    }
+
     pub fn status(&self) -> ClockStatus {
         match self.duration {
             Some(_) => ClockStatus::Closed,
@@ -39,7 +53,243 @@ impl Clock {
 
 /// The status of a [`Clock`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ClockStatus {
     Running,
     Closed,
 }
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CLOCK:")?;
+        if let Some(timestamp) = &self.timestamp {
+            write!(f, " {}", timestamp)?;
+        }
+        if let Some(duration) = self.duration {
+            write!(f, " => {:>5}", duration)?;
+        }
+        Ok(())
+    }
+}
+
+/// A duration in minutes (e.g. of a [`Clock`] or an effort).
+///
+/// Durations are written as `H:MM` (the hours can have any number of digits). They are parsed
+/// from `H:MM` (optionally after the `=>` of a clock), plain minutes or units like `1d 2h 30min`
+/// (the units of `org-duration-units`: `min`, `h`, `d`, `w`, `m` for 30 days and `y` for 365.25
+/// days). Units can be followed by `H:MM` (e.g. `1d 2:30`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrgDuration {
+    minutes: i64,
+}
+
+/// The units of `org-duration-units` in minutes (longest name first for parsing).
+const UNITS: [(&str, i64); 6] = [
+    ("min", 1),
+    ("h", 60),
+    ("d", 60 * 24),
+    ("w", 60 * 24 * 7),
+    ("m", 60 * 24 * 30),
+    ("y", 60 * 24 * 36525 / 100),
+];
+
+impl OrgDuration {
+    pub fn from_minutes(minutes: i64) -> Self {
+        OrgDuration { minutes }
+    }
+
+    pub fn from_hours_minutes(hours: i64, minutes: i64) -> Self {
+        OrgDuration {
+            minutes: hours * 60 + minutes,
+        }
+    }
+
+    /// The whole duration in minutes.
+    pub fn minutes(&self) -> i64 {
+        self.minutes
+    }
+
+    /// The whole hours of the duration.
+    pub fn hours(&self) -> i64 {
+        self.minutes / 60
+    }
+
+    /// Writes the duration with units (e.g. `1d 2h 30min`). Only days, hours and minutes are
+    /// used, units that are zero are left out.
+    pub fn to_units_string(&self) -> String {
+        let sign = if self.minutes < 0 { "-" } else { "" };
+        let minutes = self.minutes.abs();
+        let parts = [
+            (minutes / (60 * 24), "d"),
+            (minutes / 60 % 24, "h"),
+            (minutes % 60, "min"),
+        ];
+        let units: Vec<_> = parts
+            .iter()
+            .filter(|(value, _)| *value > 0)
+            .map(|(value, unit)| format!("{}{}", value, unit))
+            .collect();
+        if units.is_empty() {
+            "0min".to_string()
+        } else {
+            format!("{}{}", sign, units.join(" "))
+        }
+    }
+}
+
+impl From<chrono::Duration> for OrgDuration {
+    fn from(duration: chrono::Duration) -> Self {
+        OrgDuration::from_minutes(duration.num_minutes())
+    }
+}
+
+impl From<OrgDuration> for chrono::Duration {
+    fn from(duration: OrgDuration) -> Self {
+        chrono::Duration::minutes(duration.minutes)
+    }
+}
+
+impl Add for OrgDuration {
+    type Output = OrgDuration;
+
+    fn add(self, other: OrgDuration) -> OrgDuration {
+        OrgDuration::from_minutes(self.minutes + other.minutes)
+    }
+}
+
+impl Sub for OrgDuration {
+    type Output = OrgDuration;
+
+    fn sub(self, other: OrgDuration) -> OrgDuration {
+        OrgDuration::from_minutes(self.minutes - other.minutes)
+    }
+}
+
+impl AddAssign for OrgDuration {
+    fn add_assign(&mut self, other: OrgDuration) {
+        self.minutes += other.minutes;
+    }
+}
+
+impl SubAssign for OrgDuration {
+    fn sub_assign(&mut self, other: OrgDuration) {
+        self.minutes -= other.minutes;
+    }
+}
+
+impl Sum for OrgDuration {
+    fn sum<I: Iterator<Item = OrgDuration>>(iter: I) -> Self {
+        iter.fold(OrgDuration::default(), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a OrgDuration> for OrgDuration {
+    fn sum<I: Iterator<Item = &'a OrgDuration>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+/// Writes the duration as `H:MM` (padded to the width of the formatter).
+impl fmt::Display for OrgDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.minutes < 0 { "-" } else { "" };
+        let minutes = self.minutes.abs();
+        f.pad(&format!("{}{}:{:02}", sign, minutes / 60, minutes % 60))
+    }
+}
+
+/// The error returned when parsing an [`OrgDuration`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseDurationError {
+    input: String,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid duration: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+impl FromStr for OrgDuration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDurationError {
+            input: s.to_string(),
+        };
+        let input = s.trim();
+        let input = input.strip_prefix("=>").unwrap_or(input).trim();
+        if input.is_empty() {
+            return Err(error());
+        }
+        let mut minutes = 0i64;
+        let mut parts = input.split_whitespace().peekable();
+        while let Some(part) = parts.next() {
+            let digits = part
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(part.len());
+            let (value, rest) = part.split_at(digits);
+            let value: i64 = value.parse().map_err(|_| error())?;
+            let part = match rest {
+                // a plain number is only allowed alone (as minutes)
+                "" if minutes == 0 && parts.peek().is_none() => Some(value),
+                // `H:MM` is only allowed as the last part
+                _ if rest.starts_with(':') && parts.peek().is_none() => {
+                    let rest = &rest[1..];
+                    if rest.len() != 2 {
+                        return Err(error());
+                    }
+                    let extra: i64 = rest.parse().map_err(|_| error())?;
+                    value.checked_mul(60).map(|hours| hours + extra)
+                }
+                unit => {
+                    let (_, factor) = UNITS
+                        .iter()
+                        .find(|(name, _)| *name == unit)
+                        .ok_or_else(error)?;
+                    value.checked_mul(*factor)
+                }
+            };
+            minutes = part
+                .and_then(|part| minutes.checked_add(part))
+                .ok_or_else(error)?;
+        }
+        Ok(OrgDuration::from_minutes(minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration() {
+        let parse = |s: &str| s.parse::<OrgDuration>().map(|duration| duration.minutes());
+        assert_eq!(parse("=>  1:30"), Ok(90));
+        assert_eq!(parse("26:10"), Ok(1570));
+        assert_eq!(parse("45"), Ok(45));
+        assert_eq!(parse("1d 2h"), Ok(1560));
+        assert_eq!(parse("1w 30min"), Ok(10110));
+        assert_eq!(parse("1d 2:05"), Ok(1565));
+        assert!(parse("1:5").is_err());
+        assert!(parse("2 hours").is_err());
+        assert!(parse("").is_err());
+
+        let duration = OrgDuration::from_hours_minutes(26, 5);
+        assert_eq!(duration.to_string(), "26:05");
+        assert_eq!(format!("{:>5}", OrgDuration::from_minutes(90)), " 1:30");
+        assert_eq!(duration.to_units_string(), "1d 2h 5min");
+        assert_eq!(
+            (OrgDuration::from_minutes(30) - duration).to_string(),
+            "-25:35"
+        );
+        let durations = [OrgDuration::from_minutes(30), OrgDuration::from_minutes(45)];
+        assert_eq!(durations.iter().sum::<OrgDuration>().to_string(), "1:15");
+        let mut total = duration;
+        total += OrgDuration::from_minutes(55);
+        assert_eq!(total.hours(), 27);
+    }
+}
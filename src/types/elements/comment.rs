@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A comment element.
 ///
@@ -21,7 +22,17 @@ use super::*;
 ///
 /// Consecutive comment lines are accumulated into one comment.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Comment {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    /// The lines of the comment without the leading `#` and space.
     pub value: String,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl fmt::Display for Comment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write_prefixed_lines(f, "#", &self.value)
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A comment block.
 ///
@@ -17,7 +18,34 @@ use super::*;
 /// `CONTENTS` can contain anything except a line `#+END_COMMENT` on its own. Lines beginning
 /// with stars must be quoted by a comma. `CONTENTS` will not be parsed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommentBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub value: String,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl CommentBlock {
+    pub fn new(value: String) -> Self {
+        CommentBlock {
+            affiliated_keywords: None,
+            value,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for CommentBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{}", keyword("BEGIN_COMMENT"))?;
+        write_block_content(f, &self.value)?;
+        write!(f, "#+{}", keyword("END_COMMENT"))
+    }
 }
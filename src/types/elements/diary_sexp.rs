@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A diary sexp.
 ///
@@ -18,7 +19,58 @@ use super::*;
 /// `VALUE` can contain any character except a newline. The expression has to start at the
 /// beginning of the line.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DiarySexp {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub value: String,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl DiarySexp {
+    pub fn new(value: impl Into<String>) -> Self {
+        DiarySexp {
+            affiliated_keywords: None,
+            value: value.into(),
+            post_blank: None,
+        }
+    }
+
+    /// Returns the expression without the outer parentheses (e.g. `diary-float t 4 4`).
+    ///
+    /// See [`DiaryExpr`](`crate::diary::DiaryExpr`) for evaluating it.
+    pub fn sexp(&self) -> &str {
+        &self.value[..self.sexp_end()]
+    }
+
+    /// Returns the text after the expression (shown in the agenda).
+    pub fn text(&self) -> &str {
+        self.value[self.sexp_end()..]
+            .trim_start_matches(')')
+            .trim()
+    }
+
+    /// Returns the index of the parenthesis closing the expression (or the length).
+    fn sexp_end(&self) -> usize {
+        let mut depth = 1;
+        for (index, c) in self.value.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return index;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.value.len()
+    }
+}
+
+impl fmt::Display for DiarySexp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "%%({}", self.value)
+    }
 }
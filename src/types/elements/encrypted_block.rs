@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// An encrypted block of text.
+///
+/// # Semantics
+///
+/// Usually the encrypted body of an entry tagged with `:crypt:` (see [`crypt`]). The message is
+/// opaque: it is never parsed or reformatted.
+///
+/// # Syntax
+///
+/// An ASCII armored PGP message:
+///
+/// ```text
+/// -----BEGIN PGP MESSAGE-----
+///
+/// CIPHERTEXT
+/// -----END PGP MESSAGE-----
+/// ```
+///
+/// [`crypt`]: `crate::crypt`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncryptedBlock {
+    /// The whole message including the `BEGIN` and `END` lines.
+    pub message: String,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl EncryptedBlock {
+    pub fn new(message: impl Into<String>) -> Self {
+        EncryptedBlock {
+            message: message.into(),
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for EncryptedBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
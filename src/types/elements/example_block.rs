@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// An example block.
 ///
@@ -20,8 +21,40 @@ use super::*;
 ///
 /// `FLAGS` see [`BlockFlags`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExampleBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub value: String,
     pub flags: BlockFlags,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl ExampleBlock {
+    pub fn new(value: String) -> Self {
+        ExampleBlock {
+            affiliated_keywords: None,
+            value,
+            flags: BlockFlags::default(),
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for ExampleBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        write!(f, "#+{}", keyword("BEGIN_EXAMPLE"))?;
+        if !self.flags.is_default() {
+            write!(f, " {}", self.flags)?;
+        }
+        writeln!(f)?;
+        write_block_content(f, &self.value)?;
+        write!(f, "#+{}", keyword("END_EXAMPLE"))
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// An export block.
 ///
@@ -19,9 +20,37 @@ use super::*;
 ///
 /// `BACKEND` can contain any alpha-numerical character. Case is ignored.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExportBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub value: String,
     /// Always lowercase.
     pub backend: String,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl ExportBlock {
+    pub fn new(backend: &str, value: String) -> Self {
+        ExportBlock {
+            affiliated_keywords: None,
+            value,
+            backend: backend.to_lowercase(),
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for ExportBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{} {}", keyword("BEGIN_EXPORT"), self.backend)?;
+        write_block_content(f, &self.value)?;
+        write!(f, "#+{}", keyword("END_EXPORT"))
+    }
 }
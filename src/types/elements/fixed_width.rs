@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A fixed width area.
 ///
@@ -13,7 +14,27 @@ use super::*;
 ///
 /// Consecutive fixed width lines are accumulated.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FixedWidth {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    /// The lines of the fixed width area without the leading `:` and space.
     pub value: String,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl FixedWidth {
+    pub fn new(value: String) -> Self {
+        FixedWidth {
+            affiliated_keywords: None,
+            value,
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for FixedWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write_prefixed_lines(f, ":", &self.value)
+    }
 }
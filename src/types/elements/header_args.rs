@@ -0,0 +1,403 @@
+use super::*;
+use std::fmt;
+
+/// The header arguments of a [`SrcBlock`] or [`BabelCall`] (e.g. `:var x=1 :results output`).
+///
+/// # Syntax
+///
+/// ```text
+/// :KEY VALUE :KEY VALUE ...
+/// ```
+///
+/// `KEY` can contain anything except whitespace. `VALUE` is everything up to the next word
+/// starting with a colon (outside of double quotes). It is optional.
+///
+/// Header arguments are also set by `#+HEADER:` keywords and (for babel calls) the arguments
+/// of the call. Later arguments override earlier ones with the same key (except for `:var`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderArgs {
+    /// The keys (without the colon) and values in order.
+    ///
+    /// Text before the first key is kept with an empty key.
+    pub args: Vec<(String, String)>,
+}
+
+/// A variable set with `:var NAME=VALUE`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Var {
+    pub name: String,
+    /// The value without surrounding double quotes.
+    pub value: String,
+}
+
+/// The settings of the `:results` header argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultsParams {
+    pub collection: Collection,
+    pub kind: Option<ResultKind>,
+    pub format: Option<ResultFormat>,
+    pub handling: Handling,
+}
+
+/// What the result of a block is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Collection {
+    /// `value` - the value of the last expression.
+    #[default]
+    Value,
+    /// `output` - everything written to the standard output.
+    Output,
+}
+
+/// How the result is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResultKind {
+    /// `table` or `vector`
+    Table,
+    /// `list` - every line becomes an item of a plain list.
+    List,
+    /// `scalar` or `verbatim`
+    Scalar,
+    /// `file` - the result is a link to the file.
+    File,
+}
+
+/// How the result is inserted into the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResultFormat {
+    /// `raw` - as org text.
+    Raw,
+    /// `org` - in an `org` source block.
+    Org,
+    /// `html` - in an HTML export block.
+    Html,
+    /// `latex` - in a LaTeX export block.
+    Latex,
+    /// `code` - in a source block of the same language.
+    Code,
+    /// `drawer` - as org text in a `RESULTS` drawer.
+    Drawer,
+    /// `pp` - like the default (a fixed width area).
+    Pp,
+}
+
+/// What happens with the results of a previous evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Handling {
+    /// `replace` - they are replaced.
+    #[default]
+    Replace,
+    /// `silent` - the results are not inserted.
+    Silent,
+    /// `none` - the results are not inserted.
+    None,
+    /// `append` - [`babel`](`crate::babel`) treats it like `replace`.
+    Append,
+    /// `prepend` - [`babel`](`crate::babel`) treats it like `replace`.
+    Prepend,
+}
+
+/// Which parts of a block are exported (`:exports`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Exports {
+    #[default]
+    Code,
+    Results,
+    Both,
+    None,
+}
+
+/// The value of the `:tangle` header argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tangle {
+    /// `no` (the default)
+    #[default]
+    No,
+    /// `yes` - the block is written to a file named like the org file with the extension of
+    /// the language.
+    Yes,
+    /// The file the block is written to.
+    File(String),
+}
+
+impl HeaderArgs {
+    /// Parses header arguments (e.g. `:var x=1 :results output`).
+    pub fn parse(s: &str) -> Self {
+        let mut args = HeaderArgs::default();
+        args.extend(s);
+        args
+    }
+
+    /// Returns `true` if there are no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Appends the parsed arguments.
+    pub fn extend(&mut self, s: &str) {
+        let keys: Vec<_> = words(s)
+            .into_iter()
+            .filter(|&(start, end)| s[start..].starts_with(':') && end - start > 1)
+            .collect();
+        let first = keys.first().map_or(s.len(), |&(start, _)| start);
+        if !s[..first].trim().is_empty() {
+            self.args
+                .push((String::new(), s[..first].trim().to_string()));
+        }
+        for (index, &(start, end)) in keys.iter().enumerate() {
+            let next = keys.get(index + 1).map_or(s.len(), |&(start, _)| start);
+            self.args.push((
+                s[start + 1..end].to_string(),
+                s[end..next].trim().to_string(),
+            ));
+        }
+    }
+
+    /// Returns the value of the last argument with the key (e.g. `results`).
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.args
+            .iter()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the argument. Replaces the value of the last argument with the key (if any).
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        match self
+            .args
+            .iter_mut()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&key))
+        {
+            Some((_, old)) => *old = value,
+            None => self.args.push((key, value)),
+        }
+    }
+
+    /// Removes all arguments with the key.
+    pub fn remove(&mut self, key: &str) {
+        self.args.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+    }
+
+    /// Returns the variables of all `:var` arguments (`:var x=1, y="a b"`).
+    pub fn vars(&self) -> Vec<Var> {
+        self.args
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("var"))
+            .flat_map(|(_, value)| split_outside_quotes(value, ','))
+            .filter_map(|assignment| {
+                let equals = assignment.find('=')?;
+                let value = assignment[equals + 1..].trim();
+                let value = if value.len() > 1 && value.starts_with('"') && value.ends_with('"') {
+                    &value[1..value.len() - 1]
+                } else {
+                    value
+                };
+                Some(Var {
+                    name: assignment[..equals].trim().to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the settings of the `:results` arguments. Unknown words are ignored.
+    pub fn results(&self) -> ResultsParams {
+        let mut params = ResultsParams::default();
+        let words = self
+            .args
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("results"))
+            .flat_map(|(_, value)| value.split_whitespace());
+        for word in words {
+            match word.to_lowercase().as_str() {
+                "value" => params.collection = Collection::Value,
+                "output" => params.collection = Collection::Output,
+                "table" | "vector" => params.kind = Some(ResultKind::Table),
+                "list" => params.kind = Some(ResultKind::List),
+                "scalar" | "verbatim" => params.kind = Some(ResultKind::Scalar),
+                "file" => params.kind = Some(ResultKind::File),
+                "raw" => params.format = Some(ResultFormat::Raw),
+                "org" => params.format = Some(ResultFormat::Org),
+                "html" => params.format = Some(ResultFormat::Html),
+                "latex" => params.format = Some(ResultFormat::Latex),
+                "code" => params.format = Some(ResultFormat::Code),
+                "drawer" => params.format = Some(ResultFormat::Drawer),
+                "pp" => params.format = Some(ResultFormat::Pp),
+                "replace" => params.handling = Handling::Replace,
+                "silent" => params.handling = Handling::Silent,
+                "none" => params.handling = Handling::None,
+                "append" => params.handling = Handling::Append,
+                "prepend" => params.handling = Handling::Prepend,
+                _ => {}
+            }
+        }
+        params
+    }
+
+    /// Returns the value of the `:exports` argument.
+    pub fn exports(&self) -> Exports {
+        match self.get("exports").map(str::to_lowercase).as_deref() {
+            Some("results") => Exports::Results,
+            Some("both") => Exports::Both,
+            Some("none") => Exports::None,
+            _ => Exports::Code,
+        }
+    }
+
+    /// Returns `false` if evaluating the block is disabled (`:eval no` or `:eval never`).
+    pub fn evaluate(&self) -> bool {
+        !matches!(
+            self.get("eval").map(str::to_lowercase).as_deref(),
+            Some("no") | Some("never")
+        )
+    }
+
+    /// Returns the value of the `:tangle` argument.
+    pub fn tangle(&self) -> Tangle {
+        match self.get("tangle") {
+            None | Some("") | Some("no") => Tangle::No,
+            Some("yes") => Tangle::Yes,
+            Some(file) => Tangle::File(file.trim_matches('"').to_string()),
+        }
+    }
+}
+
+/// Returns the start and end of the words. Whitespace inside double quotes doesn't separate
+/// words.
+fn words(s: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (index, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if let Some(start) = start.take() {
+                words.push((start, index));
+            }
+        } else if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, s.len()));
+    }
+    words
+}
+
+/// Splits the string at the separator if it is not inside double quotes.
+fn split_outside_quotes(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (index, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == separator && !quoted {
+            parts.push(&s[start..index]);
+            start = index + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+impl fmt::Display for HeaderArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|(key, value)| match (key.is_empty(), value.is_empty()) {
+                (true, _) => value.clone(),
+                (false, true) => format!(":{}", key),
+                (false, false) => format!(":{} {}", key, value),
+            });
+        write!(f, "{}", args.format(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARGS: &str =
+        "-x :var x=6, s=\"a :b\"  :results output table :tangle \"src/main.rs\" :eval";
+
+    #[test]
+    fn parse_header_args() {
+        let args = HeaderArgs::parse(ARGS);
+        assert_eq!(args.args[0], (String::new(), "-x".to_string()));
+        assert_eq!(args.get("eval"), Some(""));
+        assert_eq!(args.to_string(), ARGS.replace("  ", " "));
+    }
+
+    #[test]
+    fn vars() {
+        assert_eq!(
+            HeaderArgs::parse(ARGS).vars(),
+            [
+                Var {
+                    name: "x".to_string(),
+                    value: "6".to_string(),
+                },
+                Var {
+                    name: "s".to_string(),
+                    value: "a :b".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn results_exports_and_tangle() {
+        let args = HeaderArgs::parse(ARGS);
+        let results = args.results();
+        assert_eq!(results.collection, Collection::Output);
+        assert_eq!(results.kind, Some(ResultKind::Table));
+        assert_eq!(results.handling, Handling::Replace);
+        assert_eq!(args.exports(), Exports::Code);
+        assert_eq!(args.tangle(), Tangle::File("src/main.rs".to_string()));
+    }
+
+    #[test]
+    fn set_header_args() {
+        let mut args = HeaderArgs::parse(":tangle no");
+        args.set("tangle", "yes");
+        args.set("exports", "both");
+        assert_eq!(args.tangle(), Tangle::Yes);
+        assert_eq!(args.to_string(), ":tangle yes :exports both");
+    }
+
+    #[test]
+    fn babel_call_header_args() {
+        let call = BabelCall {
+            affiliated_keywords: None,
+            call: "double".to_string(),
+            inside_header: HeaderArgs::parse(":results silent"),
+            arguments: "x=2".to_string(),
+            end_header: HeaderArgs::parse(":results output"),
+            post_blank: None,
+        };
+        let args = call.header_args();
+        assert_eq!(args.vars()[0].value, "2");
+        assert_eq!(args.results().collection, Collection::Output);
+        assert_eq!(
+            call.to_string(),
+            "#+CALL: double[:results silent](x=2) :results output"
+        );
+    }
+}
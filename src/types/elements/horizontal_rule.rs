@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A horizontal line.
 ///
@@ -14,6 +15,30 @@ use super::*;
 /// -----
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HorizontalRule {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl HorizontalRule {
+    pub fn new() -> Self {
+        HorizontalRule {
+            affiliated_keywords: None,
+            post_blank: None,
+        }
+    }
+}
+
+impl Default for HorizontalRule {
+    fn default() -> Self {
+        HorizontalRule::new()
+    }
+}
+
+impl fmt::Display for HorizontalRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "-----")
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A keyword.
 ///
@@ -18,47 +19,56 @@ use super::*;
 ///
 /// `VALUE` can contain any character except a newline.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Keyword {
     affiliated_keywords: Spanned<AffiliatedKeywords>,
     pub key: String,
     pub value: Spanned<Vec<KeywordValueSetOfObjects>>,
+    pub(crate) post_blank: Option<usize>,
 }
-impl Element for Keyword {}
+impl Keyword {
+    pub fn new(key: impl Into<String>, value: Vec<KeywordValueSetOfObjects>) -> Self {
+        Keyword {
+            affiliated_keywords: Spanned::new(AffiliatedKeywords::new()),
+            key: key.into(),
+            value: Spanned::new(value),
+            post_blank: None,
+        }
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, Some(&self.affiliated_keywords))?;
+        write!(f, "#+{}: {}", self.key, self.value.value().iter().format(""))
+    }
+}
+
 impl HasAffiliatedKeywords for Keyword {
     fn affiliated_keywords(&self) -> Option<&Spanned<AffiliatedKeywords>> {
         Some(&self.affiliated_keywords)
     }
 }
 
-/// The set of objects a [`Keyword`] can contain.
-///
-/// Keywords can't contain [`objects::FootnoteReference`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum KeywordValueSetOfObjects {
-    RawString(String),
-    Entity(objects::Entity),
-    ExportSnippet(objects::ExportSnippet),
-    InlineBabelCall(objects::InlineBabelCall),
-    InlineSrcBlock(objects::InlineSrcBlock),
-    LatexFragment(objects::LatexFragment),
-    LineBreak(objects::LineBreak),
-    Link(objects::Link),
-    Macro(objects::Macro),
-    RadioTarget(objects::RadioTarget),
-    StatisticsCookie(objects::StatisticsCookie),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    Target(objects::Target),
-    TextMarkup(objects::TextMarkup),
-    Timestamp(objects::Timestamp),
-}
-
-impl AsRawString for KeywordValueSetOfObjects {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let KeywordValueSetOfObjects::RawString(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+define_object_set! {
+    /// The set of objects a [`Keyword`] can contain.
+    ///
+    /// Keywords can't contain [`objects::FootnoteReference`].
+    KeywordValueSetOfObjects from StandardSet {
+        Entity,
+        ExportSnippet,
+        InlineBabelCall,
+        InlineSrcBlock,
+        LatexFragment,
+        LineBreak,
+        Link,
+        Macro,
+        RadioTarget,
+        StatisticsCookie,
+        Subscript,
+        Superscript,
+        Target,
+        TextMarkup,
+        Timestamp,
     }
 }
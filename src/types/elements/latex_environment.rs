@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A LaTeX environment.
 ///
@@ -20,8 +21,17 @@ use super::*;
 ///
 /// `CONTENT` can be anything except `\end{ENVIRONMENT}`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LatexEnvironment {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     /// Contains everything including `\begin...` and `\end`.
     pub value: String,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl fmt::Display for LatexEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "{}", self.value)
+    }
 }
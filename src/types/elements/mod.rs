@@ -7,9 +7,11 @@ mod clock;
 mod comment;
 mod comment_block;
 mod diary_sexp;
+mod encrypted_block;
 mod example_block;
 mod export_block;
 mod fixed_width;
+mod header_args;
 mod horizontal_rule;
 mod keyword;
 mod latex_environment;
@@ -19,13 +21,17 @@ mod planning;
 mod src_block;
 
 pub use self::babel_call::BabelCall;
-pub use self::clock::{Clock, ClockStatus};
+pub use self::clock::{Clock, ClockStatus, OrgDuration, ParseDurationError};
 pub use self::comment::Comment;
 pub use self::comment_block::CommentBlock;
 pub use self::diary_sexp::DiarySexp;
+pub use self::encrypted_block::EncryptedBlock;
 pub use self::example_block::ExampleBlock;
 pub use self::export_block::ExportBlock;
 pub use self::fixed_width::FixedWidth;
+pub use self::header_args::{
+    Collection, Exports, Handling, HeaderArgs, ResultFormat, ResultKind, ResultsParams, Tangle, Var,
+};
 pub use self::horizontal_rule::HorizontalRule;
 pub use self::keyword::{Keyword, KeywordValueSetOfObjects};
 pub use self::latex_environment::LatexEnvironment;
@@ -52,6 +58,7 @@ pub use self::src_block::SrcBlock;
 ///
 /// `FMT` can contain everything except `"` and newlines.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockFlags {
     pub number_lines: Option<NumberLinesFlag>,
     /// Default: false
@@ -66,7 +73,77 @@ pub struct BlockFlags {
 /// Flag of [`BlockFlags`] that defines if line numbering is continued or start fresh (and
 /// optionally from where)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NumberLinesFlag {
     Continued(Option<u64>),
     New(Option<u64>),
 }
+
+impl Default for BlockFlags {
+    fn default() -> Self {
+        BlockFlags {
+            number_lines: None,
+            preserve_indent: false,
+            retain_labels: true,
+            label_fmt: None,
+        }
+    }
+}
+
+impl BlockFlags {
+    /// Returns `true` if no flag is set (see [`Default`]).
+    pub fn is_default(&self) -> bool {
+        *self == BlockFlags::default()
+    }
+}
+
+impl fmt::Display for BlockFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut flags = Vec::new();
+        match &self.number_lines {
+            Some(NumberLinesFlag::Continued(amount)) => flags.push(number_flag("+n", amount)),
+            Some(NumberLinesFlag::New(amount)) => flags.push(number_flag("-n", amount)),
+            None => {}
+        }
+        if self.preserve_indent {
+            flags.push("-i".to_string());
+        }
+        if !self.retain_labels {
+            flags.push("-r".to_string());
+        }
+        if let Some(label_fmt) = &self.label_fmt {
+            flags.push(format!("-l \"{}\"", label_fmt));
+        }
+        write!(f, "{}", flags.iter().format(" "))
+    }
+}
+
+fn number_flag(flag: &str, amount: &Option<u64>) -> String {
+    match amount {
+        Some(amount) => format!("{} {}", flag, amount),
+        None => flag.to_string(),
+    }
+}
+
+/// Returns the header arguments of the `#+HEADER:` keywords.
+fn header_keyword_args(keywords: Option<&Spanned<AffiliatedKeywords>>) -> HeaderArgs {
+    let mut args = HeaderArgs::default();
+    for header in keywords.into_iter().flat_map(|keywords| keywords.value().headers()) {
+        args.extend(header);
+    }
+    args
+}
+
+/// Writes every line of `value` prefixed with `prefix` (and a space if the line is not empty).
+///
+/// Used for elements like [`Comment`] and [`FixedWidth`].
+fn write_prefixed_lines(f: &mut fmt::Formatter<'_>, prefix: &str, value: &str) -> fmt::Result {
+    let lines = value.lines().map(|line| {
+        if line.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{} {}", prefix, line)
+        }
+    });
+    write!(f, "{}", lines.format("\n"))
+}
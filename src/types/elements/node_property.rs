@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// A node property.
 ///
 /// # Semantics
@@ -18,7 +20,18 @@
 ///
 /// `VALUE` can contain anything but a newline character.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeProperty {
     pub name: String,
     pub value: String,
 }
+
+impl fmt::Display for NodeProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value.is_empty() {
+            write!(f, ":{}:", self.name)
+        } else {
+            write!(f, ":{}: {}", self.name, self.value)
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A paragraph.
 ///
@@ -7,19 +8,24 @@ use super::*;
 /// A paragraph is a list of strings and objects ([`SecondaryString`]). Line breaks in the text
 /// are ignored and only [`objects::LineBreak`] will be recognized as a line break.
 ///
+/// Lines that would be parsed as another element (e.g. a headline) are escaped with a zero
+/// width space when writing the paragraph.
+///
 /// # Syntax
 ///
 /// Everything that is not another element is a paragraph. Empty lines and other elements end
 /// paragraphs but all inner elements of the current paragraph must be closed first.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Paragraph {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     /// The content of the paragraph.
     ///
     /// Newlines are ignored and are not stored here.
     ///
     /// TODO also store the ignored newlines somewhere/somehow.
     pub content: SecondaryString<StandardSet>,
+    pub(crate) post_blank: Option<usize>,
 }
 
 impl Paragraph {
@@ -27,6 +33,113 @@ impl Paragraph {
         Paragraph {
             affiliated_keywords: None,
             content,
+            post_blank: None,
+        }
+    }
+
+    /// Rewraps the paragraph so its lines are at most `width` characters long (like
+    /// `fill-paragraph`).
+    ///
+    /// Only the whitespace between words is changed. Objects (e.g. links and timestamps) are
+    /// never broken, so lines with long words or objects can be longer. Line breaks (`\\`) are
+    /// kept.
+    pub fn fill(&mut self, width: usize) {
+        let mut words: Vec<Vec<StandardSet>> = Vec::new();
+        let mut word = Vec::new();
+        for object in self.content.iter().cloned() {
+            match object {
+                StandardSet::RawString(s) => {
+                    for (i, part) in s.split(char::is_whitespace).enumerate() {
+                        if i > 0 && !word.is_empty() {
+                            words.push(std::mem::take(&mut word));
+                        }
+                        if !part.is_empty() {
+                            word.push(StandardSet::RawString(part.to_string()));
+                        }
+                    }
+                }
+                StandardSet::LineBreak(_) => {
+                    word.push(object);
+                    words.push(std::mem::take(&mut word));
+                }
+                object => word.push(object),
+            }
         }
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        let mut filled = Vec::new();
+        let mut column = 0;
+        for word in words {
+            let len: usize = word
+                .iter()
+                .map(|object| object.to_string().trim_end_matches('\n').chars().count())
+                .sum();
+            if column > 0 {
+                if column + 1 + len > width {
+                    push_text(&mut filled, "\n");
+                    column = 0;
+                } else {
+                    push_text(&mut filled, " ");
+                    column += 1;
+                }
+            }
+            column += len;
+            if let Some(StandardSet::LineBreak(_)) = word.last() {
+                column = 0;
+            }
+            for object in word {
+                match object {
+                    StandardSet::RawString(s) => push_text(&mut filled, &s),
+                    object => filled.push(object),
+                }
+            }
+        }
+        self.content = filled.into();
+    }
+
+    /// Joins the lines of the paragraph (except at line breaks) into one.
+    pub fn unfill(&mut self) {
+        self.fill(usize::MAX);
+    }
+}
+
+/// Appends text to the objects (merged with a raw string at the end).
+fn push_text(objects: &mut Vec<StandardSet>, text: &str) {
+    match objects.last_mut() {
+        Some(StandardSet::RawString(s)) => s.push_str(text),
+        _ => objects.push(StandardSet::RawString(text.to_string())),
+    }
+}
+
+impl fmt::Display for Paragraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "{}", escape_paragraph(&self.content.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::parse_objects;
+
+    #[test]
+    fn fill() {
+        let mut paragraph = Paragraph::new(
+            parse_objects("a long  line with\n[[https://orgmode.org][a link]] and\\\\\nbreak")
+                .into(),
+        );
+        paragraph.fill(10);
+        assert_eq!(
+            paragraph.to_string(),
+            "a long\nline with\n[[https://orgmode.org][a link]]\nand\\\\\nbreak"
+        );
+        paragraph.unfill();
+        assert_eq!(
+            paragraph.to_string(),
+            "a long line with [[https://orgmode.org][a link]] and\\\\\nbreak"
+        );
     }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A planning element.
 ///
@@ -20,9 +21,30 @@ use super::*;
 /// `TIMESTAMP` is a [`objects::Timestamp`].
 ///
 /// Consecutive planning items are aggregated into one.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Planning {
     pub closed: Option<objects::Timestamp>,
     pub deadline: Option<objects::Timestamp>,
     pub scheduled: Option<objects::Timestamp>,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl fmt::Display for Planning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [
+            ("CLOSED", &self.closed),
+            ("DEADLINE", &self.deadline),
+            ("SCHEDULED", &self.scheduled),
+        ];
+        let parts = parts
+            .iter()
+            .filter_map(|(keyword, timestamp)| {
+                timestamp
+                    .as_ref()
+                    .map(|timestamp| format!("{}: {}", keyword, timestamp))
+            })
+            .format(" ");
+        write!(f, "{}", parts)
+    }
 }
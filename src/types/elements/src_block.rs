@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A block of source code.
 ///
@@ -24,9 +25,59 @@ use super::*;
 ///
 /// `ARGUMENTS` can contain any character except a newline.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SrcBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub language: String,
     pub flags: BlockFlags,
-    pub arguments: String,
+    pub arguments: HeaderArgs,
+    /// The unparsed content of the block.
+    pub value: String,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl SrcBlock {
+    pub fn new(language: String, value: String) -> Self {
+        SrcBlock {
+            affiliated_keywords: None,
+            language,
+            flags: BlockFlags::default(),
+            arguments: HeaderArgs::default(),
+            value,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+
+    /// Returns the header arguments of the `#+HEADER:` keywords followed by the arguments of the
+    /// block.
+    pub fn header_args(&self) -> HeaderArgs {
+        let mut args = header_keyword_args(self.affiliated_keywords.as_ref());
+        args.args.extend(self.arguments.args.iter().cloned());
+        args
+    }
+}
+
+impl fmt::Display for SrcBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        write!(f, "#+{}", keyword("BEGIN_SRC"))?;
+        if !self.language.is_empty() {
+            write!(f, " {}", self.language)?;
+        }
+        if !self.flags.is_default() {
+            write!(f, " {}", self.flags)?;
+        }
+        if !self.arguments.is_empty() {
+            write!(f, " {}", self.arguments)?;
+        }
+        writeln!(f)?;
+        write_block_content(f, &self.value)?;
+        write!(f, "#+{}", keyword("END_SRC"))
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A center block.
 ///
@@ -17,9 +18,26 @@ use super::*;
 /// `CONTENTS` can contain anything except a line `#+END_CENTER` on its own. Lines beginning
 /// with stars must be quoted by comma. `CONTENTS` will not be parsed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CenterBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<String>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<String>,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl CenterBlock {
+    pub fn new(content: Spanned<String>) -> Self {
+        CenterBlock {
+            affiliated_keywords: None,
+            content,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
 }
 
 impl Parent<String> for CenterBlock {
@@ -27,3 +45,13 @@ impl Parent<String> for CenterBlock {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for CenterBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{}", keyword("BEGIN_CENTER"))?;
+        write_block_content(f, self.content.value())?;
+        write!(f, "#+{}", keyword("END_CENTER"))
+    }
+}
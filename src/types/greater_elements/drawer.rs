@@ -1,5 +1,6 @@
 use crate::types::{
-    AffiliatedKeywords, Element, ElementSet, GreaterElement, HasAffiliatedKeywords, Parent, Spanned,
+    join_compact_elements, serialize, write_affiliated_keywords, AffiliatedKeywords, ElementSet,
+    HasAffiliatedKeywords, Parent, Spanned,
 };
 use std::fmt;
 
@@ -24,21 +25,36 @@ use std::fmt;
 ///
 /// Drawers can be indented.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Drawer {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<ElementSet>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>,
     pub name: String,
+    pub(crate) post_blank: Option<usize>,
     // hiddenp: bool,
 }
 
+impl Drawer {
+    pub fn new(name: String, content: Spanned<Vec<ElementSet>>) -> Self {
+        Drawer {
+            affiliated_keywords: None,
+            content,
+            name,
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
+}
+
 impl Parent<Vec<ElementSet>> for Drawer {
     fn content(&self) -> Option<&Spanned<Vec<ElementSet>>> {
         Some(&self.content)
     }
 }
 
-impl Element for Drawer {}
-impl GreaterElement for Drawer {}
 impl HasAffiliatedKeywords for Drawer {
     fn affiliated_keywords(&self) -> Option<&Spanned<AffiliatedKeywords>> {
         self.affiliated_keywords.as_ref()
@@ -47,10 +63,12 @@ impl HasAffiliatedKeywords for Drawer {
 
 impl fmt::Display for Drawer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
         writeln!(f, ":{}:", self.name)?;
-        for _line in self.content() {
-            // TODO this should work once all elements impl Display (also impl Display for ElementSet)
-            //writeln!(f, "{}", line)?;
+        // Drawers usually contain lines which don't need to be separated (e.g. clock lines).
+        let content = join_compact_elements(self.content.value());
+        if !content.is_empty() {
+            writeln!(f, "{}", serialize::indent(&content))?;
         }
         write!(f, ":END:")
     }
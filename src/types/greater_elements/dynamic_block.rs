@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A dynamic block.
 ///
@@ -26,9 +27,10 @@ use super::*;
 ///
 /// `CONTENTS` is auto-generated and will not be parsed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DynamicBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<ElementSet>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>,
     /// The name of the function that can update this block.
     pub name: String,
     /// The parameters to pass to the function updating this block.
@@ -37,7 +39,18 @@ pub struct DynamicBlock {
     ///
     /// If the function needs the current content of the block add a parameter `:content`.
     pub parameters: String, // TODO maybe parse this as a list
-                            // hiddenp: bool
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+    // hiddenp: bool
+}
+
+impl DynamicBlock {
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
 }
 
 impl Parent<Vec<ElementSet>> for DynamicBlock {
@@ -45,3 +58,17 @@ impl Parent<Vec<ElementSet>> for DynamicBlock {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for DynamicBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        write!(f, "#+{} {}", keyword("BEGIN:"), self.name)?;
+        if !self.parameters.is_empty() {
+            write!(f, " {}", self.parameters)?;
+        }
+        writeln!(f)?;
+        write_block_elements(f, self.content.value())?;
+        write!(f, "#+{}", keyword("END:"))
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A footnote definition.
 ///
@@ -19,15 +20,41 @@ use super::*;
 /// It ends at the next footnote definition, headline, with two consecutive empty lines or the
 /// end of the buffer.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FootnoteDefinition {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<ElementSet>>, // TODO
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>, // TODO
+    /// The label without the `fn:` prefix.
     pub label: String,
+    pub(crate) post_blank: Option<usize>,
     // pre_blank: u32 // TODO (maybe) blank lines after `[LABEL]`
 }
 
+impl FootnoteDefinition {
+    pub fn new(label: String, content: Spanned<Vec<ElementSet>>) -> Self {
+        FootnoteDefinition {
+            affiliated_keywords: None,
+            content,
+            label,
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
+}
+
 impl Parent<Vec<ElementSet>> for FootnoteDefinition {
     fn content(&self) -> Option<&Spanned<Vec<ElementSet>>> {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for FootnoteDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "[fn:{}] ", self.label)?;
+        write_elements(f, self.content.value())
+    }
+}
@@ -1,4 +1,9 @@
 use super::*;
+use crate::logbook::LOGBOOK;
+use crate::parsing::{parse_objects, TodoSequence};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
 
 /// A headline.
 ///
@@ -40,6 +45,7 @@ use super::*;
 /// arbitraty amount of whitespace (except newlines) between `TITLE` and `TAGS`. Tags are
 /// usually right aligned at a specified column by the editor.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Headline {
     pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     pub level: u32,
@@ -50,7 +56,13 @@ pub struct Headline {
     pub planning: Option<elements::Planning>,
     pub property_drawer: Option<PropertyDrawer>,
     pub(crate) content: Option<Spanned<Vec<HeadlineContentSet>>>,
-    // quotedp ?
+    /// Whether the section of the headline is quoted text.
+    ///
+    /// Only org versions before 8.3 have quoted headlines (title starting with `QUOTE`). This is
+    /// only set when parsing with [`Compat::quote_headlines`].
+    ///
+    /// [`Compat::quote_headlines`]: `crate::parsing::Compat::quote_headlines`
+    pub quoted: bool,
     // hiddenp: bool,
     // pre_blank: u32 // TODO (maybe) blank lines before the content starts
 }
@@ -78,26 +90,864 @@ impl Headline {
         self.tags.contains(&"ARCHIVE".to_string())
     }
 
+    /// Returns `true` if the headline or one of its ancestors is commented.
+    ///
+    /// `document` has to contain this headline. Otherwise only the headline itself is checked.
+    pub fn is_effectively_commented(&self, document: &document::Document) -> bool {
+        self.is_commented()
+            || dependencies::ancestors(document, self).any(Headline::is_commented)
+    }
+
+    /// Returns `true` if the headline or one of its ancestors is archived.
+    ///
+    /// `document` has to contain this headline. Otherwise only the headline itself is checked.
+    pub fn is_effectively_archived(&self, document: &document::Document) -> bool {
+        self.is_archived()
+            || dependencies::ancestors(document, self).any(Headline::is_archived)
+    }
+
+    /// Returns the value of a property in the property drawer of this headline.
+    ///
+    /// See [`PropertyDrawer::get`].
+    pub fn property(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.property_drawer
+            .as_ref()
+            .and_then(|drawer| drawer.get(name))
+    }
+
+    /// Sets a property of this headline. The property drawer is created if necessary.
+    pub fn set_property(&mut self, name: &str, value: impl Into<String>) {
+        self.property_drawer
+            .get_or_insert_with(|| PropertyDrawer::new(Spanned::new(Vec::new())))
+            .set(name, value);
+    }
+
+    /// Removes a property of this headline and returns its value. The property drawer is
+    /// removed if it becomes empty.
+    pub fn remove_property(&mut self, name: &str) -> Option<String> {
+        let drawer = self.property_drawer.as_mut()?;
+        let value = drawer.remove(name);
+        if drawer.is_empty() {
+            self.property_drawer = None;
+        }
+        value
+    }
+
+    /// Returns the value of a property of this headline or inherited from its ancestors and the
+    /// document (like `org-entry-get` with inheritance).
+    ///
+    /// The nearest headline with the property wins. Otherwise the value comes from the
+    /// `#+PROPERTY: NAME VALUE` keywords and the property drawer in the preface. `NAME+`
+    /// properties append to the inherited value.
+    ///
+    /// `document` has to contain this headline. Otherwise no headline properties are inherited.
+    pub fn property_with_inheritance(
+        &self,
+        document: &document::Document,
+        name: &str,
+    ) -> Option<String> {
+        let mut value = document.property(name);
+        let drawers = dependencies::ancestors(document, self)
+            .chain(std::iter::once(self))
+            .flat_map(|headline| headline.property_drawer.as_ref());
+        for drawer in drawers {
+            value = drawer
+                .get_with_inherited(name, value.map(Cow::Owned))
+                .map(Cow::into_owned);
+        }
+        value
+    }
+
+    /// Returns the tags of this headline including the tags inherited from its ancestors and the
+    /// `#+FILETAGS:` of the document (outermost first, without duplicates).
+    ///
+    /// `document` has to contain this headline. Otherwise only the file tags are inherited.
+    pub fn tags_with_inheritance(&self, document: &document::Document) -> Vec<String> {
+        let mut tags = document.file_tags();
+        let own = dependencies::ancestors(document, self)
+            .chain(std::iter::once(self))
+            .flat_map(|headline| &headline.tags);
+        for tag in own {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        tags
+    }
+
+    /// Returns `true` if the headline can't be marked as done because it depends on tasks that
+    /// are not done yet (see [`dependencies`] for the rules).
+    ///
+    /// `document` has to contain this headline. Otherwise only the children are checked.
+    ///
+    /// [`dependencies`]: `crate::types::dependencies`
+    pub fn is_blocked(&self, document: &document::Document) -> bool {
+        dependencies::is_blocked(document, self)
+    }
+
+    /// Returns the headlines which get a new todo keyword (from the `TRIGGER` property) when
+    /// this headline is marked as done.
+    pub fn triggered<'a>(&self, document: &'a document::Document) -> Vec<(&'a Headline, String)> {
+        dependencies::triggered(document, self)
+    }
+
+    /// Returns the headline line (without the stars) with the parts selected by `format`.
+    ///
+    /// With [`TitleFormat::default`] this is the title without statistics cookies like the
+    /// `ITEM` column of the column view.
+    pub fn formatted_title(&self, format: &TitleFormat) -> String {
+        let mut parts = Vec::new();
+        if let (true, Some(todo_keyword)) = (format.todo_keyword, &self.todo_keyword) {
+            parts.push(todo_keyword.to_string());
+        }
+        if let (true, Some(priority)) = (format.priority, self.priority) {
+            parts.push(format!("[#{}]", priority));
+        }
+        if let Some(title) = &self.title {
+            let title = if format.statistics_cookies {
+                title.to_string()
+            } else {
+                let mut result = String::new();
+                for object in title.iter() {
+                    match object {
+                        StandardSetNoLineBreak::StatisticsCookie(_) => {
+                            result.truncate(result.trim_end().len())
+                        }
+                        StandardSetNoLineBreak::RawString(s) => {
+                            push_without_statistics_cookies(&mut result, s)
+                        }
+                        object => result.push_str(&object.to_string()),
+                    }
+                }
+                result.trim().to_string()
+            };
+            if !title.is_empty() {
+                parts.push(title);
+            }
+        }
+        if format.tags && !self.tags.is_empty() {
+            parts.push(format!(":{}:", self.tags.iter().format(":")));
+        }
+        parts.join(" ")
+    }
+
+    /// Changes the level of this headline. The levels of all nested headlines are changed by
+    /// the same amount.
+    pub fn set_level(&mut self, level: u32) {
+        let offset = i64::from(level) - i64::from(self.level);
+        self.shift_level(offset);
+    }
+
+    fn shift_level(&mut self, offset: i64) {
+        self.level = (i64::from(self.level) + offset).max(1) as u32;
+        for child in self.children_mut() {
+            child.shift_level(offset);
+        }
+    }
+
+    /// Returns the last headline in document order (the last descendant of this headline or
+    /// this headline itself).
+    pub(crate) fn last_descendant_mut(&mut self) -> &mut Headline {
+        let is_leaf = !matches!(
+            self.content.as_ref().and_then(|content| content.value().last()),
+            Some(HeadlineContentSet::Headline(_))
+        );
+        if is_leaf {
+            return self;
+        }
+        match self
+            .content
+            .as_mut()
+            .and_then(|content| content.get_mut_value().last_mut())
+        {
+            Some(HeadlineContentSet::Headline(headline)) => headline.last_descendant_mut(),
+            _ => unreachable!("the last content is a headline"),
+        }
+    }
+
+    /// Returns the headlines directly nested in this headline.
+    pub fn children(&self) -> impl Iterator<Item = &Headline> {
+        self.content
+            .iter()
+            .flat_map(Spanned::value)
+            .filter_map(|content| match content {
+                HeadlineContentSet::Headline(child) => Some(&**child),
+                HeadlineContentSet::Section(_) => None,
+            })
+    }
+
+    /// Returns the headlines directly nested in this headline (see [`Headline::children`]).
+    pub fn children_mut(&mut self) -> impl Iterator<Item = &mut Headline> {
+        self.content
+            .iter_mut()
+            .flat_map(Spanned::get_mut_value)
+            .filter_map(|content| match content {
+                HeadlineContentSet::Headline(child) => Some(&mut **child),
+                HeadlineContentSet::Section(_) => None,
+            })
+    }
+
     pub fn push_content(&mut self, content: impl IntoIterator<Item=HeadlineContentSet>) {
         self.content
             .get_or_insert_with(|| Spanned::new(Vec::new()))
             .get_mut_value()
             .extend(content);
     }
+
+    /// Returns the `LOGBOOK` drawer in the section of this headline (containing the notes about
+    /// state changes and clock lines).
+    pub fn logbook(&self) -> Option<&Drawer> {
+        let section = match self.content.as_ref()?.value().first()? {
+            HeadlineContentSet::Section(section) => section,
+            HeadlineContentSet::Headline(_) => return None,
+        };
+        section.content.value().iter().find_map(|element| match element {
+            ElementSet::Drawer(drawer) if drawer.name.eq_ignore_ascii_case(LOGBOOK) => Some(&**drawer),
+            _ => None,
+        })
+    }
+
+    /// Returns the section of this headline. An empty section is created if necessary.
+    pub fn section_mut(&mut self) -> &mut Section {
+        let content = self
+            .content
+            .get_or_insert_with(|| Spanned::new(Vec::new()))
+            .get_mut_value();
+        match content.first() {
+            Some(HeadlineContentSet::Section(_)) => {}
+            _ => content.insert(
+                0,
+                HeadlineContentSet::Section(Section::new(Spanned::new(Vec::new()))),
+            ),
+        }
+        match &mut content[0] {
+            HeadlineContentSet::Section(section) => section,
+            HeadlineContentSet::Headline(_) => unreachable!("the section was inserted above"),
+        }
+    }
+
+    /// Splits the section of this headline before the element at `offset` (like inserting a
+    /// headline in the middle of the section in an outliner).
+    ///
+    /// The elements from `offset` on and all child headlines are moved into a new headline with
+    /// the same level, which has to be inserted as the next sibling. A single line paragraph at
+    /// the start of the moved elements becomes the title of the new headline. The todo keyword,
+    /// priority, tags, planning and property drawer stay with this headline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is greater than the number of elements in the section.
+    pub fn split_at(&mut self, offset: usize) -> Headline {
+        let mut content = self
+            .content
+            .take()
+            .map(Spanned::to_value)
+            .unwrap_or_default();
+        let mut elements: Vec<_> = match content.first() {
+            Some(HeadlineContentSet::Section(_)) => match content.remove(0) {
+                HeadlineContentSet::Section(mut section) => {
+                    section.content_mut().drain(..).collect()
+                }
+                HeadlineContentSet::Headline(_) => unreachable!("the first content is a section"),
+            },
+            _ => Vec::new(),
+        };
+        let mut rest = elements.split_off(offset);
+        if !elements.is_empty() {
+            self.push_content(Some(HeadlineContentSet::Section(Section::new(
+                Spanned::new(elements),
+            ))));
+        }
+
+        let is_title = match rest.first() {
+            Some(ElementSet::Paragraph(paragraph)) => !paragraph.to_string().contains('\n'),
+            _ => false,
+        };
+        let title = if is_title {
+            match rest.remove(0) {
+                ElementSet::Paragraph(paragraph) => {
+                    Some(paragraph.content.into_iter().map(Into::into).collect())
+                }
+                _ => unreachable!("the first element is a paragraph"),
+            }
+        } else {
+            None
+        };
+        let mut headline = Headline {
+            level: self.level,
+            title,
+            ..Headline::default()
+        };
+        if !rest.is_empty() {
+            headline.push_content(Some(HeadlineContentSet::Section(Section::new(
+                Spanned::new(rest),
+            ))));
+        }
+        headline.push_content(content);
+        headline
+    }
+
+    /// Joins the next sibling headline into this headline (the reverse of
+    /// [`Headline::split_at`]).
+    ///
+    /// The title of `next` becomes a paragraph at the end of the section of this headline,
+    /// followed by the section of `next`. The children of `next` are appended to the children of
+    /// this headline. The planning timestamps and properties of `next` are only kept if this
+    /// headline doesn't have a value for them. Tags are merged. The todo keyword and priority of
+    /// `next` are dropped.
+    pub fn join_with_next(&mut self, mut next: Headline) {
+        next.set_level(self.level);
+        let mut elements = Vec::new();
+        if let Some(title) = next.title.take() {
+            let title = title.into_iter().map(Into::into).collect();
+            elements.push(elements::Paragraph::new(title).into());
+        }
+        let mut children = Vec::new();
+        for content in next
+            .content
+            .take()
+            .map(Spanned::to_value)
+            .unwrap_or_default()
+        {
+            match content {
+                HeadlineContentSet::Section(mut section) => elements.append(section.content_mut()),
+                child => children.push(child),
+            }
+        }
+        if !elements.is_empty() {
+            self.section_mut().content_mut().append(&mut elements);
+        }
+        self.push_content(children);
+
+        if let Some(planning) = next.planning {
+            match &mut self.planning {
+                Some(existing) => {
+                    existing.closed = existing.closed.take().or(planning.closed);
+                    existing.deadline = existing.deadline.take().or(planning.deadline);
+                    existing.scheduled = existing.scheduled.take().or(planning.scheduled);
+                }
+                None => self.planning = Some(planning),
+            }
+        }
+        if let Some(drawer) = next.property_drawer {
+            for (name, value) in drawer.iter() {
+                if self.property(name).is_none() {
+                    self.set_property(name, value);
+                }
+            }
+        }
+        for tag in next.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    /// Sorts the child headlines of this headline (like `org-sort-entries`).
+    ///
+    /// The sort is stable so children that are equal according to the key keep their order.
+    /// The section stays before the children. Spans are not updated.
+    pub fn sort_children(&mut self, key: SortKey) {
+        let content = match &mut self.content {
+            Some(content) => content.get_mut_value(),
+            None => return,
+        };
+        let start = match content.first() {
+            Some(HeadlineContentSet::Section(_)) => 1,
+            _ => 0,
+        };
+        content[start..].sort_by(|a, b| match (a, b) {
+            (HeadlineContentSet::Headline(a), HeadlineContentSet::Headline(b)) => key.compare(a, b),
+            _ => unreachable!("only the first content can be a section"),
+        });
+    }
+
+    /// Computes a structural hash of the headline and all of its content (including nested
+    /// headlines).
+    ///
+    /// Spans, whitespace and the level of this headline are ignored (nested headlines are hashed
+    /// with their level relative to this headline). So a subtree that was moved, promoted or
+    /// demoted without otherwise changing it keeps its fingerprint.
+    ///
+    /// The hash is a 64 bit FNV-1a hash of the org text of the parts of the headline. It doesn't
+    /// depend on the platform or the version of the standard library but it changes when this
+    /// crate writes org text differently. So fingerprints are only stable within one version of
+    /// this crate and should not be stored across upgrades.
+    pub fn fingerprint(&self) -> u64 {
+        let mut fingerprint = Fingerprint::new();
+        self.write_fingerprint(self.level, &mut fingerprint);
+        fingerprint.finish()
+    }
+
+    fn write_fingerprint(&self, root_level: u32, fingerprint: &mut Fingerprint) {
+        fingerprint.field(&self.level.saturating_sub(root_level).to_string());
+        fingerprint.field(&match &self.todo_keyword {
+            Some(TodoKeyword::Todo(keyword)) => format!("todo {}", keyword),
+            Some(TodoKeyword::Done(keyword)) => format!("done {}", keyword),
+            None => String::new(),
+        });
+        fingerprint.field(&self.priority.map(String::from).unwrap_or_default());
+        fingerprint.field(&self.title.as_ref().map(ToString::to_string).unwrap_or_default());
+        fingerprint.field(&self.tags.join(":"));
+        fingerprint.field(&self.planning.as_ref().map(ToString::to_string).unwrap_or_default());
+        fingerprint.field(
+            &self
+                .property_drawer
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+        for content in self.content.iter().flat_map(Spanned::value) {
+            match content {
+                HeadlineContentSet::Section(section) => {
+                    fingerprint.field("section");
+                    fingerprint.field(&section.to_string());
+                }
+                HeadlineContentSet::Headline(headline) => {
+                    fingerprint.field("headline");
+                    headline.write_fingerprint(root_level, fingerprint);
+                }
+            }
+        }
+    }
+}
+
+impl Headline {
+    /// Returns a builder for a headline (with level 1 unless set).
+    ///
+    /// ```
+    /// use rust_orgmode::types::greater_elements::Headline;
+    ///
+    /// let headline = Headline::builder()
+    ///     .level(2)
+    ///     .todo("TODO")
+    ///     .priority('A')
+    ///     .title("Task")
+    ///     .tag("work")
+    ///     .build();
+    /// assert_eq!(headline.to_string(), "** TODO [#A] Task :work:");
+    /// ```
+    pub fn builder() -> HeadlineBuilder {
+        HeadlineBuilder {
+            headline: Headline {
+                level: 1,
+                ..Headline::default()
+            },
+        }
+    }
+}
+
+/// Builds a [`Headline`] without parsing org text (see [`Headline::builder`]).
+///
+/// The values of the headline have no spans.
+#[derive(Debug, Clone)]
+pub struct HeadlineBuilder {
+    headline: Headline,
+}
+
+impl HeadlineBuilder {
+    /// Sets the level. Levels below 1 are set to 1.
+    pub fn level(mut self, level: u32) -> Self {
+        self.headline.level = level.max(1);
+        self
+    }
+
+    /// Sets a todo keyword that is not done (e.g. `TODO`).
+    pub fn todo(mut self, keyword: impl Into<String>) -> Self {
+        self.headline.todo_keyword = Some(TodoKeyword::Todo(keyword.into()));
+        self
+    }
+
+    /// Sets a done keyword (e.g. `DONE`).
+    pub fn done(mut self, keyword: impl Into<String>) -> Self {
+        self.headline.todo_keyword = Some(TodoKeyword::Done(keyword.into()));
+        self
+    }
+
+    pub fn priority(mut self, priority: char) -> Self {
+        self.headline.priority = Some(priority);
+        self
+    }
+
+    /// Sets the title. The markup in the title (e.g. `*bold*` or links) is parsed.
+    pub fn title(mut self, title: &str) -> Self {
+        let title = title.lines().next().unwrap_or("");
+        self.headline.title = Some(
+            parse_objects(title)
+                .into_iter()
+                .map(StandardSetNoLineBreak::from)
+                .collect(),
+        );
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.headline.tags.push(tag.into());
+        self
+    }
+
+    pub fn planning(mut self, planning: elements::Planning) -> Self {
+        self.headline.planning = Some(planning);
+        self
+    }
+
+    /// Sets a property (see [`Headline::set_property`]).
+    pub fn property(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headline.set_property(name, value);
+        self
+    }
+
+    /// Adds an element to the section.
+    pub fn element(mut self, element: impl Into<ElementSet>) -> Self {
+        self.headline
+            .section_mut()
+            .content_mut()
+            .push(element.into());
+        self
+    }
+
+    /// Adds a child headline. Its level is set to the level below this headline when building
+    /// unless it is already lower.
+    pub fn child(mut self, child: Headline) -> Self {
+        self.headline
+            .push_content(Some(HeadlineContentSet::Headline(Box::new(child))));
+        self
+    }
+
+    pub fn build(mut self) -> Headline {
+        let level = self.headline.level;
+        for child in self.headline.children_mut() {
+            if child.level <= level {
+                child.set_level(level + 1);
+            }
+        }
+        self.headline
+    }
+}
+
+/// The parts of a headline included in [`Headline::formatted_title`].
+///
+/// The default only includes the title without statistics cookies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TitleFormat {
+    pub todo_keyword: bool,
+    pub priority: bool,
+    pub statistics_cookies: bool,
+    pub tags: bool,
+}
+
+impl TitleFormat {
+    /// Includes all parts of the headline line.
+    pub fn full() -> Self {
+        TitleFormat {
+            todo_keyword: true,
+            priority: true,
+            statistics_cookies: true,
+            tags: true,
+        }
+    }
+}
+
+/// A comparator for [`SortKey::Custom`].
+pub type HeadlineComparator = dyn Fn(&Headline, &Headline) -> Ordering;
+
+/// How [`Headline::sort_children`] orders the children.
+pub enum SortKey {
+    /// Alphabetically by the title without todo keyword, priority, statistics cookies and tags
+    /// (ignoring case).
+    Alphabetical,
+    /// By the position of the todo keyword in the sequences (todo keywords of a sequence before
+    /// its done keywords). Headlines without (or with an unknown) todo keyword are last.
+    Todo(Vec<TodoSequence>),
+    /// Highest priority first. Headlines without priority have the default priority `B` (like
+    /// `org-priority-default`).
+    Priority,
+    /// Earliest scheduled date first. Headlines without scheduled date are last.
+    Scheduled,
+    /// Earliest deadline first. Headlines without deadline are last.
+    Deadline,
+    /// Ordered by a comparator.
+    Custom(Box<HeadlineComparator>),
+}
+
+impl SortKey {
+    /// Compares two headlines by this key.
+    pub fn compare(&self, a: &Headline, b: &Headline) -> Ordering {
+        match self {
+            SortKey::Alphabetical => {
+                let title = |headline: &Headline| {
+                    headline.formatted_title(&TitleFormat::default()).to_lowercase()
+                };
+                title(a).cmp(&title(b))
+            }
+            SortKey::Todo(sequences) => {
+                let position = |headline: &Headline| {
+                    let keyword = match headline.todo_keyword.as_ref()? {
+                        TodoKeyword::Todo(keyword) | TodoKeyword::Done(keyword) => keyword,
+                    };
+                    sequences
+                        .iter()
+                        .flat_map(|sequence| sequence.todo.iter().chain(&sequence.done))
+                        .position(|known| known == keyword)
+                };
+                compare_missing_last(position(a), position(b))
+            }
+            SortKey::Priority => a.priority.unwrap_or('B').cmp(&b.priority.unwrap_or('B')),
+            SortKey::Scheduled => {
+                let date = |headline: &Headline| {
+                    headline.planning.as_ref()?.scheduled.as_ref()?.start_date_time()
+                };
+                compare_missing_last(date(a), date(b))
+            }
+            SortKey::Deadline => {
+                let date = |headline: &Headline| {
+                    headline.planning.as_ref()?.deadline.as_ref()?.start_date_time()
+                };
+                compare_missing_last(date(a), date(b))
+            }
+            SortKey::Custom(compare) => compare(a, b),
+        }
+    }
+}
+
+impl fmt::Debug for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortKey::Alphabetical => write!(f, "Alphabetical"),
+            SortKey::Todo(sequences) => f.debug_tuple("Todo").field(sequences).finish(),
+            SortKey::Priority => write!(f, "Priority"),
+            SortKey::Scheduled => write!(f, "Scheduled"),
+            SortKey::Deadline => write!(f, "Deadline"),
+            SortKey::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+fn compare_missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Which subtrees are searched by queries like the [agenda](`crate::agenda`) and
+/// [`TagMatch::search`](`crate::tag_match::TagMatch::search`).
+///
+/// The default skips commented and archived subtrees like org mode does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SubtreeFilter {
+    /// Also search subtrees whose root is commented.
+    pub include_commented: bool,
+    /// Also search subtrees whose root is archived.
+    pub include_archived: bool,
+}
+
+impl SubtreeFilter {
+    /// Searches all subtrees.
+    pub fn all() -> Self {
+        SubtreeFilter {
+            include_commented: true,
+            include_archived: true,
+        }
+    }
+
+    /// Returns `true` if the headline and its children are skipped.
+    pub fn skips(&self, headline: &Headline) -> bool {
+        (!self.include_commented && headline.is_commented())
+            || (!self.include_archived && headline.is_archived())
+    }
+}
+
+/// Appends `s` to `result` without statistics cookies (`[1/3]` or `[33%]`) and the whitespace
+/// before them.
+fn push_without_statistics_cookies(result: &mut String, s: &str) {
+    let mut rest = s;
+    while let Some(start) = rest.find('[') {
+        result.push_str(&rest[..start]);
+        let cookie = &rest[start + 1..];
+        let end = cookie.find(']').filter(|&end| {
+            let inner = &cookie[..end];
+            let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+            match inner.find('/') {
+                Some(slash) => digits(&inner[..slash]) && digits(&inner[slash + 1..]),
+                None => inner.ends_with('%') && digits(&inner[..inner.len() - 1]),
+            }
+        });
+        match end {
+            Some(end) => {
+                result.truncate(result.trim_end().len());
+                rest = &cookie[end + 1..];
+            }
+            None => {
+                result.push('[');
+                rest = cookie;
+            }
+        }
+    }
+    result.push_str(rest);
+}
+
+/// 64 bit FNV-1a hash over whitespace normalized fields.
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Fingerprint(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Hashes the words of the value separated by single spaces followed by a separator that
+    /// can't be part of a string (`0xff` is not valid UTF-8).
+    fn field(&mut self, value: &str) {
+        for word in value.split_whitespace() {
+            self.write(word.as_bytes());
+            self.write(b" ");
+        }
+        self.write(&[0xff]);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Headline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_headline_line(
+            f,
+            &"*".repeat(self.level as usize),
+            &self.todo_keyword,
+            self.priority,
+            &self.title,
+            &self.tags,
+        )?;
+        if let Some(planning) = &self.planning {
+            write!(f, "\n{}", planning)?;
+        }
+        if let Some(property_drawer) = &self.property_drawer {
+            write!(f, "\n{}", property_drawer)?;
+        }
+        if let Some(content) = &self.content {
+            if !content.value().is_empty() {
+                let blank_lines =
+                    serialize::with_config(|config| config.blank_lines_after_headline);
+                write!(f, "{}", "\n".repeat(blank_lines))?;
+            }
+            for content in content.value() {
+                write!(f, "\n{}", content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the first line of a [`Headline`] or [`Inlinetask`].
+///
+/// The tags are aligned as configured in [`SerializeConfig::tags_column`].
+///
+/// [`SerializeConfig::tags_column`]: `serialize::SerializeConfig::tags_column`
+fn write_headline_line(
+    f: &mut fmt::Formatter<'_>,
+    stars: &str,
+    todo_keyword: &Option<TodoKeyword>,
+    priority: Option<char>,
+    title: &Option<SecondaryString<StandardSetNoLineBreak>>,
+    tags: &[String],
+) -> fmt::Result {
+    let mut line = stars.to_string();
+    if let Some(todo_keyword) = todo_keyword {
+        line.push_str(&format!(" {}", todo_keyword));
+    }
+    if let Some(priority) = priority {
+        line.push_str(&format!(" [#{}]", priority));
+    }
+    if let Some(title) = title {
+        let title = title.to_string();
+        line.push(' ');
+        line.push_str(&title);
+        // a title ending like tags would be parsed as tags
+        if tags.is_empty() && ends_with_tags(&title) {
+            line.push(ZERO_WIDTH_SPACE);
+        }
+    }
+    if !tags.is_empty() {
+        let tags = format!(":{}:", tags.iter().format(":"));
+        let width = line.chars().count();
+        let column = serialize::with_config(|config| config.tags_column);
+        let start = if column < 0 {
+            (-column as usize).saturating_sub(tags.chars().count())
+        } else {
+            column as usize
+        };
+        line.push_str(&" ".repeat(start.saturating_sub(width).max(1)));
+        line.push_str(&tags);
+    }
+    if line == stars {
+        // the stars of a headline have to be followed by a space
+        line.push(' ');
+    }
+    write!(f, "{}", line)
+}
+
+/// Returns `true` if the last word of `title` looks like tags (e.g. `:a:b:`).
+fn ends_with_tags(title: &str) -> bool {
+    let last = match title.rsplit(char::is_whitespace).next() {
+        Some(last) if last.len() > 2 && last != title => last,
+        _ => return false,
+    };
+    last.starts_with(':')
+        && last.ends_with(':')
+        && last[1..last.len() - 1].split(':').all(|tag| {
+            !tag.is_empty()
+                && tag
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "_@#%".contains(c))
+        })
 }
 
 /// List of elements that are content of a [`Headline`] or [`Inlinetask`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HeadlineContentSet {
     Section(greater_elements::Section),
     Headline(Box<greater_elements::Headline>),
 }
 
+impl Element for HeadlineContentSet {
+    fn kind(&self) -> ElementKind {
+        match self {
+            HeadlineContentSet::Section(_) => ElementKind::Section,
+            HeadlineContentSet::Headline(_) => ElementKind::Headline,
+        }
+    }
+}
+
+impl fmt::Display for HeadlineContentSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlineContentSet::Section(section) => write!(f, "{}", section),
+            HeadlineContentSet::Headline(headline) => write!(f, "{}", headline),
+        }
+    }
+}
+
 /// A todo keyword of a [`Headline`] or [`Inlinetask`].
 ///
 /// Todo keywords can be configured before parsing. The default is to parse `TODO` and `NEXT` as
 /// **`Todo`** and `DONE` as **`Done`**. The actual keyword used is the string in the variant.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TodoKeyword {
     /// Usually parsed from `TODO` and `NEXT`.
     Todo(String),
@@ -105,6 +955,14 @@ pub enum TodoKeyword {
     Done(String),
 }
 
+impl fmt::Display for TodoKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoKeyword::Todo(keyword) | TodoKeyword::Done(keyword) => write!(f, "{}", keyword),
+        }
+    }
+}
+
 /// An inline task.
 ///
 /// # Semantics
@@ -114,26 +972,337 @@ pub enum TodoKeyword {
 ///
 /// # Syntax
 ///
-/// Same syntax as [`Headline`] but starts with at least *org-inlinetask-min-level* astersisks.
-/// This variable is currently not implemented. Inline tasks can also not be commented or
+/// Same syntax as [`Headline`] but starts with at least *org-inlinetask-min-level* astersisks
+/// (see [`ParseConfig::inlinetask_min_level`]). Inline tasks can also not be commented or
 /// archived.
 ///
-/// Inline tasks can be ended with a line of *org-inlinetask-min-level* asterisks followed by a
-/// space and the string `END`. This should start at the beginning of a line but that is not
-/// required.
+/// Inline tasks can be ended with a line of at least *org-inlinetask-min-level* asterisks
+/// followed by a space and the string `END`. Only inline tasks with such a line have content.
+/// Without it the following lines belong to the section the inline task is in.
+///
+/// [`ParseConfig::inlinetask_min_level`]: `crate::parsing::ParseConfig::inlinetask_min_level`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inlinetask {
-    content: Spanned<Vec<HeadlineContentSet>>,
+    pub(crate) content: Spanned<Vec<HeadlineContentSet>>,
+    /// The number of stars.
+    pub level: u32,
     pub todo_keyword: Option<TodoKeyword>,
     pub priority: Option<char>, // TODO maybe make separate struct (maybe use old enum)
     pub title: Option<SecondaryString<StandardSetNoLineBreak>>,
     pub tags: Vec<String>,
+    /// Whether the inline task is ended by an `END` line.
+    pub(crate) has_end: bool,
+    pub(crate) post_blank: Option<usize>,
     // hiddenp: bool,
     // pre_blank: u32 // blank lines before the content starts
 }
 
+impl Inlinetask {
+    /// Returns `true` if the inline task is ended by an `END` line.
+    pub fn has_end(&self) -> bool {
+        self.has_end
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<HeadlineContentSet> {
+        self.content.get_mut_value()
+    }
+}
+
 impl Parent<Vec<HeadlineContentSet>> for Inlinetask {
     fn content(&self) -> Option<&Spanned<Vec<HeadlineContentSet>>> {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for Inlinetask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stars = "*".repeat(self.level as usize);
+        write_headline_line(
+            f,
+            &stars,
+            &self.todo_keyword,
+            self.priority,
+            &self.title,
+            &self.tags,
+        )?;
+        if self.has_end {
+            for content in self.content.value() {
+                write!(f, "\n{}", content)?;
+            }
+            write!(f, "\n{} END", stars)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headline(text: &str) -> Headline {
+        document::Document::parse(text).unwrap().headlines.remove(0)
+    }
+
+    #[test]
+    fn builder() {
+        let headline = Headline::builder()
+            .done("DONE")
+            .title("Read *the* docs")
+            .property("EFFORT", "1:00")
+            .element(elements::Paragraph::new(SecondaryString::with_one(
+                StandardSet::RawString("Notes.".to_string()),
+            )))
+            .child(Headline::builder().title("Child").build())
+            .level(2)
+            .build();
+        assert_eq!(
+            headline.to_string(),
+            "** DONE Read *the* docs\n:PROPERTIES:\n:EFFORT: 1:00\n:END:\nNotes.\n*** Child"
+        );
+        assert_eq!(headline.title.unwrap().len(), 3);
+    }
+
+    /// Returns whether the headlines of the two org texts have the same fingerprint.
+    fn same_fingerprint(a: &str, b: &str) -> bool {
+        headline(a).fingerprint() == headline(b).fingerprint()
+    }
+
+    #[test]
+    fn fingerprint_ignores_position_and_whitespace() {
+        assert!(same_fingerprint(
+            "* title\nsome text\n** child\nmore\n",
+            "*** title\nsome\n  text \n\n**** child\nmore\n",
+        ));
+    }
+
+    #[test]
+    fn fingerprint_of_title() {
+        assert!(!same_fingerprint("* title\n", "* other\n"));
+    }
+
+    #[test]
+    fn fingerprint_of_todo_keyword() {
+        assert!(!same_fingerprint("* TODO title\n", "* DONE title\n"));
+        assert!(!same_fingerprint("* TODO title\n", "* title\n"));
+    }
+
+    #[test]
+    fn fingerprint_of_priority() {
+        assert!(!same_fingerprint("* [#A] title\n", "* [#B] title\n"));
+    }
+
+    #[test]
+    fn fingerprint_of_tags() {
+        assert!(!same_fingerprint("* title :a:\n", "* title :a:b:\n"));
+    }
+
+    #[test]
+    fn fingerprint_of_planning() {
+        assert!(!same_fingerprint(
+            "* title\nDEADLINE: <2018-10-12 Fri>\n",
+            "* title\nDEADLINE: <2018-10-13 Sat>\n",
+        ));
+    }
+
+    #[test]
+    fn fingerprint_of_properties() {
+        assert!(!same_fingerprint(
+            "* title\n:PROPERTIES:\n:ID: 1\n:END:\n",
+            "* title\n:PROPERTIES:\n:ID: 2\n:END:\n",
+        ));
+    }
+
+    #[test]
+    fn fingerprint_of_section() {
+        assert!(!same_fingerprint("* title\nsome text\n", "* title\nother text\n"));
+    }
+
+    #[test]
+    fn fingerprint_of_children() {
+        assert!(!same_fingerprint("* title\n** child\n", "* title\n** other\n"));
+        assert!(!same_fingerprint("* title\n** child\n", "* title\n*** child\n"));
+    }
+
+    /// Returns a headline with the children `b`, `A`, `c` and `d` where `A` and `d` are
+    /// scheduled.
+    fn tasks() -> Headline {
+        let text = "* Tasks\n\
+                    Notes\n\
+                    ** DONE b task\n\
+                    ** TODO [#C] A task\n\
+                    SCHEDULED: <2020-03-02 Mon>\n\
+                    *** Child\n\
+                    ** [#A] c task\n\
+                    ** NEXT d task\n\
+                    SCHEDULED: <2020-03-01 Sun>\n";
+        document::Document::parse(text).unwrap().headlines.remove(0)
+    }
+
+    fn sorted_titles(key: SortKey) -> Vec<String> {
+        let mut tasks = tasks();
+        tasks.sort_children(key);
+        tasks
+            .children()
+            .map(|child| child.formatted_title(&TitleFormat::default()))
+            .collect()
+    }
+
+    #[test]
+    fn children() {
+        let mut tasks = tasks();
+        let levels: Vec<_> = tasks.children().map(|child| child.level).collect();
+        assert_eq!(levels, [2, 2, 2, 2]);
+        tasks.children_mut().for_each(|child| child.shift_level(1));
+        assert!(tasks.children().all(|child| child.level == 3));
+    }
+
+    #[test]
+    fn sort_alphabetically() {
+        assert_eq!(
+            sorted_titles(SortKey::Alphabetical),
+            ["A task", "b task", "c task", "d task"]
+        );
+    }
+
+    #[test]
+    fn sort_by_todo_keyword() {
+        let todo = SortKey::Todo(crate::parsing::ParseConfig::default().todo_keywords);
+        assert_eq!(sorted_titles(todo), ["A task", "d task", "b task", "c task"]);
+    }
+
+    #[test]
+    fn sort_by_priority() {
+        // headlines without a priority have the default priority `B`
+        assert_eq!(
+            sorted_titles(SortKey::Priority),
+            ["c task", "b task", "d task", "A task"]
+        );
+    }
+
+    #[test]
+    fn sort_by_planning() {
+        assert_eq!(
+            sorted_titles(SortKey::Scheduled),
+            ["d task", "A task", "b task", "c task"]
+        );
+        // the order of headlines without a deadline is kept
+        assert_eq!(
+            sorted_titles(SortKey::Deadline),
+            ["b task", "A task", "c task", "d task"]
+        );
+    }
+
+    #[test]
+    fn sort_with_custom_order() {
+        let reverse = SortKey::Custom(Box::new(|a, b| SortKey::Alphabetical.compare(b, a)));
+        assert_eq!(sorted_titles(reverse), ["d task", "c task", "b task", "A task"]);
+    }
+
+    #[test]
+    fn write_sorted_children() {
+        let mut tasks = tasks();
+        tasks.sort_children(SortKey::Scheduled);
+        let expected = "* Tasks\n\
+                        Notes\n\
+                        ** NEXT d task\n\
+                        SCHEDULED: <2020-03-01 Sun>\n\
+                        ** TODO [#C] A task\n\
+                        SCHEDULED: <2020-03-02 Mon>\n\
+                        *** Child\n\
+                        ** DONE b task\n\
+                        ** [#A] c task";
+        assert_eq!(tasks.to_string(), expected);
+    }
+
+    #[test]
+    fn title_like_tags_is_escaped() {
+        let mut headline = Headline::builder().title("Ratio :1:2:").build();
+        assert_eq!(headline.to_string(), "* Ratio :1:2:\u{200b}");
+        headline.tags.push("tag".to_string());
+        assert_eq!(headline.to_string(), "* Ratio :1:2: :tag:");
+    }
+
+    #[test]
+    fn split_and_join() {
+        let mut a = headline("* a :work:\n:PROPERTIES:\n:ID: 1\n:END:\nfirst\nb\n** child\n");
+        let original = a.clone();
+
+        let b = a.split_at(1);
+        assert_eq!(
+            a.to_string(),
+            "* a :work:\n:PROPERTIES:\n:ID: 1\n:END:\nfirst"
+        );
+        assert_eq!(b.to_string(), "* b\n** child");
+
+        // the spans are not updated when splitting
+        a.join_with_next(b);
+        assert_eq!(a.to_string(), original.to_string());
+
+        let c = Headline::builder()
+            .title("c")
+            .tag("home")
+            .property("ID", "2")
+            .property("CATEGORY", "x")
+            .build();
+        a.join_with_next(c);
+        assert_eq!(a.tags, vec!["work".to_string(), "home".to_string()]);
+        assert_eq!(a.property("ID").as_deref(), Some("1"));
+        assert_eq!(a.property("CATEGORY").as_deref(), Some("x"));
+    }
+
+    #[test]
+    fn property_with_inheritance() {
+        let document = document::Document::parse(
+            "#+PROPERTY: header-args :results silent\n\
+             #+PROPERTY: header-args+ :exports code\n\
+             * a\n\
+             :PROPERTIES:\n\
+             :CATEGORY: work\n\
+             :header-args+: :eval no\n\
+             :END:\n\
+             ** b\n\
+             :PROPERTIES:\n\
+             :CATEGORY: home\n\
+             :END:\n\
+             *** c\n",
+        )
+        .unwrap();
+        let (id, _) = document.node_ids().pop().unwrap();
+        let property = |name| document.property_with_inheritance(&id, name);
+        assert_eq!(property("category").as_deref(), Some("home"));
+        assert_eq!(
+            property("header-args").as_deref(),
+            Some(":results silent :exports code :eval no")
+        );
+        assert_eq!(property("ID"), None);
+        assert_eq!(
+            document.headlines[0]
+                .property_with_inheritance(&document, "CATEGORY")
+                .as_deref(),
+            Some("work")
+        );
+    }
+
+    #[test]
+    fn formatted_title() {
+        let headline = headline("* TODO [#A] Write [1/3] report [50%] [x] :work:urgent:\n");
+        assert_eq!(
+            headline.formatted_title(&TitleFormat::default()),
+            "Write report [x]"
+        );
+        assert_eq!(
+            headline.formatted_title(&TitleFormat::full()),
+            "TODO [#A] Write [1/3] report [50%] [x] :work:urgent:"
+        );
+        let format = TitleFormat {
+            todo_keyword: true,
+            tags: true,
+            ..TitleFormat::default()
+        };
+        assert_eq!(
+            headline.formatted_title(&format),
+            "TODO Write report [x] :work:urgent:"
+        );
+    }
+}
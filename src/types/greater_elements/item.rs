@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// An item in a list.
 ///
@@ -24,34 +25,157 @@ use super::*;
 /// newline. Only parsed as the description in unordered lists. Then the list is a description
 /// list.
 ///
-/// `CONTENT` is parsed as
+/// `CONTENT` can contain any element (including other lists).
 ///
 /// An item ends before the next item, the first line that is less or equally indented that its
 /// starting line or two consecutive empty lines. Indentation of lines within other greater
 /// elements including inlinetask boundaries are ignored.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Item {
-    content: Spanned<Vec<StandardSetNoLineBreak>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>,
     pub kind: ItemKind,
     pub checkbox: Option<Checkbox>,
+    pub(crate) post_blank: Option<usize>,
     // structure ?
     // hiddenp: bool
 }
 
-impl Parent<Vec<StandardSetNoLineBreak>> for Item {
-    fn content(&self) -> Option<&Spanned<Vec<StandardSetNoLineBreak>>> {
+impl Item {
+    pub fn new(kind: ItemKind, content: Spanned<Vec<ElementSet>>) -> Self {
+        Item {
+            content,
+            kind,
+            checkbox: None,
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
+
+    /// Sets the checkbox of this item.
+    ///
+    /// A checked or unchecked state is also set for all nested items with a checkbox (like
+    /// `org-toggle-checkbox` on an item with children). The parent items are not changed, use
+    /// [`PlainList::update_checkboxes`] on the outermost list for that.
+    pub fn set_checkbox(&mut self, checkbox: Checkbox) {
+        if checkbox != Checkbox::Partial {
+            self.set_nested_checkboxes(&checkbox);
+        }
+        self.checkbox = Some(checkbox);
+    }
+
+    /// Checks an unchecked or partially checked checkbox and unchecks a checked one (see
+    /// [`set_checkbox`][Item::set_checkbox]).
+    ///
+    /// Returns `false` if the item has no checkbox.
+    pub fn toggle_checkbox(&mut self) -> bool {
+        match self.checkbox {
+            Some(Checkbox::Checked) => self.set_checkbox(Checkbox::Unchecked),
+            Some(_) => self.set_checkbox(Checkbox::Checked),
+            None => return false,
+        }
+        true
+    }
+
+    fn set_nested_checkboxes(&mut self, checkbox: &Checkbox) {
+        for item in self.nested_items_mut() {
+            if item.checkbox.is_some() {
+                item.checkbox = Some(checkbox.clone());
+            }
+            item.set_nested_checkboxes(checkbox);
+        }
+    }
+
+    /// Updates the checkbox of this item from the checkboxes of the directly nested items
+    /// (after updating them).
+    pub(crate) fn update_checkbox(&mut self) {
+        let mut states = Vec::new();
+        for item in self.nested_items_mut() {
+            item.update_checkbox();
+            states.extend(item.checkbox.clone());
+        }
+        if self.checkbox.is_none() || states.is_empty() {
+            return;
+        }
+        let all = |checkbox| states.iter().all(|state| *state == checkbox);
+        self.checkbox = Some(if all(Checkbox::Checked) {
+            Checkbox::Checked
+        } else if all(Checkbox::Unchecked) {
+            Checkbox::Unchecked
+        } else {
+            Checkbox::Partial
+        });
+    }
+
+    /// Returns the items of the lists directly in this item.
+    fn nested_items_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.content_mut()
+            .iter_mut()
+            .filter_map(|element| match element {
+                ElementSet::PlainList(list) => Some(list),
+                _ => None,
+            })
+            .flat_map(|list| list.content_mut().iter_mut())
+    }
+}
+
+impl Parent<Vec<ElementSet>> for Item {
+    fn content(&self) -> Option<&Spanned<Vec<ElementSet>>> {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bullet = self.kind.bullet();
+        let mut line = bullet.clone();
+        if let ItemKind::Ordered {
+            counter: Some(counter),
+            ..
+        } = &self.kind
+        {
+            line.push_str(&format!(" [@{}]", counter));
+        }
+        if let Some(checkbox) = &self.checkbox {
+            line.push_str(&format!(" {}", checkbox));
+        }
+        if let ItemKind::Description { tag, .. } = &self.kind {
+            line.push_str(&format!(" {} ::", tag));
+        }
+
+        let content = join_compact_elements(self.content.value());
+
+        if content.is_empty() {
+            write!(f, "{}", line)
+        } else if let Some(ElementSet::Paragraph(_)) = self.content.value().first() {
+            write!(f, "{} {}", line, indent_rest(&content, bullet.len() + 1))
+        } else {
+            // only a paragraph can start on the line of the bullet
+            let indentation = " ".repeat(bullet.len() + 1);
+            write!(
+                f,
+                "{}\n{}{}",
+                line,
+                indentation,
+                indent_rest(&content, bullet.len() + 1)
+            )
+        }
+    }
+}
 /// The kind of an [`Item`] (and it's metadata).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ItemKind {
     Unordered {
         bullet: UnorderedBullet,
     },
     Ordered {
         bullet: OrderedBullet,
-        counter: Counter,
+        /// The counter set with `[@COUNTER]`.
+        counter: Option<Counter>,
     },
     Description {
         bullet: UnorderedBullet,
@@ -59,39 +183,90 @@ pub enum ItemKind {
     },
 }
 
+impl ItemKind {
+    /// Returns the bullet as it is written in the org file (e.g. `-` or `1.`).
+    pub fn bullet(&self) -> String {
+        match self {
+            ItemKind::Unordered { bullet } | ItemKind::Description { bullet, .. } => {
+                bullet.to_string()
+            }
+            ItemKind::Ordered { bullet, .. } => bullet.to_string(),
+        }
+    }
+}
+
 /// An unordered bullet of a lists [`ItemKind`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnorderedBullet {
     Minus,
     Plus,
     Star,
 }
 
+impl fmt::Display for UnorderedBullet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnorderedBullet::Minus => write!(f, "-"),
+            UnorderedBullet::Plus => write!(f, "+"),
+            UnorderedBullet::Star => write!(f, "*"),
+        }
+    }
+}
+
 /// An ordered bullet of a lists [`ItemKind`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderedBullet {
     pub counter: Counter,
     pub delimiter: CounterDelimiter,
 }
 
+impl fmt::Display for OrderedBullet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.counter, self.delimiter)
+    }
+}
+
 /// A counter of an ordered [`Item`].
 ///
 /// See [`ItemKind`] and [`OrderedBullet`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Counter {
     Number(u64),
     Letter(char),
 }
 
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Counter::Number(number) => write!(f, "{}", number),
+            Counter::Letter(letter) => write!(f, "{}", letter),
+        }
+    }
+}
+
 /// A delimiter after a [`Counter`] in an [`OrderedBullet`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CounterDelimiter {
     Period,
     Parenthesis,
 }
 
+impl fmt::Display for CounterDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CounterDelimiter::Period => write!(f, "."),
+            CounterDelimiter::Parenthesis => write!(f, ")"),
+        }
+    }
+}
+
 /// Checkbox of an [`Item`] in a list.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Checkbox {
     /// A space. (Empty checkbox)
     Unchecked,
@@ -100,3 +275,13 @@ pub enum Checkbox {
     /// `-`. (Some children of this list item are unchecked and some are checked)
     Partial,
 }
+
+impl fmt::Display for Checkbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Checkbox::Unchecked => write!(f, "[ ]"),
+            Checkbox::Checked => write!(f, "[X]"),
+            Checkbox::Partial => write!(f, "[-]"),
+        }
+    }
+}
@@ -25,6 +25,7 @@ pub use self::headline::*;
 pub use self::item::*;
 pub use self::plain_list::{ListKind, PlainList};
 pub use self::property_drawer::PropertyDrawer;
+pub(crate) use self::property_drawer::accumulate as accumulate_property;
 pub use self::quote_block::QuoteBlock;
 pub use self::section::Section;
 pub use self::special_block::SpecialBlock;
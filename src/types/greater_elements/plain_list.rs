@@ -1,4 +1,7 @@
 use super::*;
+use crate::parsing::parse_objects;
+use crate::types::elements::Paragraph;
+use std::fmt;
 
 /// A plain list.
 ///
@@ -15,9 +18,11 @@ use super::*;
 /// list*. If it contains a tag it is be a *descriptive list*. Otherwise it is be an
 /// *unordered list*.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlainList {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<Item>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<Item>>,
+    pub(crate) post_blank: Option<usize>,
     // structure ?
 }
 
@@ -28,17 +33,327 @@ impl Parent<Vec<Item>> for PlainList {
 }
 
 impl PlainList {
+    pub fn new(content: Spanned<Vec<Item>>) -> Self {
+        PlainList {
+            affiliated_keywords: None,
+            content,
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<Item> {
+        self.content.get_mut_value()
+    }
+
+    /// Updates the checkboxes of the items from the checkboxes of their nested items (like org
+    /// mode does after toggling a checkbox).
+    ///
+    /// An item with a checkbox is checked if all nested checkboxes are checked, unchecked if
+    /// none of them are checked and partially checked (`[-]`) otherwise. Items without nested
+    /// checkboxes keep their state.
+    pub fn update_checkboxes(&mut self) {
+        for item in self.content_mut() {
+            item.update_checkbox();
+        }
+    }
+
+    /// Returns the kind of the list which is determined by the first item.
+    ///
+    /// Empty lists are unordered.
     pub fn kind(&self) -> ListKind {
-        // find first item and get kind of item
-        // TODO not sure if this is the best way
-        unimplemented!()
+        match self.content.value().first().map(|item| &item.kind) {
+            Some(ItemKind::Ordered { .. }) => ListKind::Ordered,
+            Some(ItemKind::Description { .. }) => ListKind::Description,
+            Some(ItemKind::Unordered { .. }) | None => ListKind::Unordered,
+        }
+    }
+
+    /// Fixes the counters of an ordered list (e.g. after items were inserted or removed).
+    ///
+    /// The items are numbered from 1 (or `a` if the first bullet is a letter) and all bullets
+    /// get the delimiter of the first one. A counter set with `[@COUNTER]` restarts the
+    /// numbering at its value. Nested lists are renumbered too. Unordered and description lists
+    /// are not changed (except for their nested lists).
+    pub fn renumber(&mut self) {
+        let first = match self.content.value().first().map(|item| &item.kind) {
+            Some(ItemKind::Ordered { bullet, .. }) => Some(bullet.clone()),
+            _ => None,
+        };
+        let mut next = 1;
+        for item in self.content_mut() {
+            if let Some(first) = &first {
+                let counter = match &item.kind {
+                    ItemKind::Ordered { counter, .. } => counter.clone(),
+                    _ => None,
+                };
+                if let Some(counter) = &counter {
+                    next = counter_value(counter);
+                }
+                tag_to_text(item);
+                item.kind = ItemKind::Ordered {
+                    bullet: OrderedBullet {
+                        counter: counter_like(next, &first.counter),
+                        delimiter: first.delimiter.clone(),
+                    },
+                    counter,
+                };
+                next += 1;
+            }
+            for element in item.content_mut() {
+                if let ElementSet::PlainList(list) = element {
+                    list.renumber();
+                }
+            }
+        }
+    }
+
+    /// Converts the list to another kind and rewrites all bullets the same way.
+    ///
+    /// Unordered and description lists use the unordered bullet of the first item (or `-`),
+    /// ordered lists use the delimiter of the first item (or `.`) and are renumbered (see
+    /// [`PlainList::renumber`]). Nested lists are not converted.
+    ///
+    /// Only description items have tags. When converting a description list the tags are kept
+    /// as text at the start of the items (`TAG :: `, which is how org mode writes them). When
+    /// converting to a description list this text is split off again. Items without it use
+    /// their first paragraph as the tag.
+    pub fn convert(&mut self, kind: ListKind) {
+        let first = self.content.value().first().map(|item| item.kind.clone());
+        match kind {
+            ListKind::Unordered | ListKind::Description => {
+                let bullet = match first {
+                    Some(ItemKind::Unordered { bullet })
+                    | Some(ItemKind::Description { bullet, .. }) => bullet,
+                    _ => UnorderedBullet::Minus,
+                };
+                for item in self.content_mut() {
+                    tag_to_text(item);
+                    item.kind = match kind {
+                        ListKind::Description => ItemKind::Description {
+                            bullet: bullet.clone(),
+                            tag: text_to_tag(item),
+                        },
+                        _ => ItemKind::Unordered {
+                            bullet: bullet.clone(),
+                        },
+                    };
+                }
+            }
+            ListKind::Ordered => {
+                let delimiter = match first {
+                    Some(ItemKind::Ordered { bullet, .. }) => bullet.delimiter,
+                    _ => CounterDelimiter::Period,
+                };
+                for item in self.content_mut() {
+                    if let ItemKind::Ordered { .. } = item.kind {
+                        continue;
+                    }
+                    tag_to_text(item);
+                    item.kind = ItemKind::Ordered {
+                        bullet: OrderedBullet {
+                            counter: Counter::Number(1),
+                            delimiter: delimiter.clone(),
+                        },
+                        counter: None,
+                    };
+                }
+                self.renumber();
+            }
+        }
+    }
+}
+
+/// Returns the number of the counter (letters count from 1 for `a`).
+fn counter_value(counter: &Counter) -> u64 {
+    match counter {
+        Counter::Number(number) => *number,
+        Counter::Letter(letter) => u64::from(letter.to_ascii_lowercase() as u8 - b'a') + 1,
+    }
+}
+
+/// Returns the counter with the value written like `like` (a number or a letter in the same
+/// case). Values after `z` are written as numbers.
+fn counter_like(value: u64, like: &Counter) -> Counter {
+    match like {
+        Counter::Letter(letter) if (1..=26).contains(&value) => {
+            let letter_a = if letter.is_ascii_uppercase() {
+                b'A'
+            } else {
+                b'a'
+            };
+            Counter::Letter((letter_a + value as u8 - 1) as char)
+        }
+        _ => Counter::Number(value),
+    }
+}
+
+/// Moves the tag of a description item to the start of its first paragraph (`TAG :: `).
+fn tag_to_text(item: &mut Item) {
+    let tag = match &item.kind {
+        ItemKind::Description { tag, .. } => tag.clone(),
+        _ => return,
+    };
+    let content = item.content_mut();
+    match content.first_mut() {
+        Some(ElementSet::Paragraph(paragraph)) => {
+            let text = format!("{} :: {}", tag, paragraph.content);
+            paragraph.content = parse_objects(&text).into();
+        }
+        _ => {
+            let text = format!("{} ::", tag);
+            content.insert(0, Paragraph::new(parse_objects(&text).into()).into());
+        }
+    }
+}
+
+/// Removes the tag from the start of the first paragraph of the item (see [`tag_to_text`]).
+///
+/// The whole paragraph is the tag if it contains no ` :: `.
+fn text_to_tag(item: &mut Item) -> String {
+    let content = item.content_mut();
+    let text = match content.first() {
+        Some(ElementSet::Paragraph(paragraph)) => paragraph.content.to_string(),
+        _ => return String::new(),
+    };
+    match text.find(" :: ") {
+        Some(end) => {
+            content[0] = Paragraph::new(parse_objects(&text[end + 4..]).into()).into();
+            text[..end].trim().to_string()
+        }
+        None => {
+            content.remove(0);
+            text.trim_end_matches(" ::").trim().to_string()
+        }
+    }
+}
+
+impl fmt::Display for PlainList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let items = self.content.value();
+        for (i, item) in items.iter().enumerate() {
+            write!(f, "{}", item)?;
+            if i + 1 < items.len() {
+                // blank lines between items are kept, the ones after the list are written by
+                // the parent
+                write!(f, "{}", "\n".repeat(1 + item.post_blank.unwrap_or(0)))?;
+            }
+        }
+        Ok(())
     }
 }
 
 /// The list kind of a [`PlainList`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListKind {
     Unordered,
     Ordered,
     Description,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::document::Document;
+
+    /// Returns the list nested in the item with the index.
+    fn nested(list: &mut PlainList, index: usize) -> &mut PlainList {
+        match &mut list.content_mut()[index].content_mut()[1] {
+            ElementSet::PlainList(nested) => nested,
+            element => panic!("expected a list: {:?}", element),
+        }
+    }
+
+    #[test]
+    fn toggle_and_update_checkboxes() {
+        let mut document = Document::parse(
+            "- [ ] Trip\n\
+             \x20 - [ ] Tickets\n\
+             \x20 - [ ] Hotel\n\
+             \x20   - [X] Book\n\
+             \x20   - [ ] Pay\n\
+             \x20 - Notes\n",
+        )
+        .unwrap();
+        let list = match &mut document.preface.as_mut().unwrap().content_mut()[0] {
+            ElementSet::PlainList(list) => list,
+            element => panic!("expected a list: {:?}", element),
+        };
+        list.update_checkboxes();
+        assert_eq!(
+            list.to_string(),
+            "- [-] Trip\n  - [ ] Tickets\n  - [-] Hotel\n    - [X] Book\n    - [ ] Pay\n  - Notes"
+        );
+
+        assert!(nested(list, 0).content_mut()[1].toggle_checkbox());
+        list.update_checkboxes();
+        assert_eq!(
+            list.to_string(),
+            "- [-] Trip\n  - [ ] Tickets\n  - [X] Hotel\n    - [X] Book\n    - [X] Pay\n  - Notes"
+        );
+
+        list.content_mut()[0].set_checkbox(Checkbox::Unchecked);
+        list.update_checkboxes();
+        assert_eq!(
+            list.to_string(),
+            "- [ ] Trip\n  - [ ] Tickets\n  - [ ] Hotel\n    - [ ] Book\n    - [ ] Pay\n  - Notes"
+        );
+        assert!(!nested(list, 0).content_mut()[2].toggle_checkbox());
+    }
+
+    /// Parses the text and returns its first element, a list.
+    fn list(text: &str) -> PlainList {
+        let mut document = Document::parse(text).unwrap();
+        match document.preface.as_mut().unwrap().content_mut().remove(0) {
+            ElementSet::PlainList(list) => *list,
+            element => panic!("expected a list: {:?}", element),
+        }
+    }
+
+    const ORDERED: &str = "b) first\n\
+                           z) [@5] second\n\
+                           a) third\n\
+                           \x20  1. nested\n\
+                           \x20  7. nested\n";
+
+    #[test]
+    fn renumber() {
+        let mut list = list(ORDERED);
+        list.renumber();
+        assert_eq!(
+            list.to_string(),
+            "a) first\ne) [@5] second\nf) third\n   1. nested\n   2. nested"
+        );
+    }
+
+    #[test]
+    fn convert_to_description() {
+        let mut list = list(ORDERED);
+        list.convert(ListKind::Description);
+        assert_eq!(list.kind(), ListKind::Description);
+        assert_eq!(
+            list.to_string(),
+            "- first ::\n- second ::\n- third ::\n  1. nested\n  7. nested"
+        );
+    }
+
+    #[test]
+    fn convert_description_to_ordered() {
+        // the tags are kept in the paragraph
+        let mut list = list("- first :: a\n- second ::\n");
+        list.convert(ListKind::Ordered);
+        assert_eq!(list.to_string(), "1. first :: a\n2. second ::");
+    }
+
+    #[test]
+    fn convert_to_unordered() {
+        let mut list = list(ORDERED);
+        list.convert(ListKind::Unordered);
+        assert_eq!(list.kind(), ListKind::Unordered);
+        assert_eq!(
+            list.to_string(),
+            "- first\n- second\n- third\n  1. nested\n  7. nested"
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use super::*;
+use std::borrow::Cow;
+use std::fmt;
 
 /// A property drawer.
 ///
@@ -18,13 +20,186 @@ use super::*;
 ///
 /// `CONTENTS` consists of zero or more [`elements::NodeProperty`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PropertyDrawer {
-    content: Spanned<Vec<elements::NodeProperty>>,
+    pub(crate) content: Spanned<Vec<elements::NodeProperty>>,
+    pub(crate) post_blank: Option<usize>,
     // hiddenp: bool
 }
 
+impl PropertyDrawer {
+    pub fn new(content: Spanned<Vec<elements::NodeProperty>>) -> Self {
+        PropertyDrawer {
+            content,
+            post_blank: None,
+        }
+    }
+
+    /// Returns the value of the property with the given name.
+    ///
+    /// Property names are case-insensitive. The values of `NAME+` properties are appended to
+    /// the value (separated by a space) like org mode does.
+    pub fn get(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.get_with_inherited(name, None)
+    }
+
+    /// Like [`PropertyDrawer::get`] but `NAME+` properties are appended to the inherited value
+    /// (e.g. the value of the parent headline).
+    pub(crate) fn get_with_inherited<'a>(
+        &'a self,
+        name: &str,
+        inherited: Option<Cow<'a, str>>,
+    ) -> Option<Cow<'a, str>> {
+        self.content
+            .value()
+            .iter()
+            .fold(inherited, |value, property| {
+                accumulate(value, name, &property.name, &property.value)
+            })
+    }
+
+    /// Sets the value of a property (replacing an existing value).
+    ///
+    /// `NAME+` properties for the name are removed. New properties are added at the end of the
+    /// drawer.
+    pub fn set(&mut self, name: &str, value: impl Into<String>) {
+        let name = base_name(name);
+        let properties = self.content.get_mut_value();
+        let value = value.into();
+        properties.retain(|property| {
+            !(property.name.ends_with('+') && base_name(&property.name).eq_ignore_ascii_case(name))
+        });
+        match properties
+            .iter_mut()
+            .find(|property| property.name.eq_ignore_ascii_case(name))
+        {
+            Some(property) => property.value = value,
+            None => properties.push(elements::NodeProperty {
+                name: name.to_string(),
+                value,
+            }),
+        }
+    }
+
+    /// Removes a property (including its `NAME+` properties) and returns its value.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let value = self.get(name).map(Cow::into_owned);
+        let name = base_name(name);
+        self.content
+            .get_mut_value()
+            .retain(|property| !base_name(&property.name).eq_ignore_ascii_case(name));
+        value
+    }
+
+    /// Returns the names and values of all properties in the order they first appear.
+    ///
+    /// `NAME+` properties are merged into the value of `NAME` (see [`PropertyDrawer::get`]).
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        let properties = self.content.value();
+        properties
+            .iter()
+            .enumerate()
+            .filter(move |(index, property)| {
+                let name = base_name(&property.name);
+                !properties[..*index]
+                    .iter()
+                    .any(|previous| base_name(&previous.name).eq_ignore_ascii_case(name))
+            })
+            .filter_map(move |(_, property)| {
+                let name = base_name(&property.name);
+                self.get(name).map(|value| (name, value))
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.value().is_empty()
+    }
+}
+
+/// Adds a property to the value of the property `name`.
+///
+/// Properties with another name are ignored. `NAME+` properties are appended to the value
+/// (separated by a space), `NAME` properties replace it.
+pub(crate) fn accumulate<'a>(
+    value: Option<Cow<'a, str>>,
+    name: &str,
+    property_name: &str,
+    property_value: &'a str,
+) -> Option<Cow<'a, str>> {
+    if !base_name(property_name).eq_ignore_ascii_case(base_name(name)) {
+        return value;
+    }
+    match value {
+        Some(previous) if property_name.ends_with('+') => {
+            Some(format!("{} {}", previous, property_value).into())
+        }
+        _ => Some(property_value.into()),
+    }
+}
+
+/// Returns the name of the property without the `+` of accumulating properties.
+fn base_name(name: &str) -> &str {
+    if name.len() > 1 && name.ends_with('+') {
+        &name[..name.len() - 1]
+    } else {
+        name
+    }
+}
+
 impl Parent<Vec<elements::NodeProperty>> for PropertyDrawer {
     fn content(&self) -> Option<&Spanned<Vec<elements::NodeProperty>>> {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for PropertyDrawer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, ":PROPERTIES:")?;
+        for property in self.content.value() {
+            writeln!(f, "{}", serialize::indent(&property.to_string()))?;
+        }
+        write!(f, ":END:")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(name: &str, value: &str) -> elements::NodeProperty {
+        elements::NodeProperty {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn accumulated_properties() {
+        let mut drawer = PropertyDrawer::new(Spanned::new(vec![
+            property("VAR", "a=1"),
+            property("ID", "x"),
+            property("var+", "b=2"),
+            property("TAGS+", "work"),
+        ]));
+        assert_eq!(drawer.get("var").as_deref(), Some("a=1 b=2"));
+        assert_eq!(drawer.get("TAGS").as_deref(), Some("work"));
+        assert_eq!(
+            drawer
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>(),
+            vec!["VAR=a=1 b=2", "ID=x", "TAGS=work"]
+        );
+
+        drawer.set("VAR+", "c=3");
+        assert_eq!(drawer.get("VAR").as_deref(), Some("c=3"));
+        assert_eq!(drawer.remove("tags"), Some("work".to_string()));
+        assert_eq!(drawer.remove("tags"), None);
+        assert_eq!(drawer.to_string(), ":PROPERTIES:\n:VAR: c=3\n:ID: x\n:END:");
+
+        let mut headline = Headline::default();
+        headline.set_property("ID", "y");
+        assert_eq!(headline.remove_property("ID"), Some("y".to_string()));
+        assert_eq!(headline.property_drawer, None);
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A quote.
 ///
@@ -19,14 +20,45 @@ use super::*;
 ///
 /// TODO not sure if this is actually a greater element
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct QuoteBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<ElementSet>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
     // hiddenp: bool
 }
 
+impl QuoteBlock {
+    pub fn new(content: Spanned<Vec<ElementSet>>) -> Self {
+        QuoteBlock {
+            affiliated_keywords: None,
+            content,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
+}
+
 impl Parent<Vec<ElementSet>> for QuoteBlock {
     fn content(&self) -> Option<&Spanned<Vec<ElementSet>>> {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for QuoteBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{}", keyword("BEGIN_QUOTE"))?;
+        write_block_elements(f, self.content.value())?;
+        write!(f, "#+{}", keyword("END_QUOTE"))
+    }
+}
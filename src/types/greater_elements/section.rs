@@ -1,4 +1,5 @@
-use crate::types::{ElementSet, Parent, Spanned};
+use crate::types::{write_elements, ElementSet, Parent, Spanned};
+use std::fmt;
 
 /// A section.
 ///
@@ -16,14 +17,48 @@ use crate::types::{ElementSet, Parent, Spanned};
 ///
 /// A section ends at the beginning of the next headline or the end of the file.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Section {
-    content: Spanned<Vec<ElementSet>>,
+    pub(crate) content: Spanned<Vec<ElementSet>>,
 }
 
 impl Section {
     pub fn new(content: Spanned<Vec<ElementSet>>) -> Self {
         Section { content }
     }
+
+    pub fn content_mut(&mut self) -> &mut Vec<ElementSet> {
+        self.content.get_mut_value()
+    }
+
+    /// Appends the elements of `other` to this section.
+    ///
+    /// A property drawer at the start of `other` (only allowed in the preface) is merged into
+    /// the property drawer of this section: properties that are already set keep their value.
+    pub fn merge(&mut self, other: Section) {
+        let mut elements = other.content.to_value();
+        let drawer = match elements.first() {
+            Some(ElementSet::PropertyDrawer(_)) => match elements.remove(0) {
+                ElementSet::PropertyDrawer(drawer) => Some(drawer),
+                _ => unreachable!("the first element is a property drawer"),
+            },
+            _ => None,
+        };
+        let content = self.content.get_mut_value();
+        if let Some(drawer) = drawer {
+            match content.first_mut() {
+                Some(ElementSet::PropertyDrawer(existing)) => {
+                    for (name, value) in drawer.iter() {
+                        if existing.get(name).is_none() {
+                            existing.set(name, value);
+                        }
+                    }
+                }
+                _ => content.insert(0, ElementSet::PropertyDrawer(drawer)),
+            }
+        }
+        content.extend(elements);
+    }
 }
 
 impl Parent<Vec<ElementSet>> for Section {
@@ -31,3 +66,9 @@ impl Parent<Vec<ElementSet>> for Section {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_elements(f, self.content.value())
+    }
+}
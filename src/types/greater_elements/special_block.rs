@@ -1,5 +1,8 @@
 use crate::types::affiliated_keywords::AffiliatedKeywords;
-use crate::types::{Element, GreaterElement, HasAffiliatedKeywords, Parent, Spanned};
+use crate::types::{
+    serialize, write_affiliated_keywords, write_block_content, HasAffiliatedKeywords, Parent,
+    Spanned,
+};
 use std::fmt;
 
 /// A special block.
@@ -23,12 +26,30 @@ use std::fmt;
 ///
 /// TODO not sure if this is actually a greater element
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SpecialBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<String>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<String>,
     pub name: String,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
     // hiddenp: bool
 }
+impl SpecialBlock {
+    pub fn new(name: String, content: Spanned<String>) -> Self {
+        SpecialBlock {
+            affiliated_keywords: None,
+            content,
+            name,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+}
+
 impl Parent<String> for SpecialBlock {
     fn content(&self) -> Option<&Spanned<String>> {
         Some(&self.content)
@@ -39,16 +60,14 @@ impl HasAffiliatedKeywords for SpecialBlock {
         self.affiliated_keywords.as_ref()
     }
 }
-impl Element for SpecialBlock {}
-impl GreaterElement for SpecialBlock {}
 
 impl fmt::Display for SpecialBlock {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "#+BEGIN_{}", self.name)?;
-        for line in self.content() {
-            writeln!(f, "{}", line)?;
-        }
-        write!(f, "#+END_{}", self.name)
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{}{}", keyword("BEGIN_"), self.name)?;
+        write_block_content(f, self.content.value())?;
+        write!(f, "#+{}{}", keyword("END_"), self.name)
     }
 }
 
@@ -1,4 +1,6 @@
 use super::*;
+use crate::table::csv::CsvOptions;
+use std::fmt;
 
 /// A table.
 ///
@@ -44,11 +46,79 @@ use super::*;
 /// +------+------+------+
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
     // TODO make more type safe, org and table.el can't be mixed in one table.
-    content: Spanned<Vec<TableContent>>,
+    pub(crate) content: Spanned<Vec<TableContent>>,
     pub kind: TableKind,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl Table {
+    /// Creates a new org table with the given rows.
+    pub fn new(rows: Spanned<Vec<TableRow>>) -> Self {
+        Table {
+            affiliated_keywords: None,
+            content: rows.map_value(|rows| rows.into_iter().map(TableContent::Org).collect()),
+            kind: TableKind::Org {
+                formulas: Vec::new(),
+            },
+            post_blank: None,
+        }
+    }
+
+    /// Creates a new table.el table with the given lines.
+    pub fn table_el(lines: Spanned<Vec<String>>) -> Self {
+        let value = lines.value().join("\n");
+        Table {
+            affiliated_keywords: None,
+            content: lines
+                .map_value(|lines| lines.into_iter().map(TableContent::TableEl).collect()),
+            kind: TableKind::TableEl { value: Some(value) },
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<TableContent> {
+        self.content.get_mut_value()
+    }
+
+    /// Reads a table from CSV data. See [`csv`](`crate::table::csv`) for the details.
+    pub fn from_csv(reader: impl std::io::Read, options: &CsvOptions) -> std::io::Result<Table> {
+        crate::table::csv::from_csv(reader, options)
+    }
+
+    /// Writes the table as CSV data separated by commas.
+    ///
+    /// See [`csv`](`crate::table::csv`) for the details.
+    pub fn to_csv(&self, writer: impl std::io::Write) -> std::io::Result<()> {
+        self.to_csv_with_options(writer, &CsvOptions::default())
+    }
+
+    /// Writes the table as CSV data with the given options (e.g. [`CsvOptions::tsv`]).
+    pub fn to_csv_with_options(
+        &self,
+        writer: impl std::io::Write,
+        options: &CsvOptions,
+    ) -> std::io::Result<()> {
+        crate::table::csv::to_csv(self, writer, options)
+    }
+
+    /// Returns the text of the table with aligned columns (like `org-table-align`).
+    ///
+    /// The [`Display`](`fmt::Display`) implementation doesn't align the columns. See
+    /// [`align`](`crate::table::align`) for the details.
+    pub fn format(&self) -> String {
+        crate::table::align::align(self)
+    }
+
+    /// Applies the `#+TBLFM:` formulas and returns the table with the computed fields.
+    ///
+    /// See [`formula`](`crate::table::formula`) for the supported formulas.
+    pub fn recalculate(&self) -> Result<Table, crate::table::formula::FormulaError> {
+        crate::table::formula::recalculate(self)
+    }
 }
 
 impl Parent<Vec<TableContent>> for Table {
@@ -59,6 +129,7 @@ impl Parent<Vec<TableContent>> for Table {
 
 /// The set of objects that can be in a [`Table`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableContent {
     Org(TableRow),
     TableEl(String),
@@ -66,10 +137,36 @@ pub enum TableContent {
 
 /// The kind of a [`Table`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableKind {
-    Org,
-    TableEl {
+    Org {
+        /// The formulas of the `#+TBLFM:` lines after the table.
         formulas: Vec<String>,
+    },
+    TableEl {
+        /// The unparsed table.
         value: Option<String>,
     },
 }
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        write!(f, "{}", self.content.value().iter().format("\n"))?;
+        if let TableKind::Org { formulas } = &self.kind {
+            for formula in formulas {
+                write!(f, "\n#+TBLFM: {}", formula)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TableContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableContent::Org(row) => write!(f, "{}", row),
+            TableContent::TableEl(line) => write!(f, "{}", line),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A row in a [`Table`][`Table`].
 ///
@@ -19,16 +20,15 @@ use super::*;
 ///   |--------|
 ///   ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableRow {
     pub kind: TableRowKind,
 }
 
-impl Element for TableRow {}
-impl GreaterElement for TableRow {}
 impl Parent<Vec<objects::TableCell>> for TableRow {
     fn content(&self) -> Option<&Spanned<Vec<objects::TableCell>>> {
         match self.kind {
-            TableRowKind::Normal(ref content) => Some(&content),
+            TableRowKind::Normal(ref content) => Some(content),
             TableRowKind::Rule => None,
         }
     }
@@ -36,7 +36,23 @@ impl Parent<Vec<objects::TableCell>> for TableRow {
 
 /// The kind of a [`TableRow`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableRowKind {
     Normal(Spanned<Vec<objects::TableCell>>),
     Rule,
 }
+
+impl fmt::Display for TableRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TableRowKind::Normal(cells) => {
+                write!(f, "|")?;
+                for cell in cells.value() {
+                    write!(f, " {} |", cell)?;
+                }
+                Ok(())
+            }
+            TableRowKind::Rule => write!(f, "|-"),
+        }
+    }
+}
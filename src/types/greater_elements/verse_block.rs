@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A verse block.
 ///
@@ -18,9 +19,30 @@ use super::*;
 /// `CONTENTS` can contain anything except a line `#+END_VERSE` on its own. Lines beginning
 /// with stars must be quoted by comma. `CONTENTS` will be parsed as objects.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VerseBlock {
-    affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
-    content: Spanned<Vec<StandardSet>>,
+    pub(crate) affiliated_keywords: Option<Spanned<AffiliatedKeywords>>,
+    pub(crate) content: Spanned<Vec<StandardSet>>,
+    /// The case the keywords were written in (unless [`SerializeConfig::keyword_case`] is set).
+    ///
+    /// [`SerializeConfig::keyword_case`]: `serialize::SerializeConfig::keyword_case`
+    pub keyword_case: serialize::KeywordCase,
+    pub(crate) post_blank: Option<usize>,
+}
+
+impl VerseBlock {
+    pub fn new(content: Spanned<Vec<StandardSet>>) -> Self {
+        VerseBlock {
+            affiliated_keywords: None,
+            content,
+            keyword_case: serialize::KeywordCase::default(),
+            post_blank: None,
+        }
+    }
+
+    pub fn content_mut(&mut self) -> &mut Vec<StandardSet> {
+        self.content.get_mut_value()
+    }
 }
 
 impl Parent<Vec<StandardSet>> for VerseBlock {
@@ -28,3 +50,13 @@ impl Parent<Vec<StandardSet>> for VerseBlock {
         Some(&self.content)
     }
 }
+
+impl fmt::Display for VerseBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_affiliated_keywords(f, self.affiliated_keywords.as_ref())?;
+        let keyword = |keyword| serialize::keyword(keyword, self.keyword_case);
+        writeln!(f, "#+{}", keyword("BEGIN_VERSE"))?;
+        write_block_content(f, &self.content.value().iter().format("").to_string())?;
+        write!(f, "#+{}", keyword("END_VERSE"))
+    }
+}
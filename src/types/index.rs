@@ -0,0 +1,200 @@
+//! Indexes of the objects in a document.
+//!
+//! Features like completion, go to definition or find references (e.g. in a language server)
+//! need all objects of some kind. [`DocumentIndex`] collects them in a single walk over the
+//! document so the tree doesn't have to be traversed again for every request.
+
+use super::document::Document;
+use super::greater_elements::FootnoteDefinition;
+use super::objects::{FootnoteReference, Link, Macro, RadioTarget, Target, Timestamp};
+use super::visit::{self, ObjectRef, Visitor};
+use super::*;
+
+/// All links, targets, footnotes, macros, timestamps and named elements of a document in document
+/// order.
+///
+/// Objects nested in other objects (e.g. a timestamp in bold text or the description of a link)
+/// are included. Timestamps include the timestamps of planning lines and clocks.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DocumentIndex<'a> {
+    pub links: Vec<&'a Link>,
+    pub targets: Vec<&'a Target>,
+    pub radio_targets: Vec<&'a RadioTarget>,
+    pub footnote_references: Vec<&'a FootnoteReference>,
+    pub footnote_definitions: Vec<&'a FootnoteDefinition>,
+    pub macros: Vec<&'a Macro>,
+    pub timestamps: Vec<&'a Timestamp>,
+    /// The elements with a `#+NAME:`.
+    pub named_elements: Vec<&'a ElementSet>,
+}
+
+/// The definition of a footnote (see [`DocumentIndex::footnote_definition`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FootnoteDefinitionRef<'a> {
+    /// A footnote definition element (`[fn:LABEL] CONTENTS`).
+    Element(&'a FootnoteDefinition),
+    /// The definition of an inline footnote (`[fn:LABEL:DEFINITION]`).
+    Inline(&'a SecondaryString<StandardSet>),
+}
+
+impl<'a> DocumentIndex<'a> {
+    /// Finds the definition of the footnote with the label (without `fn:`).
+    ///
+    /// Footnote definition elements are preferred over inline footnotes. If a label is defined
+    /// more than once the first definition in the document is used.
+    pub fn footnote_definition(&self, label: &str) -> Option<FootnoteDefinitionRef<'a>> {
+        if let Some(definition) = self
+            .footnote_definitions
+            .iter()
+            .find(|definition| definition.label == label)
+        {
+            return Some(FootnoteDefinitionRef::Element(definition));
+        }
+        self.footnote_references
+            .iter()
+            .filter(|reference| reference.label() == Some(label))
+            .find_map(|reference| reference.definition())
+            .map(FootnoteDefinitionRef::Inline)
+    }
+
+    /// Returns the references to the footnote with the label in document order.
+    pub fn footnote_references_to(&self, label: &str) -> Vec<&'a FootnoteReference> {
+        self.footnote_references
+            .iter()
+            .cloned()
+            .filter(|reference| reference.label() == Some(label))
+            .collect()
+    }
+}
+
+pub(crate) fn index(document: &Document) -> DocumentIndex<'_> {
+    let mut index = DocumentIndex::default();
+    visit::walk(document, &mut index);
+    index
+}
+
+impl<'a> Visitor<'a> for DocumentIndex<'a> {
+    fn visit_element(&mut self, element: &'a ElementSet) {
+        if element.name().is_some() {
+            self.named_elements.push(element);
+        }
+        visit::walk_element(self, element);
+    }
+
+    fn visit_footnote_definition(&mut self, definition: &'a FootnoteDefinition) {
+        self.footnote_definitions.push(definition);
+        visit::walk_footnote_definition(self, definition);
+    }
+
+    fn visit_object(&mut self, object: ObjectRef<'a>) {
+        match object {
+            ObjectRef::Target(target) => self.targets.push(target),
+            ObjectRef::RadioTarget(target) => self.radio_targets.push(target),
+            ObjectRef::Macro(macro_object) => self.macros.push(macro_object),
+            _ => {}
+        }
+        visit::walk_object(self, object);
+    }
+
+    fn visit_link(&mut self, link: &'a Link) {
+        self.links.push(link);
+        visit::walk_link(self, link);
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &'a FootnoteReference) {
+        self.footnote_references.push(reference);
+        visit::walk_footnote_reference(self, reference);
+    }
+
+    fn visit_timestamp(&mut self, timestamp: &'a Timestamp) {
+        self.timestamps.push(timestamp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Paragraph;
+    use crate::types::greater_elements::{Headline, HeadlineContentSet, Section};
+
+    #[test]
+    fn footnotes() {
+        let document = Document::parse(
+            "Text[fn:1] and[fn:inline:defined /here/] and[fn::anonymous] and[fn:1].\n\
+             \n\
+             [fn:1] The definition.\n",
+        )
+        .unwrap();
+        let index = document.index();
+        assert_eq!(index.footnote_references.len(), 4);
+        assert_eq!(index.footnote_references_to("1").len(), 2);
+        match document.resolve_footnote("1") {
+            Some(FootnoteDefinitionRef::Element(definition)) => {
+                assert_eq!(definition.to_string(), "[fn:1] The definition.")
+            }
+            definition => panic!("expected a definition element, got {:?}", definition),
+        }
+        match document.resolve_footnote("inline") {
+            Some(FootnoteDefinitionRef::Inline(definition)) => {
+                assert_eq!(definition.to_string(), "defined /here/")
+            }
+            definition => panic!("expected an inline definition, got {:?}", definition),
+        }
+        assert_eq!(document.resolve_footnote("2"), None);
+    }
+
+    fn document() -> Document {
+        let paragraph = Paragraph::new(
+            crate::parsing::parse_objects("*see [[#a][<2018-10-12 Fri>]]* on <2018-10-13 Sat>")
+                .into(),
+        );
+        let definition = FootnoteDefinition::new(
+            "1".to_string(),
+            Spanned::new(vec![Paragraph::new(
+                crate::parsing::parse_objects("[[https://orgmode.org]]").into(),
+            )
+            .into()]),
+        );
+        let mut headline = Headline::default();
+        headline.push_content(vec![HeadlineContentSet::Section(Section::new(
+            Spanned::new(vec![paragraph.into(), definition.into()]),
+        ))]);
+        Document {
+            preface: None,
+            headlines: vec![headline],
+        }
+    }
+
+    #[test]
+    fn index_links() {
+        // links in markup and footnote definitions
+        assert_eq!(
+            document()
+                .index()
+                .links
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["[[#a][<2018-10-12 Fri>]]", "[[https://orgmode.org]]"]
+        );
+    }
+
+    #[test]
+    fn index_footnote_definitions() {
+        assert_eq!(document().index().footnote_definitions.len(), 1);
+    }
+
+    #[test]
+    fn index_timestamps() {
+        // timestamps in the descriptions of links are not indexed
+        assert_eq!(
+            document()
+                .index()
+                .timestamps
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["<2018-10-13 Sat>"]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Converting byte offsets (e.g. of a [`Span`]) to lines and columns and back.
+//!
+//! Spans are byte offsets into the parsed text but editors count lines and columns. The
+//! [Language Server Protocol](https://microsoft.github.io/language-server-protocol/) counts
+//! columns in UTF-16 code units by default, so the [`ColumnEncoding`] can be chosen.
+//!
+//! A [`LineIndex`] is built once for the text (e.g. with [`Document::parse_with_line_index`])
+//! and doesn't keep the text. Lines and columns start at 0 (unlike the ones of
+//! [`ParseDiagnostic`]). Lines end at `\n` (a `\r` before it is not part of the line).
+//!
+//! [`Document::parse_with_line_index`]: `super::document::Document::parse_with_line_index`
+//! [`ParseDiagnostic`]: `crate::parsing::ParseDiagnostic`
+
+use super::Span;
+use std::ops::Range;
+
+/// The unit in which columns are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColumnEncoding {
+    /// Bytes of the UTF-8 encoding (like the offsets of spans).
+    Utf8,
+    /// UTF-16 code units (the default of the Language Server Protocol).
+    #[default]
+    Utf16,
+    /// Characters (Unicode scalar values).
+    Utf32,
+}
+
+impl ColumnEncoding {
+    /// Returns the number of units of the character with the UTF-8 length.
+    fn len(self, len_utf8: usize) -> usize {
+        match self {
+            ColumnEncoding::Utf8 => len_utf8,
+            ColumnEncoding::Utf16 if len_utf8 == 4 => 2,
+            ColumnEncoding::Utf16 | ColumnEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// A position in the text (both starting at 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineCol {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A character that is longer than one byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct WideChar {
+    /// The byte offset in the line.
+    start: usize,
+    len_utf8: usize,
+}
+
+impl WideChar {
+    fn end(self) -> usize {
+        self.start + self.len_utf8
+    }
+}
+
+/// The start offsets of the lines of a text.
+///
+/// Only the characters longer than one byte are stored for every line, so converting columns
+/// of ASCII lines is cheap.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    /// The end of every line before the line break.
+    line_ends: Vec<usize>,
+    wide_chars: Vec<Vec<WideChar>>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut index = LineIndex {
+            line_starts: Vec::new(),
+            line_ends: Vec::new(),
+            wide_chars: Vec::new(),
+        };
+        let mut start = 0;
+        for line in text.split('\n') {
+            let content = if start + line.len() < text.len() {
+                line.trim_end_matches('\r')
+            } else {
+                line
+            };
+            index.line_starts.push(start);
+            index.line_ends.push(start + content.len());
+            index.wide_chars.push(
+                line.char_indices()
+                    .filter(|(_, c)| !c.is_ascii())
+                    .map(|(start, c)| WideChar {
+                        start,
+                        len_utf8: c.len_utf8(),
+                    })
+                    .collect(),
+            );
+            start += line.len() + 1;
+        }
+        index
+    }
+
+    /// Returns the number of lines (an empty text and a text ending with `\n` have an empty
+    /// last line).
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the line and column of the byte offset.
+    ///
+    /// Returns `None` if the offset is after the end of the text or inside a character.
+    pub fn line_col(&self, offset: usize, encoding: ColumnEncoding) -> Option<LineCol> {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let last_end = *self.line_ends.last().unwrap();
+        if line == self.line_count() - 1 && offset > last_end {
+            return None;
+        }
+        let bytes = offset - self.line_starts[line];
+        let mut column = bytes;
+        for wide_char in &self.wide_chars[line] {
+            if wide_char.end() <= bytes {
+                column -= wide_char.len_utf8 - encoding.len(wide_char.len_utf8);
+            } else if wide_char.start < bytes {
+                return None;
+            } else {
+                break;
+            }
+        }
+        Some(LineCol { line, column })
+    }
+
+    /// Returns the byte offset of the line and column.
+    ///
+    /// Columns after the end of the line are moved to the end of the line (like the Language
+    /// Server Protocol requires). Returns `None` if there is no such line or the column is
+    /// inside a character (e.g. between the UTF-16 code units of an emoji).
+    pub fn offset(&self, position: LineCol, encoding: ColumnEncoding) -> Option<usize> {
+        let start = *self.line_starts.get(position.line)?;
+        let mut bytes = position.column;
+        for wide_char in &self.wide_chars[position.line] {
+            if wide_char.start < bytes {
+                bytes += wide_char.len_utf8 - encoding.len(wide_char.len_utf8);
+            } else {
+                break;
+            }
+        }
+        let line_len = self.line_ends[position.line] - start;
+        if bytes >= line_len {
+            return Some(start + line_len);
+        }
+        let inside = self.wide_chars[position.line]
+            .iter()
+            .any(|wide_char| wide_char.start < bytes && bytes < wide_char.end());
+        if inside {
+            None
+        } else {
+            Some(start + bytes)
+        }
+    }
+
+    /// Returns the positions of the start and end of the span.
+    pub fn span_to_range(&self, span: &Span, encoding: ColumnEncoding) -> Option<Range<LineCol>> {
+        Some(self.line_col(span.start(), encoding)?..self.line_col(span.end(), encoding)?)
+    }
+
+    /// Returns the span between the positions (see [`LineIndex::offset`]).
+    pub fn range_to_span(&self, range: Range<LineCol>, encoding: ColumnEncoding) -> Option<Span> {
+        Some(Span::new(
+            self.offset(range.start, encoding)?,
+            self.offset(range.end, encoding)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "* Héllo\r\nä😀b\n";
+    /// The offset of `b` in [`TEXT`].
+    const B: usize = 16;
+
+    fn position(line: usize, column: usize) -> LineCol {
+        LineCol { line, column }
+    }
+
+    #[test]
+    fn line_count() {
+        assert_eq!(LineIndex::new(TEXT).line_count(), 3);
+        assert_eq!(LineIndex::new("").line_count(), 1);
+    }
+
+    #[test]
+    fn offset_to_line_col() {
+        let index = LineIndex::new(TEXT);
+        assert_eq!(TEXT.find('b'), Some(B));
+        assert_eq!(
+            index.line_col(B, ColumnEncoding::Utf8),
+            Some(position(1, 6))
+        );
+        assert_eq!(
+            index.line_col(B, ColumnEncoding::Utf16),
+            Some(position(1, 3))
+        );
+        assert_eq!(
+            index.line_col(B, ColumnEncoding::Utf32),
+            Some(position(1, 2))
+        );
+        assert_eq!(
+            index.line_col(TEXT.len(), ColumnEncoding::Utf16),
+            Some(position(2, 0))
+        );
+    }
+
+    #[test]
+    fn invalid_offset() {
+        let index = LineIndex::new(TEXT);
+        // inside the `é`
+        assert_eq!(index.line_col(4, ColumnEncoding::Utf16), None);
+        assert_eq!(index.line_col(TEXT.len() + 1, ColumnEncoding::Utf16), None);
+    }
+
+    #[test]
+    fn line_col_to_offset() {
+        let index = LineIndex::new(TEXT);
+        assert_eq!(index.offset(position(1, 3), ColumnEncoding::Utf16), Some(B));
+        assert_eq!(index.offset(position(1, 2), ColumnEncoding::Utf32), Some(B));
+        // the `\r` is not part of the line
+        assert_eq!(
+            index.offset(position(0, 40), ColumnEncoding::Utf16),
+            Some(8)
+        );
+    }
+
+    #[test]
+    fn invalid_line_col() {
+        let index = LineIndex::new(TEXT);
+        // between the code units of the emoji
+        assert_eq!(index.offset(position(1, 2), ColumnEncoding::Utf16), None);
+        assert_eq!(index.offset(position(3, 0), ColumnEncoding::Utf16), None);
+    }
+
+    #[test]
+    fn span_to_range_and_back() {
+        let index = LineIndex::new(TEXT);
+        let span = Span::new(2, B + 1);
+        let range = index.span_to_range(&span, ColumnEncoding::Utf16).unwrap();
+        assert_eq!(range, position(0, 2)..position(1, 4));
+        assert_eq!(
+            index.range_to_span(range, ColumnEncoding::Utf16),
+            Some(span)
+        );
+    }
+}
@@ -23,19 +23,57 @@
 //! [`HasAffiliatedKeywords`]: `types::HasAffiliatedKeywords`
 
 pub mod affiliated_keywords;
+pub mod dependencies;
 pub mod document;
 pub mod elements;
 pub mod greater_elements;
+pub mod index;
+pub mod line_index;
+pub mod node_id;
 pub mod objects;
+pub mod position;
+pub mod serialize;
+pub mod snapshot;
+pub mod validation;
+pub mod visit;
 
 use self::affiliated_keywords::AffiliatedKeywords;
 use itertools::Itertools;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Range;
 use std::str::pattern::Pattern;
 
-// TODO
-#[allow(dead_code)]
-static ORG_LINK_TYPES: () = ();
+/// The link types of plain and angle links (the default link types of `org-link-parameters`).
+///
+/// Bracket links can use any type.
+pub const ORG_LINK_TYPES: &[&str] = &[
+    "attachment",
+    "bbdb",
+    "bibtex",
+    "doi",
+    "docview",
+    "elisp",
+    "eww",
+    "file",
+    "file+emacs",
+    "file+sys",
+    "ftp",
+    "gnus",
+    "help",
+    "http",
+    "https",
+    "id",
+    "info",
+    "irc",
+    "mailto",
+    "mhe",
+    "news",
+    "rmail",
+    "shell",
+    "w3m",
+];
 
 /// Represents where in the file the a object or element is.
 ///
@@ -53,6 +91,7 @@ static ORG_LINK_TYPES: () = ();
 ///
 /// [`RangeFull`]: `std::ops::RangeFull`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     start: usize,
     end: usize,
@@ -68,6 +107,20 @@ impl Span {
     pub fn end(&self) -> usize {
         self.end
     }
+    /// Returns `true` if the byte at `offset` is part of the span.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+    /// Moves the span by `delta` bytes (e.g. after text before it was inserted or removed).
+    pub(crate) fn shift(&mut self, delta: isize) {
+        self.start = (self.start as isize + delta) as usize;
+        self.end = (self.end as isize + delta) as usize;
+    }
+    /// Moves the start and end of the span to the offsets returned by `f`.
+    pub(crate) fn map(&mut self, f: &dyn Fn(usize) -> usize) {
+        self.start = f(self.start);
+        self.end = f(self.end);
+    }
 }
 
 impl<'i> From<pest::Span<'i>> for Span {
@@ -99,6 +152,7 @@ pub trait HasAffiliatedKeywords: Element {
 /// Wrap the `Span` in an `Option` to represent the case where this element has been created
 /// artificially and is not part of a file.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spanned<T> {
     value: T,
     span: Option<Span>,
@@ -138,15 +192,18 @@ impl<T> Spanned<T> {
     pub fn get_mut_value(&mut self) -> &mut T {
         &mut self.value
     }
-}
-
-trait IntoSpanned<T> {
-    fn into_spanned(self, span: Option<Span>) -> Spanned<T>;
-}
-
-impl<T> IntoSpanned<T> for T {
-    fn into_spanned(self, span: Option<Span>) -> Spanned<T> {
-        Spanned::with_optional_span(self, span)
+    pub(crate) fn set_span(&mut self, span: Option<Span>) {
+        self.span = span;
+    }
+    pub(crate) fn shift_span(&mut self, delta: isize) {
+        if let Some(span) = &mut self.span {
+            span.shift(delta);
+        }
+    }
+    pub(crate) fn map_span(&mut self, f: &dyn Fn(usize) -> usize) {
+        if let Some(span) = &mut self.span {
+            span.map(f);
+        }
     }
 }
 
@@ -159,21 +216,128 @@ impl<T: fmt::Display> fmt::Display for Spanned<T> {
 /// A secondary string is a list of raw strings and objects.
 ///
 /// It is used for attributes of elements that can contain objects.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct SecondaryString<T: AsRawString>(Vec<T>);
+///
+/// Parsed secondary strings whose position in the file is known (e.g. the content of paragraphs
+/// and headline titles) also store the [`Span`] of every object. The spans are ignored when
+/// comparing and hashing secondary strings.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondaryString<T: AsRawString> {
+    objects: Vec<T>,
+    /// The spans of the objects. Either empty or one for each object.
+    spans: Vec<Span>,
+}
 
 impl<T: fmt::Display + AsRawString> fmt::Display for SecondaryString<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0.iter().format(" "))
+        write!(f, "{}", self.objects.iter().format(""))
     }
 }
 
 impl<T: AsRawString> SecondaryString<T> {
     pub fn new() -> Self {
-        SecondaryString(Vec::new())
+        SecondaryString::from(Vec::new())
     }
     pub fn with_one(t: T) -> Self {
-        SecondaryString(vec![t])
+        SecondaryString::from(vec![t])
+    }
+    pub(crate) fn with_spans(objects: Vec<T>, spans: Vec<Span>) -> Self {
+        debug_assert_eq!(objects.len(), spans.len());
+        SecondaryString { objects, spans }
+    }
+
+    /// Appends an object.
+    ///
+    /// The spans of the other objects are removed because the position of the new object is not
+    /// known.
+    pub fn push(&mut self, t: T) {
+        self.objects.push(t);
+        self.spans.clear();
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.objects.iter()
+    }
+    /// Returns an iterator that allows modifying each object.
+    ///
+    /// The spans are not updated.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.objects.iter_mut()
+    }
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Moves the spans of the objects (not of objects inside them) by `delta` bytes.
+    pub(crate) fn shift_spans(&mut self, delta: isize) {
+        for span in &mut self.spans {
+            span.shift(delta);
+        }
+    }
+    /// Moves the spans of the objects (not of objects inside them) with [`Span::map`].
+    pub(crate) fn map_spans(&mut self, f: &dyn Fn(usize) -> usize) {
+        for span in &mut self.spans {
+            span.map(f);
+        }
+    }
+    pub(crate) fn clear_spans(&mut self) {
+        self.spans.clear();
+    }
+
+    /// Returns the objects with their spans (`None` if the positions are not known).
+    pub fn iter_spanned(&self) -> impl Iterator<Item = (&T, Option<&Span>)> {
+        let spans = self.spans.iter().map(Some).chain(std::iter::repeat(None));
+        self.objects.iter().zip(spans)
+    }
+
+    /// Returns the object (or raw string) containing the byte offset with its span.
+    ///
+    /// Only the objects directly in this secondary string are searched. Objects containing other
+    /// objects (e.g. bold text) have their own secondary string with spans.
+    pub fn object_at_offset(&self, offset: usize) -> Option<(&T, &Span)> {
+        self.objects
+            .iter()
+            .zip(&self.spans)
+            .find(|(_, span)| span.contains(offset))
+    }
+
+    /// Converts every object and keeps the spans.
+    pub fn map<U: AsRawString, F: FnMut(T) -> U>(self, f: F) -> SecondaryString<U> {
+        SecondaryString {
+            objects: self.objects.into_iter().map(f).collect(),
+            spans: self.spans,
+        }
+    }
+
+    /// Replaces raw strings with the objects `split` returns for them.
+    ///
+    /// `split` returns the objects with their byte ranges in the raw string, or nothing to keep
+    /// the raw string. The spans of the new objects are computed from the span of the raw string.
+    pub(crate) fn split_raw_strings<F>(&mut self, mut split: F)
+    where
+        F: FnMut(&str) -> Vec<(Range<usize>, T)>,
+    {
+        let old_spans = mem::take(&mut self.spans);
+        let mut spans = old_spans.iter().map(Some).chain(std::iter::repeat(None));
+        let mut objects = Vec::with_capacity(self.objects.len());
+        for object in mem::take(&mut self.objects) {
+            let span = spans.next().unwrap();
+            let parts = object.as_raw_string().map(&mut split).unwrap_or_default();
+            if parts.is_empty() {
+                objects.push(object);
+                self.spans.extend(span.cloned());
+            }
+            for (range, part) in parts {
+                objects.push(part);
+                let start = span.map(Span::start);
+                self.spans.extend(
+                    start.map(|start| Span::new(start + range.start, start + range.end)),
+                );
+            }
+        }
+        self.objects = objects;
     }
 
     /// Returns `true` if this `SecondaryString` starts with a raw string and the given pattern matches
@@ -184,7 +348,7 @@ impl<T: AsRawString> SecondaryString<T> {
     where
         P: Pattern<'a>,
     {
-        self.0
+        self.objects
             .first()
             .and_then(|x| x.as_raw_string())
             .map(|s| s.starts_with(pat))
@@ -192,15 +356,53 @@ impl<T: AsRawString> SecondaryString<T> {
     }
 }
 
+impl<T: AsRawString + PartialEq> PartialEq for SecondaryString<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.objects == other.objects
+    }
+}
+
+impl<T: AsRawString + Eq> Eq for SecondaryString<T> {}
+
+impl<T: AsRawString + Hash> Hash for SecondaryString<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.objects.hash(state)
+    }
+}
+
 impl<T: AsRawString> Default for SecondaryString<T> {
     fn default() -> SecondaryString<T> {
         SecondaryString::new()
     }
 }
 
+impl<T: AsRawString> From<Vec<T>> for SecondaryString<T> {
+    fn from(objects: Vec<T>) -> Self {
+        SecondaryString {
+            objects,
+            spans: Vec::new(),
+        }
+    }
+}
+
+impl<T: AsRawString> std::iter::FromIterator<T> for SecondaryString<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SecondaryString::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: AsRawString> IntoIterator for SecondaryString<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects.into_iter()
+    }
+}
+
 impl<T: AsRawString> PartialEq<str> for SecondaryString<T> {
     fn eq(&self, other: &str) -> bool {
-        self.0
+        self.objects
             .first()
             .and_then(|x| x.as_raw_string())
             .map(|s| s == other)
@@ -222,12 +424,28 @@ pub trait AsRawString {
 /// Objects are the smallest units and represent the content of the org file.
 pub trait Object: crate::private::Sealed {}
 
-/// Marker trait for the elements in an org file.
+/// Trait for the elements in an org file.
 ///
-/// Elements represent the structure of the org file.
+/// Elements represent the structure of the org file. Generic code (e.g. serializers) can use the
+/// [`ElementKind`] of an element instead of matching on [`ElementSet`] and
+/// [`HeadlineContentSet`].
 ///
 /// See [`elements`] module for all available elements.
-pub trait Element: crate::private::Sealed {}
+///
+/// [`HeadlineContentSet`]: `greater_elements::HeadlineContentSet`
+pub trait Element: crate::private::Sealed {
+    fn kind(&self) -> ElementKind;
+
+    /// See [`ElementKind::is_greater_element`].
+    fn is_greater_element(&self) -> bool {
+        self.kind().is_greater_element()
+    }
+
+    /// See [`ElementKind::can_have_affiliated_keywords`].
+    fn can_have_affiliated_keywords(&self) -> bool {
+        self.kind().can_have_affiliated_keywords()
+    }
+}
 
 /// Marker trait for the greater elements in an org file.
 ///
@@ -237,84 +455,302 @@ pub trait Element: crate::private::Sealed {}
 /// See [`greater_elements`] module for all available greater elements.
 pub trait GreaterElement: Element {}
 
-/// The standard set of objects as defined by org mode.
-///
-/// These objects are used by most other recursive objects. E.g. a bold text can contain an entity.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum StandardSet {
-    RawString(String),
-    Entity(objects::Entity),
-    ExportSnippet(objects::ExportSnippet),
-    FootnoteReference(objects::FootnoteReference),
-    InlineBabelCall(objects::InlineBabelCall),
-    InlineSrcBlock(objects::InlineSrcBlock),
-    LatexFragment(objects::LatexFragment),
-    LineBreak(objects::LineBreak),
-    Link(objects::Link),
-    Macro(objects::Macro),
-    RadioTarget(objects::RadioTarget),
-    StatisticsCookie(objects::StatisticsCookie),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    Target(objects::Target),
-    TextMarkup(objects::TextMarkup),
-    Timestamp(objects::Timestamp),
-}
-
-impl fmt::Display for StandardSet {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+/// The kinds of elements and greater elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ElementKind {
+    BabelCall,
+    CenterBlock,
+    Clock,
+    Comment,
+    CommentBlock,
+    DiarySexp,
+    Drawer,
+    DynamicBlock,
+    EncryptedBlock,
+    ExampleBlock,
+    ExportBlock,
+    FixedWidth,
+    FootnoteDefinition,
+    Headline,
+    HorizontalRule,
+    Inlinetask,
+    Item,
+    Keyword,
+    LatexEnvironment,
+    NodeProperty,
+    Paragraph,
+    PlainList,
+    Planning,
+    PropertyDrawer,
+    QuoteBlock,
+    Section,
+    SpecialBlock,
+    SrcBlock,
+    Table,
+    TableRow,
+    VerseBlock,
+}
+
+impl ElementKind {
+    /// Returns `true` for the elements in the [`greater_elements`] module.
+    pub fn is_greater_element(self) -> bool {
+        matches!(
+            self,
+            ElementKind::CenterBlock
+                | ElementKind::Drawer
+                | ElementKind::DynamicBlock
+                | ElementKind::FootnoteDefinition
+                | ElementKind::Headline
+                | ElementKind::Inlinetask
+                | ElementKind::Item
+                | ElementKind::PlainList
+                | ElementKind::PropertyDrawer
+                | ElementKind::QuoteBlock
+                | ElementKind::Section
+                | ElementKind::SpecialBlock
+                | ElementKind::Table
+                | ElementKind::TableRow
+                | ElementKind::VerseBlock
+        )
+    }
+
+    /// Returns `true` if affiliated keywords (e.g. `#+NAME:`) directly above the element belong
+    /// to it.
+    pub fn can_have_affiliated_keywords(self) -> bool {
+        matches!(
+            self,
+            ElementKind::BabelCall
+                | ElementKind::CenterBlock
+                | ElementKind::Comment
+                | ElementKind::CommentBlock
+                | ElementKind::DiarySexp
+                | ElementKind::Drawer
+                | ElementKind::DynamicBlock
+                | ElementKind::ExampleBlock
+                | ElementKind::ExportBlock
+                | ElementKind::FixedWidth
+                | ElementKind::FootnoteDefinition
+                | ElementKind::HorizontalRule
+                | ElementKind::LatexEnvironment
+                | ElementKind::Paragraph
+                | ElementKind::PlainList
+                | ElementKind::QuoteBlock
+                | ElementKind::SpecialBlock
+                | ElementKind::SrcBlock
+                | ElementKind::Table
+                | ElementKind::VerseBlock
+        )
+    }
+
+    /// The name of the element type in org mode (e.g. `src-block`).
+    pub fn name(self) -> &'static str {
         match self {
-            StandardSet::RawString(s) => write!(f, "{}", s),
-            _ => unimplemented!(),
+            ElementKind::BabelCall => "babel-call",
+            ElementKind::CenterBlock => "center-block",
+            ElementKind::Clock => "clock",
+            ElementKind::Comment => "comment",
+            ElementKind::CommentBlock => "comment-block",
+            ElementKind::DiarySexp => "diary-sexp",
+            ElementKind::Drawer => "drawer",
+            ElementKind::DynamicBlock => "dynamic-block",
+            ElementKind::EncryptedBlock => "encrypted-block",
+            ElementKind::ExampleBlock => "example-block",
+            ElementKind::ExportBlock => "export-block",
+            ElementKind::FixedWidth => "fixed-width",
+            ElementKind::FootnoteDefinition => "footnote-definition",
+            ElementKind::Headline => "headline",
+            ElementKind::HorizontalRule => "horizontal-rule",
+            ElementKind::Inlinetask => "inlinetask",
+            ElementKind::Item => "item",
+            ElementKind::Keyword => "keyword",
+            ElementKind::LatexEnvironment => "latex-environment",
+            ElementKind::NodeProperty => "node-property",
+            ElementKind::Paragraph => "paragraph",
+            ElementKind::PlainList => "plain-list",
+            ElementKind::Planning => "planning",
+            ElementKind::PropertyDrawer => "property-drawer",
+            ElementKind::QuoteBlock => "quote-block",
+            ElementKind::Section => "section",
+            ElementKind::SpecialBlock => "special-block",
+            ElementKind::SrcBlock => "src-block",
+            ElementKind::Table => "table",
+            ElementKind::TableRow => "table-row",
+            ElementKind::VerseBlock => "verse-block",
         }
     }
 }
 
-impl AsRawString for StandardSet {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let StandardSet::RawString(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
     }
 }
 
-/// The standard set of objects without [`LineBreak`]s.
-///
-/// Used for elements that can contain the standard set but no line breaks. E.g.
-/// [`Headline`] or [`Inlinetask`].
+/// Implements [`Element`] (and [`GreaterElement`] for the types in [`greater_elements`]).
+macro_rules! impl_element {
+    ($($module:ident::$kind:ident),+ $(,)*) => {
+        $(
+            impl Element for $module::$kind {
+                fn kind(&self) -> ElementKind {
+                    ElementKind::$kind
+                }
+            }
+            impl_element!(@greater $module::$kind);
+        )+
+    };
+    (@greater elements::$kind:ident) => {};
+    (@greater greater_elements::$kind:ident) => {
+        impl GreaterElement for greater_elements::$kind {}
+    };
+}
+
+impl_element! {
+    elements::BabelCall,
+    greater_elements::CenterBlock,
+    elements::Clock,
+    elements::Comment,
+    elements::CommentBlock,
+    elements::DiarySexp,
+    greater_elements::Drawer,
+    greater_elements::DynamicBlock,
+    elements::EncryptedBlock,
+    elements::ExampleBlock,
+    elements::ExportBlock,
+    elements::FixedWidth,
+    greater_elements::FootnoteDefinition,
+    greater_elements::Headline,
+    elements::HorizontalRule,
+    greater_elements::Inlinetask,
+    greater_elements::Item,
+    elements::Keyword,
+    elements::LatexEnvironment,
+    elements::NodeProperty,
+    elements::Paragraph,
+    greater_elements::PlainList,
+    elements::Planning,
+    greater_elements::PropertyDrawer,
+    greater_elements::QuoteBlock,
+    greater_elements::Section,
+    greater_elements::SpecialBlock,
+    elements::SrcBlock,
+    greater_elements::Table,
+    greater_elements::TableRow,
+    greater_elements::VerseBlock,
+}
+
+/// Behavior shared by all elements.
 ///
-/// [`LineBreak`]: `objects::LineBreak`
-/// [`Headline`]: `greater_elements::Headline`
-/// [`Inlinetask`]: `greater_elements::Inlinetask`
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum StandardSetNoLineBreak {
-    RawString(String),
-    Entity(objects::Entity),
-    ExportSnippet(objects::ExportSnippet),
-    FootnoteReference(objects::FootnoteReference),
-    InlineBabelCall(objects::InlineBabelCall),
-    InlineSrcBlock(objects::InlineSrcBlock),
-    LatexFragment(objects::LatexFragment),
-    Link(objects::Link),
-    Macro(objects::Macro),
-    RadioTarget(objects::RadioTarget),
-    StatisticsCookie(objects::StatisticsCookie),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    Target(objects::Target),
-    TextMarkup(objects::TextMarkup),
-    Timestamp(objects::Timestamp),
-}
-
-impl AsRawString for StandardSetNoLineBreak {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let StandardSetNoLineBreak::RawString(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+/// Objects don't need this because the whitespace after them is part of the surrounding raw
+/// strings.
+pub trait SharedBehavior: crate::private::Sealed {
+    /// The number of blank lines after the element.
+    ///
+    /// This is only known for parsed elements. When it is `None` the element is separated from
+    /// the following element like the serializer does by default.
+    fn post_blank(&self) -> Option<usize>;
+
+    fn set_post_blank(&mut self, post_blank: Option<usize>);
+}
+
+/// Implements [`SharedBehavior`] for elements with a `post_blank` field.
+macro_rules! impl_shared_behavior {
+    ($($ty:ty),+ $(,)*) => {
+        $(
+            impl SharedBehavior for $ty {
+                fn post_blank(&self) -> Option<usize> {
+                    self.post_blank
+                }
+
+                fn set_post_blank(&mut self, post_blank: Option<usize>) {
+                    self.post_blank = post_blank;
+                }
+            }
+        )+
+    };
+}
+
+impl_shared_behavior! {
+    elements::BabelCall,
+    greater_elements::CenterBlock,
+    elements::Clock,
+    elements::Comment,
+    elements::CommentBlock,
+    elements::DiarySexp,
+    greater_elements::Drawer,
+    greater_elements::DynamicBlock,
+    elements::EncryptedBlock,
+    elements::ExampleBlock,
+    elements::ExportBlock,
+    elements::FixedWidth,
+    greater_elements::FootnoteDefinition,
+    elements::HorizontalRule,
+    greater_elements::Inlinetask,
+    greater_elements::Item,
+    elements::Keyword,
+    elements::LatexEnvironment,
+    elements::Paragraph,
+    greater_elements::PlainList,
+    elements::Planning,
+    greater_elements::PropertyDrawer,
+    greater_elements::QuoteBlock,
+    greater_elements::SpecialBlock,
+    elements::SrcBlock,
+    greater_elements::Table,
+    greater_elements::VerseBlock,
+}
+
+define_object_set! {
+    /// The standard set of objects as defined by org mode.
+    ///
+    /// These objects are used by most other recursive objects. E.g. a bold text can contain an
+    /// entity.
+    StandardSet {
+        Entity,
+        ExportSnippet,
+        FootnoteReference,
+        InlineBabelCall,
+        InlineSrcBlock,
+        LatexFragment,
+        LineBreak,
+        Link,
+        Macro,
+        RadioTarget,
+        StatisticsCookie,
+        Subscript,
+        Superscript,
+        Target,
+        TextMarkup,
+        Timestamp,
+    }
+}
+
+define_object_set! {
+    /// The standard set of objects without [`LineBreak`]s.
+    ///
+    /// Used for elements that can contain the standard set but no line breaks. E.g.
+    /// [`Headline`] or [`Inlinetask`].
+    ///
+    /// [`LineBreak`]: `objects::LineBreak`
+    /// [`Headline`]: `greater_elements::Headline`
+    /// [`Inlinetask`]: `greater_elements::Inlinetask`
+    StandardSetNoLineBreak from StandardSet {
+        Entity,
+        ExportSnippet,
+        FootnoteReference,
+        InlineBabelCall,
+        InlineSrcBlock,
+        LatexFragment,
+        Link,
+        Macro,
+        RadioTarget,
+        StatisticsCookie,
+        Subscript,
+        Superscript,
+        Target,
+        TextMarkup,
+        Timestamp,
     }
 }
 
@@ -324,6 +760,8 @@ impl AsRawString for StandardSetNoLineBreak {
 /// usually directly contain elements of the same type. So this is not strictly type safe. E.g. a
 /// drawer can't contain a drawer.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum ElementSet {
     BabelCall(Box<elements::BabelCall>),
     CenterBlock(Box<greater_elements::CenterBlock>),
@@ -333,6 +771,7 @@ pub enum ElementSet {
     DiarySexp(Box<elements::DiarySexp>),
     Drawer(Box<greater_elements::Drawer>),
     DynamicBlock(Box<greater_elements::DynamicBlock>),
+    EncryptedBlock(Box<elements::EncryptedBlock>),
     ExampleBlock(Box<elements::ExampleBlock>),
     ExportBlock(Box<elements::ExportBlock>),
     FixedWidth(Box<elements::FixedWidth>),
@@ -353,8 +792,450 @@ pub enum ElementSet {
     VerseBlock(Box<greater_elements::VerseBlock>),
 }
 
-impl From<elements::Paragraph> for ElementSet {
-    fn from(paragraph: elements::Paragraph) -> Self {
-        ElementSet::Paragraph(Box::new(paragraph))
+impl fmt::Display for ElementSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElementSet::BabelCall(e) => write!(f, "{}", e),
+            ElementSet::CenterBlock(e) => write!(f, "{}", e),
+            ElementSet::Clock(e) => write!(f, "{}", e),
+            ElementSet::Comment(e) => write!(f, "{}", e),
+            ElementSet::CommentBlock(e) => write!(f, "{}", e),
+            ElementSet::DiarySexp(e) => write!(f, "{}", e),
+            ElementSet::Drawer(e) => write!(f, "{}", e),
+            ElementSet::DynamicBlock(e) => write!(f, "{}", e),
+            ElementSet::EncryptedBlock(e) => write!(f, "{}", e),
+            ElementSet::ExampleBlock(e) => write!(f, "{}", e),
+            ElementSet::ExportBlock(e) => write!(f, "{}", e),
+            ElementSet::FixedWidth(e) => write!(f, "{}", e),
+            ElementSet::FootnoteDefinition(e) => write!(f, "{}", e),
+            ElementSet::HorizontalRule(e) => write!(f, "{}", e),
+            ElementSet::Inlinetask(e) => write!(f, "{}", e),
+            ElementSet::Keyword(e) => write!(f, "{}", e),
+            ElementSet::LatexEnvironment(e) => write!(f, "{}", e),
+            ElementSet::Paragraph(e) => write!(f, "{}", e),
+            ElementSet::PlainList(e) => write!(f, "{}", e),
+            ElementSet::Planning(e) => write!(f, "{}", e),
+            ElementSet::PropertyDrawer(e) => write!(f, "{}", e),
+            ElementSet::QuoteBlock(e) => write!(f, "{}", e),
+            ElementSet::SpecialBlock(e) => write!(f, "{}", e),
+            ElementSet::SrcBlock(e) => write!(f, "{}", e),
+            ElementSet::Table(e) => write!(f, "{}", e),
+            ElementSet::VerseBlock(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Calls a method of the element in an [`ElementSet`].
+macro_rules! element_set_dispatch {
+    ($element:expr, $e:ident => $call:expr) => {
+        match $element {
+            ElementSet::BabelCall($e) => $call,
+            ElementSet::CenterBlock($e) => $call,
+            ElementSet::Clock($e) => $call,
+            ElementSet::Comment($e) => $call,
+            ElementSet::CommentBlock($e) => $call,
+            ElementSet::DiarySexp($e) => $call,
+            ElementSet::Drawer($e) => $call,
+            ElementSet::DynamicBlock($e) => $call,
+            ElementSet::EncryptedBlock($e) => $call,
+            ElementSet::ExampleBlock($e) => $call,
+            ElementSet::ExportBlock($e) => $call,
+            ElementSet::FixedWidth($e) => $call,
+            ElementSet::FootnoteDefinition($e) => $call,
+            ElementSet::HorizontalRule($e) => $call,
+            ElementSet::Inlinetask($e) => $call,
+            ElementSet::Keyword($e) => $call,
+            ElementSet::LatexEnvironment($e) => $call,
+            ElementSet::Paragraph($e) => $call,
+            ElementSet::PlainList($e) => $call,
+            ElementSet::Planning($e) => $call,
+            ElementSet::PropertyDrawer($e) => $call,
+            ElementSet::QuoteBlock($e) => $call,
+            ElementSet::SpecialBlock($e) => $call,
+            ElementSet::SrcBlock($e) => $call,
+            ElementSet::Table($e) => $call,
+            ElementSet::VerseBlock($e) => $call,
+        }
+    };
+}
+
+impl Element for ElementSet {
+    fn kind(&self) -> ElementKind {
+        element_set_dispatch!(self, e => Element::kind(&**e))
+    }
+}
+
+impl SharedBehavior for ElementSet {
+    fn post_blank(&self) -> Option<usize> {
+        element_set_dispatch!(self, e => e.post_blank())
+    }
+
+    fn set_post_blank(&mut self, post_blank: Option<usize>) {
+        element_set_dispatch!(self, e => e.set_post_blank(post_blank))
+    }
+}
+
+/// Evaluates `$call` for the elements in an [`ElementSet`] that can have affiliated keywords
+/// and `$other` for the others.
+macro_rules! affiliated_keywords_dispatch {
+    ($element:expr, $e:ident => $call:expr, $other:expr) => {
+        match $element {
+            ElementSet::BabelCall($e) => $call,
+            ElementSet::CenterBlock($e) => $call,
+            ElementSet::Comment($e) => $call,
+            ElementSet::CommentBlock($e) => $call,
+            ElementSet::DiarySexp($e) => $call,
+            ElementSet::Drawer($e) => $call,
+            ElementSet::DynamicBlock($e) => $call,
+            ElementSet::ExampleBlock($e) => $call,
+            ElementSet::ExportBlock($e) => $call,
+            ElementSet::FixedWidth($e) => $call,
+            ElementSet::FootnoteDefinition($e) => $call,
+            ElementSet::HorizontalRule($e) => $call,
+            ElementSet::LatexEnvironment($e) => $call,
+            ElementSet::Paragraph($e) => $call,
+            ElementSet::PlainList($e) => $call,
+            ElementSet::QuoteBlock($e) => $call,
+            ElementSet::SpecialBlock($e) => $call,
+            ElementSet::SrcBlock($e) => $call,
+            ElementSet::Table($e) => $call,
+            ElementSet::VerseBlock($e) => $call,
+            ElementSet::Clock(_)
+            | ElementSet::EncryptedBlock(_)
+            | ElementSet::Inlinetask(_)
+            | ElementSet::Keyword(_)
+            | ElementSet::Planning(_)
+            | ElementSet::PropertyDrawer(_) => $other,
+        }
+    };
+}
+
+impl ElementSet {
+    /// Returns the affiliated keywords or `None` if there are none (or the element can't have
+    /// affiliated keywords).
+    pub fn affiliated_keywords(&self) -> Option<&Spanned<AffiliatedKeywords>> {
+        affiliated_keywords_dispatch!(self, e => e.affiliated_keywords.as_ref(), None)
+    }
+
+    /// Returns the name of the element (set with `#+NAME:`).
+    pub fn name(&self) -> Option<&str> {
+        self.affiliated_keywords()
+            .and_then(|keywords| keywords.value().name())
+            .map(String::as_str)
+    }
+
+    /// Returns the affiliated keywords or `None` if the element can't have affiliated keywords.
+    pub(crate) fn affiliated_keywords_mut(
+        &mut self,
+    ) -> Option<&mut Option<Spanned<AffiliatedKeywords>>> {
+        affiliated_keywords_dispatch!(self, e => Some(&mut e.affiliated_keywords), None)
+    }
+}
+
+macro_rules! impl_from_for_element_set {
+    ($($variant:ident: $ty:ty),+ $(,)*) => {
+        $(
+            impl From<$ty> for ElementSet {
+                fn from(element: $ty) -> Self {
+                    ElementSet::$variant(Box::new(element))
+                }
+            }
+        )+
+    };
+}
+
+impl_from_for_element_set! {
+    BabelCall: elements::BabelCall,
+    CenterBlock: greater_elements::CenterBlock,
+    Clock: elements::Clock,
+    Comment: elements::Comment,
+    CommentBlock: elements::CommentBlock,
+    DiarySexp: elements::DiarySexp,
+    Drawer: greater_elements::Drawer,
+    DynamicBlock: greater_elements::DynamicBlock,
+    EncryptedBlock: elements::EncryptedBlock,
+    ExampleBlock: elements::ExampleBlock,
+    ExportBlock: elements::ExportBlock,
+    FixedWidth: elements::FixedWidth,
+    FootnoteDefinition: greater_elements::FootnoteDefinition,
+    HorizontalRule: elements::HorizontalRule,
+    Inlinetask: greater_elements::Inlinetask,
+    Keyword: elements::Keyword,
+    LatexEnvironment: elements::LatexEnvironment,
+    Paragraph: elements::Paragraph,
+    PlainList: greater_elements::PlainList,
+    Planning: elements::Planning,
+    PropertyDrawer: greater_elements::PropertyDrawer,
+    QuoteBlock: greater_elements::QuoteBlock,
+    SpecialBlock: greater_elements::SpecialBlock,
+    SrcBlock: elements::SrcBlock,
+    Table: greater_elements::Table,
+    VerseBlock: greater_elements::VerseBlock,
+}
+
+/// Writes the affiliated keywords of an element (if there are any), each followed by a
+/// newline.
+fn write_affiliated_keywords(
+    f: &mut fmt::Formatter,
+    affiliated_keywords: Option<&Spanned<AffiliatedKeywords>>,
+) -> fmt::Result {
+    match affiliated_keywords {
+        Some(keywords) if !keywords.value().is_empty() => writeln!(f, "{}", keywords),
+        _ => Ok(()),
+    }
+}
+
+/// Writes a list of elements separated by empty lines.
+fn write_elements(f: &mut fmt::Formatter, elements: &[ElementSet]) -> fmt::Result {
+    write!(f, "{}", join_elements(elements, |_, _| 1))
+}
+
+/// Writes the elements of a greater block (e.g. `#+BEGIN_QUOTE`) followed by a newline.
+fn write_block_elements(f: &mut fmt::Formatter, elements: &[ElementSet]) -> fmt::Result {
+    if elements.is_empty() {
+        Ok(())
+    } else {
+        let content = join_elements(elements, |_, _| 1);
+        writeln!(f, "{}", serialize::indent(&content))
+    }
+}
+
+/// Joins elements with newlines. Only consecutive paragraphs (and consecutive plain lists) are
+/// separated by an empty line because they would be merged otherwise.
+fn join_compact_elements(elements: &[ElementSet]) -> String {
+    join_elements(elements, |previous, element| match (previous, element) {
+        (ElementSet::Paragraph(_), ElementSet::Paragraph(_))
+        | (ElementSet::PlainList(_), ElementSet::PlainList(_)) => 1,
+        _ => 0,
+    })
+}
+
+/// Joins elements with newlines followed by the blank lines after each element (see
+/// [`SharedBehavior::post_blank`]).
+///
+/// `blank_lines` returns the number of blank lines between two elements whose number of blank
+/// lines is unknown. Blank lines after the last element are only written if they are known.
+fn join_elements(
+    elements: &[ElementSet],
+    blank_lines: impl Fn(&ElementSet, &ElementSet) -> usize,
+) -> String {
+    let mut content = String::new();
+    let mut previous: Option<&ElementSet> = None;
+    for element in elements {
+        if let Some(previous) = previous {
+            let blank_lines = previous
+                .post_blank()
+                .unwrap_or_else(|| blank_lines(previous, element));
+            content.push_str(&"\n".repeat(blank_lines + 1));
+        }
+        content.push_str(&element.to_string());
+        previous = Some(element);
+    }
+    if let Some(blank_lines) = previous.and_then(SharedBehavior::post_blank) {
+        content.push_str(&"\n".repeat(blank_lines));
+    }
+    content
+}
+
+/// Writes the content of a block (e.g. `#+BEGIN_SRC`) followed by a newline if it doesn't
+/// already end with one.
+///
+/// Lines starting with `*` or `#+` (optionally after commas) are quoted with a comma so they
+/// are not parsed as headlines or keywords (e.g. `#+END_SRC`).
+///
+/// The lines are indented as configured in [`SerializeConfig::indentation`].
+///
+/// [`SerializeConfig::indentation`]: `serialize::SerializeConfig::indentation`
+fn write_block_content(f: &mut fmt::Formatter, content: &str) -> fmt::Result {
+    for line in content.split_terminator('\n') {
+        let indentation = line.len() - line.trim_start_matches(&[' ', '\t'][..]).len();
+        let rest = line[indentation..].trim_start_matches(',');
+        let line = if rest.starts_with('*') || rest.starts_with("#+") {
+            format!("{},{}", &line[..indentation], &line[indentation..])
+        } else {
+            line.to_string()
+        };
+        writeln!(f, "{}", serialize::indent(&line))?;
+    }
+    Ok(())
+}
+
+/// The escape character of org mode. It is inserted where text would otherwise be parsed as
+/// syntax.
+const ZERO_WIDTH_SPACE: char = '\u{200b}';
+
+/// Returns `true` if the line of a paragraph would start another element: a headline (`* `), a
+/// keyword (`#+`) or a comment (`# `).
+fn starts_element(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let stars = line.trim_start_matches('*');
+    let is_headline =
+        stars.len() < line.len() && (stars.is_empty() || stars.starts_with(char::is_whitespace));
+    let is_comment = trimmed == "#" || trimmed.starts_with("# ");
+    is_headline || is_comment || trimmed.starts_with("#+")
+}
+
+/// Escapes lines of a paragraph that would start another element (see [`starts_element`]).
+fn escape_paragraph(s: &str) -> String {
+    s.split('\n')
+        .map(|line| {
+            if starts_element(line) {
+                format!("{}{}", ZERO_WIDTH_SPACE, line)
+            } else {
+                line.to_string()
+            }
+        })
+        .join("\n")
+}
+
+/// Removes the escape characters [`escape_paragraph`] inserted from the raw strings of a parsed
+/// paragraph.
+///
+/// The spans of the raw strings still include the removed characters.
+pub(crate) fn unescape_paragraph(content: &mut SecondaryString<StandardSet>) {
+    for (index, object) in content.iter_mut().enumerate() {
+        if let StandardSet::RawString(s) = object {
+            *s = s
+                .split('\n')
+                .enumerate()
+                .map(|(line, text)| match text.strip_prefix(ZERO_WIDTH_SPACE) {
+                    // a raw string after another object doesn't start at the start of a line
+                    Some(rest) if (index == 0 || line > 0) && starts_element(rest) => rest,
+                    _ => text,
+                })
+                .join("\n");
+        }
+    }
+}
+
+/// Indents every line except the first one by `width` spaces.
+///
+/// Empty lines are not indented.
+fn indent_rest(s: &str, width: usize) -> String {
+    let indentation = " ".repeat(width);
+    let mut lines = s.split('\n');
+    let mut result = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        result.push('\n');
+        if !line.is_empty() {
+            result.push_str(&indentation);
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comma_quoting_in_blocks() {
+        let block = elements::ExampleBlock::new("* a\n  #+END_EXAMPLE\n,* b\nc, *d\n".to_string());
+        assert_eq!(
+            block.to_string(),
+            "#+BEGIN_EXAMPLE\n,* a\n  ,#+END_EXAMPLE\n,,* b\nc, *d\n#+END_EXAMPLE"
+        );
+    }
+
+    #[test]
+    fn secondary_string_without_separator() {
+        // the whitespace between the objects is part of the raw strings
+        let objects = crate::parsing::parse_objects("see *bold* and [[link]] text");
+        assert_eq!(objects.len(), 5);
+        let string = SecondaryString::from(objects);
+        assert_eq!(string.to_string(), "see *bold* and [[link]] text");
+    }
+
+    #[test]
+    fn escaping_in_paragraphs() {
+        let paragraph = elements::Paragraph::new(SecondaryString::with_one(
+            StandardSet::RawString("*bold* text\n** not a headline\n#+TITLE: x".to_string()),
+        ));
+        assert_eq!(
+            paragraph.to_string(),
+            "*bold* text\n\u{200b}** not a headline\n\u{200b}#+TITLE: x"
+        );
+    }
+
+    #[test]
+    fn escaping_in_cells() {
+        let cell = objects::TableCell::new(vec![objects::TableCellSetOfObjects::RawString(
+            "a | b".to_string(),
+        )]);
+        assert_eq!(cell.to_string(), "a \\vert{} b");
+    }
+
+    /// Writes a paragraph with the text and returns the content of the paragraph parsed again.
+    fn reparse_paragraph(text: &str) -> SecondaryString<StandardSet> {
+        let paragraph = elements::Paragraph::new(SecondaryString::with_one(
+            StandardSet::RawString(text.to_string()),
+        ));
+        let document = document::Document::parse(&paragraph.to_string()).unwrap();
+        match &document.preface.unwrap().content().unwrap().value()[..] {
+            [ElementSet::Paragraph(paragraph)] => paragraph.content.clone(),
+            elements => panic!("expected a paragraph, got {:?}", elements),
+        }
+    }
+
+    #[test]
+    fn escaped_headline_is_parsed_again() {
+        let expected = StandardSet::RawString("** not a headline".to_string());
+        assert_eq!(
+            reparse_paragraph("** not a headline"),
+            SecondaryString::with_one(expected)
+        );
+    }
+
+    #[test]
+    fn escaped_keyword_is_parsed_again() {
+        let expected = StandardSet::RawString("#+TITLE: x".to_string());
+        assert_eq!(
+            reparse_paragraph("#+TITLE: x"),
+            SecondaryString::with_one(expected)
+        );
+    }
+
+    #[test]
+    fn escaped_comment_is_parsed_again() {
+        let expected = StandardSet::RawString("# not a comment".to_string());
+        assert_eq!(
+            reparse_paragraph("# not a comment"),
+            SecondaryString::with_one(expected)
+        );
+    }
+
+    #[test]
+    fn escaped_vertical_bar_is_parsed_again() {
+        let cell = objects::TableCell::new(vec![objects::TableCellSetOfObjects::RawString(
+            "a | b".to_string(),
+        )]);
+        assert_eq!(crate::parsing::parse_table_cell(&cell.to_string()), cell);
+    }
+
+    #[test]
+    fn element_kinds() {
+        let document = document::Document::parse("#+TITLE: x\n- a\n* b\nc\n").unwrap();
+        let kinds: Vec<_> = document
+            .preface
+            .iter()
+            .flat_map(|preface| preface.content().map_or(&[][..], |c| c.value()))
+            .map(Element::kind)
+            .collect();
+        assert_eq!(kinds, vec![ElementKind::Keyword, ElementKind::PlainList]);
+        let content = document.headlines[0].content().unwrap().value();
+        assert_eq!(content[0].kind(), ElementKind::Section);
+        assert!(content[0].is_greater_element());
+        assert!(!content[0].can_have_affiliated_keywords());
+
+        let mut elements = document.preface.unwrap().content().unwrap().value().clone();
+        for element in &mut elements {
+            assert_eq!(
+                element.can_have_affiliated_keywords(),
+                element.affiliated_keywords_mut().is_some()
+            );
+        }
+        assert!(elements[1].is_greater_element());
+        assert_eq!(ElementKind::SrcBlock.to_string(), "src-block");
     }
 }
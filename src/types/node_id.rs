@@ -0,0 +1,241 @@
+//! Stable references to headlines.
+//!
+//! A [`NodeId`] identifies a headline in a way that survives edits to the document (and
+//! reparsing it). This allows attaching external data (e.g. UI state or caches) to headlines.
+
+use super::document::Document;
+use super::greater_elements::Headline;
+use super::*;
+use std::fmt;
+
+/// A reference to a headline that stays valid when the document is edited.
+///
+/// The id is derived from (in order of precedence):
+///
+/// 1. The `ID` property (as used by org-id).
+/// 2. The `CUSTOM_ID` property.
+/// 3. The outline path: the titles of the headline and its ancestors. To distinguish siblings
+///    with the same title the number of previous siblings with the same title is stored too.
+///
+/// Ids based on properties are always stable. Outline ids stay valid when other headlines are
+/// added, removed or edited. When the headline itself is moved to another parent it can still
+/// be found as long as its title is unique in the document (see [`NodeId::resolve`]).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeId {
+    Id(String),
+    CustomId(String),
+    Outline(Vec<OutlineStep>),
+}
+
+/// One headline in the outline path of a [`NodeId::Outline`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OutlineStep {
+    pub title: String,
+    /// The number of previous siblings with the same title.
+    pub occurrence: usize,
+}
+
+impl NodeId {
+    /// Finds the headline this id refers to.
+    ///
+    /// If an outline id can't be followed (e.g. because the headline or one of its ancestors was
+    /// moved) the headline is searched by its title in the whole document. It is only found if
+    /// the title is unique.
+    pub fn resolve<'a>(&self, document: &'a Document) -> Option<&'a Headline> {
+        match self {
+            NodeId::Id(id) => find_headline(document, |headline, _| {
+                headline.property("ID").as_deref() == Some(id.as_str())
+            }),
+            NodeId::CustomId(id) => find_headline(document, |headline, _| {
+                headline.property("CUSTOM_ID").as_deref() == Some(id.as_str())
+            }),
+            NodeId::Outline(steps) => follow_outline(document, steps).or_else(|| {
+                let title = &steps.last()?.title;
+                let mut candidates = Vec::new();
+                walk(&top_level(document), &mut Vec::new(), &mut |headline, _| {
+                    if title_of(headline) == *title {
+                        candidates.push(headline);
+                    }
+                });
+                if candidates.len() == 1 {
+                    candidates.pop()
+                } else {
+                    None
+                }
+            }),
+        }
+    }
+
+    fn for_headline(headline: &Headline, outline: &[OutlineStep]) -> NodeId {
+        if let Some(id) = headline.property("ID") {
+            NodeId::Id(id.to_string())
+        } else if let Some(id) = headline.property("CUSTOM_ID") {
+            NodeId::CustomId(id.to_string())
+        } else {
+            NodeId::Outline(outline.to_vec())
+        }
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeId::Id(id) => write!(f, "id:{}", id),
+            NodeId::CustomId(id) => write!(f, "#{}", id),
+            NodeId::Outline(steps) => {
+                let steps = steps.iter().map(|step| {
+                    if step.occurrence == 0 {
+                        step.title.clone()
+                    } else {
+                        format!("{}[{}]", step.title, step.occurrence)
+                    }
+                });
+                write!(f, "{}", steps.format("/"))
+            }
+        }
+    }
+}
+
+/// Returns the ids of all headlines in the document in document order.
+pub(crate) fn node_ids(document: &Document) -> Vec<(NodeId, &Headline)> {
+    let mut ids = Vec::new();
+    walk(
+        &top_level(document),
+        &mut Vec::new(),
+        &mut |headline, outline| {
+            ids.push((NodeId::for_headline(headline, outline), headline));
+        },
+    );
+    ids
+}
+
+fn top_level(document: &Document) -> Vec<&Headline> {
+    document.headlines.iter().collect()
+}
+
+fn title_of(headline: &Headline) -> String {
+    headline
+        .title
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+/// Calls `f` for every headline (in document order) with its outline path.
+fn walk<'a>(
+    headlines: &[&'a Headline],
+    outline: &mut Vec<OutlineStep>,
+    f: &mut impl FnMut(&'a Headline, &[OutlineStep]),
+) {
+    let mut seen: Vec<String> = Vec::new();
+    for headline in headlines {
+        let title = title_of(headline);
+        let occurrence = seen.iter().filter(|seen| **seen == title).count();
+        seen.push(title.clone());
+
+        outline.push(OutlineStep { title, occurrence });
+        f(headline, outline);
+        walk(&headline.children().collect::<Vec<_>>(), outline, f);
+        outline.pop();
+    }
+}
+
+fn find_headline(
+    document: &Document,
+    predicate: impl Fn(&Headline, &[OutlineStep]) -> bool,
+) -> Option<&Headline> {
+    let mut found = None;
+    walk(
+        &top_level(document),
+        &mut Vec::new(),
+        &mut |headline, outline| {
+            if found.is_none() && predicate(headline, outline) {
+                found = Some(headline);
+            }
+        },
+    );
+    found
+}
+
+fn follow_outline<'a>(document: &'a Document, steps: &[OutlineStep]) -> Option<&'a Headline> {
+    let mut headlines = top_level(document);
+    let mut current = None;
+    for step in steps {
+        let headline = headlines
+            .iter()
+            .filter(|headline| title_of(headline) == step.title)
+            .nth(step.occurrence)
+            .cloned()?;
+        headlines = headline.children().collect();
+        current = Some(headline);
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline(steps: &[(&str, usize)]) -> NodeId {
+        NodeId::Outline(
+            steps
+                .iter()
+                .map(|&(title, occurrence)| OutlineStep {
+                    title: title.to_string(),
+                    occurrence,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn node_ids() {
+        let text = "* a\n\
+                    ** b\n\
+                    ** b\n\
+                    * c\n\
+                    :PROPERTIES:\n\
+                    :ID: 1234\n\
+                    :END:\n\
+                    * d\n\
+                    :PROPERTIES:\n\
+                    :CUSTOM_ID: dee\n\
+                    :END:\n";
+        let document = Document::parse(text).unwrap();
+        let ids: Vec<_> = document
+            .node_ids()
+            .into_iter()
+            .map(|(id, _)| id.to_string())
+            .collect();
+        assert_eq!(ids, vec!["a", "a/b", "a/b[1]", "id:1234", "#dee"]);
+    }
+
+    #[test]
+    fn resolve_after_edits() {
+        let old = Document::parse("* a\n** b\n* c\n:PROPERTIES:\n:id: 1234\n:END:\n").unwrap();
+        let ids = old.node_ids();
+
+        // Insert a headline before all others and move "b" to the top level.
+        let text = "* new\n\
+                    * a\n\
+                    * b\n\
+                    * renamed\n\
+                    :PROPERTIES:\n\
+                    :ID: 1234\n\
+                    :END:\n";
+        let new = Document::parse(text).unwrap();
+        let resolved: Vec<_> = ids
+            .iter()
+            .map(|(id, _)| id.resolve(&new).map(title_of))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("renamed".to_string()),
+            ]
+        );
+        assert_eq!(outline(&[("a", 0), ("gone", 0)]).resolve(&new), None);
+    }
+}
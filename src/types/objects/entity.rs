@@ -22,6 +22,7 @@ use std::fmt;
 ///
 /// [`entities`]: ../../entities/index.html
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entity {
     pub name: String,
     /// True if the entity ended with `{}`.
@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// An export snippet.
 ///
 /// # Semantics
@@ -15,7 +17,14 @@
 ///
 /// `VALUE` can contain anything but the `@@` string.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExportSnippet {
     pub backend: String,
     pub value: String,
 }
+
+impl fmt::Display for ExportSnippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@@{}:{}@@", self.backend, self.value)
+    }
+}
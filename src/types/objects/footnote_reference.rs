@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A footnote reference.
 ///
@@ -21,12 +22,14 @@ use super::*;
 /// balanced in it. It can contain the standard set of objects, even other footnote references.
 /// Will be parsed as a secondary string and can contain the standard set of objects.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FootnoteReference {
     pub kind: FootnoteReferenceKind,
 }
 
 /// The kind of a [`FootnoteReference`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FootnoteReferenceKind {
     Normal {
         label: String,
@@ -39,3 +42,35 @@ pub enum FootnoteReferenceKind {
         definition: SecondaryString<StandardSet>,
     },
 }
+
+impl FootnoteReference {
+    /// Returns the label of the footnote or `None` for anonymous footnotes.
+    pub fn label(&self) -> Option<&str> {
+        match &self.kind {
+            FootnoteReferenceKind::Normal { label }
+            | FootnoteReferenceKind::Inline { label, .. } => Some(label),
+            FootnoteReferenceKind::Anonymous { .. } => None,
+        }
+    }
+
+    /// Returns the definition of inline and anonymous footnotes.
+    pub fn definition(&self) -> Option<&SecondaryString<StandardSet>> {
+        match &self.kind {
+            FootnoteReferenceKind::Inline { definition, .. }
+            | FootnoteReferenceKind::Anonymous { definition } => Some(definition),
+            FootnoteReferenceKind::Normal { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FootnoteReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FootnoteReferenceKind::Normal { label } => write!(f, "[fn:{}]", label),
+            FootnoteReferenceKind::Inline { label, definition } => {
+                write!(f, "[fn:{}:{}]", label, definition)
+            }
+            FootnoteReferenceKind::Anonymous { definition } => write!(f, "[fn::{}]", definition),
+        }
+    }
+}
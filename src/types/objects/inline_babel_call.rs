@@ -1,3 +1,6 @@
+use crate::types::elements::HeaderArgs;
+use std::fmt;
+
 /// An inline babe call.
 ///
 /// # Semantics
@@ -18,9 +21,41 @@
 ///
 /// Both `HEADER`s are optional. But then there are also no square brackets.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineBabelCall {
     pub call: String,
-    pub inside_header: String,
+    /// The header arguments used when evaluating the called block.
+    pub inside_header: HeaderArgs,
+    /// The arguments of the call (e.g. `x=1, y=2`).
     pub arguments: String,
-    pub end_header: String,
+    /// The header arguments applied to the results of the call.
+    pub end_header: HeaderArgs,
+}
+
+impl InlineBabelCall {
+    /// Returns the header arguments of the inside header, the arguments of the call (as `:var`)
+    /// and the end header (in this order).
+    pub fn header_args(&self) -> HeaderArgs {
+        let mut args = self.inside_header.clone();
+        if !self.arguments.trim().is_empty() {
+            args.args
+                .push(("var".to_string(), self.arguments.trim().to_string()));
+        }
+        args.args.extend(self.end_header.args.iter().cloned());
+        args
+    }
+}
+
+impl fmt::Display for InlineBabelCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "call_{}", self.call)?;
+        if !self.inside_header.is_empty() {
+            write!(f, "[{}]", self.inside_header)?;
+        }
+        write!(f, "({})", self.arguments)?;
+        if !self.end_header.is_empty() {
+            write!(f, "[{}]", self.end_header)?;
+        }
+        Ok(())
+    }
 }
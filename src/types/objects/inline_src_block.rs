@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// An inline src block.
 ///
 /// # Semantics
@@ -16,8 +18,19 @@
 ///
 /// `OPTIONS` is optional. But then there are also not quote brackets.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineSrcBlock {
     pub lang: String,
     pub value: String,
     pub options: String,
 }
+
+impl fmt::Display for InlineSrcBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "src_{}", self.lang)?;
+        if !self.options.is_empty() {
+            write!(f, "[{}]", self.options)?;
+        }
+        write!(f, "{{{}}}", self.value)
+    }
+}
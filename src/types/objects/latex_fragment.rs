@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// A LaTeX fragment.
 ///
 /// # Semantics
@@ -42,7 +44,14 @@
 ///
 /// [`entities`]: ../../entities/index.html
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LatexFragment {
     /// Contains the entire parsed string, except the `PRE` and `POST` parts.
     pub value: String,
 }
+
+impl fmt::Display for LatexFragment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
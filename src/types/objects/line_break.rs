@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// A line break.
 ///
 /// # Semantics
@@ -12,7 +14,17 @@
 ///
 /// `SPACE` is zero or more whitespace characters followed by the end of line or end of
 /// document.
+///
+/// The line break includes the end of the line. So the newline is also written when
+/// displaying it.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineBreak {
     pub spaces: u64,
 }
+
+impl fmt::Display for LineBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\\\\{}", " ".repeat(self.spaces as usize))
+    }
+}
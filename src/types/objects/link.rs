@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A link.
 ///
@@ -51,12 +52,14 @@ use super::*;
 ///
 /// Whitespace and newlines in the link are replaced with a single space.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Link {
     pub link: LinkFormat,
 }
 
 /// The format with the actual link data of a [`Link`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LinkFormat {
     Radio(String),
     Angle(String),
@@ -71,41 +74,40 @@ pub enum LinkFormat {
     ),
 }
 
-/// The set of objects a [`Link`] can contain.
-///
-/// Linkss can't contain [`FootnoteReference`], [`LineBreak`], [`Target`], [`Target`] and
-/// [`Timestamp`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum LinkDescriptionSetOfObjects {
-    RawString(String),
-    Entity(objects::Entity),
-    ExportSnippet(objects::ExportSnippet),
-    InlineBabelCall(objects::InlineBabelCall),
-    InlineSrcBlock(objects::InlineSrcBlock),
-    LatexFragment(objects::LatexFragment),
-    /// Can contain links that are not plain or angle links. This will probably be ignored.
-    Link(objects::Link),
-    Macro(objects::Macro),
-    StatisticsCookie(objects::StatisticsCookie),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    TextMarkup(objects::TextMarkup),
-}
-
-impl AsRawString for LinkDescriptionSetOfObjects {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let LinkDescriptionSetOfObjects::RawString(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+define_object_set! {
+    /// The set of objects a [`Link`] can contain.
+    ///
+    /// Linkss can't contain [`FootnoteReference`], [`LineBreak`], [`Target`], [`Target`] and
+    /// [`Timestamp`].
+    LinkDescriptionSetOfObjects from StandardSet {
+        Entity,
+        ExportSnippet,
+        InlineBabelCall,
+        InlineSrcBlock,
+        LatexFragment,
+        /// Can contain links that are not plain or angle links. This will probably be ignored.
+        Link,
+        Macro,
+        StatisticsCookie,
+        Subscript,
+        Superscript,
+        TextMarkup,
     }
 }
 
 /// The kind and data of a bracket link in [`LinkFormat`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum LinkPath {
     File(String),
+    /// A link with a protocol other than `file`, `id` (e.g. `https://orgmode.org`).
+    ///
+    /// `path` does not contain the protocol and the colon.
+    Protocol {
+        protocol: String,
+        path: String,
+    },
     Id(String),
     CustomId(String),
     CodeRef(String),
@@ -114,6 +116,7 @@ pub enum LinkPath {
 
 /// The search option of bracket [`LinkFormat`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SearchOption {
     /// Jump to line.
     Line(u64),
@@ -126,3 +129,48 @@ pub enum SearchOption {
     /// Do a regular expression search.
     Regex(String),
 }
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.link {
+            LinkFormat::Radio(s) | LinkFormat::Plain(s) => write!(f, "{}", s),
+            LinkFormat::Angle(s) => write!(f, "<{}>", s),
+            LinkFormat::Bracket(path, search_option, description) => {
+                write!(f, "[[{}", path)?;
+                if let Some(search_option) = search_option {
+                    write!(f, "::{}", search_option)?;
+                }
+                write!(f, "]")?;
+                if let Some(description) = description {
+                    write!(f, "[{}]", description)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl fmt::Display for LinkPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkPath::File(path) => write!(f, "file:{}", path),
+            LinkPath::Protocol { protocol, path } => write!(f, "{}:{}", protocol, path),
+            LinkPath::Id(id) => write!(f, "id:{}", id),
+            LinkPath::CustomId(id) => write!(f, "#{}", id),
+            LinkPath::CodeRef(coderef) => write!(f, "({})", coderef),
+            LinkPath::Fuzzy(fuzzy) => write!(f, "{}", fuzzy),
+        }
+    }
+}
+
+impl fmt::Display for SearchOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchOption::Line(line) => write!(f, "{}", line),
+            SearchOption::Target(target) => write!(f, "{}", target),
+            SearchOption::Headlines(headline) => write!(f, "*{}", headline),
+            SearchOption::CustomId(id) => write!(f, "#{}", id),
+            SearchOption::Regex(regex) => write!(f, "/{}/", regex),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use itertools::Itertools;
+use std::fmt;
+
 /// A macro.
 ///
 /// # Semantics
@@ -45,7 +48,19 @@
 ///
 /// Multiple whitespace and newline characters in `ARGUMENTS` are replaced by a single space.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Macro {
     pub name: String,
     pub arguments: Vec<String>,
 }
+
+impl fmt::Display for Macro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{{{{{}", self.name)?;
+        if !self.arguments.is_empty() {
+            let arguments = self.arguments.iter().map(|arg| arg.replace(',', "\\,"));
+            write!(f, "({})", arguments.format(","))?;
+        }
+        write!(f, "}}}}}}")
+    }
+}
@@ -37,7 +37,7 @@ pub use self::table_cell::{TableCell, TableCellSetOfObjects};
 pub use self::target::Target;
 pub use self::text_markup::{TextMarkup, TextMarkupKind};
 pub use self::timestamp::{
-    Date, RepeatStrategy, Repeater, Time, TimePeriod, TimeUnit, Timestamp, TimestampData,
-    TimestampDataWithTime, TimestampKind, TimestampRange, TimestampStatus, Warning,
+    Date, ParseTimestampError, RepeatStrategy, Repeater, Time, TimePeriod, TimeUnit, Timestamp,
+    TimestampData, TimestampDataWithTime, TimestampKind, TimestampRange, TimestampStatus, Warning,
     WarningStrategy,
 };
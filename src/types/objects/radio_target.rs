@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A target that is automatically linked to.
 ///
@@ -16,31 +17,30 @@ use super::*;
 /// ```
 ///
 /// `TARGET` can contain any character except `<`, `>` and newline. It can't start or end with
-/// a whitespace character. It will be parsed as a [`SecondaryString`].
+/// a whitespace character. It will be parsed as a [`SecondaryString`] of
+/// [`RadioTargetSetOfObjects`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RadioTarget {
-    pub target: SecondaryString<StandardSet>,
+    pub target: SecondaryString<RadioTargetSetOfObjects>,
 }
 
-/// The set of objects a [`RadioTarget`] can contain.
-///
-/// Radio targets content is limited to easy to parse objects. Mainly just markup and raw strings.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum RadioTargetSetOfObjects {
-    RawString(String),
-    Entity(objects::Entity),
-    LatexFragment(objects::LatexFragment),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    TextMarkup(objects::TextMarkup),
+define_object_set! {
+    /// The set of objects a [`RadioTarget`] can contain.
+    ///
+    /// Radio targets content is limited to easy to parse objects. Mainly just markup and raw
+    /// strings.
+    RadioTargetSetOfObjects from StandardSet {
+        Entity,
+        LatexFragment,
+        Subscript,
+        Superscript,
+        TextMarkup,
+    }
 }
 
-impl AsRawString for RadioTargetSetOfObjects {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let RadioTargetSetOfObjects::RawString(s) = self {
-            Some(s)
-        } else {
-            None
-        }
+impl fmt::Display for RadioTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<<<{}>>>", self.target)
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// A statistics cookie.
 ///
 /// # Semantics
@@ -19,13 +21,28 @@
 /// [`Item`]: `greater_elements::Item`
 /// [`PlainList`]: `greater_elements::PlainList`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatisticsCookie {
     pub cookie: CookieKind,
 }
 
 /// This is the kind and data of a [`StatisticsCookie`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CookieKind {
     Percent(Option<u32>),
     Number(Option<u32>, Option<u32>),
 }
+
+impl fmt::Display for StatisticsCookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn number(n: &Option<u32>) -> String {
+            n.map(|n| n.to_string()).unwrap_or_default()
+        }
+
+        match &self.cookie {
+            CookieKind::Percent(percent) => write!(f, "[{}%]", number(percent)),
+            CookieKind::Number(done, total) => write!(f, "[{}/{}]", number(done), number(total)),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A subscript.
 ///
@@ -35,8 +36,22 @@ use super::*;
 /// TODO this is recursive object. figure out how to handle recursive objects because some can
 /// only contain specific objects and therefore other recursive objects in them may contain
 /// less objects than they can usually contain
+///
+/// `CHAR` is not part of the subscript. It is the last character of the preceding raw string or
+/// object.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Subscript {
     pub used_brackets: bool,
     pub content: SecondaryString<StandardSet>,
 }
+
+impl fmt::Display for Subscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.used_brackets {
+            write!(f, "_{{{}}}", self.content)
+        } else {
+            write!(f, "_{}", self.content)
+        }
+    }
+}
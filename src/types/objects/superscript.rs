@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A superscript.
 ///
@@ -14,7 +15,18 @@ use super::*;
 ///
 /// See [`Subscript`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Superscript {
     pub used_brackets: bool,
     pub content: SecondaryString<StandardSet>,
 }
+
+impl fmt::Display for Superscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.used_brackets {
+            write!(f, "^{{{}}}", self.content)
+        } else {
+            write!(f, "^{}", self.content)
+        }
+    }
+}
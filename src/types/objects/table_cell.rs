@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A table cell in a [`greater_elements::TableRow`].
 ///
@@ -12,49 +13,64 @@ use super::*;
 /// CONTENTS SPACES |
 /// ```
 ///
-/// `CONTENTS` can contain any character except a vertical bar.
+/// `CONTENTS` can contain any character except a vertical bar. Vertical bars in the text are
+/// written as the `\vert{}` entity (which is parsed as a vertical bar again).
 ///
 /// `SPACES` contains any number (including zero) of soace and tab characters. This is usually
 /// used to align the table properly.
 ///
 /// The final bar my be replaced with a newline character for the last cell in the row.
 ///
-/// TODO recusrive object. can contain: export snippet, footnote reference, latex fragment,
-/// entity, link, macro, radio target, sub/superscript, target, text markup, timestamp
+/// Recursive object. Can contain the objects in [`TableCellSetOfObjects`]. Use
+/// [`parse_table_cell`] to parse the content of a cell.
+///
+/// [`parse_table_cell`]: `crate::parsing::parse_table_cell`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableCell {
-    pub content: Spanned<TableCellSetOfObjects>,
+    pub content: Spanned<Vec<TableCellSetOfObjects>>,
+}
+
+impl TableCell {
+    pub fn new(content: Vec<TableCellSetOfObjects>) -> Self {
+        TableCell {
+            content: Spanned::new(content),
+        }
+    }
 }
 
 impl Object for TableCell {}
 
-/// The set of objects [`TableCell`] can contain.
-///
-/// Table cells can't contain [`InlineBabelCall`], [`InlineSrcBlock`] because formulas are
-/// possible. Also they can't contain [`LineBreak`] and [`StatisticsCookie`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum TableCellSetOfObjects {
-    RawString(String),
-    Entity(objects::Entity),
-    ExportSnippet(objects::ExportSnippet),
-    FootnoteReference(objects::FootnoteReference),
-    LatexFragment(objects::LatexFragment),
-    Link(objects::Link),
-    Macro(objects::Macro),
-    RadioTarget(objects::RadioTarget),
-    Subscript(objects::Subscript),
-    Superscript(objects::Superscript),
-    Target(objects::Target),
-    TextMarkup(objects::TextMarkup),
-    Timestamp(objects::Timestamp),
+define_object_set! {
+    /// The set of objects [`TableCell`] can contain.
+    ///
+    /// Table cells can't contain [`InlineBabelCall`], [`InlineSrcBlock`] because formulas are
+    /// possible. Also they can't contain [`LineBreak`] and [`StatisticsCookie`].
+    TableCellSetOfObjects from StandardSet {
+        Entity,
+        ExportSnippet,
+        FootnoteReference,
+        LatexFragment,
+        Link,
+        Macro,
+        RadioTarget,
+        Subscript,
+        Superscript,
+        Target,
+        TextMarkup,
+        Timestamp,
+    }
 }
 
-impl AsRawString for TableCellSetOfObjects {
-    fn as_raw_string(&self) -> Option<&str> {
-        if let TableCellSetOfObjects::RawString(s) = self {
-            Some(s)
-        } else {
-            None
+impl fmt::Display for TableCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for object in self.content.value() {
+            match object {
+                // a vertical bar would end the cell
+                TableCellSetOfObjects::RawString(s) => write!(f, "{}", s.replace('|', "\\vert{}"))?,
+                object => write!(f, "{}", object)?,
+            }
         }
+        Ok(())
     }
 }
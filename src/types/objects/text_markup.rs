@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt;
 
 /// A text formatter.
 ///
@@ -36,6 +37,7 @@ use super::*;
 /// standard set of objects when the markup is bold, italic, strike through or udnerline. The
 /// content of verbatim and code is not parsed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextMarkup {
     pub kind: TextMarkupKind,
 }
@@ -44,6 +46,7 @@ pub struct TextMarkup {
 ///
 /// Only code and verbatim can't contain other objects.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextMarkupKind {
     // TODO maybe make these actual different types instead of an enum
     Bold(SecondaryString<StandardSet>),
@@ -53,3 +56,16 @@ pub enum TextMarkupKind {
     Code(String),
     Verbatim(String),
 }
+
+impl fmt::Display for TextMarkup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TextMarkupKind::Bold(content) => write!(f, "*{}*", content),
+            TextMarkupKind::Italic(content) => write!(f, "/{}/", content),
+            TextMarkupKind::Underline(content) => write!(f, "_{}_", content),
+            TextMarkupKind::StrikeThrough(content) => write!(f, "+{}+", content),
+            TextMarkupKind::Code(content) => write!(f, "~{}~", content),
+            TextMarkupKind::Verbatim(content) => write!(f, "={}=", content),
+        }
+    }
+}
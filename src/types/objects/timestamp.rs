@@ -1,4 +1,9 @@
-use chrono::{NaiveDate, NaiveTime};
+use crate::diary::DiaryExpr;
+use crate::types::serialize;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 /// A timestamp.
 ///
@@ -27,8 +32,8 @@ use chrono::{NaiveDate, NaiveTime};
 /// (`0`-`9`). `DAYNAME` is optional and can contain any non-whitespace character except `+`,
 /// `-`, `]`, `>`, digits and newlines. Usually it is the three letter name of the weekday.
 ///
-/// `TIME` follows the pattern `HH:MM`. Where `H` and `M` are digits. The first `H` can be
-/// omitted.
+/// `TIME` follows the pattern `HH:MM` or `HH:MM:SS`. Where `H`, `M` and `S` are digits. The
+/// first `H` can be omitted.
 ///
 /// `REPEATERORDELAY` follows the pattern `MARK VALUE UNIT` where `MARK` is one of `+`, `++`,
 /// `.+`, `-` or `--` for the repeat or delay strategy. `VALUE` is a (positive) number. `UNIT`
@@ -37,11 +42,51 @@ use chrono::{NaiveDate, NaiveTime};
 /// There can be two `REPEATERORYEAR` in the timestamp. One as a repeater and on as a warning
 /// delay.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Timestamp {
     pub kind: TimestampKind,
 }
 
 impl Timestamp {
+    /// Creates a timestamp for a single date and optional time.
+    pub fn new(status: TimestampStatus, date: NaiveDate, time: Option<NaiveTime>) -> Self {
+        Timestamp {
+            kind: TimestampKind::Single(
+                status,
+                TimestampData {
+                    date: date.into(),
+                    time: time.map(Time::from),
+                    repeater: None,
+                    warning: None,
+                },
+            ),
+        }
+    }
+
+    /// Returns the start of the timestamp as date and time.
+    ///
+    /// Timestamps without time start at midnight. Returns `None` for diary sexps.
+    pub fn start_date_time(&self) -> Option<NaiveDateTime> {
+        self.timestamp_start().map(|(date, time)| {
+            date.0.and_time(
+                time.map(|time| time.time)
+                    .unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0)),
+            )
+        })
+    }
+
+    /// Returns the end of the timestamp (the start for single timestamps).
+    ///
+    /// Like [`Timestamp::start_date_time`] a missing time is midnight.
+    pub fn end_date_time(&self) -> Option<NaiveDateTime> {
+        self.timestamp_end().map(|(date, time)| {
+            date.0.and_time(
+                time.map(|time| time.time)
+                    .unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0)),
+            )
+        })
+    }
+
     pub fn timestamp_start(&self) -> Option<(&Date, Option<&Time>)> {
         use self::TimestampKind::*;
         use self::TimestampRange::*;
@@ -50,10 +95,10 @@ impl Timestamp {
             DiarySexp(_) => None,
             Single(_, TimestampData { date, time, .. })
             | Range(_, DateRange(TimestampData { date, time, .. }, ..)) => {
-                Some((&date, time.as_ref()))
+                Some((date, time.as_ref()))
             }
             Range(_, TimeRange(TimestampDataWithTime { date, time, .. }, ..)) => {
-                Some((&date, Some(&time)))
+                Some((date, Some(time)))
             }
         }
     }
@@ -63,13 +108,11 @@ impl Timestamp {
 
         match &self.kind {
             DiarySexp(_) => None,
-            Single(_, TimestampData { date, time, .. }) => Some((&date, time.as_ref())),
+            Single(_, TimestampData { date, time, .. }) => Some((date, time.as_ref())),
             Range(_, TimeRange(TimestampDataWithTime { date, .. }, time)) => {
-                Some((&date, Some(&time)))
-            }
-            Range(_, DateRange(_, TimestampData { date, time, .. })) => {
-                Some((&date, time.as_ref()))
+                Some((date, Some(time)))
             }
+            Range(_, DateRange(_, TimestampData { date, time, .. })) => Some((date, time.as_ref())),
         }
     }
     pub fn repeater(&self) -> Option<&Repeater> {
@@ -94,10 +137,128 @@ impl Timestamp {
             | Range(_, DateRange(TimestampData { warning, .. }, _)) => warning.as_ref(),
         }
     }
+
+    /// Returns the first start of the timestamp or of one of its repetitions after the given
+    /// date and time.
+    ///
+    /// Returns `None` if there is none (e.g. the timestamp has no repeater and starts before).
+    pub fn next_occurrence(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut next = self.start_date_time()?;
+        while next <= after {
+            next = self.repeat_period()?.add_to(next)?;
+        }
+        Some(next)
+    }
+
+    /// Returns the start of the timestamp and of all its repetitions on the dates of the range.
+    ///
+    /// Diary sexps occur at midnight of the dates they match (see [`DiaryExpr`]). Unsupported
+    /// sexps have no occurrences.
+    ///
+    /// [`DiaryExpr`]: `crate::diary::DiaryExpr`
+    pub fn occurrences_between(&self, range: RangeInclusive<NaiveDate>) -> Vec<NaiveDateTime> {
+        if let TimestampKind::DiarySexp(sexp) = &self.kind {
+            return sexp
+                .parse::<DiaryExpr>()
+                .map(|expr| expr.occurrences_between(range))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|date| date.and_hms(0, 0, 0))
+                .collect();
+        }
+        let mut occurrences = Vec::new();
+        let mut next = self.start_date_time();
+        while let Some(date_time) = next {
+            if date_time.date() > *range.end() {
+                break;
+            }
+            if range.contains(&date_time.date()) {
+                occurrences.push(date_time);
+            }
+            next = self
+                .repeat_period()
+                .and_then(|period| period.add_to(date_time));
+        }
+        occurrences
+    }
+
+    /// Moves the timestamp to its next repetition like org mode does when a repeated task is
+    /// marked as done at the time `now`:
+    ///
+    /// - `+` adds the period once
+    /// - `++` adds the period until the timestamp is after `now` (but at least once)
+    /// - `.+` adds the period to today (keeping the time of the timestamp) or to `now` for hours
+    ///
+    /// The end of ranges moves by the same amount. Returns `false` (and doesn't change the
+    /// timestamp) if there is no repeater.
+    pub fn apply_repeat(&mut self, now: NaiveDateTime) -> bool {
+        let (repeater, start) = match (self.repeater(), self.start_date_time()) {
+            (Some(repeater), Some(start)) if repeater.period.value > 0 => (repeater.clone(), start),
+            _ => return false,
+        };
+        let period = &repeater.period;
+        let next = match repeater.strategy {
+            RepeatStrategy::Cumulative => period.add_to(start),
+            RepeatStrategy::CatchUp => {
+                let mut next = period.add_to(start);
+                while let Some(date_time) = next.filter(|&date_time| date_time <= now) {
+                    next = period.add_to(date_time);
+                }
+                next
+            }
+            RepeatStrategy::Restart if period.unit == TimeUnit::Hour => period.add_to(now),
+            RepeatStrategy::Restart => period.add_to(now.date().and_time(start.time())),
+        };
+        match next {
+            Some(next) => {
+                self.shift(next - start);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn repeat_period(&self) -> Option<&TimePeriod> {
+        self.repeater()
+            .map(|repeater| &repeater.period)
+            .filter(|period| period.value > 0)
+    }
+
+    /// Moves all dates and times of the timestamp. Timestamps without time only move by whole
+    /// days.
+    fn shift(&mut self, by: Duration) {
+        use self::TimestampKind::*;
+        use self::TimestampRange::*;
+
+        let shift = |date: &mut Date, time: Option<&mut Time>| {
+            let midnight = NaiveTime::from_hms(0, 0, 0);
+            let date_time = date
+                .0
+                .and_time(time.as_ref().map_or(midnight, |time| time.time))
+                + by;
+            date.0 = date_time.date();
+            if let Some(time) = time {
+                time.time = date_time.time();
+            }
+        };
+        match &mut self.kind {
+            DiarySexp(_) => {}
+            Single(_, TimestampData { date, time, .. }) => shift(date, time.as_mut()),
+            Range(_, DateRange(start, end)) => {
+                shift(&mut start.date, start.time.as_mut());
+                shift(&mut end.date, end.time.as_mut());
+            }
+            Range(_, TimeRange(TimestampDataWithTime { date, time, .. }, end)) => {
+                shift(date, Some(time));
+                end.time = end.time.overflowing_add_signed(by).0;
+            }
+        }
+    }
 }
 
 /// The kind and date for a [`Timestamp`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimestampKind {
     DiarySexp(String),
     Single(TimestampStatus, TimestampData),
@@ -106,6 +267,7 @@ pub enum TimestampKind {
 
 /// The status of a [`Timestamp`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimestampStatus {
     /// Timestamp in angle brackets (`<...>`).
     Active,
@@ -115,6 +277,7 @@ pub enum TimestampStatus {
 
 /// The data for a [`TimestampKind`] with optional [`Time`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimestampData {
     pub date: Date,
     pub time: Option<Time>,
@@ -128,16 +291,95 @@ pub struct TimestampData {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Date(NaiveDate);
 
-/// A time.
+/// A time (`HH:MM` or `HH:MM:SS`).
 ///
-/// This is a wrapper around [`chrono::NaiveTime`].
+/// This is a wrapper around [`chrono::NaiveTime`]. The seconds are written if they were parsed
+/// or are not zero.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Time(NaiveTime);
+pub struct Time {
+    time: NaiveTime,
+    seconds: bool,
+}
+
+impl Date {
+    pub fn naive_date(&self) -> NaiveDate {
+        self.0
+    }
+}
+
+impl From<NaiveDate> for Date {
+    fn from(date: NaiveDate) -> Self {
+        Date(date)
+    }
+}
+
+impl Time {
+    /// Creates a time that is written with seconds (even if they are zero).
+    pub fn with_seconds(time: NaiveTime) -> Self {
+        Time {
+            time,
+            seconds: true,
+        }
+    }
+
+    pub fn naive_time(&self) -> NaiveTime {
+        self.time
+    }
+
+    /// Returns `true` if the time is written with seconds.
+    pub fn has_seconds(&self) -> bool {
+        self.seconds
+    }
+}
+
+impl From<NaiveTime> for Time {
+    fn from(time: NaiveTime) -> Self {
+        Time {
+            time,
+            seconds: time.second() != 0,
+        }
+    }
+}
+
+/// Dates are (de)serialized as `YYYY-MM-DD` strings.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Date {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0.format("%Y-%m-%d"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+            .map(Date)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Times are (de)serialized as `HH:MM` or `HH:MM:SS` strings (like they are written).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Time {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Time {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_time(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid time: {:?}", s)))
+    }
+}
 
 /// The repeater of a timestamp.
 ///
 /// See [`TimestampData`] and [`TimestampDataWithTime`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Repeater {
     pub period: TimePeriod,
     pub strategy: RepeatStrategy,
@@ -147,6 +389,7 @@ pub struct Repeater {
 ///
 /// See [`TimestampData`] and [`TimestampDataWithTime`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Warning {
     pub delay: TimePeriod,
     pub strategy: WarningStrategy,
@@ -154,13 +397,38 @@ pub struct Warning {
 
 /// The time period (with unit) of a [`Repeater`] or [`Warning`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimePeriod {
     pub value: u32,
     pub unit: TimeUnit,
 }
 
+impl TimePeriod {
+    /// Adds the period to the date and time. Adding months keeps the day of the month unless
+    /// the month is shorter (e.g. a month after January 31 is February 28 or 29).
+    pub fn add_to(&self, date_time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let value = i64::from(self.value);
+        let months = match self.unit {
+            TimeUnit::Hour => return Some(date_time + Duration::hours(value)),
+            TimeUnit::Day => return Some(date_time + Duration::days(value)),
+            TimeUnit::Week => return Some(date_time + Duration::weeks(value)),
+            TimeUnit::Month => value,
+            TimeUnit::Year => value * 12,
+        };
+        let date = date_time.date();
+        let month0 = i64::from(date.month0()) + months;
+        let year = date.year() + (month0 / 12) as i32;
+        let month = (month0 % 12) as u32 + 1;
+        let date = (1..=date.day())
+            .rev()
+            .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))?;
+        Some(date.and_time(date_time.time()))
+    }
+}
+
 /// The strategy of a [`Repeater`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RepeatStrategy {
     /// Add the repeat duration to the task date once.
     Cumulative,
@@ -173,6 +441,7 @@ pub enum RepeatStrategy {
 
 /// The strategy of a [`Warning`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WarningStrategy {
     /// Warns for all (repeated) date. Represented as `-` in the org file.
     All,
@@ -182,6 +451,7 @@ pub enum WarningStrategy {
 
 /// The unit of a [`TimePeriod`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimeUnit {
     Year,
     Month,
@@ -194,6 +464,7 @@ pub enum TimeUnit {
 ///
 /// See [`TimestampKind`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimestampRange {
     /// `<DATE TIME-TIME REPEATER-OR-DELAY>` or
     /// `[DATE TIME-TIME REPEATER-OR-DELAY]`
@@ -207,6 +478,7 @@ pub enum TimestampRange {
 ///
 /// See [`TimestampRange`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimestampDataWithTime {
     pub date: Date,
     pub time: Time,
@@ -214,7 +486,413 @@ pub struct TimestampDataWithTime {
     pub warning: Option<Warning>,
 }
 
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TimestampKind::DiarySexp(sexp) => write!(f, "<%%({})>", sexp),
+            TimestampKind::Single(status, data) => {
+                write!(f, "{}{}{}", status.open(), data, status.close())
+            }
+            TimestampKind::Range(status, TimestampRange::TimeRange(data, end)) => {
+                write!(f, "{}{} {}-{}", status.open(), data.date, data.time, end)?;
+                write_repeater_and_warning(f, &data.repeater, &data.warning)?;
+                write!(f, "{}", status.close())
+            }
+            TimestampKind::Range(status, TimestampRange::DateRange(start, end)) => write!(
+                f,
+                "{open}{}{close}--{open}{}{close}",
+                start,
+                end,
+                open = status.open(),
+                close = status.close()
+            ),
+        }
+    }
+}
+
+impl TimestampStatus {
+    fn open(&self) -> char {
+        match self {
+            TimestampStatus::Active => '<',
+            TimestampStatus::Inactive => '[',
+        }
+    }
+    fn close(&self) -> char {
+        match self {
+            TimestampStatus::Active => '>',
+            TimestampStatus::Inactive => ']',
+        }
+    }
+}
+
+fn write_repeater_and_warning(
+    f: &mut fmt::Formatter<'_>,
+    repeater: &Option<Repeater>,
+    warning: &Option<Warning>,
+) -> fmt::Result {
+    if let Some(repeater) = repeater {
+        write!(f, " {}", repeater)?;
+    }
+    if let Some(warning) = warning {
+        write!(f, " {}", warning)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for TimestampData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date)?;
+        if let Some(time) = &self.time {
+            write!(f, " {}", time)?;
+        }
+        write_repeater_and_warning(f, &self.repeater, &self.warning)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if serialize::with_config(|config| config.weekday_names) {
+            write!(f, "{}", self.0.format("%Y-%m-%d %a"))
+        } else {
+            write!(f, "{}", self.0.format("%Y-%m-%d"))
+        }
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.seconds {
+            write!(f, "{}", self.time.format("%H:%M:%S"))
+        } else {
+            write!(f, "{}", self.time.format("%H:%M"))
+        }
+    }
+}
+
+impl fmt::Display for Repeater {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.strategy {
+            RepeatStrategy::Cumulative => "+",
+            RepeatStrategy::CatchUp => "++",
+            RepeatStrategy::Restart => ".+",
+        };
+        write!(f, "{}{}", mark, self.period)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = match self.strategy {
+            WarningStrategy::All => "-",
+            WarningStrategy::First => "--",
+        };
+        write!(f, "{}{}", mark, self.delay)
+    }
+}
+
+impl fmt::Display for TimePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            TimeUnit::Year => 'y',
+            TimeUnit::Month => 'm',
+            TimeUnit::Week => 'w',
+            TimeUnit::Day => 'd',
+            TimeUnit::Hour => 'h',
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+/// The error returned when parsing a [`Timestamp`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseTimestampError {
+    input: String,
+}
+
+impl fmt::Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid timestamp: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseTimestampError {}
+
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    /// Parses a timestamp (see [`Timestamp`] for the syntax).
+    ///
+    /// Surrounding whitespace is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseTimestampError {
+            input: s.to_string(),
+        };
+        let trimmed = s.trim();
+
+        if trimmed.starts_with("<%%(") && trimmed.ends_with(")>") {
+            let sexp = &trimmed[4..trimmed.len() - 2];
+            return Ok(Timestamp {
+                kind: TimestampKind::DiarySexp(sexp.to_string()),
+            });
+        }
+
+        let status = if trimmed.starts_with('<') {
+            TimestampStatus::Active
+        } else if trimmed.starts_with('[') {
+            TimestampStatus::Inactive
+        } else {
+            return Err(error());
+        };
+        if !trimmed.ends_with(status.close()) || trimmed.len() < 2 {
+            return Err(error());
+        }
+        let inner = &trimmed[1..trimmed.len() - 1];
+
+        let separator = format!("{}--{}", status.close(), status.open());
+        let kind = if let Some(index) = inner.find(&separator) {
+            let (start, start_end) = parse_timestamp_inner(&inner[..index]).ok_or_else(error)?;
+            let (end, end_end) =
+                parse_timestamp_inner(&inner[index + separator.len()..]).ok_or_else(error)?;
+            if start_end.is_some() || end_end.is_some() {
+                return Err(error());
+            }
+            TimestampKind::Range(status, TimestampRange::DateRange(start, end))
+        } else {
+            match parse_timestamp_inner(inner).ok_or_else(error)? {
+                (data, None) => TimestampKind::Single(status, data),
+                (data, Some(end)) => TimestampKind::Range(
+                    status,
+                    TimestampRange::TimeRange(
+                        TimestampDataWithTime {
+                            date: data.date,
+                            // a time range always has a start time
+                            time: data.time.ok_or_else(error)?,
+                            repeater: data.repeater,
+                            warning: data.warning,
+                        },
+                        end,
+                    ),
+                ),
+            }
+        };
+        Ok(Timestamp { kind })
+    }
+}
+
+/// Parses `DATE TIME REPEATER-OR-DELAY` where `TIME` can also be a time range (the end of the
+/// time range is returned separately).
+fn parse_timestamp_inner(s: &str) -> Option<(TimestampData, Option<Time>)> {
+    let mut parts = s.split_whitespace();
+    let date = NaiveDate::parse_from_str(parts.next()?, "%Y-%m-%d").ok()?;
+    let mut data = TimestampData {
+        date: Date(date),
+        time: None,
+        repeater: None,
+        warning: None,
+    };
+    let mut end = None;
+
+    for (i, part) in parts.enumerate() {
+        if i == 0 && part.chars().all(char::is_alphabetic) {
+            // the day name is redundant and is regenerated when printing
+        } else if part.starts_with(|c: char| c.is_ascii_digit()) && data.time.is_none() {
+            let mut times = part.splitn(2, '-');
+            data.time = Some(parse_time(times.next()?)?);
+            if let Some(time) = times.next() {
+                end = Some(parse_time(time)?);
+            }
+        } else if let Some(repeater) = parse_repeater(part) {
+            data.repeater = Some(repeater);
+        } else {
+            let warning = parse_warning(part)?;
+            data.warning = Some(warning);
+        }
+    }
+    Some((data, end))
+}
+
+/// Parses `HH:MM` or `HH:MM:SS`.
+fn parse_time(s: &str) -> Option<Time> {
+    if s.matches(':').count() == 2 {
+        NaiveTime::parse_from_str(s, "%H:%M:%S")
+            .ok()
+            .map(Time::with_seconds)
+    } else {
+        NaiveTime::parse_from_str(s, "%H:%M").ok().map(Time::from)
+    }
+}
+
+fn parse_repeater(s: &str) -> Option<Repeater> {
+    let (strategy, period) = if let Some(period) = s.strip_prefix("++") {
+        (RepeatStrategy::CatchUp, period)
+    } else if let Some(period) = s.strip_prefix(".+") {
+        (RepeatStrategy::Restart, period)
+    } else {
+        (RepeatStrategy::Cumulative, s.strip_prefix('+')?)
+    };
+    Some(Repeater {
+        period: period.parse().ok()?,
+        strategy,
+    })
+}
+
+fn parse_warning(s: &str) -> Option<Warning> {
+    let (strategy, delay) = if let Some(delay) = s.strip_prefix("--") {
+        (WarningStrategy::First, delay)
+    } else {
+        (WarningStrategy::All, s.strip_prefix('-')?)
+    };
+    Some(Warning {
+        delay: delay.parse().ok()?,
+        strategy,
+    })
+}
+
+impl FromStr for TimePeriod {
+    type Err = ();
+
+    /// Parses a period like `3d` (see [`TimeUnit`] for the units).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s.chars().last() {
+            Some('y') => TimeUnit::Year,
+            Some('m') => TimeUnit::Month,
+            Some('w') => TimeUnit::Week,
+            Some('d') => TimeUnit::Day,
+            Some('h') => TimeUnit::Hour,
+            _ => return Err(()),
+        };
+        let value = s[..s.len() - 1].parse().map_err(|_| ())?;
+        Ok(TimePeriod { value, unit })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    //use super::*;
+    use super::*;
+
+    fn roundtrip(s: &str) -> String {
+        s.parse::<Timestamp>().unwrap().to_string()
+    }
+
+    #[test]
+    fn parse_single() {
+        assert_eq!(
+            "<2018-10-12 Fri>".parse(),
+            Ok(Timestamp::new(
+                TimestampStatus::Active,
+                NaiveDate::from_ymd(2018, 10, 12),
+                None
+            ))
+        );
+        assert_eq!(
+            "[2018-10-12 Fri 8:30]".parse(),
+            Ok(Timestamp::new(
+                TimestampStatus::Inactive,
+                NaiveDate::from_ymd(2018, 10, 12),
+                Some(NaiveTime::from_hms(8, 30, 0))
+            ))
+        );
+    }
+
+    fn date_time(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2018, 10, day).and_hms(hour, 0, 0)
+    }
+
+    /// Applies the repeater of the timestamp on October 24 at 12:00.
+    fn apply(s: &str) -> String {
+        let mut timestamp: Timestamp = s.parse().unwrap();
+        assert!(timestamp.apply_repeat(date_time(24, 12)));
+        timestamp.to_string()
+    }
+
+    #[test]
+    fn next_occurrence() {
+        let timestamp: Timestamp = "<2018-10-08 Mon 10:00 +1w>".parse().unwrap();
+        assert_eq!(
+            timestamp.next_occurrence(date_time(8, 10)),
+            Some(date_time(15, 10))
+        );
+        let once: Timestamp = "<2018-10-08 Mon>".parse().unwrap();
+        assert_eq!(once.next_occurrence(date_time(8, 0)), None);
+    }
+
+    #[test]
+    fn occurrences_between() {
+        let timestamp: Timestamp = "<2018-10-08 Mon 10:00 +1w>".parse().unwrap();
+        assert_eq!(
+            timestamp.occurrences_between(
+                NaiveDate::from_ymd(2018, 10, 1)..=NaiveDate::from_ymd(2018, 10, 22)
+            ),
+            vec![date_time(8, 10), date_time(15, 10), date_time(22, 10)]
+        );
+    }
+
+    #[test]
+    fn apply_cumulative_repeater() {
+        assert_eq!(
+            apply("<2018-10-08 Mon 10:00 +1w>"),
+            "<2018-10-15 Mon 10:00 +1w>"
+        );
+        // the day of the month is clamped
+        assert_eq!(apply("<2018-01-31 Wed +1m>"), "<2018-02-28 Wed +1m>");
+    }
+
+    #[test]
+    fn apply_catch_up_repeater() {
+        assert_eq!(
+            apply("<2018-10-08 Mon 10:00 ++1w>"),
+            "<2018-10-29 Mon 10:00 ++1w>"
+        );
+    }
+
+    #[test]
+    fn apply_restart_repeater() {
+        assert_eq!(
+            apply("<2018-10-08 Mon 10:00 .+1w>"),
+            "<2018-10-31 Wed 10:00 .+1w>"
+        );
+        assert_eq!(
+            apply("<2018-10-08 Mon 10:00-11:00 .+2h>"),
+            "<2018-10-24 Wed 14:00-15:00 .+2h>"
+        );
+    }
+
+    #[test]
+    fn apply_without_repeater() {
+        let mut once: Timestamp = "<2018-10-08 Mon>".parse().unwrap();
+        assert!(!once.apply_repeat(date_time(24, 12)));
+    }
+
+    #[test]
+    fn parse_roundtrip() {
+        assert_eq!(roundtrip("<2018-10-12>"), "<2018-10-12 Fri>");
+        assert_eq!(
+            roundtrip("<2018-10-12 Fri 10:00 .+1w -2d>"),
+            "<2018-10-12 Fri 10:00 .+1w -2d>"
+        );
+        assert_eq!(
+            roundtrip("[2018-10-12 Fri 10:00-12:30 ++1m --1d]"),
+            "[2018-10-12 Fri 10:00-12:30 ++1m --1d]"
+        );
+        assert_eq!(
+            roundtrip("<2018-10-12 Fri>--<2018-10-14 Sun +1y>"),
+            "<2018-10-12 Fri>--<2018-10-14 Sun +1y>"
+        );
+        assert_eq!(
+            roundtrip("<%%(diary-float t 4 2)>"),
+            "<%%(diary-float t 4 2)>"
+        );
+        assert_eq!(
+            roundtrip("[2018-10-12 Fri 8:30:00-9:15:30]"),
+            "[2018-10-12 Fri 08:30:00-09:15:30]"
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("2018-10-12".parse::<Timestamp>().is_err());
+        assert!("<2018-10-12]".parse::<Timestamp>().is_err());
+        assert!("<2018-13-12>".parse::<Timestamp>().is_err());
+        assert!("<2018-10-12 Fri 10:00 +1x>".parse::<Timestamp>().is_err());
+    }
 }
@@ -0,0 +1,306 @@
+//! Finding the node at a position in the file (e.g. the cursor in an editor).
+//!
+//! Only the content of headlines, sections and greater elements has a [`Span`] (see
+//! [`Parent::content`]). The objects in paragraphs and headline titles store their spans too.
+//! So the innermost element containing an offset is the innermost of those whose content (or
+//! whose objects) contains the offset. Other elements (e.g. keywords) don't store their
+//! position and are never found.
+//!
+//! [`Document::object_at`] then searches the objects of the paragraph or headline title (and the
+//! objects inside them) for the innermost object at the offset (e.g. the link under the
+//! cursor).
+
+use super::document::Document;
+use super::greater_elements::{Headline, HeadlineContentSet, Item, Section};
+use super::objects::{FootnoteReferenceKind, LinkFormat, TextMarkupKind};
+use super::validation::{element_span, headline_span, section_span};
+use super::visit::ObjectRef;
+use super::*;
+
+/// A reference to an element (or a headline, section or item) of a [`Document`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementRef<'a> {
+    Headline(&'a Headline),
+    Section(&'a Section),
+    Element(&'a ElementSet),
+    Item(&'a Item),
+}
+
+pub(crate) fn element_at(document: &Document, offset: usize) -> Option<ElementRef<'_>> {
+    document
+        .preface
+        .iter()
+        .find_map(|preface| section(preface, offset))
+        .or_else(|| {
+            document
+                .headlines
+                .iter()
+                .find_map(|child| headline(child, offset))
+        })
+}
+
+fn contains(span: Option<&Span>, offset: usize) -> bool {
+    span.map(|span| span.contains(offset)).unwrap_or(false)
+}
+
+pub(crate) fn object_at(document: &Document, offset: usize) -> Option<ObjectRef<'_>> {
+    match element_at(document, offset)? {
+        ElementRef::Headline(headline) => object_in(headline.title.as_ref()?, offset),
+        ElementRef::Element(ElementSet::Paragraph(paragraph)) => {
+            object_in(&paragraph.content, offset)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the innermost object containing the offset.
+fn object_in<'a, T>(objects: &'a SecondaryString<T>, offset: usize) -> Option<ObjectRef<'a>>
+where
+    T: AsRawString,
+    &'a T: Into<ObjectRef<'a>>,
+{
+    let object = objects.object_at_offset(offset)?.0.into();
+    Some(inner_object(object, offset).unwrap_or(object))
+}
+
+/// Returns the innermost object in the object containing the offset.
+fn inner_object(object: ObjectRef<'_>, offset: usize) -> Option<ObjectRef<'_>> {
+    match object {
+        ObjectRef::TextMarkup(markup) => match &markup.kind {
+            TextMarkupKind::Bold(content)
+            | TextMarkupKind::Italic(content)
+            | TextMarkupKind::Underline(content)
+            | TextMarkupKind::StrikeThrough(content) => object_in(content, offset),
+            TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => None,
+        },
+        ObjectRef::Link(link) => match &link.link {
+            LinkFormat::Bracket(_, _, Some(description)) => object_in(description, offset),
+            _ => None,
+        },
+        ObjectRef::FootnoteReference(reference) => match &reference.kind {
+            FootnoteReferenceKind::Inline { definition, .. }
+            | FootnoteReferenceKind::Anonymous { definition } => object_in(definition, offset),
+            FootnoteReferenceKind::Normal { .. } => None,
+        },
+        ObjectRef::RadioTarget(target) => object_in(&target.target, offset),
+        ObjectRef::Subscript(script) => object_in(&script.content, offset),
+        ObjectRef::Superscript(script) => object_in(&script.content, offset),
+        _ => None,
+    }
+}
+
+/// Returns `true` if one of the objects contains the offset.
+fn objects_contain<T: AsRawString>(objects: &SecondaryString<T>, offset: usize) -> bool {
+    objects.object_at_offset(offset).is_some()
+}
+
+fn headline(headline: &Headline, offset: usize) -> Option<ElementRef<'_>> {
+    headline_content(headline.content(), offset).or_else(|| {
+        let in_title = headline
+            .title
+            .as_ref()
+            .is_some_and(|title| objects_contain(title, offset));
+        if in_title || contains(headline_span(headline), offset) {
+            Some(ElementRef::Headline(headline))
+        } else {
+            None
+        }
+    })
+}
+
+fn headline_content(
+    content: Option<&Spanned<Vec<HeadlineContentSet>>>,
+    offset: usize,
+) -> Option<ElementRef<'_>> {
+    let content = content.map(Spanned::value);
+    let content = content.map(Vec::as_slice).unwrap_or(&[]);
+    content.iter().find_map(|content| match content {
+        HeadlineContentSet::Section(child) => section(child, offset),
+        HeadlineContentSet::Headline(child) => headline(child, offset),
+    })
+}
+
+fn section(section: &Section, offset: usize) -> Option<ElementRef<'_>> {
+    elements(section.content(), offset).or_else(|| {
+        if contains(section_span(section), offset) {
+            Some(ElementRef::Section(section))
+        } else {
+            None
+        }
+    })
+}
+
+fn elements(elements: Option<&Spanned<Vec<ElementSet>>>, offset: usize) -> Option<ElementRef<'_>> {
+    let elements = elements.map(Spanned::value);
+    let elements = elements.map(Vec::as_slice).unwrap_or(&[]);
+    elements.iter().find_map(|child| element(child, offset))
+}
+
+fn element(element: &ElementSet, offset: usize) -> Option<ElementRef<'_>> {
+    let inner = match element {
+        ElementSet::Drawer(drawer) => elements(drawer.content(), offset),
+        ElementSet::DynamicBlock(block) => elements(block.content(), offset),
+        ElementSet::FootnoteDefinition(definition) => elements(definition.content(), offset),
+        ElementSet::QuoteBlock(block) => elements(block.content(), offset),
+        ElementSet::Inlinetask(inlinetask) => headline_content(inlinetask.content(), offset),
+        ElementSet::PlainList(list) => {
+            let items = list.content().map(Spanned::value);
+            let items = items.map(Vec::as_slice).unwrap_or(&[]);
+            items.iter().find_map(|item| {
+                elements(item.content(), offset).or_else(|| {
+                    if contains(item.content().and_then(|c| c.span().as_ref()), offset) {
+                        Some(ElementRef::Item(item))
+                    } else {
+                        None
+                    }
+                })
+            })
+        }
+        _ => None,
+    };
+    inner.or_else(|| {
+        let in_paragraph = match element {
+            ElementSet::Paragraph(paragraph) => objects_contain(&paragraph.content, offset),
+            _ => false,
+        };
+        if in_paragraph || contains(element_span(element), offset) {
+            Some(ElementRef::Element(element))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::greater_elements::{Drawer, ItemKind, PlainList, UnorderedBullet};
+
+    #[test]
+    fn innermost_node() {
+        let item = Item::new(
+            ItemKind::Unordered {
+                bullet: UnorderedBullet::Minus,
+            },
+            Spanned::with_span(vec![], Span::new(12, 15)),
+        );
+        let list = PlainList::new(Spanned::with_span(vec![item], Span::new(12, 15)));
+        let drawer = Drawer::new(
+            "NOTES".to_string(),
+            Spanned::with_span(vec![list.into()], Span::new(10, 20)),
+        );
+        let mut headline = Headline::default();
+        headline.push_content(vec![HeadlineContentSet::Section(Section::new(
+            Spanned::with_span(vec![drawer.into()], Span::new(5, 25)),
+        ))]);
+        let document = Document {
+            preface: None,
+            headlines: vec![headline],
+        };
+
+        let name = |offset| match document.element_at(offset) {
+            Some(ElementRef::Headline(_)) => "headline",
+            Some(ElementRef::Section(_)) => "section",
+            Some(ElementRef::Element(ElementSet::Drawer(_))) => "drawer",
+            Some(ElementRef::Element(_)) => "element",
+            Some(ElementRef::Item(_)) => "item",
+            None => "none",
+        };
+        assert_eq!(name(0), "none");
+        assert_eq!(name(5), "section");
+        assert_eq!(name(11), "drawer");
+        assert_eq!(name(12), "item");
+        assert_eq!(name(20), "section");
+        assert_eq!(name(25), "none");
+    }
+
+    #[test]
+    fn innermost_paragraph() {
+        let text = "* Tasks\n- first\n- second\n  - see [[#a][the link]]\n";
+        let document = Document::parse(text).unwrap();
+        match document.element_at(text.find("see").unwrap()) {
+            Some(ElementRef::Element(ElementSet::Paragraph(paragraph))) => {
+                assert_eq!(paragraph.to_string(), "see [[#a][the link]]")
+            }
+            element => panic!("expected a paragraph, got {:?}", element),
+        }
+        match document.element_at(text.find("Tasks").unwrap()) {
+            Some(ElementRef::Headline(headline)) => assert_eq!(headline.level, 1),
+            element => panic!("expected a headline, got {:?}", element),
+        }
+        assert_eq!(document.element_at(text.len() + 1), None);
+    }
+
+    #[test]
+    fn link_at_offset() {
+        let text = "Text [[#a][the link]] after\n";
+        let document = Document::parse(text).unwrap();
+        let start = text.find('[').unwrap();
+        let end = text.find(" after").unwrap();
+        let is_link = |offset| matches!(document.object_at(offset), Some(ObjectRef::Link(_)));
+        assert!(is_link(start));
+        assert!(is_link(start + 1));
+        assert!(is_link(end - 1));
+        assert!(!is_link(start - 1));
+        assert_eq!(document.object_at(end), Some(ObjectRef::RawString(" after")));
+        assert_eq!(
+            document.object_at(text.find("link").unwrap()),
+            Some(ObjectRef::RawString("the link"))
+        );
+    }
+
+    #[test]
+    fn markup_at_offset() {
+        let text = "Text and *bold /it/*.\n";
+        let document = Document::parse(text).unwrap();
+        let object = |needle| document.object_at(text.find(needle).unwrap());
+        assert_eq!(object("it"), Some(ObjectRef::RawString("it")));
+        match object("/it") {
+            Some(ObjectRef::TextMarkup(markup)) => assert_eq!(markup.to_string(), "/it/"),
+            object => panic!("expected italic text, got {:?}", object),
+        }
+        match object("*bold") {
+            Some(ObjectRef::TextMarkup(markup)) => assert_eq!(markup.to_string(), "*bold /it/*"),
+            object => panic!("expected bold text, got {:?}", object),
+        }
+        assert_eq!(object("."), Some(ObjectRef::RawString(".")));
+        assert_eq!(object("and"), Some(ObjectRef::RawString("Text and ")));
+    }
+
+    #[test]
+    fn object_in_title() {
+        let text = "* See [[#a][*the* link]]\n";
+        let document = Document::parse(text).unwrap();
+        match document.object_at(text.find("*the").unwrap()) {
+            Some(ObjectRef::TextMarkup(markup)) => assert_eq!(markup.to_string(), "*the*"),
+            object => panic!("expected bold text, got {:?}", object),
+        }
+        assert_eq!(
+            document.object_at(text.find(" link").unwrap()),
+            Some(ObjectRef::RawString(" link"))
+        );
+        assert_eq!(document.object_at(0), None);
+    }
+
+    #[test]
+    fn object_on_continuation_line_of_item() {
+        let text = "- first\n  and *more*\n";
+        let document = Document::parse(text).unwrap();
+        match document.object_at(text.find("*more").unwrap()) {
+            Some(ObjectRef::TextMarkup(markup)) => assert_eq!(markup.to_string(), "*more*"),
+            object => panic!("expected bold text, got {:?}", object),
+        }
+        assert_eq!(
+            document.object_at(text.find("more").unwrap()),
+            Some(ObjectRef::RawString("more"))
+        );
+    }
+
+    #[test]
+    fn object_past_end_of_file() {
+        let text = "Text\n";
+        let document = Document::parse(text).unwrap();
+        assert_eq!(document.object_at(text.len()), None);
+        assert_eq!(document.object_at(text.len() + 10), None);
+    }
+}
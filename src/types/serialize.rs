@@ -0,0 +1,253 @@
+//! Configuring how org files are written.
+//!
+//! Everything is written with its `Display` implementation. By default this produces the style
+//! of org mode itself. [`SerializeConfig::format`] writes a value with another style, e.g. to
+//! match the conventions of a team:
+//!
+//! ```
+//! use rust_orgmode::types::serialize::{KeywordCase, SerializeConfig};
+//! # use rust_orgmode::types::document::Document;
+//! # let document = Document::default();
+//!
+//! let config = SerializeConfig {
+//!     keyword_case: Some(KeywordCase::Lower),
+//!     tags_column: -77,
+//!     ..SerializeConfig::default()
+//! };
+//! let org = config.format(&document);
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+
+/// The style used when writing org files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SerializeConfig {
+    /// The number of spaces the content of drawers and blocks is indented by.
+    pub indentation: usize,
+    /// The number of empty lines between a headline (including its planning and property
+    /// drawer) and its content.
+    pub blank_lines_after_headline: usize,
+    /// The case of block keywords (e.g. `#+BEGIN_SRC` and `#+END_SRC`).
+    ///
+    /// `None` keeps the case the keywords were parsed in (upper case for new blocks).
+    pub keyword_case: Option<KeywordCase>,
+    /// The column tags are aligned to (like `org-tags-column`).
+    ///
+    /// Positive values align the start of the tags, negative values align the end. `0` separates
+    /// them from the title by a single space. There is always at least one space before the tags.
+    pub tags_column: i32,
+    /// Write the name of the weekday in timestamps (`<2018-10-12 Fri>`).
+    pub weekday_names: bool,
+}
+
+impl Default for SerializeConfig {
+    fn default() -> Self {
+        SerializeConfig {
+            indentation: 0,
+            blank_lines_after_headline: 0,
+            keyword_case: None,
+            tags_column: 0,
+            weekday_names: true,
+        }
+    }
+}
+
+/// The case of keywords in [`SerializeConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+}
+
+impl KeywordCase {
+    /// Returns the case of a parsed keyword. Keywords in mixed case (e.g. `#+Begin_Src`) are
+    /// upper case.
+    pub(crate) fn of(keyword: &str) -> Self {
+        if keyword.chars().any(char::is_uppercase) {
+            KeywordCase::Upper
+        } else {
+            KeywordCase::Lower
+        }
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<SerializeConfig> = RefCell::new(SerializeConfig::default());
+}
+
+impl SerializeConfig {
+    /// Writes the value (e.g. a [`Document`]) with this configuration.
+    ///
+    /// [`Document`]: `crate::types::document::Document`
+    pub fn format(&self, value: &impl fmt::Display) -> String {
+        /// Restores the previous configuration (even when writing panics).
+        struct Restore(Option<SerializeConfig>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                if let Some(previous) = self.0.take() {
+                    CONFIG.with(|config| *config.borrow_mut() = previous);
+                }
+            }
+        }
+
+        let previous = CONFIG.with(|config| config.replace(self.clone()));
+        let _restore = Restore(Some(previous));
+        value.to_string()
+    }
+}
+
+/// Calls `f` with the configuration of the value that is currently written.
+pub(crate) fn with_config<R>(f: impl FnOnce(&SerializeConfig) -> R) -> R {
+    CONFIG.with(|config| f(&config.borrow()))
+}
+
+/// Returns the block keyword (e.g. `BEGIN_SRC`) in the configured case or else in the case it
+/// was parsed in.
+pub(crate) fn keyword(keyword: &str, parsed: KeywordCase) -> String {
+    with_config(|config| match config.keyword_case.unwrap_or(parsed) {
+        KeywordCase::Upper => keyword.to_uppercase(),
+        KeywordCase::Lower => keyword.to_lowercase(),
+    })
+}
+
+/// Indents every non-empty line by the configured indentation.
+pub(crate) fn indent(s: &str) -> String {
+    let indentation = with_config(|config| " ".repeat(config.indentation));
+    if indentation.is_empty() {
+        return s.to_string();
+    }
+    s.split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", indentation, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Paragraph;
+    use crate::types::greater_elements::{Drawer, Headline, QuoteBlock};
+    use crate::types::objects::{Timestamp, TimestampStatus};
+    use crate::types::{ElementSet, SecondaryString, Spanned, StandardSet};
+    use chrono::NaiveDate;
+
+    fn paragraph(object: StandardSet) -> ElementSet {
+        Paragraph::new(SecondaryString::with_one(object)).into()
+    }
+
+    fn headline() -> Headline {
+        let timestamp = Timestamp::new(
+            TimestampStatus::Active,
+            NaiveDate::from_ymd(2018, 10, 12),
+            None,
+        );
+        Headline::builder()
+            .title("Task")
+            .tag("work")
+            .element(Drawer::new(
+                "NOTES".to_string(),
+                Spanned::new(vec![paragraph(StandardSet::Timestamp(timestamp))]),
+            ))
+            .element(QuoteBlock::new(Spanned::new(vec![paragraph(
+                StandardSet::RawString("quote".to_string()),
+            )])))
+            .build()
+    }
+
+    const DEFAULT_STYLE: &str = "* Task :work:\n\
+                                 :NOTES:\n<2018-10-12 Fri>\n:END:\n\n\
+                                 #+BEGIN_QUOTE\nquote\n#+END_QUOTE";
+
+    #[test]
+    fn default_style() {
+        assert_eq!(headline().to_string(), DEFAULT_STYLE);
+    }
+
+    #[test]
+    fn indentation() {
+        let config = SerializeConfig {
+            indentation: 2,
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&headline()),
+            DEFAULT_STYLE
+                .replace("\n<2018", "\n  <2018")
+                .replace("\nquote", "\n  quote")
+        );
+    }
+
+    #[test]
+    fn blank_lines_after_headline() {
+        let config = SerializeConfig {
+            blank_lines_after_headline: 1,
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&headline()),
+            DEFAULT_STYLE.replace(":work:\n", ":work:\n\n")
+        );
+    }
+
+    #[test]
+    fn keyword_case() {
+        let config = SerializeConfig {
+            keyword_case: Some(KeywordCase::Lower),
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&headline()),
+            DEFAULT_STYLE
+                .replace("BEGIN_QUOTE", "begin_quote")
+                .replace("END_QUOTE", "end_quote")
+        );
+    }
+
+    #[test]
+    fn tags_column() {
+        let config = SerializeConfig {
+            tags_column: -20,
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&headline()),
+            DEFAULT_STYLE.replace("* Task :work:", "* Task        :work:")
+        );
+    }
+
+    #[test]
+    fn without_weekday_names() {
+        let config = SerializeConfig {
+            weekday_names: false,
+            ..SerializeConfig::default()
+        };
+        assert_eq!(
+            config.format(&headline()),
+            DEFAULT_STYLE.replace(" Fri>", ">")
+        );
+    }
+
+    #[test]
+    fn restore_default_style() {
+        let config = SerializeConfig {
+            indentation: 2,
+            blank_lines_after_headline: 1,
+            keyword_case: Some(KeywordCase::Lower),
+            tags_column: -20,
+            weekday_names: false,
+        };
+        let headline = headline();
+        config.format(&headline);
+        assert_eq!(headline.to_string(), DEFAULT_STYLE);
+    }
+}
@@ -0,0 +1,138 @@
+//! Consistent read access to a document that is being edited.
+//!
+//! A server (e.g. a language server) applies edits to a document while it answers queries on
+//! other threads. Queries work on a [`Snapshot`]: an immutable version of the document that
+//! doesn't change while the query runs.
+//!
+//! [`SharedDocument`] holds the current version in an [`Arc`]. Taking a snapshot only clones the
+//! `Arc`. Edits are copy-on-write: the document is only copied if snapshots of the current
+//! version are still in use.
+
+use super::document::Document;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// An immutable version of a [`Document`].
+///
+/// Cloning a snapshot is cheap. Snapshots can be sent to and shared between threads.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    document: Arc<Document>,
+    version: u64,
+}
+
+impl Snapshot {
+    /// The number of edits applied to the [`SharedDocument`] before this snapshot was taken.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` if both snapshots are the same version of the same document.
+    pub fn ptr_eq(&self, other: &Snapshot) -> bool {
+        Arc::ptr_eq(&self.document, &other.document)
+    }
+}
+
+impl Deref for Snapshot {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        &self.document
+    }
+}
+
+impl From<Document> for Snapshot {
+    fn from(document: Document) -> Self {
+        Snapshot {
+            document: Arc::new(document),
+            version: 0,
+        }
+    }
+}
+
+/// A document that is edited while snapshots of it are read.
+#[derive(Debug, Clone, Default)]
+pub struct SharedDocument {
+    current: Arc<Document>,
+    version: u64,
+}
+
+impl SharedDocument {
+    pub fn new(document: Document) -> Self {
+        SharedDocument {
+            current: Arc::new(document),
+            version: 0,
+        }
+    }
+
+    /// Returns a snapshot of the current version. This doesn't copy the document.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            document: Arc::clone(&self.current),
+            version: self.version,
+        }
+    }
+
+    /// Applies an edit to the document.
+    ///
+    /// Snapshots taken before keep seeing the old version. The document is copied first if such
+    /// snapshots still exist.
+    pub fn edit<R>(&mut self, edit: impl FnOnce(&mut Document) -> R) -> R {
+        self.version += 1;
+        edit(Arc::make_mut(&mut self.current))
+    }
+
+    /// The number of edits applied so far.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the current version of the document (copying it if snapshots still exist).
+    pub fn into_document(self) -> Document {
+        Arc::try_unwrap(self.current).unwrap_or_else(|current| (*current).clone())
+    }
+}
+
+impl Deref for SharedDocument {
+    type Target = Document;
+
+    fn deref(&self) -> &Document {
+        &self.current
+    }
+}
+
+impl From<Document> for SharedDocument {
+    fn from(document: Document) -> Self {
+        SharedDocument::new(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::greater_elements::Headline;
+    use std::thread;
+
+    #[test]
+    fn snapshots() {
+        let mut shared = SharedDocument::new(Document::default());
+        let before = shared.snapshot();
+        assert!(before.ptr_eq(&shared.snapshot()));
+
+        shared.edit(|document| document.headlines.push(Headline::default()));
+        let after = shared.snapshot();
+        assert_eq!((before.version(), after.version()), (0, 1));
+        assert!(!before.ptr_eq(&after));
+
+        let reader = thread::spawn(move || before.headlines.len());
+        assert_eq!(reader.join().unwrap(), 0);
+        assert_eq!(after.headlines.len(), 1);
+
+        // the edit happens in place once no snapshot uses the current version
+        drop(after);
+        let address = &*shared as *const Document;
+        shared.edit(|document| document.headlines.clear());
+        assert_eq!(&*shared as *const Document, address);
+        assert_eq!(shared.into_document(), Document::default());
+    }
+}
@@ -0,0 +1,796 @@
+//! Checks for structural invariants of a [`Document`].
+//!
+//! The parser only produces valid documents but after programmatic mutation a document can end
+//! up in a state that can't be represented as an org file. E.g. a headline of level 1 nested in
+//! a headline of level 2 would be a sibling when the file is parsed again.
+//!
+//! The same applies to objects: the types of most object containers only allow the objects
+//! the syntax allows. The remaining containment rules (e.g. no markup nested in markup of the
+//! same kind) are checked here too.
+//!
+//! See [`Document::validate`].
+//!
+//! [`Document`]: `crate::types::document::Document`
+//! [`Document::validate`]: `crate::types::document::Document::validate`
+
+use super::document::Document;
+use super::greater_elements::{Headline, HeadlineContentSet, Section, TableContent, TableRowKind};
+use super::objects::{
+    Link, LinkDescriptionSetOfObjects, LinkFormat, RadioTarget, RadioTargetSetOfObjects,
+    TableCellSetOfObjects, TextMarkup, TextMarkupKind,
+};
+use super::*;
+use std::fmt;
+
+/// A violated invariant found by [`Document::validate`].
+///
+/// [`Document::validate`]: `crate::types::document::Document::validate`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// The titles of the headlines containing the problem. Empty for the preface.
+    pub path: Vec<String>,
+    /// The span of the offending element if it is known.
+    pub span: Option<Span>,
+}
+
+/// The invariant that is violated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticKind {
+    /// A headline is nested in a headline that doesn't have a lower level.
+    HeadlineLevel { parent_level: u32, level: u32 },
+    /// A property drawer is not directly after a headline (or its planning line).
+    ///
+    /// Property drawers of headlines are stored in [`Headline::property_drawer`]. Only the
+    /// first element of the preface can be a property drawer (with properties for the whole
+    /// file).
+    ///
+    /// [`Headline::property_drawer`]: `greater_elements::Headline::property_drawer`
+    MisplacedPropertyDrawer,
+    /// A drawer or property drawer is inside of a drawer.
+    NestedDrawer,
+    /// The span of an element is not inside the span of its parent.
+    SpanOutsideParent { parent: Span },
+    /// The span of an element starts before the previous sibling ends.
+    SpanOverlapsPrevious { previous: Span },
+    /// An object is inside of an object or element that can't contain it. E.g. a link in a
+    /// link description, a newline in a headline title or bold markup inside of bold markup.
+    InvalidObject {
+        object: &'static str,
+        container: &'static str,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(preface)")?;
+        } else {
+            write!(f, "{}", self.path.iter().format(" / "))?;
+        }
+        write!(f, ": {}", self.kind)?;
+        if let Some(span) = &self.span {
+            write!(f, " at {} to {}", span.start(), span.end())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::HeadlineLevel {
+                parent_level,
+                level,
+            } => write!(
+                f,
+                "headline of level {} is nested in a headline of level {}",
+                level, parent_level
+            ),
+            DiagnosticKind::MisplacedPropertyDrawer => write!(
+                f,
+                "property drawer is not directly after a headline or planning line"
+            ),
+            DiagnosticKind::NestedDrawer => write!(f, "drawer is inside of another drawer"),
+            DiagnosticKind::SpanOutsideParent { parent } => write!(
+                f,
+                "span is outside of the span of the parent ({} to {})",
+                parent.start(),
+                parent.end()
+            ),
+            DiagnosticKind::SpanOverlapsPrevious { previous } => write!(
+                f,
+                "span overlaps the span of the previous element ({} to {})",
+                previous.start(),
+                previous.end()
+            ),
+            DiagnosticKind::InvalidObject { object, container } => {
+                write!(f, "{} is not allowed in {}", object, container)
+            }
+        }
+    }
+}
+
+pub(crate) fn validate(document: &Document) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        path: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    if let Some(preface) = &document.preface {
+        validator.section(preface, true);
+    }
+    validator.check_spans(
+        None,
+        document
+            .headlines
+            .iter()
+            .map(|headline| headline_span(headline)),
+    );
+    for headline in &document.headlines {
+        validator.headline(headline);
+    }
+    validator.diagnostics
+}
+
+struct Validator {
+    path: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn report(&mut self, kind: DiagnosticKind, span: Option<&Span>) {
+        self.diagnostics.push(Diagnostic {
+            kind,
+            path: self.path.clone(),
+            span: span.cloned(),
+        });
+    }
+
+    fn headline(&mut self, headline: &Headline) {
+        self.path.push(
+            headline
+                .title
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+        if let Some(title) = &headline.title {
+            for object in title.iter() {
+                self.object(object.into(), Container::Title, &mut Vec::new());
+            }
+        }
+
+        let content = headline
+            .content()
+            .map(|content| content.value().as_slice())
+            .unwrap_or(&[]);
+        self.check_spans(headline_span(headline), content.iter().map(content_span));
+        for content in content {
+            match content {
+                HeadlineContentSet::Section(section) => self.section(section, false),
+                HeadlineContentSet::Headline(child) => {
+                    if child.level <= headline.level {
+                        self.report(
+                            DiagnosticKind::HeadlineLevel {
+                                parent_level: headline.level,
+                                level: child.level,
+                            },
+                            headline_span(child),
+                        );
+                    }
+                    self.headline(child);
+                }
+            }
+        }
+
+        self.path.pop();
+    }
+
+    /// Checks a section. Only the first element of the preface can be a property drawer.
+    fn section(&mut self, section: &Section, is_preface: bool) {
+        let elements = section.content().map(Spanned::value);
+        let elements = elements.map(Vec::as_slice).unwrap_or(&[]);
+        self.check_spans(section_span(section), elements.iter().map(element_span));
+        for (i, element) in elements.iter().enumerate() {
+            if let ElementSet::PropertyDrawer(_) = element {
+                if !(is_preface && i == 0) {
+                    self.report(
+                        DiagnosticKind::MisplacedPropertyDrawer,
+                        element_span(element),
+                    );
+                }
+            } else {
+                self.element(element, false);
+            }
+        }
+    }
+
+    /// Checks an element that is inside of a section or a greater element.
+    fn element(&mut self, element: &ElementSet, in_drawer: bool) {
+        match element {
+            ElementSet::Drawer(drawer) => {
+                if in_drawer {
+                    self.report(DiagnosticKind::NestedDrawer, element_span(element));
+                }
+                self.elements(element_span(element), drawer.content(), true);
+            }
+            ElementSet::PropertyDrawer(_) => {
+                if in_drawer {
+                    self.report(DiagnosticKind::NestedDrawer, element_span(element));
+                } else {
+                    self.report(
+                        DiagnosticKind::MisplacedPropertyDrawer,
+                        element_span(element),
+                    );
+                }
+            }
+            ElementSet::DynamicBlock(block) => {
+                self.elements(element_span(element), block.content(), in_drawer)
+            }
+            ElementSet::FootnoteDefinition(definition) => {
+                self.elements(element_span(element), definition.content(), in_drawer)
+            }
+            ElementSet::QuoteBlock(block) => {
+                self.elements(element_span(element), block.content(), in_drawer)
+            }
+            ElementSet::Paragraph(paragraph) => {
+                self.standard_objects(paragraph.content.iter(), Container::Element)
+            }
+            ElementSet::Table(table) => {
+                let rows = Parent::content(&**table).map(Spanned::value);
+                let rows = rows.map(Vec::as_slice).unwrap_or(&[]);
+                for row in rows {
+                    let cells = match row {
+                        TableContent::Org(row) => match &row.kind {
+                            TableRowKind::Normal(cells) => cells.value(),
+                            TableRowKind::Rule => continue,
+                        },
+                        _ => continue,
+                    };
+                    for cell in cells {
+                        for object in cell.content.value() {
+                            self.object(object.into(), Container::TableCell, &mut Vec::new());
+                        }
+                    }
+                }
+            }
+            ElementSet::PlainList(list) => {
+                let items = list.content().map(Spanned::value);
+                let items = items.map(Vec::as_slice).unwrap_or(&[]);
+                self.check_spans(
+                    element_span(element),
+                    items
+                        .iter()
+                        .map(|item| item.content().and_then(|content| content.span().as_ref())),
+                );
+                for item in items {
+                    self.elements(
+                        item.content().and_then(|content| content.span().as_ref()),
+                        item.content(),
+                        in_drawer,
+                    );
+                }
+            }
+            ElementSet::Inlinetask(inlinetask) => {
+                let content = inlinetask.content().map(Spanned::value);
+                let content = content.map(Vec::as_slice).unwrap_or(&[]);
+                self.check_spans(element_span(element), content.iter().map(content_span));
+                for (i, content) in content.iter().enumerate() {
+                    match content {
+                        // Inlinetasks don't store their property drawer separately.
+                        HeadlineContentSet::Section(section) => {
+                            let elements = section.content().map(Spanned::value);
+                            let elements = elements.map(Vec::as_slice).unwrap_or(&[]);
+                            self.check_spans(
+                                section_span(section),
+                                elements.iter().map(element_span),
+                            );
+                            for (j, element) in elements.iter().enumerate() {
+                                match element {
+                                    ElementSet::PropertyDrawer(_) if i == 0 && j == 0 => {}
+                                    element => self.element(element, in_drawer),
+                                }
+                            }
+                        }
+                        HeadlineContentSet::Headline(headline) => self.headline(headline),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn elements(
+        &mut self,
+        parent: Option<&Span>,
+        elements: Option<&Spanned<Vec<ElementSet>>>,
+        in_drawer: bool,
+    ) {
+        let elements = elements.map(Spanned::value);
+        let elements = elements.map(Vec::as_slice).unwrap_or(&[]);
+        self.check_spans(parent, elements.iter().map(element_span));
+        for element in elements {
+            self.element(element, in_drawer);
+        }
+    }
+
+    fn standard_objects<'a>(
+        &mut self,
+        objects: impl Iterator<Item = &'a StandardSet>,
+        container: Container,
+    ) {
+        for object in objects {
+            self.object(object.into(), container, &mut Vec::new());
+        }
+    }
+
+    /// Checks that an object is allowed in the container and checks its content.
+    ///
+    /// `markup` contains the kinds of the text markup enclosing the object.
+    fn object(&mut self, object: Object<'_>, container: Container, markup: &mut Vec<&'static str>) {
+        let name = object.name();
+        let allowed = match (container, &object) {
+            (Container::Title, Object::Text(s)) | (Container::RadioTarget, Object::Text(s)) => {
+                !s.contains('\n')
+            }
+            (Container::TableCell, Object::Text(s)) => !s.contains('|') && !s.contains('\n'),
+            (_, Object::Text(_)) => true,
+            (Container::LinkDescription, Object::Link(link)) => {
+                matches!(link.link, LinkFormat::Plain(_) | LinkFormat::Angle(_))
+            }
+            (Container::RadioTarget, Object::TextMarkup(_))
+            | (Container::RadioTarget, Object::Entity)
+            | (Container::RadioTarget, Object::LatexFragment)
+            | (Container::RadioTarget, Object::Script) => true,
+            (Container::RadioTarget, _) => false,
+            (_, Object::LineBreak) => container == Container::Element,
+            _ => true,
+        };
+        if !allowed {
+            self.report(
+                DiagnosticKind::InvalidObject {
+                    object: name,
+                    container: container.name(),
+                },
+                None,
+            );
+        }
+
+        match object {
+            Object::Link(link) => {
+                if let LinkFormat::Bracket(_, _, Some(description)) = &link.link {
+                    for object in description.iter() {
+                        self.object(object.into(), Container::LinkDescription, &mut Vec::new());
+                    }
+                }
+            }
+            Object::RadioTarget(target) => {
+                for object in target.target.iter() {
+                    self.object(object.into(), Container::RadioTarget, &mut Vec::new());
+                }
+            }
+            Object::TextMarkup(text_markup) => {
+                if markup.contains(&name) {
+                    self.report(
+                        DiagnosticKind::InvalidObject {
+                            object: name,
+                            container: name,
+                        },
+                        None,
+                    );
+                }
+                let content = match &text_markup.kind {
+                    TextMarkupKind::Bold(content)
+                    | TextMarkupKind::Italic(content)
+                    | TextMarkupKind::Underline(content)
+                    | TextMarkupKind::StrikeThrough(content) => content,
+                    TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => return,
+                };
+                markup.push(name);
+                for object in content.iter() {
+                    self.object(object.into(), container, markup);
+                }
+                markup.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that the spans of the children are inside of the parent span and don't overlap.
+    ///
+    /// Missing spans (e.g. of artificially created elements) are skipped.
+    fn check_spans<'a>(
+        &mut self,
+        parent: Option<&Span>,
+        children: impl Iterator<Item = Option<&'a Span>>,
+    ) {
+        let mut previous: Option<&Span> = None;
+        for child in children.flatten() {
+            if let Some(parent) = parent {
+                if child.start() < parent.start() || child.end() > parent.end() {
+                    self.report(
+                        DiagnosticKind::SpanOutsideParent {
+                            parent: parent.clone(),
+                        },
+                        Some(child),
+                    );
+                }
+            }
+            if let Some(previous) = previous {
+                if child.start() < previous.end() {
+                    self.report(
+                        DiagnosticKind::SpanOverlapsPrevious {
+                            previous: previous.clone(),
+                        },
+                        Some(child),
+                    );
+                }
+            }
+            previous = Some(child);
+        }
+    }
+}
+
+/// An object or element that contains objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// An element like a paragraph.
+    Element,
+    Title,
+    TableCell,
+    LinkDescription,
+    RadioTarget,
+}
+
+impl Container {
+    fn name(self) -> &'static str {
+        match self {
+            Container::Element => "element",
+            Container::Title => "headline title",
+            Container::TableCell => "table cell",
+            Container::LinkDescription => "link description",
+            Container::RadioTarget => "radio target",
+        }
+    }
+}
+
+/// The objects relevant for the containment rules.
+enum Object<'a> {
+    Text(&'a str),
+    Entity,
+    LatexFragment,
+    /// A subscript or superscript.
+    Script,
+    LineBreak,
+    Link(&'a Link),
+    RadioTarget(&'a RadioTarget),
+    TextMarkup(&'a TextMarkup),
+    Other(&'static str),
+}
+
+impl<'a> Object<'a> {
+    fn name(&self) -> &'static str {
+        match self {
+            Object::Text(s) if s.contains('\n') => "newline",
+            Object::Text(s) if s.contains('|') => "vertical bar",
+            Object::Text(_) => "text",
+            Object::Entity => "entity",
+            Object::LatexFragment => "latex fragment",
+            Object::Script => "subscript or superscript",
+            Object::LineBreak => "line break",
+            Object::Link(_) => "link",
+            Object::RadioTarget(_) => "radio target",
+            Object::TextMarkup(markup) => match markup.kind {
+                TextMarkupKind::Bold(_) => "bold markup",
+                TextMarkupKind::Italic(_) => "italic markup",
+                TextMarkupKind::Underline(_) => "underline markup",
+                TextMarkupKind::StrikeThrough(_) => "strike-through markup",
+                TextMarkupKind::Code(_) => "code markup",
+                TextMarkupKind::Verbatim(_) => "verbatim markup",
+            },
+            Object::Other(name) => name,
+        }
+    }
+}
+
+impl<'a> From<&'a StandardSet> for Object<'a> {
+    fn from(object: &'a StandardSet) -> Self {
+        match object {
+            StandardSet::RawString(s) => Object::Text(s),
+            StandardSet::Entity(_) => Object::Entity,
+            StandardSet::LatexFragment(_) => Object::LatexFragment,
+            StandardSet::Subscript(_) | StandardSet::Superscript(_) => Object::Script,
+            StandardSet::LineBreak(_) => Object::LineBreak,
+            StandardSet::Link(o) => Object::Link(o),
+            StandardSet::RadioTarget(o) => Object::RadioTarget(o),
+            StandardSet::TextMarkup(o) => Object::TextMarkup(o),
+            StandardSet::ExportSnippet(_) => Object::Other("export snippet"),
+            StandardSet::FootnoteReference(_) => Object::Other("footnote reference"),
+            StandardSet::InlineBabelCall(_) => Object::Other("inline babel call"),
+            StandardSet::InlineSrcBlock(_) => Object::Other("inline src block"),
+            StandardSet::Macro(_) => Object::Other("macro"),
+            StandardSet::StatisticsCookie(_) => Object::Other("statistics cookie"),
+            StandardSet::Target(_) => Object::Other("target"),
+            StandardSet::Timestamp(_) => Object::Other("timestamp"),
+        }
+    }
+}
+
+impl<'a> From<&'a StandardSetNoLineBreak> for Object<'a> {
+    fn from(object: &'a StandardSetNoLineBreak) -> Self {
+        match object {
+            StandardSetNoLineBreak::RawString(s) => Object::Text(s),
+            StandardSetNoLineBreak::Entity(_) => Object::Entity,
+            StandardSetNoLineBreak::LatexFragment(_) => Object::LatexFragment,
+            StandardSetNoLineBreak::Subscript(_) | StandardSetNoLineBreak::Superscript(_) => {
+                Object::Script
+            }
+            StandardSetNoLineBreak::Link(o) => Object::Link(o),
+            StandardSetNoLineBreak::RadioTarget(o) => Object::RadioTarget(o),
+            StandardSetNoLineBreak::TextMarkup(o) => Object::TextMarkup(o),
+            _ => Object::Other("object"),
+        }
+    }
+}
+
+impl<'a> From<&'a TableCellSetOfObjects> for Object<'a> {
+    fn from(object: &'a TableCellSetOfObjects) -> Self {
+        match object {
+            TableCellSetOfObjects::RawString(s) => Object::Text(s),
+            TableCellSetOfObjects::Entity(_) => Object::Entity,
+            TableCellSetOfObjects::LatexFragment(_) => Object::LatexFragment,
+            TableCellSetOfObjects::Subscript(_) | TableCellSetOfObjects::Superscript(_) => {
+                Object::Script
+            }
+            TableCellSetOfObjects::Link(o) => Object::Link(o),
+            TableCellSetOfObjects::RadioTarget(o) => Object::RadioTarget(o),
+            TableCellSetOfObjects::TextMarkup(o) => Object::TextMarkup(o),
+            _ => Object::Other("object"),
+        }
+    }
+}
+
+impl<'a> From<&'a LinkDescriptionSetOfObjects> for Object<'a> {
+    fn from(object: &'a LinkDescriptionSetOfObjects) -> Self {
+        match object {
+            LinkDescriptionSetOfObjects::RawString(s) => Object::Text(s),
+            LinkDescriptionSetOfObjects::Entity(_) => Object::Entity,
+            LinkDescriptionSetOfObjects::LatexFragment(_) => Object::LatexFragment,
+            LinkDescriptionSetOfObjects::Subscript(_)
+            | LinkDescriptionSetOfObjects::Superscript(_) => Object::Script,
+            LinkDescriptionSetOfObjects::Link(o) => Object::Link(o),
+            LinkDescriptionSetOfObjects::TextMarkup(o) => Object::TextMarkup(o),
+            _ => Object::Other("object"),
+        }
+    }
+}
+
+impl<'a> From<&'a RadioTargetSetOfObjects> for Object<'a> {
+    fn from(object: &'a RadioTargetSetOfObjects) -> Self {
+        match object {
+            RadioTargetSetOfObjects::RawString(s) => Object::Text(s),
+            RadioTargetSetOfObjects::Entity(_) => Object::Entity,
+            RadioTargetSetOfObjects::LatexFragment(_) => Object::LatexFragment,
+            RadioTargetSetOfObjects::Subscript(_) | RadioTargetSetOfObjects::Superscript(_) => {
+                Object::Script
+            }
+            RadioTargetSetOfObjects::TextMarkup(o) => Object::TextMarkup(o),
+        }
+    }
+}
+
+pub(super) fn headline_span(headline: &Headline) -> Option<&Span> {
+    headline
+        .content()
+        .and_then(|content| content.span().as_ref())
+}
+
+pub(super) fn section_span(section: &Section) -> Option<&Span> {
+    section
+        .content()
+        .and_then(|content| content.span().as_ref())
+}
+
+fn content_span(content: &HeadlineContentSet) -> Option<&Span> {
+    match content {
+        HeadlineContentSet::Section(section) => section_span(section),
+        HeadlineContentSet::Headline(headline) => headline_span(headline),
+    }
+}
+
+/// Returns the span of the content of an element if it has one.
+pub(super) fn element_span(element: &ElementSet) -> Option<&Span> {
+    fn span<T>(content: Option<&Spanned<T>>) -> Option<&Span> {
+        content.and_then(|content| content.span().as_ref())
+    }
+    match element {
+        ElementSet::CenterBlock(e) => span(e.content()),
+        ElementSet::Drawer(e) => span(e.content()),
+        ElementSet::DynamicBlock(e) => span(e.content()),
+        ElementSet::FootnoteDefinition(e) => span(e.content()),
+        ElementSet::Inlinetask(e) => span(e.content()),
+        ElementSet::PlainList(e) => span(e.content()),
+        ElementSet::PropertyDrawer(e) => span(e.content()),
+        ElementSet::QuoteBlock(e) => span(e.content()),
+        ElementSet::SpecialBlock(e) => span(e.content()),
+        ElementSet::Table(e) => span(e.content()),
+        ElementSet::VerseBlock(e) => span(e.content()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elements::Paragraph;
+    use crate::types::greater_elements::{Drawer, PropertyDrawer};
+    use crate::types::objects::LinkPath;
+
+    /// Returns the kinds of the diagnostics of a document with only the headline.
+    fn diagnostics(headline: Headline) -> Vec<DiagnosticKind> {
+        let document = Document {
+            preface: None,
+            headlines: vec![headline],
+        };
+        document
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let parent = Headline::builder()
+            .title("a")
+            .element(Drawer::new("NOTES".to_string(), Spanned::new(vec![])))
+            .child(Headline::builder().title("b").build())
+            .build();
+        let document = Document {
+            preface: Some(Section::new(Spanned::new(vec![PropertyDrawer::new(
+                Spanned::new(vec![]),
+            )
+            .into()]))),
+            headlines: vec![parent],
+        };
+        assert_eq!(document.validate(), vec![]);
+    }
+
+    #[test]
+    fn headline_level() {
+        let mut parent = Headline::builder().level(2).title("a").build();
+        let child = Headline::builder().level(1).title("b").build();
+        parent.push_content(Some(HeadlineContentSet::Headline(Box::new(child))));
+        let document = Document {
+            preface: None,
+            headlines: vec![parent],
+        };
+        assert_eq!(
+            document.validate(),
+            vec![Diagnostic {
+                kind: DiagnosticKind::HeadlineLevel {
+                    parent_level: 2,
+                    level: 1,
+                },
+                path: vec!["a".to_string()],
+                span: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn misplaced_and_nested_drawers() {
+        let inner = Drawer::new("INNER".to_string(), Spanned::new(vec![]));
+        let outer = Drawer::new("OUTER".to_string(), Spanned::new(vec![inner.into()]));
+        let parent = Headline::builder()
+            .title("a")
+            .element(outer)
+            .element(PropertyDrawer::new(Spanned::new(vec![])))
+            .build();
+        assert_eq!(
+            diagnostics(parent),
+            vec![
+                DiagnosticKind::NestedDrawer,
+                DiagnosticKind::MisplacedPropertyDrawer,
+            ]
+        );
+    }
+
+    #[test]
+    fn spans() {
+        let drawer = |start, end| {
+            ElementSet::from(Drawer::new(
+                "D".to_string(),
+                Spanned::with_span(vec![], Span::new(start, end)),
+            ))
+        };
+        let mut parent = Headline::builder().title("a").build();
+        parent.push_content(Some(HeadlineContentSet::Section(Section::new(
+            Spanned::with_span(vec![drawer(2, 8), drawer(6, 12)], Span::new(0, 10)),
+        ))));
+        assert_eq!(
+            diagnostics(parent),
+            vec![
+                DiagnosticKind::SpanOutsideParent {
+                    parent: Span::new(0, 10),
+                },
+                DiagnosticKind::SpanOverlapsPrevious {
+                    previous: Span::new(2, 8),
+                },
+            ]
+        );
+    }
+
+    fn text(s: &str) -> StandardSet {
+        StandardSet::RawString(s.to_string())
+    }
+
+    fn bold(content: Vec<StandardSet>) -> StandardSet {
+        StandardSet::TextMarkup(TextMarkup {
+            kind: TextMarkupKind::Bold(SecondaryString::from(content)),
+        })
+    }
+
+    fn link(description: LinkDescriptionSetOfObjects) -> Link {
+        Link {
+            link: LinkFormat::Bracket(
+                LinkPath::Fuzzy("a".to_string()),
+                None,
+                Some(SecondaryString::with_one(description)),
+            ),
+        }
+    }
+
+    /// Returns the kinds of the diagnostics of a headline with a paragraph of the objects.
+    fn invalid_objects(objects: Vec<StandardSet>) -> Vec<DiagnosticKind> {
+        let paragraph = Paragraph::new(SecondaryString::from(objects));
+        diagnostics(Headline::builder().title("a").element(paragraph).build())
+    }
+
+    fn invalid(object: &'static str, container: &'static str) -> DiagnosticKind {
+        DiagnosticKind::InvalidObject { object, container }
+    }
+
+    #[test]
+    fn newline_in_title() {
+        // the builder only keeps the first line of a title
+        let mut headline = Headline::builder().build();
+        headline.title = Some(SecondaryString::with_one(
+            StandardSetNoLineBreak::RawString("a\nb".to_string()),
+        ));
+        assert_eq!(
+            diagnostics(headline),
+            vec![invalid("newline", "headline title")]
+        );
+    }
+
+    #[test]
+    fn markup_in_same_markup() {
+        assert_eq!(
+            invalid_objects(vec![bold(vec![text("a "), bold(vec![text("b")])])]),
+            vec![invalid("bold markup", "bold markup")]
+        );
+    }
+
+    #[test]
+    fn link_in_link_description() {
+        let inner_link = link(LinkDescriptionSetOfObjects::RawString("b".to_string()));
+        let outer_link = link(LinkDescriptionSetOfObjects::Link(inner_link));
+        assert_eq!(
+            invalid_objects(vec![StandardSet::Link(outer_link)]),
+            vec![invalid("link", "link description")]
+        );
+    }
+
+    #[test]
+    fn newline_in_radio_target() {
+        let radio_target = StandardSet::RadioTarget(RadioTarget {
+            target: SecondaryString::from(vec![bold(vec![text("c")]), text("d\n")]).map(Into::into),
+        });
+        assert_eq!(
+            invalid_objects(vec![radio_target]),
+            vec![invalid("newline", "radio target")]
+        );
+    }
+}
@@ -0,0 +1,667 @@
+//! Walking the elements and objects of a document.
+//!
+//! A [`Visitor`] has a method for every kind of node it can visit. The default implementations
+//! call the `walk_*` function of the same name, which visits the children of the node. A visitor
+//! only overrides the methods for the nodes it is interested in. An overriding method calls the
+//! `walk_*` function itself if the children should still be visited.
+//!
+//! ```
+//! use rust_orgmode::types::document::Document;
+//! use rust_orgmode::types::objects::Timestamp;
+//! use rust_orgmode::types::visit::{walk, Visitor};
+//!
+//! #[derive(Default)]
+//! struct CountTimestamps(usize);
+//!
+//! impl<'a> Visitor<'a> for CountTimestamps {
+//!     fn visit_timestamp(&mut self, _timestamp: &'a Timestamp) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let mut counter = CountTimestamps::default();
+//! walk(&Document::default(), &mut counter);
+//! assert_eq!(counter.0, 0);
+//! ```
+//!
+//! [`VisitorMut`] does the same with mutable references, e.g. to change all links of a document.
+
+use super::document::Document;
+use super::elements::{Clock, Keyword, Paragraph, Planning, SrcBlock};
+use super::greater_elements::{
+    FootnoteDefinition, Headline, HeadlineContentSet, Item, PlainList, Section, Table,
+    TableContent, TableRowKind,
+};
+use super::objects::{
+    Entity, ExportSnippet, FootnoteReference, FootnoteReferenceKind, InlineBabelCall,
+    InlineSrcBlock, LatexFragment, LineBreak, Link, LinkFormat, Macro, RadioTarget,
+    StatisticsCookie, Subscript, Superscript, Target, TextMarkup, TextMarkupKind, Timestamp,
+};
+use super::{ElementSet, Parent, Spanned};
+
+/// A reference to an object in any of the object sets (e.g. [`StandardSet`]).
+///
+/// Every object set can be converted into this with `From`.
+///
+/// [`StandardSet`]: `super::StandardSet`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectRef<'a> {
+    RawString(&'a str),
+    Entity(&'a Entity),
+    ExportSnippet(&'a ExportSnippet),
+    FootnoteReference(&'a FootnoteReference),
+    InlineBabelCall(&'a InlineBabelCall),
+    InlineSrcBlock(&'a InlineSrcBlock),
+    LatexFragment(&'a LatexFragment),
+    LineBreak(&'a LineBreak),
+    Link(&'a Link),
+    Macro(&'a Macro),
+    RadioTarget(&'a RadioTarget),
+    StatisticsCookie(&'a StatisticsCookie),
+    Subscript(&'a Subscript),
+    Superscript(&'a Superscript),
+    Target(&'a Target),
+    TextMarkup(&'a TextMarkup),
+    Timestamp(&'a Timestamp),
+}
+
+/// A mutable reference to an object in any of the object sets.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum ObjectRefMut<'a> {
+    RawString(&'a mut String),
+    Entity(&'a mut Entity),
+    ExportSnippet(&'a mut ExportSnippet),
+    FootnoteReference(&'a mut FootnoteReference),
+    InlineBabelCall(&'a mut InlineBabelCall),
+    InlineSrcBlock(&'a mut InlineSrcBlock),
+    LatexFragment(&'a mut LatexFragment),
+    LineBreak(&'a mut LineBreak),
+    Link(&'a mut Link),
+    Macro(&'a mut Macro),
+    RadioTarget(&'a mut RadioTarget),
+    StatisticsCookie(&'a mut StatisticsCookie),
+    Subscript(&'a mut Subscript),
+    Superscript(&'a mut Superscript),
+    Target(&'a mut Target),
+    TextMarkup(&'a mut TextMarkup),
+    Timestamp(&'a mut Timestamp),
+}
+
+/// Visits the nodes of a document.
+///
+/// The lifetime `'a` is the lifetime of the document, so visitors can keep references to the
+/// nodes they visit.
+pub trait Visitor<'a> {
+    fn visit_document(&mut self, document: &'a Document) {
+        walk_document(self, document)
+    }
+
+    fn visit_headline(&mut self, headline: &'a Headline) {
+        walk_headline(self, headline)
+    }
+
+    fn visit_section(&mut self, section: &'a Section) {
+        walk_section(self, section)
+    }
+
+    /// Visits any element. The default implementation calls the method for the kind of element
+    /// (e.g. [`Visitor::visit_paragraph`]) or visits the children of greater elements without
+    /// their own method.
+    fn visit_element(&mut self, element: &'a ElementSet) {
+        walk_element(self, element)
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &'a Paragraph) {
+        walk_paragraph(self, paragraph)
+    }
+
+    fn visit_keyword(&mut self, keyword: &'a Keyword) {
+        walk_keyword(self, keyword)
+    }
+
+    fn visit_planning(&mut self, planning: &'a Planning) {
+        walk_planning(self, planning)
+    }
+
+    fn visit_clock(&mut self, clock: &'a Clock) {
+        walk_clock(self, clock)
+    }
+
+    fn visit_plain_list(&mut self, list: &'a PlainList) {
+        walk_plain_list(self, list)
+    }
+
+    fn visit_item(&mut self, item: &'a Item) {
+        walk_item(self, item)
+    }
+
+    fn visit_table(&mut self, table: &'a Table) {
+        walk_table(self, table)
+    }
+
+    fn visit_footnote_definition(&mut self, definition: &'a FootnoteDefinition) {
+        walk_footnote_definition(self, definition)
+    }
+
+    fn visit_src_block(&mut self, _block: &'a SrcBlock) {}
+
+    /// Visits any object. The default implementation calls the method for the kind of object
+    /// (e.g. [`Visitor::visit_link`]) or visits the children of objects without their own method.
+    fn visit_object(&mut self, object: ObjectRef<'a>) {
+        walk_object(self, object)
+    }
+
+    fn visit_raw_string(&mut self, _text: &'a str) {}
+
+    fn visit_link(&mut self, link: &'a Link) {
+        walk_link(self, link)
+    }
+
+    fn visit_text_markup(&mut self, markup: &'a TextMarkup) {
+        walk_text_markup(self, markup)
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &'a FootnoteReference) {
+        walk_footnote_reference(self, reference)
+    }
+
+    fn visit_timestamp(&mut self, _timestamp: &'a Timestamp) {}
+}
+
+/// Visits all nodes of the document.
+pub fn walk<'a>(document: &'a Document, visitor: &mut impl Visitor<'a>) {
+    visitor.visit_document(document)
+}
+
+pub fn walk_document<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, document: &'a Document) {
+    if let Some(preface) = &document.preface {
+        visitor.visit_section(preface);
+    }
+    for headline in &document.headlines {
+        visitor.visit_headline(headline);
+    }
+}
+
+pub fn walk_headline<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, headline: &'a Headline) {
+    if let Some(title) = &headline.title {
+        walk_objects(visitor, title.iter());
+    }
+    if let Some(planning) = &headline.planning {
+        visitor.visit_planning(planning);
+    }
+    walk_headline_content(visitor, headline.content());
+}
+
+fn walk_headline_content<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    content: Option<&'a Spanned<Vec<HeadlineContentSet>>>,
+) {
+    for content in content.into_iter().flat_map(|c| c.value()) {
+        match content {
+            HeadlineContentSet::Section(section) => visitor.visit_section(section),
+            HeadlineContentSet::Headline(headline) => visitor.visit_headline(headline),
+        }
+    }
+}
+
+pub fn walk_section<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, section: &'a Section) {
+    walk_elements(visitor, section.content());
+}
+
+fn walk_elements<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    elements: Option<&'a Spanned<Vec<ElementSet>>>,
+) {
+    for element in elements.into_iter().flat_map(|e| e.value()) {
+        visitor.visit_element(element);
+    }
+}
+
+pub fn walk_element<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, element: &'a ElementSet) {
+    match element {
+        ElementSet::Paragraph(paragraph) => visitor.visit_paragraph(paragraph),
+        ElementSet::Keyword(keyword) => visitor.visit_keyword(keyword),
+        ElementSet::Planning(planning) => visitor.visit_planning(planning),
+        ElementSet::Clock(clock) => visitor.visit_clock(clock),
+        ElementSet::PlainList(list) => visitor.visit_plain_list(list),
+        ElementSet::Table(table) => visitor.visit_table(table),
+        ElementSet::FootnoteDefinition(definition) => visitor.visit_footnote_definition(definition),
+        ElementSet::SrcBlock(block) => visitor.visit_src_block(block),
+        ElementSet::Drawer(drawer) => walk_elements(visitor, drawer.content()),
+        ElementSet::DynamicBlock(block) => walk_elements(visitor, block.content()),
+        ElementSet::QuoteBlock(block) => walk_elements(visitor, block.content()),
+        ElementSet::VerseBlock(block) => {
+            if let Some(content) = block.content() {
+                walk_objects(visitor, content.value());
+            }
+        }
+        ElementSet::Inlinetask(inlinetask) => {
+            if let Some(title) = &inlinetask.title {
+                walk_objects(visitor, title.iter());
+            }
+            walk_headline_content(visitor, inlinetask.content());
+        }
+        _ => {}
+    }
+}
+
+pub fn walk_paragraph<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, paragraph: &'a Paragraph) {
+    walk_objects(visitor, paragraph.content.iter());
+}
+
+pub fn walk_keyword<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, keyword: &'a Keyword) {
+    walk_objects(visitor, keyword.value.value());
+}
+
+pub fn walk_planning<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, planning: &'a Planning) {
+    let timestamps = [&planning.closed, &planning.deadline, &planning.scheduled];
+    for timestamp in timestamps.iter().filter_map(|timestamp| timestamp.as_ref()) {
+        visitor.visit_timestamp(timestamp);
+    }
+}
+
+pub fn walk_clock<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, clock: &'a Clock) {
+    if let Some(timestamp) = &clock.timestamp {
+        visitor.visit_timestamp(timestamp);
+    }
+}
+
+pub fn walk_plain_list<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, list: &'a PlainList) {
+    for item in list.content().into_iter().flat_map(|items| items.value()) {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, item: &'a Item) {
+    walk_elements(visitor, item.content());
+}
+
+pub fn walk_table<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, table: &'a Table) {
+    for row in Parent::content(table)
+        .into_iter()
+        .flat_map(|rows| rows.value())
+    {
+        if let TableContent::Org(row) = row {
+            if let TableRowKind::Normal(cells) = &row.kind {
+                for cell in cells.value() {
+                    walk_objects(visitor, cell.content.value());
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_footnote_definition<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    definition: &'a FootnoteDefinition,
+) {
+    walk_elements(visitor, definition.content());
+}
+
+fn walk_objects<'a, V, T>(visitor: &mut V, objects: impl IntoIterator<Item = &'a T>)
+where
+    V: Visitor<'a> + ?Sized,
+    T: 'a,
+    &'a T: Into<ObjectRef<'a>>,
+{
+    for object in objects {
+        visitor.visit_object(object.into());
+    }
+}
+
+pub fn walk_object<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, object: ObjectRef<'a>) {
+    match object {
+        ObjectRef::RawString(text) => visitor.visit_raw_string(text),
+        ObjectRef::Link(link) => visitor.visit_link(link),
+        ObjectRef::TextMarkup(markup) => visitor.visit_text_markup(markup),
+        ObjectRef::FootnoteReference(reference) => visitor.visit_footnote_reference(reference),
+        ObjectRef::Timestamp(timestamp) => visitor.visit_timestamp(timestamp),
+        ObjectRef::RadioTarget(target) => walk_objects(visitor, target.target.iter()),
+        ObjectRef::Subscript(script) => walk_objects(visitor, script.content.iter()),
+        ObjectRef::Superscript(script) => walk_objects(visitor, script.content.iter()),
+        _ => {}
+    }
+}
+
+pub fn walk_link<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, link: &'a Link) {
+    if let LinkFormat::Bracket(_, _, Some(description)) = &link.link {
+        walk_objects(visitor, description.iter());
+    }
+}
+
+pub fn walk_text_markup<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, markup: &'a TextMarkup) {
+    match &markup.kind {
+        TextMarkupKind::Bold(content)
+        | TextMarkupKind::Italic(content)
+        | TextMarkupKind::Underline(content)
+        | TextMarkupKind::StrikeThrough(content) => walk_objects(visitor, content.iter()),
+        TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => {}
+    }
+}
+
+pub fn walk_footnote_reference<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    reference: &'a FootnoteReference,
+) {
+    match &reference.kind {
+        FootnoteReferenceKind::Inline { definition, .. }
+        | FootnoteReferenceKind::Anonymous { definition } => {
+            walk_objects(visitor, definition.iter())
+        }
+        FootnoteReferenceKind::Normal { .. } => {}
+    }
+}
+
+/// Visits and changes the nodes of a document.
+///
+/// The methods and `walk_*_mut` functions are the same as the ones of [`Visitor`].
+pub trait VisitorMut {
+    fn visit_document(&mut self, document: &mut Document) {
+        walk_document_mut(self, document)
+    }
+
+    fn visit_headline(&mut self, headline: &mut Headline) {
+        walk_headline_mut(self, headline)
+    }
+
+    fn visit_section(&mut self, section: &mut Section) {
+        walk_section_mut(self, section)
+    }
+
+    fn visit_element(&mut self, element: &mut ElementSet) {
+        walk_element_mut(self, element)
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &mut Paragraph) {
+        walk_paragraph_mut(self, paragraph)
+    }
+
+    fn visit_keyword(&mut self, keyword: &mut Keyword) {
+        walk_keyword_mut(self, keyword)
+    }
+
+    fn visit_planning(&mut self, planning: &mut Planning) {
+        walk_planning_mut(self, planning)
+    }
+
+    fn visit_clock(&mut self, clock: &mut Clock) {
+        walk_clock_mut(self, clock)
+    }
+
+    fn visit_plain_list(&mut self, list: &mut PlainList) {
+        walk_plain_list_mut(self, list)
+    }
+
+    fn visit_item(&mut self, item: &mut Item) {
+        walk_item_mut(self, item)
+    }
+
+    fn visit_table(&mut self, table: &mut Table) {
+        walk_table_mut(self, table)
+    }
+
+    fn visit_footnote_definition(&mut self, definition: &mut FootnoteDefinition) {
+        walk_footnote_definition_mut(self, definition)
+    }
+
+    fn visit_src_block(&mut self, _block: &mut SrcBlock) {}
+
+    fn visit_object(&mut self, object: ObjectRefMut<'_>) {
+        walk_object_mut(self, object)
+    }
+
+    fn visit_raw_string(&mut self, _text: &mut String) {}
+
+    fn visit_link(&mut self, link: &mut Link) {
+        walk_link_mut(self, link)
+    }
+
+    fn visit_text_markup(&mut self, markup: &mut TextMarkup) {
+        walk_text_markup_mut(self, markup)
+    }
+
+    fn visit_footnote_reference(&mut self, reference: &mut FootnoteReference) {
+        walk_footnote_reference_mut(self, reference)
+    }
+
+    fn visit_timestamp(&mut self, _timestamp: &mut Timestamp) {}
+}
+
+/// Visits and changes all nodes of the document.
+pub fn walk_mut(document: &mut Document, visitor: &mut impl VisitorMut) {
+    visitor.visit_document(document)
+}
+
+pub fn walk_document_mut<V: VisitorMut + ?Sized>(visitor: &mut V, document: &mut Document) {
+    if let Some(preface) = &mut document.preface {
+        visitor.visit_section(preface);
+    }
+    for headline in &mut document.headlines {
+        visitor.visit_headline(headline);
+    }
+}
+
+pub fn walk_headline_mut<V: VisitorMut + ?Sized>(visitor: &mut V, headline: &mut Headline) {
+    if let Some(title) = &mut headline.title {
+        walk_objects_mut(visitor, title.iter_mut());
+    }
+    if let Some(planning) = &mut headline.planning {
+        visitor.visit_planning(planning);
+    }
+    if let Some(content) = &mut headline.content {
+        walk_headline_content_mut(visitor, content.get_mut_value());
+    }
+}
+
+fn walk_headline_content_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    content: &mut [HeadlineContentSet],
+) {
+    for content in content {
+        match content {
+            HeadlineContentSet::Section(section) => visitor.visit_section(section),
+            HeadlineContentSet::Headline(headline) => visitor.visit_headline(headline),
+        }
+    }
+}
+
+pub fn walk_section_mut<V: VisitorMut + ?Sized>(visitor: &mut V, section: &mut Section) {
+    walk_elements_mut(visitor, section.content_mut());
+}
+
+fn walk_elements_mut<V: VisitorMut + ?Sized>(visitor: &mut V, elements: &mut [ElementSet]) {
+    for element in elements {
+        visitor.visit_element(element);
+    }
+}
+
+pub fn walk_element_mut<V: VisitorMut + ?Sized>(visitor: &mut V, element: &mut ElementSet) {
+    match element {
+        ElementSet::Paragraph(paragraph) => visitor.visit_paragraph(paragraph),
+        ElementSet::Keyword(keyword) => visitor.visit_keyword(keyword),
+        ElementSet::Planning(planning) => visitor.visit_planning(planning),
+        ElementSet::Clock(clock) => visitor.visit_clock(clock),
+        ElementSet::PlainList(list) => visitor.visit_plain_list(list),
+        ElementSet::Table(table) => visitor.visit_table(table),
+        ElementSet::FootnoteDefinition(definition) => visitor.visit_footnote_definition(definition),
+        ElementSet::SrcBlock(block) => visitor.visit_src_block(block),
+        ElementSet::Drawer(drawer) => walk_elements_mut(visitor, drawer.content_mut()),
+        ElementSet::DynamicBlock(block) => walk_elements_mut(visitor, block.content_mut()),
+        ElementSet::QuoteBlock(block) => walk_elements_mut(visitor, block.content_mut()),
+        ElementSet::VerseBlock(block) => walk_objects_mut(visitor, block.content_mut()),
+        ElementSet::Inlinetask(inlinetask) => {
+            if let Some(title) = &mut inlinetask.title {
+                walk_objects_mut(visitor, title.iter_mut());
+            }
+            walk_headline_content_mut(visitor, inlinetask.content_mut());
+        }
+        _ => {}
+    }
+}
+
+pub fn walk_paragraph_mut<V: VisitorMut + ?Sized>(visitor: &mut V, paragraph: &mut Paragraph) {
+    walk_objects_mut(visitor, paragraph.content.iter_mut());
+}
+
+pub fn walk_keyword_mut<V: VisitorMut + ?Sized>(visitor: &mut V, keyword: &mut Keyword) {
+    walk_objects_mut(visitor, keyword.value.get_mut_value());
+}
+
+pub fn walk_planning_mut<V: VisitorMut + ?Sized>(visitor: &mut V, planning: &mut Planning) {
+    let timestamps = vec![
+        &mut planning.closed,
+        &mut planning.deadline,
+        &mut planning.scheduled,
+    ];
+    for timestamp in timestamps.into_iter().filter_map(Option::as_mut) {
+        visitor.visit_timestamp(timestamp);
+    }
+}
+
+pub fn walk_clock_mut<V: VisitorMut + ?Sized>(visitor: &mut V, clock: &mut Clock) {
+    if let Some(timestamp) = &mut clock.timestamp {
+        visitor.visit_timestamp(timestamp);
+    }
+}
+
+pub fn walk_plain_list_mut<V: VisitorMut + ?Sized>(visitor: &mut V, list: &mut PlainList) {
+    for item in list.content_mut() {
+        visitor.visit_item(item);
+    }
+}
+
+pub fn walk_item_mut<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut Item) {
+    walk_elements_mut(visitor, item.content_mut());
+}
+
+pub fn walk_table_mut<V: VisitorMut + ?Sized>(visitor: &mut V, table: &mut Table) {
+    for row in table.content_mut() {
+        if let TableContent::Org(row) = row {
+            if let TableRowKind::Normal(cells) = &mut row.kind {
+                for cell in cells.get_mut_value() {
+                    walk_objects_mut(visitor, cell.content.get_mut_value());
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_footnote_definition_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    definition: &mut FootnoteDefinition,
+) {
+    walk_elements_mut(visitor, definition.content_mut());
+}
+
+fn walk_objects_mut<'a, V, T>(visitor: &mut V, objects: impl IntoIterator<Item = &'a mut T>)
+where
+    V: VisitorMut + ?Sized,
+    T: 'a,
+    &'a mut T: Into<ObjectRefMut<'a>>,
+{
+    for object in objects {
+        visitor.visit_object(object.into());
+    }
+}
+
+pub fn walk_object_mut<V: VisitorMut + ?Sized>(visitor: &mut V, object: ObjectRefMut<'_>) {
+    match object {
+        ObjectRefMut::RawString(text) => visitor.visit_raw_string(text),
+        ObjectRefMut::Link(link) => visitor.visit_link(link),
+        ObjectRefMut::TextMarkup(markup) => visitor.visit_text_markup(markup),
+        ObjectRefMut::FootnoteReference(reference) => visitor.visit_footnote_reference(reference),
+        ObjectRefMut::Timestamp(timestamp) => visitor.visit_timestamp(timestamp),
+        ObjectRefMut::RadioTarget(target) => walk_objects_mut(visitor, target.target.iter_mut()),
+        ObjectRefMut::Subscript(script) => walk_objects_mut(visitor, script.content.iter_mut()),
+        ObjectRefMut::Superscript(script) => walk_objects_mut(visitor, script.content.iter_mut()),
+        _ => {}
+    }
+}
+
+pub fn walk_link_mut<V: VisitorMut + ?Sized>(visitor: &mut V, link: &mut Link) {
+    if let LinkFormat::Bracket(_, _, Some(description)) = &mut link.link {
+        walk_objects_mut(visitor, description.iter_mut());
+    }
+}
+
+pub fn walk_text_markup_mut<V: VisitorMut + ?Sized>(visitor: &mut V, markup: &mut TextMarkup) {
+    match &mut markup.kind {
+        TextMarkupKind::Bold(content)
+        | TextMarkupKind::Italic(content)
+        | TextMarkupKind::Underline(content)
+        | TextMarkupKind::StrikeThrough(content) => walk_objects_mut(visitor, content.iter_mut()),
+        TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => {}
+    }
+}
+
+pub fn walk_footnote_reference_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    reference: &mut FootnoteReference,
+) {
+    match &mut reference.kind {
+        FootnoteReferenceKind::Inline { definition, .. }
+        | FootnoteReferenceKind::Anonymous { definition } => {
+            walk_objects_mut(visitor, definition.iter_mut())
+        }
+        FootnoteReferenceKind::Normal { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::objects::LinkPath;
+
+    /// Upper cases all text and counts the links.
+    #[derive(Default)]
+    struct Shout {
+        links: usize,
+    }
+
+    impl VisitorMut for Shout {
+        fn visit_raw_string(&mut self, text: &mut String) {
+            *text = text.to_uppercase();
+        }
+
+        fn visit_link(&mut self, link: &mut Link) {
+            self.links += 1;
+            walk_link_mut(self, link)
+        }
+    }
+
+    #[test]
+    fn visitors() {
+        let mut document: Document = crate::parsing::parse_document(
+            "#+TITLE: a *b*\n\n* c\n[[https://orgmode.org][d]]\n\n- e <2018-10-12 Fri>\n",
+        )
+        .unwrap();
+
+        let mut shout = Shout::default();
+        walk_mut(&mut document, &mut shout);
+        assert_eq!(shout.links, 1);
+        assert_eq!(
+            document.to_string(),
+            "#+TITLE: A *B*\n\n* C\n[[https://orgmode.org][D]]\n\n- E <2018-10-12 Fri>\n"
+        );
+
+        #[derive(Default)]
+        struct Collect<'a> {
+            paths: Vec<&'a LinkPath>,
+            timestamps: Vec<&'a Timestamp>,
+        }
+        impl<'a> Visitor<'a> for Collect<'a> {
+            fn visit_link(&mut self, link: &'a Link) {
+                if let LinkFormat::Bracket(path, _, _) = &link.link {
+                    self.paths.push(path);
+                }
+            }
+            fn visit_timestamp(&mut self, timestamp: &'a Timestamp) {
+                self.timestamps.push(timestamp);
+            }
+        }
+        let mut collect = Collect::default();
+        walk(&document, &mut collect);
+        assert_eq!(collect.paths[0].to_string(), "https://orgmode.org");
+        assert_eq!(collect.timestamps[0].to_string(), "<2018-10-12 Fri>");
+    }
+}
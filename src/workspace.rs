@@ -0,0 +1,497 @@
+//! Refactorings across a set of org files.
+//!
+//! A [`Workspace`] holds the parsed documents of several files (e.g. all files of
+//! `org-agenda-files` or a notes directory). Renaming a tag or todo keyword or moving a file
+//! has to update every file that uses it.
+//!
+//! The refactorings don't change the workspace. They return a [`FileEdit`] for every file that
+//! would change so the edits can be previewed (e.g. as a [`diff`]) before they are applied with
+//! [`Workspace::apply`].
+//!
+//! [`diff`]: `crate::diff`
+
+use crate::id_registry::IdRegistry;
+use crate::types::document::Document;
+use crate::types::elements::{Keyword, KeywordValueSetOfObjects};
+use crate::types::greater_elements::{Headline, HeadlineContentSet, TodoKeyword};
+use crate::types::objects::{Link, LinkFormat, LinkPath, TextMarkup, TextMarkupKind};
+use crate::types::{ElementSet, SecondaryString, StandardSet, StandardSetNoLineBreak};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Keywords defining the todo keywords of a file.
+const TODO_KEYWORDS: [&str; 3] = ["TODO", "SEQ_TODO", "TYP_TODO"];
+
+/// Keywords defining or setting tags.
+const TAG_KEYWORDS: [&str; 2] = ["TAGS", "FILETAGS"];
+
+/// The documents of a set of files.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    files: BTreeMap<PathBuf, Document>,
+}
+
+/// The new version of a file changed by a refactoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    pub document: Document,
+    /// The number of places that were changed.
+    pub changes: usize,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Workspace::default()
+    }
+
+    /// Adds a file to the workspace. Returns the previous document if the file was already added.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, document: Document) -> Option<Document> {
+        self.files.insert(path.into(), document)
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&Document> {
+        self.files.get(path.as_ref())
+    }
+
+    /// The files of the workspace ordered by their path.
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &Document)> {
+        self.files
+            .iter()
+            .map(|(path, document)| (path.as_path(), document))
+    }
+
+    /// Indexes the `ID` properties and `id:` links of all files (see [`IdRegistry`]).
+    pub fn id_registry(&self) -> IdRegistry<'_> {
+        IdRegistry::new(self)
+    }
+
+    /// Replaces the documents of the edited files with their new version.
+    pub fn apply(&mut self, edits: impl IntoIterator<Item = FileEdit>) {
+        for edit in edits {
+            self.files.insert(edit.path, edit.document);
+        }
+    }
+
+    /// Renames a tag in the tags of headlines and inline tasks and in `#+TAGS:` and
+    /// `#+FILETAGS:` keywords.
+    ///
+    /// Headlines that already have the new tag keep it only once.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Vec<FileEdit> {
+        self.edit(|_, document| {
+            let mut changes = 0;
+            preface_keywords(document, &TAG_KEYWORDS, |keyword| {
+                changes += rename_words(keyword, old, new, true);
+            });
+            for_each_headline(&mut document.headlines, &mut |headline| {
+                changes += rename_in_tags(&mut headline.tags, old, new);
+                elements_of_headline(headline, &mut |element| {
+                    if let ElementSet::Inlinetask(inlinetask) = element {
+                        changes += rename_in_tags(&mut inlinetask.tags, old, new);
+                    }
+                });
+            });
+            changes
+        })
+    }
+
+    /// Renames a todo keyword of headlines and inline tasks and in the `#+TODO:`,
+    /// `#+SEQ_TODO:` and `#+TYP_TODO:` keywords defining it (including a fast access key like
+    /// `NEXT(n)`).
+    pub fn rename_todo_keyword(&self, old: &str, new: &str) -> Vec<FileEdit> {
+        self.edit(|_, document| {
+            let mut changes = 0;
+            preface_keywords(document, &TODO_KEYWORDS, |keyword| {
+                changes += rename_words(keyword, old, new, false);
+            });
+            for_each_headline(&mut document.headlines, &mut |headline| {
+                changes += rename_todo_keyword(&mut headline.todo_keyword, old, new);
+                elements_of_headline(headline, &mut |element| {
+                    if let ElementSet::Inlinetask(inlinetask) = element {
+                        changes += rename_todo_keyword(&mut inlinetask.todo_keyword, old, new);
+                    }
+                });
+            });
+            changes
+        })
+    }
+
+    /// Changes file links to `old_path` to link to `new_path` (e.g. after moving a file).
+    ///
+    /// Relative links are resolved relative to the directory of the file containing them. They
+    /// stay relative (to the new path) while absolute links are replaced with `new_path`.
+    /// Search options and descriptions of the links are kept.
+    pub fn retarget_links(
+        &self,
+        old_path: impl AsRef<Path>,
+        new_path: impl AsRef<Path>,
+    ) -> Vec<FileEdit> {
+        let old_path = normalize(old_path.as_ref());
+        let new_path = normalize(new_path.as_ref());
+        self.edit(|path, document| {
+            let directory = path.parent().unwrap_or_else(|| Path::new(""));
+            let mut retarget = |link: &mut Link| -> bool {
+                let file = match &mut link.link {
+                    LinkFormat::Bracket(LinkPath::File(file), _, _) => file,
+                    _ => return false,
+                };
+                let target = Path::new(file.as_str());
+                if normalize(&directory.join(target)) != old_path {
+                    return false;
+                }
+                *file = if target.is_absolute() {
+                    new_path.display().to_string()
+                } else {
+                    let relative = relative_to(&new_path, &normalize(directory));
+                    match relative {
+                        Some(relative) if file.starts_with("./") && !relative.starts_with("..") => {
+                            format!("./{}", relative.display())
+                        }
+                        Some(relative) => relative.display().to_string(),
+                        None => new_path.display().to_string(),
+                    }
+                };
+                true
+            };
+            for_each_link(document, &mut retarget)
+        })
+    }
+
+    /// Applies a change to a copy of every document and returns the edits of the changed ones.
+    ///
+    /// `change` returns the number of places it changed.
+    fn edit(&self, mut change: impl FnMut(&Path, &mut Document) -> usize) -> Vec<FileEdit> {
+        self.files
+            .iter()
+            .filter_map(|(path, document)| {
+                let mut document = document.clone();
+                match change(path, &mut document) {
+                    0 => None,
+                    changes => Some(FileEdit {
+                        path: path.clone(),
+                        document,
+                        changes,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Calls `f` for the links in the preface, headline titles and sections of the document.
+/// Returns the number of links `f` changed.
+pub(crate) fn for_each_link(
+    document: &mut Document,
+    f: &mut impl FnMut(&mut Link) -> bool,
+) -> usize {
+    let mut changes = 0;
+    if let Some(preface) = &mut document.preface {
+        for element in preface.content_mut() {
+            changes += links_in_element(element, f);
+        }
+    }
+    for_each_headline(&mut document.headlines, &mut |headline| {
+        if let Some(title) = &mut headline.title {
+            changes += links(title, f);
+        }
+        elements_of_headline(headline, &mut |element| {
+            changes += links_in_element(element, f);
+        });
+    });
+    changes
+}
+
+/// Calls `f` for every headline (parents before their children).
+fn for_each_headline(headlines: &mut [Headline], f: &mut impl FnMut(&mut Headline)) {
+    for headline in headlines {
+        f(headline);
+        for child in headline.children_mut() {
+            for_each_headline(std::slice::from_mut(child), f);
+        }
+    }
+}
+
+/// Calls `f` for the elements of the section of a headline (not its children).
+fn elements_of_headline(headline: &mut Headline, f: &mut impl FnMut(&mut ElementSet)) {
+    let sections = headline
+        .content
+        .as_mut()
+        .map(|content| content.get_mut_value())
+        .into_iter()
+        .flat_map(|content| content.iter_mut())
+        .filter_map(|content| match content {
+            HeadlineContentSet::Section(section) => Some(section),
+            HeadlineContentSet::Headline(_) => None,
+        });
+    for section in sections {
+        for element in section.content_mut() {
+            f(element);
+        }
+    }
+}
+
+/// Calls `f` for the preface keywords with one of the given keys.
+fn preface_keywords(document: &mut Document, keys: &[&str], mut f: impl FnMut(&mut Keyword)) {
+    let elements = document
+        .preface
+        .as_mut()
+        .map(|preface| preface.content_mut())
+        .into_iter()
+        .flat_map(|elements| elements.iter_mut());
+    for element in elements {
+        if let ElementSet::Keyword(keyword) = element {
+            if keys
+                .iter()
+                .any(|key| key.eq_ignore_ascii_case(&keyword.key))
+            {
+                f(keyword);
+            }
+        }
+    }
+}
+
+fn rename_in_tags(tags: &mut Vec<String>, old: &str, new: &str) -> usize {
+    let changes = tags.iter().filter(|tag| *tag == old).count();
+    if tags.iter().any(|tag| tag == new) {
+        tags.retain(|tag| tag != old);
+    } else {
+        for tag in tags.iter_mut().filter(|tag| *tag == old) {
+            *tag = new.to_string();
+        }
+    }
+    changes
+}
+
+fn rename_todo_keyword(keyword: &mut Option<TodoKeyword>, old: &str, new: &str) -> usize {
+    match keyword {
+        Some(TodoKeyword::Todo(keyword)) | Some(TodoKeyword::Done(keyword)) if keyword == old => {
+            *keyword = new.to_string();
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Renames the words in the value of a keyword. A word may be followed by a fast access key in
+/// parentheses (`NEXT(n)`). If `tags` is `true` words are also separated by colons (`:a:b:`).
+fn rename_words(keyword: &mut Keyword, old: &str, new: &str, tags: bool) -> usize {
+    let separators: &[char] = if tags {
+        &[' ', '\t', ':']
+    } else {
+        &[' ', '\t']
+    };
+    let mut changes = 0;
+    for value in keyword.value.get_mut_value() {
+        if let KeywordValueSetOfObjects::RawString(text) = value {
+            let mut renamed = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+            while !rest.is_empty() {
+                let (word, after) = rest.split_at(rest.find(separators).unwrap_or(rest.len()));
+                let name_end = word.find('(').unwrap_or(word.len());
+                if &word[..name_end] == old {
+                    renamed.push_str(new);
+                    renamed.push_str(&word[name_end..]);
+                    changes += 1;
+                } else {
+                    renamed.push_str(word);
+                }
+                let separator = after.chars().next().map_or(0, char::len_utf8);
+                renamed.push_str(&after[..separator]);
+                rest = &after[separator..];
+            }
+            *text = renamed;
+        }
+    }
+    changes
+}
+
+/// Calls `f` for the links in an element and the elements it contains. Returns the number of
+/// links `f` changed.
+fn links_in_element(element: &mut ElementSet, f: &mut impl FnMut(&mut Link) -> bool) -> usize {
+    match element {
+        ElementSet::Paragraph(paragraph) => links(&mut paragraph.content, f),
+        ElementSet::Drawer(drawer) => drawer
+            .content_mut()
+            .iter_mut()
+            .map(|element| links_in_element(element, f))
+            .sum(),
+        ElementSet::PlainList(list) => list
+            .content_mut()
+            .iter_mut()
+            .flat_map(|item| item.content_mut().iter_mut())
+            .map(|element| links_in_element(element, f))
+            .sum(),
+        ElementSet::Inlinetask(inlinetask) => {
+            let mut changes = inlinetask.title.as_mut().map_or(0, |title| links(title, f));
+            for content in inlinetask.content_mut() {
+                if let HeadlineContentSet::Section(section) = content {
+                    for element in section.content_mut() {
+                        changes += links_in_element(element, f);
+                    }
+                }
+            }
+            changes
+        }
+        _ => 0,
+    }
+}
+
+/// Calls `f` for the links in the objects (including links in text markup). Returns the number
+/// of links `f` changed.
+fn links<T: ObjectMut>(
+    objects: &mut SecondaryString<T>,
+    f: &mut impl FnMut(&mut Link) -> bool,
+) -> usize {
+    let mut changes = 0;
+    for object in objects.iter_mut() {
+        changes += match object.object_mut() {
+            Some(ObjectRefMut::Link(link)) => f(link) as usize,
+            Some(ObjectRefMut::TextMarkup(markup)) => match &mut markup.kind {
+                TextMarkupKind::Bold(content)
+                | TextMarkupKind::Italic(content)
+                | TextMarkupKind::Underline(content)
+                | TextMarkupKind::StrikeThrough(content) => links(content, f),
+                TextMarkupKind::Code(_) | TextMarkupKind::Verbatim(_) => 0,
+            },
+            None => 0,
+        };
+    }
+    changes
+}
+
+/// The objects that are links or can contain links.
+enum ObjectRefMut<'a> {
+    Link(&'a mut Link),
+    TextMarkup(&'a mut TextMarkup),
+}
+
+trait ObjectMut: crate::types::AsRawString {
+    fn object_mut(&mut self) -> Option<ObjectRefMut<'_>>;
+}
+
+/// Implements [`ObjectMut`] for object sets.
+macro_rules! impl_object_mut {
+    ($($set:ident),+) => {
+        $(
+            impl ObjectMut for $set {
+                fn object_mut(&mut self) -> Option<ObjectRefMut<'_>> {
+                    match self {
+                        $set::Link(link) => Some(ObjectRefMut::Link(link)),
+                        $set::TextMarkup(markup) => Some(ObjectRefMut::TextMarkup(markup)),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_object_mut!(StandardSet, StandardSetNoLineBreak);
+
+/// Removes `.` and resolves `..` components without accessing the file system.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns `path` relative to the directory `base`. Both have to be normalized.
+///
+/// Returns `None` if only one of them is absolute.
+pub(crate) fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    if path.is_absolute() != base.is_absolute() {
+        return None;
+    }
+    let mut path = path.components().peekable();
+    let mut base = base.components().peekable();
+    while path.peek().is_some() && path.peek() == base.peek() {
+        path.next();
+        base.next();
+    }
+    let mut relative: PathBuf = base.map(|_| Component::ParentDir).collect();
+    relative.extend(path);
+    Some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes() -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.insert(
+            "/notes/a.org",
+            Document::parse(
+                "#+TODO: TODO NEXT(n) | DONE\n\
+                 #+FILETAGS: :work:\n\
+                 * NEXT :work:job:\n\
+                 see [[file:sub/b.org::*Task][b]]\n",
+            )
+            .unwrap(),
+        );
+        workspace.insert(
+            "/notes/sub/b.org",
+            Document::parse(
+                "* TODO :home:\n\
+                 see *[[file:/notes/sub/b.org]]* and [[file:./c.org]]\n",
+            )
+            .unwrap(),
+        );
+        workspace
+    }
+
+    #[test]
+    fn rename_tag() {
+        let edits = notes().rename_tag("work", "job");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].changes, 2);
+        let org = edits[0].document.to_string();
+        assert!(org.contains("#+FILETAGS: :job:\n"));
+        assert!(org.contains("* NEXT :job:\n"));
+    }
+
+    #[test]
+    fn rename_todo_keyword() {
+        let mut workspace = notes();
+        let edits = workspace.rename_todo_keyword("NEXT", "WAITING");
+        assert_eq!(
+            edits.iter().map(|edit| edit.changes).collect::<Vec<_>>(),
+            vec![2]
+        );
+        workspace.apply(edits);
+        assert!(workspace
+            .get("/notes/a.org")
+            .unwrap()
+            .to_string()
+            .starts_with("#+TODO: TODO WAITING(n) | DONE"));
+    }
+
+    #[test]
+    fn retarget_links() {
+        let edits = notes().retarget_links("/notes/sub/b.org", "/notes/b.org");
+        assert_eq!(edits.len(), 2);
+        assert!(edits[0]
+            .document
+            .to_string()
+            .contains("see [[file:b.org::*Task][b]]"));
+        assert!(edits[1]
+            .document
+            .to_string()
+            .contains("*[[file:/notes/b.org]]* and [[file:./c.org]]"));
+    }
+
+    #[test]
+    fn retarget_unlinked_file() {
+        assert!(notes().retarget_links("/other.org", "/new.org").is_empty());
+    }
+}
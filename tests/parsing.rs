@@ -14,11 +14,7 @@ fn test_files() -> impl Iterator<Item = File> {
         };
         let path = entry.path();
         if path.is_file() {
-            if let Ok(file) = File::open(path) {
-                Some(file)
-            } else {
-                None
-            }
+            File::open(path).ok()
         } else {
             None
         }